@@ -0,0 +1,67 @@
+//! Compares the cost of describing paths with `Describer::describe`, which
+//! returns a new string for each path, and `Describer::describe_into`, which
+//! reuses a buffer.
+//!
+//! Run with `cargo bench --bench describe`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use def::{DefPath, Describer, PatternKind};
+
+/// Number of times every path is described.
+const ROUNDS: u32 = 200;
+
+/// Return a describer with descriptions, patterns, and globs of a tree of
+/// projects, along with the paths of the tree.
+fn tree() -> (Describer, Vec<DefPath>) {
+    let mut d = Describer::new();
+    d.add_pattern_with_kind("/projects", "** is in projects.", PatternKind::Recursive)
+        .unwrap();
+    d.add_glob("*.log", "{stem} log.").unwrap();
+    d.add_glob("/projects/*/target", "Build output.").unwrap();
+
+    let mut paths = Vec::new();
+    for project in 0..50 {
+        let dir = format!("/projects/p{}", project);
+        d.add_description(dir.as_str(), "A project.");
+        d.add_pattern(format!("{}/src", dir).as_str(), "{stem} module.")
+            .unwrap();
+        for name in ["src", "target", "build.log", "README.md"].iter() {
+            paths.push(DefPath::new(&format!("{}/{}", dir, name)));
+        }
+        for module in 0..10 {
+            paths.push(DefPath::new(&format!("{}/src/m{}.rs", dir, module)));
+        }
+    }
+    (d, paths)
+}
+
+/// Run f ROUNDS times and return the average time it took per path.
+fn time<F: FnMut()>(paths: usize, mut f: F) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        f();
+    }
+    start.elapsed() / (ROUNDS * paths as u32)
+}
+
+fn main() {
+    let (d, paths) = tree();
+
+    let cloned = time(paths.len(), || {
+        for path in paths.iter() {
+            black_box(d.describe(path));
+        }
+    });
+    let mut description = String::new();
+    let reused = time(paths.len(), || {
+        for path in paths.iter() {
+            black_box(d.describe_into(path, &mut description));
+        }
+    });
+
+    println!("{} paths, {} rounds", paths.len(), ROUNDS);
+    println!("describe:      {:>8?} per path", cloned);
+    println!("describe_into: {:>8?} per path", reused);
+}
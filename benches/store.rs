@@ -0,0 +1,87 @@
+//! Compares the cost of loading a describer to describe one path: whole, from
+//! a JSON file and from an SQLite database, and selectively from the same
+//! database (see `Store::load_for`), for configs of growing sizes.
+//! `store::PARTIAL_LOAD_MIN_BYTES` is based on these numbers.
+//!
+//! Run with `cargo bench --bench store --features sqlite`.
+
+use std::fs;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use def::store::{self, Backend};
+use def::Describer;
+
+/// Numbers of descriptions of the compared configs.
+const SIZES: [usize; 5] = [100, 1_000, 5_000, 20_000, 50_000];
+
+/// Number of times every config is loaded.
+const ROUNDS: u32 = 10;
+
+/// Return a describer with n descriptions of a tree of projects, and a
+/// pattern for each project.
+fn config(n: usize) -> Describer {
+    let mut d = Describer::new();
+    d.add_glob("*.log", "{stem} log.").unwrap();
+    for i in 0..n {
+        let project = format!("/home/user/projects/p{}", i / 100);
+        if i % 100 == 0 {
+            d.add_description(project.as_str(), "A project.");
+            d.add_pattern(project.as_str(), "Part of a project.")
+                .unwrap();
+        } else {
+            d.add_description(
+                format!("{}/src/module{}.rs", project, i).as_str(),
+                "A module of the project, described at length.",
+            );
+        }
+    }
+    d
+}
+
+/// Run f ROUNDS times and return the average time it took.
+fn time<F: FnMut()>(mut f: F) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        f();
+    }
+    start.elapsed() / ROUNDS
+}
+
+fn main() {
+    let dir = std::env::temp_dir().join(format!("def-store-bench-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = "/home/user/projects/p0/src/module1.rs";
+
+    println!(
+        "{:>8} {:>10} {:>12} {:>12} {:>12}",
+        "entries", "db size", "json", "sqlite", "selective"
+    );
+    for n in SIZES.iter() {
+        let d = config(*n);
+        let json = store::open(dir.join(format!("{}.json", n)), None).unwrap();
+        let sqlite = store::open(dir.join(format!("{}.db", n)), Some(Backend::Sqlite)).unwrap();
+        json.save(&d).unwrap();
+        sqlite.save(&d).unwrap();
+        let size = fs::metadata(dir.join(format!("{}.db", n))).unwrap().len();
+
+        let whole_json = time(|| {
+            black_box(json.load().unwrap().describe(path));
+        });
+        let whole_sqlite = time(|| {
+            black_box(sqlite.load().unwrap().describe(path));
+        });
+        let selective = time(|| {
+            black_box(sqlite.load_for(&[path]).unwrap().describe(path));
+        });
+        println!(
+            "{:>8} {:>9}K {:>12?} {:>12?} {:>12?}",
+            n,
+            size / 1024,
+            whole_json,
+            whole_sqlite,
+            selective
+        );
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,104 @@
+//! Read-only lookups of descriptions kept by def.
+//!
+//! def-query loads def's config and describes paths, and nothing else, so
+//! that tools needing descriptions (such as shell prompts and file managers)
+//! start fast and build with few dependencies. Adding, moving, and
+//! otherwise changing descriptions is left to the def crate.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! let descriptions = def_query::Descriptions::load().unwrap();
+//! if let Some(description) = descriptions.describe("/home/user/notes") {
+//!     println!("{}", description);
+//! }
+//! ```
+
+use std::path::Path;
+
+use def::format::{self, Json};
+use def::{config, local, Describer};
+
+pub use def::{DefPath, Error};
+
+/// Descriptions is a read-only view of descriptions, ready to describe paths.
+pub struct Descriptions {
+    describer: Describer,
+}
+
+impl Descriptions {
+    /// Load descriptions used by the def command (see
+    /// `def::config::default_path`), with variables in their keys expanded.
+    /// Empty descriptions are returned if def has none yet.
+    pub fn load() -> Result<Descriptions, Error> {
+        let settings = config::load_settings()?;
+        let describer = if config::open_store(&settings)?.exists() {
+            config::load_for_lookup(&settings)?
+        } else {
+            Describer::new()
+        };
+        Ok(Descriptions { describer })
+    }
+
+    /// Like `load`, but with local files (see `def::local`) found in dir and
+    /// its ancestors merged over the descriptions, as def does when
+    /// describing paths under dir.
+    pub fn load_for(dir: &str) -> Result<Descriptions, Error> {
+        let mut descriptions = Descriptions::load()?;
+        local::merge_into(&mut descriptions.describer, dir)?;
+        Ok(descriptions)
+    }
+
+    /// Load descriptions from a file, decoded based on its extension (see
+    /// `def::format::from_path`), or as JSON if it has none.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Descriptions, Error> {
+        let format = format::from_path(path.as_ref()).unwrap_or(&Json);
+        let describer = Describer::load(path, format)?;
+        Ok(Descriptions { describer })
+    }
+
+    /// Return the description of path, or None if it has none.
+    pub fn describe<P: Into<DefPath>>(&self, path: P) -> Option<String> {
+        self.describer.describe(path)
+    }
+
+    /// Return descriptions of paths, in their order, with None for paths
+    /// that have none.
+    pub fn describe_many<I>(&self, paths: I) -> Vec<Option<String>>
+    where
+        I: IntoIterator,
+        I::Item: Into<DefPath>,
+    {
+        self.describer.describe_many(paths)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+
+    #[test]
+    fn from_file_test() {
+        let file = env::temp_dir().join(format!("def-query-test-{}.json", std::process::id()));
+        fs::write(
+            &file,
+            r#"{"descriptions": {"/etc": "Configuration."}, "patterns": {"/srv": "* is served."}}"#,
+        )
+        .unwrap();
+        let descriptions = Descriptions::from_file(&file);
+        fs::remove_file(&file).unwrap();
+
+        assert_eq!(
+            descriptions
+                .unwrap()
+                .describe_many(vec!["/etc", "/srv/www", "/tmp"]),
+            vec![
+                Some("Configuration.".to_string()),
+                Some("www is served.".to_string()),
+                None
+            ]
+        );
+    }
+}
@@ -0,0 +1,308 @@
+//! Annotation files: descriptions of many paths, kept in one file to be
+//! added in bulk (see `def apply`).
+//!
+//! Each line of an annotation file is a path and its description, separated
+//! by a tab. Blank lines, and lines starting with "#", are ignored. Tabs,
+//! newlines, and backslashes in descriptions are written as "\t", "\n", and
+//! "\\". For example, with tabs shown as "<TAB>":
+//!
+//! ```text
+//! # Layout of the project.
+//! .<TAB>A project.
+//! src<TAB>Source code.
+//! src/main.rs<TAB>Entry point.\nParses arguments.
+//! ```
+//!
+//! Files with the extension of a format of `def::format`, such as
+//! `layout.yaml`, hold a map of paths to descriptions instead. Relative paths
+//! are resolved against the directory of the file (see `read`), "." being
+//! the directory itself. Build tools can generate annotation files using
+//! `to_text`.
+
+use std::fs;
+use std::path::Path;
+
+use serde_json::Value;
+
+use crate::{format, paths, DefPath, Describer, Error};
+
+/// Marks lines of comments.
+const COMMENT: char = '#';
+
+/// Separates paths from descriptions.
+const SEPERATOR: char = '\t';
+
+/// Annotation is a path and its description.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Annotation {
+    /// The path, as written in the file.
+    pub path: String,
+    /// The description of the path.
+    pub description: String,
+}
+
+impl Annotation {
+    /// Create an annotation of path.
+    pub fn new(path: &str, description: &str) -> Annotation {
+        Annotation {
+            path: path.to_string(),
+            description: description.to_string(),
+        }
+    }
+}
+
+/// Parse annotations from text, in the tab separated layout. An error is
+/// returned for lines without a path or a description.
+///
+/// # Examples
+///
+/// ```
+/// use def::annotations::{parse, Annotation};
+///
+/// let annotations = parse("# Comment.\nsrc\tSource code.\n").unwrap();
+/// assert_eq!(annotations, vec![Annotation::new("src", "Source code.")]);
+/// assert!(parse("src").is_err());
+/// ```
+pub fn parse(text: &str) -> Result<Vec<Annotation>, Error> {
+    let mut annotations = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        if line.trim().is_empty() || line.trim_start().starts_with(COMMENT) {
+            continue;
+        }
+        let invalid =
+            |reason: &str| Error::InvalidAnnotations(format!("line {}: {}", i + 1, reason));
+        let (path, description) = match line.find(SEPERATOR) {
+            Some(at) => (line[..at].trim(), line[at + 1..].trim()),
+            None => {
+                return Err(invalid(
+                    "expected a path and a description separated by a tab",
+                ))
+            }
+        };
+        if path.is_empty() {
+            return Err(invalid("missing path"));
+        }
+        if description.is_empty() {
+            return Err(invalid("missing description"));
+        }
+        annotations.push(Annotation::new(path, &unescape(description)));
+    }
+    Ok(annotations)
+}
+
+/// Return annotations of a value mapping paths to descriptions, as decoded
+/// from a file of one of `def::format`'s formats.
+pub fn from_value(value: Value) -> Result<Vec<Annotation>, Error> {
+    let map = match value {
+        Value::Object(map) => map,
+        _ => {
+            return Err(Error::InvalidAnnotations(
+                "expected a map of paths to descriptions".to_string(),
+            ))
+        }
+    };
+    map.into_iter()
+        .map(|(path, description)| match description {
+            Value::String(description) if !description.trim().is_empty() => {
+                Ok(Annotation::new(&path, description.trim()))
+            }
+            _ => Err(Error::InvalidAnnotations(format!(
+                "{}: expected a description",
+                path
+            ))),
+        })
+        .collect()
+}
+
+/// Return annotations as text, in the tab separated layout, which `parse`
+/// reads back.
+///
+/// # Examples
+///
+/// ```
+/// use def::annotations::{parse, to_text, Annotation};
+///
+/// let annotations = vec![Annotation::new("src", "Source code.\nAll of it.")];
+/// let text = to_text(&annotations);
+/// assert_eq!(text, "src\tSource code.\\nAll of it.\n");
+/// assert_eq!(parse(&text).unwrap(), annotations);
+/// ```
+pub fn to_text(annotations: &[Annotation]) -> String {
+    let mut text = String::new();
+    for a in annotations.iter() {
+        text.push_str(&a.path);
+        text.push(SEPERATOR);
+        text.push_str(&escape(&a.description));
+        text.push('\n');
+    }
+    text
+}
+
+/// Read the annotation file at path, in the format matching its extension,
+/// or in the tab separated layout if there's none. Relative paths of
+/// annotations are resolved against the file's directory, so paths of the
+/// returned annotations are keys (see `DefPath`). An error is returned if
+/// the file can't be read or parsed.
+pub fn read<P: AsRef<Path>>(path: P) -> Result<Vec<Annotation>, Error> {
+    let path = path.as_ref();
+    let bytes = fs::read(path)?;
+    let annotations = match format::from_path(path) {
+        Some(format) => from_value(format.decode(&bytes).map_err(Error::InvalidAnnotations)?)?,
+        None => parse(&String::from_utf8_lossy(&bytes))?,
+    };
+
+    let file = paths::absolute(path)?;
+    let base = Path::new(&file)
+        .parent()
+        .unwrap_or_else(|| Path::new(&file));
+    annotations
+        .into_iter()
+        .map(|a| {
+            let resolved = paths::resolve_lexically(Path::new(&a.path), base);
+            match resolved.to_str() {
+                Some(key) => Ok(Annotation::new(DefPath::new(key).as_str(), &a.description)),
+                None => Err(Error::InvalidPath(a.path)),
+            }
+        })
+        .collect()
+}
+
+/// Return a describer with the descriptions of annotations, whose paths
+/// should be keys (see `read`). Later annotations of the same path win. The
+/// describer can be merged into another to apply the annotations, see
+/// `Describer::merge_with`.
+pub fn to_describer(annotations: &[Annotation]) -> Describer {
+    let mut describer = Describer::new();
+    for a in annotations.iter() {
+        describer.add_description(&a.path, &a.description);
+    }
+    describer
+}
+
+/// Return description with tabs, newlines, and backslashes escaped.
+fn escape(description: &str) -> String {
+    let mut escaped = String::with_capacity(description.len());
+    for c in description.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Return description with escapes replaced by what they stand for. Unknown
+/// escapes are kept as written.
+fn unescape(description: &str) -> String {
+    let mut unescaped = String::with_capacity(description.len());
+    let mut chars = description.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('\\') => unescaped.push('\\'),
+            Some('t') => unescaped.push('\t'),
+            Some('n') => unescaped.push('\n'),
+            Some(other) => {
+                unescaped.push('\\');
+                unescaped.push(other);
+            }
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_test() {
+        for (text, parsed) in [
+            ("", Ok(vec![])),
+            (
+                "# Layout.\n\n.\tA project.\nsrc \t Source code. \n",
+                Ok(vec![
+                    Annotation::new(".", "A project."),
+                    Annotation::new("src", "Source code."),
+                ]),
+            ),
+            (
+                "a b\tFirst.\\nSecond.\\tTabbed \\\\ \\x\n",
+                Ok(vec![Annotation::new(
+                    "a b",
+                    "First.\nSecond.\tTabbed \\ \\x",
+                )]),
+            ),
+            (
+                "src\tOne.\tTwo.",
+                Ok(vec![Annotation::new("src", "One.\tTwo.")]),
+            ),
+            (".\tA project.\nsrc Source code.", Err(2)),
+            ("\tNo path.", Err(1)),
+            ("src\t ", Err(1)),
+        ]
+        .iter()
+        {
+            match (parse(text), parsed) {
+                (Ok(annotations), Ok(parsed)) => assert_eq!(&annotations, parsed, "{}", text),
+                (Err(Error::InvalidAnnotations(e)), Err(line)) => {
+                    assert!(e.starts_with(&format!("line {}:", line)), "{}", e)
+                }
+                (result, _) => panic!("unexpected {:?} for {:?}", result, text),
+            }
+        }
+
+        let annotations = vec![
+            Annotation::new("/a", "Back\\slash."),
+            Annotation::new("/b", "Two\nlines."),
+        ];
+        assert_eq!(parse(&to_text(&annotations)).unwrap(), annotations);
+    }
+
+    #[test]
+    fn read_test() {
+        let dir = std::env::temp_dir().join(format!("def-annotations-test-{}", std::process::id()));
+        fs::create_dir_all(dir.join("project")).unwrap();
+        let tabbed = dir.join("project").join("layout.def");
+        fs::write(
+            &tabbed,
+            ".\tA project.\nsrc\tSource code.\n../notes\tNotes.\n/abs\tAbsolute.\n",
+        )
+        .unwrap();
+        let json = dir.join("layout.json");
+        fs::write(&json, r#"{"project": "A project.", "other": 1}"#).unwrap();
+
+        let read_tabbed = read(&tabbed);
+        let read_json = read(&json);
+        let base = paths::absolute(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            read_tabbed.unwrap(),
+            vec![
+                Annotation::new(&format!("{}/project", base), "A project."),
+                Annotation::new(&format!("{}/project/src", base), "Source code."),
+                Annotation::new(&format!("{}/notes", base), "Notes."),
+                Annotation::new("/abs", "Absolute."),
+            ]
+        );
+        assert!(matches!(read_json, Err(Error::InvalidAnnotations(_))));
+        assert_eq!(
+            from_value(serde_json::json!({"src": " Source code. "})).unwrap(),
+            vec![Annotation::new("src", "Source code.")]
+        );
+        assert!(from_value(serde_json::json!(["src"])).is_err());
+
+        let d = to_describer(&[
+            Annotation::new("/a", "First."),
+            Annotation::new("/a", "Second."),
+        ]);
+        assert_eq!(d.description("/a"), Some("Second."));
+    }
+}
@@ -0,0 +1,57 @@
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use flate2::read::GzDecoder;
+
+/// members returns sorted paths of all members of the archive at path.
+/// Supported formats are zip, tar, and gzipped tar, and are detected using
+/// the file's extension.
+pub fn members(path: &str) -> io::Result<Vec<String>> {
+    let file = File::open(path)?;
+    let name = Path::new(path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    let mut members = if name.ends_with(".zip") {
+        zip_members(file)?
+    } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        tar_members(GzDecoder::new(file))?
+    } else if name.ends_with(".tar") {
+        tar_members(file)?
+    } else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "unsupported archive format (expected .zip, .tar, .tar.gz, or .tgz)",
+        ));
+    };
+
+    members.sort();
+    members.dedup();
+    Ok(members)
+}
+
+/// zip_members returns paths of all members of a zip archive.
+fn zip_members(file: File) -> io::Result<Vec<String>> {
+    let archive = zip::ZipArchive::new(file)?;
+    Ok(archive.file_names().map(normalize).collect())
+}
+
+/// tar_members returns paths of all members of a tar archive.
+fn tar_members<R: io::Read>(reader: R) -> io::Result<Vec<String>> {
+    let mut archive = tar::Archive::new(reader);
+    let mut members = Vec::new();
+    for entry in archive.entries()? {
+        members.push(normalize(&entry?.path()?.to_string_lossy()));
+    }
+    Ok(members)
+}
+
+/// normalize strips leading "./" and trailing separators from a member's path.
+fn normalize(member: &str) -> String {
+    member
+        .trim_start_matches("./")
+        .trim_end_matches('/')
+        .to_string()
+}
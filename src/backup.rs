@@ -0,0 +1,106 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use def::trash;
+
+/// Prefix of backup file names, which are followed by a timestamp and the
+/// extension of the config file.
+const BACKUP_PREFIX: &str = "config-";
+
+/// Rotation is the outcome of rotating backups of a config file.
+#[derive(Debug, Default, PartialEq)]
+pub struct Rotation {
+    /// Path of the created backup, or None if the latest backup was already
+    /// identical to the config.
+    pub created: Option<String>,
+    /// Paths of old backups that were deleted.
+    pub deleted: Vec<String>,
+}
+
+/// rotate copies config to a timestamped file in dir, unless the latest backup
+/// in dir is identical to config, then deletes the oldest backups so at most
+/// keep backups remain. Running it repeatedly without changing config doesn't
+/// create new backups.
+pub fn rotate(config: &str, dir: &str, keep: usize) -> io::Result<Rotation> {
+    fs::create_dir_all(dir)?;
+    let content = fs::read(config)?;
+    let mut backups = list(dir)?;
+    let mut rotation = Rotation::default();
+
+    let latest = backups.last().map(fs::read).transpose()?;
+    if latest.as_ref() != Some(&content) {
+        let extension = Path::new(config)
+            .extension()
+            .map(|e| format!(".{}", e.to_string_lossy()))
+            .unwrap_or_default();
+        let path = Path::new(dir).join(format!("{}{}{}", BACKUP_PREFIX, trash::now(), extension));
+        fs::write(&path, &content)?;
+        rotation.created = Some(path.to_string_lossy().to_string());
+        backups.push(path);
+    }
+
+    let excess = backups.len().saturating_sub(keep);
+    for path in backups.drain(..excess) {
+        fs::remove_file(&path)?;
+        rotation.deleted.push(path.to_string_lossy().to_string());
+    }
+
+    Ok(rotation)
+}
+
+/// list returns backups in dir sorted from oldest to newest.
+fn list(dir: &str) -> io::Result<Vec<PathBuf>> {
+    let mut backups = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if timestamp(&path).is_some() {
+            backups.push(path);
+        }
+    }
+    backups.sort_by_key(|p| timestamp(p));
+    Ok(backups)
+}
+
+/// timestamp extracts the timestamp from a backup's file name, or returns None
+/// if path isn't a backup.
+fn timestamp(path: &Path) -> Option<u64> {
+    path.file_stem()
+        .and_then(|n| n.to_str())
+        .and_then(|n| n.strip_prefix(BACKUP_PREFIX))
+        .and_then(|n| n.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotate_test() {
+        let dir = std::env::temp_dir().join(format!("def-backup-test-{}", std::process::id()));
+        let config = dir.join("config.json");
+        let backups = dir.join("backups");
+        let (config_str, backups_str) = (
+            config.to_string_lossy().to_string(),
+            backups.to_string_lossy().to_string(),
+        );
+        fs::create_dir_all(&backups).unwrap();
+        for (name, content) in [("config-1.json", "1"), ("config-2.json", "2")].iter() {
+            fs::write(backups.join(name), content).unwrap();
+        }
+
+        fs::write(&config, "3").unwrap();
+        let first = rotate(&config_str, &backups_str, 2).unwrap();
+        let second = rotate(&config_str, &backups_str, 2).unwrap();
+        let remaining = list(&backups_str).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(first.created.is_some());
+        assert_eq!(
+            first.deleted,
+            vec![backups.join("config-1.json").to_string_lossy().to_string()]
+        );
+        assert_eq!(second, Rotation::default());
+        assert_eq!(remaining.len(), 2);
+    }
+}
@@ -0,0 +1,635 @@
+use std::collections::HashSet;
+use std::io::{self, IsTerminal};
+use std::process;
+
+use def::meta::PathMetadata;
+use def::virtual_tree::{Node, VirtualTree};
+use def::{Describer, Entry, PatternKind};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::errors::Handle;
+use crate::i18n::{self, tr, trf};
+use crate::theme::error_label;
+
+/// Name shown for patterns, as in `def list`.
+const PATTERN_NAME: &str = "*";
+
+/// Name shown for recursive patterns, as in `def list`.
+const RECURSIVE_PATTERN_NAME: &str = "**";
+
+/// Number of spaces each level of the tree is indented by.
+const INDENT: usize = 2;
+
+/// Row is a line of the tree: a description, pattern, or glob, or a directory
+/// without entries that has described paths under it.
+#[derive(Debug, PartialEq)]
+struct Row {
+    /// Path of the row's node.
+    path: String,
+    /// Kind of the pattern shown, or None for descriptions and directories.
+    pattern: Option<PatternKind>,
+    /// The description or pattern, or None for directories without entries.
+    text: Option<String>,
+    /// Name shown for the row.
+    label: String,
+    /// Level of the row in the tree.
+    depth: usize,
+    /// True if the row can be folded to hide the rows under it.
+    foldable: bool,
+}
+
+/// Mode is what keys typed in the browser do.
+#[derive(Debug, PartialEq)]
+enum Mode {
+    /// Keys move around the tree, or start another mode.
+    Browsing,
+    /// Keys edit the selected entry.
+    Editing(Input),
+    /// Keys edit the search query.
+    Searching(Input),
+    /// The user is asked to confirm moving the selected entry to the trash.
+    Deleting,
+}
+
+/// Input is a line of text being typed, with a cursor.
+#[derive(Debug, PartialEq, Default)]
+struct Input {
+    text: String,
+    /// Position of the cursor, in characters.
+    cursor: usize,
+}
+
+impl Input {
+    fn new(text: &str) -> Input {
+        Input {
+            text: text.to_string(),
+            cursor: text.chars().count(),
+        }
+    }
+
+    /// Return the byte offset of the cursor.
+    fn offset(&self) -> usize {
+        self.text
+            .char_indices()
+            .nth(self.cursor)
+            .map(|(i, _)| i)
+            .unwrap_or(self.text.len())
+    }
+
+    /// Apply key to the text, and return true if it changed.
+    fn handle(&mut self, key: KeyCode) -> bool {
+        let len = self.text.chars().count();
+        match key {
+            KeyCode::Char(c) => {
+                let offset = self.offset();
+                self.text.insert(offset, c);
+                self.cursor += 1;
+                return true;
+            }
+            KeyCode::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                let offset = self.offset();
+                self.text.remove(offset);
+                return true;
+            }
+            KeyCode::Delete if self.cursor < len => {
+                let offset = self.offset();
+                self.text.remove(offset);
+                return true;
+            }
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(len),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = len,
+            _ => {}
+        }
+        false
+    }
+}
+
+/// Browser is the state of `def browse`.
+struct Browser {
+    describer: Describer,
+    rows: Vec<Row>,
+    /// Paths of folded nodes.
+    folded: HashSet<String>,
+    /// Query rows are filtered by, empty if all entries are shown.
+    query: String,
+    selected: ListState,
+    mode: Mode,
+    /// Message shown in the status line, such as the result of an edit.
+    status: String,
+    quit: bool,
+}
+
+/// run opens the interactive browser of the config, which lists described
+/// paths as a tree, and shows the selected entry in a preview pane. Entries
+/// can be edited, moved to the trash, and searched. The config is locked
+/// until the browser is closed, and changes are saved as they're made.
+pub fn run() {
+    if !io::stdout().is_terminal() {
+        eprintln!("{}: {}", error_label(), tr("browse-needs-terminal"));
+        process::exit(1);
+    }
+
+    let _lock = crate::lock_config();
+    let describer = if crate::open_store().exists() {
+        crate::get_describer()
+    } else {
+        Describer::new()
+    };
+    let mut browser = Browser::new(describer);
+
+    let mut terminal = ratatui::init();
+    let result = browser.run(&mut terminal);
+    ratatui::restore();
+    result.extract_or_exit(tr("failed-run-browser"));
+}
+
+impl Browser {
+    fn new(describer: Describer) -> Browser {
+        let mut browser = Browser {
+            describer,
+            rows: Vec::new(),
+            folded: HashSet::new(),
+            query: String::new(),
+            selected: ListState::default(),
+            mode: Mode::Browsing,
+            status: String::new(),
+            quit: false,
+        };
+        browser.rebuild();
+        browser
+    }
+
+    /// Draw the browser and handle keys until the user quits.
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        while !self.quit {
+            terminal.draw(|frame| self.draw(frame))?;
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press {
+                    self.handle(key);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Recreate rows from the describer, keeping the selection on the same
+    /// entry if it's still shown.
+    fn rebuild(&mut self) {
+        let current = self.row().map(|r| (r.path.clone(), r.pattern));
+        let entries: Vec<Entry> = if self.query.is_empty() {
+            self.describer.entries_under("")
+        } else {
+            self.describer
+                .search(&self.query)
+                .iter()
+                .map(|m| Entry {
+                    path: m.path,
+                    description: m.text,
+                    pattern: m.pattern,
+                })
+                .collect()
+        };
+        let tree = VirtualTree::new(&entries);
+
+        let mut rows = Vec::new();
+        push_rows(&mut rows, &self.folded, tree.roots(), 0);
+        self.rows = rows;
+
+        let index = current
+            .and_then(|(path, pattern)| {
+                self.rows
+                    .iter()
+                    .position(|r| r.path == path && r.pattern == pattern)
+            })
+            .or_else(|| self.selected.selected())
+            .map(|i| i.min(self.rows.len().saturating_sub(1)));
+        self.selected.select(if self.rows.is_empty() {
+            None
+        } else {
+            index.or(Some(0))
+        });
+    }
+
+    /// Return the selected row.
+    fn row(&self) -> Option<&Row> {
+        self.rows.get(self.selected.selected()?)
+    }
+
+    fn handle(&mut self, key: KeyEvent) {
+        match std::mem::replace(&mut self.mode, Mode::Browsing) {
+            Mode::Browsing => self.browse(key.code),
+            Mode::Editing(input) => self.edit(input, key.code),
+            Mode::Searching(input) => self.search(input, key.code),
+            Mode::Deleting => {
+                if let KeyCode::Char(c) = key.code {
+                    if i18n::is_yes(c.encode_utf8(&mut [0; 4])) {
+                        self.delete();
+                    }
+                }
+            }
+        }
+    }
+
+    /// Handle a key typed while browsing.
+    fn browse(&mut self, key: KeyCode) {
+        self.status.clear();
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Down | KeyCode::Char('j') => self.selected.select_next(),
+            KeyCode::Up | KeyCode::Char('k') => self.selected.select_previous(),
+            KeyCode::Home | KeyCode::Char('g') => self.selected.select_first(),
+            KeyCode::End | KeyCode::Char('G') => self.selected.select_last(),
+            KeyCode::Left | KeyCode::Char('h') => self.fold(true),
+            KeyCode::Right | KeyCode::Char('l') => self.fold(false),
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                if let Some(row) = self.row().filter(|r| r.foldable) {
+                    let folded = self.folded.contains(&row.path);
+                    self.fold(!folded);
+                }
+            }
+            KeyCode::Char('e') => {
+                if let Some(row) = self.row() {
+                    let text = row.text.as_deref().unwrap_or_default();
+                    self.mode = Mode::Editing(Input::new(text));
+                }
+            }
+            KeyCode::Char('d') if self.row().is_some_and(|r| r.text.is_some()) => {
+                self.mode = Mode::Deleting
+            }
+            KeyCode::Char('/') => self.mode = Mode::Searching(Input::new(&self.query)),
+            _ => {}
+        }
+    }
+
+    /// Fold (or unfold) the node of the selected row. Folding a row that
+    /// can't be folded, or is already folded, selects its parent instead.
+    fn fold(&mut self, fold: bool) {
+        let (path, foldable, depth) = match self.row() {
+            Some(row) => (row.path.clone(), row.foldable, row.depth),
+            None => return,
+        };
+        if !foldable || (fold && self.folded.contains(&path)) {
+            if fold {
+                let index = self.selected.selected().unwrap_or(0);
+                let parent = self.rows[..index]
+                    .iter()
+                    .rposition(|r| r.depth < depth && r.foldable);
+                if let Some(parent) = parent {
+                    self.selected.select(Some(parent));
+                }
+            }
+            return;
+        }
+
+        if fold {
+            self.folded.insert(path);
+        } else {
+            self.folded.remove(&path);
+        }
+        self.rebuild();
+    }
+
+    /// Handle a key typed while editing the selected entry. The entry is
+    /// saved on enter, and editing is cancelled on escape.
+    fn edit(&mut self, mut input: Input, key: KeyCode) {
+        match key {
+            KeyCode::Esc => self.status.clear(),
+            KeyCode::Enter => {
+                if let Err(message) = self.save(input.text.trim()) {
+                    self.status = message;
+                    self.mode = Mode::Editing(input);
+                }
+            }
+            key => {
+                input.handle(key);
+                self.mode = Mode::Editing(input);
+            }
+        }
+    }
+
+    /// Map text to the selected row's path, as a description or a pattern of
+    /// the row's kind, and save the config.
+    fn save(&mut self, text: &str) -> Result<(), String> {
+        let (path, pattern) = match self.row() {
+            Some(row) => (row.path.clone(), row.pattern),
+            None => return Ok(()),
+        };
+        if text.is_empty() {
+            return Err(tr("empty-description").to_string());
+        }
+
+        match pattern {
+            Some(kind) => self
+                .describer
+                .add_pattern_with_kind(&path, text, kind)
+                .map_err(|e| format!("{}: {}", tr("invalid-pattern"), e))?,
+            None => {
+                self.describer.add_description(&path, text);
+                self.describer.stamp(&path, def::trash::now());
+                if let Ok(metadata) = PathMetadata::read(&path) {
+                    self.describer.record_metadata(&path, metadata);
+                }
+            }
+        }
+        crate::write_describer(&mut self.describer);
+        self.status = trf("browse-saved", &[&path]);
+        self.rebuild();
+        Ok(())
+    }
+
+    /// Move the selected entry to the trash, and save the config.
+    fn delete(&mut self) {
+        let (path, pattern) = match self.row() {
+            Some(row) => (row.path.clone(), row.pattern),
+            None => return,
+        };
+        let removed = match pattern {
+            Some(_) => self.describer.trash_pattern(&path),
+            None => self.describer.trash_description(&path),
+        };
+        if removed {
+            crate::write_describer(&mut self.describer);
+            self.status = trf("moved-to-trash", &[&path]);
+            self.rebuild();
+        }
+    }
+
+    /// Handle a key typed while searching. Rows are filtered as the query is
+    /// typed; enter keeps the filter, and escape clears it.
+    fn search(&mut self, mut input: Input, key: KeyCode) {
+        match key {
+            KeyCode::Esc => {
+                self.query.clear();
+                self.rebuild();
+            }
+            KeyCode::Enter => {}
+            key => {
+                if input.handle(key) {
+                    self.query = input.text.trim().to_string();
+                    self.rebuild();
+                }
+                self.mode = Mode::Searching(input);
+            }
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, status] = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(1), Constraint::Length(1)])
+            .areas(frame.area());
+        let [tree, preview] = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
+            .areas(main);
+
+        let title = if self.query.is_empty() {
+            tr("browse-title").to_string()
+        } else {
+            trf("browse-title-search", &[&self.query])
+        };
+        let items: Vec<ListItem> = self
+            .rows
+            .iter()
+            .map(|r| ListItem::new(row_line(r, self.folded.contains(&r.path))))
+            .collect();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, tree, &mut self.selected);
+
+        let preview_text = match self.row() {
+            Some(row) => self.preview(row),
+            None => vec![Line::from(tr("browse-empty"))],
+        };
+        frame.render_widget(
+            Paragraph::new(preview_text)
+                .wrap(Wrap { trim: false })
+                .block(Block::default().borders(Borders::ALL)),
+            preview,
+        );
+
+        let (prompt, input) = match &self.mode {
+            Mode::Editing(input) => (tr("browse-edit"), Some(input)),
+            Mode::Searching(input) => (tr("browse-search"), Some(input)),
+            Mode::Deleting => (tr("browse-delete"), None),
+            Mode::Browsing if self.status.is_empty() => (tr("browse-keys"), None),
+            Mode::Browsing => (self.status.as_str(), None),
+        };
+        match input {
+            Some(input) => {
+                let line = format!("{} {}", prompt, input.text);
+                frame.render_widget(Paragraph::new(line), status);
+                let column = prompt.chars().count() + 1 + input.cursor;
+                frame.set_cursor_position((status.x + column as u16, status.y));
+            }
+            None => frame.render_widget(Paragraph::new(prompt), status),
+        }
+    }
+
+    /// Return lines shown in the preview pane for row: its path and kind, its
+    /// description or pattern, and the tags and times of descriptions.
+    fn preview(&self, row: &Row) -> Vec<Line<'static>> {
+        let mut lines = vec![
+            Line::from(Span::styled(
+                format!("{}{}", row.path, crate::kind_label(row.pattern)),
+                Style::default().add_modifier(Modifier::BOLD),
+            )),
+            Line::from(""),
+        ];
+        match &row.text {
+            Some(text) => lines.extend(text.lines().map(|l| Line::from(l.to_string()))),
+            None => lines.push(Line::from(tr("no-description"))),
+        }
+        if row.pattern.is_some() || row.text.is_none() {
+            return lines;
+        }
+
+        let tags = self.describer.tags_of(&row.path);
+        if !tags.is_empty() {
+            lines.push(Line::from(""));
+            lines.push(Line::from(trf("browse-tags", &[&tags.join(", ")])));
+        }
+        if let Some(times) = self.describer.timestamps(&row.path) {
+            let now = def::trash::now();
+            lines.push(Line::from(trf(
+                "browse-times",
+                &[
+                    &crate::elapsed(now.saturating_sub(times.created)),
+                    &crate::elapsed(now.saturating_sub(times.updated)),
+                ],
+            )));
+        }
+        lines
+    }
+}
+
+/// push_rows adds rows of nodes and of their children to rows, following the
+/// layout of `def list`: chains of directories without entries are shown on
+/// one row, and patterns are shown as "*" (or "**") children of their
+/// directories. Children of folded nodes are skipped.
+fn push_rows<'a, 'b: 'a>(
+    rows: &mut Vec<Row>,
+    folded: &HashSet<String>,
+    nodes: impl Iterator<Item = &'a Node<'b>>,
+    depth: usize,
+) {
+    for node in nodes {
+        let (name, node) = node.collapse();
+        let path = node.path().to_string();
+        let patterns: Vec<(PatternKind, &str)> = [PatternKind::Children, PatternKind::Recursive]
+            .iter()
+            .filter_map(|kind| node.pattern(*kind).map(|p| (*kind, p)))
+            .collect();
+        let foldable = node.child_count() > 0 || !patterns.is_empty();
+
+        let mut own: Vec<(Option<PatternKind>, Option<&str>)> = Vec::new();
+        if let Some(description) = node.description() {
+            own.push((None, Some(description)));
+        }
+        if let Some(glob) = node.pattern(PatternKind::Glob) {
+            own.push((Some(PatternKind::Glob), Some(glob)));
+        }
+        if own.is_empty() {
+            own.push((None, None));
+        }
+        for (i, (pattern, text)) in own.into_iter().enumerate() {
+            rows.push(Row {
+                path: path.clone(),
+                pattern,
+                text: text.map(|t| t.to_string()),
+                label: name.clone(),
+                depth,
+                foldable: foldable && i == 0,
+            });
+        }
+        if folded.contains(&path) {
+            continue;
+        }
+
+        for (kind, pattern) in patterns {
+            let label = match kind {
+                PatternKind::Recursive => RECURSIVE_PATTERN_NAME,
+                _ => PATTERN_NAME,
+            };
+            rows.push(Row {
+                path: path.clone(),
+                pattern: Some(kind),
+                text: Some(pattern.to_string()),
+                label: label.to_string(),
+                depth: depth + 1,
+                foldable: false,
+            });
+        }
+        push_rows(rows, folded, node.children(), depth + 1);
+    }
+}
+
+/// row_line returns the line shown for row in the tree: its label, indented
+/// and marked if it can be folded, followed by the first line of its
+/// description or pattern.
+fn row_line(row: &Row, folded: bool) -> Line<'static> {
+    let marker = match (row.foldable, folded) {
+        (true, true) => "▸ ",
+        (true, false) => "▾ ",
+        _ => "  ",
+    };
+    let color = match row.pattern {
+        Some(PatternKind::Glob) => Color::Cyan,
+        Some(_) => Color::Yellow,
+        None if row.text.is_some() => Color::Green,
+        None => Color::Reset,
+    };
+    let mut spans = vec![
+        Span::raw(format!("{}{}", " ".repeat(row.depth * INDENT), marker)),
+        Span::styled(row.label.clone(), Style::default().fg(color)),
+    ];
+    if let Some(text) = &row.text {
+        let first = text.lines().next().unwrap_or_default();
+        spans.push(Span::styled(
+            format!(": {}", first),
+            Style::default().add_modifier(Modifier::DIM),
+        ));
+    }
+    Line::from(spans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_test() {
+        let mut d = Describer::new();
+        d.add_description("/home/user/src", "Code.");
+        d.add_description("/home/user/src/def", "This tool.");
+        d.add_pattern("/home/user/src", "{name} is a project.")
+            .unwrap();
+        d.add_description("/etc", "Configuration.");
+        let mut browser = Browser::new(d);
+
+        let rows = |b: &Browser| -> Vec<(String, usize, bool)> {
+            b.rows
+                .iter()
+                .map(|r| (r.label.clone(), r.depth, r.foldable))
+                .collect()
+        };
+        let all = vec![
+            ("/".to_string(), 0, true),
+            ("etc".to_string(), 1, false),
+            ("home/user/src".to_string(), 1, true),
+            ("*".to_string(), 2, false),
+            ("def".to_string(), 2, false),
+        ];
+        assert_eq!(rows(&browser), all);
+
+        browser.selected.select(Some(2));
+        browser.fold(true);
+        assert_eq!(rows(&browser), all[..3].to_vec());
+        assert_eq!(
+            browser.row().map(|r| r.path.as_str()),
+            Some("/home/user/src")
+        );
+        browser.fold(false);
+        assert_eq!(rows(&browser), all);
+
+        browser.query = "tool".to_string();
+        browser.rebuild();
+        assert_eq!(
+            rows(&browser),
+            vec![("/home/user/src/def".to_string(), 0, false)]
+        );
+    }
+
+    #[test]
+    fn input_test() {
+        let mut input = Input::new("añb");
+        for (key, text, cursor) in [
+            (KeyCode::Left, "añb", 2),
+            (KeyCode::Backspace, "ab", 1),
+            (KeyCode::Char('ñ'), "añb", 2),
+            (KeyCode::Home, "añb", 0),
+            (KeyCode::Delete, "ñb", 0),
+            (KeyCode::End, "ñb", 2),
+            (KeyCode::Right, "ñb", 2),
+        ]
+        .iter()
+        {
+            input.handle(*key);
+            assert_eq!(
+                (input.text.as_str(), input.cursor),
+                (*text, *cursor),
+                "{:?}",
+                key
+            );
+        }
+    }
+}
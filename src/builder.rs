@@ -0,0 +1,250 @@
+//! Building describers, for tools embedding def.
+//!
+//! A `DescriberBuilder` collects descriptions and patterns along with the
+//! options a describer uses (how paths are matched, the resolution order,
+//! and the syntax of patterns and paths), and builds describers from them.
+//! Its methods take and return `&mut DescriberBuilder`, so they can be
+//! chained, or called one at a time, such as depending on a tool's own
+//! settings. Building doesn't consume the builder, which can build many
+//! describers alike, such as one for each thread.
+
+use crate::matching::MatchOptions;
+use crate::pattern::PatternError;
+use crate::resolution::ResolutionOrder;
+use crate::syntax::Syntax;
+use crate::{Describer, PatternKind};
+
+/// DescriberBuilder configures and builds describers, see the module's
+/// documentation. A describer built with no configuration is the same as
+/// `Describer::new`.
+///
+/// # Examples
+///
+/// ```
+/// use def::DescriberBuilder;
+///
+/// let d = DescriberBuilder::new()
+///     .with_descriptions([("/Users/me", "Home.")])
+///     .with_patterns([("/Users/me/src", "% is a repository, built by *.sh.")])
+///     .case_insensitive(true)
+///     .placeholder('%')
+///     .build()
+///     .unwrap();
+/// assert_eq!(d.describe("/users/me"), Some("Home.".to_string()));
+/// assert_eq!(
+///     d.describe("/users/me/src/def"),
+///     Some("def is a repository, built by *.sh.".to_string())
+/// );
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct DescriberBuilder {
+    descriptions: Vec<(String, String)>,
+    patterns: Vec<(PatternKind, String, String)>,
+    matching: MatchOptions,
+    resolution: ResolutionOrder,
+    syntax: Syntax,
+}
+
+impl DescriberBuilder {
+    /// Create a builder of empty describers with default options.
+    pub fn new() -> DescriberBuilder {
+        DescriberBuilder::default()
+    }
+
+    /// Add descriptions, as pairs of paths and descriptions.
+    pub fn with_descriptions<I, K, V>(&mut self, descriptions: I) -> &mut DescriberBuilder
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.descriptions.extend(
+            descriptions
+                .into_iter()
+                .map(|(path, desc)| (path.into(), desc.into())),
+        );
+        self
+    }
+
+    /// Add patterns describing children of directories, as pairs of paths
+    /// and patterns.
+    pub fn with_patterns<I, K, V>(&mut self, patterns: I) -> &mut DescriberBuilder
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.with_kind(PatternKind::Children, patterns)
+    }
+
+    /// Add patterns describing descendants of directories, as pairs of paths
+    /// and patterns.
+    pub fn with_recursive_patterns<I, K, V>(&mut self, patterns: I) -> &mut DescriberBuilder
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.with_kind(PatternKind::Recursive, patterns)
+    }
+
+    /// Add globs, as pairs of globs and patterns.
+    pub fn with_globs<I, K, V>(&mut self, globs: I) -> &mut DescriberBuilder
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.with_kind(PatternKind::Glob, globs)
+    }
+
+    /// Add patterns of the given kind.
+    fn with_kind<I, K, V>(&mut self, kind: PatternKind, patterns: I) -> &mut DescriberBuilder
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.patterns.extend(
+            patterns
+                .into_iter()
+                .map(|(key, pattern)| (kind, key.into(), pattern.into())),
+        );
+        self
+    }
+
+    /// Match paths against keys ignoring case, see
+    /// `MatchOptions::case_insensitive`.
+    pub fn case_insensitive(&mut self, yes: bool) -> &mut DescriberBuilder {
+        self.matching.case_insensitive = yes;
+        self
+    }
+
+    /// Match paths against keys ignoring Unicode normalization, see
+    /// `MatchOptions::normalize_unicode`.
+    pub fn normalize_unicode(&mut self, yes: bool) -> &mut DescriberBuilder {
+        self.matching.normalize_unicode = yes;
+        self
+    }
+
+    /// Try descriptions and patterns in order, see
+    /// `Describer::set_resolution_order`.
+    pub fn resolution_order(&mut self, order: ResolutionOrder) -> &mut DescriberBuilder {
+        self.resolution = order;
+        self
+    }
+
+    /// Use placeholder rather than `*` as the placeholder of names in
+    /// patterns, see `def::syntax`.
+    pub fn placeholder(&mut self, placeholder: char) -> &mut DescriberBuilder {
+        self.syntax.placeholder = placeholder;
+        self
+    }
+
+    /// Accept paths whose components are separated by separator, rather
+    /// than by `/`, see `def::syntax`.
+    pub fn separator(&mut self, separator: char) -> &mut DescriberBuilder {
+        self.syntax.separator = separator;
+        self
+    }
+
+    /// Build a describer holding the added descriptions and patterns, with
+    /// the set options. An error is returned if a pattern is invalid (see
+    /// `Pattern::parse`).
+    pub fn build(&self) -> Result<Describer, PatternError> {
+        let mut describer = Describer::new();
+        describer.set_syntax(self.syntax);
+        describer.set_match_options(self.matching);
+        describer.set_resolution_order(self.resolution.clone());
+        for (path, desc) in self.descriptions.iter() {
+            describer.add_description(path.as_str(), desc);
+        }
+        for (kind, key, pattern) in self.patterns.iter() {
+            describer.add_pattern_with_kind(key, pattern, *kind)?;
+        }
+        Ok(describer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resolution::Stage;
+
+    #[test]
+    fn build_test() {
+        let mut builder = DescriberBuilder::new();
+        builder
+            .with_descriptions([("/srv", "Served files.")])
+            .with_patterns([("/srv", "* is served.")])
+            .with_recursive_patterns([("/srv/www", "** is a page.")])
+            .with_globs([("*.log", "A log.")]);
+        let d = builder.build().unwrap();
+        assert_eq!(d.describe("/srv"), Some("Served files.".to_string()));
+        assert_eq!(d.describe("/srv/ftp"), Some("ftp is served.".to_string()));
+        assert_eq!(
+            d.describe("/srv/www/blog/index.html"),
+            Some("blog/index.html is a page.".to_string())
+        );
+        assert_eq!(d.describe("/var/access.log"), Some("A log.".to_string()));
+
+        let order = ResolutionOrder::new(vec![
+            vec![Stage::Glob],
+            vec![Stage::Description],
+            vec![Stage::Pattern, Stage::Recursive],
+        ])
+        .unwrap();
+        let d = builder.resolution_order(order).build().unwrap();
+        assert_eq!(d.describe("/srv/error.log"), Some("A log.".to_string()));
+
+        assert!(builder
+            .with_patterns([("/tmp", "{owner} made *.")])
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn default_test() {
+        let built = DescriberBuilder::new().build().unwrap();
+        let new = Describer::new();
+        assert_eq!(built.syntax(), new.syntax());
+        assert_eq!(built.match_options(), new.match_options());
+        assert_eq!(built.resolution_order(), new.resolution_order());
+        assert_eq!(built.to_json(false).unwrap(), new.to_json(false).unwrap());
+    }
+
+    #[test]
+    fn syntax_test() {
+        let d = DescriberBuilder::new()
+            .with_descriptions([(r"C:\Users\me", "Home.")])
+            .with_patterns([(r"C:\Users\me\src", "% is a repository.")])
+            .with_recursive_patterns([(r"C:\Users\me\docs", "%% is a document.")])
+            .placeholder('%')
+            .separator('\\')
+            .build()
+            .unwrap();
+        assert_eq!(d.describe(r"C:\Users\me"), Some("Home.".to_string()));
+        assert_eq!(d.describe("C:/Users/me"), Some("Home.".to_string()));
+        assert_eq!(
+            d.describe(r"C:\Users\me\src\def"),
+            Some("def is a repository.".to_string())
+        );
+        assert_eq!(
+            d.describe(r"C:\Users\me\docs\notes\todo.md"),
+            Some("notes/todo.md is a document.".to_string())
+        );
+        assert_eq!(d.entries_under(r"C:\Users\me\src").len(), 1);
+
+        let mut d = d;
+        d.add_pattern(r"C:\tmp", "% and * are temporary.").unwrap();
+        assert_eq!(
+            d.describe(r"C:\tmp\a"),
+            Some("a and * are temporary.".to_string())
+        );
+        d.set_syntax(Syntax::default());
+        assert_eq!(
+            d.describe("C:/tmp/a"),
+            Some("% and a are temporary.".to_string())
+        );
+    }
+}
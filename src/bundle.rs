@@ -0,0 +1,294 @@
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use def::store::Backend;
+
+/// Name of the manifest of a bundle, which identifies the archive as one.
+const MANIFEST: &str = "bundle.json";
+
+/// Version of the layout of bundles written by this version of def.
+const VERSION: u32 = 1;
+
+/// Directory of installed packs in a bundle.
+const PACKS_DIR: &str = "packs";
+
+/// Directory of translations in a bundle.
+const LOCALES_DIR: &str = "locales";
+
+/// Name of the settings file in a bundle.
+const SETTINGS: &str = "settings.json";
+
+/// Manifest describes a bundle.
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+pub struct Manifest {
+    /// Version of the bundle's layout.
+    pub version: u32,
+    /// Time the bundle was created at, in seconds since the Unix epoch.
+    pub created: u64,
+    /// Name of the config file in the bundle (such as "config.json"), or
+    /// None if there was no config to bundle.
+    pub config: Option<String>,
+}
+
+/// Member is a file in a bundle.
+#[derive(Debug, PartialEq)]
+pub struct Member {
+    /// Path of the file in the bundle, such as "packs/server.json".
+    pub name: String,
+    pub contents: Vec<u8>,
+}
+
+/// Part is what a member of a bundle holds, and decides where it's written
+/// when the bundle is applied.
+#[derive(Debug, PartialEq)]
+pub enum Part {
+    Settings,
+    Config,
+    /// An installed pack, with the name of its file in the packs dir.
+    Pack(String),
+    /// Translations, with the name of their file in the locales dir.
+    Locale(String),
+}
+
+/// collect reads def's state into a manifest and members of a bundle: the
+/// settings file and the config file if they exist, and JSON files in the
+/// packs and locales dirs. The config is named "config" in the bundle,
+/// followed by the extension of config_file.
+pub fn collect(
+    settings_file: &Path,
+    config_file: Option<&Path>,
+    packs_dir: &Path,
+    locales_dir: &Path,
+    created: u64,
+) -> io::Result<(Manifest, Vec<Member>)> {
+    let mut manifest = Manifest {
+        version: VERSION,
+        created,
+        config: None,
+    };
+    let mut members = Vec::new();
+    if settings_file.exists() {
+        members.push(Member {
+            name: SETTINGS.to_string(),
+            contents: fs::read(settings_file)?,
+        });
+    }
+    if let Some(config_file) = config_file {
+        let name = match config_file.extension() {
+            Some(extension) => format!("config.{}", extension.to_string_lossy()),
+            None => "config".to_string(),
+        };
+        members.push(Member {
+            name: name.clone(),
+            contents: fs::read(config_file)?,
+        });
+        manifest.config = Some(name);
+    }
+    for (dir, name) in [(packs_dir, PACKS_DIR), (locales_dir, LOCALES_DIR)].iter() {
+        if !dir.exists() {
+            continue;
+        }
+        let mut files: Vec<_> = fs::read_dir(dir)?.collect::<io::Result<_>>()?;
+        files.sort_by_key(|f| f.file_name());
+        for file in files {
+            let file_name = file.file_name().to_string_lossy().to_string();
+            if file_name.ends_with(".json") && file.file_type()?.is_file() {
+                members.push(Member {
+                    name: format!("{}/{}", name, file_name),
+                    contents: fs::read(file.path())?,
+                });
+            }
+        }
+    }
+    Ok((manifest, members))
+}
+
+/// part returns what the member named name holds, or None if it's not a
+/// part of def's state. Files of packs and locales must be JSON files
+/// directly in their directories, so that applying a bundle can't write
+/// anywhere else.
+pub fn part(name: &str, manifest: &Manifest) -> Option<Part> {
+    if name == SETTINGS {
+        return Some(Part::Settings);
+    }
+    if manifest.config.as_deref() == Some(name) {
+        return Some(Part::Config);
+    }
+
+    let (dir, file) = name.split_once('/')?;
+    let valid = file.ends_with(".json") && !file.starts_with('.') && !file.contains(['/', '\\']);
+    match dir {
+        PACKS_DIR if valid => Some(Part::Pack(file.to_string())),
+        LOCALES_DIR if valid => Some(Part::Locale(file.to_string())),
+        _ => None,
+    }
+}
+
+/// check_config returns an error if the config in the bundle isn't stored
+/// with backend, the backend of the config it would replace (see
+/// `config::store_backend`), as its contents are written as they are.
+/// Formats are told apart by the extension of the bundled config's name.
+pub fn check_config(manifest: &Manifest, backend: Backend) -> io::Result<()> {
+    match manifest.config.as_deref() {
+        Some(name) if Backend::from_path(name) != backend => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "bundled {} is stored as {}, but the local config is stored as {}",
+                name,
+                Backend::from_path(name).name(),
+                backend.name()
+            ),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// write creates a bundle at output: a tar archive holding the manifest,
+/// followed by members.
+pub fn write(output: &str, manifest: &Manifest, members: &[Member]) -> io::Result<()> {
+    let mut builder = tar::Builder::new(File::create(output)?);
+    let json = serde_json::to_vec_pretty(manifest)?;
+    append(&mut builder, MANIFEST, &json, manifest.created)?;
+    for member in members.iter() {
+        append(
+            &mut builder,
+            &member.name,
+            &member.contents,
+            manifest.created,
+        )?;
+    }
+    builder.into_inner()?.flush()
+}
+
+/// append adds a file named name to the archive being built.
+fn append<W: Write>(
+    builder: &mut tar::Builder<W>,
+    name: &str,
+    contents: &[u8],
+    mtime: u64,
+) -> io::Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(contents.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(mtime);
+    header.set_cksum();
+    builder.append_data(&mut header, name, contents)
+}
+
+/// read returns the manifest and other members of the bundle at path. An
+/// error is returned if the archive has no manifest, or was written by a
+/// newer version of def.
+pub fn read(path: &str) -> io::Result<(Manifest, Vec<Member>)> {
+    let mut archive = tar::Archive::new(File::open(path)?);
+    let mut manifest = None;
+    let mut members = Vec::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let name = entry.path()?.to_string_lossy().to_string();
+        let mut contents = Vec::new();
+        entry.read_to_end(&mut contents)?;
+        if name == MANIFEST {
+            manifest = Some(serde_json::from_slice::<Manifest>(&contents)?);
+        } else {
+            members.push(Member { name, contents });
+        }
+    }
+
+    match manifest {
+        Some(manifest) if manifest.version <= VERSION => Ok((manifest, members)),
+        Some(manifest) => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("unsupported bundle version {}", manifest.version),
+        )),
+        None => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "not a def bundle (no bundle.json)",
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundle_test() {
+        let dir = std::env::temp_dir().join(format!("def-bundle-test-{}", std::process::id()));
+        fs::create_dir_all(dir.join("packs")).unwrap();
+        fs::write(dir.join("config.json"), "{}").unwrap();
+        fs::write(dir.join("packs").join("server.json"), "{}").unwrap();
+        fs::write(dir.join("packs").join("notes.txt"), "").unwrap();
+        let file = dir.join("bundle.tar").to_string_lossy().to_string();
+
+        let (manifest, members) = collect(
+            &dir.join("settings.json"),
+            Some(&dir.join("config.json")),
+            &dir.join("packs"),
+            &dir.join("locales"),
+            1700000000,
+        )
+        .unwrap();
+        write(&file, &manifest, &members).unwrap();
+        let read = read(&file);
+        fs::remove_dir_all(&dir).unwrap();
+
+        let (read_manifest, read_members) = read.unwrap();
+        assert_eq!(read_manifest, manifest);
+        assert_eq!(manifest.config.as_deref(), Some("config.json"));
+        let names: Vec<&str> = read_members.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, vec!["config.json", "packs/server.json"]);
+        assert_eq!(read_members, members);
+    }
+
+    #[test]
+    fn check_config_test() {
+        let manifest = |config: Option<&str>| Manifest {
+            version: VERSION,
+            created: 0,
+            config: config.map(str::to_string),
+        };
+        let json = Backend::from_path("config.json");
+        let sqlite = Backend::from_path("config.db");
+        assert!(check_config(&manifest(Some("config.json")), json).is_ok());
+        assert!(check_config(&manifest(Some("config.sqlite")), sqlite).is_ok());
+        assert!(check_config(&manifest(None), sqlite).is_ok());
+
+        let err = check_config(&manifest(Some("config.db")), json).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            err.to_string(),
+            "bundled config.db is stored as sqlite, but the local config is stored as json"
+        );
+        assert!(check_config(&manifest(Some("config.json")), sqlite).is_err());
+    }
+
+    #[test]
+    fn part_test() {
+        let manifest = Manifest {
+            version: VERSION,
+            created: 0,
+            config: Some("config.db".to_string()),
+        };
+        for (name, part) in [
+            ("settings.json", Some(Part::Settings)),
+            ("config.db", Some(Part::Config)),
+            ("config.json", None),
+            (
+                "packs/server.json",
+                Some(Part::Pack("server.json".to_string())),
+            ),
+            ("locales/fr.json", Some(Part::Locale("fr.json".to_string()))),
+            ("packs/../config.json", None),
+            ("packs/a/b.json", None),
+            ("packs/server.toml", None),
+            ("backups/config-1.json", None),
+        ]
+        .iter()
+        {
+            assert_eq!(super::part(name, &manifest), *part, "{}", name);
+        }
+    }
+}
@@ -0,0 +1,299 @@
+//! Descriptions captured from the output of commands.
+//!
+//! A description can be the output of a command, such as `head -1 README.md`
+//! or `git log -1 --format=%s`, rather than text written by hand. The command
+//! is kept beside the description as its source (see
+//! `Describer::set_source`), so that the description can be refreshed by
+//! running it again. Commands run in a shell, in the described directory, or
+//! in the parent of a described file, and are killed if they don't finish
+//! in time.
+
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::Describer;
+
+/// Time commands are given to finish, unless set otherwise in settings.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Interval between checks of whether a command finished.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Source is where a specific description comes from. Sources are kept as
+/// maps tagged by "kind", such as `{"kind": "command", "command": "pwd"}`,
+/// which every format of `def::format` can hold.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Source {
+    /// Text written by hand.
+    Literal,
+    /// Output of a shell command.
+    Command {
+        /// The command, run in a shell.
+        command: String,
+    },
+}
+
+impl Source {
+    /// Return the command of the source, or None if it's literal.
+    pub fn command(&self) -> Option<&str> {
+        match self {
+            Source::Literal => None,
+            Source::Command { command } => Some(command),
+        }
+    }
+}
+
+/// CaptureError is returned when the output of a command can't be used as a
+/// description.
+#[derive(Debug)]
+pub enum CaptureError {
+    /// The command can't be run.
+    Io(io::Error),
+    /// The command exited with a failure, along with the first line it
+    /// printed to stderr, if any.
+    Failed(String),
+    /// The command didn't finish within the given time.
+    TimedOut(Duration),
+    /// The command printed nothing.
+    Empty,
+}
+
+impl fmt::Display for CaptureError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CaptureError::Io(e) => write!(f, "{}", e),
+            CaptureError::Failed(e) => write!(f, "command failed: {}", e),
+            CaptureError::TimedOut(t) => {
+                write!(f, "command didn't finish in {} seconds", t.as_secs_f64())
+            }
+            CaptureError::Empty => write!(f, "command printed nothing"),
+        }
+    }
+}
+
+impl Error for CaptureError {}
+
+impl From<io::Error> for CaptureError {
+    fn from(e: io::Error) -> CaptureError {
+        CaptureError::Io(e)
+    }
+}
+
+/// Return the directory commands capturing the description of path run in:
+/// path itself if it's a directory, and its parent otherwise.
+pub fn working_dir<P: AsRef<Path>>(path: P) -> PathBuf {
+    let path = path.as_ref();
+    if path.is_dir() {
+        return path.to_path_buf();
+    }
+    match path.parent() {
+        Some(parent) if parent.is_dir() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    }
+}
+
+/// Run command in a shell in dir, and return what it printed to stdout,
+/// without leading and trailing whitespace. The command is killed if it
+/// doesn't finish within timeout.
+///
+/// ```
+/// use std::time::Duration;
+///
+/// let output = def::capture::run("echo ' Captured. '", ".", Duration::from_secs(5));
+/// assert_eq!(output.unwrap(), "Captured.");
+/// ```
+pub fn run<P: AsRef<Path>>(
+    command: &str,
+    dir: P,
+    timeout: Duration,
+) -> Result<String, CaptureError> {
+    let mut child = shell(command)
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    // Pipes are read as the command runs, so that it doesn't block on a
+    // full pipe.
+    let stdout = read_in_background(child.stdout.take());
+    let stderr = read_in_background(child.stderr.take());
+
+    let status = wait(&mut child, timeout)?;
+    let status = match status {
+        Some(status) => status,
+        None => {
+            // Readers are left behind, as processes started by the command
+            // may keep the pipes open.
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(CaptureError::TimedOut(timeout));
+        }
+    };
+    let stdout = stdout.join().unwrap_or_default();
+    if !status.success() {
+        let stderr = stderr.join().unwrap_or_default();
+        let reason = String::from_utf8_lossy(&stderr)
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .map(str::to_string)
+            .unwrap_or_else(|| status.to_string());
+        return Err(CaptureError::Failed(reason));
+    }
+
+    let output = String::from_utf8_lossy(&stdout).trim().to_string();
+    if output.is_empty() {
+        return Err(CaptureError::Empty);
+    }
+    Ok(output)
+}
+
+/// Run the command describing path (its source, see `Describer::source`)
+/// again, and replace the description of path with its output. Return true
+/// if the description changed. An error is returned if the command fails,
+/// and nothing is done if the description's source isn't a command.
+pub fn refresh(
+    describer: &mut Describer,
+    path: &str,
+    timeout: Duration,
+) -> Result<bool, CaptureError> {
+    let command = match describer.source(path).command() {
+        Some(command) => command.to_string(),
+        None => return Ok(false),
+    };
+    let output = run(&command, working_dir(path), timeout)?;
+    if describer.description(path) == Some(output.as_str()) {
+        return Ok(false);
+    }
+    describer.add_description(path, &output);
+    Ok(true)
+}
+
+/// Return a command running command in the platform's shell.
+fn shell(command: &str) -> Command {
+    let mut shell = if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C");
+        cmd
+    } else {
+        let mut sh = Command::new("sh");
+        sh.arg("-c");
+        sh
+    };
+    shell.arg(command);
+    shell
+}
+
+/// Read everything from pipe on another thread.
+fn read_in_background<R: Read + Send + 'static>(pipe: Option<R>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut read = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut read);
+        }
+        read
+    })
+}
+
+/// Wait for child to exit, for at most timeout. Return None if it didn't.
+fn wait(child: &mut Child, timeout: Duration) -> io::Result<Option<std::process::ExitStatus>> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        if Instant::now() >= deadline {
+            return Ok(None);
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_test() {
+        let timeout = Duration::from_secs(5);
+        for (command, output) in [
+            ("echo '  One line.  '", Some("One line.")),
+            ("printf 'First.\\nSecond.\\n'", Some("First.\nSecond.")),
+            ("pwd", Some("/")),
+            ("true", None),
+            ("echo ignored; echo 'no such thing' >&2; exit 3", None),
+        ]
+        .iter()
+        {
+            assert_eq!(
+                run(command, "/", timeout).ok().as_deref(),
+                *output,
+                "{}",
+                command
+            );
+        }
+        assert!(matches!(
+            run("true", "/", timeout),
+            Err(CaptureError::Empty)
+        ));
+        match run("echo 'no such thing' >&2; exit 3", "/", timeout) {
+            Err(CaptureError::Failed(reason)) => assert_eq!(reason, "no such thing"),
+            result => panic!("unexpected {:?}", result),
+        }
+
+        let started = Instant::now();
+        let result = run("sleep 5", "/", Duration::from_millis(100));
+        assert!(matches!(result, Err(CaptureError::TimedOut(_))));
+        assert!(started.elapsed() < Duration::from_secs(4));
+    }
+
+    #[test]
+    fn refresh_test() {
+        let timeout = Duration::from_secs(5);
+        let mut d = Describer::new();
+        d.add_description("/", "Old.");
+        d.add_description("/tmp", "Literal.");
+        assert!(d.set_source(
+            "/",
+            Source::Command {
+                command: "echo New.".to_string(),
+            }
+        ));
+        assert!(!d.set_source(
+            "/undescribed",
+            Source::Command {
+                command: "echo".to_string(),
+            }
+        ));
+
+        assert!(refresh(&mut d, "/", timeout).unwrap());
+        assert_eq!(d.description("/"), Some("New."));
+        assert!(!refresh(&mut d, "/", timeout).unwrap());
+        assert!(!refresh(&mut d, "/tmp", timeout).unwrap());
+        assert_eq!(d.description("/tmp"), Some("Literal."));
+        assert_eq!(
+            d.source("/"),
+            &Source::Command {
+                command: "echo New.".to_string(),
+            }
+        );
+        assert_eq!(d.source("/tmp"), &Source::Literal);
+
+        d.set_source(
+            "/",
+            Source::Command {
+                command: "exit 1".to_string(),
+            },
+        );
+        assert!(refresh(&mut d, "/", timeout).is_err());
+        assert_eq!(d.description("/"), Some("New."));
+    }
+}
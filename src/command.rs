@@ -1,7 +1,214 @@
+use def::meta::FileKind;
+use def::readmes::Target;
+use def::search::SearchOptions;
+
+use crate::output::OutputFormat;
+
 // def's sub-commands.
-const ADD_COMMAND: &str = "add";
-const PATTERN_COMMAND: &str = "pattern";
-const HELP_COMMAND: &str = "help";
+pub const ADD_COMMAND: &str = "add";
+pub const PATTERN_COMMAND: &str = "pattern";
+pub const GLOB_COMMAND: &str = "glob";
+pub const HELP_COMMAND: &str = "help";
+pub const DOCTOR_COMMAND: &str = "doctor";
+pub const REMOVE_COMMAND: &str = "remove";
+pub const TRASH_COMMAND: &str = "trash";
+pub const MOVE_COMMAND: &str = "mv";
+pub const ANNOTATE_ARCHIVE_COMMAND: &str = "annotate-archive";
+pub const LIST_COMMAND: &str = "list";
+pub const LS_COMMAND: &str = "ls";
+pub const LISTS_COMMAND: &str = "lists";
+pub const DRIFT_COMMAND: &str = "drift";
+pub const MAINTAIN_COMMAND: &str = "maintain";
+pub const TOUR_COMMAND: &str = "tour";
+pub const BROWSE_COMMAND: &str = "browse";
+pub const BUNDLE_COMMAND: &str = "bundle";
+pub const SEARCH_COMMAND: &str = "search";
+pub const EDIT_COMMAND: &str = "edit";
+pub const HOOK_COMMAND: &str = "hook";
+pub const EXPORT_COMMAND: &str = "export";
+pub const IMPORT_COMMAND: &str = "import";
+pub const TAGGED_COMMAND: &str = "tagged";
+pub const PACK_COMMAND: &str = "pack";
+pub const STALE_COMMAND: &str = "stale";
+pub const CHECK_COMMAND: &str = "check";
+pub const EVENTS_COMMAND: &str = "events";
+pub const COMPLETIONS_COMMAND: &str = "completions";
+pub const FROM_INODE_COMMAND: &str = "from-inode";
+pub const FROM_FD_COMMAND: &str = "from-fd";
+pub const PRIVATE_COMMAND: &str = "private";
+pub const PUBLIC_COMMAND: &str = "public";
+pub const EXPLAIN_COMMAND: &str = "explain";
+pub const INFO_COMMAND: &str = "info";
+pub const STATS_COMMAND: &str = "stats";
+pub const UNDO_COMMAND: &str = "undo";
+pub const HISTORY_COMMAND: &str = "history";
+pub const PROFILE_COMMAND: &str = "profile";
+pub const DAEMON_COMMAND: &str = "daemon";
+pub const FIXTURES_COMMAND: &str = "fixtures";
+pub const APPLY_COMMAND: &str = "apply";
+pub const SYNC_READMES_COMMAND: &str = "sync-readmes";
+pub const SYNC_COMMAND: &str = "sync";
+
+/// Hidden sub-command listing described paths for shell completions, see
+/// "def completions".
+pub const COMPLETE_COMMAND: &str = "__complete";
+
+// Sub-commands of trash.
+const TRASH_LIST: &str = "list";
+const TRASH_RESTORE: &str = "restore";
+const TRASH_EMPTY: &str = "empty";
+
+// Sub-commands of fixtures.
+const FIXTURES_GENERATE: &str = "generate";
+
+// Sub-commands of pack.
+const PACK_INSTALL: &str = "install";
+const PACK_REMOVE: &str = "remove";
+const PACK_LIST: &str = "list";
+
+// Sub-commands of profile.
+const PROFILE_LIST: &str = "list";
+const PROFILE_CREATE: &str = "create";
+const PROFILE_DELETE: &str = "delete";
+
+// Sub-commands of sync.
+const SYNC_PULL: &str = "pull";
+const SYNC_PUSH: &str = "push";
+
+/// Prefix of names of saved searches, as in "def list @name".
+pub const SAVED_SEARCH_PREFIX: &str = "@";
+
+// def's flags.
+pub const CONVERT_PATHS_FLAG: &str = "--convert-paths";
+pub const FIX_PERMS_FLAG: &str = "--fix-perms";
+pub const PATTERN_FLAG: &str = "--pattern";
+pub const PREFIX_FLAG: &str = "--prefix";
+pub const DRY_RUN_FLAG: &str = "--dry-run";
+pub const ROOT_FLAG: &str = "--root";
+pub const RECURSIVE_FLAG: &str = "--recursive";
+pub const FILES_ONLY_FLAG: &str = "--files-only";
+pub const DIRS_ONLY_FLAG: &str = "--dirs-only";
+pub const SYMLINKS_ONLY_FLAG: &str = "--symlinks-only";
+pub const FUZZY_FLAG: &str = "--fuzzy";
+pub const STEM_FLAG: &str = "--stem";
+pub const TYPOS_FLAG: &str = "--typos";
+pub const SAVE_FLAG: &str = "--save";
+pub const DEPTH_FLAG: &str = "--depth";
+pub const LOCAL_FLAG: &str = "--local";
+pub const ALL_FLAG: &str = "--all";
+pub const STDIN_FLAG: &str = "--stdin";
+pub const QUIET_FLAG: &str = "--quiet";
+pub const OVERWRITE_FLAG: &str = "--overwrite";
+pub const KEEP_EXISTING_FLAG: &str = "--keep-existing";
+pub const INTERACTIVE_FLAG: &str = "--interactive";
+pub const WHERE_FLAG: &str = "--where";
+pub const ONLY_FLAG: &str = "--only";
+pub const TAG_FLAG: &str = "--tag";
+pub const DAYS_FLAG: &str = "--days";
+pub const KEY_FLAG: &str = "--key";
+pub const YES_FLAG: &str = "--yes";
+pub const PRUNE_FLAG: &str = "--prune";
+pub const OUTPUT_FLAG: &str = "--output";
+pub const APPLY_FLAG: &str = "--apply";
+pub const FOLLOW_FLAG: &str = "--follow";
+pub const PRIVATE_FLAG: &str = "--private";
+pub const INCLUDE_PRIVATE_FLAG: &str = "--include-private";
+pub const PRIORITY_FLAG: &str = "--priority";
+pub const APPEND_FLAG: &str = "--append";
+pub const PREPEND_FLAG: &str = "--prepend";
+pub const FORCE_FLAG: &str = "--force";
+pub const FROM_COMMAND_FLAG: &str = "--from-command";
+pub const REFRESH_FLAG: &str = "--refresh";
+pub const README_FLAG: &str = "--readme";
+
+// Global flags, which can be used with any sub-command.
+pub const PLAIN_FLAG: &str = "--plain";
+pub const RAW_FLAG: &str = "--raw";
+pub const NO_RESOLVE_FLAG: &str = "--no-resolve";
+pub const NO_STAT_FLAG: &str = "--no-stat";
+pub const INHERIT_FLAG: &str = "--inherit";
+pub const ALLOW_COMMANDS_FLAG: &str = "--allow-commands";
+pub const FORMAT_FLAG: &str = "--format";
+pub const PROFILE_FLAG: &str = "--profile";
+pub const CONFIG_FLAG: &str = "--config";
+
+/// Options holds global flags, which affect how any sub-command behaves
+/// rather than what it does.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Options {
+    /// Print plain output without colors or alignment.
+    pub plain: bool,
+    /// Print descriptions as written, without rendering Markdown.
+    pub raw: bool,
+    /// Resolve paths lexically, without requiring them to exist or following
+    /// symlinks.
+    pub no_resolve: bool,
+    /// Leave metadata placeholders of patterns as written, rather than
+    /// filling them in from the file system.
+    pub no_stat: bool,
+    /// Describe paths without a description by that of their nearest
+    /// described ancestor, see `def::Describer::describe_inherited`.
+    pub inherit: bool,
+    /// Run commands capturing descriptions, see `def::capture`.
+    pub allow_commands: bool,
+    /// Format of printed descriptions.
+    pub format: OutputFormat,
+    /// Name of the profile whose descriptions are used, see
+    /// `def::config::use_profile`.
+    pub profile: Option<String>,
+    /// Config file used instead of the one of settings, see
+    /// `def::config::resolve`.
+    pub config: Option<String>,
+}
+
+/// ImportStrategy is how "def import" merges imported entries which conflict
+/// with existing ones (see def::merge).
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub enum ImportStrategy {
+    /// Replace existing entries.
+    Overwrite,
+    /// Keep existing entries.
+    #[default]
+    KeepExisting,
+    /// Ask which entry to keep for each conflict.
+    Interactive,
+}
+
+/// AddMode is how "def add" combines a description with the existing
+/// description of the path, if any.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub enum AddMode {
+    /// Replace the existing description, warning if it's different.
+    #[default]
+    Add,
+    /// Replace the existing description without a warning.
+    Force,
+    /// Add the description after the existing one.
+    Append,
+    /// Add the description before the existing one.
+    Prepend,
+}
+
+/// Subset is a kind of entries exported or imported alone, selected by
+/// --only.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Subset {
+    /// Specific descriptions.
+    Descriptions,
+    /// Patterns, recursive patterns, and globs.
+    Patterns,
+}
+
+impl Subset {
+    /// Return the subset with the given name, or None if there's none.
+    pub fn from_name(name: &str) -> Option<Subset> {
+        match name {
+            "descriptions" => Some(Subset::Descriptions),
+            "patterns" => Some(Subset::Patterns),
+            _ => None,
+        }
+    }
+}
 
 /// InvokedTo defines different things the `def` command can do, such as:
 /// print a help message, describe directory, add a description, etc. Only
@@ -11,7 +218,7 @@ const HELP_COMMAND: &str = "help";
 /// be extracted from the command line argumenst.
 ///
 /// For example: `def add path describition` is parsed to
-/// `InvokedTo::AddDescription("path", "description")`.
+/// `InvokedTo::AddDescription { path: "path", description: "description", .. }`.
 ///
 /// If new functionality is added to the command (such as a new flag), then
 /// a new enum defining it should be added here.
@@ -19,9 +226,118 @@ const HELP_COMMAND: &str = "help";
 pub enum InvokedTo {
     ShortHelp,
     Help,
+    HelpFor(String),
     DescribePath(String),
-    AddDescription(String, String),
-    AddPattern(String, String),
+    DescribeStdin,
+    DescribeQuietly(String),
+    Refresh(Option<String>),
+    AddDescription {
+        path: String,
+        description: String,
+        tags: Vec<String>,
+        private: bool,
+        mode: AddMode,
+        /// The description is a command whose output is captured.
+        from_command: bool,
+    },
+    AddLocalDescription(String, String),
+    AddPattern(String, String, Option<i32>),
+    AddRecursivePattern(String, String, Option<i32>),
+    AddTypedPattern(String, String, FileKind, Option<i32>),
+    AddGlob(String, String, Option<i32>),
+    Edit(String),
+    EditAll,
+    Doctor,
+    ConvertPaths,
+    FixPermissions,
+    RemoveDescription(String),
+    RemovePattern(String),
+    ListTrash,
+    RestoreFromTrash(String),
+    EmptyTrash,
+    Move {
+        old: String,
+        new: String,
+        recursive: bool,
+        dry_run: bool,
+    },
+    List {
+        prefix: Option<String>,
+        depth: Option<usize>,
+        filter: Option<String>,
+    },
+    Ls(Option<String>),
+    Drift(String),
+    Stats(Option<String>),
+    Undo,
+    History(String),
+    Stale(u64),
+    Check(bool),
+    Maintain(Option<String>),
+    AnnotateArchive {
+        archive: String,
+        root: Option<String>,
+    },
+    Tour,
+    Browse,
+    Daemon,
+    Bundle(String),
+    ApplyBundle(String),
+    GenerateFixture(String),
+    FollowEvents,
+    Completions(String),
+    DescribeInode(String),
+    DescribeFd(String),
+    SetPrivate(String, bool),
+    Explain(String),
+    Info(String),
+    Complete(String),
+    Search {
+        query: String,
+        options: SearchOptions,
+        filter: Option<String>,
+    },
+    SaveSearch {
+        name: String,
+        query: String,
+    },
+    ListSavedSearches,
+    RunSavedSearch(String),
+    ListTagged(String),
+    InstallPack {
+        source: String,
+        prefix: Option<String>,
+        key: Option<String>,
+        yes: bool,
+    },
+    RemovePack(String),
+    ListPacks,
+    ListProfiles,
+    CreateProfile(String),
+    DeleteProfile(String),
+    SyncPull,
+    SyncPush,
+    Hook(String),
+    Export {
+        file: Option<String>,
+        filter: Option<String>,
+        only: Option<Subset>,
+        include_private: bool,
+    },
+    Import {
+        file: String,
+        strategy: ImportStrategy,
+        only: Option<Subset>,
+    },
+    Apply {
+        file: String,
+        dry_run: bool,
+    },
+    SyncReadmes {
+        root: String,
+        target: Target,
+        dry_run: bool,
+    },
     Unknown,
 }
 
@@ -29,35 +345,911 @@ pub enum InvokedTo {
 /// what the command should achieve (print a help message, print description, add
 /// description, etc.), and a list of arguments needed to do it.
 pub fn parse(args: &[String]) -> InvokedTo {
+    if args.len() > 2 {
+        match args[1].as_str() {
+            ADD_COMMAND => return parse_add(&args[2..]),
+            PATTERN_COMMAND => return parse_pattern(&args[2..], false),
+            GLOB_COMMAND => return parse_pattern(&args[2..], true),
+            MOVE_COMMAND => return parse_move(&args[2..]),
+            ANNOTATE_ARCHIVE_COMMAND => return parse_annotate_archive(&args[2..]),
+            SEARCH_COMMAND => return parse_search(&args[2..]),
+            LIST_COMMAND => return parse_list(&args[2..]),
+            EXPORT_COMMAND => return parse_export(&args[2..]),
+            IMPORT_COMMAND => return parse_import(&args[2..]),
+            APPLY_COMMAND => return parse_apply(&args[2..]),
+            SYNC_READMES_COMMAND => return parse_sync_readmes(&args[2..]),
+            PACK_COMMAND => return parse_pack(&args[2..]),
+            PROFILE_COMMAND => return parse_profile(&args[2..]),
+            SYNC_COMMAND => return parse_sync(&args[2..]),
+            _ => {}
+        }
+    }
+
     match args.len() {
         1 => InvokedTo::ShortHelp,
         2 => match args[1].as_str() {
             HELP_COMMAND => InvokedTo::Help,
+            DOCTOR_COMMAND => InvokedTo::Doctor,
+            LIST_COMMAND => InvokedTo::List {
+                prefix: None,
+                depth: None,
+                filter: None,
+            },
+            LS_COMMAND => InvokedTo::Ls(None),
+            LISTS_COMMAND => InvokedTo::ListSavedSearches,
+            MAINTAIN_COMMAND => InvokedTo::Maintain(None),
+            STATS_COMMAND => InvokedTo::Stats(None),
+            UNDO_COMMAND => InvokedTo::Undo,
+            STALE_COMMAND => InvokedTo::Stale(0),
+            CHECK_COMMAND => InvokedTo::Check(false),
+            TOUR_COMMAND => InvokedTo::Tour,
+            BROWSE_COMMAND => InvokedTo::Browse,
+            DAEMON_COMMAND => InvokedTo::Daemon,
+            COMPLETE_COMMAND => InvokedTo::Complete(String::new()),
+            EXPORT_COMMAND => InvokedTo::Export {
+                file: None,
+                filter: None,
+                only: None,
+                include_private: false,
+            },
+            STDIN_FLAG => InvokedTo::DescribeStdin,
+            REFRESH_FLAG => InvokedTo::Refresh(None),
             _ => InvokedTo::DescribePath(args[1].clone()),
         },
-        4 => match args[1].as_str() {
-            ADD_COMMAND => InvokedTo::AddDescription(args[2].clone(), args[3].clone()),
-            PATTERN_COMMAND => InvokedTo::AddPattern(args[2].clone(), args[3].clone()),
+        3 => match (args[1].as_str(), args[2].as_str()) {
+            (HELP_COMMAND, command) => InvokedTo::HelpFor(command.to_string()),
+            (QUIET_FLAG, path) => InvokedTo::DescribeQuietly(path.to_string()),
+            (REFRESH_FLAG, path) => InvokedTo::Refresh(Some(path.to_string())),
+            (HOOK_COMMAND, shell) => InvokedTo::Hook(shell.to_string()),
+            (COMPLETIONS_COMMAND, shell) => InvokedTo::Completions(shell.to_string()),
+            (COMPLETE_COMMAND, prefix) => InvokedTo::Complete(prefix.to_string()),
+            (FROM_INODE_COMMAND, id) => InvokedTo::DescribeInode(id.to_string()),
+            (FROM_FD_COMMAND, id) => InvokedTo::DescribeFd(id.to_string()),
+            (PRIVATE_COMMAND, path) => InvokedTo::SetPrivate(path.to_string(), true),
+            (PUBLIC_COMMAND, path) => InvokedTo::SetPrivate(path.to_string(), false),
+            (EXPLAIN_COMMAND, path) => InvokedTo::Explain(path.to_string()),
+            (INFO_COMMAND, path) => InvokedTo::Info(path.to_string()),
+            (TAGGED_COMMAND, tag) => InvokedTo::ListTagged(tag.to_string()),
+            (DOCTOR_COMMAND, CONVERT_PATHS_FLAG) => InvokedTo::ConvertPaths,
+            (DOCTOR_COMMAND, FIX_PERMS_FLAG) => InvokedTo::FixPermissions,
+            (EDIT_COMMAND, ALL_FLAG) => InvokedTo::EditAll,
+            (EDIT_COMMAND, path) => InvokedTo::Edit(path.to_string()),
+            (REMOVE_COMMAND, path) => InvokedTo::RemoveDescription(path.to_string()),
+            (LS_COMMAND, dir) => InvokedTo::Ls(Some(dir.to_string())),
+            (DRIFT_COMMAND, root) => InvokedTo::Drift(root.to_string()),
+            (STATS_COMMAND, dir) => InvokedTo::Stats(Some(dir.to_string())),
+            (HISTORY_COMMAND, path) => InvokedTo::History(path.to_string()),
+            (CHECK_COMMAND, PRUNE_FLAG) => InvokedTo::Check(true),
+            (EVENTS_COMMAND, FOLLOW_FLAG) => InvokedTo::FollowEvents,
+            (TRASH_COMMAND, TRASH_LIST) => InvokedTo::ListTrash,
+            (TRASH_COMMAND, TRASH_EMPTY) => InvokedTo::EmptyTrash,
+            _ => InvokedTo::Unknown,
+        },
+        4 => match (args[1].as_str(), args[2].as_str()) {
+            (REMOVE_COMMAND, PATTERN_FLAG) => InvokedTo::RemovePattern(args[3].clone()),
+            (TRASH_COMMAND, TRASH_RESTORE) => InvokedTo::RestoreFromTrash(args[3].clone()),
+            (MAINTAIN_COMMAND, ROOT_FLAG) => InvokedTo::Maintain(Some(args[3].clone())),
+            (BUNDLE_COMMAND, OUTPUT_FLAG) => InvokedTo::Bundle(args[3].clone()),
+            (BUNDLE_COMMAND, APPLY_FLAG) => InvokedTo::ApplyBundle(args[3].clone()),
+            (FIXTURES_COMMAND, FIXTURES_GENERATE) => InvokedTo::GenerateFixture(args[3].clone()),
+            (STALE_COMMAND, DAYS_FLAG) => match args[3].parse::<u64>() {
+                Ok(days) => InvokedTo::Stale(days),
+                Err(_) => InvokedTo::Unknown,
+            },
             _ => InvokedTo::Unknown,
         },
         _ => InvokedTo::Unknown,
     }
 }
 
+/// operation returns the name of the operation arguments (without global
+/// flags) invoke def to do, as changes it makes to the config are journaled
+/// under: the sub-command, or the flag without dashes if def is invoked with
+/// one, such as "refresh" for --refresh. Sub-commands of trash are named
+/// along with it, such as "trash restore".
+pub fn operation(args: &[String]) -> String {
+    match (args.get(1).map(String::as_str), args.get(2)) {
+        (Some(TRASH_COMMAND), Some(sub)) => format!("{} {}", TRASH_COMMAND, sub),
+        _ => args
+            .get(1)
+            .map(|arg| arg.trim_start_matches('-').to_string())
+            .unwrap_or_default(),
+    }
+}
+
+/// parse_options removes global flags from a list of command line arguments,
+/// and returns the remaining arguments along with the options they set.
+pub fn parse_options(args: &[String]) -> (Vec<String>, Options) {
+    let mut options = Options::default();
+    let mut remaining = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            PLAIN_FLAG => options.plain = true,
+            RAW_FLAG => options.raw = true,
+            NO_RESOLVE_FLAG => options.no_resolve = true,
+            NO_STAT_FLAG => options.no_stat = true,
+            INHERIT_FLAG => options.inherit = true,
+            ALLOW_COMMANDS_FLAG => options.allow_commands = true,
+            FORMAT_FLAG => match args.clone().next().and_then(|f| OutputFormat::from_name(f)) {
+                Some(format) => {
+                    options.format = format;
+                    args.next();
+                }
+                // Left in place, so that the arguments are invalid.
+                None => remaining.push(arg.clone()),
+            },
+            PROFILE_FLAG | CONFIG_FLAG => match args.clone().next() {
+                Some(value) if !value.starts_with("--") => {
+                    match arg.as_str() {
+                        PROFILE_FLAG => options.profile = Some(value.clone()),
+                        _ => options.config = Some(value.clone()),
+                    }
+                    args.next();
+                }
+                _ => remaining.push(arg.clone()),
+            },
+            _ => remaining.push(arg.clone()),
+        }
+    }
+    (remaining, options)
+}
+
+/// parse_add parses arguments of add sub-command: a path and a description,
+/// preceded by --local for local descriptions, and any number of
+/// `--tag <tag>` anywhere, along with --private and one of --force,
+/// --append, and --prepend. Local descriptions can't be tagged, or use any
+/// other flag. The description can be replaced by `--from-command <cmd>`,
+/// which can't be combined with --append or --prepend.
+fn parse_add(args: &[String]) -> InvokedTo {
+    let mut tags = Vec::new();
+    let mut private = false;
+    let mut command = None;
+    let mut modes = Vec::new();
+    let mut rest = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            TAG_FLAG => match args.next() {
+                Some(tag) if !tag.is_empty() && !tag.starts_with("--") => {
+                    tags.push(tag.to_string())
+                }
+                _ => return InvokedTo::Unknown,
+            },
+            FROM_COMMAND_FLAG => match args.next() {
+                Some(c) if command.is_none() && !c.trim().is_empty() => command = Some(c),
+                _ => return InvokedTo::Unknown,
+            },
+            PRIVATE_FLAG => private = true,
+            FORCE_FLAG => modes.push(AddMode::Force),
+            APPEND_FLAG => modes.push(AddMode::Append),
+            PREPEND_FLAG => modes.push(AddMode::Prepend),
+            _ => rest.push(arg),
+        }
+    }
+    let mode = match modes.as_slice() {
+        [] => AddMode::Add,
+        [mode] => *mode,
+        _ => return InvokedTo::Unknown,
+    };
+
+    match (rest.as_slice(), command) {
+        ([flag, path, description], None)
+            if *flag == LOCAL_FLAG && tags.is_empty() && !private && mode == AddMode::Add =>
+        {
+            InvokedTo::AddLocalDescription(path.to_string(), description.to_string())
+        }
+        ([path, description], None) => InvokedTo::AddDescription {
+            path: path.to_string(),
+            description: description.to_string(),
+            tags,
+            private,
+            mode,
+            from_command: false,
+        },
+        // Output of commands replaces descriptions as a whole, so that
+        // refreshing them gives the same result.
+        ([path], Some(command)) if matches!(mode, AddMode::Add | AddMode::Force) => {
+            InvokedTo::AddDescription {
+                path: path.to_string(),
+                description: command.to_string(),
+                tags,
+                private,
+                mode,
+                from_command: true,
+            }
+        }
+        _ => InvokedTo::Unknown,
+    }
+}
+
+/// parse_pattern parses arguments of pattern sub-command, or of glob
+/// sub-command if glob is true: a path (or a glob) and a pattern, along with
+/// an optional `--priority <n>` anywhere, and --recursive or one of
+/// --files-only, --dirs-only, and --symlinks-only for patterns.
+fn parse_pattern(args: &[String], glob: bool) -> InvokedTo {
+    let mut recursive = false;
+    let mut entry_type = None;
+    let mut priority = None;
+    let mut rest = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let only = match arg.as_str() {
+            FILES_ONLY_FLAG => Some(FileKind::File),
+            DIRS_ONLY_FLAG => Some(FileKind::Directory),
+            SYMLINKS_ONLY_FLAG => Some(FileKind::Symlink),
+            _ => None,
+        };
+        match arg.as_str() {
+            _ if only.is_some() && !glob && entry_type.is_none() => entry_type = only,
+            RECURSIVE_FLAG if !glob => recursive = true,
+            PRIORITY_FLAG => match args.next().and_then(|p| p.parse::<i32>().ok()) {
+                Some(p) => priority = Some(p),
+                None => return InvokedTo::Unknown,
+            },
+            flag if flag.starts_with("--") => return InvokedTo::Unknown,
+            _ => rest.push(arg),
+        }
+    }
+
+    match rest.as_slice() {
+        [path, pattern] if glob => {
+            InvokedTo::AddGlob(path.to_string(), pattern.to_string(), priority)
+        }
+        [_, _] if recursive && entry_type.is_some() => InvokedTo::Unknown,
+        [path, pattern] if recursive => {
+            InvokedTo::AddRecursivePattern(path.to_string(), pattern.to_string(), priority)
+        }
+        [path, pattern] => match entry_type {
+            Some(kind) => {
+                InvokedTo::AddTypedPattern(path.to_string(), pattern.to_string(), kind, priority)
+            }
+            None => InvokedTo::AddPattern(path.to_string(), pattern.to_string(), priority),
+        },
+        _ => InvokedTo::Unknown,
+    }
+}
+
+/// parse_pack parses arguments of pack sub-command: "install" followed by a
+/// file or URL and optional `--prefix <dir>`, `--key <key>`, and `--yes` in
+/// any order, "remove" followed by a pack's name, or "list".
+fn parse_pack(args: &[String]) -> InvokedTo {
+    match args {
+        [list] if list == PACK_LIST => InvokedTo::ListPacks,
+        [remove, name] if remove == PACK_REMOVE => InvokedTo::RemovePack(name.clone()),
+        [install, rest @ ..] if install == PACK_INSTALL => parse_pack_install(rest),
+        _ => InvokedTo::Unknown,
+    }
+}
+
+/// parse_profile parses arguments of profile sub-command: list, or create
+/// or delete followed by the name of a profile.
+fn parse_profile(args: &[String]) -> InvokedTo {
+    match args {
+        [list] if list == PROFILE_LIST => InvokedTo::ListProfiles,
+        [create, name] if create == PROFILE_CREATE => InvokedTo::CreateProfile(name.clone()),
+        [delete, name] if delete == PROFILE_DELETE => InvokedTo::DeleteProfile(name.clone()),
+        _ => InvokedTo::Unknown,
+    }
+}
+
+/// parse_sync parses arguments of sync sub-command: pull or push.
+fn parse_sync(args: &[String]) -> InvokedTo {
+    match args {
+        [pull] if pull == SYNC_PULL => InvokedTo::SyncPull,
+        [push] if push == SYNC_PUSH => InvokedTo::SyncPush,
+        _ => InvokedTo::Unknown,
+    }
+}
+
+/// parse_pack_install parses arguments of "pack install", see parse_pack.
+fn parse_pack_install(args: &[String]) -> InvokedTo {
+    let mut source = None;
+    let mut prefix = None;
+    let mut key = None;
+    let mut yes = false;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            PREFIX_FLAG => match args.next() {
+                Some(dir) => prefix = Some(dir.to_string()),
+                None => return InvokedTo::Unknown,
+            },
+            KEY_FLAG => match args.next() {
+                Some(k) => key = Some(k.to_string()),
+                None => return InvokedTo::Unknown,
+            },
+            YES_FLAG => yes = true,
+            flag if flag.starts_with("--") => return InvokedTo::Unknown,
+            _ if source.is_some() => return InvokedTo::Unknown,
+            s => source = Some(s.to_string()),
+        }
+    }
+    match source {
+        Some(source) => InvokedTo::InstallPack {
+            source,
+            prefix,
+            key,
+            yes,
+        },
+        None => InvokedTo::Unknown,
+    }
+}
+
+/// parse_move parses arguments of mv sub-command. Flags can appear in any order
+/// before, between, or after the old and new paths. --prefix is an older name
+/// of --recursive.
+fn parse_move(args: &[String]) -> InvokedTo {
+    let (flags, paths) = split_flags(args);
+    let mut recursive = false;
+    let mut dry_run = false;
+    for flag in flags {
+        match flag {
+            RECURSIVE_FLAG | PREFIX_FLAG => recursive = true,
+            DRY_RUN_FLAG => dry_run = true,
+            _ => return InvokedTo::Unknown,
+        }
+    }
+
+    match paths.as_slice() {
+        [old, new] => InvokedTo::Move {
+            old: old.to_string(),
+            new: new.to_string(),
+            recursive,
+            dry_run,
+        },
+        _ => InvokedTo::Unknown,
+    }
+}
+
+/// parse_annotate_archive parses arguments of annotate-archive sub-command,
+/// which are an archive's path and an optional `--root <dir>`.
+fn parse_annotate_archive(args: &[String]) -> InvokedTo {
+    match args {
+        [archive] => InvokedTo::AnnotateArchive {
+            archive: archive.clone(),
+            root: None,
+        },
+        [flag, root, archive] | [archive, flag, root] if flag == ROOT_FLAG => {
+            InvokedTo::AnnotateArchive {
+                archive: archive.clone(),
+                root: Some(root.clone()),
+            }
+        }
+        _ => InvokedTo::Unknown,
+    }
+}
+
+/// parse_list parses arguments of list sub-command, which are an optional
+/// prefix and an optional `--depth <n>`, where n is a positive number, or
+/// the name of a saved search following "@".
+fn parse_list(args: &[String]) -> InvokedTo {
+    if let [name] = args {
+        if let Some(name) = name.strip_prefix(SAVED_SEARCH_PREFIX) {
+            return InvokedTo::RunSavedSearch(name.to_string());
+        }
+    }
+
+    let mut prefix = None;
+    let mut depth = None;
+    let mut filter = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            DEPTH_FLAG => match args.next().and_then(|d| d.parse::<usize>().ok()) {
+                Some(d) if d > 0 => depth = Some(d),
+                _ => return InvokedTo::Unknown,
+            },
+            WHERE_FLAG => match args.next() {
+                Some(query) => filter = Some(query.to_string()),
+                None => return InvokedTo::Unknown,
+            },
+            flag if flag.starts_with("--") => return InvokedTo::Unknown,
+            _ if prefix.is_some() => return InvokedTo::Unknown,
+            path => prefix = Some(path.to_string()),
+        }
+    }
+    InvokedTo::List {
+        prefix,
+        depth,
+        filter,
+    }
+}
+
+/// parse_export parses arguments of export sub-command: an optional file,
+/// an optional --where query, and an optional --only subset, both selecting
+/// exported entries.
+fn parse_export(args: &[String]) -> InvokedTo {
+    let mut file = None;
+    let mut filter = None;
+    let mut only = None;
+    let mut include_private = false;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            WHERE_FLAG => match args.next() {
+                Some(query) => filter = Some(query.to_string()),
+                None => return InvokedTo::Unknown,
+            },
+            ONLY_FLAG => match args.next().and_then(|s| Subset::from_name(s)) {
+                Some(subset) => only = Some(subset),
+                None => return InvokedTo::Unknown,
+            },
+            INCLUDE_PRIVATE_FLAG => include_private = true,
+            flag if flag.starts_with("--") => return InvokedTo::Unknown,
+            _ if file.is_some() => return InvokedTo::Unknown,
+            path => file = Some(path.to_string()),
+        }
+    }
+    InvokedTo::Export {
+        file,
+        filter,
+        only,
+        include_private,
+    }
+}
+
+/// parse_search parses arguments of search sub-command. Words of the query
+/// can be given as one argument or several, which are joined using spaces.
+/// With --where, words can be omitted to find all entries matching it.
+fn parse_search(args: &[String]) -> InvokedTo {
+    let mut options = SearchOptions::default();
+    let mut typos = None;
+    let mut save = None;
+    let mut filter = None;
+    let mut words = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            FUZZY_FLAG => options = SearchOptions::fuzzy(),
+            STEM_FLAG => options.stem = true,
+            TYPOS_FLAG => match args.next().and_then(|n| n.parse::<usize>().ok()) {
+                Some(n) => typos = Some(n),
+                None => return InvokedTo::Unknown,
+            },
+            SAVE_FLAG => match args.next() {
+                Some(name) if !name.is_empty() && !name.starts_with("--") => save = Some(name),
+                _ => return InvokedTo::Unknown,
+            },
+            WHERE_FLAG => match args.next() {
+                Some(query) => filter = Some(query.to_string()),
+                None => return InvokedTo::Unknown,
+            },
+            flag if flag.starts_with("--") => return InvokedTo::Unknown,
+            word => words.push(word),
+        }
+    }
+    // --typos overrides the typos allowed by --fuzzy, wherever it's given.
+    if let Some(typos) = typos {
+        options.max_typos = typos;
+    }
+
+    match (words.is_empty() && filter.is_none(), save) {
+        (true, _) => InvokedTo::Unknown,
+        // Saved searches are strict, so other flags can't be saved.
+        (false, Some(_)) if !options.is_strict() || filter.is_some() => InvokedTo::Unknown,
+        (false, Some(name)) => InvokedTo::SaveSearch {
+            name: name.to_string(),
+            query: words.join(" "),
+        },
+        (false, None) => InvokedTo::Search {
+            query: words.join(" "),
+            options,
+            filter,
+        },
+    }
+}
+
+/// parse_import parses arguments of import sub-command, which are a file
+/// and at most one of --overwrite, --keep-existing, and --interactive, in
+/// any order.
+fn parse_import(args: &[String]) -> InvokedTo {
+    let mut strategy = None;
+    let mut only = None;
+    let mut file = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            // Only one strategy can be given.
+            OVERWRITE_FLAG | KEEP_EXISTING_FLAG | INTERACTIVE_FLAG if strategy.is_some() => {
+                return InvokedTo::Unknown
+            }
+            OVERWRITE_FLAG => strategy = Some(ImportStrategy::Overwrite),
+            KEEP_EXISTING_FLAG => strategy = Some(ImportStrategy::KeepExisting),
+            INTERACTIVE_FLAG => strategy = Some(ImportStrategy::Interactive),
+            ONLY_FLAG => match args.next().and_then(|s| Subset::from_name(s)) {
+                Some(subset) => only = Some(subset),
+                None => return InvokedTo::Unknown,
+            },
+            flag if flag.starts_with("--") => return InvokedTo::Unknown,
+            _ if file.is_some() => return InvokedTo::Unknown,
+            path => file = Some(path.to_string()),
+        }
+    }
+    match file {
+        Some(file) => InvokedTo::Import {
+            file,
+            strategy: strategy.unwrap_or_default(),
+            only,
+        },
+        None => InvokedTo::Unknown,
+    }
+}
+
+/// parse_apply parses arguments of apply sub-command, which are an
+/// annotation file and an optional `--dry-run`.
+fn parse_apply(args: &[String]) -> InvokedTo {
+    let (flags, paths) = split_flags(args);
+    let mut dry_run = false;
+    for flag in flags {
+        match flag {
+            DRY_RUN_FLAG => dry_run = true,
+            _ => return InvokedTo::Unknown,
+        }
+    }
+
+    match paths.as_slice() {
+        [file] => InvokedTo::Apply {
+            file: file.to_string(),
+            dry_run,
+        },
+        _ => InvokedTo::Unknown,
+    }
+}
+
+/// parse_sync_readmes parses arguments of sync-readmes sub-command, which
+/// are a root directory, and optional `--readme` and `--dry-run`.
+fn parse_sync_readmes(args: &[String]) -> InvokedTo {
+    let (flags, paths) = split_flags(args);
+    let mut target = Target::Dirdesc;
+    let mut dry_run = false;
+    for flag in flags {
+        match flag {
+            README_FLAG => target = Target::Readme,
+            DRY_RUN_FLAG => dry_run = true,
+            _ => return InvokedTo::Unknown,
+        }
+    }
+
+    match paths.as_slice() {
+        [root] => InvokedTo::SyncReadmes {
+            root: root.to_string(),
+            target,
+            dry_run,
+        },
+        _ => InvokedTo::Unknown,
+    }
+}
+
+/// split_flags separates flags (arguments starting with "--") from other
+/// arguments, keeping the order of each.
+fn split_flags(args: &[String]) -> (Vec<&str>, Vec<&str>) {
+    args.iter()
+        .map(|a| a.as_str())
+        .partition(|a| a.starts_with("--"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn parse_options_test() {
+        for (args, remaining, options) in [
+            (vec!["def", "ls"], vec!["def", "ls"], Options::default()),
+            (
+                vec!["def", "--plain", "ls"],
+                vec!["def", "ls"],
+                Options {
+                    plain: true,
+                    ..Options::default()
+                },
+            ),
+            (
+                vec!["def", "list", "/path", "--plain"],
+                vec!["def", "list", "/path"],
+                Options {
+                    plain: true,
+                    ..Options::default()
+                },
+            ),
+            (
+                vec!["def", "add", "--no-resolve", "new", "A new file."],
+                vec!["def", "add", "new", "A new file."],
+                Options {
+                    no_resolve: true,
+                    ..Options::default()
+                },
+            ),
+            (
+                vec!["def", "--no-stat", "ls"],
+                vec!["def", "ls"],
+                Options {
+                    no_stat: true,
+                    ..Options::default()
+                },
+            ),
+            (
+                vec!["def", "--allow-commands", "--refresh"],
+                vec!["def", "--refresh"],
+                Options {
+                    allow_commands: true,
+                    ..Options::default()
+                },
+            ),
+            (
+                vec!["def", "/path/file", "--inherit"],
+                vec!["def", "/path/file"],
+                Options {
+                    inherit: true,
+                    ..Options::default()
+                },
+            ),
+            (
+                vec!["def", "ls", "--format", "json"],
+                vec!["def", "ls"],
+                Options {
+                    format: OutputFormat::Json,
+                    ..Options::default()
+                },
+            ),
+            (
+                vec!["def", "list", "--format", "xml"],
+                vec!["def", "list", "--format", "xml"],
+                Options::default(),
+            ),
+            (
+                vec!["def", "--profile", "work", "add", "/path", "A path."],
+                vec!["def", "add", "/path", "A path."],
+                Options {
+                    profile: Some("work".to_string()),
+                    ..Options::default()
+                },
+            ),
+            (
+                vec!["def", "--config", "/tmp/def.json", "ls"],
+                vec!["def", "ls"],
+                Options {
+                    config: Some("/tmp/def.json".to_string()),
+                    ..Options::default()
+                },
+            ),
+            (
+                vec!["def", "ls", "--profile"],
+                vec!["def", "ls", "--profile"],
+                Options::default(),
+            ),
+            (
+                vec!["def", "--raw", "/path", "--plain"],
+                vec!["def", "/path"],
+                Options {
+                    plain: true,
+                    raw: true,
+                    ..Options::default()
+                },
+            ),
+        ]
+        .iter()
+        {
+            let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+            assert_eq!(
+                parse_options(&args),
+                (
+                    remaining.iter().map(|a| a.to_string()).collect(),
+                    options.clone()
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn operation_test() {
+        for (args, expected) in [
+            (vec!["def"], ""),
+            (vec!["def", "add", "/path", "A path."], "add"),
+            (vec!["def", "--refresh"], "refresh"),
+            (vec!["def", "trash", "restore", "/path"], "trash restore"),
+            (vec!["def", "trash"], "trash"),
+        ]
+        .iter()
+        {
+            let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+            assert_eq!(operation(&args), *expected);
+        }
+    }
+
     #[test]
     fn parse_test() {
         for (args, res) in [
             (vec!["def".to_string()], InvokedTo::ShortHelp),
             (vec!["./renamed".to_string()], InvokedTo::ShortHelp),
             (vec!["def".to_string(), "help".to_string()], InvokedTo::Help),
+            (
+                vec!["def".to_string(), "help".to_string(), "add".to_string()],
+                InvokedTo::HelpFor("add".to_string()),
+            ),
             (
                 vec!["def".to_string(), "/path/to/dir".to_string()],
                 InvokedTo::DescribePath("/path/to/dir".to_string()),
             ),
+            (
+                vec!["def".to_string(), "--stdin".to_string()],
+                InvokedTo::DescribeStdin,
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "--quiet".to_string(),
+                    "/path/to/dir".to_string(),
+                ],
+                InvokedTo::DescribeQuietly("/path/to/dir".to_string()),
+            ),
+            (
+                vec!["def".to_string(), "hook".to_string(), "zsh".to_string()],
+                InvokedTo::Hook("zsh".to_string()),
+            ),
+            (
+                vec!["def".to_string(), "export".to_string()],
+                InvokedTo::Export {
+                    file: None,
+                    filter: None,
+                    only: None,
+                    include_private: false,
+                },
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "export".to_string(),
+                    "defs.toml".to_string(),
+                ],
+                InvokedTo::Export {
+                    file: Some("defs.toml".to_string()),
+                    filter: None,
+                    only: None,
+                    include_private: false,
+                },
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "apply".to_string(),
+                    "layout.def".to_string(),
+                ],
+                InvokedTo::Apply {
+                    file: "layout.def".to_string(),
+                    dry_run: false,
+                },
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "apply".to_string(),
+                    "--dry-run".to_string(),
+                    "layout.def".to_string(),
+                ],
+                InvokedTo::Apply {
+                    file: "layout.def".to_string(),
+                    dry_run: true,
+                },
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "sync-readmes".to_string(),
+                    ".".to_string(),
+                ],
+                InvokedTo::SyncReadmes {
+                    root: ".".to_string(),
+                    target: Target::Dirdesc,
+                    dry_run: false,
+                },
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "sync-readmes".to_string(),
+                    "--readme".to_string(),
+                    "--dry-run".to_string(),
+                    "~/work".to_string(),
+                ],
+                InvokedTo::SyncReadmes {
+                    root: "~/work".to_string(),
+                    target: Target::Readme,
+                    dry_run: true,
+                },
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "sync-readmes".to_string(),
+                    "--prefix".to_string(),
+                    ".".to_string(),
+                ],
+                InvokedTo::Unknown,
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "apply".to_string(),
+                    "a.def".to_string(),
+                    "b.def".to_string(),
+                ],
+                InvokedTo::Unknown,
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "import".to_string(),
+                    "defs.json".to_string(),
+                ],
+                InvokedTo::Import {
+                    file: "defs.json".to_string(),
+                    strategy: ImportStrategy::KeepExisting,
+                    only: None,
+                },
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "import".to_string(),
+                    "--interactive".to_string(),
+                    "defs.json".to_string(),
+                ],
+                InvokedTo::Import {
+                    file: "defs.json".to_string(),
+                    strategy: ImportStrategy::Interactive,
+                    only: None,
+                },
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "import".to_string(),
+                    "defs.json".to_string(),
+                    "--overwrite".to_string(),
+                    "--keep-existing".to_string(),
+                ],
+                InvokedTo::Unknown,
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "import".to_string(),
+                    "--only".to_string(),
+                    "patterns".to_string(),
+                    "team.json".to_string(),
+                    "--overwrite".to_string(),
+                ],
+                InvokedTo::Import {
+                    file: "team.json".to_string(),
+                    strategy: ImportStrategy::Overwrite,
+                    only: Some(Subset::Patterns),
+                },
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "export".to_string(),
+                    "--only".to_string(),
+                    "descriptions".to_string(),
+                ],
+                InvokedTo::Export {
+                    file: None,
+                    filter: None,
+                    only: Some(Subset::Descriptions),
+                    include_private: false,
+                },
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "export".to_string(),
+                    "--only".to_string(),
+                    "tags".to_string(),
+                ],
+                InvokedTo::Unknown,
+            ),
             (
                 vec![
                     "def".to_string(),
@@ -65,16 +1257,1035 @@ mod tests {
                     "/path".to_string(),
                     "description".to_string(),
                 ],
-                InvokedTo::AddDescription("/path".to_string(), "description".to_string()),
+                InvokedTo::AddDescription {
+                    path: "/path".to_string(),
+                    description: "description".to_string(),
+                    tags: vec![],
+                    private: false,
+                    mode: AddMode::Add,
+                    from_command: false,
+                },
             ),
             (
                 vec![
                     "def".to_string(),
-                    "pattern".to_string(),
+                    "add".to_string(),
+                    "--tag".to_string(),
+                    "work".to_string(),
+                    "/path".to_string(),
+                    "description".to_string(),
+                    "--tag".to_string(),
+                    "archived".to_string(),
+                ],
+                InvokedTo::AddDescription {
+                    path: "/path".to_string(),
+                    description: "description".to_string(),
+                    tags: vec!["work".to_string(), "archived".to_string()],
+                    private: false,
+                    mode: AddMode::Add,
+                    from_command: false,
+                },
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "add".to_string(),
+                    "/path".to_string(),
+                    "description".to_string(),
+                    "--tag".to_string(),
+                ],
+                InvokedTo::Unknown,
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "add".to_string(),
+                    "--local".to_string(),
+                    "/path".to_string(),
+                    "description".to_string(),
+                    "--tag".to_string(),
+                    "work".to_string(),
+                ],
+                InvokedTo::Unknown,
+            ),
+            (
+                vec!["def".to_string(), "tagged".to_string(), "work".to_string()],
+                InvokedTo::ListTagged("work".to_string()),
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "pack".to_string(),
+                    "install".to_string(),
+                    "https://example.com/server.json".to_string(),
+                ],
+                InvokedTo::InstallPack {
+                    source: "https://example.com/server.json".to_string(),
+                    prefix: None,
+                    key: None,
+                    yes: false,
+                },
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "pack".to_string(),
+                    "install".to_string(),
+                    "project.toml".to_string(),
+                    "--prefix".to_string(),
+                    "~/analysis".to_string(),
+                ],
+                InvokedTo::InstallPack {
+                    source: "project.toml".to_string(),
+                    prefix: Some("~/analysis".to_string()),
+                    key: None,
+                    yes: false,
+                },
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "pack".to_string(),
+                    "install".to_string(),
+                    "--yes".to_string(),
+                    "https://example.com/server.json".to_string(),
+                    "--key".to_string(),
+                    "minisign.pub".to_string(),
+                ],
+                InvokedTo::InstallPack {
+                    source: "https://example.com/server.json".to_string(),
+                    prefix: None,
+                    key: Some("minisign.pub".to_string()),
+                    yes: true,
+                },
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "pack".to_string(),
+                    "install".to_string(),
+                    "--key".to_string(),
+                ],
+                InvokedTo::Unknown,
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "pack".to_string(),
+                    "remove".to_string(),
+                    "server".to_string(),
+                ],
+                InvokedTo::RemovePack("server".to_string()),
+            ),
+            (
+                vec!["def".to_string(), "pack".to_string(), "list".to_string()],
+                InvokedTo::ListPacks,
+            ),
+            (
+                vec!["def".to_string(), "profile".to_string(), "list".to_string()],
+                InvokedTo::ListProfiles,
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "profile".to_string(),
+                    "create".to_string(),
+                    "work".to_string(),
+                ],
+                InvokedTo::CreateProfile("work".to_string()),
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "profile".to_string(),
+                    "delete".to_string(),
+                    "work".to_string(),
+                ],
+                InvokedTo::DeleteProfile("work".to_string()),
+            ),
+            (
+                vec!["def".to_string(), "profile".to_string(), "work".to_string()],
+                InvokedTo::Unknown,
+            ),
+            (
+                vec!["def".to_string(), "sync".to_string(), "pull".to_string()],
+                InvokedTo::SyncPull,
+            ),
+            (
+                vec!["def".to_string(), "sync".to_string(), "push".to_string()],
+                InvokedTo::SyncPush,
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "sync".to_string(),
+                    "push".to_string(),
+                    "--force".to_string(),
+                ],
+                InvokedTo::Unknown,
+            ),
+            (
+                vec!["def".to_string(), "stale".to_string()],
+                InvokedTo::Stale(0),
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "stale".to_string(),
+                    "--days".to_string(),
+                    "30".to_string(),
+                ],
+                InvokedTo::Stale(30),
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "stale".to_string(),
+                    "--days".to_string(),
+                    "-1".to_string(),
+                ],
+                InvokedTo::Unknown,
+            ),
+            (
+                vec!["def".to_string(), "check".to_string()],
+                InvokedTo::Check(false),
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "bundle".to_string(),
+                    "--output".to_string(),
+                    "def-bundle.tar".to_string(),
+                ],
+                InvokedTo::Bundle("def-bundle.tar".to_string()),
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "bundle".to_string(),
+                    "--apply".to_string(),
+                    "def-bundle.tar".to_string(),
+                ],
+                InvokedTo::ApplyBundle("def-bundle.tar".to_string()),
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "fixtures".to_string(),
+                    "generate".to_string(),
+                    "spec.yaml".to_string(),
+                ],
+                InvokedTo::GenerateFixture("spec.yaml".to_string()),
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "check".to_string(),
+                    "--prune".to_string(),
+                ],
+                InvokedTo::Check(true),
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "events".to_string(),
+                    "--follow".to_string(),
+                ],
+                InvokedTo::FollowEvents,
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "completions".to_string(),
+                    "fish".to_string(),
+                ],
+                InvokedTo::Completions("fish".to_string()),
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "from-inode".to_string(),
+                    "8,1:1234".to_string(),
+                ],
+                InvokedTo::DescribeInode("8,1:1234".to_string()),
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "from-fd".to_string(),
+                    "1234:3".to_string(),
+                ],
+                InvokedTo::DescribeFd("1234:3".to_string()),
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "add".to_string(),
+                    "--private".to_string(),
+                    "/path".to_string(),
+                    "description".to_string(),
+                ],
+                InvokedTo::AddDescription {
+                    path: "/path".to_string(),
+                    description: "description".to_string(),
+                    tags: vec![],
+                    private: true,
+                    mode: AddMode::Add,
+                    from_command: false,
+                },
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "add".to_string(),
+                    "/path".to_string(),
+                    "description".to_string(),
+                    "--append".to_string(),
+                ],
+                InvokedTo::AddDescription {
+                    path: "/path".to_string(),
+                    description: "description".to_string(),
+                    tags: vec![],
+                    private: false,
+                    mode: AddMode::Append,
+                    from_command: false,
+                },
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "add".to_string(),
+                    "--force".to_string(),
+                    "/path".to_string(),
+                    "description".to_string(),
+                ],
+                InvokedTo::AddDescription {
+                    path: "/path".to_string(),
+                    description: "description".to_string(),
+                    tags: vec![],
+                    private: false,
+                    mode: AddMode::Force,
+                    from_command: false,
+                },
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "add".to_string(),
+                    "/repo".to_string(),
+                    "--from-command".to_string(),
+                    "git log -1 --format=%s".to_string(),
+                    "--force".to_string(),
+                ],
+                InvokedTo::AddDescription {
+                    path: "/repo".to_string(),
+                    description: "git log -1 --format=%s".to_string(),
+                    tags: vec![],
+                    private: false,
+                    mode: AddMode::Force,
+                    from_command: true,
+                },
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "add".to_string(),
+                    "/repo".to_string(),
+                    "description".to_string(),
+                    "--from-command".to_string(),
+                    "pwd".to_string(),
+                ],
+                InvokedTo::Unknown,
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "add".to_string(),
+                    "/repo".to_string(),
+                    "--append".to_string(),
+                    "--from-command".to_string(),
+                    "pwd".to_string(),
+                ],
+                InvokedTo::Unknown,
+            ),
+            (
+                vec!["def".to_string(), "--refresh".to_string()],
+                InvokedTo::Refresh(None),
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "--refresh".to_string(),
+                    "/repo".to_string(),
+                ],
+                InvokedTo::Refresh(Some("/repo".to_string())),
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "add".to_string(),
+                    "--prepend".to_string(),
+                    "--append".to_string(),
+                    "/path".to_string(),
+                    "description".to_string(),
+                ],
+                InvokedTo::Unknown,
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "add".to_string(),
+                    "--local".to_string(),
+                    "--prepend".to_string(),
+                    "/path".to_string(),
+                    "description".to_string(),
+                ],
+                InvokedTo::Unknown,
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "add".to_string(),
+                    "--local".to_string(),
+                    "/path".to_string(),
+                    "description".to_string(),
+                    "--private".to_string(),
+                ],
+                InvokedTo::Unknown,
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "private".to_string(),
+                    "/path".to_string(),
+                ],
+                InvokedTo::SetPrivate("/path".to_string(), true),
+            ),
+            (
+                vec!["def".to_string(), "public".to_string(), "/path".to_string()],
+                InvokedTo::SetPrivate("/path".to_string(), false),
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "export".to_string(),
+                    "--include-private".to_string(),
+                ],
+                InvokedTo::Export {
+                    file: None,
+                    filter: None,
+                    only: None,
+                    include_private: true,
+                },
+            ),
+            (
+                vec!["def".to_string(), "__complete".to_string()],
+                InvokedTo::Complete(String::new()),
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "__complete".to_string(),
+                    "/sr".to_string(),
+                ],
+                InvokedTo::Complete("/sr".to_string()),
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "pack".to_string(),
+                    "install".to_string(),
+                    "a.json".to_string(),
+                    "b.json".to_string(),
+                ],
+                InvokedTo::Unknown,
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "pattern".to_string(),
+                    "/path".to_string(),
+                    "description".to_string(),
+                ],
+                InvokedTo::AddPattern("/path".to_string(), "description".to_string(), None),
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "glob".to_string(),
+                    "*.log".to_string(),
+                    "description".to_string(),
+                ],
+                InvokedTo::AddGlob("*.log".to_string(), "description".to_string(), None),
+            ),
+            (
+                vec!["def".to_string(), "edit".to_string(), "/path".to_string()],
+                InvokedTo::Edit("/path".to_string()),
+            ),
+            (
+                vec!["def".to_string(), "edit".to_string(), "--all".to_string()],
+                InvokedTo::EditAll,
+            ),
+            (
+                vec!["def".to_string(), "doctor".to_string()],
+                InvokedTo::Doctor,
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "doctor".to_string(),
+                    "--convert-paths".to_string(),
+                ],
+                InvokedTo::ConvertPaths,
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "doctor".to_string(),
+                    "--fix-perms".to_string(),
+                ],
+                InvokedTo::FixPermissions,
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "doctor".to_string(),
+                    "--unknown".to_string(),
+                ],
+                InvokedTo::Unknown,
+            ),
+            (
+                vec!["def".to_string(), "remove".to_string(), "/path".to_string()],
+                InvokedTo::RemoveDescription("/path".to_string()),
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "remove".to_string(),
+                    "--pattern".to_string(),
+                    "/path".to_string(),
+                ],
+                InvokedTo::RemovePattern("/path".to_string()),
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "remove".to_string(),
+                    "--unknown".to_string(),
+                    "/path".to_string(),
+                ],
+                InvokedTo::Unknown,
+            ),
+            (
+                vec!["def".to_string(), "trash".to_string(), "list".to_string()],
+                InvokedTo::ListTrash,
+            ),
+            (
+                vec!["def".to_string(), "trash".to_string(), "empty".to_string()],
+                InvokedTo::EmptyTrash,
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "trash".to_string(),
+                    "restore".to_string(),
+                    "/path".to_string(),
+                ],
+                InvokedTo::RestoreFromTrash("/path".to_string()),
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "trash".to_string(),
+                    "unknown".to_string(),
+                ],
+                InvokedTo::Unknown,
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "mv".to_string(),
+                    "--prefix".to_string(),
+                    "/old".to_string(),
+                    "/new".to_string(),
+                ],
+                InvokedTo::Move {
+                    old: "/old".to_string(),
+                    new: "/new".to_string(),
+                    recursive: true,
+                    dry_run: false,
+                },
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "mv".to_string(),
+                    "/old".to_string(),
+                    "/new".to_string(),
+                    "--dry-run".to_string(),
+                    "--prefix".to_string(),
+                ],
+                InvokedTo::Move {
+                    old: "/old".to_string(),
+                    new: "/new".to_string(),
+                    recursive: true,
+                    dry_run: true,
+                },
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "mv".to_string(),
+                    "/old".to_string(),
+                    "/new".to_string(),
+                    "--recursive".to_string(),
+                ],
+                InvokedTo::Move {
+                    old: "/old".to_string(),
+                    new: "/new".to_string(),
+                    recursive: true,
+                    dry_run: false,
+                },
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "mv".to_string(),
+                    "/old".to_string(),
+                    "/new".to_string(),
+                ],
+                InvokedTo::Move {
+                    old: "/old".to_string(),
+                    new: "/new".to_string(),
+                    recursive: false,
+                    dry_run: false,
+                },
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "mv".to_string(),
+                    "--prefix".to_string(),
+                    "/old".to_string(),
+                ],
+                InvokedTo::Unknown,
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "mv".to_string(),
+                    "--prefix".to_string(),
+                    "--unknown".to_string(),
+                    "/old".to_string(),
+                    "/new".to_string(),
+                ],
+                InvokedTo::Unknown,
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "annotate-archive".to_string(),
+                    "a.tar".to_string(),
+                ],
+                InvokedTo::AnnotateArchive {
+                    archive: "a.tar".to_string(),
+                    root: None,
+                },
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "annotate-archive".to_string(),
+                    "--root".to_string(),
+                    "/root".to_string(),
+                    "a.tar".to_string(),
+                ],
+                InvokedTo::AnnotateArchive {
+                    archive: "a.tar".to_string(),
+                    root: Some("/root".to_string()),
+                },
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "annotate-archive".to_string(),
+                    "a.tar".to_string(),
+                    "--root".to_string(),
+                    "/root".to_string(),
+                ],
+                InvokedTo::AnnotateArchive {
+                    archive: "a.tar".to_string(),
+                    root: Some("/root".to_string()),
+                },
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "annotate-archive".to_string(),
+                    "a.tar".to_string(),
+                    "b.tar".to_string(),
+                ],
+                InvokedTo::Unknown,
+            ),
+            (
+                vec!["def".to_string(), "list".to_string()],
+                InvokedTo::List {
+                    prefix: None,
+                    depth: None,
+                    filter: None,
+                },
+            ),
+            (
+                vec!["def".to_string(), "list".to_string(), "/path".to_string()],
+                InvokedTo::List {
+                    prefix: Some("/path".to_string()),
+                    depth: None,
+                    filter: None,
+                },
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "list".to_string(),
+                    "--depth".to_string(),
+                    "2".to_string(),
+                    "/path".to_string(),
+                ],
+                InvokedTo::List {
+                    prefix: Some("/path".to_string()),
+                    depth: Some(2),
+                    filter: None,
+                },
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "list".to_string(),
+                    "--depth".to_string(),
+                    "0".to_string(),
+                ],
+                InvokedTo::Unknown,
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "pattern".to_string(),
+                    "--recursive".to_string(),
+                    "/path".to_string(),
+                    "description".to_string(),
+                ],
+                InvokedTo::AddRecursivePattern(
+                    "/path".to_string(),
+                    "description".to_string(),
+                    None,
+                ),
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "pattern".to_string(),
+                    "/path".to_string(),
+                    "--priority".to_string(),
+                    "-5".to_string(),
+                    "--recursive".to_string(),
+                    "description".to_string(),
+                ],
+                InvokedTo::AddRecursivePattern(
+                    "/path".to_string(),
+                    "description".to_string(),
+                    Some(-5),
+                ),
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "glob".to_string(),
+                    "--priority".to_string(),
+                    "10".to_string(),
+                    "*.log".to_string(),
+                    "description".to_string(),
+                ],
+                InvokedTo::AddGlob("*.log".to_string(), "description".to_string(), Some(10)),
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "pattern".to_string(),
+                    "--priority".to_string(),
+                    "high".to_string(),
+                    "/path".to_string(),
+                    "description".to_string(),
+                ],
+                InvokedTo::Unknown,
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "glob".to_string(),
+                    "--recursive".to_string(),
+                    "*.log".to_string(),
+                    "description".to_string(),
+                ],
+                InvokedTo::Unknown,
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "pattern".to_string(),
+                    "/path".to_string(),
+                    "--files-only".to_string(),
+                    "description".to_string(),
+                ],
+                InvokedTo::AddTypedPattern(
+                    "/path".to_string(),
+                    "description".to_string(),
+                    FileKind::File,
+                    None,
+                ),
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "pattern".to_string(),
+                    "--dirs-only".to_string(),
+                    "--priority".to_string(),
+                    "2".to_string(),
+                    "/path".to_string(),
+                    "description".to_string(),
+                ],
+                InvokedTo::AddTypedPattern(
+                    "/path".to_string(),
+                    "description".to_string(),
+                    FileKind::Directory,
+                    Some(2),
+                ),
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "pattern".to_string(),
+                    "--files-only".to_string(),
+                    "--dirs-only".to_string(),
+                    "/path".to_string(),
+                    "description".to_string(),
+                ],
+                InvokedTo::Unknown,
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "pattern".to_string(),
+                    "--recursive".to_string(),
+                    "--symlinks-only".to_string(),
+                    "/path".to_string(),
+                    "description".to_string(),
+                ],
+                InvokedTo::Unknown,
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "explain".to_string(),
+                    "/path".to_string(),
+                ],
+                InvokedTo::Explain("/path".to_string()),
+            ),
+            (
+                vec!["def".to_string(), "info".to_string(), "/path".to_string()],
+                InvokedTo::Info("/path".to_string()),
+            ),
+            (
+                vec!["def".to_string(), "drift".to_string(), "/root".to_string()],
+                InvokedTo::Drift("/root".to_string()),
+            ),
+            (
+                vec!["def".to_string(), "stats".to_string()],
+                InvokedTo::Stats(None),
+            ),
+            (vec!["def".to_string(), "undo".to_string()], InvokedTo::Undo),
+            (
+                vec![
+                    "def".to_string(),
+                    "history".to_string(),
+                    "/path".to_string(),
+                ],
+                InvokedTo::History("/path".to_string()),
+            ),
+            (
+                vec!["def".to_string(), "stats".to_string(), "dir".to_string()],
+                InvokedTo::Stats(Some("dir".to_string())),
+            ),
+            (
+                vec!["def".to_string(), "ls".to_string()],
+                InvokedTo::Ls(None),
+            ),
+            (
+                vec!["def".to_string(), "ls".to_string(), "dir".to_string()],
+                InvokedTo::Ls(Some("dir".to_string())),
+            ),
+            (
+                vec!["def".to_string(), "maintain".to_string()],
+                InvokedTo::Maintain(None),
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "maintain".to_string(),
+                    "--root".to_string(),
+                    "/root".to_string(),
+                ],
+                InvokedTo::Maintain(Some("/root".to_string())),
+            ),
+            (vec!["def".to_string(), "tour".to_string()], InvokedTo::Tour),
+            (
+                vec!["def".to_string(), "daemon".to_string()],
+                InvokedTo::Daemon,
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "search".to_string(),
+                    "nginx".to_string(),
+                    "config".to_string(),
+                ],
+                InvokedTo::Search {
+                    query: "nginx config".to_string(),
+                    options: SearchOptions::default(),
+                    filter: None,
+                },
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "search".to_string(),
+                    "--typos".to_string(),
+                    "2".to_string(),
+                    "bakcup".to_string(),
+                    "--stem".to_string(),
+                ],
+                InvokedTo::Search {
+                    query: "bakcup".to_string(),
+                    options: SearchOptions {
+                        subsequences: false,
+                        stem: true,
+                        max_typos: 2,
+                    },
+                    filter: None,
+                },
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "search".to_string(),
+                    "--typos".to_string(),
+                    "bakcup".to_string(),
+                ],
+                InvokedTo::Unknown,
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "search".to_string(),
+                    "--save".to_string(),
+                    "infra".to_string(),
+                    "nginx config".to_string(),
+                ],
+                InvokedTo::SaveSearch {
+                    name: "infra".to_string(),
+                    query: "nginx config".to_string(),
+                },
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "search".to_string(),
+                    "--save".to_string(),
+                    "infra".to_string(),
+                    "--fuzzy".to_string(),
+                    "nginx".to_string(),
+                ],
+                InvokedTo::Unknown,
+            ),
+            (
+                vec!["def".to_string(), "lists".to_string()],
+                InvokedTo::ListSavedSearches,
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "search".to_string(),
+                    "--where".to_string(),
+                    "kind:glob".to_string(),
+                ],
+                InvokedTo::Search {
+                    query: String::new(),
+                    options: SearchOptions::default(),
+                    filter: Some("kind:glob".to_string()),
+                },
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "list".to_string(),
+                    "/path".to_string(),
+                    "--where".to_string(),
+                    "NOT kind:pattern".to_string(),
+                ],
+                InvokedTo::List {
+                    prefix: Some("/path".to_string()),
+                    depth: None,
+                    filter: Some("NOT kind:pattern".to_string()),
+                },
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "export".to_string(),
+                    "--where".to_string(),
+                    "path:/srv".to_string(),
+                    "srv.json".to_string(),
+                ],
+                InvokedTo::Export {
+                    file: Some("srv.json".to_string()),
+                    filter: Some("path:/srv".to_string()),
+                    only: None,
+                    include_private: false,
+                },
+            ),
+            (
+                vec!["def".to_string(), "list".to_string(), "@infra".to_string()],
+                InvokedTo::RunSavedSearch("infra".to_string()),
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "search".to_string(),
+                    "--fuzzy".to_string(),
+                    "dwnld".to_string(),
+                ],
+                InvokedTo::Search {
+                    query: "dwnld".to_string(),
+                    options: SearchOptions::fuzzy(),
+                    filter: None,
+                },
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "search".to_string(),
+                    "--fuzzy".to_string(),
+                ],
+                InvokedTo::Unknown,
+            ),
+            (
+                vec![
+                    "def".to_string(),
+                    "add".to_string(),
+                    "--local".to_string(),
                     "/path".to_string(),
                     "description".to_string(),
                 ],
-                InvokedTo::AddPattern("/path".to_string(), "description".to_string()),
+                InvokedTo::AddLocalDescription("/path".to_string(), "description".to_string()),
             ),
             (vec![], InvokedTo::Unknown),
             (
@@ -0,0 +1,666 @@
+//! Locations of def's files, and loading and saving of the config.
+//!
+//! def keeps its files in `.config/def` under the user's home directory (as
+//! found by the `dirs` crate on every platform), or in `$XDG_CONFIG_HOME/def`:
+//! settings in `settings.json`, and descriptions in `config.json`, unless
+//! another config file is chosen (see `resolve`).
+//!
+//! Profiles keep separate sets of descriptions, such as one for work and one
+//! for personal machines, each in a JSON file named after it in the same
+//! directory (such as `work.json` for "work"), see `use_profile`.
+//!
+//! Changes to the config should be made with `update`, which holds the
+//! config's lock while loading, changing, and saving it, so that concurrent
+//! processes don't lose each other's changes. Changes are journaled beside
+//! the config file (see `def::journal`), and the last one can be undone
+//! using `undo`.
+//!
+//! With "sync" feature, the config can be synced with a remote copy shared
+//! by several machines using `sync`, see `def::sync`.
+//!
+//! Other tools can read and write the same config as the def command using
+//! `default_path`, or `Describer::load_default` and
+//! `Describer::save_default`, and the config of a profile using
+//! `Describer::load_profile`.
+
+pub mod resolve;
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+#[cfg(feature = "sync")]
+use crate::format::Json;
+use crate::journal::{self, Mutation};
+use crate::settings::Settings;
+use crate::store::{self, Backend, Lock, Store};
+#[cfg(feature = "sync")]
+use crate::sync::{self, Remote, SyncReport};
+use crate::{trash, Describer, Error};
+
+/// Name of the default config file, in config_dir.
+const CONFIG_FILE: &str = "config.json";
+
+/// Name of the settings file, in config_dir.
+const SETTINGS_FILE: &str = "settings.json";
+
+/// Name of the directory installed pattern packs are kept in, in config_dir.
+const PACKS_DIR: &str = "packs";
+
+/// Name of the file the search index is cached in, in config_dir.
+const SEARCH_INDEX_FILE: &str = "search-index.json";
+
+/// Suffix of the name of a config file's journal, see `journal_file`.
+const JOURNAL_SUFFIX: &str = ".history";
+
+/// Suffix of the name of the copy of a config as last synced, see
+/// `sync_base_file`.
+#[cfg(feature = "sync")]
+const SYNC_BASE_SUFFIX: &str = ".sync-base";
+
+/// Operation journaled by `sync`.
+#[cfg(feature = "sync")]
+const SYNC_OP: &str = "sync";
+
+/// Operation journaled by `update`.
+const UPDATE_OP: &str = "update";
+
+/// Operation journaled by `Describer::save_default` and
+/// `Describer::save_profile`.
+pub(crate) const SAVE_OP: &str = "save";
+
+/// Extension of config files of profiles.
+const PROFILE_EXTENSION: &str = "json";
+
+/// Names that can't be used by profiles, as files with these names in
+/// config_dir are def's other files.
+const RESERVED_PROFILES: [&str; 3] = ["config", "settings", "search-index"];
+
+/// Return the user's home directory: $HOME on Unix, or the user's profile
+/// directory on Windows.
+pub fn home_dir() -> Result<PathBuf, Error> {
+    dirs::home_dir().ok_or(Error::NoHome)
+}
+
+/// Return the directory containing def's files, see `resolve`.
+pub fn config_dir() -> Result<PathBuf, Error> {
+    resolve::config_dir(env::var_os(resolve::XDG_CONFIG_HOME_VAR), home_dir)
+}
+
+/// Return path of the settings file.
+pub fn settings_file() -> Result<PathBuf, Error> {
+    Ok(config_dir()?.join(SETTINGS_FILE))
+}
+
+/// Return the directory copies of installed pattern packs are kept in (see
+/// `def::pack`), one file for each pack, named after it.
+pub fn packs_dir() -> Result<PathBuf, Error> {
+    Ok(config_dir()?.join(PACKS_DIR))
+}
+
+/// Return path of the file the search index is cached in (see
+/// `index::cached`).
+pub fn search_index_file() -> Result<PathBuf, Error> {
+    Ok(config_dir()?.join(SEARCH_INDEX_FILE))
+}
+
+/// Load settings from the settings file, or return default settings if it
+/// doesn't exist.
+pub fn load_settings() -> Result<Settings, Error> {
+    Settings::load(settings_file()?)
+}
+
+/// Save query as the search named name in the settings file, see
+/// `settings::save_search`.
+pub fn save_search(name: &str, query: &str) -> Result<(), Error> {
+    crate::settings::save_search(settings_file()?, name, query)
+}
+
+/// Return path of the config file, as set in settings, or config.json in
+/// config_dir by default.
+pub fn config_file(settings: &Settings) -> Result<PathBuf, Error> {
+    match &settings.config_file {
+        Some(file) => Ok(PathBuf::from(file)),
+        None => Ok(config_dir()?.join(CONFIG_FILE)),
+    }
+}
+
+/// Return path of the journal of the config file (see `def::journal`), named
+/// after it, such as `config.json.history`.
+pub fn journal_file(settings: &Settings) -> Result<PathBuf, Error> {
+    let mut file = config_file(settings)?.into_os_string();
+    file.push(JOURNAL_SUFFIX);
+    Ok(PathBuf::from(file))
+}
+
+/// Return path of the remote copy of the config as it was when last synced
+/// (see `def::sync`), named after the config file, such as
+/// `config.json.sync-base`.
+#[cfg(feature = "sync")]
+pub fn sync_base_file(settings: &Settings) -> Result<PathBuf, Error> {
+    let mut file = config_file(settings)?.into_os_string();
+    file.push(SYNC_BASE_SUFFIX);
+    Ok(PathBuf::from(file))
+}
+
+/// Return settings as used by the def command when no flags are given: the
+/// user's settings, with the config file chosen by environment variables if
+/// any is set (see `resolve`).
+pub fn default_settings() -> Result<Settings, Error> {
+    let settings = load_settings()?;
+    match resolve::choose_from_env(None, None) {
+        Some(choice) => resolve::apply(&settings, &choice),
+        None => Ok(settings),
+    }
+}
+
+/// Return path of the config file used by the def command, as set in the
+/// user's settings or the environment. Tools integrating with def can use it
+/// to share def's descriptions, see also `Describer::load_default`.
+pub fn default_path() -> Result<PathBuf, Error> {
+    config_file(&default_settings()?)
+}
+
+/// Return the store of the config file, using the backend set in settings,
+/// or the one matching the file's extension.
+pub fn open_store(settings: &Settings) -> Result<Box<dyn Store>, Error> {
+    Ok(store::open(
+        config_file(settings)?,
+        Some(store_backend(settings)?),
+    )?)
+}
+
+/// Return the backend the config file is stored with: the store set in
+/// settings, or the one matching the config file's extension (see
+/// `Backend::from_path`).
+pub fn store_backend(settings: &Settings) -> Result<Backend, Error> {
+    match &settings.store {
+        Some(name) => Backend::from_name(name).ok_or_else(|| Error::UnknownStore(name.clone())),
+        None => Ok(Backend::from_path(config_file(settings)?)),
+    }
+}
+
+/// Return path of the config file of the profile named name, see
+/// `use_profile`. An error is returned if name isn't a valid profile name:
+/// one made of ASCII letters, digits, "-", and "_", other than "config",
+/// "settings", and "search-index".
+pub fn profile_file(name: &str) -> Result<PathBuf, Error> {
+    profile_file_in(&config_dir()?, name)
+}
+
+/// Like `profile_file`, but for profiles kept in dir.
+fn profile_file_in(dir: &Path, name: &str) -> Result<PathBuf, Error> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        && !RESERVED_PROFILES.contains(&name);
+    if !valid {
+        return Err(Error::InvalidProfile(name.to_string()));
+    }
+    Ok(dir.join(format!("{}.{}", name, PROFILE_EXTENSION)))
+}
+
+/// Return settings that use the config file of the profile named name
+/// instead of the one set in settings, so that descriptions of different
+/// profiles never mix. Other settings are kept.
+pub fn use_profile(settings: &Settings, name: &str) -> Result<Settings, Error> {
+    Ok(Settings {
+        config_file: Some(profile_file(name)?.to_string_lossy().to_string()),
+        store: None,
+        ..settings.clone()
+    })
+}
+
+/// Return names of existing profiles, sorted.
+pub fn profiles() -> Result<Vec<String>, Error> {
+    profiles_in(&config_dir()?)
+}
+
+/// Like `profiles`, but for profiles kept in dir.
+fn profiles_in(dir: &Path) -> Result<Vec<String>, Error> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut names = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some(PROFILE_EXTENSION) {
+            continue;
+        }
+        let name = path.file_stem().unwrap_or_default().to_string_lossy();
+        if profile_file_in(dir, &name).is_ok() && path.is_file() {
+            names.push(name.to_string());
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+/// Create the profile named name, with an empty config file. An error is
+/// returned if it already exists.
+pub fn create_profile(name: &str) -> Result<(), Error> {
+    create_profile_in(&config_dir()?, name)
+}
+
+/// Like `create_profile`, but for profiles kept in dir.
+fn create_profile_in(dir: &Path, name: &str) -> Result<(), Error> {
+    let file = profile_file_in(dir, name)?;
+    if file.exists() {
+        return Err(Error::ProfileExists(name.to_string()));
+    }
+    fs::create_dir_all(dir)?;
+    Ok(store::open(file, None)?.save(&Describer::new())?)
+}
+
+/// Delete the profile named name, along with its descriptions. An error is
+/// returned if it doesn't exist.
+pub fn delete_profile(name: &str) -> Result<(), Error> {
+    delete_profile_in(&config_dir()?, name)
+}
+
+/// Like `delete_profile`, but for profiles kept in dir.
+fn delete_profile_in(dir: &Path, name: &str) -> Result<(), Error> {
+    let file = profile_file_in(dir, name)?;
+    if !file.is_file() {
+        return Err(Error::UnknownProfile(name.to_string()));
+    }
+    fs::remove_file(&file)?;
+    // The lock is only left behind if def was killed while holding it.
+    let _ = fs::remove_file(dir.join(format!("{}.{}.lock", name, PROFILE_EXTENSION)));
+    Ok(())
+}
+
+/// Return true if the config file can be read by users other than its
+/// owner, which matters if it holds sensitive descriptions (see
+/// `is_sensitive`). Always false if the file doesn't exist, and on platforms
+/// other than Unix.
+pub fn is_exposed(settings: &Settings) -> Result<bool, Error> {
+    let file = config_file(settings)?;
+    if !file.exists() {
+        return Ok(false);
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        Ok(fs::metadata(&file)?.permissions().mode() & 0o044 != 0)
+    }
+    #[cfg(not(unix))]
+    Ok(false)
+}
+
+/// Make the config file accessible only by its owner, by removing the
+/// permissions of its group and other users. Does nothing on platforms other
+/// than Unix.
+pub fn restrict_permissions(settings: &Settings) -> Result<(), Error> {
+    let file = config_file(settings)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mut permissions = fs::metadata(&file)?.permissions();
+        permissions.set_mode(permissions.mode() & 0o700);
+        fs::set_permissions(&file, permissions)?;
+    }
+    #[cfg(not(unix))]
+    let _ = file;
+    Ok(())
+}
+
+/// Return true if describer holds descriptions other users shouldn't read:
+/// private descriptions, or (if def is built with the "redact" feature)
+/// entries that look like they hold secrets, see `redact::find_secrets`.
+pub fn is_sensitive(describer: &Describer) -> bool {
+    #[cfg(feature = "redact")]
+    let secrets = || !crate::redact::find_secrets(describer).is_empty();
+    #[cfg(not(feature = "redact"))]
+    let secrets = || false;
+    describer.has_private() || secrets()
+}
+
+/// Load the describer from the config file, using the resolution order and
+/// match options of settings.
+pub fn load(settings: &Settings) -> Result<Describer, Error> {
+    let mut describer = open_store(settings)?.load()?;
+    describer.set_resolution_order(settings.resolution_order.clone());
+    describer.set_match_options(settings.matching);
+    Ok(describer)
+}
+
+/// Load the describer from the config file, with variables in its keys
+/// expanded (see `Describer::expand_keys`), ready to describe paths. Keys
+/// expanding to other keys are kept as written. The returned describer
+/// shouldn't be saved, as that would replace variables with their values.
+pub fn load_for_lookup(settings: &Settings) -> Result<Describer, Error> {
+    let mut describer = load(settings)?;
+    describer.expand_keys(|name| settings.variable(name));
+    Ok(describer)
+}
+
+/// Like `load_for_lookup`, but only load the entries needed to describe paths
+/// (see `Store::load_for`). Entries are looked up by their exact keys, so
+/// the whole config is loaded if settings match paths ignoring case or
+/// Unicode normalization.
+pub fn load_for_paths(settings: &Settings, paths: &[&str]) -> Result<Describer, Error> {
+    if !settings.matching.is_exact() {
+        return load_for_lookup(settings);
+    }
+    let mut describer = open_store(settings)?.load_for(paths)?;
+    describer.set_resolution_order(settings.resolution_order.clone());
+    describer.set_match_options(settings.matching);
+    describer.expand_keys(|name| settings.variable(name));
+    Ok(describer)
+}
+
+/// Delete expired entries from the describer's trash, as set by the trash
+/// retention of settings, and save it to the config file.
+pub fn save(settings: &Settings, describer: &mut Describer) -> Result<(), Error> {
+    describer.expire_trash(trash_retention(settings));
+    Ok(open_store(settings)?.save(describer)?)
+}
+
+/// Like `save`, but journal the entries changed since the config file was
+/// last saved as changed by op, such as "add", so that they can be undone.
+pub fn save_as(settings: &Settings, describer: &mut Describer, op: &str) -> Result<(), Error> {
+    let store = open_store(settings)?;
+    let old = if store.exists() {
+        store.load()?
+    } else {
+        Describer::new()
+    };
+    save_over(settings, &old, describer, op)
+}
+
+/// Save describer, and journal the entries changed since old, the describer
+/// last saved, as changed by op.
+fn save_over(
+    settings: &Settings,
+    old: &Describer,
+    describer: &mut Describer,
+    op: &str,
+) -> Result<(), Error> {
+    save(settings, describer)?;
+    if let Some(mutation) = Mutation::between(old, describer, op, trash::now()) {
+        journal::append(journal_file(settings)?, &mutation)?;
+    }
+    Ok(())
+}
+
+/// Undo the last journaled change of the config while holding its lock (see
+/// `Mutation::revert`), and remove it from the journal. Return the undone
+/// change, or None if there's none.
+pub fn undo(settings: &Settings) -> Result<Option<Mutation>, Error> {
+    let _lock = lock(settings)?;
+    let file = journal_file(settings)?;
+    let mutation = match journal::read(&file)?.pop() {
+        Some(mutation) => mutation,
+        None => return Ok(None),
+    };
+    let store = open_store(settings)?;
+    let mut describer = if store.exists() {
+        store.load()?
+    } else {
+        Describer::new()
+    };
+    mutation.revert(&mut describer)?;
+    save(settings, &mut describer)?;
+    journal::pop(&file)?;
+    Ok(Some(mutation))
+}
+
+/// Sync the config with the remote set in settings while holding its lock:
+/// the config and the remote copy are merged (see `sync::merge`), and the
+/// merged describer is saved, journaled so that it can be undone. If push is
+/// true, the merged describer also replaces the remote copy. Return what
+/// syncing changed.
+#[cfg(feature = "sync")]
+pub fn sync(settings: &Settings, push: bool) -> Result<SyncReport, Error> {
+    let remote = Remote::parse(settings.sync_remote.as_deref().ok_or(Error::NoRemote)?);
+    let _lock = lock(settings)?;
+    let store = open_store(settings)?;
+    let local = if store.exists() {
+        store.load()?
+    } else {
+        Describer::new()
+    };
+    let fetched = remote.fetch()?.unwrap_or_default();
+    let base_file = sync_base_file(settings)?;
+    let base = if base_file.exists() {
+        Describer::load(&base_file, &Json)?
+    } else {
+        Describer::new()
+    };
+
+    let (mut merged, report) = sync::merge(&base, &local, &fetched);
+    save_over(settings, &local, &mut merged, SYNC_OP)?;
+    if push {
+        remote.push(&merged)?;
+        merged.save(&base_file, &Json)?;
+    } else {
+        fetched.save(&base_file, &Json)?;
+    }
+    Ok(report)
+}
+
+/// Acquire the lock of the config file (see `Store::lock`), creating the
+/// directory containing it if needed. Blocks until the lock is acquired.
+pub fn lock(settings: &Settings) -> Result<Lock, Error> {
+    if let Some(dir) = config_file(settings)?.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    Ok(open_store(settings)?.lock()?)
+}
+
+/// Change the config using f while holding its lock: the describer is loaded
+/// from the config file (or empty if it doesn't exist yet), passed to f, and
+/// saved (see `save_as`). Returns what f returns.
+pub fn update<T, F>(settings: &Settings, f: F) -> Result<T, Error>
+where
+    F: FnOnce(&mut Describer) -> T,
+{
+    let _lock = lock(settings)?;
+    let store = open_store(settings)?;
+    let mut describer = if store.exists() {
+        store.load()?
+    } else {
+        Describer::new()
+    };
+    let old = describer.clone();
+    let result = f(&mut describer);
+    save_over(settings, &old, &mut describer, UPDATE_OP)?;
+    Ok(result)
+}
+
+/// Return how long trashed entries are kept for, as set in settings.
+pub fn trash_retention(settings: &Settings) -> Duration {
+    Duration::from_secs(settings.trash_retention_days * 24 * 60 * 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn save_load_test() {
+        let file = env::temp_dir().join(format!("def-config-test-{}.json", std::process::id()));
+        let settings = Settings {
+            config_file: Some(file.to_string_lossy().to_string()),
+            ..Settings::default()
+        };
+
+        let mut d = Describer::new();
+        d.add_description("/path", "A path.");
+        save(&settings, &mut d).unwrap();
+        let loaded = load(&settings).map(|d| d.describe("/path"));
+        std::fs::remove_file(&file).unwrap();
+        assert_eq!(loaded.unwrap(), Some("A path.".to_string()));
+
+        let settings = Settings {
+            store: Some("xml".to_string()),
+            ..settings
+        };
+        assert!(matches!(load(&settings), Err(Error::UnknownStore(s)) if s == "xml"));
+    }
+
+    #[test]
+    fn update_test() {
+        let dir = env::temp_dir().join(format!("def-update-test-{}", std::process::id()));
+        let settings = Settings {
+            config_file: Some(dir.join("config.json").to_string_lossy().to_string()),
+            ..Settings::default()
+        };
+
+        let handles: Vec<_> = (0..4)
+            .map(|i| {
+                let settings = settings.clone();
+                std::thread::spawn(move || {
+                    update(&settings, |d| {
+                        d.add_description(format!("/{}", i), "A path.")
+                    })
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap().unwrap();
+        }
+        let loaded = load(&settings).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        for i in 0..4 {
+            assert!(loaded.describe(format!("/{}", i)).is_some(), "{}", i);
+        }
+    }
+
+    #[test]
+    fn undo_test() {
+        let dir = env::temp_dir().join(format!("def-undo-test-{}", std::process::id()));
+        let settings = Settings {
+            config_file: Some(dir.join("config.json").to_string_lossy().to_string()),
+            ..Settings::default()
+        };
+
+        update(&settings, |d| d.add_description("/a", "First.")).unwrap();
+        update(&settings, |d| d.add_description("/a", "Second.")).unwrap();
+        update(&settings, |d| d.add_description("/a", "Second.")).unwrap();
+        let journaled = journal::read(journal_file(&settings).unwrap()).unwrap();
+        let undone = undo(&settings).unwrap().unwrap();
+        let after_one = load(&settings).unwrap().describe("/a");
+        undo(&settings).unwrap();
+        let after_two = load(&settings).unwrap().describe("/a");
+        let nothing = undo(&settings).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(journaled.len(), 2);
+        assert_eq!(undone.op, UPDATE_OP);
+        assert_eq!(undone.changes[0].after.as_deref(), Some("Second."));
+        assert_eq!(after_one, Some("First.".to_string()));
+        assert_eq!(after_two, None);
+        assert_eq!(nothing, None);
+    }
+
+    #[test]
+    fn undo_restore_test() {
+        let dir = env::temp_dir().join(format!("def-undo-restore-test-{}", std::process::id()));
+        let settings = Settings {
+            config_file: Some(dir.join("config.json").to_string_lossy().to_string()),
+            ..Settings::default()
+        };
+        fs::create_dir_all(&dir).unwrap();
+
+        let mut d = Describer::new();
+        d.add_description("/a", "A path.");
+        save_as(&settings, &mut d, "add").unwrap();
+        d.trash_description("/a");
+        save_as(&settings, &mut d, "rm").unwrap();
+        d.restore("/a").unwrap();
+        save_as(&settings, &mut d, "trash restore").unwrap();
+        let undone = undo(&settings).unwrap().unwrap();
+        let restore_undone = load(&settings).unwrap();
+        undo(&settings).unwrap();
+        let rm_undone = load(&settings).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(undone.op, "trash restore");
+        assert_eq!(restore_undone.describe("/a"), None);
+        let trashed: Vec<&str> = restore_undone
+            .trashed()
+            .iter()
+            .map(|e| e.description.as_str())
+            .collect();
+        assert_eq!(trashed, vec!["A path."]);
+        assert_eq!(rm_undone.describe("/a"), Some("A path.".to_string()));
+        assert!(rm_undone.trashed().is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn permissions_test() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let file = env::temp_dir().join(format!("def-perms-test-{}.json", std::process::id()));
+        let settings = Settings {
+            config_file: Some(file.to_string_lossy().to_string()),
+            ..Settings::default()
+        };
+        let mode = || fs::metadata(&file).unwrap().permissions().mode() & 0o777;
+
+        let mut d = Describer::new();
+        d.add_description("/path", "A path.");
+        assert!(!is_exposed(&settings).unwrap());
+        save(&settings, &mut d).unwrap();
+        fs::set_permissions(&file, fs::Permissions::from_mode(0o644)).unwrap();
+        let exposed = is_exposed(&settings).unwrap();
+        restrict_permissions(&settings).unwrap();
+        let restricted = (is_exposed(&settings).unwrap(), mode());
+        // Saving again keeps the permissions.
+        save(&settings, &mut d).unwrap();
+        let saved = mode();
+        fs::remove_file(&file).unwrap();
+
+        assert!(exposed);
+        assert_eq!(restricted, (false, 0o600));
+        assert_eq!(saved, 0o600);
+
+        assert!(!is_sensitive(&d));
+        d.set_private("/path", true);
+        assert!(is_sensitive(&d));
+    }
+
+    #[test]
+    fn profile_test() {
+        let dir = env::temp_dir().join(format!("def-profile-test-{}", std::process::id()));
+        for (name, valid) in [
+            ("work", true),
+            ("home_2", true),
+            ("", false),
+            ("../work", false),
+            ("my work", false),
+            ("config", false),
+            ("settings", false),
+        ]
+        .iter()
+        {
+            assert_eq!(profile_file_in(&dir, name).is_ok(), *valid, "{}", name);
+        }
+
+        assert_eq!(profiles_in(&dir).unwrap(), Vec::<String>::new());
+        create_profile_in(&dir, "work").unwrap();
+        create_profile_in(&dir, "home").unwrap();
+        fs::write(dir.join("settings.json"), "{}").unwrap();
+        fs::write(dir.join("notes.txt"), "").unwrap();
+        let created = profiles_in(&dir).unwrap();
+        let exists = create_profile_in(&dir, "work");
+        delete_profile_in(&dir, "home").unwrap();
+        let deleted = profiles_in(&dir).unwrap();
+        let missing = delete_profile_in(&dir, "home");
+        let loaded = store::open(dir.join("work.json"), None).and_then(|s| s.load());
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(created, vec!["home", "work"]);
+        assert!(matches!(exists, Err(Error::ProfileExists(n)) if n == "work"));
+        assert_eq!(deleted, vec!["work"]);
+        assert!(matches!(missing, Err(Error::UnknownProfile(n)) if n == "home"));
+        assert!(loaded.unwrap().entries_under("").is_empty());
+    }
+}
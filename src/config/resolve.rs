@@ -0,0 +1,201 @@
+//! Resolution of the locations of def's files.
+//!
+//! def's files are kept in `$XDG_CONFIG_HOME/def` if XDG_CONFIG_HOME is set
+//! to an absolute path, and in `.config/def` under the user's home directory
+//! otherwise.
+//!
+//! The config file is chosen by, in order of precedence:
+//!
+//! 1. `--config <file>`.
+//! 2. `--profile <name>`, see `config::use_profile`.
+//! 3. `$DEF_CONFIG`, a path like --config.
+//! 4. `$DEF_PROFILE`, a name like --profile.
+//! 5. "config_file" of settings.
+//! 6. `config.json` in def's directory.
+//!
+//! Flags override environment variables, which override settings, so that
+//! scripts, CI jobs, and containers can point def at a config without
+//! touching the user's settings.
+
+use std::env;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use crate::settings::Settings;
+use crate::Error;
+
+/// Environment variable of the base directory of config files, see the XDG
+/// base directory specification.
+pub const XDG_CONFIG_HOME_VAR: &str = "XDG_CONFIG_HOME";
+
+/// Environment variable choosing the config file, like --config.
+pub const CONFIG_VAR: &str = "DEF_CONFIG";
+
+/// Environment variable choosing a profile, like --profile.
+pub const PROFILE_VAR: &str = "DEF_PROFILE";
+
+/// Name of def's directory, in the base directory of config files.
+const DIR_NAME: &str = "def";
+
+/// Choice is a config file chosen by a flag or an environment variable,
+/// rather than by settings.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Choice {
+    /// A config file, by path.
+    File(PathBuf),
+    /// The config file of a profile, by name.
+    Profile(String),
+}
+
+/// Return the config file chosen by config (given with --config), profile
+/// (given with --profile), or environment variables read using var, as
+/// described in the module's documentation. None is returned if none of
+/// them is set. Empty values are ignored.
+pub fn choose<F>(config: Option<&str>, profile: Option<&str>, var: F) -> Option<Choice>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let non_empty = |value: Option<String>| value.filter(|v| !v.is_empty());
+    let file = |path: String| Choice::File(PathBuf::from(path));
+    non_empty(config.map(str::to_string))
+        .map(file)
+        .or_else(|| non_empty(profile.map(str::to_string)).map(Choice::Profile))
+        .or_else(|| non_empty(var(CONFIG_VAR)).map(file))
+        .or_else(|| non_empty(var(PROFILE_VAR)).map(Choice::Profile))
+}
+
+/// Like `choose`, but read environment variables of the process.
+pub fn choose_from_env(config: Option<&str>, profile: Option<&str>) -> Option<Choice> {
+    choose(config, profile, |name| env::var(name).ok())
+}
+
+/// Return settings that use the chosen config file instead of the one set
+/// in settings. Relative paths are relative to the current directory. An
+/// error is returned if the name of a chosen profile is invalid.
+pub fn apply(settings: &Settings, choice: &Choice) -> Result<Settings, Error> {
+    match choice {
+        Choice::File(path) => {
+            let path = if path.is_absolute() {
+                path.clone()
+            } else {
+                env::current_dir()?.join(path)
+            };
+            Ok(Settings {
+                config_file: Some(path.to_string_lossy().to_string()),
+                store: None,
+                ..settings.clone()
+            })
+        }
+        Choice::Profile(name) => super::use_profile(settings, name),
+    }
+}
+
+/// Return the directory of def's files, given the value of XDG_CONFIG_HOME
+/// and a function returning the home directory. Relative values of
+/// XDG_CONFIG_HOME are ignored, as the specification requires.
+pub(crate) fn config_dir<F>(xdg_config_home: Option<OsString>, home: F) -> Result<PathBuf, Error>
+where
+    F: FnOnce() -> Result<PathBuf, Error>,
+{
+    let base = match xdg_config_home {
+        Some(dir) if Path::new(&dir).is_absolute() => PathBuf::from(dir),
+        _ => home()?.join(".config"),
+    };
+    Ok(base.join(DIR_NAME))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn choose_test() {
+        let file = |path: &str| Some(Choice::File(PathBuf::from(path)));
+        let profile = |name: &str| Some(Choice::Profile(name.to_string()));
+        let all_vars = [(CONFIG_VAR, "/env.json"), (PROFILE_VAR, "env")];
+        for (config, flag_profile, vars, choice) in [
+            (None, None, &[][..], None),
+            (
+                Some("/flag.json"),
+                Some("flag"),
+                &all_vars[..],
+                file("/flag.json"),
+            ),
+            (None, Some("flag"), &all_vars[..], profile("flag")),
+            (None, None, &all_vars[..], file("/env.json")),
+            (None, None, &all_vars[1..], profile("env")),
+            (Some(""), None, &all_vars[1..], profile("env")),
+            (None, None, &[(CONFIG_VAR, ""), (PROFILE_VAR, "")][..], None),
+        ]
+        .iter()
+        {
+            let var = |name: &str| {
+                vars.iter()
+                    .find(|(n, _)| *n == name)
+                    .map(|(_, value)| value.to_string())
+            };
+            assert_eq!(
+                choose(*config, *flag_profile, var),
+                *choice,
+                "{:?} {:?} {:?}",
+                config,
+                flag_profile,
+                vars
+            );
+        }
+    }
+
+    #[test]
+    fn apply_test() {
+        let settings = Settings {
+            config_file: Some("/settings.db".to_string()),
+            store: Some("sqlite".to_string()),
+            ..Settings::default()
+        };
+        let applied = apply(&settings, &Choice::File(PathBuf::from("/flag.json"))).unwrap();
+        assert_eq!(applied.config_file.as_deref(), Some("/flag.json"));
+        assert_eq!(applied.store, None);
+
+        let applied = apply(&settings, &Choice::File(PathBuf::from("flag.json"))).unwrap();
+        assert_eq!(
+            applied.config_file,
+            Some(
+                env::current_dir()
+                    .unwrap()
+                    .join("flag.json")
+                    .to_string_lossy()
+                    .to_string()
+            )
+        );
+        assert!(matches!(
+            apply(&settings, &Choice::Profile("a b".to_string())),
+            Err(Error::InvalidProfile(_))
+        ));
+    }
+
+    #[test]
+    fn config_dir_test() {
+        let home = || Ok(PathBuf::from("/home/user"));
+        for (xdg, dir) in [
+            (None, "/home/user/.config/def"),
+            (Some("/xdg"), "/xdg/def"),
+            (Some("relative"), "/home/user/.config/def"),
+            (Some(""), "/home/user/.config/def"),
+        ]
+        .iter()
+        {
+            assert_eq!(
+                config_dir(xdg.map(OsString::from), home).unwrap(),
+                PathBuf::from(dir),
+                "{:?}",
+                xdg
+            );
+        }
+        // The home directory isn't needed if XDG_CONFIG_HOME is set.
+        assert!(config_dir(Some(OsString::from("/xdg")), || Err(Error::NoHome)).is_ok());
+        assert!(matches!(
+            config_dir(None, || Err(Error::NoHome)),
+            Err(Error::NoHome)
+        ));
+    }
+}
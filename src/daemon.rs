@@ -0,0 +1,521 @@
+//! A daemon answering queries about descriptions over a Unix domain socket.
+//! Sockets are supported only on Unix, but lookups can be answered the
+//! same way anywhere, see `lookup`.
+//!
+//! Prompts and editors describe paths often, and parsing a large config for
+//! every lookup is wasteful. The daemon loads the config once, reloads it
+//! when it changes, and answers queries sent to the socket next to the
+//! config file (see `socket_file`), so a config has at most one daemon.
+//!
+//! Queries and answers are JSON objects, one per line, and a connection can
+//! be used for any number of queries:
+//!
+//! ```json
+//! {"query":"describe","path":"/srv/www","fs":true,"inherit":false}
+//! {"description":"Served files.","specific":true,"inherited_from":null}
+//! {"query":"list","prefix":"/srv","dir":"/srv"}
+//! {"entries":[{"path":"/srv","description":"Served files.","pattern":null,"score":0}]}
+//! {"query":"search","text":"nginx","fuzzy":false,"dir":"/home/user"}
+//! {"error":"invalid query: missing field `text`"}
+//! ```
+//!
+//! Local files (see `def::local`) in the described path (or in `dir`) and
+//! its ancestors are merged over the config, as when describing paths
+//! without the daemon.
+
+use std::borrow::Cow;
+use std::fs;
+#[cfg(unix)]
+use std::io::{self, BufRead, BufReader, Write};
+#[cfg(unix)]
+use std::os::unix::fs::PermissionsExt;
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+#[cfg(unix)]
+use std::sync::Arc;
+use std::sync::{Mutex, TryLockError};
+#[cfg(unix)]
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+use crate::search::{Match, SearchOptions};
+use crate::settings::Settings;
+use crate::snapshot::{DescriberSnapshot, SnapshotCell};
+use crate::{config, local, store, Describer, Entry, Error, MatchKind, PatternKind};
+
+/// Time a client waits for an answer before giving up on the daemon.
+pub const TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Request is a query sent to the daemon.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(tag = "query", rename_all = "lowercase")]
+pub enum Request {
+    /// Describe a path. Metadata placeholders are filled in if fs is true,
+    /// and paths without a description inherit that of their nearest
+    /// described ancestor if inherit is true.
+    Describe {
+        path: String,
+        #[serde(default)]
+        fs: bool,
+        #[serde(default)]
+        inherit: bool,
+    },
+    /// List descriptions and patterns of prefix and paths under it, or of
+    /// all paths if prefix is empty, with local files of dir merged.
+    List {
+        prefix: String,
+        #[serde(default)]
+        dir: Option<String>,
+    },
+    /// Search descriptions and patterns, with local files of dir merged. See
+    /// `SearchOptions::fuzzy` for what fuzzy matches.
+    Search {
+        text: String,
+        #[serde(default)]
+        fuzzy: bool,
+        #[serde(default)]
+        dir: Option<String>,
+    },
+}
+
+/// Response is the daemon's answer to a request. Its kind is told apart by
+/// its fields: "error", "entries", or "description".
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(untagged)]
+pub enum Response {
+    /// The request couldn't be answered.
+    Error { error: String },
+    /// Answer of list and search requests.
+    Entries { entries: Vec<Item> },
+    /// Answer of describe requests.
+    Description(Lookup),
+}
+
+/// Lookup is the description of a path.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Clone)]
+pub struct Lookup {
+    /// Description of the path, or None if it has none.
+    pub description: Option<String>,
+    /// True if the description was added for the path itself, rather than
+    /// by a pattern or inherited.
+    pub specific: bool,
+    /// Ancestor the description is inherited from, if it is.
+    pub inherited_from: Option<String>,
+}
+
+/// Item is a description or a pattern listed or found by the daemon.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Item {
+    /// Path the description or pattern is mapped to.
+    pub path: String,
+    /// The description or pattern.
+    pub description: String,
+    /// Kind of the pattern, or None if the item is a specific description.
+    pub pattern: Option<PatternKind>,
+    /// Score of a search match (see `search::Match`), 0 for listed items.
+    #[serde(default)]
+    pub score: u32,
+}
+
+impl Item {
+    /// Return the item as an entry.
+    pub fn entry(&self) -> Entry<'_> {
+        Entry {
+            path: &self.path,
+            description: &self.description,
+            pattern: self.pattern,
+        }
+    }
+
+    /// Return the item as a search match.
+    pub fn search_match(&self) -> Match<'_> {
+        Match {
+            path: &self.path,
+            text: &self.description,
+            pattern: self.pattern,
+            score: self.score,
+        }
+    }
+}
+
+/// Return path of the socket of the daemon serving the config file at
+/// config_file: its path with ".sock" appended.
+pub fn socket_file<P: AsRef<Path>>(config_file: P) -> PathBuf {
+    store::sibling(config_file.as_ref(), ".sock")
+}
+
+/// Return the description of path in describer, see `Request::Describe`.
+pub fn lookup(describer: &Describer, path: &str, fs: bool, inherit: bool) -> Lookup {
+    let result = match fs {
+        true => describer.describe_detailed_with_fs(path),
+        false => describer.describe_detailed(path),
+    };
+    let result = match (result, inherit, fs) {
+        (Some(result), _, _) => Some(result),
+        (None, false, _) => None,
+        (None, true, false) => describer.describe_inherited_detailed(path),
+        (None, true, true) => describer.describe_inherited_detailed_with_fs(path),
+    };
+    match result {
+        Some(result) => Lookup {
+            specific: result.kind == MatchKind::Specific,
+            inherited_from: match result.kind {
+                MatchKind::Inherited => Some(result.key),
+                _ => None,
+            },
+            description: Some(result.description),
+        },
+        None => Lookup::default(),
+    }
+}
+
+/// Modification time and size of a config file.
+type Version = Option<(Option<SystemTime>, u64)>;
+
+/// Daemon holds a snapshot of the describer of a config, ready to answer
+/// requests from any number of threads at once.
+#[derive(Debug)]
+pub struct Daemon {
+    settings: Settings,
+    describer: SnapshotCell,
+    /// Version of the config file when it was loaded.
+    version: Mutex<Version>,
+}
+
+impl Daemon {
+    /// Create a daemon answering requests about the config of settings,
+    /// loaded for lookup (see `config::load_for_lookup`).
+    pub fn new(settings: Settings) -> Result<Daemon, Error> {
+        let version = version(&config::config_file(&settings)?);
+        let describer = config::load_for_lookup(&settings)?;
+        Ok(Daemon {
+            settings,
+            describer: SnapshotCell::new(describer.into()),
+            version: Mutex::new(version),
+        })
+    }
+
+    /// Return the current snapshot of the config.
+    pub fn snapshot(&self) -> DescriberSnapshot {
+        self.describer.get()
+    }
+
+    /// Reload the config if it changed since it was loaded, and make it the
+    /// current snapshot. Return true if it was reloaded. Nothing is done if
+    /// another thread is reloading it, so that requests are answered using
+    /// the current snapshot meanwhile.
+    pub fn reload(&self) -> Result<bool, Error> {
+        let mut loaded = match self.version.try_lock() {
+            Ok(loaded) => loaded,
+            Err(TryLockError::WouldBlock) => return Ok(false),
+            Err(TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+        };
+        let version = version(&config::config_file(&self.settings)?);
+        if version == *loaded {
+            return Ok(false);
+        }
+        let describer = config::load_for_lookup(&self.settings)?;
+        self.describer.replace(describer.into());
+        *loaded = version;
+        Ok(true)
+    }
+
+    /// Answer request, reloading the config first if it changed.
+    pub fn answer(&self, request: &Request) -> Response {
+        match self.try_answer(request) {
+            Ok(response) => response,
+            Err(e) => Response::Error {
+                error: e.to_string(),
+            },
+        }
+    }
+
+    fn try_answer(&self, request: &Request) -> Result<Response, Error> {
+        self.reload()?;
+        let snapshot = self.snapshot();
+        Ok(match request {
+            Request::Describe { path, fs, inherit } => {
+                let describer = with_local_files(&snapshot, path)?;
+                Response::Description(lookup(&describer, path, *fs, *inherit))
+            }
+            Request::List { prefix, dir } => {
+                let describer = with_local_files(&snapshot, dir.as_deref().unwrap_or(prefix))?;
+                let entries = describer
+                    .entries_under(prefix)
+                    .into_iter()
+                    .map(|e| Item {
+                        path: e.path.to_string(),
+                        description: e.description.to_string(),
+                        pattern: e.pattern,
+                        score: 0,
+                    })
+                    .collect();
+                Response::Entries { entries }
+            }
+            Request::Search { text, fuzzy, dir } => {
+                let describer = with_local_files(&snapshot, dir.as_deref().unwrap_or_default())?;
+                let options = match fuzzy {
+                    true => SearchOptions::fuzzy(),
+                    false => SearchOptions::default(),
+                };
+                let entries = describer
+                    .search_with(text, &options)
+                    .into_iter()
+                    .map(|m| Item {
+                        path: m.path.to_string(),
+                        description: m.text.to_string(),
+                        pattern: m.pattern,
+                        score: m.score,
+                    })
+                    .collect();
+                Response::Entries { entries }
+            }
+        })
+    }
+}
+
+/// Return describer with local files of path merged over it, which is
+/// cloned only if there are any.
+fn with_local_files<'a>(describer: &'a Describer, path: &str) -> Result<Cow<'a, Describer>, Error> {
+    let files = local::discover(path);
+    if files.is_empty() {
+        return Ok(Cow::Borrowed(describer));
+    }
+    let mut describer = describer.clone();
+    for file in files {
+        describer.merge(local::load(file)?);
+    }
+    Ok(Cow::Owned(describer))
+}
+
+/// Return the modification time and size of file, or None if it doesn't
+/// exist.
+fn version(file: &Path) -> Version {
+    fs::metadata(file)
+        .ok()
+        .map(|m| (m.modified().ok(), m.len()))
+}
+
+/// Listen on socket, which only the user can connect to. An error is
+/// returned if another daemon is listening on it, and a socket left by a
+/// daemon that exited is replaced.
+#[cfg(unix)]
+pub fn listen<P: AsRef<Path>>(socket: P) -> io::Result<UnixListener> {
+    let socket = socket.as_ref();
+    if socket.exists() {
+        if UnixStream::connect(socket).is_ok() {
+            return Err(io::Error::new(
+                io::ErrorKind::AddrInUse,
+                format!("a daemon is already listening on {}", socket.display()),
+            ));
+        }
+        fs::remove_file(socket)?;
+    }
+    let listener = UnixListener::bind(socket)?;
+    fs::set_permissions(socket, fs::Permissions::from_mode(0o600))?;
+    Ok(listener)
+}
+
+/// Answer requests of every connection to listener using daemon, each
+/// connection in its own thread.
+#[cfg(unix)]
+pub fn serve(daemon: Daemon, listener: UnixListener) -> io::Result<()> {
+    let daemon = Arc::new(daemon);
+    for stream in listener.incoming() {
+        let stream = stream?;
+        let daemon = Arc::clone(&daemon);
+        thread::spawn(move || {
+            // Errors only end the connection, such as when the client hangs
+            // up early.
+            let _ = handle(stream, &daemon);
+        });
+    }
+    Ok(())
+}
+
+/// Answer requests read from stream, one per line, until it's closed.
+#[cfg(unix)]
+fn handle(stream: UnixStream, daemon: &Daemon) -> io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(request) => daemon.answer(&request),
+            Err(e) => Response::Error {
+                error: format!("invalid query: {}", e),
+            },
+        };
+        serde_json::to_writer(&mut writer, &response)?;
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+/// Client is a connection to a daemon.
+#[cfg(unix)]
+#[derive(Debug)]
+pub struct Client {
+    reader: BufReader<UnixStream>,
+    writer: UnixStream,
+}
+
+#[cfg(unix)]
+impl Client {
+    /// Connect to the daemon listening on socket. Requests fail if it
+    /// doesn't answer within `TIMEOUT`.
+    pub fn connect<P: AsRef<Path>>(socket: P) -> io::Result<Client> {
+        let stream = UnixStream::connect(socket)?;
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        stream.set_write_timeout(Some(TIMEOUT))?;
+        Ok(Client {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+        })
+    }
+
+    /// Send request and return the daemon's response.
+    pub fn ask(&mut self, request: &Request) -> io::Result<Response> {
+        serde_json::to_writer(&mut self.writer, request)?;
+        self.writer.write_all(b"\n")?;
+        self.writer.flush()?;
+
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "the daemon closed the connection",
+            ));
+        }
+        Ok(serde_json::from_str(&line)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookup_test() {
+        let mut d = Describer::new();
+        d.add_description("/srv", "Served files.");
+        d.add_pattern("/srv", "* is served.").unwrap();
+
+        for (path, inherit, description, specific, inherited_from) in [
+            ("/srv", false, Some("Served files."), true, None),
+            ("/srv/www", false, Some("www is served."), false, None),
+            ("/srv/www/index.html", false, None, false, None),
+            (
+                "/srv/www/index.html",
+                true,
+                Some("www is served."),
+                false,
+                Some("/srv/www"),
+            ),
+        ]
+        .iter()
+        {
+            assert_eq!(
+                lookup(&d, path, false, *inherit),
+                Lookup {
+                    description: description.map(str::to_string),
+                    specific: *specific,
+                    inherited_from: inherited_from.map(str::to_string),
+                },
+                "{} {}",
+                path,
+                inherit
+            );
+        }
+    }
+
+    #[test]
+    fn protocol_test() {
+        for (json, request) in [
+            (
+                r#"{"query":"describe","path":"/srv"}"#,
+                Request::Describe {
+                    path: "/srv".to_string(),
+                    fs: false,
+                    inherit: false,
+                },
+            ),
+            (
+                r#"{"query":"search","text":"nginx","fuzzy":true}"#,
+                Request::Search {
+                    text: "nginx".to_string(),
+                    fuzzy: true,
+                    dir: None,
+                },
+            ),
+        ]
+        .iter()
+        {
+            assert_eq!(serde_json::from_str::<Request>(json).unwrap(), *request);
+        }
+
+        for response in [
+            Response::Error {
+                error: "failed".to_string(),
+            },
+            Response::Entries { entries: vec![] },
+            Response::Description(Lookup::default()),
+        ]
+        .iter()
+        {
+            let json = serde_json::to_string(response).unwrap();
+            assert_eq!(
+                serde_json::from_str::<Response>(&json).unwrap(),
+                *response,
+                "{}",
+                json
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn serve_test() {
+        let dir = std::env::temp_dir().join(format!("def-daemon-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("config.json");
+        let settings = Settings {
+            config_file: Some(file.to_string_lossy().to_string()),
+            ..Settings::default()
+        };
+        config::update(&settings, |d| d.add_description("/srv", "Served files.")).unwrap();
+
+        let socket = socket_file(&file);
+        let daemon = Daemon::new(settings.clone()).unwrap();
+        let listener = listen(&socket).unwrap();
+        thread::spawn(move || serve(daemon, listener));
+        let describe = Request::Describe {
+            path: "/srv".to_string(),
+            fs: false,
+            inherit: false,
+        };
+        let mut client = Client::connect(&socket).unwrap();
+        let first = client.ask(&describe).unwrap();
+        config::update(&settings, |d| d.add_description("/srv", "Changed.")).unwrap();
+        let changed = client.ask(&describe).unwrap();
+        let second = listen(&socket);
+        fs::remove_dir_all(&dir).unwrap();
+
+        let description = |text: &str| {
+            Response::Description(Lookup {
+                description: Some(text.to_string()),
+                specific: true,
+                inherited_from: None,
+            })
+        };
+        assert_eq!(first, description("Served files."));
+        assert_eq!(changed, description("Changed."));
+        assert_eq!(second.unwrap_err().kind(), io::ErrorKind::AddrInUse);
+    }
+}
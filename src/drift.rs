@@ -0,0 +1,275 @@
+//! Comparison between described paths and the file system.
+//!
+//! A drift report lists descriptions that may no longer be accurate: those of
+//! paths that don't exist anymore, or whose metadata changed drastically since
+//! they were described. It also lists prominent paths that lack descriptions.
+//! Stale descriptions (see `stale`) are found similarly, by comparing times
+//! descriptions were updated with times their paths were modified, and dead
+//! entries (see `is_dead`) by checking that their paths exist at all.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, UNIX_EPOCH};
+
+use crate::meta::PathMetadata;
+use crate::{paths, Describer, Entry, PatternKind};
+
+/// Changed is a described path whose metadata contradicts the metadata
+/// recorded when it was described.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Changed {
+    /// The described path.
+    pub path: String,
+    /// Metadata recorded when the path was described.
+    pub recorded: PathMetadata,
+    /// Current metadata of the path.
+    pub current: PathMetadata,
+}
+
+/// Stale is a description that may be outdated, since its path was modified
+/// after it was updated, or no longer exists.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Stale {
+    /// The described path.
+    pub path: String,
+    /// Time the description was last updated, in seconds since the Unix
+    /// epoch, if it was recorded.
+    pub updated: Option<u64>,
+    /// Time the path was last modified, in seconds since the Unix epoch, or
+    /// None if it no longer exists.
+    pub modified: Option<u64>,
+}
+
+/// DriftReport lists differences between a describer and the file system
+/// under a root directory. All lists are sorted by path.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct DriftReport {
+    /// Paths with descriptions or patterns that no longer exist.
+    pub missing: Vec<String>,
+    /// Described paths whose metadata changed drastically.
+    pub changed: Vec<Changed>,
+    /// Non-hidden directories directly under root that can't be described.
+    pub undescribed: Vec<String>,
+}
+
+impl DriftReport {
+    /// Return true if the report found nothing.
+    pub fn is_empty(&self) -> bool {
+        self.missing.is_empty() && self.changed.is_empty() && self.undescribed.is_empty()
+    }
+}
+
+/// Compare descriptions and patterns of root and paths under it with the file
+/// system, and return a report of the differences. An error is returned if
+/// root can't be read.
+pub fn report(describer: &Describer, root: &str) -> io::Result<DriftReport> {
+    let mut report = DriftReport::default();
+
+    let entries = describer.entries_under(root);
+    let mut keys: Vec<&str> = entries.iter().map(|e| e.path).collect();
+    keys.dedup();
+    for key in keys {
+        match PathMetadata::read(key) {
+            Ok(current) => {
+                if let Some(recorded) = describer.metadata(key) {
+                    if recorded.contradicts(&current) {
+                        report.changed.push(Changed {
+                            path: key.to_string(),
+                            recorded: *recorded,
+                            current,
+                        });
+                    }
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => report.missing.push(key.to_string()),
+            Err(_) => {}
+        }
+    }
+
+    for dir_entry in fs::read_dir(root)? {
+        let dir_entry = dir_entry?;
+        let name = dir_entry.file_name().to_string_lossy().to_string();
+        if name.starts_with('.') || !dir_entry.file_type()?.is_dir() {
+            continue;
+        }
+
+        let path = Path::new(root).join(&name).to_string_lossy().to_string();
+        if describer.describe(&path).is_none() {
+            report.undescribed.push(path);
+        }
+    }
+    report.undescribed.sort();
+
+    Ok(report)
+}
+
+/// Return descriptions whose paths were modified more than grace after they
+/// were last updated, or no longer exist, sorted by path. Descriptions
+/// without recorded timestamps are only returned if their paths don't exist.
+pub fn stale(describer: &Describer, grace: Duration) -> Vec<Stale> {
+    let mut stale = Vec::new();
+    for (path, _) in describer.iter_descriptions() {
+        let updated = describer.timestamps(path).map(|t| t.updated);
+        let modified = match modified_time(path) {
+            Ok(modified) => Some(modified),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(_) => continue,
+        };
+        let outdated = match (updated, modified) {
+            (_, None) => true,
+            (Some(updated), Some(modified)) => modified > updated.saturating_add(grace.as_secs()),
+            (None, Some(_)) => false,
+        };
+        if outdated {
+            stale.push(Stale {
+                path: path.to_string(),
+                updated,
+                modified,
+            });
+        }
+    }
+    stale.sort_by(|a, b| a.path.cmp(&b.path));
+    stale
+}
+
+/// Return true if entry is a description or a pattern mapped to a path that
+/// doesn't exist. Globs, and keys that aren't absolute paths, aren't dead.
+/// Can be used with `Describer::prune_with` to remove dead entries.
+pub fn is_dead(entry: &Entry) -> bool {
+    entry.pattern != Some(PatternKind::Glob)
+        && paths::root_len(entry.path).is_some()
+        && matches!(
+            fs::symlink_metadata(entry.path),
+            Err(e) if e.kind() == io::ErrorKind::NotFound
+        )
+}
+
+/// Return the time path was last modified, in seconds since the Unix epoch.
+/// Symbolic links aren't followed.
+pub(crate) fn modified_time(path: &str) -> io::Result<u64> {
+    let modified = fs::symlink_metadata(path)?.modified()?;
+    Ok(modified
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::meta::FileKind;
+
+    #[test]
+    fn report_test() {
+        let root = std::env::temp_dir().join(format!("def-drift-test-{}", std::process::id()));
+        let root_str = root.to_string_lossy().to_string();
+        let path = |name: &str| root.join(name).to_string_lossy().to_string();
+        fs::create_dir_all(root.join("described")).unwrap();
+        fs::create_dir_all(root.join("undescribed")).unwrap();
+        fs::create_dir_all(root.join(".hidden")).unwrap();
+        fs::write(root.join("was-dir"), "now a file").unwrap();
+
+        let mut d = Describer::new();
+        d.add_description(path("described"), "Described.");
+        d.add_description(path("gone"), "Doesn't exist.");
+        d.add_description(path("was-dir"), "Was a directory.");
+        d.record_metadata(
+            path("was-dir"),
+            PathMetadata {
+                kind: FileKind::Directory,
+                size: 2,
+            },
+        );
+        let r = report(&d, &root_str);
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(
+            r.unwrap(),
+            DriftReport {
+                missing: vec![path("gone")],
+                changed: vec![Changed {
+                    path: path("was-dir"),
+                    recorded: PathMetadata {
+                        kind: FileKind::Directory,
+                        size: 2,
+                    },
+                    current: PathMetadata {
+                        kind: FileKind::File,
+                        size: 10,
+                    },
+                }],
+                undescribed: vec![path("undescribed")],
+            }
+        );
+    }
+
+    #[test]
+    fn stale_test() {
+        let root = std::env::temp_dir().join(format!("def-stale-test-{}", std::process::id()));
+        let path = |name: &str| root.join(name).to_string_lossy().to_string();
+        fs::create_dir_all(&root).unwrap();
+        for name in ["fresh", "modified", "unstamped"].iter() {
+            fs::write(root.join(name), "").unwrap();
+        }
+        let now = modified_time(&path("modified")).unwrap();
+
+        let mut d = Describer::new();
+        for name in ["fresh", "modified", "unstamped", "gone"].iter() {
+            d.add_description(path(name), "Described.");
+        }
+        d.stamp(path("fresh"), now);
+        d.stamp(path("modified"), now - 3 * 86400);
+        d.stamp(path("gone"), now);
+        let found = stale(&d, Duration::from_secs(0));
+        let within_grace = stale(&d, Duration::from_secs(7 * 86400));
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(
+            found,
+            vec![
+                Stale {
+                    path: path("gone"),
+                    updated: Some(now),
+                    modified: None,
+                },
+                Stale {
+                    path: path("modified"),
+                    updated: Some(now - 3 * 86400),
+                    modified: Some(now),
+                },
+            ]
+        );
+        assert_eq!(within_grace.len(), 1);
+    }
+
+    #[test]
+    fn prune_dead_test() {
+        let root = std::env::temp_dir().join(format!("def-dead-test-{}", std::process::id()));
+        let path = |name: &str| root.join(name).to_string_lossy().to_string();
+        fs::create_dir_all(root.join("alive")).unwrap();
+
+        let mut d = Describer::new();
+        d.add_description(path("alive"), "Alive.");
+        d.add_description(path("gone"), "Gone.");
+        d.add_pattern(path("gone-dir"), "Gone too.").unwrap();
+        d.add_glob("*.gone", "A glob.").unwrap();
+        d.add_pattern("relative", "Relative.").unwrap();
+        let removed = d.prune_with(is_dead);
+        fs::remove_dir_all(&root).unwrap();
+
+        let removed: Vec<(&str, Option<PatternKind>)> = removed
+            .iter()
+            .map(|r| (r.path.as_str(), r.pattern))
+            .collect();
+        assert_eq!(
+            removed,
+            vec![
+                (path("gone").as_str(), None),
+                (path("gone-dir").as_str(), Some(PatternKind::Children)),
+            ]
+        );
+        assert_eq!(d.describe(path("alive")), Some("Alive.".to_string()));
+        assert_eq!(d.entries_under("").len(), 3);
+    }
+}
@@ -0,0 +1,160 @@
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::process::{self, Command};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::i18n::{self, tr};
+
+/// Editor used if neither $VISUAL nor $EDITOR is set.
+const DEFAULT_EDITOR: &str = "vi";
+
+/// Number of names create_temp tries before giving up.
+const TEMP_ATTEMPTS: u32 = 16;
+
+/// edit opens text in the user's editor (see editor_command), and returns the
+/// edited text once the editor exits. The text is edited in a temporary file
+/// (see create_temp) whose name ends with suffix (such as ".json"), so that
+/// editors can pick the right syntax.
+pub fn edit(text: &str, suffix: &str) -> io::Result<String> {
+    let (file, mut temp) = create_temp(suffix)?;
+    let written = temp.write_all(text.as_bytes());
+    drop(temp);
+
+    let result = written
+        .and_then(|_| run_editor(&file.to_string_lossy()))
+        .and_then(|_| fs::read_to_string(&file));
+    let _ = fs::remove_file(&file);
+    result
+}
+
+/// create_temp creates a new file in the temporary directory, whose name ends
+/// with suffix, and returns its path along with the opened file. Only the
+/// user can read and write the file. Names already taken (such as by files
+/// or links other users made) are never opened, another name is tried
+/// instead.
+fn create_temp(suffix: &str) -> io::Result<(PathBuf, File)> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.subsec_nanos());
+    for attempt in 0..TEMP_ATTEMPTS {
+        let name = format!("def-edit-{}-{}-{}{}", process::id(), nanos, attempt, suffix);
+        let file = env::temp_dir().join(name);
+        match options.open(&file) {
+            Ok(temp) => return Ok((file, temp)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::AlreadyExists,
+        "failed to name a temporary file",
+    ))
+}
+
+/// run_editor runs the user's editor on file, and waits for it to exit.
+fn run_editor(file: &str) -> io::Result<()> {
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_default();
+    let (program, args) = editor_command(&editor);
+    let status = Command::new(program).args(args).arg(file).status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "{} exited with {}",
+            program, status
+        )))
+    }
+}
+
+/// editor_command splits the value of $VISUAL or $EDITOR (such as
+/// "code --wait") into a program and its arguments. DEFAULT_EDITOR is used
+/// if editor is blank.
+fn editor_command(editor: &str) -> (&str, Vec<&str>) {
+    let mut words = editor.split_whitespace();
+    match words.next() {
+        Some(program) => (program, words.collect()),
+        None => (DEFAULT_EDITOR, Vec::new()),
+    }
+}
+
+/// trim_edited removes the trailing newlines editors add when saving.
+pub fn trim_edited(text: &str) -> &str {
+    text.trim_end_matches(['\n', '\r'])
+}
+
+/// ask_to_retry asks whether to edit again after an invalid edit, and
+/// returns true unless the user said no (see i18n::is_no). Returns false if
+/// input is closed.
+pub fn ask_to_retry<R: BufRead>(input: &mut R) -> bool {
+    print!("{} ", tr("edit-again"));
+    let _ = io::stdout().flush();
+    let mut line = String::new();
+    match input.read_line(&mut line) {
+        Ok(0) | Err(_) => false,
+        Ok(_) => !i18n::is_no(&line),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn editor_command_test() {
+        for (editor, program, args) in [
+            ("vim", "vim", vec![]),
+            ("code --wait", "code", vec!["--wait"]),
+            ("  ", DEFAULT_EDITOR, vec![]),
+            ("", DEFAULT_EDITOR, vec![]),
+        ]
+        .iter()
+        {
+            assert_eq!(
+                editor_command(editor),
+                (*program, args.clone()),
+                "{}",
+                editor
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn create_temp_test() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let (file, _) = create_temp(".json").unwrap();
+        let (other, _) = create_temp(".json").unwrap();
+        let mode = fs::metadata(&file).unwrap().permissions().mode() & 0o777;
+        fs::remove_file(&file).unwrap();
+        fs::remove_file(&other).unwrap();
+
+        assert_ne!(file, other);
+        assert!(file.to_string_lossy().ends_with(".json"));
+        assert_eq!(mode, 0o600);
+    }
+
+    #[test]
+    fn ask_to_retry_test() {
+        for (input, retries) in [
+            ("", false),
+            ("\n", true),
+            ("y\n", true),
+            ("N\n", false),
+            ("no\n", false),
+        ]
+        .iter()
+        {
+            assert_eq!(ask_to_retry(&mut input.as_bytes()), *retries, "{:?}", input);
+        }
+    }
+}
@@ -0,0 +1,110 @@
+//! Errors returned by the library.
+
+use std::fmt;
+use std::io;
+
+use crate::store::StoreError;
+use crate::trash::TrashError;
+
+/// Error is returned by fallible functions of the library, such as those
+/// loading and saving the config.
+#[derive(Debug)]
+pub enum Error {
+    /// A file can't be read or written.
+    Io(io::Error),
+    /// Settings can't be deserialized.
+    Json(serde_json::Error),
+    /// A path can't be used, such as a path that contains invalid characters.
+    InvalidPath(String),
+    /// A path has no description.
+    NoDescription(String),
+    /// The home directory, which contains def's files, isn't known.
+    NoHome,
+    /// An unknown storage backend is set in settings.
+    UnknownStore(String),
+    /// The config can't be loaded or saved.
+    Store(StoreError),
+    /// A trashed entry can't be restored.
+    Trash(TrashError),
+    /// A profile name is invalid, see `config::profile_file`.
+    InvalidProfile(String),
+    /// A profile doesn't exist.
+    UnknownProfile(String),
+    /// A profile already exists.
+    ProfileExists(String),
+    /// A fixture can't be generated from a spec, see `fixture::Spec`.
+    InvalidFixture(String),
+    /// An annotation file can't be parsed, see `def::annotations`.
+    InvalidAnnotations(String),
+    /// The journal of the config can't be parsed, see `def::journal`.
+    InvalidJournal(String),
+    /// A journaled change can't be undone, as the entry of the path was
+    /// changed since.
+    Conflict(String),
+    /// No remote to sync the config with is set in settings, see
+    /// `def::sync`.
+    NoRemote,
+    /// The config can't be synced with its remote, such as when git or curl
+    /// fails, see `def::sync`.
+    Sync(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "{}", e),
+            Error::Json(e) => write!(f, "invalid JSON: {}", e),
+            Error::InvalidPath(p) => write!(f, "invalid path {}", p),
+            Error::NoDescription(p) => write!(f, "{} has no description", p),
+            Error::NoHome => write!(f, "home directory couldn't be found"),
+            Error::UnknownStore(s) => write!(f, "unknown store \"{}\"", s),
+            Error::Store(e) => write!(f, "{}", e),
+            Error::Trash(e) => write!(f, "{}", e),
+            Error::InvalidProfile(p) => write!(f, "invalid profile name \"{}\"", p),
+            Error::UnknownProfile(p) => write!(f, "profile \"{}\" doesn't exist", p),
+            Error::ProfileExists(p) => write!(f, "profile \"{}\" already exists", p),
+            Error::InvalidFixture(e) => write!(f, "invalid fixture spec: {}", e),
+            Error::InvalidAnnotations(e) => write!(f, "invalid annotations: {}", e),
+            Error::InvalidJournal(e) => write!(f, "invalid journal: {}", e),
+            Error::Conflict(p) => write!(f, "{} was changed since", p),
+            Error::NoRemote => write!(f, "no sync remote is set in settings"),
+            Error::Sync(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Json(e) => Some(e),
+            Error::Store(e) => Some(e),
+            Error::Trash(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Error {
+        Error::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Error {
+        Error::Json(e)
+    }
+}
+
+impl From<StoreError> for Error {
+    fn from(e: StoreError) -> Error {
+        Error::Store(e)
+    }
+}
+
+impl From<TrashError> for Error {
+    fn from(e: TrashError) -> Error {
+        Error::Trash(e)
+    }
+}
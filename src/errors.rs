@@ -1,7 +1,7 @@
 use std::fmt::Display;
 use std::process;
 
-use colored::*;
+use crate::theme::{error_label, warning_label};
 
 /// Handle defines a method to extract a value from a Result and exit on error. It's
 /// created to avoid code repetition.
@@ -12,12 +12,17 @@ pub trait Handle<T> {
     fn extract_or_exit(self, message: &str) -> T;
 }
 
+/// warn prints a warning message to stderr without exiting.
+pub fn warn<M: Display>(message: M) {
+    eprintln!("{}: {}", warning_label(), message);
+}
+
 impl<T, E: Display> Handle<T> for Result<T, E> {
     fn extract_or_exit(self, message: &str) -> T {
         match self {
             Ok(t) => t,
             Err(e) => {
-                eprintln!("{}: {}: {}", "Err".red(), message, e);
+                eprintln!("{}: {}: {}", error_label(), message, e);
                 process::exit(1);
             }
         }
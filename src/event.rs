@@ -0,0 +1,148 @@
+//! Changes between two versions of a describer, as events that scripts can
+//! react to.
+//!
+//! Events are serialized as JSON objects, one per line (NDJSON):
+//!
+//! ```json
+//! {"event":"add","path":"/srv","pattern":null,"before":null,"after":"Served files."}
+//! {"event":"update","path":"/srv/logs","pattern":"recursive","before":"A log.","after":"** is a log."}
+//! {"event":"remove","path":"/tmp","pattern":null,"before":"Temporary files.","after":null}
+//! ```
+//!
+//! Fields are always present and in the same order, and events are sorted
+//! like `Describer::entries_under`, so that the same change always produces
+//! the same output.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Describer, PatternKind};
+
+/// EventKind is what happened to an entry.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum EventKind {
+    /// A description or pattern was mapped to a new key.
+    Add,
+    /// A description or pattern was deleted.
+    Remove,
+    /// The text mapped to a key was changed.
+    Update,
+}
+
+/// Event is a change of one entry of a describer.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+pub struct Event {
+    /// What happened to the entry, serialized as "event".
+    #[serde(rename = "event")]
+    pub kind: EventKind,
+    /// Path the entry is mapped to.
+    pub path: String,
+    /// Kind of the pattern, or None if the entry is a specific description.
+    pub pattern: Option<PatternKind>,
+    /// Text of the entry before the change, or None if it was added.
+    pub before: Option<String>,
+    /// Text of the entry after the change, or None if it was removed.
+    pub after: Option<String>,
+}
+
+/// Return events changing the entries of old into those of new, sorted by
+/// path. If multiple entries mapped to the same path changed, the
+/// description comes first, then the pattern, the recursive pattern, and the
+/// glob.
+pub fn diff(old: &Describer, new: &Describer) -> Vec<Event> {
+    let (old, new) = (texts(old), texts(new));
+    let keys: BTreeSet<_> = old.keys().chain(new.keys()).collect();
+    keys.into_iter()
+        .filter_map(|&(path, pattern)| {
+            let before = old.get(&(path, pattern)).copied();
+            let after = new.get(&(path, pattern)).copied();
+            let kind = match (before, after) {
+                (None, Some(_)) => EventKind::Add,
+                (Some(_), None) => EventKind::Remove,
+                (Some(before), Some(after)) if before != after => EventKind::Update,
+                _ => return None,
+            };
+            Some(Event {
+                kind,
+                path: path.to_string(),
+                pattern,
+                before: before.map(str::to_string),
+                after: after.map(str::to_string),
+            })
+        })
+        .collect()
+}
+
+/// Return texts of describer's entries, mapped to their paths and kinds.
+pub(crate) fn texts(describer: &Describer) -> BTreeMap<(&str, Option<PatternKind>), &str> {
+    describer
+        .entries_under("")
+        .into_iter()
+        .map(|e| ((e.path, e.pattern), e.description))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_test() {
+        let mut old = Describer::new();
+        old.add_description("/tmp", "Temporary files.");
+        old.add_description("/srv/logs", "Logs.");
+        old.add_pattern_with_kind("/srv/logs", "A log.", PatternKind::Recursive)
+            .unwrap();
+        old.add_glob("*.conf", "A config.").unwrap();
+
+        let mut new = Describer::new();
+        new.add_description("/srv", "Served files.");
+        new.add_description("/srv/logs", "Logs.");
+        new.add_pattern_with_kind("/srv/logs", "** is a log.", PatternKind::Recursive)
+            .unwrap();
+        new.add_glob("*.conf", "A config.").unwrap();
+
+        let event = |kind, path: &str, pattern, before: Option<&str>, after: Option<&str>| Event {
+            kind,
+            path: path.to_string(),
+            pattern,
+            before: before.map(str::to_string),
+            after: after.map(str::to_string),
+        };
+        for (old, new, events) in [
+            (&old, &old, vec![]),
+            (
+                &old,
+                &new,
+                vec![
+                    event(EventKind::Add, "/srv", None, None, Some("Served files.")),
+                    event(
+                        EventKind::Update,
+                        "/srv/logs",
+                        Some(PatternKind::Recursive),
+                        Some("A log."),
+                        Some("** is a log."),
+                    ),
+                    event(
+                        EventKind::Remove,
+                        "/tmp",
+                        None,
+                        Some("Temporary files."),
+                        None,
+                    ),
+                ],
+            ),
+        ]
+        .iter()
+        {
+            assert_eq!(diff(old, new), *events);
+        }
+
+        assert_eq!(
+            serde_json::to_string(&diff(&old, &new)[0]).unwrap(),
+            r#"{"event":"add","path":"/srv","pattern":null,"before":null,"after":"Served files."}"#
+        );
+    }
+}
@@ -0,0 +1,492 @@
+//! Filters selecting descriptions and patterns, written as queries.
+//!
+//! A query is a list of terms, all of which must match an entry. Terms can be
+//! combined using `OR`, negated using `NOT`, and grouped using parentheses,
+//! where `NOT` binds tightest and `OR` loosest. `AND` can be written between
+//! terms, though it's implied. A term is one of the following:
+//!
+//! - `path:<path>`: Entries mapped to path or to paths under it, or, if path
+//!   contains wildcards, entries whose path matches it as a glob (see
+//!   `def::glob`), such as `path:/var/**`.
+//! - `text:<word>`: Entries whose text contains word, ignoring case.
+//! - `kind:<kind>`: Entries of a kind, one of "description", "pattern",
+//!   "recursive", or "glob".
+//! - `modified:<date>`: Entries whose path was last modified on date (in
+//!   UTC), written as `YYYY-MM-DD`. The date can be preceded by `<`, `<=`,
+//!   `>`, or `>=`, such as `modified:>2024-01-01`. Paths that can't be read
+//!   don't match.
+//! - Any other word: Entries whose path or text contains it, ignoring case.
+//!
+//! Values containing spaces are quoted, such as `text:"web server"`.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::meta::{self, FileInfo};
+use crate::{glob, paths, Entry, PatternKind};
+
+/// Keyword combining terms which must all match.
+const AND: &str = "AND";
+
+/// Keyword combining terms of which any must match.
+const OR: &str = "OR";
+
+/// Keyword negating a term.
+const NOT: &str = "NOT";
+
+/// Filter selects entries of a describer.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Filter {
+    /// Matches every entry, parsed from an empty query.
+    All,
+    /// Path or text contains the (lowercase) word.
+    Word(String),
+    /// Path is under the path, or matches it if it's a glob.
+    Path(String),
+    /// Text contains the (lowercase) word.
+    Text(String),
+    /// Entry is of the kind, None being a specific description.
+    Kind(Option<PatternKind>),
+    /// Path was last modified at a date compared as given.
+    Modified(Comparison, String),
+    Not(Box<Filter>),
+    And(Vec<Filter>),
+    Or(Vec<Filter>),
+}
+
+/// Comparison is how a date of a `modified:` term is compared.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Comparison {
+    Before,
+    AtMost,
+    On,
+    AtLeast,
+    After,
+}
+
+/// FilterError is returned when a query can't be parsed.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum FilterError {
+    /// A term has a field that doesn't exist.
+    UnknownField(String),
+    /// A term has a value its field doesn't accept, given as (field, value).
+    InvalidValue(String, String),
+    /// A word was found where a term was expected, such as a keyword or a
+    /// closing parenthesis.
+    Unexpected(String),
+    /// The query ended where a term or a closing parenthesis was expected.
+    UnexpectedEnd,
+    /// A quote isn't closed.
+    UnclosedQuote,
+}
+
+impl fmt::Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FilterError::UnknownField(field) => write!(f, "unknown field \"{}\"", field),
+            FilterError::InvalidValue(field, value) => {
+                write!(f, "invalid value \"{}\" for \"{}\"", value, field)
+            }
+            FilterError::Unexpected(word) => write!(f, "unexpected \"{}\"", word),
+            FilterError::UnexpectedEnd => write!(f, "unexpected end of query"),
+            FilterError::UnclosedQuote => write!(f, "unclosed quote"),
+        }
+    }
+}
+
+impl Error for FilterError {}
+
+/// Token is a part of a query.
+#[derive(Debug, PartialEq, Eq, Clone)]
+enum Token {
+    Open,
+    Close,
+    /// A keyword or a term, along with whether any of it was quoted, since
+    /// quoted keywords are terms.
+    Word(String, bool),
+}
+
+impl Filter {
+    /// Parse a query, see the module's documentation for its syntax.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use def::filter::Filter;
+    /// use def::{Entry, PatternKind};
+    ///
+    /// let filter = Filter::parse("path:/srv/** AND (web OR kind:glob)").unwrap();
+    /// let entry = Entry {
+    ///     path: "/srv/www",
+    ///     description: "Web root.",
+    ///     pattern: None,
+    /// };
+    /// assert!(filter.matches(&entry));
+    /// assert!(!Filter::parse("NOT web").unwrap().matches(&entry));
+    /// ```
+    pub fn parse(query: &str) -> Result<Filter, FilterError> {
+        let tokens = tokenize(query)?;
+        if tokens.is_empty() {
+            return Ok(Filter::All);
+        }
+        let mut parser = Parser { tokens, next: 0 };
+        let filter = parser.or()?;
+        match parser.tokens.get(parser.next) {
+            None => Ok(filter),
+            Some(token) => Err(FilterError::Unexpected(token.source())),
+        }
+    }
+
+    /// Return true if entry matches the filter.
+    pub fn matches(&self, entry: &Entry) -> bool {
+        match self {
+            Filter::All => true,
+            Filter::Word(word) => contains(entry.path, word) || contains(entry.description, word),
+            Filter::Path(path) if glob::is_glob(path) => glob::matches(path, entry.path),
+            Filter::Path(path) => paths::is_under(entry.path, path),
+            Filter::Text(word) => contains(entry.description, word),
+            Filter::Kind(kind) => entry.pattern == *kind,
+            Filter::Modified(comparison, date) => match FileInfo::read(entry.path) {
+                Ok(FileInfo {
+                    modified: Some(modified),
+                    ..
+                }) => {
+                    let modified = meta::human_time(modified);
+                    let day = &modified[..date.len()];
+                    match comparison {
+                        Comparison::Before => day < date.as_str(),
+                        Comparison::AtMost => day <= date.as_str(),
+                        Comparison::On => day == date,
+                        Comparison::AtLeast => day >= date.as_str(),
+                        Comparison::After => day > date.as_str(),
+                    }
+                }
+                _ => false,
+            },
+            Filter::Not(filter) => !filter.matches(entry),
+            Filter::And(filters) => filters.iter().all(|f| f.matches(entry)),
+            Filter::Or(filters) => filters.iter().any(|f| f.matches(entry)),
+        }
+    }
+}
+
+impl Token {
+    /// Return the token as written in a query.
+    fn source(&self) -> String {
+        match self {
+            Token::Open => "(".to_string(),
+            Token::Close => ")".to_string(),
+            Token::Word(word, _) => word.clone(),
+        }
+    }
+
+    /// Return true if the token is the given keyword.
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self, Token::Word(word, false) if word == keyword)
+    }
+}
+
+/// Parser is a recursive descent parser of queries.
+struct Parser {
+    tokens: Vec<Token>,
+    next: usize,
+}
+
+impl Parser {
+    /// Consume the next token if it's the given keyword, and return true if
+    /// it was.
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let found = self
+            .tokens
+            .get(self.next)
+            .is_some_and(|t| t.is_keyword(keyword));
+        if found {
+            self.next += 1;
+        }
+        found
+    }
+
+    /// Parse terms separated by OR.
+    fn or(&mut self) -> Result<Filter, FilterError> {
+        let mut filters = vec![self.and()?];
+        while self.keyword(OR) {
+            filters.push(self.and()?);
+        }
+        Ok(combine(filters, Filter::Or))
+    }
+
+    /// Parse terms separated by AND, or by nothing.
+    fn and(&mut self) -> Result<Filter, FilterError> {
+        let mut filters = vec![self.not()?];
+        loop {
+            if self.keyword(AND) {
+                filters.push(self.not()?);
+                continue;
+            }
+            match self.tokens.get(self.next) {
+                Some(Token::Close) | None => break,
+                Some(t) if t.is_keyword(OR) => break,
+                Some(_) => filters.push(self.not()?),
+            }
+        }
+        Ok(combine(filters, Filter::And))
+    }
+
+    /// Parse a term, which may be negated or a parenthesized query.
+    fn not(&mut self) -> Result<Filter, FilterError> {
+        if self.keyword(NOT) {
+            return Ok(Filter::Not(Box::new(self.not()?)));
+        }
+        let token = self
+            .tokens
+            .get(self.next)
+            .cloned()
+            .ok_or(FilterError::UnexpectedEnd)?;
+        self.next += 1;
+        match token {
+            Token::Open => {
+                let filter = self.or()?;
+                match self.tokens.get(self.next) {
+                    Some(Token::Close) => {
+                        self.next += 1;
+                        Ok(filter)
+                    }
+                    Some(token) => Err(FilterError::Unexpected(token.source())),
+                    None => Err(FilterError::UnexpectedEnd),
+                }
+            }
+            Token::Word(word, false) if [AND, OR].contains(&word.as_str()) => {
+                Err(FilterError::Unexpected(word))
+            }
+            Token::Word(word, _) => term(&word),
+            Token::Close => Err(FilterError::Unexpected(token.source())),
+        }
+    }
+}
+
+/// Return the only filter of filters, or all of them combined using f.
+fn combine(mut filters: Vec<Filter>, f: fn(Vec<Filter>) -> Filter) -> Filter {
+    if filters.len() == 1 {
+        filters.remove(0)
+    } else {
+        f(filters)
+    }
+}
+
+/// Split a query into parentheses and words, removing quotes.
+fn tokenize(query: &str) -> Result<Vec<Token>, FilterError> {
+    let mut tokens = Vec::new();
+    let mut chars = query.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '(' => tokens.push(Token::Open),
+            ')' => tokens.push(Token::Close),
+            c => {
+                let mut word = String::new();
+                let mut quoted = false;
+                let mut in_quotes = c == '"';
+                if in_quotes {
+                    quoted = true;
+                } else {
+                    word.push(c);
+                }
+                while let Some(&c) = chars.peek() {
+                    if !in_quotes && (c.is_whitespace() || c == '(' || c == ')') {
+                        break;
+                    }
+                    chars.next();
+                    if c == '"' {
+                        in_quotes = !in_quotes;
+                        quoted = true;
+                    } else {
+                        word.push(c);
+                    }
+                }
+                if in_quotes {
+                    return Err(FilterError::UnclosedQuote);
+                }
+                tokens.push(Token::Word(word, quoted));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// Parse a term, which is either a field and its value, or a word.
+fn term(word: &str) -> Result<Filter, FilterError> {
+    let (field, value) = match word.split_once(':') {
+        // Fields are lowercase, so words such as "C:/Users" aren't fields.
+        Some((field, value))
+            if !field.is_empty() && field.chars().all(|c| c.is_ascii_lowercase()) =>
+        {
+            (field, value)
+        }
+        _ => return Ok(Filter::Word(word.to_lowercase())),
+    };
+    let invalid = || FilterError::InvalidValue(field.to_string(), value.to_string());
+    match field {
+        "path" if !value.is_empty() => Ok(Filter::Path(value.to_string())),
+        "text" if !value.is_empty() => Ok(Filter::Text(value.to_lowercase())),
+        "kind" => match value {
+            "description" => Ok(Filter::Kind(None)),
+            "pattern" => Ok(Filter::Kind(Some(PatternKind::Children))),
+            "recursive" => Ok(Filter::Kind(Some(PatternKind::Recursive))),
+            "glob" => Ok(Filter::Kind(Some(PatternKind::Glob))),
+            _ => Err(invalid()),
+        },
+        "modified" => {
+            let (comparison, date) = [
+                ("<=", Comparison::AtMost),
+                (">=", Comparison::AtLeast),
+                ("<", Comparison::Before),
+                (">", Comparison::After),
+                ("=", Comparison::On),
+            ]
+            .iter()
+            .find_map(|(op, c)| value.strip_prefix(op).map(|date| (*c, date)))
+            .unwrap_or((Comparison::On, value));
+            if is_date(date) {
+                Ok(Filter::Modified(comparison, date.to_string()))
+            } else {
+                Err(invalid())
+            }
+        }
+        "path" | "text" => Err(invalid()),
+        _ => Err(FilterError::UnknownField(field.to_string())),
+    }
+}
+
+/// Return true if s is a date written as YYYY-MM-DD.
+fn is_date(s: &str) -> bool {
+    s.len() == 10
+        && s.char_indices().all(|(i, c)| match i {
+            4 | 7 => c == '-',
+            _ => c.is_ascii_digit(),
+        })
+}
+
+/// Return true if text contains the lowercase word, ignoring case.
+fn contains(text: &str, word: &str) -> bool {
+    text.to_lowercase().contains(word)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_test() {
+        for (query, filter) in [
+            ("", Ok(Filter::All)),
+            ("Web", Ok(Filter::Word("web".to_string()))),
+            (
+                "a b OR NOT c",
+                Ok(Filter::Or(vec![
+                    Filter::And(vec![
+                        Filter::Word("a".to_string()),
+                        Filter::Word("b".to_string()),
+                    ]),
+                    Filter::Not(Box::new(Filter::Word("c".to_string()))),
+                ])),
+            ),
+            (
+                "path:/var/** AND (kind:glob OR text:\"web server\")",
+                Ok(Filter::And(vec![
+                    Filter::Path("/var/**".to_string()),
+                    Filter::Or(vec![
+                        Filter::Kind(Some(PatternKind::Glob)),
+                        Filter::Text("web server".to_string()),
+                    ]),
+                ])),
+            ),
+            (
+                "modified:>=2024-01-01",
+                Ok(Filter::Modified(
+                    Comparison::AtLeast,
+                    "2024-01-01".to_string(),
+                )),
+            ),
+            (
+                "C:/Users \"OR\"",
+                Ok(Filter::And(vec![
+                    Filter::Word("c:/users".to_string()),
+                    Filter::Word("or".to_string()),
+                ])),
+            ),
+            (
+                "owner:me",
+                Err(FilterError::UnknownField("owner".to_string())),
+            ),
+            (
+                "modified:yesterday",
+                Err(FilterError::InvalidValue(
+                    "modified".to_string(),
+                    "yesterday".to_string(),
+                )),
+            ),
+            ("a OR", Err(FilterError::UnexpectedEnd)),
+            ("(a", Err(FilterError::UnexpectedEnd)),
+            ("a)", Err(FilterError::Unexpected(")".to_string()))),
+            ("AND a", Err(FilterError::Unexpected("AND".to_string()))),
+            ("text:\"web", Err(FilterError::UnclosedQuote)),
+        ]
+        .iter()
+        {
+            assert_eq!(Filter::parse(query), *filter, "{}", query);
+        }
+    }
+
+    #[test]
+    fn matches_test() {
+        let entries = [
+            Entry {
+                path: "/srv",
+                description: "* is served.",
+                pattern: Some(PatternKind::Children),
+            },
+            Entry {
+                path: "/srv/www",
+                description: "Web root.",
+                pattern: None,
+            },
+            Entry {
+                path: "*.log",
+                description: "A log file.",
+                pattern: Some(PatternKind::Glob),
+            },
+        ];
+        for (query, paths) in [
+            ("", vec!["/srv", "/srv/www", "*.log"]),
+            ("path:/srv", vec!["/srv", "/srv/www"]),
+            ("path:/srv/*", vec!["/srv/www"]),
+            ("SRV NOT kind:pattern", vec!["/srv/www"]),
+            ("text:log OR web", vec!["/srv/www", "*.log"]),
+        ]
+        .iter()
+        {
+            let filter = Filter::parse(query).unwrap();
+            let found: Vec<&str> = entries
+                .iter()
+                .filter(|e| filter.matches(e))
+                .map(|e| e.path)
+                .collect();
+            assert_eq!(found, *paths, "{}", query);
+        }
+
+        let dir = std::env::temp_dir().to_string_lossy().to_string();
+        let entry = Entry {
+            path: &dir,
+            description: "Temporary files.",
+            pattern: None,
+        };
+        for (query, matches) in [
+            ("modified:>1970-01-01", true),
+            ("modified:<=1970-01-01", false),
+        ]
+        .iter()
+        {
+            assert_eq!(Filter::parse(query).unwrap().matches(&entry), *matches);
+        }
+    }
+}
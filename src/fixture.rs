@@ -0,0 +1,263 @@
+//! Fixtures: directory trees along with configs describing them, generated
+//! from a declarative spec, for testing def and tools integrating with it.
+//!
+//! A spec lists the files of the tree, and the config describing it in the
+//! layout of local files (see `def::local`), with keys relative to the
+//! tree's root. Specs can be written in any format of `def::format`, chosen
+//! by extension, so YAML specs need the "yaml" feature, and TOML ones the
+//! "toml" feature (a default one). For example, in JSON:
+//!
+//! ```json
+//! {
+//!   "tree": [
+//!     "src/",
+//!     "src/main.rs",
+//!     {"path": "notes.md", "contents": "# Notes"},
+//!     {"path": "data.bin", "size": 1536}
+//!   ],
+//!   "config": {
+//!     "descriptions": {".": "A project.", "src": "Source code."},
+//!     "patterns": {"src": "{name} is a {type} of {size}."},
+//!     "globs": {"*.md": "Notes."}
+//!   }
+//! }
+//! ```
+//!
+//! Paths ending with "/" are directories, and parents of files are created
+//! as needed. A generated fixture is a directory holding the tree, in "root",
+//! and its config, "config.json".
+
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::settings::Settings;
+use crate::{format, local, Describer, Error};
+
+/// Name of the directory holding the tree of a fixture.
+const ROOT_DIR: &str = "root";
+
+/// Name of the config file of a fixture.
+const CONFIG_FILE: &str = "config.json";
+
+/// Spec declares a fixture.
+#[derive(Deserialize, Debug, Default, PartialEq, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct Spec {
+    /// Files and directories of the tree.
+    pub tree: Vec<Node>,
+    /// The config, laid out like a local file, or None for an empty config.
+    pub config: Option<Value>,
+}
+
+/// Node is a file or a directory of a fixture's tree.
+#[derive(Deserialize, Debug, PartialEq, Clone)]
+#[serde(untagged)]
+pub enum Node {
+    /// A path relative to the root: a directory if it ends with "/", and an
+    /// empty file otherwise.
+    Path(String),
+    /// A file.
+    File {
+        /// Path of the file, relative to the root.
+        path: String,
+        /// Contents of the file.
+        #[serde(default)]
+        contents: Option<String>,
+        /// Size of the file, filled with zeros, if contents aren't given.
+        #[serde(default)]
+        size: Option<u64>,
+    },
+}
+
+/// Fixture is a generated directory tree and config.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Fixture {
+    /// Directory holding the tree and the config.
+    pub dir: PathBuf,
+}
+
+impl Fixture {
+    /// Return the root of the tree.
+    pub fn root(&self) -> PathBuf {
+        self.dir.join(ROOT_DIR)
+    }
+
+    /// Return the path of the config file.
+    pub fn config_file(&self) -> PathBuf {
+        self.dir.join(CONFIG_FILE)
+    }
+
+    /// Return settings that use the fixture's config.
+    pub fn settings(&self) -> Settings {
+        Settings {
+            config_file: Some(self.config_file().to_string_lossy().to_string()),
+            ..Settings::default()
+        }
+    }
+
+    /// Return the describer of the fixture's config.
+    pub fn describer(&self) -> Result<Describer, Error> {
+        Describer::load(self.config_file(), &format::Json)
+    }
+
+    /// Delete the fixture's directory.
+    pub fn remove(self) -> Result<(), Error> {
+        Ok(fs::remove_dir_all(self.dir)?)
+    }
+}
+
+/// Read the spec at path, in the format matching its extension.
+pub fn read_spec<P: AsRef<Path>>(path: P) -> Result<Spec, Error> {
+    let path = path.as_ref();
+    let format = format::from_path(path).ok_or_else(|| {
+        Error::InvalidFixture(format!("unsupported format of {}", path.display()))
+    })?;
+    let value = format
+        .decode(&fs::read(path)?)
+        .map_err(Error::InvalidFixture)?;
+    serde_json::from_value(value).map_err(|e| Error::InvalidFixture(e.to_string()))
+}
+
+/// Return a new directory for a fixture, under the temporary directory.
+pub fn temp_dir() -> PathBuf {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    std::env::temp_dir().join(format!("def-fixture-{}-{}", std::process::id(), nanos))
+}
+
+/// Generate the fixture of spec in dir, which is created if it doesn't
+/// exist. An error is returned if a path of the tree isn't relative to the
+/// root, or leaves it using "..".
+pub fn generate<P: AsRef<Path>>(spec: &Spec, dir: P) -> Result<Fixture, Error> {
+    let fixture = Fixture {
+        dir: dir.as_ref().to_path_buf(),
+    };
+    let root = fixture.root();
+    fs::create_dir_all(&root)?;
+    for node in spec.tree.iter() {
+        let (path, contents) = match node {
+            Node::Path(path) => (path, None),
+            Node::File {
+                path,
+                contents,
+                size,
+            } => {
+                let contents = match (contents, size) {
+                    (Some(contents), _) => contents.as_bytes().to_vec(),
+                    (None, Some(size)) => vec![0; *size as usize],
+                    (None, None) => Vec::new(),
+                };
+                (path, Some(contents))
+            }
+        };
+        let relative = Path::new(path);
+        let is_inside = relative
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+        if path.is_empty() || !is_inside {
+            return Err(Error::InvalidFixture(format!(
+                "{} isn't a path under the root",
+                path
+            )));
+        }
+
+        let file = root.join(relative);
+        match contents {
+            None if path.ends_with('/') => fs::create_dir_all(&file)?,
+            contents => {
+                if let Some(parent) = file.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(&file, contents.unwrap_or_default())?;
+            }
+        }
+    }
+
+    let mut describer = match &spec.config {
+        Some(config) => {
+            Describer::from_versioned_value(config.clone())
+                .map_err(|e| Error::InvalidFixture(e.to_string()))?
+                .0
+        }
+        None => Describer::new(),
+    };
+    let base = root
+        .to_str()
+        .ok_or_else(|| Error::InvalidPath(root.to_string_lossy().to_string()))?;
+    describer.map_keys(|key| local::absolute_key(key, base));
+    describer.save(fixture.config_file(), &format::Json)?;
+    Ok(fixture)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_test() {
+        let spec = read_spec(
+            Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("testdata")
+                .join("fixture.json"),
+        )
+        .unwrap();
+        let fixture = generate(&spec, temp_dir()).unwrap();
+        let root = fixture.root().to_string_lossy().to_string();
+        let describer = fixture.describer().unwrap();
+        let notes = fs::read_to_string(fixture.root().join("notes.md"));
+        let is_dir = fixture.root().join("src").join("empty").is_dir();
+        let described: Vec<_> = [
+            "",
+            "/src",
+            "/src/main.rs",
+            "/src/empty",
+            "/data.bin",
+            "/notes.md",
+        ]
+        .iter()
+        .map(|path| describer.describe_with_fs(format!("{}{}", root, path).as_str()))
+        .collect();
+        fixture.remove().unwrap();
+
+        for ((path, description), described) in [
+            ("", "A project."),
+            ("/src", "Source code."),
+            ("/src/main.rs", "main.rs is a file of 0 B."),
+            ("/src/empty", "empty is a directory of 0 entries."),
+            ("/data.bin", "data.bin is a file of 1.5 KiB."),
+            ("/notes.md", "Notes."),
+        ]
+        .iter()
+        .zip(described.iter())
+        {
+            assert_eq!(described.as_deref(), Some(*description), "{}", path);
+        }
+        assert_eq!(notes.unwrap(), "# Notes");
+        assert!(is_dir);
+    }
+
+    #[test]
+    fn invalid_test() {
+        for (json, valid) in [
+            (r#"{"tree": ["a/b/", "./c"]}"#, true),
+            (r#"{"tree": ["../escaped"]}"#, false),
+            (r#"{"tree": ["/absolute"]}"#, false),
+            (r#"{"tree": [{"path": "a/../../b", "size": 1}]}"#, false),
+        ]
+        .iter()
+        {
+            let spec: Spec = serde_json::from_str(json).unwrap();
+            let dir = temp_dir();
+            let result = generate(&spec, &dir);
+            fs::remove_dir_all(&dir).unwrap();
+            assert_eq!(result.is_ok(), *valid, "{}", json);
+        }
+        assert!(serde_json::from_str::<Spec>(r#"{"files": []}"#).is_err());
+    }
+}
@@ -0,0 +1,267 @@
+//! Serialization formats of describers.
+//!
+//! A `Format` encodes a describer as bytes, and decodes bytes into a value
+//! from which a describer is created (after being migrated, see
+//! `def::migrate`). Describers can be encoded as JSON, and as TOML, YAML or
+//! MessagePack with "toml" (enabled by default), "yaml" or "msgpack"
+//! features. Formats are chosen by name
+//! (see `from_name`) or by the extension of a file (see `from_path`).
+
+use std::path::Path;
+
+use serde_json::Value;
+
+use crate::Describer;
+
+/// Format encodes and decodes describers.
+pub trait Format: Sync {
+    /// Name of the format, such as "json".
+    fn name(&self) -> &'static str;
+
+    /// Extensions of files of the format, without a dot.
+    fn extensions(&self) -> &'static [&'static str];
+
+    /// Encode describer as bytes.
+    fn encode(&self, describer: &Describer) -> Result<Vec<u8>, String>;
+
+    /// Decode bytes into a value, to create a describer from.
+    fn decode(&self, bytes: &[u8]) -> Result<Value, String>;
+}
+
+/// Json is the JSON format, encoded pretty.
+pub struct Json;
+
+impl Format for Json {
+    fn name(&self) -> &'static str {
+        "json"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["json"]
+    }
+
+    fn encode(&self, describer: &Describer) -> Result<Vec<u8>, String> {
+        serde_json::to_vec_pretty(describer).map_err(|e| e.to_string())
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Value, String> {
+        serde_json::from_slice(bytes).map_err(|e| e.to_string())
+    }
+}
+
+/// Toml is the TOML format.
+#[cfg(feature = "toml")]
+pub struct Toml;
+
+#[cfg(feature = "toml")]
+impl Format for Toml {
+    fn name(&self) -> &'static str {
+        "toml"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["toml"]
+    }
+
+    fn encode(&self, describer: &Describer) -> Result<Vec<u8>, String> {
+        toml::to_string_pretty(describer)
+            .map(String::into_bytes)
+            .map_err(|e| e.to_string())
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Value, String> {
+        let text = std::str::from_utf8(bytes).map_err(|e| e.to_string())?;
+        toml::from_str(text).map_err(|e| e.to_string())
+    }
+}
+
+/// Yaml is the YAML format.
+#[cfg(feature = "yaml")]
+pub struct Yaml;
+
+#[cfg(feature = "yaml")]
+impl Format for Yaml {
+    fn name(&self) -> &'static str {
+        "yaml"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["yaml", "yml"]
+    }
+
+    fn encode(&self, describer: &Describer) -> Result<Vec<u8>, String> {
+        serde_yaml::to_string(describer)
+            .map(String::into_bytes)
+            .map_err(|e| e.to_string())
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Value, String> {
+        serde_yaml::from_slice(bytes).map_err(|e| e.to_string())
+    }
+}
+
+/// MsgPack is the MessagePack format, encoded with field names so that it
+/// can be migrated like the other formats.
+#[cfg(feature = "msgpack")]
+pub struct MsgPack;
+
+#[cfg(feature = "msgpack")]
+impl Format for MsgPack {
+    fn name(&self) -> &'static str {
+        "msgpack"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["msgpack", "mp"]
+    }
+
+    fn encode(&self, describer: &Describer) -> Result<Vec<u8>, String> {
+        rmp_serde::to_vec_named(describer).map_err(|e| e.to_string())
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Value, String> {
+        rmp_serde::from_slice(bytes).map_err(|e| e.to_string())
+    }
+}
+
+/// Return the formats supported by this build.
+pub fn formats() -> Vec<&'static dyn Format> {
+    vec![
+        &Json,
+        #[cfg(feature = "toml")]
+        &Toml,
+        #[cfg(feature = "yaml")]
+        &Yaml,
+        #[cfg(feature = "msgpack")]
+        &MsgPack,
+    ]
+}
+
+/// Return the format with the given name, or None if there's no such format
+/// (or it isn't supported by this build).
+pub fn from_name(name: &str) -> Option<&'static dyn Format> {
+    let name = name.to_lowercase();
+    formats().into_iter().find(|f| f.name() == name)
+}
+
+/// Return the format matching extension of the given path, or None if
+/// there's none.
+pub fn from_path<P: AsRef<Path>>(path: P) -> Option<&'static dyn Format> {
+    let ext = path.as_ref().extension()?.to_str()?.to_lowercase();
+    formats()
+        .into_iter()
+        .find(|f| f.extensions().contains(&ext.as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PatternKind;
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+
+    /// Environment variable which, if set, makes golden tests rewrite golden
+    /// files instead of comparing against them.
+    const UPDATE_GOLDEN: &str = "DEF_UPDATE_GOLDEN";
+
+    /// Return the path of a golden file.
+    fn golden_path(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("testdata")
+            .join(name)
+    }
+
+    /// Assert that bytes are those of the golden file name, or rewrite the
+    /// file if UPDATE_GOLDEN is set.
+    fn assert_golden(name: &str, bytes: &[u8]) {
+        let path = golden_path(name);
+        if env::var_os(UPDATE_GOLDEN).is_some() {
+            fs::write(&path, bytes).unwrap();
+            return;
+        }
+        let golden = fs::read(&path).unwrap();
+        assert!(
+            golden == bytes,
+            "{} changed, rerun with {}=1 and review the diff",
+            name,
+            UPDATE_GOLDEN
+        );
+    }
+
+    /// Decode and migrate a describer from the golden file name.
+    fn load_golden(name: &str, format: &dyn Format) -> Describer {
+        let bytes = fs::read(golden_path(name)).unwrap();
+        Describer::from_versioned_value(format.decode(&bytes).unwrap())
+            .unwrap()
+            .0
+    }
+
+    #[test]
+    fn from_path_test() {
+        for (path, name) in [
+            ("/config.json", Some("json")),
+            (
+                "/config.TOML",
+                Some("toml").filter(|_| cfg!(feature = "toml")),
+            ),
+            (
+                "/config.yml",
+                Some("yaml").filter(|_| cfg!(feature = "yaml")),
+            ),
+            (
+                "/config.mp",
+                Some("msgpack").filter(|_| cfg!(feature = "msgpack")),
+            ),
+            ("/config.db", None),
+            ("/config", None),
+        ]
+        .iter()
+        {
+            assert_eq!(from_path(path).map(|f| f.name()), *name, "{}", path);
+        }
+    }
+
+    #[test]
+    fn round_trip_test() {
+        let mut d = Describer::new();
+        d.add_description("/path/to/dir", "A directory.");
+        d.add_pattern_with_kind("/path", "** is under /path.", PatternKind::Recursive)
+            .unwrap();
+        d.add_glob("*.log", "A log file.").unwrap();
+        d.add_description("/trashed", "Trashed.");
+        d.trash_description("/trashed");
+
+        for format in formats() {
+            let bytes = format.encode(&d).unwrap();
+            let (loaded, _) =
+                Describer::from_versioned_value(format.decode(&bytes).unwrap()).unwrap();
+            for path in ["/path/to/dir", "/path/a/b", "/var/x.log"].iter() {
+                assert_eq!(loaded.describe(path), d.describe(path), "{}", format.name());
+            }
+            assert_eq!(loaded.trashed(), d.trashed(), "{}", format.name());
+        }
+    }
+
+    #[test]
+    fn golden_migration_test() {
+        let current = format!("v{}.json", crate::migrate::CURRENT_VERSION);
+        for version in 0..crate::migrate::CURRENT_VERSION {
+            let migrated = load_golden(&format!("v{}.json", version), &Json);
+            assert_golden(&current, &Json.encode(&migrated).unwrap());
+        }
+    }
+
+    #[test]
+    fn golden_test() {
+        let version = crate::migrate::CURRENT_VERSION;
+        let expected = load_golden(&format!("v{}.json", version), &Json);
+        for format in formats() {
+            let name = format!("v{}.{}", version, format.extensions()[0]);
+            assert_golden(&name, &format.encode(&expected).unwrap());
+
+            let loaded = load_golden(&name, format);
+            assert_golden(&name, &format.encode(&loaded).unwrap());
+        }
+    }
+}
@@ -0,0 +1,155 @@
+//! Git repositories of described paths.
+//!
+//! Patterns can contain placeholders filled in from the git repository a
+//! path is in (see `def::template`), and the root of a repository is
+//! described by `REPO_PATTERN` unless something else describes it. Both are
+//! used only when describing paths with the file system (see
+//! `Describer::describe_with_fs`).
+
+use std::path::Path;
+
+use git2::Repository;
+
+use crate::meta::RepoInfo;
+
+/// Pattern describing roots of git repositories which aren't described
+/// otherwise.
+pub const REPO_PATTERN: &str = "Git repository on {git_branch}, remote {git_remote}.";
+
+/// Name of the remote preferred for `{git_remote}`.
+const PREFERRED_REMOTE: &str = "origin";
+
+/// Number of characters of abbreviated commit hashes.
+const SHORT_HASH_LEN: usize = 7;
+
+/// Return information about the git repository path is in, or None if it
+/// isn't in one.
+pub fn read<P: AsRef<Path>>(path: P) -> Option<RepoInfo> {
+    let repo = Repository::discover(path).ok()?;
+    let head = repo.head().ok();
+    let branch = match &head {
+        Some(head) if head.is_branch() => head.shorthand().map(str::to_string),
+        Some(_) => Some("detached HEAD".to_string()),
+        // The branch of a repository without commits is only named by HEAD.
+        None => repo
+            .find_reference("HEAD")
+            .ok()
+            .and_then(|r| r.symbolic_target().map(str::to_string))
+            .map(|target| target.trim_start_matches("refs/heads/").to_string()),
+    };
+    let last_commit = head
+        .and_then(|head| head.peel_to_commit().ok())
+        .map(|commit| {
+            let hash = commit.id().to_string();
+            let hash = &hash[..SHORT_HASH_LEN.min(hash.len())];
+            match commit.summary() {
+                Some(summary) => format!("{} {}", hash, summary),
+                None => hash.to_string(),
+            }
+        });
+    Some(RepoInfo {
+        branch,
+        remote: remote_url(&repo),
+        last_commit,
+    })
+}
+
+/// Return true if path is the root of a git repository (or of a worktree).
+pub fn is_repo_root<P: AsRef<Path>>(path: P) -> bool {
+    path.as_ref().join(".git").exists()
+}
+
+/// Return the URL of the preferred remote of repo, or of its first remote.
+fn remote_url(repo: &Repository) -> Option<String> {
+    let url = |name: &str| {
+        repo.find_remote(name)
+            .ok()
+            .and_then(|remote| remote.url().map(str::to_string))
+    };
+    url(PREFERRED_REMOTE).or_else(|| {
+        let names = repo.remotes().ok()?;
+        let first = names.iter().flatten().next()?;
+        url(first)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::Signature;
+    use std::fs;
+
+    #[test]
+    fn read_test() {
+        let dir = std::env::temp_dir().join(format!("def-git-test-{}", std::process::id()));
+        let repo = Repository::init(&dir).unwrap();
+        fs::create_dir_all(dir.join("src")).unwrap();
+        let empty = read(dir.join("src"));
+        let is_root = (is_repo_root(&dir), is_repo_root(dir.join("src")));
+
+        repo.remote("upstream", "https://example.com/upstream.git")
+            .unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        let signature = Signature::now("Tester", "tester@example.com").unwrap();
+        let commit = repo
+            .commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                "Add nothing\n\nLonger message.",
+                &tree,
+                &[],
+            )
+            .unwrap();
+        let committed = read(dir.join("src"));
+        repo.remote("origin", "git@example.com:def.git").unwrap();
+        let with_origin = read(&dir);
+        let root = dir.to_string_lossy().to_string();
+        let mut d = crate::Describer::new();
+        d.add_pattern(&root, "* is on {git_branch}.").unwrap();
+        let described = (
+            d.describe_with_fs(root.as_str()),
+            d.describe_with_fs(format!("{}/src", root).as_str()),
+            d.describe(format!("{}/src", root).as_str()),
+        );
+        repo.set_head_detached(commit).unwrap();
+        let detached = read(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+
+        let branch = empty.as_ref().and_then(|info| info.branch.clone());
+        assert!(branch.is_some());
+        assert_eq!(empty.unwrap().last_commit, None);
+        assert_eq!(is_root, (true, false));
+        let committed = committed.unwrap();
+        assert_eq!(committed.branch, branch);
+        assert_eq!(
+            committed.remote.as_deref(),
+            Some("https://example.com/upstream.git")
+        );
+        let hash = commit.to_string();
+        assert_eq!(
+            committed.last_commit,
+            Some(format!("{} Add nothing", &hash[..SHORT_HASH_LEN]))
+        );
+        assert_eq!(
+            with_origin.unwrap().remote.as_deref(),
+            Some("git@example.com:def.git")
+        );
+        assert_eq!(detached.unwrap().branch.as_deref(), Some("detached HEAD"));
+        let branch = branch.unwrap();
+        assert_eq!(
+            described,
+            (
+                Some(format!(
+                    "Git repository on {}, remote git@example.com:def.git.",
+                    branch
+                )),
+                Some(format!("src is on {}.", branch)),
+                Some("src is on {git_branch}.".to_string()),
+            )
+        );
+        assert_eq!(read(std::env::temp_dir().join("def-not-a-repo")), None);
+    }
+}
@@ -0,0 +1,143 @@
+//! Matching of paths against glob patterns.
+//!
+//! Supported wildcards are `*` which matches any characters except a separator,
+//! `?` which matches a single character except a separator, and `**` which
+//! matches any characters including separators. A glob that doesn't contain a
+//! separator (such as `*.log`) is matched against the last component of a path.
+
+use crate::{paths, SEPERATOR};
+
+/// Return true if path matches the given glob.
+///
+/// # Examples
+///
+/// ```
+/// use def::glob::matches;
+///
+/// assert!(matches("/home/*/Downloads", "/home/user/Downloads"));
+/// assert!(!matches("/home/*/Downloads", "/home/user/a/Downloads"));
+/// assert!(matches("/home/**/Downloads", "/home/user/a/Downloads"));
+/// assert!(matches("*.log", "/var/log/syslog.log"));
+/// ```
+pub fn matches(glob: &str, path: &str) -> bool {
+    let path = if glob.contains(SEPERATOR) {
+        path
+    } else {
+        path.rsplit(SEPERATOR).next().unwrap_or(path)
+    };
+
+    matches_from(glob, path)
+}
+
+/// Return true if the given string contains a wildcard, and so should be
+/// treated as a glob rather than a literal path.
+pub fn is_glob(s: &str) -> bool {
+    s.contains(['*', '?'])
+}
+
+/// Return the number of literal (non-wildcard) characters in a glob, which
+/// is used to rank globs matching the same path. Globs with more literal
+/// characters are more specific.
+pub fn specificity(glob: &str) -> usize {
+    glob.chars().filter(|c| *c != '*' && *c != '?').count()
+}
+
+/// Return the directory a glob is anchored to, made of its components before
+/// the first one with a wildcard, except the last one. For example, the
+/// prefix of `/home/*/Downloads` is "/home", and that of `/var/log/syslog` is
+/// "/var/log". Globs without a separator (such as `*.log`) match paths
+/// anywhere, and have an empty prefix.
+pub fn prefix(glob: &str) -> &str {
+    let literal = glob.find(['*', '?']).unwrap_or(glob.len());
+    match glob[..literal].rfind(SEPERATOR) {
+        Some(i) => &glob[..i.max(paths::root_len(glob).unwrap_or(0))],
+        None => "",
+    }
+}
+
+/// Recursively match path against glob. Both are matched in place, without
+/// copying, as globs are matched against every described path.
+fn matches_from(glob: &str, path: &str) -> bool {
+    let mut glob_chars = glob.chars();
+    let mut path_chars = path.chars();
+    match glob_chars.next() {
+        None => path.is_empty(),
+        Some('*') if glob_chars.as_str().starts_with('*') => {
+            let rest = &glob_chars.as_str()[1..];
+            boundaries(path).any(|i| matches_from(rest, &path[i..]))
+        }
+        Some('*') => {
+            let rest = glob_chars.as_str();
+            let limit = path.find(SEPERATOR).unwrap_or(path.len());
+            boundaries(&path[..limit]).any(|i| matches_from(rest, &path[i..]))
+        }
+        Some('?') => match path_chars.next() {
+            Some(c) if c != SEPERATOR => matches_from(glob_chars.as_str(), path_chars.as_str()),
+            _ => false,
+        },
+        Some(g) => match path_chars.next() {
+            Some(c) if c == g => matches_from(glob_chars.as_str(), path_chars.as_str()),
+            _ => false,
+        },
+    }
+}
+
+/// Return the indices of the characters of s, followed by its length.
+fn boundaries(s: &str) -> impl Iterator<Item = usize> + '_ {
+    s.char_indices()
+        .map(|(i, _)| i)
+        .chain(std::iter::once(s.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_test() {
+        for (glob, path, is_match) in [
+            ("/home/*/Downloads", "/home/user/Downloads", true),
+            ("/home/*/Downloads", "/home/user/Documents", false),
+            ("/home/*/Downloads", "/home/a/b/Downloads", false),
+            ("/home/**/Downloads", "/home/a/b/Downloads", true),
+            ("/home/**", "/home/a/b", true),
+            ("/home/**/x", "/home/x", false),
+            ("/home/**x", "/home/x", true),
+            ("*.log", "/var/log/syslog.log", true),
+            ("*.log", "/var/log.d/syslog", false),
+            ("*.log", "syslog.log", true),
+            ("file?.txt", "/tmp/file1.txt", true),
+            ("file?.txt", "/tmp/file10.txt", false),
+            ("/a/?", "/a//", false),
+            ("/exact/path", "/exact/path", true),
+            ("/exact/path", "/exact/path/child", false),
+            ("*", "/anything", true),
+        ]
+        .iter()
+        {
+            assert_eq!(matches(glob, path), *is_match, "{} {}", glob, path);
+        }
+    }
+
+    #[test]
+    fn specificity_test() {
+        assert!(specificity("/home/*/Downloads") > specificity("/home/**"));
+        assert_eq!(specificity("*.log"), 4);
+    }
+
+    #[test]
+    fn prefix_test() {
+        for (glob, prefix) in [
+            ("/home/*/Downloads", "/home"),
+            ("/home/**", "/home"),
+            ("/var/log/syslog", "/var/log"),
+            ("/*.log", "/"),
+            ("*.log", ""),
+            ("C:/**/*.log", "C:/"),
+        ]
+        .iter()
+        {
+            assert_eq!(super::prefix(glob), *prefix, "{}", glob);
+        }
+    }
+}
@@ -0,0 +1,1285 @@
+use crate::command::*;
+use crate::i18n::tr;
+
+/// Width help messages are wrapped to.
+const WIDTH: usize = 84;
+
+/// Column summaries of sub-commands start at in the full help message.
+const SUMMARY_COLUMN: usize = 36;
+
+/// Indentation of continued lines of the synopsis.
+const SYNOPSIS_INDENT: usize = 8;
+
+/// Width the synopsis is wrapped to.
+const SYNOPSIS_WIDTH: usize = 72;
+
+/// Command describes a sub-command, and is used to generate help messages.
+/// Texts meant for users are given by their keys in the message catalog (see
+/// i18n), so that help is printed in the selected language.
+pub struct Command {
+    /// Name of the sub-command, empty for describing a path.
+    pub name: &'static str,
+    /// Arguments of the sub-command in the short help message, with optional
+    /// ones in brackets.
+    pub synopsis: &'static str,
+    /// Ways the sub-command can be used.
+    pub usages: &'static [Usage],
+    /// Flags the sub-command accepts.
+    pub flags: &'static [Flag],
+    /// Key of a longer explanation, shown by "def help <command>", or empty
+    /// if there's none.
+    pub details: &'static str,
+    /// Example command lines, shown by "def help <command>".
+    pub examples: &'static [&'static str],
+}
+
+/// Usage is a way of using a sub-command.
+pub struct Usage {
+    /// Arguments following the sub-command's name.
+    pub args: &'static str,
+    /// Key of what the sub-command does when used this way.
+    pub summary: &'static str,
+}
+
+/// Flag is a flag accepted by a sub-command.
+pub struct Flag {
+    pub name: &'static str,
+    /// Name of the flag's value, if it takes one.
+    pub value: Option<&'static str>,
+    /// Key of what the flag does.
+    pub summary: &'static str,
+}
+
+/// def's sub-commands, in the order they're listed in help messages.
+pub const COMMANDS: &[Command] = &[
+    Command {
+        name: "",
+        synopsis: "[--quiet | --refresh] <path> | --stdin | --refresh",
+        usages: &[
+            Usage {
+                args: "<path>",
+                summary: "help-describe-usage-1",
+            },
+            Usage {
+                args: "--stdin",
+                summary: "help-describe-usage-2",
+            },
+            Usage {
+                args: "--refresh [<path>]",
+                summary: "help-describe-usage-3",
+            },
+        ],
+        flags: &[
+            Flag {
+                name: STDIN_FLAG,
+                value: None,
+                summary: "help-describe-stdin",
+            },
+            Flag {
+                name: QUIET_FLAG,
+                value: None,
+                summary: "help-describe-quiet",
+            },
+            Flag {
+                name: REFRESH_FLAG,
+                value: None,
+                summary: "help-describe-refresh",
+            },
+        ],
+        details: "",
+        examples: &[
+            "def ~/notes",
+            "def --stdin",
+            "def --quiet ~/notes",
+            "def --allow-commands --refresh ~/src/def",
+        ],
+    },
+    Command {
+        name: ADD_COMMAND,
+        synopsis: "[--local | --append | --prepend] <path> <description> [--tag <tag>]...",
+        usages: &[
+            Usage {
+                args: "<path> <description> [--tag <tag>]... [--private]",
+                summary: "help-add-usage-1",
+            },
+            Usage {
+                args: "--append | --prepend <path> <description>",
+                summary: "help-add-usage-2",
+            },
+            Usage {
+                args: "--local <path> <description>",
+                summary: "help-add-usage-3",
+            },
+            Usage {
+                args: "<path> --from-command <cmd>",
+                summary: "help-add-usage-4",
+            },
+        ],
+        flags: &[
+            Flag {
+                name: LOCAL_FLAG,
+                value: None,
+                summary: "help-add-local",
+            },
+            Flag {
+                name: TAG_FLAG,
+                value: Some("<tag>"),
+                summary: "help-add-tag",
+            },
+            Flag {
+                name: PRIVATE_FLAG,
+                value: None,
+                summary: "help-add-private",
+            },
+            Flag {
+                name: APPEND_FLAG,
+                value: None,
+                summary: "help-add-append",
+            },
+            Flag {
+                name: PREPEND_FLAG,
+                value: None,
+                summary: "help-add-prepend",
+            },
+            Flag {
+                name: FORCE_FLAG,
+                value: None,
+                summary: "help-add-force",
+            },
+            Flag {
+                name: FROM_COMMAND_FLAG,
+                value: Some("<cmd>"),
+                summary: "help-add-from-command",
+            },
+        ],
+        details: "help-add-details",
+        examples: &[
+            "def add ~/notes \"Personal notes.\"",
+            "def add --append ~/notes \"Synced to my phone.\"",
+            "def add ~/work/old \"Old projects.\" --tag work --tag archived",
+            "def add --private ~/work/review \"Notes for my review.\"",
+            "def add --local src \"Source code.\"",
+            "def add --allow-commands ~/src/def --from-command \"git log -1 --format=%s\"",
+        ],
+    },
+    Command {
+        name: PATTERN_COMMAND,
+        synopsis: "[--recursive | --<type>-only] [--priority <n>] <dir> <description>",
+        usages: &[
+            Usage {
+                args: "<path> <description>",
+                summary: "help-pattern-usage-1",
+            },
+            Usage {
+                args: "--recursive <path> <description>",
+                summary: "help-pattern-usage-2",
+            },
+            Usage {
+                args: "--files-only <path> <description>",
+                summary: "help-pattern-usage-3",
+            },
+        ],
+        flags: &[
+            Flag {
+                name: RECURSIVE_FLAG,
+                value: None,
+                summary: "help-pattern-recursive",
+            },
+            Flag {
+                name: FILES_ONLY_FLAG,
+                value: None,
+                summary: "help-pattern-files-only",
+            },
+            Flag {
+                name: DIRS_ONLY_FLAG,
+                value: None,
+                summary: "help-pattern-dirs-only",
+            },
+            Flag {
+                name: SYMLINKS_ONLY_FLAG,
+                value: None,
+                summary: "help-pattern-symlinks-only",
+            },
+            Flag {
+                name: PRIORITY_FLAG,
+                value: Some("<n>"),
+                summary: "help-pattern-priority",
+            },
+        ],
+        details: "help-pattern-details",
+        examples: &[
+            "def pattern dir \"* is a child of dir\"",
+            "def pattern --recursive ~/src \"** is part of my code.\"",
+            "def pattern --priority 10 ~/src/vendor \"Vendored {name}.\"",
+            "def pattern --files-only ~/Downloads \"* is a downloaded file\"",
+            "def pattern --dirs-only ~/Downloads \"* was extracted from an archive\"",
+            "def pattern ~/Downloads \"* ({size}), modified {mtime}\"",
+        ],
+    },
+    Command {
+        name: GLOB_COMMAND,
+        synopsis: "[--priority <n>] <glob> <description>",
+        usages: &[Usage {
+            args: "<glob> <description>",
+            summary: "help-glob-usage-1",
+        }],
+        flags: &[Flag {
+            name: PRIORITY_FLAG,
+            value: Some("<n>"),
+            summary: "help-glob-priority",
+        }],
+        details: "help-glob-details",
+        examples: &[
+            "def glob \"*.log\" \"* is a log file.\"",
+            "def glob \"*.rs\" \"Rust source of the {stem} module.\"",
+        ],
+    },
+    Command {
+        name: EXPLAIN_COMMAND,
+        synopsis: "<path>",
+        usages: &[Usage {
+            args: "<path>",
+            summary: "help-explain-usage-1",
+        }],
+        flags: &[],
+        details: "help-explain-details",
+        examples: &["def explain ~/src/vendor/lib.rs"],
+    },
+    Command {
+        name: INFO_COMMAND,
+        synopsis: "<path>",
+        usages: &[Usage {
+            args: "<path>",
+            summary: "help-info-usage-1",
+        }],
+        flags: &[],
+        details: "help-info-details",
+        examples: &[
+            "def info /srv/backups",
+            "def --inherit info ~/src/def/src/main.rs",
+        ],
+    },
+    Command {
+        name: EDIT_COMMAND,
+        synopsis: "<path> | --all",
+        usages: &[
+            Usage {
+                args: "<path>",
+                summary: "help-edit-usage-1",
+            },
+            Usage {
+                args: "--all",
+                summary: "help-edit-usage-2",
+            },
+        ],
+        flags: &[Flag {
+            name: ALL_FLAG,
+            value: None,
+            summary: "help-edit-all",
+        }],
+        details: "help-edit-details",
+        examples: &["def edit ~/notes", "def edit --all"],
+    },
+    Command {
+        name: LIST_COMMAND,
+        synopsis: "[--depth <n>] [--where <query>] [prefix] | @<name>",
+        usages: &[
+            Usage {
+                args: "[--depth <n>] [--where <query>] [prefix]",
+                summary: "help-list-usage-1",
+            },
+            Usage {
+                args: "@<name>",
+                summary: "help-list-usage-2",
+            },
+        ],
+        flags: &[
+            Flag {
+                name: DEPTH_FLAG,
+                value: Some("<n>"),
+                summary: "help-list-depth",
+            },
+            Flag {
+                name: WHERE_FLAG,
+                value: Some("<query>"),
+                summary: "help-list-where",
+            },
+        ],
+        details: "help-list-details",
+        examples: &[
+            "def list",
+            "def list --depth 2 ~/src",
+            "def list --where \"kind:glob OR text:log\"",
+            "def list @infra",
+        ],
+    },
+    Command {
+        name: LISTS_COMMAND,
+        synopsis: "",
+        usages: &[Usage {
+            args: "",
+            summary: "help-lists-usage-1",
+        }],
+        flags: &[],
+        details: "help-lists-details",
+        examples: &["def lists"],
+    },
+    Command {
+        name: TAGGED_COMMAND,
+        synopsis: "<tag>",
+        usages: &[Usage {
+            args: "<tag>",
+            summary: "help-tagged-usage-1",
+        }],
+        flags: &[],
+        details: "help-tagged-details",
+        examples: &["def tagged work"],
+    },
+    Command {
+        name: PRIVATE_COMMAND,
+        synopsis: "<path>",
+        usages: &[Usage {
+            args: "<path>",
+            summary: "help-private-usage-1",
+        }],
+        flags: &[],
+        details: "help-private-details",
+        examples: &["def private ~/work/review"],
+    },
+    Command {
+        name: PUBLIC_COMMAND,
+        synopsis: "<path>",
+        usages: &[Usage {
+            args: "<path>",
+            summary: "help-public-usage-1",
+        }],
+        flags: &[],
+        details: "help-public-details",
+        examples: &["def public ~/work/review"],
+    },
+    Command {
+        name: LS_COMMAND,
+        synopsis: "[dir]",
+        usages: &[Usage {
+            args: "[dir]",
+            summary: "help-ls-usage-1",
+        }],
+        flags: &[],
+        details: "help-ls-details",
+        examples: &["def ls", "def ls ~/src"],
+    },
+    Command {
+        name: SEARCH_COMMAND,
+        synopsis: "[--fuzzy] [--stem] [--typos <n>] [--where <query>] <query>",
+        usages: &[
+            Usage {
+                args: "[--fuzzy] <query>",
+                summary: "help-search-usage-1",
+            },
+            Usage {
+                args: "--save <name> <query>",
+                summary: "help-search-usage-2",
+            },
+        ],
+        flags: &[
+            Flag {
+                name: FUZZY_FLAG,
+                value: None,
+                summary: "help-search-fuzzy",
+            },
+            Flag {
+                name: STEM_FLAG,
+                value: None,
+                summary: "help-search-stem",
+            },
+            Flag {
+                name: SAVE_FLAG,
+                value: Some("<name>"),
+                summary: "help-search-save",
+            },
+            Flag {
+                name: TYPOS_FLAG,
+                value: Some("<n>"),
+                summary: "help-search-typos",
+            },
+            Flag {
+                name: WHERE_FLAG,
+                value: Some("<query>"),
+                summary: "help-search-where",
+            },
+        ],
+        details: "help-search-details",
+        examples: &[
+            "def search nginx config",
+            "def search --fuzzy dwnld",
+            "def search --stem --typos 2 documnets",
+            "def search --save infra nginx config",
+            "def search --where \"path:/srv/** modified:>2024-01-01\" web",
+        ],
+    },
+    Command {
+        name: REMOVE_COMMAND,
+        synopsis: "[--pattern] <path>",
+        usages: &[
+            Usage {
+                args: "<path>",
+                summary: "help-remove-usage-1",
+            },
+            Usage {
+                args: "--pattern <path>",
+                summary: "help-remove-usage-2",
+            },
+        ],
+        flags: &[Flag {
+            name: PATTERN_FLAG,
+            value: None,
+            summary: "help-remove-pattern",
+        }],
+        details: "help-remove-details",
+        examples: &["def remove ~/notes", "def remove --pattern dir"],
+    },
+    Command {
+        name: MOVE_COMMAND,
+        synopsis: "[--recursive] [--dry-run] <old> <new>",
+        usages: &[
+            Usage {
+                args: "<old> <new>",
+                summary: "help-mv-usage-1",
+            },
+            Usage {
+                args: "--recursive <old> <new>",
+                summary: "help-mv-usage-2",
+            },
+        ],
+        flags: &[
+            Flag {
+                name: RECURSIVE_FLAG,
+                value: None,
+                summary: "help-mv-recursive",
+            },
+            Flag {
+                name: DRY_RUN_FLAG,
+                value: None,
+                summary: "help-mv-dry-run",
+            },
+        ],
+        details: "help-mv-details",
+        examples: &[
+            "def mv ~/notes.txt ~/notes.md",
+            "def mv --recursive --dry-run ~/src ~/code",
+        ],
+    },
+    Command {
+        name: TRASH_COMMAND,
+        synopsis: "[ list | restore <path> | empty ]",
+        usages: &[
+            Usage {
+                args: "list",
+                summary: "help-trash-usage-1",
+            },
+            Usage {
+                args: "restore <path>",
+                summary: "help-trash-usage-2",
+            },
+            Usage {
+                args: "empty",
+                summary: "help-trash-usage-3",
+            },
+        ],
+        flags: &[],
+        details: "help-trash-details",
+        examples: &["def trash list", "def trash restore ~/notes"],
+    },
+    Command {
+        name: DRIFT_COMMAND,
+        synopsis: "<root>",
+        usages: &[Usage {
+            args: "<root>",
+            summary: "help-drift-usage-1",
+        }],
+        flags: &[],
+        details: "help-drift-details",
+        examples: &["def drift ~/src"],
+    },
+    Command {
+        name: STATS_COMMAND,
+        synopsis: "[dir]",
+        usages: &[Usage {
+            args: "[dir]",
+            summary: "help-stats-usage-1",
+        }],
+        flags: &[],
+        details: "help-stats-details",
+        examples: &["def stats", "def stats ~/src/def"],
+    },
+    Command {
+        name: UNDO_COMMAND,
+        synopsis: "",
+        usages: &[Usage {
+            args: "",
+            summary: "help-undo-usage-1",
+        }],
+        flags: &[],
+        details: "help-undo-details",
+        examples: &["def undo"],
+    },
+    Command {
+        name: HISTORY_COMMAND,
+        synopsis: "<path>",
+        usages: &[Usage {
+            args: "<path>",
+            summary: "help-history-usage-1",
+        }],
+        flags: &[],
+        details: "help-history-details",
+        examples: &["def history ~/src/def"],
+    },
+    Command {
+        name: STALE_COMMAND,
+        synopsis: "[--days <n>]",
+        usages: &[Usage {
+            args: "[--days <n>]",
+            summary: "help-stale-usage-1",
+        }],
+        flags: &[Flag {
+            name: DAYS_FLAG,
+            value: Some("<n>"),
+            summary: "help-stale-days",
+        }],
+        details: "help-stale-details",
+        examples: &["def stale", "def stale --days 30"],
+    },
+    Command {
+        name: CHECK_COMMAND,
+        synopsis: "[--prune]",
+        usages: &[Usage {
+            args: "[--prune]",
+            summary: "help-check-usage-1",
+        }],
+        flags: &[Flag {
+            name: PRUNE_FLAG,
+            value: None,
+            summary: "help-check-prune",
+        }],
+        details: "help-check-details",
+        examples: &["def check", "def check --prune"],
+    },
+    Command {
+        name: EVENTS_COMMAND,
+        synopsis: "--follow",
+        usages: &[Usage {
+            args: "--follow",
+            summary: "help-events-usage-1",
+        }],
+        flags: &[Flag {
+            name: FOLLOW_FLAG,
+            value: None,
+            summary: "help-events-follow",
+        }],
+        details: "help-events-details",
+        examples: &["def events --follow"],
+    },
+    Command {
+        name: DAEMON_COMMAND,
+        synopsis: "",
+        usages: &[Usage {
+            args: "",
+            summary: "help-daemon-usage-1",
+        }],
+        flags: &[],
+        details: "help-daemon-details",
+        examples: &["def daemon"],
+    },
+    Command {
+        name: MAINTAIN_COMMAND,
+        synopsis: "[--root <dir>]",
+        usages: &[Usage {
+            args: "[--root <dir>]",
+            summary: "help-maintain-usage-1",
+        }],
+        flags: &[Flag {
+            name: ROOT_FLAG,
+            value: Some("<dir>"),
+            summary: "help-maintain-root",
+        }],
+        details: "help-maintain-details",
+        examples: &["def maintain", "def maintain --root ~/src"],
+    },
+    Command {
+        name: ANNOTATE_ARCHIVE_COMMAND,
+        synopsis: "[--root <dir>] <archive>",
+        usages: &[Usage {
+            args: "<archive>",
+            summary: "help-annotate-archive-usage-1",
+        }],
+        flags: &[Flag {
+            name: ROOT_FLAG,
+            value: Some("<dir>"),
+            summary: "help-annotate-archive-root",
+        }],
+        details: "help-annotate-archive-details",
+        examples: &["def annotate-archive --root /opt release.tar.gz"],
+    },
+    Command {
+        name: DOCTOR_COMMAND,
+        synopsis: "[--convert-paths | --fix-perms]",
+        usages: &[
+            Usage {
+                args: "",
+                summary: "help-doctor-usage-1",
+            },
+            Usage {
+                args: "--convert-paths",
+                summary: "help-doctor-usage-2",
+            },
+            Usage {
+                args: "--fix-perms",
+                summary: "help-doctor-usage-3",
+            },
+        ],
+        flags: &[
+            Flag {
+                name: CONVERT_PATHS_FLAG,
+                value: None,
+                summary: "help-doctor-convert-paths",
+            },
+            Flag {
+                name: FIX_PERMS_FLAG,
+                value: None,
+                summary: "help-doctor-fix-perms",
+            },
+        ],
+        details: "help-doctor-details",
+        examples: &[
+            "def doctor",
+            "def doctor --convert-paths",
+            "def doctor --fix-perms",
+        ],
+    },
+    Command {
+        name: EXPORT_COMMAND,
+        synopsis: "[--where <query>] [--only <subset>] [--include-private] [file]",
+        usages: &[Usage {
+            args: "[--where <query>] [--only <subset>] [--include-private] [file]",
+            summary: "help-export-usage-1",
+        }],
+        flags: &[
+            Flag {
+                name: WHERE_FLAG,
+                value: Some("<query>"),
+                summary: "help-export-where",
+            },
+            Flag {
+                name: ONLY_FLAG,
+                value: Some("<subset>"),
+                summary: "help-export-only",
+            },
+            Flag {
+                name: INCLUDE_PRIVATE_FLAG,
+                value: None,
+                summary: "help-export-include-private",
+            },
+        ],
+        details: "help-export-details",
+        examples: &[
+            "def export",
+            "def export ~/descriptions.toml",
+            "def export --where path:~/work work.json",
+            "def export --only patterns team-patterns.json",
+        ],
+    },
+    Command {
+        name: IMPORT_COMMAND,
+        synopsis: "[--overwrite | --keep-existing | --interactive] <file>",
+        usages: &[Usage {
+            args: "[--only <subset>] <file>",
+            summary: "help-import-usage-1",
+        }],
+        flags: &[
+            Flag {
+                name: OVERWRITE_FLAG,
+                value: None,
+                summary: "help-import-overwrite",
+            },
+            Flag {
+                name: KEEP_EXISTING_FLAG,
+                value: None,
+                summary: "help-import-keep-existing",
+            },
+            Flag {
+                name: INTERACTIVE_FLAG,
+                value: None,
+                summary: "help-import-interactive",
+            },
+            Flag {
+                name: ONLY_FLAG,
+                value: Some("<subset>"),
+                summary: "help-import-only",
+            },
+        ],
+        details: "help-import-details",
+        examples: &[
+            "def import ~/descriptions.toml",
+            "def import --interactive team.json",
+            "def import --only patterns --overwrite team.json",
+        ],
+    },
+    Command {
+        name: APPLY_COMMAND,
+        synopsis: "[--dry-run] <file>",
+        usages: &[Usage {
+            args: "[--dry-run] <file>",
+            summary: "help-apply-usage-1",
+        }],
+        flags: &[Flag {
+            name: DRY_RUN_FLAG,
+            value: None,
+            summary: "help-apply-dry-run",
+        }],
+        details: "help-apply-details",
+        examples: &[
+            "def apply layout.def",
+            "def apply --dry-run docs/layout.yaml",
+        ],
+    },
+    Command {
+        name: SYNC_READMES_COMMAND,
+        synopsis: "[--readme] [--dry-run] <root>",
+        usages: &[Usage {
+            args: "[--readme] [--dry-run] <root>",
+            summary: "help-sync-readmes-usage-1",
+        }],
+        flags: &[
+            Flag {
+                name: README_FLAG,
+                value: None,
+                summary: "help-sync-readmes-readme",
+            },
+            Flag {
+                name: DRY_RUN_FLAG,
+                value: None,
+                summary: "help-sync-readmes-dry-run",
+            },
+        ],
+        details: "help-sync-readmes-details",
+        examples: &[
+            "def sync-readmes .",
+            "def sync-readmes --readme --dry-run ~/work",
+        ],
+    },
+    Command {
+        name: PACK_COMMAND,
+        synopsis: "[ install [--prefix <dir>] <source> | remove <name> | list ]",
+        usages: &[
+            Usage {
+                args: "install [--prefix <dir>] [--key <key>] [--yes] <source>",
+                summary: "help-pack-usage-1",
+            },
+            Usage {
+                args: "remove <name>",
+                summary: "help-pack-usage-2",
+            },
+            Usage {
+                args: "list",
+                summary: "help-pack-usage-3",
+            },
+        ],
+        flags: &[
+            Flag {
+                name: PREFIX_FLAG,
+                value: Some("<dir>"),
+                summary: "help-pack-prefix",
+            },
+            Flag {
+                name: KEY_FLAG,
+                value: Some("<key>"),
+                summary: "help-pack-key",
+            },
+            Flag {
+                name: YES_FLAG,
+                value: None,
+                summary: "help-pack-yes",
+            },
+        ],
+        details: "help-pack-details",
+        examples: &[
+            "def pack install linux-server.json",
+            "def pack install --prefix ~/projects/analysis datascience-project.toml",
+            "def pack install --key minisign.pub https://example.com/linux-server.json",
+            "def pack remove linux-server",
+        ],
+    },
+    Command {
+        name: PROFILE_COMMAND,
+        synopsis: "[ list | create <name> | delete <name> ]",
+        usages: &[
+            Usage {
+                args: "list",
+                summary: "help-profile-usage-1",
+            },
+            Usage {
+                args: "create <name>",
+                summary: "help-profile-usage-2",
+            },
+            Usage {
+                args: "delete <name>",
+                summary: "help-profile-usage-3",
+            },
+        ],
+        flags: &[],
+        details: "help-profile-details",
+        examples: &[
+            "def profile create work",
+            "def --profile work add /mnt/work/reports \"Quarterly reports.\"",
+            "def profile list",
+        ],
+    },
+    Command {
+        name: SYNC_COMMAND,
+        synopsis: "[ pull | push ]",
+        usages: &[
+            Usage {
+                args: "pull",
+                summary: "help-sync-usage-1",
+            },
+            Usage {
+                args: "push",
+                summary: "help-sync-usage-2",
+            },
+        ],
+        flags: &[],
+        details: "help-sync-details",
+        examples: &["def sync pull", "def sync push"],
+    },
+    Command {
+        name: HOOK_COMMAND,
+        synopsis: "<shell>",
+        usages: &[Usage {
+            args: "<shell>",
+            summary: "help-hook-usage-1",
+        }],
+        flags: &[],
+        details: "help-hook-details",
+        examples: &["def hook zsh"],
+    },
+    Command {
+        name: COMPLETIONS_COMMAND,
+        synopsis: "<shell>",
+        usages: &[Usage {
+            args: "<shell>",
+            summary: "help-completions-usage-1",
+        }],
+        flags: &[],
+        details: "help-completions-details",
+        examples: &["def completions bash"],
+    },
+    Command {
+        name: FROM_INODE_COMMAND,
+        synopsis: "<dev>:<inode>",
+        usages: &[Usage {
+            args: "<dev>:<inode>",
+            summary: "help-from-inode-usage-1",
+        }],
+        flags: &[],
+        details: "help-from-inode-details",
+        examples: &["def from-inode 8,1:1234"],
+    },
+    Command {
+        name: FROM_FD_COMMAND,
+        synopsis: "<pid>:<fd>",
+        usages: &[Usage {
+            args: "<pid>:<fd>",
+            summary: "help-from-fd-usage-1",
+        }],
+        flags: &[],
+        details: "help-from-fd-details",
+        examples: &["def from-fd 1234:3"],
+    },
+    Command {
+        name: TOUR_COMMAND,
+        synopsis: "",
+        usages: &[Usage {
+            args: "",
+            summary: "help-tour-usage-1",
+        }],
+        flags: &[],
+        details: "help-tour-details",
+        examples: &["def tour"],
+    },
+    Command {
+        name: BROWSE_COMMAND,
+        synopsis: "",
+        usages: &[Usage {
+            args: "",
+            summary: "help-browse-usage-1",
+        }],
+        flags: &[],
+        details: "help-browse-details",
+        examples: &["def browse"],
+    },
+    Command {
+        name: BUNDLE_COMMAND,
+        synopsis: "[ --output <file> | --apply <file> ]",
+        usages: &[
+            Usage {
+                args: "--output <file>",
+                summary: "help-bundle-usage-1",
+            },
+            Usage {
+                args: "--apply <file>",
+                summary: "help-bundle-usage-2",
+            },
+        ],
+        flags: &[],
+        details: "help-bundle-details",
+        examples: &[
+            "def bundle --output def-bundle.tar",
+            "def bundle --apply /media/usb/def-bundle.tar",
+        ],
+    },
+    Command {
+        name: FIXTURES_COMMAND,
+        synopsis: "generate <spec>",
+        usages: &[Usage {
+            args: "generate <spec>",
+            summary: "help-fixtures-usage-1",
+        }],
+        flags: &[],
+        details: "help-fixtures-details",
+        examples: &["def fixtures generate tests/layout.json"],
+    },
+    Command {
+        name: HELP_COMMAND,
+        synopsis: "[command]",
+        usages: &[Usage {
+            args: "[command]",
+            summary: "help-help-usage-1",
+        }],
+        flags: &[],
+        details: "",
+        examples: &["def help pattern"],
+    },
+];
+
+/// Flags which can be used with any sub-command.
+pub const GLOBAL_FLAGS: &[Flag] = &[
+    Flag {
+        name: PLAIN_FLAG,
+        value: None,
+        summary: "help-global-plain",
+    },
+    Flag {
+        name: RAW_FLAG,
+        value: None,
+        summary: "help-global-raw",
+    },
+    Flag {
+        name: NO_RESOLVE_FLAG,
+        value: None,
+        summary: "help-global-no-resolve",
+    },
+    Flag {
+        name: NO_STAT_FLAG,
+        value: None,
+        summary: "help-global-no-stat",
+    },
+    Flag {
+        name: INHERIT_FLAG,
+        value: None,
+        summary: "help-global-inherit",
+    },
+    Flag {
+        name: ALLOW_COMMANDS_FLAG,
+        value: None,
+        summary: "help-global-allow-commands",
+    },
+    Flag {
+        name: CONFIG_FLAG,
+        value: Some("<file>"),
+        summary: "help-global-config",
+    },
+    Flag {
+        name: PROFILE_FLAG,
+        value: Some("<name>"),
+        summary: "help-global-profile",
+    },
+    Flag {
+        name: FORMAT_FLAG,
+        value: Some("<format>"),
+        summary: "help-global-format",
+    },
+];
+
+/// Sections of the full help message following the list of sub-commands,
+/// each the catalog keys of a heading and its text.
+const TOPICS: &[(&str, &str)] = &[
+    ("help-topic-descriptions", "help-topic-descriptions-text"),
+    ("help-topic-local-files", "help-topic-local-files-text"),
+    ("help-topic-storage", "help-topic-storage-text"),
+    ("help-topic-trash", "help-topic-trash-text"),
+    ("help-topic-maintenance", "help-topic-maintenance-text"),
+    ("help-topic-sync", "help-topic-sync-text"),
+    ("help-topic-colors", "help-topic-colors-text"),
+    ("help-topic-scripting", "help-topic-scripting-text"),
+    ("help-topic-queries", "help-topic-queries-text"),
+    ("help-topic-pattern-packs", "help-topic-pattern-packs-text"),
+    ("help-topic-markdown", "help-topic-markdown-text"),
+    ("help-topic-languages", "help-topic-languages-text"),
+    ("help-topic-variables", "help-topic-variables-text"),
+];
+
+/// synopsis returns a short summary of all sub-commands and global flags,
+/// printed when def is used without arguments.
+pub fn synopsis() -> String {
+    let mut items: Vec<String> = COMMANDS
+        .iter()
+        .map(|c| join(&[c.name, c.synopsis]))
+        .collect();
+    let last = items.len() - 1;
+    for (i, item) in items.iter_mut().enumerate() {
+        item.push_str(if i == last { " ]" } else { " |" });
+    }
+    items.extend(GLOBAL_FLAGS.iter().map(|f| format!("[{}]", flag_usage(f))));
+
+    let mut lines = vec!["  def [".to_string()];
+    for item in items {
+        let line = lines.last_mut().unwrap();
+        if line.len() + 1 + item.len() > SYNOPSIS_WIDTH {
+            lines.push(format!("{}{}", " ".repeat(SYNOPSIS_INDENT), item));
+        } else {
+            line.push(' ');
+            line.push_str(&item);
+        }
+    }
+    lines.join("\n")
+}
+
+/// full returns the full help message, listing all sub-commands followed by
+/// an explanation of def's concepts and settings, in the selected language.
+pub fn full() -> String {
+    full_in(tr)
+}
+
+/// full_in returns the full help message, with messages looked up by tr.
+fn full_in(tr: fn(&str) -> &'static str) -> String {
+    let mut help = format!("{}\n\n{}\n\n", tr("help-intro"), tr("usage-heading"));
+    for command in COMMANDS.iter() {
+        for usage in command.usages.iter() {
+            let usage_line = format!("  {}", join(&["def", command.name, usage.args]));
+            let summary = wrap(tr(usage.summary), WIDTH - SUMMARY_COLUMN);
+            if usage_line.len() < SUMMARY_COLUMN - 1 {
+                help.push_str(&format!(
+                    "{:width$}{}\n",
+                    usage_line,
+                    summary[0],
+                    width = SUMMARY_COLUMN
+                ));
+            } else {
+                help.push_str(&format!("{}\n", usage_line));
+                help.push_str(&format!("{}{}\n", " ".repeat(SUMMARY_COLUMN), summary[0]));
+            }
+            for line in summary[1..].iter() {
+                help.push_str(&format!("{}{}\n", " ".repeat(SUMMARY_COLUMN), line));
+            }
+        }
+    }
+
+    for (heading, text) in TOPICS.iter() {
+        help.push_str(&format!("\n{}\n\n{}\n", tr(heading), tr(text)));
+    }
+    help.push_str(&format!("\n{}", tr("help-for-command")));
+    help
+}
+
+/// for_command returns the help message of the sub-command with the given
+/// name, which lists its usages, flags, and examples, in the selected
+/// language. Returns None if there's no such sub-command.
+pub fn for_command(name: &str) -> Option<String> {
+    for_command_in(name, tr)
+}
+
+/// for_command_in returns the help message of the sub-command with the given
+/// name, with messages looked up by tr.
+fn for_command_in(name: &str, tr: fn(&str) -> &'static str) -> Option<String> {
+    let command = COMMANDS
+        .iter()
+        .find(|c| !c.name.is_empty() && c.name == name)?;
+
+    let mut help = format!("{}\n\n", tr("usage-heading"));
+    for usage in command.usages.iter() {
+        help.push_str(&format!("  {}\n", join(&["def", command.name, usage.args])));
+    }
+    for usage in command.usages.iter() {
+        help.push('\n');
+        for line in wrap(tr(usage.summary), WIDTH - 4) {
+            help.push_str(&format!("  {}\n", line));
+        }
+    }
+    if !command.details.is_empty() {
+        help.push('\n');
+        for line in wrap(tr(command.details), WIDTH - 4) {
+            help.push_str(&format!("  {}\n", line));
+        }
+    }
+
+    if !command.flags.is_empty() {
+        help.push_str(&format!("\n{}\n\n", tr("flags-heading")));
+        let width = command
+            .flags
+            .iter()
+            .map(|f| flag_usage(f).len())
+            .max()
+            .unwrap_or(0);
+        for flag in command.flags.iter() {
+            let summary = wrap(tr(flag.summary), WIDTH - width - 4);
+            help.push_str(&format!(
+                "  {:width$}  {}\n",
+                flag_usage(flag),
+                summary[0],
+                width = width
+            ));
+            for line in summary[1..].iter() {
+                help.push_str(&format!("{}{}\n", " ".repeat(width + 4), line));
+            }
+        }
+    }
+
+    if !command.examples.is_empty() {
+        help.push_str(&format!("\n{}\n\n", tr("examples-heading")));
+        for example in command.examples.iter() {
+            help.push_str(&format!("  $ {}\n", example));
+        }
+    }
+    Some(help.trim_end().to_string())
+}
+
+/// flag_usage returns a flag's name followed by its value, if it takes one.
+fn flag_usage(flag: &Flag) -> String {
+    match flag.value {
+        Some(value) => format!("{} {}", flag.name, value),
+        None => flag.name.to_string(),
+    }
+}
+
+/// join joins non-empty parts of a command line using spaces.
+fn join(parts: &[&str]) -> String {
+    parts
+        .iter()
+        .filter(|p| !p.is_empty())
+        .copied()
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+/// wrap splits text into lines no longer than width, breaking at spaces.
+/// Words longer than width are put on their own lines. Always returns at
+/// least one line.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = vec![String::new()];
+    for word in text.split_whitespace() {
+        let line = lines.last_mut().unwrap();
+        if line.is_empty() {
+            line.push_str(word);
+        } else if line.len() + 1 + word.len() > width {
+            lines.push(word.to_string());
+        } else {
+            line.push(' ');
+            line.push_str(word);
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::{parse, parse_options, InvokedTo};
+    use crate::i18n;
+
+    #[test]
+    fn wrap_test() {
+        for (text, width, lines) in [
+            ("", 10, vec![""]),
+            ("short", 10, vec!["short"]),
+            ("a few words to wrap", 10, vec!["a few", "words to", "wrap"]),
+            ("a verylongword", 5, vec!["a", "verylongword"]),
+        ]
+        .iter()
+        {
+            assert_eq!(wrap(text, *width), *lines);
+        }
+    }
+
+    #[test]
+    fn examples_test() {
+        for command in COMMANDS.iter() {
+            for example in command.examples.iter() {
+                let (args, _) = parse_options(&split_example(example));
+                assert_ne!(parse(&args), InvokedTo::Unknown, "{}", example);
+            }
+            if !command.name.is_empty() {
+                assert!(for_command(command.name).is_some(), "{}", command.name);
+            }
+        }
+    }
+
+    #[test]
+    fn width_test() {
+        for tr in [tr, es] {
+            for line in full_in(tr).lines().chain(synopsis().lines()) {
+                assert!(line.len() <= WIDTH, "{}", line);
+            }
+            for command in COMMANDS.iter().filter(|c| !c.name.is_empty()) {
+                for line in for_command_in(command.name, tr).unwrap().lines() {
+                    assert!(line.len() <= WIDTH, "{}", line);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn translated_test() {
+        let help = full_in(es);
+        assert!(help.starts_with(es("help-intro")));
+        for (heading, text) in TOPICS.iter() {
+            assert_ne!(es(text), tr(text), "{}", text);
+            assert!(help.contains(es(heading)), "{}", heading);
+            assert!(help.contains(es(text)), "{}", text);
+            assert!(!help.contains(tr(text)), "{}", text);
+        }
+
+        let flags = COMMANDS.iter().flat_map(|c| c.flags.iter());
+        let keys = COMMANDS
+            .iter()
+            .flat_map(|c| c.usages.iter().map(|u| u.summary))
+            .chain(COMMANDS.iter().map(|c| c.details))
+            .chain(flags.chain(GLOBAL_FLAGS.iter()).map(|f| f.summary))
+            .filter(|key| !key.is_empty());
+        for key in keys {
+            assert!(i18n::builtin("en", key).is_some(), "{}", key);
+            assert_ne!(es(key), tr(key), "{}", key);
+        }
+    }
+
+    /// es looks up messages in the Spanish catalog.
+    fn es(key: &str) -> &'static str {
+        i18n::builtin("es", key).unwrap_or_default()
+    }
+
+    /// split_example splits an example command line into arguments, keeping
+    /// quoted arguments together.
+    fn split_example(example: &str) -> Vec<String> {
+        let mut args = vec![];
+        let mut arg = String::new();
+        let mut quoted = false;
+        for c in example.chars() {
+            match c {
+                '"' => quoted = !quoted,
+                ' ' if !quoted => args.push(std::mem::take(&mut arg)),
+                _ => arg.push(c),
+            }
+        }
+        args.push(arg);
+        args
+    }
+}
@@ -0,0 +1,189 @@
+/// Shell is a shell "def hook" can print a snippet for, and "def completions"
+/// can print a completion script for.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl Shell {
+    /// Return the shell with the given name, or None if there's none.
+    pub fn from_name(name: &str) -> Option<Shell> {
+        match name {
+            "bash" => Some(Shell::Bash),
+            "zsh" => Some(Shell::Zsh),
+            "fish" => Some(Shell::Fish),
+            _ => None,
+        }
+    }
+
+    /// snippet returns code which, once evaluated by the shell, prints the
+    /// description of the current directory whenever it changes. Undescribed
+    /// directories print nothing, see "def --quiet".
+    pub fn snippet(self) -> &'static str {
+        match self {
+            // PROMPT_COMMAND runs before every prompt, so the last directory
+            // is kept to print only on changes, and the exit status is kept
+            // for prompts showing it.
+            Shell::Bash => concat!(
+                "__def_hook() {\n",
+                "  local status=$?\n",
+                "  if [ \"$PWD\" != \"$__def_last_dir\" ]; then\n",
+                "    __def_last_dir=\"$PWD\"\n",
+                "    def --quiet \"$PWD\"\n",
+                "  fi\n",
+                "  return $status\n",
+                "}\n",
+                "__def_last_dir=\"$PWD\"\n",
+                "PROMPT_COMMAND=\"__def_hook${PROMPT_COMMAND:+;$PROMPT_COMMAND}\"\n",
+            ),
+            Shell::Zsh => concat!(
+                "__def_hook() {\n",
+                "  def --quiet \"$PWD\"\n",
+                "}\n",
+                "autoload -Uz add-zsh-hook\n",
+                "add-zsh-hook chpwd __def_hook\n",
+            ),
+            Shell::Fish => concat!(
+                "function __def_hook --on-variable PWD\n",
+                "    def --quiet \"$PWD\"\n",
+                "end\n",
+            ),
+        }
+    }
+
+    /// completions returns a script which, once evaluated by the shell,
+    /// completes words (def's sub-commands and flags) as the first argument
+    /// of def, and described paths listed by "def __complete" as any
+    /// argument. Files are completed if no described path matches.
+    pub fn completions(self, words: &[&str]) -> String {
+        let words = words.join(" ");
+        match self {
+            Shell::Bash => format!(
+                concat!(
+                    "_def() {{\n",
+                    "  local cur=\"${{COMP_WORDS[COMP_CWORD]}}\"\n",
+                    "  local -a words=()\n",
+                    "  if [ \"$COMP_CWORD\" -eq 1 ]; then\n",
+                    "    words=($(compgen -W \"{}\" -- \"$cur\"))\n",
+                    "  fi\n",
+                    "  local IFS=$'\\n'\n",
+                    "  COMPREPLY=(\"${{words[@]}}\" $(def __complete \"$cur\" 2>/dev/null))\n",
+                    "}}\n",
+                    "complete -o default -F _def def\n",
+                ),
+                words
+            ),
+            Shell::Zsh => format!(
+                concat!(
+                    "_def() {{\n",
+                    "  local -a paths\n",
+                    "  paths=(${{(f)\"$(def __complete \"$PREFIX\" 2>/dev/null)\"}})\n",
+                    "  if (( CURRENT == 2 )); then\n",
+                    "    compadd -- {}\n",
+                    "  fi\n",
+                    "  if (( ${{#paths}} )); then\n",
+                    "    compadd -U -- \"${{paths[@]}}\"\n",
+                    "  else\n",
+                    "    _files\n",
+                    "  fi\n",
+                    "}}\n",
+                    "compdef _def def\n",
+                ),
+                words
+            ),
+            Shell::Fish => format!(
+                concat!(
+                    "complete -c def -n __fish_use_subcommand -a \"{}\"\n",
+                    "complete -c def -a \"(def __complete (commandline -ct) 2>/dev/null)\"\n",
+                ),
+                words
+            ),
+        }
+    }
+}
+
+/// candidates returns paths, sorted, which start with prefix, a path as
+/// typed on the command line: absolute, relative to cwd, or relative to home
+/// if it starts with "~". Candidates are written the way prefix is, so that
+/// shells can replace the typed prefix with them.
+pub fn candidates<'a, I: Iterator<Item = &'a str>>(
+    prefix: &str,
+    paths: I,
+    cwd: &str,
+    home: Option<&str>,
+) -> Vec<String> {
+    // Typed prefixes are written as shown followed by tail, and the paths
+    // they stand for start with head followed by tail.
+    let (head, shown, tail) = match (prefix.strip_prefix('~'), home) {
+        _ if prefix.starts_with('/') => (String::new(), "", prefix),
+        (Some(tail), Some(home)) if tail.is_empty() || tail.starts_with('/') => {
+            let tail = if tail.is_empty() { "/" } else { tail };
+            (home.trim_end_matches('/').to_string(), "~", tail)
+        }
+        _ => (format!("{}/", cwd.trim_end_matches('/')), "", prefix),
+    };
+
+    let absolute = format!("{}{}", head, tail);
+    let mut candidates: Vec<String> = paths
+        .filter(|path| path.starts_with(&absolute))
+        .map(|path| format!("{}{}", shown, &path[head.len()..]))
+        .collect();
+    candidates.sort();
+    candidates.dedup();
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_name_test() {
+        for (name, shell) in [
+            ("bash", Some(Shell::Bash)),
+            ("zsh", Some(Shell::Zsh)),
+            ("fish", Some(Shell::Fish)),
+            ("powershell", None),
+        ]
+        .iter()
+        {
+            assert_eq!(Shell::from_name(name), *shell);
+            if let Some(shell) = shell {
+                assert!(shell.snippet().contains("def --quiet \"$PWD\""));
+                assert!(shell.completions(&["add", "edit"]).contains("add edit"));
+            }
+        }
+    }
+
+    #[test]
+    fn candidates_test() {
+        let paths = ["/home/u/notes", "/home/u/projects/def", "/srv", "/srv/www"];
+        for (prefix, cwd, expected) in [
+            ("/s", "/", vec!["/srv", "/srv/www"]),
+            ("/srv/", "/", vec!["/srv/www"]),
+            ("/x", "/", vec![]),
+            ("pro", "/home/u", vec!["projects/def"]),
+            ("", "/home/u", vec!["notes", "projects/def"]),
+            (
+                "",
+                "/",
+                vec!["home/u/notes", "home/u/projects/def", "srv", "srv/www"],
+            ),
+            ("~/n", "/srv", vec!["~/notes"]),
+            ("~", "/srv", vec!["~/notes", "~/projects/def"]),
+            ("~x", "/home/u", vec![]),
+        ]
+        .iter()
+        {
+            assert_eq!(
+                candidates(prefix, paths.iter().copied(), cwd, Some("/home/u")),
+                *expected,
+                "{} in {}",
+                prefix,
+                cwd
+            );
+        }
+    }
+}
@@ -0,0 +1,3105 @@
+use std::collections::HashMap;
+use std::env;
+use std::fmt::Display;
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Messages of the selected language, set once by init.
+static CATALOG: OnceLock<HashMap<String, String>> = OnceLock::new();
+
+/// Environment variables checked for the user's language, in order.
+const LANGUAGE_VARIABLES: [&str; 3] = ["LC_ALL", "LC_MESSAGES", "LANG"];
+
+/// English messages. Every message has an English version, which is used
+/// when the selected language lacks a translation.
+const EN: &[(&str, &str)] = &[
+    ("error-label", "Err"),
+    ("warning-label", "Warn"),
+    ("error-label-plain", "ERROR"),
+    ("warning-label-plain", "WARNING"),
+    ("usage-heading", "Usage"),
+    ("try-help", "Try \"def help\" for more details."),
+    ("flags-heading", "Flags"),
+    ("examples-heading", "Examples"),
+    (
+        "help-for-command",
+        "Run \"def help <command>\" for details of a command.",
+    ),
+    ("help-intro", "def keeps track of file and directory descriptions for you."),
+    ("help-topic-descriptions", "Descriptions"),
+    (
+        "help-topic-descriptions-text",
+        concat!(
+            "  To describe a file or directory use add sub-command which simply maps a\n",
+            "  description to an absolute path.\n",
+            "\n",
+            "  Paths are resolved to their canonical form, following symlinks, so they must\n",
+            "  exist. With --no-resolve, paths are instead joined with the current directory\n",
+            "  and \".\" and \"..\" are collapsed, so paths that don't exist yet can be\n",
+            "  described, and symlinks are kept as given. This applies to add, pattern, and\n",
+            "  describing a path alike.\n",
+            "\n",
+            "  The pattern sub-command is used to describe all children of a directory using a\n",
+            "  common trait. When pattern is used, a description is mapped to a dir, but is used\n",
+            "  only to describe its children. If a wildcard \"*\" exists in the pattern, it will\n",
+            "  be replaced by the child's name.\n",
+            "\n",
+            "  Recursive patterns (added using --recursive) describe all descendants of a\n",
+            "  directory. In addition to \"*\", a double wildcard \"**\" is replaced by the\n",
+            "  descendant's path relative to the directory.\n",
+            "\n",
+            "  Patterns added using --files-only, --dirs-only, or --symlinks-only describe\n",
+            "  only children of that type, and win over the untyped pattern of the same\n",
+            "  directory. Paths that don't exist are only described by untyped patterns.\n",
+            "\n",
+            "  The glob sub-command describes all paths matching a glob, where \"*\" matches\n",
+            "  any characters except \"/\", \"?\" matches a single character, and \"**\" matches\n",
+            "  any characters including \"/\". A glob without \"/\" (such as \"*.log\") is\n",
+            "  matched against names of paths. In the description, \"*\" is replaced by the\n",
+            "  path's name, and \"**\" by the whole path.\n",
+            "\n",
+            "  A specific description always wins over patterns. When several patterns apply\n",
+            "  to a path, the one mapped closest to it is used: the parent for patterns, the\n",
+            "  ancestor for recursive patterns, and the dir a glob starts with for globs\n",
+            "  (\"/srv\" for \"/srv/*.log\", none for \"*.log\"). Ties go to the pattern with\n",
+            "  the highest --priority, then to globs, patterns, and recursive patterns, and\n",
+            "  then to the most specific glob. \"def explain <path>\" shows which one is used.\n",
+            "  This order can be changed by \"resolution_order\" in settings, such as\n",
+            "  [\"description\", \"glob\", [\"pattern\", \"recursive\"]] to prefer globs over\n",
+            "  patterns of dirs. Stages left out (such as \"repository\") are never used.\n",
+            "\n",
+            "  A path no description or pattern applies to has no description, unless\n",
+            "  --inherit is given (or \"inherit\" is set in settings), in which case the\n",
+            "  description of its nearest described ancestor is printed, marked as inherited\n",
+            "  from it.\n",
+            "\n",
+            "  Paths match descriptions and patterns only if they're written the same. On\n",
+            "  file systems that ignore case, set \"matching\" in settings, such as\n",
+            "  {\"case_insensitive\": true, \"normalize_unicode\": true}, so that \"~/Downloads\"\n",
+            "  matches \"~/downloads\", and accented names match however they're encoded.\n",
+            "\n",
+            "  Patterns and globs can also contain named placeholders: {name} (same as\n",
+            "  \"*\"), {stem} (name without extension), {ext} (extension without a dot),\n",
+            "  {parent} (name of the containing dir), and {path} (the whole path). Use {{\n",
+            "  and }} for literal braces. Patterns with unknown placeholders or unmatched\n",
+            "  braces are rejected.\n",
+            "\n",
+            "  Metadata placeholders are filled in from the described path on disk: {size}\n",
+            "  (such as \"1.5 KiB\", or \"3 entries\" for dirs), {mtime} (last modification,\n",
+            "  in UTC), and {type} (file, directory, or symlink). They're left as written\n",
+            "  for paths that don't exist, and with --no-stat.\n",
+            "\n",
+            "  If def is built with \"git\" feature, git placeholders are filled in from the\n",
+            "  git repository of the described path: {git_branch}, {git_remote} (the URL of\n",
+            "  origin), and {git_last_commit} (hash and summary). Roots of repositories no\n",
+            "  description or pattern applies to are described by their branch and remote.\n",
+            "\n",
+            "  For example:\n",
+            "\n",
+            "  $ def pattern dir \"* is a child of dir\"\n",
+            "  $ def dir/temp\n",
+            "  /path/to/dir/temp: temp is a child of dir\n",
+            "\n",
+            "Descriptions and patterns are kept in ~/.config/def/config.json which maps each\n",
+            "description to an absolute path and can be added to or adjusted manually.\n",
+            "\n",
+            "  def's files are kept in $XDG_CONFIG_HOME/def instead of ~/.config/def if\n",
+            "  XDG_CONFIG_HOME is set. Another config file can be used with --config <file>\n",
+            "  or $DEF_CONFIG, or by \"config_file\" in settings. Flags win over environment\n",
+            "  variables, which win over settings.",
+        ),
+    ),
+    ("help-topic-local-files", "Local files"),
+    (
+        "help-topic-local-files-text",
+        concat!(
+            "  Descriptions can also be kept in a .def.json (or .def) file in any dir, such\n",
+            "  as the root of a repository, to be committed along with it. Keys in local\n",
+            "  files are relative to their dir (\".\" being the dir itself). When a path is\n",
+            "  described, local files in the path and its ancestors are merged over the\n",
+            "  config, the nearest file winning. Use \"def add --local\" to add to them.",
+        ),
+    ),
+    ("help-topic-storage", "Storage"),
+    (
+        "help-topic-storage-text",
+        concat!(
+            "  The config can be kept elsewhere by setting \"config_file\" in settings.json.\n",
+            "  It's stored as JSON by default, or based on its extension: as TOML (.toml),\n",
+            "  YAML (.yaml or .yml), MessagePack (.msgpack or .mp), or in an SQLite database\n",
+            "  (.db or .sqlite). TOML (enabled by default), YAML, MessagePack, and SQLite\n",
+            "  require def to be built with \"toml\", \"yaml\", \"msgpack\", and \"sqlite\"\n",
+            "  features. Set \"store\" to \"json\", \"toml\", \"yaml\", \"msgpack\", \"sqlite\", or\n",
+            "  \"crdt\" in settings.json to choose explicitly.\n",
+            "\n",
+            "  A config kept in a folder synced by a tool like Syncthing or Dropbox can be a\n",
+            "  CRDT file (.crdt), which records when each entry was last changed. If it's\n",
+            "  changed on two machines at once, def merges the conflicting copy left by the\n",
+            "  sync tool, keeping the latest change of every entry, and deletes the copy.\n",
+            "\n",
+            "  Files are saved atomically, so a crash never leaves a half-written config.\n",
+            "  Commands changing the config lock it (using a .lock file next to it) until\n",
+            "  they're done, so concurrent invocations don't lose each other's changes.",
+        ),
+    ),
+    ("help-topic-trash", "Trash"),
+    (
+        "help-topic-trash-text",
+        concat!(
+            "  Removed descriptions are moved to a trash, from which they can be restored.\n",
+            "  Trashed descriptions are deleted for good after \"trash_retention_days\" (30 by\n",
+            "  default) as set in ~/.config/def/settings.json.",
+        ),
+    ),
+    ("help-topic-maintenance", "Maintenance"),
+    (
+        "help-topic-maintenance-text",
+        concat!(
+            "  def maintain keeps the latest \"backups_kept\" (5 by default) backups of\n",
+            "  the config in ~/.config/def/backups. A backup is only made when the config\n",
+            "  changed since the latest one. The dir checked for drift can be set using\n",
+            "  \"maintain_root\" in ~/.config/def/settings.json.",
+        ),
+    ),
+    ("help-topic-sync", "Sync"),
+    (
+        "help-topic-sync-text",
+        concat!(
+            "  def sync pull and push merge the config with a remote copy, set by\n",
+            "  \"sync_remote\" in ~/.config/def/settings.json, such as\n",
+            "  {\"sync_remote\": \"git@example.com:me/descriptions.git\"}. The remote is a\n",
+            "  git repository holding the config as def.json, or an http(s) URL of a JSON\n",
+            "  file, read with GET and written with PUT. git or curl must be installed to\n",
+            "  reach it, and def must be built with \"sync\" feature.",
+        ),
+    ),
+    ("help-topic-colors", "Colors"),
+    (
+        "help-topic-colors-text",
+        concat!(
+            "  Colors are set in the \"colors\" map of ~/.config/def/settings.json, which maps\n",
+            "  error, warning, path, description, pattern, glob, heading, and code to a color\n",
+            "  name (such as \"red\" or \"bright blue\"), a 256-color code (such as \"208\"), a\n",
+            "  hex value (such as \"#ff8700\"), or \"none\". Set NO_COLOR to disable all colors.\n",
+            "  Colors are also disabled when output isn't a terminal.\n",
+            "\n",
+            "  The global --plain flag (or \"plain_output\": true in settings.json) prints\n",
+            "  output without colors or alignment, as simple \"path: description\" lines, and\n",
+            "  labels errors with \"ERROR:\", which is easier to follow using screen readers.",
+        ),
+    ),
+    ("help-topic-scripting", "Scripting"),
+    (
+        "help-topic-scripting-text",
+        concat!(
+            "  With --format json, describing a path prints an object with \"path\",\n",
+            "  \"description\", and \"source\" (\"specific\" or \"pattern\") fields, which are\n",
+            "  null if the path has no description. ls and list print arrays of such objects.\n",
+            "  With --format tsv, the same fields are printed as tab-separated lines, with tabs\n",
+            "  and newlines in descriptions replaced by spaces. \"def --stdin\" describes many\n",
+            "  paths read from stdin at once, and \"def --quiet <path>\" prints only the\n",
+            "  description, exiting with 1 and printing nothing if there's none.",
+        ),
+    ),
+    ("help-topic-queries", "Queries"),
+    (
+        "help-topic-queries-text",
+        concat!(
+            "  list, search, and export take a query using --where, such as\n",
+            "  \"path:/var/** AND (kind:glob OR NOT text:temp)\". Terms must all match,\n",
+            "  unless joined by OR, and NOT negates a term. A term is one of:\n",
+            "\n",
+            "    path:<path>       Entries of path and paths under it, or matching it if\n",
+            "                      it contains wildcards (as in globs).\n",
+            "    text:<word>       Entries whose text contains word.\n",
+            "    kind:<kind>       description, pattern, recursive, or glob entries.\n",
+            "    modified:<date>   Entries whose path was last modified on date (in UTC,\n",
+            "                      as YYYY-MM-DD), or before or after it using <, <=, >, >=.\n",
+            "    <word>            Entries whose path or text contains word.\n",
+            "\n",
+            "  Case is ignored, and values with spaces are quoted, such as text:\"web root\".",
+        ),
+    ),
+    ("help-topic-pattern-packs", "Pattern packs"),
+    (
+        "help-topic-pattern-packs-text",
+        concat!(
+            "  A pack is a shareable set of patterns, written in JSON (or another format of\n",
+            "  config files, by its extension), such as:\n",
+            "\n",
+            "    {\"name\": \"linux-server\", \"author\": \"...\", \"description\": \"...\",\n",
+            "     \"prefix\": \"/\", \"patterns\": [\n",
+            "       {\"path\": \"srv\", \"pattern\": \"{name} is served.\"},\n",
+            "       {\"path\": \"var/log\", \"pattern\": \"** is a log.\", \"kind\": \"recursive\"},\n",
+            "       {\"path\": \"*.conf\", \"pattern\": \"Configures {stem}.\", \"kind\": \"glob\"}]}\n",
+            "\n",
+            "  Paths are relative to prefix (\"/\" by default), \".\" being prefix itself, and\n",
+            "  globs without a separator match paths anywhere. kind is \"children\" (the\n",
+            "  default), \"recursive\", or \"glob\". Variables in prefix (such as $HOME) are\n",
+            "  expanded, and --prefix installs the pack elsewhere. Installed packs are kept\n",
+            "  in ~/.config/def/packs.\n",
+            "\n",
+            "  Packs can be signed using minisign (minisign -Sm pack.json), with the\n",
+            "  signature published beside the pack as pack.json.minisig. If \"trusted_keys\"\n",
+            "  in settings.json lists minisign public keys, packs installed from URLs must\n",
+            "  be signed by one of them; --key checks a pack against a given key instead.",
+        ),
+    ),
+    ("help-topic-markdown", "Markdown"),
+    (
+        "help-topic-markdown-text",
+        concat!(
+            "  If def is built with \"markdown\" feature, descriptions are rendered as\n",
+            "  Markdown: bold, italics, code, links, and lists are shown as such in the\n",
+            "  terminal. Use the global --raw flag (or set \"markdown\" to false in\n",
+            "  settings.json) to print descriptions as written.\n",
+            "\n",
+            "  Descriptions can span multiple lines. In a terminal, \"def <path>\" prints\n",
+            "  them below the path, wrapped to the terminal's width, while ls and list\n",
+            "  print their first line, followed by \"…\" if they go on or don't fit.",
+        ),
+    ),
+    ("help-topic-languages", "Languages"),
+    (
+        "help-topic-languages-text",
+        concat!(
+            "  Messages are printed in the language set by \"language\" in settings.json (such\n",
+            "  as \"es\"), or by LC_ALL, LC_MESSAGES, or LANG. English and Spanish are built\n",
+            "  in. Translations can be added or adjusted using a JSON file named after the\n",
+            "  language (such as es.json) in ~/.config/def/locales, which maps message keys\n",
+            "  to messages. Untranslated messages are printed in English.",
+        ),
+    ),
+    ("help-topic-variables", "Variables"),
+    (
+        "help-topic-variables-text",
+        concat!(
+            "  Keys in config.json can contain variables such as $HOME or ${PROJECTS}, which\n",
+            "  are expanded when descriptions are looked up. Variables are defined in the\n",
+            "  \"variables\" map of ~/.config/def/settings.json, or taken from the environment.",
+        ),
+    ),
+    (
+        "help-describe-usage-1",
+        "Print description of file/dir at path.",
+    ),
+    (
+        "help-describe-usage-2",
+        "Print descriptions of paths read from stdin, one per line.",
+    ),
+    (
+        "help-describe-usage-3",
+        "Re-run commands capturing descriptions (see \"def help add\").",
+    ),
+    (
+        "help-describe-stdin",
+        "Read newline-separated paths from stdin, and print a line for each: its description, \
+         or an empty line if it has none. Newlines in descriptions are printed as spaces. \
+         Meant for scripts, such as \"find . -maxdepth 1 | def --stdin\".",
+    ),
+    (
+        "help-describe-quiet",
+        "Print only the description, and nothing (not even errors) if there's none. Exits with \
+         0 if path is described, 1 otherwise.",
+    ),
+    (
+        "help-describe-refresh",
+        "Run the command capturing the description of path again, save its output, and print \
+         it. Without a path, refresh every description captured from a command. Needs \
+         --allow-commands.",
+    ),
+    (
+        "help-add-usage-1",
+        "Add a description for file/dir at path.",
+    ),
+    (
+        "help-add-usage-2",
+        "Add text to the end or the start of the description of path.",
+    ),
+    (
+        "help-add-usage-3",
+        "Add a description to the nearest local file (see \"Local files\").",
+    ),
+    (
+        "help-add-usage-4",
+        "Add the output of a shell command as the description of path.",
+    ),
+    (
+        "help-add-local",
+        "Write to the nearest .def.json or .def file in path's ancestors, or to .def.json in \
+         the current dir if there's none.",
+    ),
+    (
+        "help-add-tag",
+        "Tag the description, keeping tags it already has. Can be given more than once. Local \
+         descriptions can't be tagged.",
+    ),
+    (
+        "help-add-private",
+        "Mark the description private, leaving it out of exports (see \"def private\"). \
+         Descriptions stay private when replaced.",
+    ),
+    (
+        "help-add-append",
+        "Add description after the existing one, separated by a space.",
+    ),
+    (
+        "help-add-prepend",
+        "Add description before the existing one, separated by a space.",
+    ),
+    (
+        "help-add-force",
+        "Replace the existing description without a warning.",
+    ),
+    (
+        "help-add-from-command",
+        "Run cmd in a shell, in path (or its parent if it's a file), and use what it prints as \
+         the description. Needs --allow-commands.",
+    ),
+    (
+        "help-add-details",
+        "Maps description to the absolute path of file/dir at path, replacing its previous \
+         description, with a warning showing it if it was different. Metadata of path (its \
+         kind and size) is recorded, to be compared against later by drift. Descriptions added \
+         with --from-command keep their command, so that \"def --refresh\" can run it again to \
+         update them. Commands only run with --allow-commands (or \"allow_commands\" set in \
+         settings), and are stopped after \"command_timeout\" seconds of settings (5 by \
+         default).",
+    ),
+    (
+        "help-pattern-usage-1",
+        "Add a pattern to describe children of dir. A wildcard in the pattern will be replaced \
+         with the child's name.",
+    ),
+    (
+        "help-pattern-usage-2",
+        "Add a pattern to describe all descendants of dir. A double wildcard will be replaced \
+         with the descendant's path relative to dir.",
+    ),
+    (
+        "help-pattern-usage-3",
+        "Add a pattern to describe children of dir that are files. --dirs-only and \
+         --symlinks-only add patterns of subdirectories and symlinks.",
+    ),
+    (
+        "help-pattern-recursive",
+        "Describe all descendants of dir rather than its children.",
+    ),
+    (
+        "help-pattern-files-only",
+        "Describe only children of dir that are files.",
+    ),
+    (
+        "help-pattern-dirs-only",
+        "Describe only children of dir that are directories.",
+    ),
+    (
+        "help-pattern-symlinks-only",
+        "Describe only children of dir that are symlinks.",
+    ),
+    (
+        "help-pattern-priority",
+        "Set the priority of patterns of path (0 by default), which wins ties with patterns \
+         mapped as close to a path.",
+    ),
+    (
+        "help-pattern-details",
+        "A pattern is mapped to a dir, but is used only to describe its children. If a \
+         wildcard \"*\" exists in the pattern, it's replaced by the child's name. Recursive \
+         patterns describe all descendants of a dir, and a double wildcard \"**\" is replaced \
+         by the descendant's path relative to the dir. Of the patterns applying to a path, the \
+         one mapped closest to it is used, and ties go to the highest priority (see \"def \
+         explain\"). Named placeholders, such as {stem} and {ext}, can be used as well (see \
+         \"def help\"). A dir can have a pattern for each type of children (--files-only, \
+         --dirs-only, and --symlinks-only), which wins over its untyped pattern. Typed \
+         patterns only apply to paths that exist, as their type is read from the file system \
+         (and never with --no-stat).",
+    ),
+    (
+        "help-glob-usage-1",
+        "Add a description for all paths matching glob.",
+    ),
+    (
+        "help-glob-priority",
+        "Set the priority of the glob (0 by default), which wins ties with patterns mapped as \
+         close to a path.",
+    ),
+    (
+        "help-glob-details",
+        "In globs, \"*\" matches any characters except \"/\", \"?\" matches a single \
+         character, and \"**\" matches any characters including \"/\". A glob without \"/\" \
+         (such as \"*.log\") is matched against names of paths. In the description, \"*\" is \
+         replaced by the path's name, and \"**\" by the whole path. A glob is as close to a \
+         path as the dir it starts with, such as \"/srv\" for \"/srv/*.log\", and globs \
+         without \"/\" are the farthest. Globs win ties with patterns, and the most specific \
+         glob (with most non-wildcard characters) is used.",
+    ),
+    (
+        "help-explain-usage-1",
+        "Show which description or pattern describes path, and which others apply but lose to \
+         it.",
+    ),
+    (
+        "help-explain-details",
+        "Prints the description or pattern used to describe path, followed by the others \
+         applying to it, in the order they'd be used if the ones before them were removed. \
+         Patterns mapped closer to path come first, then ones with a higher priority (see \
+         \"def pattern --priority\"). The placeholders the used pattern filled in are listed \
+         with their values. If \"resolution_order\" is set in settings, the order is printed \
+         too, and stages it leaves out aren't listed.",
+    ),
+    (
+        "help-info-usage-1",
+        "Show everything known about path: its description, where it comes from, its tags, and \
+         its metadata.",
+    ),
+    (
+        "help-info-details",
+        "Prints the description of path, followed by the description or pattern it comes from \
+         (see \"def explain\"), the command it was captured from, when it was added and \
+         updated, and whether it's private. Then come the tags of path, and its type, size, \
+         modification time, owner, and the mount point of its file system, along with the \
+         metadata recorded when it was described if they contradict. With --no-stat, the file \
+         system isn't read.",
+    ),
+    (
+        "help-edit-usage-1",
+        "Edit description of file/dir at path in your editor.",
+    ),
+    (
+        "help-edit-usage-2",
+        "Edit the whole config as JSON in your editor.",
+    ),
+    (
+        "help-edit-all",
+        "Edit all descriptions, patterns, and globs at once.",
+    ),
+    (
+        "help-edit-details",
+        "Opens $VISUAL (or $EDITOR, or vi if neither is set) on a temporary file holding the \
+         current description, or nothing if there's none, which makes long and multi-line \
+         descriptions easy to write. The description is saved once the editor exits, unless \
+         it's left empty. With --all, the config is checked when the editor exits, and if it's \
+         invalid JSON or has invalid patterns, you're asked to edit it again; nothing is saved \
+         otherwise.",
+    ),
+    (
+        "help-list-usage-1",
+        "Print all descriptions and patterns (of paths under prefix) as a tree.",
+    ),
+    (
+        "help-list-usage-2",
+        "Run the search saved as name, see \"def search --save\".",
+    ),
+    (
+        "help-list-depth",
+        "Print only n levels of the tree, each dir with hidden children followed by the number \
+         of entries under it.",
+    ),
+    (
+        "help-list-where",
+        "Print only descriptions and patterns matching query (see \"Queries\" in \"def \
+         help\").",
+    ),
+    (
+        "help-list-details",
+        "Chains of dirs without descriptions, each containing a single dir, are collapsed into \
+         one line (such as \"/home/user\"). With --plain, descriptions are printed as a flat \
+         list of \"path: description\" lines instead, and --depth is ignored.",
+    ),
+    (
+        "help-lists-usage-1",
+        "Print saved searches and their queries.",
+    ),
+    (
+        "help-lists-details",
+        "Searches are saved in settings.json, under \"searches\", by \"def search --save\".",
+    ),
+    (
+        "help-tagged-usage-1",
+        "List descriptions tagged with tag.",
+    ),
+    (
+        "help-tagged-details",
+        "Descriptions are listed like \"def list\" lists them. Tags are added by \"def add \
+         --tag\".",
+    ),
+    (
+        "help-private-usage-1",
+        "Mark the description of path private.",
+    ),
+    (
+        "help-private-details",
+        "Private descriptions describe paths like any other, but are left out of \"def \
+         export\" unless --include-private is given, so personal notes can be kept in a config \
+         shared with a team. Patterns can't be private.",
+    ),
+    (
+        "help-public-usage-1",
+        "Mark the private description of path shareable again.",
+    ),
+    (
+        "help-public-details",
+        "Undoes \"def private\" or \"def add --private\".",
+    ),
+    (
+        "help-ls-usage-1",
+        "List entries of dir (current dir by default) with their descriptions.",
+    ),
+    (
+        "help-ls-details",
+        "Entries are described the same way \"def <path>\" describes them, and entries without \
+         a description are listed without one.",
+    ),
+    (
+        "help-search-usage-1",
+        "Print descriptions and patterns whose path or text contains every word of query, best \
+         matches first.",
+    ),
+    (
+        "help-search-usage-2",
+        "Save query as name, to be run by \"def list @name\".",
+    ),
+    (
+        "help-search-fuzzy",
+        "Also match paths and text containing the characters of a word in order, such as \
+         \"dwnld\" matching \"Downloads\", as well as words matched by --stem and --typos 1.",
+    ),
+    (
+        "help-search-stem",
+        "Also match words with the same stem, such as \"backups\" and \"backup\".",
+    ),
+    (
+        "help-search-save",
+        "Save query instead of searching, replacing any search saved as name. Other flags \
+         can't be saved.",
+    ),
+    (
+        "help-search-typos",
+        "Also match words with up to n typos, such as \"bakup\" for \"backup\". A word is \
+         allowed one typo for every 4 characters.",
+    ),
+    (
+        "help-search-where",
+        "Print only matches of query (see \"Queries\" in \"def help\"). Words to search can \
+         then be omitted, to print all its matches.",
+    ),
+    (
+        "help-search-details",
+        "Case is ignored. Matches at the start of a word, and earlier in the path or text, \
+         rank higher, and substring matches rank higher than stems, which rank higher than \
+         typos and other fuzzy matches. For large configs, set \"search_index\" to true in \
+         settings.json to search an index kept in ~/.config/def instead, unless other flags \
+         are given: words of query then match only whole words or their starts, and matches \
+         near the current directory rank higher.",
+    ),
+    (
+        "help-remove-usage-1",
+        "Move description of file/dir at path to trash.",
+    ),
+    (
+        "help-remove-usage-2",
+        "Move pattern of dir at path to trash.",
+    ),
+    (
+        "help-remove-pattern",
+        "Remove the pattern of dir rather than its description.",
+    ),
+    (
+        "help-remove-details",
+        "Removed descriptions can be restored using \"def trash restore\".",
+    ),
+    (
+        "help-mv-usage-1",
+        "Rewrite the description and patterns of old to use new instead.",
+    ),
+    (
+        "help-mv-usage-2",
+        "Rewrite every description and pattern of old or of paths under old to use new \
+         instead. Use --dry-run to preview changes.",
+    ),
+    (
+        "help-mv-recursive",
+        "Rewrite paths under old as well as old itself. --prefix works too.",
+    ),
+    (
+        "help-mv-dry-run",
+        "Print changes without making them.",
+    ),
+    (
+        "help-mv-details",
+        "Useful after moving or renaming a directory. Entries that already exist under new are \
+         overwritten.",
+    ),
+    (
+        "help-trash-usage-1",
+        "List trashed descriptions.",
+    ),
+    (
+        "help-trash-usage-2",
+        "Restore the last trashed description of path.",
+    ),
+    (
+        "help-trash-usage-3",
+        "Delete all trashed descriptions for good.",
+    ),
+    (
+        "help-trash-details",
+        "Trashed descriptions are deleted for good after \"trash_retention_days\" (30 by \
+         default) as set in ~/.config/def/settings.json.",
+    ),
+    (
+        "help-drift-usage-1",
+        "Report described paths under root that no longer exist or changed drastically since \
+         they were described, and dirs in root lacking descriptions.",
+    ),
+    (
+        "help-drift-details",
+        "A path changed drastically if its kind changed (such as a file replaced by a dir).",
+    ),
+    (
+        "help-stats-usage-1",
+        "Print the number of descriptions and patterns, the deepest described path, and the \
+         descriptions added last, along with how many entries of dir can be described.",
+    ),
+    (
+        "help-stats-details",
+        "Entries of dir are those \"def ls\" lists, and they're covered if they have a \
+         description or a pattern applies to them. Entries that aren't covered are listed by \
+         path.",
+    ),
+    (
+        "help-undo-usage-1",
+        "Revert the last change made to the config, such as an add, a remove, or a mv.",
+    ),
+    (
+        "help-undo-details",
+        "Changes to descriptions and patterns are journaled in a file beside the config file \
+         (config.json.history for config.json), and undone last first. A change isn't undone \
+         if the descriptions or patterns it changed were changed since, such as by editing the \
+         config file by hand. Tags, metadata, and other records of a removed description \
+         aren't restored with it.",
+    ),
+    (
+        "help-history-usage-1",
+        "Show how the description and patterns of path changed, oldest change first.",
+    ),
+    (
+        "help-history-details",
+        "Lists the journaled changes of path (see \"def undo\"), each with the time it was \
+         made and the command that made it.",
+    ),
+    (
+        "help-stale-usage-1",
+        "Report descriptions whose paths were modified after the descriptions were last \
+         updated, or no longer exist.",
+    ),
+    (
+        "help-stale-days",
+        "Report only paths modified more than n days after their descriptions were updated.",
+    ),
+    (
+        "help-stale-details",
+        "Times descriptions are added and updated are recorded by add and edit. Descriptions \
+         added before times were recorded are only reported if their paths no longer exist.",
+    ),
+    (
+        "help-check-usage-1",
+        "Report descriptions and patterns mapped to paths that no longer exist.",
+    ),
+    (
+        "help-check-prune",
+        "Delete them, and print what was deleted.",
+    ),
+    (
+        "help-check-details",
+        "Every described path is checked, unlike drift which checks paths under a root. Globs, \
+         and keys that aren't absolute paths (such as those using variables), are skipped. \
+         Exits with status 2 if entries are reported without --prune. Deleted entries aren't \
+         moved to the trash, so consider \"def export\" first.",
+    ),
+    (
+        "help-events-usage-1",
+        "Watch the config, and print a JSON line for each description or pattern that is \
+         added, removed, or updated, until interrupted.",
+    ),
+    (
+        "help-events-follow",
+        "Keep running, and print events as changes happen.",
+    ),
+    (
+        "help-events-details",
+        "Each line is an object with the fields \"event\" (add, remove, or update), \"path\", \
+         \"pattern\" (null, children, recursive, or glob), \"before\", and \"after\", always \
+         in this order. Changes made at once are printed sorted by path, so the same change \
+         always prints the same lines. Changes made by any process are noticed within half a \
+         second.",
+    ),
+    (
+        "help-daemon-usage-1",
+        "Keep the config loaded, and answer lookups over a socket until interrupted.",
+    ),
+    (
+        "help-daemon-details",
+        "The daemon listens on a Unix socket next to the config file (such as \
+         config.json.sock), and reloads the config when it changes. While it runs, describing \
+         paths, list, and search ask it instead of reading the config, which makes them faster \
+         for large configs, and def falls back to reading the config if it doesn't answer. \
+         Editors can query the socket directly, with a line of JSON per query, such as \
+         {\"query\":\"describe\",\"path\":\"/srv\"}, answered by a line of JSON. Queries are \
+         describe (with \"path\", \"fs\", and \"inherit\"), list (with \"prefix\"), and search \
+         (with \"text\" and \"fuzzy\").",
+    ),
+    (
+        "help-maintain-usage-1",
+        "Back up config, delete expired trash and stale metadata, and check dir ($HOME by \
+         default) for drift. Prints a JSON summary, and exits with 2 if described paths are \
+         missing or changed. Safe to run repeatedly (e.g. from cron or a systemd timer).",
+    ),
+    (
+        "help-maintain-root",
+        "Check dir for drift instead of \"maintain_root\" of settings, or $HOME.",
+    ),
+    (
+        "help-maintain-details",
+        "The latest \"backups_kept\" (5 by default) backups of the config are kept in \
+         ~/.config/def/backups. A backup is only made when the config changed since the latest \
+         one.",
+    ),
+    (
+        "help-annotate-archive-usage-1",
+        "List members of a zip or tar archive with their descriptions, as if extracted in the \
+         current directory (or in dir given by --root). Requires def to be built with \
+         \"archive\" feature.",
+    ),
+    (
+        "help-annotate-archive-root",
+        "Describe members as if extracted in dir.",
+    ),
+    (
+        "help-annotate-archive-details",
+        "Supported archives are zip, tar, and gzipped tar files.",
+    ),
+    (
+        "help-doctor-usage-1",
+        "Check config for incompatible keys and pasted secrets.",
+    ),
+    (
+        "help-doctor-usage-2",
+        "Rewrite incompatible keys to use this platform's conventions.",
+    ),
+    (
+        "help-doctor-usage-3",
+        "Make the config accessible only by you.",
+    ),
+    (
+        "help-doctor-convert-paths",
+        "Rewrite incompatible keys instead of reporting them.",
+    ),
+    (
+        "help-doctor-fix-perms",
+        "Remove permissions of other users from the config file.",
+    ),
+    (
+        "help-doctor-details",
+        "Keys are incompatible if they use another platform's separators or roots, such as \
+         keys added on Windows and used on Linux. Entries that look like they hold secrets, \
+         such as tokens or passwords, are reported too, and so is a config readable by other \
+         users while it holds such entries or private ones (which is also reported whenever \
+         it's saved).",
+    ),
+    (
+        "help-export-usage-1",
+        "Write all descriptions and patterns to file, or print them.",
+    ),
+    (
+        "help-export-where",
+        "Export only descriptions and patterns matching query (see \"Queries\" in \"def \
+         help\").",
+    ),
+    (
+        "help-export-only",
+        "Export only descriptions, or only patterns (subset is \"descriptions\" or \
+         \"patterns\").",
+    ),
+    (
+        "help-export-include-private",
+        "Export private descriptions too, still marked private.",
+    ),
+    (
+        "help-export-details",
+        "file is written in the format of its extension (json, toml, yaml, or msgpack, if def \
+         is built with it), or JSON if it has none. Without a file, JSON is printed. Trashed \
+         and private descriptions aren't exported. Text matching the regular expressions of \
+         \"redact\" in settings is replaced by [REDACTED], or only the text of their groups if \
+         they have any. If \"redact\" isn't set, common secrets (such as access tokens, \
+         private keys, and passwords) are masked, and nothing is if it's empty.",
+    ),
+    (
+        "help-import-usage-1",
+        "Add descriptions and patterns of an exported file.",
+    ),
+    (
+        "help-import-overwrite",
+        "Replace existing entries which conflict with imported ones.",
+    ),
+    (
+        "help-import-keep-existing",
+        "Keep existing entries which conflict with imported ones (the default).",
+    ),
+    (
+        "help-import-interactive",
+        "Ask which entry to keep for each conflict.",
+    ),
+    (
+        "help-import-only",
+        "Import only descriptions, or only patterns, of file (subset is \"descriptions\" or \
+         \"patterns\").",
+    ),
+    (
+        "help-import-details",
+        "Entries conflict if they're mapped to the same path, but differ. file is read in the \
+         format of its extension, and is checked before the config is changed: nothing is \
+         imported if it has invalid patterns. Each added, updated, or kept entry is printed.",
+    ),
+    (
+        "help-apply-usage-1",
+        "Add descriptions of an annotation file.",
+    ),
+    (
+        "help-apply-dry-run",
+        "Print what would be created and updated, without saving.",
+    ),
+    (
+        "help-apply-details",
+        "Each line of an annotation file is a path and its description, separated by a tab, \
+         with tabs, newlines, and backslashes in descriptions written as \\t, \\n, and \\\\. \
+         Blank lines and lines starting with # are ignored. Files with the extension of a \
+         format, such as layout.yaml, hold a map of paths to descriptions instead. Relative \
+         paths are resolved against the file's directory, . being the directory itself. \
+         Existing descriptions of the file's paths are replaced, and each created or updated \
+         description is printed.",
+    ),
+    (
+        "help-sync-readmes-usage-1",
+        "Sync descriptions of directories with files in them.",
+    ),
+    (
+        "help-sync-readmes-readme",
+        "Keep descriptions in the front matter of README.md, as \"def: <description>\", rather \
+         than in .dirdesc.",
+    ),
+    (
+        "help-sync-readmes-dry-run",
+        "Print what would be synced, without changing anything.",
+    ),
+    (
+        "help-sync-readmes-details",
+        "Descriptions of root and of directories under it are kept in a .dirdesc file in each \
+         directory, holding nothing but the description, so that they can be read and edited \
+         along with the files. Syncing is two-way: a file edited after the description of its \
+         directory was last updated is read back, and the description is written to the file \
+         otherwise. Hidden directories, and directories described only by patterns, aren't \
+         synced.",
+    ),
+    (
+        "help-pack-usage-1",
+        "Install a pattern pack from a file or an http(s) URL.",
+    ),
+    (
+        "help-pack-usage-2",
+        "Remove patterns of an installed pack.",
+    ),
+    (
+        "help-pack-usage-3",
+        "List installed packs.",
+    ),
+    (
+        "help-pack-prefix",
+        "Install the pack's patterns under dir, rather than under the pack's own prefix.",
+    ),
+    (
+        "help-pack-key",
+        "Require the pack to be signed by key, a minisign public key or a file holding one, \
+         rather than by a trusted key.",
+    ),
+    (
+        "help-pack-yes",
+        "Install a pack fetched from a URL without asking.",
+    ),
+    (
+        "help-pack-details",
+        "See \"Pattern packs\" in \"def help\" for the format of packs. Installing a pack \
+         replaces an installed pack of the same name. Patterns changed since their pack was \
+         installed are kept when it's removed. Before installing a pack fetched from a URL, \
+         def shows who made it, whether its signature was verified, and the paths it adds \
+         patterns for, and asks to continue.",
+    ),
+    (
+        "help-profile-usage-1",
+        "List profiles.",
+    ),
+    (
+        "help-profile-usage-2",
+        "Create a profile with no descriptions.",
+    ),
+    (
+        "help-profile-usage-3",
+        "Delete a profile along with its descriptions.",
+    ),
+    (
+        "help-profile-details",
+        "Profiles keep separate sets of descriptions, such as one for work and one for \
+         personal machines, so that they never mix. A profile is used by passing --profile \
+         <name> to any sub-command, or by setting $DEF_PROFILE. Its descriptions are kept in \
+         ~/.config/def/<name>.json, and other settings are shared by all profiles. Names are \
+         made of letters, digits, \"-\", and \"_\".",
+    ),
+    (
+        "help-sync-usage-1",
+        "Merge descriptions of the remote set in settings into the config.",
+    ),
+    (
+        "help-sync-usage-2",
+        "Merge descriptions of the remote into the config, and push the result to the remote.",
+    ),
+    (
+        "help-sync-details",
+        "The remote is set by \"sync_remote\" in settings: a git repository, holding \
+         descriptions in def.json, or an http(s) URL of a JSON file, such as one on a WebDAV \
+         server, read with GET and written with PUT. git or curl must be installed. Entries \
+         changed on one side since the last sync take that side's change. Entries changed on \
+         both sides conflict: the description updated last is kept, as is the local pattern, \
+         and each conflict is printed. A sync can be reverted with \"def undo\", which leaves \
+         the remote as it is. Requires def to be built with \"sync\" feature.",
+    ),
+    (
+        "help-hook-usage-1",
+        "Print a snippet describing directories as you cd into them.",
+    ),
+    (
+        "help-hook-details",
+        "shell is bash, zsh, or fish. Once evaluated by the shell, the snippet prints the \
+         description of the current directory whenever it changes, using \"def --quiet\", so \
+         nothing is printed for undescribed directories. Add it to your shell's startup file, \
+         such as ~/.bashrc: eval \"$(def hook bash)\". For fish, add \"def hook fish | \
+         source\" to ~/.config/fish/config.fish.",
+    ),
+    (
+        "help-completions-usage-1",
+        "Print a script completing sub-commands, flags, and described paths.",
+    ),
+    (
+        "help-completions-details",
+        "shell is bash, zsh, or fish. Arguments are completed with paths that have \
+         descriptions or patterns, read from your config rather than the file system, and with \
+         files if none match. Add the script to your shell's startup file, such as ~/.bashrc: \
+         eval \"$(def completions bash)\". For zsh, evaluate it after compinit. For fish, save \
+         it to ~/.config/fish/completions/def.fish.",
+    ),
+    (
+        "help-from-inode-usage-1",
+        "Print the description of an open file, found by its device and inode.",
+    ),
+    (
+        "help-from-inode-details",
+        "dev is \"major,minor\" as in the DEVICE column of lsof, or a device number as printed \
+         by stat. The file is found among files that processes you can inspect have open, \
+         through /proc. Needs Linux, and def built with the \"procfs\" feature.",
+    ),
+    (
+        "help-from-fd-usage-1",
+        "Print the description of the file a process has open as fd.",
+    ),
+    (
+        "help-from-fd-details",
+        "pid and fd are as in the PID and FD columns of lsof, without the access mode (such as \
+         \"3\" for \"3r\"). The path is read from /proc. Needs Linux, and def built with the \
+         \"procfs\" feature.",
+    ),
+    (
+        "help-tour-usage-1",
+        "Take a guided tour of def's sub-commands.",
+    ),
+    (
+        "help-tour-details",
+        "Each step explains a sub-command and runs it once enter is pressed. Commands are run \
+         against a sandbox config in a temporary directory, which is deleted when the tour \
+         ends, so your own config isn't touched.",
+    ),
+    (
+        "help-browse-usage-1",
+        "Browse descriptions and patterns in an interactive tree.",
+    ),
+    (
+        "help-browse-details",
+        "The tree is laid out as by list, with the selected entry shown in a preview pane. Use \
+         arrows (or j and k) to move, left and right (or h and l) to fold and unfold \
+         directories, e to edit the selected entry, d to move it to the trash, / to search, \
+         and q to quit. Changes are saved as they're made, and other def commands changing the \
+         config wait until the browser is closed. Requires def to be built with \"tui\" \
+         feature.",
+    ),
+    (
+        "help-bundle-usage-1",
+        "Write settings, the config, installed packs, and translations to a tar file.",
+    ),
+    (
+        "help-bundle-usage-2",
+        "Replace this machine's settings, config, packs, and translations with those of a \
+         bundle.",
+    ),
+    (
+        "help-bundle-details",
+        "Bundles carry def's state to machines without network access, such as air-gapped \
+         servers. Applying a bundle backs up the existing config first, as maintain does, and \
+         leaves packs and translations missing from the bundle in place. Backups and the \
+         search index aren't bundled. Requires def to be built with \"archive\" feature.",
+    ),
+    (
+        "help-fixtures-usage-1",
+        "Create a directory tree and a config describing it from a spec, for testing, and \
+         print the path of the directory holding them.",
+    ),
+    (
+        "help-fixtures-details",
+        "A spec lists the files of the tree under \"tree\" (paths ending with \"/\" are dirs, \
+         and files can have \"contents\" or a \"size\"), and the config under \"config\", laid \
+         out like a .def.json file with keys relative to the tree. Specs can be JSON, TOML, or \
+         YAML (with the yaml feature), by extension, as formats of the config are. The tree is \
+         created in the \"root\" dir of a new temporary dir, next to its config, \
+         \"config.json\", which can be used with --config.",
+    ),
+    (
+        "help-help-usage-1",
+        "Print this help message, or details of command.",
+    ),
+    (
+        "help-global-plain",
+        "Print output without colors or alignment.",
+    ),
+    (
+        "help-global-raw",
+        "Print descriptions as written, without rendering Markdown or wrapping.",
+    ),
+    (
+        "help-global-no-resolve",
+        "Resolve paths without following symlinks or requiring them to exist.",
+    ),
+    (
+        "help-global-no-stat",
+        "Leave metadata and git placeholders of patterns unfilled, without reading paths.",
+    ),
+    (
+        "help-global-inherit",
+        "Describe a path without a description by its nearest described ancestor.",
+    ),
+    (
+        "help-global-allow-commands",
+        "Run commands capturing descriptions, for add --from-command and --refresh.",
+    ),
+    (
+        "help-global-config",
+        "Keep descriptions in file, rather than in the config chosen by --profile, \
+         $DEF_CONFIG, $DEF_PROFILE, or settings, in this order.",
+    ),
+    (
+        "help-global-profile",
+        "Use the descriptions of a profile (see \"def help profile\"), rather than \
+         $DEF_PROFILE's or the default ones.",
+    ),
+    (
+        "help-global-format",
+        "Print descriptions of a path, ls, and list as \"human\" (the default), \"plain\" \
+         (like --plain), \"json\", or \"tsv\".",
+    ),
+    ("unknown-command", "unknown command \"{}\""),
+    ("invalid-arguments", "invalid argument list"),
+    ("no-description", "no available description"),
+    (
+        "replaced-description",
+        "replaced description of {}, which was \"{}\" (use --append to add to it)",
+    ),
+    ("no-drift", "no drift found"),
+    ("no-stale", "no stale descriptions"),
+    ("no-dead", "all described paths exist"),
+    ("prune-hint", "Use \"def check --prune\" to delete them."),
+    ("pruned", "deleted {} entries"),
+    ("no-matches", "no matching descriptions"),
+    ("no-saved-searches", "no saved searches"),
+    ("unknown-saved-search", "no search is saved as \"{}\""),
+    ("saved-search", "saved search {}"),
+    ("hidden-entry", "({} entry)"),
+    ("hidden-entries", "({} entries)"),
+    ("section-missing", "Missing"),
+    ("section-changed", "Changed"),
+    ("section-modified", "Modified"),
+    ("section-deleted", "Deleted"),
+    ("stale-modified", "{}: described {}, modified {}"),
+    ("section-undescribed", "Undescribed"),
+    ("drift-changed", "{}: was {}, now {}"),
+    ("metadata-file", "file ({} bytes)"),
+    ("metadata-directory", "directory ({} entries)"),
+    ("metadata-symlink", "symlink"),
+    ("moved-to-trash", "{}: moved to trash"),
+    ("no-description-to-remove", "{}: no description to remove"),
+    ("no-pattern-to-remove", "{}: no pattern to remove"),
+    ("overwrites", " (overwrites)"),
+    ("would-rename", "would rename {} entries"),
+    ("renamed", "renamed {} entries"),
+    ("restored", "{}: restored"),
+    ("deleted-trash", "deleted {} trashed entries"),
+    ("minute-ago", "{} minute ago"),
+    ("minutes-ago", "{} minutes ago"),
+    ("hour-ago", "{} hour ago"),
+    ("hours-ago", "{} hours ago"),
+    ("day-ago", "{} day ago"),
+    ("days-ago", "{} days ago"),
+    ("no-problems", "no problems found"),
+    (
+        "possible-secret",
+        "{} may hold a secret ({})",
+    ),
+    (
+        "run-convert-paths",
+        "run \"def doctor --convert-paths\" to fix incompatible keys",
+    ),
+    (
+        "key-collision",
+        "{} wasn't converted, as {} is already described",
+    ),
+    (
+        "expanded-key-collision",
+        "{} isn't expanded when describing paths, as {} is already described",
+    ),
+    ("unknown-color-setting", "unknown color setting \"{}\""),
+    (
+        "unknown-shell",
+        "unknown shell \"{}\", expected bash, zsh, or fish",
+    ),
+    ("invalid-color", "invalid color \"{}\" for \"{}\""),
+    (
+        "archive-unsupported",
+        "def was built without \"archive\" feature",
+    ),
+    ("invalid-translations", "invalid translations in {}"),
+    ("failed-back-up-config", "failed to back up config"),
+    ("failed-create-bundle", "failed to create bundle"),
+    ("failed-read-bundle", "failed to read bundle"),
+    ("failed-apply-bundle", "failed to apply bundle"),
+    ("ignored-bundle-member", "ignored {} in bundle"),
+    ("bundle-created", "bundled {} files into {}"),
+    ("bundle-applied", "applied {} files from {}"),
+    ("backed-up-config", "backed up config to {}"),
+    ("section-written", "Written"),
+    ("section-used", "Used"),
+    ("section-overridden", "Overridden"),
+    ("section-resolution", "Resolution order"),
+    ("section-placeholders", "Placeholders"),
+    ("section-description", "Description"),
+    ("section-provenance", "Provenance"),
+    ("section-tags", "Tags"),
+    ("section-file", "File"),
+    ("section-totals", "Totals"),
+    ("section-deepest", "Deepest described path"),
+    ("section-recent", "Added last"),
+    ("section-coverage", "Coverage of {}"),
+    ("stats-descriptions", "{} descriptions"),
+    ("stats-patterns", "{} patterns: {} of dirs, {} recursive, {} globs"),
+    ("stats-coverage", "{} of {} entries described ({})"),
+    ("info-specific", "added to the path itself"),
+    ("info-inherited", "inherited from {}"),
+    ("info-repository", "root of a git repository"),
+    ("info-pattern", "pattern of {} [{}]"),
+    ("info-command", "captured from `{}`"),
+    ("info-added", "added {}"),
+    ("info-updated", "updated {}"),
+    ("info-private", "private"),
+    ("info-missing", "doesn't exist"),
+    ("info-modified", "modified {}"),
+    ("info-owner", "owned by {}"),
+    ("info-mount", "on the file system mounted at {}"),
+    ("info-recorded", "was a {} when described"),
+    ("priority", "priority {}"),
+    ("inherited-from", "inherited from {}: {}"),
+    ("failed-create-summary", "failed to create summary"),
+    ("failed-get-home", "failed to find the home directory"),
+    ("failed-get-absolute-path", "failed to get absolute path"),
+    ("failed-get-current-dir", "failed to get current directory"),
+    ("failed-open-config", "failed to open config"),
+    ("failed-read-archive", "failed to read archive"),
+    ("failed-read-config", "failed to read config"),
+    ("failed-read-dir", "failed to read dir"),
+    ("failed-read-root", "failed to read root"),
+    ("failed-read-settings", "failed to read settings"),
+    ("failed-restore", "failed to restore description"),
+    ("failed-write-config", "failed to write config to file"),
+    ("failed-read-stdin", "failed to read from stdin"),
+    ("failed-lock-config", "failed to lock config file"),
+    ("invalid-pattern", "invalid pattern"),
+    ("invalid-query", "invalid query"),
+    ("invalid-config", "invalid config"),
+    ("failed-run-editor", "failed to run editor"),
+    ("empty-description", "description is empty, nothing was saved"),
+    ("tui-unsupported", "def was built without \"tui\" feature"),
+    (
+        "procfs-unsupported",
+        "def was built without \"procfs\" feature, which needs Linux",
+    ),
+    ("failed-read-proc", "failed to read /proc"),
+    ("daemon-listening", "listening on {}"),
+    ("failed-generate-fixture", "failed to generate fixture"),
+    ("failed-run-daemon", "failed to run daemon"),
+    ("daemon-unsupported", "the daemon needs Unix domain sockets"),
+    (
+        "commands-disabled",
+        "running commands is disabled, use --allow-commands or set \"allow_commands\" in settings",
+    ),
+    ("failed-run-command", "failed to run command"),
+    ("failed-refresh", "failed to refresh {}: {}"),
+    (
+        "not-from-command",
+        "the description of {} isn't the output of a command",
+    ),
+    ("refreshed", "refreshed {} descriptions"),
+    (
+        "invalid-file-id",
+        "invalid file \"{}\", expected <dev>:<inode> such as 8,1:1234",
+    ),
+    (
+        "invalid-fd",
+        "invalid file descriptor \"{}\", expected <pid>:<fd> such as 1234:3",
+    ),
+    ("inode-not-open", "no process has file {} open"),
+    ("browse-needs-terminal", "browse needs a terminal"),
+    ("failed-run-browser", "failed to run browser"),
+    ("browse-title", "Descriptions"),
+    ("browse-title-search", "Descriptions matching \"{}\""),
+    ("browse-empty", "Nothing is described yet."),
+    (
+        "browse-keys",
+        "↑↓ move  ←→ fold  e edit  d delete  / search  q quit",
+    ),
+    ("browse-edit", "Edit:"),
+    ("browse-search", "Search:"),
+    ("browse-delete", "Move to trash? [y/N]"),
+    ("browse-saved", "{}: saved"),
+    ("browse-tags", "Tags: {}"),
+    ("browse-times", "Added {}, updated {}."),
+    ("nothing-changed", "Nothing changed."),
+    ("nothing-saved", "Nothing was saved."),
+    ("edit-again", "Edit again? [Y/n]"),
+    ("no-answers", "n,no"),
+    ("failed-read-import", "failed to read file to import"),
+    ("failed-save-search", "failed to save search"),
+    ("failed-export", "failed to export descriptions"),
+    ("failed-redact", "failed to read redaction rules"),
+    (
+        "exposed-config",
+        "{} is readable by other users but holds private or secret-like descriptions, \
+         run \"def doctor --fix-perms\" to restrict it",
+    ),
+    ("fixed-perms", "made {} accessible only by you"),
+    ("failed-fix-perms", "failed to change permissions of config"),
+    ("replace-existing", "Replace existing? [y/N]"),
+    ("yes-answers", "y,yes"),
+    ("import-added", "added"),
+    ("import-updated", "updated"),
+    ("import-kept", "kept existing"),
+    ("imported", "{} added, {} updated, {} conflicts kept"),
+    ("failed-read-annotations", "failed to read annotations"),
+    ("apply-created", "created"),
+    ("apply-updated", "updated"),
+    ("applied", "{} created, {} updated"),
+    ("would-apply", "would create {} and update {} descriptions"),
+    ("synced-to-file", "written to {}"),
+    ("synced-from-file", "read back from {}"),
+    ("synced", "{} written, {} read back"),
+    ("would-sync", "would write {} and read back {} descriptions"),
+    ("failed-sync", "failed to sync descriptions"),
+    ("failed-undo", "failed to undo"),
+    ("nothing-to-undo", "nothing to undo"),
+    ("undone", "Undid \"def {}\", made {}"),
+    ("undo-removed", "{}{} removed"),
+    ("undo-restored", "{}{} restored"),
+    ("undo-reverted", "{}{} set back to: {}"),
+    ("failed-read-history", "failed to read history"),
+    ("no-history", "no history of {}"),
+    ("history-removed", "removed"),
+    ("failed-sync-remote", "failed to sync with remote"),
+    ("section-pulled", "Pulled"),
+    ("section-pushed", "Pushed"),
+    ("section-conflicts", "Conflicts"),
+    ("sync-removed", "removed"),
+    ("sync-kept-local", "{}{}: kept local change, remote one was: {}"),
+    ("sync-kept-remote", "{}{}: kept remote change, local one was: {}"),
+    ("synced-pull", "{} pulled, {} conflicts"),
+    ("synced-push", "{} pulled, {} pushed, {} conflicts"),
+    ("sync-unsupported", "def was built without \"sync\" feature"),
+    ("failed-read-pack", "failed to read pack"),
+    ("failed-write-pack", "failed to write installed pack"),
+    ("failed-download", "failed to download"),
+    ("invalid-pack", "invalid pack"),
+    ("installed-pack", "installed pack {} ({} patterns)"),
+    ("removed-pack", "removed pack {} ({} patterns)"),
+    ("pack-not-installed", "no pack named \"{}\" is installed"),
+    ("no-packs", "no installed packs"),
+    ("no-profiles", "no profiles"),
+    ("active-profile", "{} (in use)"),
+    ("created-profile", "created profile {}"),
+    ("deleted-profile", "deleted profile {}"),
+    (
+        "unknown-profile",
+        "profile \"{}\" doesn't exist, create it using \"def profile create {}\"",
+    ),
+    ("failed-use-profile", "failed to use profile"),
+    ("failed-list-profiles", "failed to list profiles"),
+    ("failed-create-profile", "failed to create profile"),
+    ("failed-delete-profile", "failed to delete profile"),
+    ("failed-read-signature", "failed to read signature of pack"),
+    ("unverified-pack", "failed to verify pack"),
+    ("pack-by", "by {}"),
+    ("pack-signed", "Signed by a trusted key."),
+    ("pack-unsigned", "Not verified: no trusted keys are set."),
+    ("pack-affects", "Patterns will be added for:"),
+    ("install-pack", "Install? [y/N]"),
+    ("pack-declined", "pack wasn't installed"),
+    ("failed-read-local", "failed to read local file"),
+    ("failed-write-local", "failed to write local file"),
+    (
+        "no-local-file",
+        "no local file found for {}, and it isn't under the current directory",
+    ),
+    ("failed-create-sandbox", "failed to create sandbox"),
+    ("failed-delete-sandbox", "failed to delete sandbox"),
+    (
+        "tour-intro",
+        "Welcome to def! This tour runs a few commands against a sandbox in {},\nso your own descriptions aren't touched.",
+    ),
+    ("tour-step", "Step {} of {}"),
+    ("tour-prompt", "Press enter to run it, or q to quit."),
+    (
+        "tour-done",
+        "That's it! The sandbox was deleted. Run \"def help\" to learn more.",
+    ),
+];
+
+/// Spanish messages.
+const ES: &[(&str, &str)] = &[
+    ("error-label", "Error"),
+    ("warning-label", "Aviso"),
+    ("error-label-plain", "ERROR"),
+    ("warning-label-plain", "AVISO"),
+    ("usage-heading", "Uso"),
+    ("try-help", "Pruebe \"def help\" para más detalles."),
+    ("flags-heading", "Opciones"),
+    ("examples-heading", "Ejemplos"),
+    (
+        "help-for-command",
+        "Ejecute \"def help <comando>\" para ver los detalles de un comando.",
+    ),
+    ("help-intro", "def guarda por usted las descripciones de archivos y directorios."),
+    ("help-topic-descriptions", "Descripciones"),
+    (
+        "help-topic-descriptions-text",
+        concat!(
+            "  Para describir un archivo o directorio use el subcomando add, que simplemente\n",
+            "  asocia una descripción a una ruta absoluta.\n",
+            "\n",
+            "  Las rutas se resuelven a su forma canónica, siguiendo los enlaces simbólicos,\n",
+            "  por lo que deben existir. Con --no-resolve, las rutas se unen en cambio al\n",
+            "  directorio actual y se eliminan \".\" y \"..\", de modo que se pueden describir rutas\n",
+            "  que aún no existen, y los enlaces simbólicos se conservan tal como se dan. Esto\n",
+            "  se aplica por igual a add, a pattern y a describir una ruta.\n",
+            "\n",
+            "  El subcomando pattern sirve para describir todos los hijos de un directorio\n",
+            "  mediante un rasgo común. Al usar pattern, la descripción se asocia a un\n",
+            "  directorio, pero solo se usa para describir a sus hijos. Si el patrón contiene\n",
+            "  un comodín \"*\", se reemplaza por el nombre del hijo.\n",
+            "\n",
+            "  Los patrones recursivos (añadidos con --recursive) describen todos los\n",
+            "  descendientes de un directorio. Además de \"*\", un comodín doble \"**\" se\n",
+            "  reemplaza por la ruta del descendiente relativa al directorio.\n",
+            "\n",
+            "  Los patrones añadidos con --files-only, --dirs-only o --symlinks-only describen\n",
+            "  solo los hijos de ese tipo, y tienen prioridad sobre el patrón sin tipo del\n",
+            "  mismo directorio. Las rutas que no existen solo se describen con patrones sin\n",
+            "  tipo.\n",
+            "\n",
+            "  El subcomando glob describe todas las rutas que coinciden con un glob, donde \"*\"\n",
+            "  coincide con cualquier carácter salvo \"/\", \"?\" con un único carácter, y \"**\"\n",
+            "  con cualquier carácter, incluido \"/\". Un glob sin \"/\" (como \"*.log\") se compara\n",
+            "  con los nombres de las rutas. En la descripción, \"*\" se reemplaza por el nombre\n",
+            "  de la ruta, y \"**\" por la ruta completa.\n",
+            "\n",
+            "  Una descripción específica siempre tiene prioridad sobre los patrones. Cuando\n",
+            "  varios patrones se aplican a una ruta, se usa el asociado más cerca de ella: el\n",
+            "  padre para los patrones, el ancestro para los patrones recursivos, y el\n",
+            "  directorio con el que empieza el glob para los globs (\"/srv\" para \"/srv/*.log\",\n",
+            "  ninguno para \"*.log\"). Los empates se resuelven a favor del patrón con mayor\n",
+            "  --priority, luego de los globs, los patrones y los patrones recursivos, y luego\n",
+            "  del glob más específico. \"def explain <path>\" muestra cuál se usa. Este orden\n",
+            "  se puede cambiar con \"resolution_order\" en los ajustes, como [\"description\",\n",
+            "  \"glob\", [\"pattern\", \"recursive\"]] para preferir los globs a los patrones de\n",
+            "  directorios. Las etapas omitidas (como \"repository\") nunca se usan.\n",
+            "\n",
+            "  Una ruta a la que no se aplica ninguna descripción ni patrón no tiene\n",
+            "  descripción, salvo que se dé --inherit (o se active \"inherit\" en los ajustes),\n",
+            "  en cuyo caso se muestra la descripción de su ancestro descrito más cercano,\n",
+            "  marcada como heredada de él.\n",
+            "\n",
+            "  Las rutas coinciden con descripciones y patrones solo si se escriben igual. En\n",
+            "  sistemas de archivos que ignoran mayúsculas y minúsculas, configure \"matching\"\n",
+            "  en los ajustes, como {\"case_insensitive\": true, \"normalize_unicode\": true}, para\n",
+            "  que \"~/Downloads\" coincida con \"~/downloads\", y los nombres con acentos coincidan\n",
+            "  sea cual sea su codificación.\n",
+            "\n",
+            "  Los patrones y globs también pueden contener marcadores con nombre: {name}\n",
+            "  (igual que \"*\"), {stem} (nombre sin extensión), {ext} (extensión sin punto),\n",
+            "  {parent} (nombre del directorio que lo contiene) y {path} (la ruta completa). Use\n",
+            "  {{ y }} para llaves literales. Se rechazan los patrones con marcadores\n",
+            "  desconocidos o llaves sin pareja.\n",
+            "\n",
+            "  Los marcadores de metadatos se rellenan a partir de la ruta descrita en el disco:\n",
+            "  {size} (como \"1.5 KiB\", o \"3 entries\" para directorios), {mtime} (última\n",
+            "  modificación, en UTC) y {type} (file, directory o symlink). Se dejan tal cual\n",
+            "  para las rutas que no existen, y con --no-stat.\n",
+            "\n",
+            "  Si def se compila con la característica \"git\", los marcadores de git se rellenan\n",
+            "  a partir del repositorio git de la ruta descrita: {git_branch}, {git_remote} (la\n",
+            "  URL de origin) y {git_last_commit} (hash y resumen). Las raíces de repositorios\n",
+            "  a las que no se aplica ninguna descripción ni patrón se describen por su rama y\n",
+            "  su remoto.\n",
+            "\n",
+            "  Por ejemplo:\n",
+            "\n",
+            "  $ def pattern dir \"* es hijo de dir\"\n",
+            "  $ def dir/temp\n",
+            "  /path/to/dir/temp: temp es hijo de dir\n",
+            "\n",
+            "Las descripciones y los patrones se guardan en ~/.config/def/config.json, que\n",
+            "asocia cada descripción a una ruta absoluta y se puede ampliar o ajustar a\n",
+            "mano.\n",
+            "\n",
+            "  Los archivos de def se guardan en $XDG_CONFIG_HOME/def en lugar de ~/.config/def\n",
+            "  si XDG_CONFIG_HOME está definida. Se puede usar otro archivo de configuración\n",
+            "  con --config <file> o $DEF_CONFIG, o con \"config_file\" en los ajustes. Las\n",
+            "  opciones tienen prioridad sobre las variables de entorno, que la tienen sobre los\n",
+            "  ajustes.",
+        ),
+    ),
+    ("help-topic-local-files", "Archivos locales"),
+    (
+        "help-topic-local-files-text",
+        concat!(
+            "  Las descripciones también pueden guardarse en un archivo .def.json (o .def) en\n",
+            "  cualquier directorio, como la raíz de un repositorio, para confirmarlo junto con\n",
+            "  él. Las claves de los archivos locales son relativas a su directorio (\".\" es el\n",
+            "  propio directorio). Al describir una ruta, los archivos locales de la ruta y de\n",
+            "  sus ancestros se combinan sobre la configuración, y gana el más cercano. Use\n",
+            "  \"def add --local\" para añadir a ellos.",
+        ),
+    ),
+    ("help-topic-storage", "Almacenamiento"),
+    (
+        "help-topic-storage-text",
+        concat!(
+            "  La configuración se puede guardar en otro lugar definiendo \"config_file\" en\n",
+            "  settings.json. Se guarda como JSON por defecto, o según su extensión: como TOML\n",
+            "  (.toml), YAML (.yaml o .yml), MessagePack (.msgpack o .mp), o en una base de\n",
+            "  datos SQLite (.db o .sqlite). TOML (activado por defecto), YAML, MessagePack y\n",
+            "  SQLite requieren compilar def con las características \"toml\", \"yaml\", \"msgpack\"\n",
+            "  y \"sqlite\". Defina \"store\" como \"json\", \"toml\", \"yaml\", \"msgpack\", \"sqlite\" o\n",
+            "  \"crdt\" en settings.json para elegirlo explícitamente.\n",
+            "\n",
+            "  Una configuración guardada en una carpeta sincronizada por una herramienta como\n",
+            "  Syncthing o Dropbox puede ser un archivo CRDT (.crdt), que registra cuándo\n",
+            "  cambió cada entrada por última vez. Si se cambia en dos máquinas a la vez, def\n",
+            "  combina la copia en conflicto que deja la herramienta de sincronización,\n",
+            "  conservando el último cambio de cada entrada, y borra la copia.\n",
+            "\n",
+            "  Los archivos se guardan de forma atómica, así que un fallo nunca deja una\n",
+            "  configuración a medio escribir. Los comandos que cambian la configuración la\n",
+            "  bloquean (con un archivo .lock junto a ella) hasta terminar, para que las\n",
+            "  invocaciones simultáneas no pierdan los cambios de las demás.",
+        ),
+    ),
+    ("help-topic-trash", "Papelera"),
+    (
+        "help-topic-trash-text",
+        concat!(
+            "  Las descripciones eliminadas se mueven a una papelera, desde la que se pueden\n",
+            "  restaurar. Las descripciones de la papelera se borran definitivamente pasados\n",
+            "  \"trash_retention_days\" días (30 por defecto), según se defina en\n",
+            "  ~/.config/def/settings.json.",
+        ),
+    ),
+    ("help-topic-maintenance", "Mantenimiento"),
+    (
+        "help-topic-maintenance-text",
+        concat!(
+            "  def maintain conserva las últimas \"backups_kept\" (5 por defecto) copias de\n",
+            "  seguridad de la configuración en ~/.config/def/backups. Solo se hace una copia\n",
+            "  cuando la configuración cambió desde la última. El directorio en el que se\n",
+            "  comprueban los cambios se puede definir con \"maintain_root\" en\n",
+            "  ~/.config/def/settings.json.",
+        ),
+    ),
+    ("help-topic-sync", "Sincronización"),
+    (
+        "help-topic-sync-text",
+        concat!(
+            "  def sync pull y push combinan la configuración con una copia remota, definida\n",
+            "  por \"sync_remote\" en ~/.config/def/settings.json, como {\"sync_remote\":\n",
+            "  \"git@example.com:me/descriptions.git\"}. El remoto es un repositorio git que\n",
+            "  guarda la configuración como def.json, o una URL http(s) de un archivo JSON, que\n",
+            "  se lee con GET y se escribe con PUT. Para acceder a él debe estar instalado git\n",
+            "  o curl, y def debe compilarse con la característica \"sync\".",
+        ),
+    ),
+    ("help-topic-colors", "Colores"),
+    (
+        "help-topic-colors-text",
+        concat!(
+            "  Los colores se definen en el mapa \"colors\" de ~/.config/def/settings.json, que\n",
+            "  asocia error, warning, path, description, pattern, glob, heading y code a un\n",
+            "  nombre de color (como \"red\" o \"bright blue\"), un código de 256 colores (como\n",
+            "  \"208\"), un valor hexadecimal (como \"#ff8700\") o \"none\". Defina NO_COLOR para\n",
+            "  desactivar todos los colores. Los colores también se desactivan cuando la salida\n",
+            "  no es una terminal.\n",
+            "\n",
+            "  La opción global --plain (o \"plain_output\": true en settings.json) muestra la\n",
+            "  salida sin colores ni alineación, como simples líneas \"ruta: descripción\", y\n",
+            "  marca los errores con \"ERROR:\", lo que es más fácil de seguir con lectores de\n",
+            "  pantalla.",
+        ),
+    ),
+    ("help-topic-scripting", "Scripts"),
+    (
+        "help-topic-scripting-text",
+        concat!(
+            "  Con --format json, describir una ruta muestra un objeto con los campos \"path\",\n",
+            "  \"description\" y \"source\" (\"specific\" o \"pattern\"), que son null si la ruta no\n",
+            "  tiene descripción. ls y list muestran arrays de tales objetos. Con --format tsv,\n",
+            "  los mismos campos se muestran como líneas separadas por tabuladores, con los\n",
+            "  tabuladores y saltos de línea de las descripciones reemplazados por espacios.\n",
+            "  \"def --stdin\" describe de una vez muchas rutas leídas de stdin, y \"def --quiet\n",
+            "  <path>\" muestra solo la descripción, y termina con 1 sin mostrar nada si no la\n",
+            "  hay.",
+        ),
+    ),
+    ("help-topic-queries", "Consultas"),
+    (
+        "help-topic-queries-text",
+        concat!(
+            "  list, search y export aceptan una consulta con --where, como \"path:/var/** AND\n",
+            "  (kind:glob OR NOT text:temp)\". Todos los términos deben coincidir, salvo que se\n",
+            "  unan con OR, y NOT niega un término. Un término es uno de:\n",
+            "\n",
+            "    path:<path>       Entradas de path y de las rutas bajo ella, o que coinciden\n",
+            "                      con ella si contiene comodines (como los globs).\n",
+            "    text:<word>       Entradas cuyo texto contiene word.\n",
+            "    kind:<kind>       Entradas description, pattern, recursive o glob.\n",
+            "    modified:<date>   Entradas cuya ruta se modificó por última vez en date (en\n",
+            "                      UTC, como AAAA-MM-DD), o antes o después, con <, <=, >, >=.\n",
+            "    <word>            Entradas cuya ruta o texto contiene word.\n",
+            "\n",
+            "  Se ignoran mayúsculas y minúsculas, y los valores con espacios van entre\n",
+            "  comillas, como text:\"web root\".",
+        ),
+    ),
+    ("help-topic-pattern-packs", "Paquetes de patrones"),
+    (
+        "help-topic-pattern-packs-text",
+        concat!(
+            "  Un paquete es un conjunto de patrones para compartir, escrito en JSON (o en otro\n",
+            "  formato de archivos de configuración, según su extensión), como:\n",
+            "\n",
+            "    {\"name\": \"linux-server\", \"author\": \"...\", \"description\": \"...\",\n",
+            "     \"prefix\": \"/\", \"patterns\": [\n",
+            "       {\"path\": \"srv\", \"pattern\": \"{name} is served.\"},\n",
+            "       {\"path\": \"var/log\", \"pattern\": \"** is a log.\", \"kind\": \"recursive\"},\n",
+            "       {\"path\": \"*.conf\", \"pattern\": \"Configures {stem}.\", \"kind\": \"glob\"}]}\n",
+            "\n",
+            "  Las rutas son relativas a prefix (\"/\" por defecto), siendo \".\" el propio prefix,\n",
+            "  y los globs sin separador coinciden con rutas en cualquier lugar. kind es\n",
+            "  \"children\" (por defecto), \"recursive\" o \"glob\". Las variables de prefix (como\n",
+            "  $HOME) se expanden, y --prefix instala el paquete en otro lugar. Los paquetes\n",
+            "  instalados se guardan en ~/.config/def/packs.\n",
+            "\n",
+            "  Los paquetes se pueden firmar con minisign (minisign -Sm pack.json), publicando\n",
+            "  la firma junto al paquete como pack.json.minisig. Si \"trusted_keys\" en\n",
+            "  settings.json enumera claves públicas de minisign, los paquetes instalados desde\n",
+            "  URL deben estar firmados por una de ellas; --key comprueba en cambio un paquete\n",
+            "  con una clave dada.",
+        ),
+    ),
+    ("help-topic-markdown", "Markdown"),
+    (
+        "help-topic-markdown-text",
+        concat!(
+            "  Si def se compila con la característica \"markdown\", las descripciones se\n",
+            "  muestran como Markdown: las negritas, cursivas, código, enlaces y listas se ven\n",
+            "  como tales en la terminal. Use la opción global --raw (o defina \"markdown\" como\n",
+            "  false en settings.json) para mostrar las descripciones tal como se escribieron.\n",
+            "\n",
+            "  Las descripciones pueden ocupar varias líneas. En una terminal, \"def <path>\" las\n",
+            "  muestra bajo la ruta, ajustadas al ancho de la terminal, mientras que ls y list\n",
+            "  muestran su primera línea, seguida de \"…\" si continúan o no caben.",
+        ),
+    ),
+    ("help-topic-languages", "Idiomas"),
+    (
+        "help-topic-languages-text",
+        concat!(
+            "  Los mensajes se muestran en el idioma definido por \"language\" en settings.json\n",
+            "  (como \"es\"), o por LC_ALL, LC_MESSAGES o LANG. El inglés y el español vienen\n",
+            "  incluidos. Las traducciones se pueden añadir o ajustar con un archivo JSON con\n",
+            "  el nombre del idioma (como es.json) en ~/.config/def/locales, que asocia claves\n",
+            "  de mensajes a mensajes. Los mensajes sin traducir se muestran en inglés.",
+        ),
+    ),
+    ("help-topic-variables", "Variables"),
+    (
+        "help-topic-variables-text",
+        concat!(
+            "  Las claves de config.json pueden contener variables como $HOME o ${PROJECTS}, que\n",
+            "  se expanden al buscar descripciones. Las variables se definen en el mapa\n",
+            "  \"variables\" de ~/.config/def/settings.json, o se toman del entorno.",
+        ),
+    ),
+    (
+        "help-describe-usage-1",
+        "Muestra la descripción del archivo o directorio en path.",
+    ),
+    (
+        "help-describe-usage-2",
+        "Muestra las descripciones de rutas leídas de stdin, una por línea.",
+    ),
+    (
+        "help-describe-usage-3",
+        "Vuelve a ejecutar los comandos que capturan descripciones (vea \"def help add\").",
+    ),
+    (
+        "help-describe-stdin",
+        "Lee rutas separadas por saltos de línea de stdin, y muestra una línea por cada una: \
+         su descripción, o una línea vacía si no tiene. Los saltos de línea de las \
+         descripciones se muestran como espacios. Pensado para scripts, como \"find . \
+         -maxdepth 1 | def --stdin\".",
+    ),
+    (
+        "help-describe-quiet",
+        "Muestra solo la descripción, y nada (ni siquiera errores) si no la hay. Termina con 0 \
+         si path está descrita, y con 1 si no.",
+    ),
+    (
+        "help-describe-refresh",
+        "Vuelve a ejecutar el comando que captura la descripción de path, guarda su salida y \
+         la muestra. Sin una ruta, actualiza todas las descripciones capturadas de un comando. \
+         Necesita --allow-commands.",
+    ),
+    (
+        "help-add-usage-1",
+        "Añade una descripción para el archivo o directorio en path.",
+    ),
+    (
+        "help-add-usage-2",
+        "Añade texto al final o al principio de la descripción de path.",
+    ),
+    (
+        "help-add-usage-3",
+        "Añade una descripción al archivo local más cercano (vea \"Archivos locales\").",
+    ),
+    (
+        "help-add-usage-4",
+        "Añade la salida de un comando de shell como descripción de path.",
+    ),
+    (
+        "help-add-local",
+        "Escribe en el archivo .def.json o .def más cercano entre los ancestros de path, o en \
+         .def.json en el directorio actual si no hay ninguno.",
+    ),
+    (
+        "help-add-tag",
+        "Etiqueta la descripción, conservando las etiquetas que ya tiene. Se puede dar más de \
+         una vez. Las descripciones locales no se pueden etiquetar.",
+    ),
+    (
+        "help-add-private",
+        "Marca la descripción como privada, dejándola fuera de las exportaciones (vea \"def \
+         private\"). Las descripciones siguen siendo privadas al reemplazarlas.",
+    ),
+    (
+        "help-add-append",
+        "Añade la descripción después de la existente, separada por un espacio.",
+    ),
+    (
+        "help-add-prepend",
+        "Añade la descripción antes de la existente, separada por un espacio.",
+    ),
+    (
+        "help-add-force",
+        "Reemplaza la descripción existente sin avisar.",
+    ),
+    (
+        "help-add-from-command",
+        "Ejecuta cmd en una shell, en path (o en su padre si es un archivo), y usa lo que \
+         muestra como descripción. Necesita --allow-commands.",
+    ),
+    (
+        "help-add-details",
+        "Asocia la descripción a la ruta absoluta del archivo o directorio en path, \
+         reemplazando su descripción anterior, con un aviso que la muestra si era distinta. Se \
+         registran los metadatos de path (su tipo y tamaño), para compararlos más tarde con \
+         drift. Las descripciones añadidas con --from-command conservan su comando, para que \
+         \"def --refresh\" pueda volver a ejecutarlo y actualizarlas. Los comandos solo se \
+         ejecutan con --allow-commands (o con \"allow_commands\" activado en los ajustes), y \
+         se detienen pasados los segundos de \"command_timeout\" de los ajustes (5 por \
+         defecto).",
+    ),
+    (
+        "help-pattern-usage-1",
+        "Añade un patrón para describir los hijos de dir. Un comodín en el patrón se reemplaza \
+         por el nombre del hijo.",
+    ),
+    (
+        "help-pattern-usage-2",
+        "Añade un patrón para describir todos los descendientes de dir. Un comodín doble se \
+         reemplaza por la ruta del descendiente relativa a dir.",
+    ),
+    (
+        "help-pattern-usage-3",
+        "Añade un patrón para describir los hijos de dir que son archivos. --dirs-only y \
+         --symlinks-only añaden patrones de subdirectorios y de enlaces simbólicos.",
+    ),
+    (
+        "help-pattern-recursive",
+        "Describe todos los descendientes de dir en lugar de sus hijos.",
+    ),
+    (
+        "help-pattern-files-only",
+        "Describe solo los hijos de dir que son archivos.",
+    ),
+    (
+        "help-pattern-dirs-only",
+        "Describe solo los hijos de dir que son directorios.",
+    ),
+    (
+        "help-pattern-symlinks-only",
+        "Describe solo los hijos de dir que son enlaces simbólicos.",
+    ),
+    (
+        "help-pattern-priority",
+        "Define la prioridad de los patrones de path (0 por defecto), que resuelve los empates \
+         con patrones asociados igual de cerca de una ruta.",
+    ),
+    (
+        "help-pattern-details",
+        "Un patrón se asocia a un directorio, pero solo se usa para describir a sus hijos. Si \
+         el patrón contiene un comodín \"*\", se reemplaza por el nombre del hijo. Los \
+         patrones recursivos describen todos los descendientes de un directorio, y un comodín \
+         doble \"**\" se reemplaza por la ruta del descendiente relativa al directorio. De los \
+         patrones que se aplican a una ruta, se usa el asociado más cerca de ella, y los \
+         empates se resuelven a favor de la mayor prioridad (vea \"def explain\"). También se \
+         pueden usar marcadores con nombre, como {stem} y {ext} (vea \"def help\"). Un \
+         directorio puede tener un patrón para cada tipo de hijos (--files-only, --dirs-only y \
+         --symlinks-only), que tiene prioridad sobre su patrón sin tipo. Los patrones con tipo \
+         solo se aplican a rutas que existen, ya que su tipo se lee del sistema de archivos (y \
+         nunca con --no-stat).",
+    ),
+    (
+        "help-glob-usage-1",
+        "Añade una descripción para todas las rutas que coinciden con glob.",
+    ),
+    (
+        "help-glob-priority",
+        "Define la prioridad del glob (0 por defecto), que resuelve los empates con patrones \
+         asociados igual de cerca de una ruta.",
+    ),
+    (
+        "help-glob-details",
+        "En los globs, \"*\" coincide con cualquier carácter salvo \"/\", \"?\" con un único \
+         carácter, y \"**\" con cualquier carácter, incluido \"/\". Un glob sin \"/\" (como \
+         \"*.log\") se compara con los nombres de las rutas. En la descripción, \"*\" se \
+         reemplaza por el nombre de la ruta, y \"**\" por la ruta completa. Un glob está tan \
+         cerca de una ruta como el directorio con el que empieza, como \"/srv\" para \
+         \"/srv/*.log\", y los globs sin \"/\" son los más lejanos. Los globs ganan los \
+         empates con los patrones, y se usa el glob más específico (el de más caracteres que \
+         no son comodines).",
+    ),
+    (
+        "help-explain-usage-1",
+        "Muestra qué descripción o patrón describe path, y qué otros se aplican pero pierden \
+         ante él.",
+    ),
+    (
+        "help-explain-details",
+        "Muestra la descripción o el patrón usado para describir path, seguido de los demás \
+         que se le aplican, en el orden en que se usarían si se quitaran los anteriores. \
+         Primero van los patrones asociados más cerca de path, y luego los de mayor prioridad \
+         (vea \"def pattern --priority\"). Se enumeran los marcadores que rellenó el patrón \
+         usado, con sus valores. Si \"resolution_order\" está definido en los ajustes, también \
+         se muestra el orden, y no se enumeran las etapas que omite.",
+    ),
+    (
+        "help-info-usage-1",
+        "Muestra todo lo que se sabe de path: su descripción, de dónde viene, sus etiquetas y \
+         sus metadatos.",
+    ),
+    (
+        "help-info-details",
+        "Muestra la descripción de path, seguida de la descripción o el patrón del que viene \
+         (vea \"def explain\"), el comando del que se capturó, cuándo se añadió y se \
+         actualizó, y si es privada. Luego vienen las etiquetas de path, y su tipo, tamaño, \
+         fecha de modificación, propietario y el punto de montaje de su sistema de archivos, \
+         junto con los metadatos registrados al describirla si no coinciden. Con --no-stat, no \
+         se lee el sistema de archivos.",
+    ),
+    (
+        "help-edit-usage-1",
+        "Edita la descripción del archivo o directorio en path en su editor.",
+    ),
+    (
+        "help-edit-usage-2",
+        "Edita toda la configuración como JSON en su editor.",
+    ),
+    (
+        "help-edit-all",
+        "Edita todas las descripciones, patrones y globs a la vez.",
+    ),
+    (
+        "help-edit-details",
+        "Abre $VISUAL (o $EDITOR, o vi si no hay ninguno definido) con un archivo temporal que \
+         contiene la descripción actual, o nada si no la hay, lo que facilita escribir \
+         descripciones largas y de varias líneas. La descripción se guarda al salir del \
+         editor, salvo que se deje vacía. Con --all, la configuración se comprueba al salir \
+         del editor, y si no es JSON válido o tiene patrones no válidos, se le pregunta si \
+         quiere editarla de nuevo; en caso contrario no se guarda nada.",
+    ),
+    (
+        "help-list-usage-1",
+        "Muestra todas las descripciones y patrones (de rutas bajo prefix) como un árbol.",
+    ),
+    (
+        "help-list-usage-2",
+        "Ejecuta la búsqueda guardada como name, vea \"def search --save\".",
+    ),
+    (
+        "help-list-depth",
+        "Muestra solo n niveles del árbol, y cada directorio con hijos ocultos va seguido del \
+         número de entradas que contiene.",
+    ),
+    (
+        "help-list-where",
+        "Muestra solo las descripciones y patrones que coinciden con query (vea \"Consultas\" \
+         en \"def help\").",
+    ),
+    (
+        "help-list-details",
+        "Las cadenas de directorios sin descripción, cada uno con un único directorio, se \
+         juntan en una línea (como \"/home/user\"). Con --plain, las descripciones se muestran \
+         en cambio como una lista plana de líneas \"ruta: descripción\", y se ignora --depth.",
+    ),
+    (
+        "help-lists-usage-1",
+        "Muestra las búsquedas guardadas y sus consultas.",
+    ),
+    (
+        "help-lists-details",
+        "Las búsquedas se guardan en settings.json, en \"searches\", con \"def search \
+         --save\".",
+    ),
+    (
+        "help-tagged-usage-1",
+        "Enumera las descripciones etiquetadas con tag.",
+    ),
+    (
+        "help-tagged-details",
+        "Las descripciones se enumeran como las enumera \"def list\". Las etiquetas se añaden \
+         con \"def add --tag\".",
+    ),
+    (
+        "help-private-usage-1",
+        "Marca como privada la descripción de path.",
+    ),
+    (
+        "help-private-details",
+        "Las descripciones privadas describen rutas como cualquier otra, pero quedan fuera de \
+         \"def export\" salvo que se dé --include-private, de modo que se pueden guardar notas \
+         personales en una configuración compartida con un equipo. Los patrones no pueden ser \
+         privados.",
+    ),
+    (
+        "help-public-usage-1",
+        "Vuelve a marcar como compartible la descripción privada de path.",
+    ),
+    (
+        "help-public-details",
+        "Deshace \"def private\" o \"def add --private\".",
+    ),
+    (
+        "help-ls-usage-1",
+        "Enumera las entradas de dir (el directorio actual por defecto) con sus descripciones.",
+    ),
+    (
+        "help-ls-details",
+        "Las entradas se describen igual que las describe \"def <path>\", y las entradas sin \
+         descripción se enumeran sin ella.",
+    ),
+    (
+        "help-search-usage-1",
+        "Muestra las descripciones y patrones cuya ruta o texto contiene todas las palabras de \
+         query, primero las mejores coincidencias.",
+    ),
+    (
+        "help-search-usage-2",
+        "Guarda query como name, para ejecutarla con \"def list @name\".",
+    ),
+    (
+        "help-search-fuzzy",
+        "Busca también rutas y textos que contienen en orden los caracteres de una palabra, \
+         como \"dwnld\" para \"Downloads\", además de las palabras que encuentran --stem y \
+         --typos 1.",
+    ),
+    (
+        "help-search-stem",
+        "Busca también palabras con la misma raíz, como \"backups\" y \"backup\".",
+    ),
+    (
+        "help-search-save",
+        "Guarda query en lugar de buscar, reemplazando cualquier búsqueda guardada como name. \
+         Las demás opciones no se pueden guardar.",
+    ),
+    (
+        "help-search-typos",
+        "Busca también palabras con hasta n erratas, como \"bakup\" para \"backup\". Se \
+         permite una errata por cada 4 caracteres de la palabra.",
+    ),
+    (
+        "help-search-where",
+        "Muestra solo las coincidencias de query (vea \"Consultas\" en \"def help\"). Las \
+         palabras a buscar se pueden omitir entonces, para mostrar todas sus coincidencias.",
+    ),
+    (
+        "help-search-details",
+        "Se ignoran mayúsculas y minúsculas. Las coincidencias al principio de una palabra, y \
+         antes en la ruta o el texto, van primero, y las coincidencias de subcadenas van antes \
+         que las de raíces, que van antes que las erratas y otras coincidencias aproximadas. \
+         Para configuraciones grandes, active \"search_index\" en settings.json para buscar en \
+         cambio en un índice guardado en ~/.config/def, salvo que se den otras opciones: las \
+         palabras de query solo coinciden entonces con palabras enteras o con su principio, y \
+         las coincidencias cerca del directorio actual van primero.",
+    ),
+    (
+        "help-remove-usage-1",
+        "Mueve a la papelera la descripción del archivo o directorio en path.",
+    ),
+    (
+        "help-remove-usage-2",
+        "Mueve a la papelera el patrón del directorio en path.",
+    ),
+    (
+        "help-remove-pattern",
+        "Elimina el patrón de dir en lugar de su descripción.",
+    ),
+    (
+        "help-remove-details",
+        "Las descripciones eliminadas se pueden restaurar con \"def trash restore\".",
+    ),
+    (
+        "help-mv-usage-1",
+        "Reescribe la descripción y los patrones de old para que usen new.",
+    ),
+    (
+        "help-mv-usage-2",
+        "Reescribe todas las descripciones y patrones de old o de rutas bajo old para que usen \
+         new. Use --dry-run para ver los cambios antes.",
+    ),
+    (
+        "help-mv-recursive",
+        "Reescribe las rutas bajo old además de la propia old. También vale --prefix.",
+    ),
+    (
+        "help-mv-dry-run",
+        "Muestra los cambios sin hacerlos.",
+    ),
+    (
+        "help-mv-details",
+        "Útil tras mover o renombrar un directorio. Se sobrescriben las entradas que ya \
+         existen bajo new.",
+    ),
+    (
+        "help-trash-usage-1",
+        "Enumera las descripciones de la papelera.",
+    ),
+    (
+        "help-trash-usage-2",
+        "Restaura la última descripción de path movida a la papelera.",
+    ),
+    (
+        "help-trash-usage-3",
+        "Borra definitivamente todas las descripciones de la papelera.",
+    ),
+    (
+        "help-trash-details",
+        "Las descripciones de la papelera se borran definitivamente pasados \
+         \"trash_retention_days\" días (30 por defecto), según se defina en \
+         ~/.config/def/settings.json.",
+    ),
+    (
+        "help-drift-usage-1",
+        "Informa de las rutas descritas bajo root que ya no existen o que cambiaron \
+         radicalmente desde que se describieron, y de los directorios de root sin descripción.",
+    ),
+    (
+        "help-drift-details",
+        "Una ruta cambió radicalmente si cambió su tipo (como un archivo reemplazado por un \
+         directorio).",
+    ),
+    (
+        "help-stats-usage-1",
+        "Muestra el número de descripciones y patrones, la ruta descrita más profunda y las \
+         últimas descripciones añadidas, junto con cuántas entradas de dir se pueden \
+         describir.",
+    ),
+    (
+        "help-stats-details",
+        "Las entradas de dir son las que enumera \"def ls\", y están cubiertas si tienen una \
+         descripción o se les aplica un patrón. Las entradas que no están cubiertas se \
+         enumeran por su ruta.",
+    ),
+    (
+        "help-undo-usage-1",
+        "Revierte el último cambio hecho en la configuración, como un add, un remove o un mv.",
+    ),
+    (
+        "help-undo-details",
+        "Los cambios de descripciones y patrones se registran en un archivo junto al de \
+         configuración (config.json.history para config.json), y se deshacen empezando por el \
+         último. Un cambio no se deshace si las descripciones o patrones que cambió se \
+         cambiaron después, por ejemplo editando a mano el archivo de configuración. Las \
+         etiquetas, los metadatos y demás registros de una descripción eliminada no se \
+         restauran con ella.",
+    ),
+    (
+        "help-history-usage-1",
+        "Muestra cómo cambiaron la descripción y los patrones de path, empezando por el cambio \
+         más antiguo.",
+    ),
+    (
+        "help-history-details",
+        "Enumera los cambios registrados de path (vea \"def undo\"), cada uno con la hora en \
+         que se hizo y el comando que lo hizo.",
+    ),
+    (
+        "help-stale-usage-1",
+        "Informa de las descripciones cuyas rutas se modificaron después de la última \
+         actualización de las descripciones, o ya no existen.",
+    ),
+    (
+        "help-stale-days",
+        "Informa solo de las rutas modificadas más de n días después de actualizar sus \
+         descripciones.",
+    ),
+    (
+        "help-stale-details",
+        "add y edit registran cuándo se añaden y actualizan las descripciones. De las \
+         descripciones añadidas antes de registrarse estas horas solo se informa si sus rutas \
+         ya no existen.",
+    ),
+    (
+        "help-check-usage-1",
+        "Informa de las descripciones y patrones asociados a rutas que ya no existen.",
+    ),
+    (
+        "help-check-prune",
+        "Los borra, y muestra lo que se borró.",
+    ),
+    (
+        "help-check-details",
+        "Se comprueban todas las rutas descritas, a diferencia de drift, que comprueba las \
+         rutas bajo una raíz. Se omiten los globs y las claves que no son rutas absolutas \
+         (como las que usan variables). Termina con el estado 2 si se informa de entradas sin \
+         --prune. Las entradas borradas no se mueven a la papelera, así que considere antes \
+         \"def export\".",
+    ),
+    (
+        "help-events-usage-1",
+        "Vigila la configuración, y muestra una línea JSON por cada descripción o patrón que \
+         se añade, elimina o actualiza, hasta que se interrumpe.",
+    ),
+    (
+        "help-events-follow",
+        "Sigue en ejecución, y muestra los eventos a medida que ocurren los cambios.",
+    ),
+    (
+        "help-events-details",
+        "Cada línea es un objeto con los campos \"event\" (add, remove o update), \"path\", \
+         \"pattern\" (null, children, recursive o glob), \"before\" y \"after\", siempre en \
+         este orden. Los cambios hechos a la vez se muestran ordenados por ruta, así que el \
+         mismo cambio siempre muestra las mismas líneas. Los cambios hechos por cualquier \
+         proceso se detectan en medio segundo.",
+    ),
+    (
+        "help-daemon-usage-1",
+        "Mantiene cargada la configuración, y responde a consultas por un socket hasta que se \
+         interrumpe.",
+    ),
+    (
+        "help-daemon-details",
+        "El demonio escucha en un socket Unix junto al archivo de configuración (como \
+         config.json.sock), y vuelve a cargar la configuración cuando cambia. Mientras se \
+         ejecuta, describir rutas, list y search le preguntan a él en lugar de leer la \
+         configuración, lo que los acelera con configuraciones grandes, y def vuelve a leer la \
+         configuración si no responde. Los editores pueden consultar el socket directamente, \
+         con una línea de JSON por consulta, como {\"query\":\"describe\",\"path\":\"/srv\"}, \
+         que se responde con una línea de JSON. Las consultas son describe (con \"path\", \
+         \"fs\" e \"inherit\"), list (con \"prefix\") y search (con \"text\" y \"fuzzy\").",
+    ),
+    (
+        "help-maintain-usage-1",
+        "Hace una copia de seguridad de la configuración, borra la papelera caducada y los \
+         metadatos obsoletos, y comprueba los cambios en dir ($HOME por defecto). Muestra un \
+         resumen en JSON, y termina con 2 si faltan rutas descritas o cambiaron. Se puede \
+         ejecutar repetidamente sin riesgo (p. ej. desde cron o un temporizador de systemd).",
+    ),
+    (
+        "help-maintain-root",
+        "Comprueba los cambios en dir en lugar de en \"maintain_root\" de los ajustes, o en \
+         $HOME.",
+    ),
+    (
+        "help-maintain-details",
+        "Las últimas \"backups_kept\" (5 por defecto) copias de seguridad de la configuración \
+         se guardan en ~/.config/def/backups. Solo se hace una copia cuando la configuración \
+         cambió desde la última.",
+    ),
+    (
+        "help-annotate-archive-usage-1",
+        "Enumera los miembros de un archivo zip o tar con sus descripciones, como si se \
+         extrajeran en el directorio actual (o en el dir dado con --root). Requiere compilar \
+         def con la característica \"archive\".",
+    ),
+    (
+        "help-annotate-archive-root",
+        "Describe los miembros como si se extrajeran en dir.",
+    ),
+    (
+        "help-annotate-archive-details",
+        "Los archivos admitidos son zip, tar y tar comprimidos con gzip.",
+    ),
+    (
+        "help-doctor-usage-1",
+        "Comprueba si la configuración tiene claves incompatibles y secretos pegados.",
+    ),
+    (
+        "help-doctor-usage-2",
+        "Reescribe las claves incompatibles para que usen las convenciones de esta plataforma.",
+    ),
+    (
+        "help-doctor-usage-3",
+        "Hace que solo usted pueda acceder a la configuración.",
+    ),
+    (
+        "help-doctor-convert-paths",
+        "Reescribe las claves incompatibles en lugar de informar de ellas.",
+    ),
+    (
+        "help-doctor-fix-perms",
+        "Quita los permisos de otros usuarios del archivo de configuración.",
+    ),
+    (
+        "help-doctor-details",
+        "Las claves son incompatibles si usan los separadores o las raíces de otra plataforma, \
+         como las claves añadidas en Windows y usadas en Linux. También se informa de las \
+         entradas que parecen contener secretos, como tokens o contraseñas, y de una \
+         configuración que otros usuarios pueden leer mientras contiene tales entradas o \
+         privadas (de lo que también se informa cada vez que se guarda).",
+    ),
+    (
+        "help-export-usage-1",
+        "Escribe todas las descripciones y patrones en file, o los muestra.",
+    ),
+    (
+        "help-export-where",
+        "Exporta solo las descripciones y patrones que coinciden con query (vea \"Consultas\" \
+         en \"def help\").",
+    ),
+    (
+        "help-export-only",
+        "Exporta solo las descripciones, o solo los patrones (subset es \"descriptions\" o \
+         \"patterns\").",
+    ),
+    (
+        "help-export-include-private",
+        "Exporta también las descripciones privadas, todavía marcadas como privadas.",
+    ),
+    (
+        "help-export-details",
+        "file se escribe en el formato de su extensión (json, toml, yaml o msgpack, si def se \
+         compila con él), o en JSON si no tiene. Sin un archivo, se muestra JSON. Las \
+         descripciones de la papelera y las privadas no se exportan. El texto que coincide con \
+         las expresiones regulares de \"redact\" en los ajustes se reemplaza por [REDACTED], o \
+         solo el texto de sus grupos si los tienen. Si \"redact\" no está definido, se ocultan \
+         los secretos comunes (como tokens de acceso, claves privadas y contraseñas), y no se \
+         oculta nada si está vacío.",
+    ),
+    (
+        "help-import-usage-1",
+        "Añade las descripciones y patrones de un archivo exportado.",
+    ),
+    (
+        "help-import-overwrite",
+        "Reemplaza las entradas existentes que entran en conflicto con las importadas.",
+    ),
+    (
+        "help-import-keep-existing",
+        "Conserva las entradas existentes que entran en conflicto con las importadas (por \
+         defecto).",
+    ),
+    (
+        "help-import-interactive",
+        "Pregunta qué entrada conservar en cada conflicto.",
+    ),
+    (
+        "help-import-only",
+        "Importa solo las descripciones, o solo los patrones, de file (subset es \
+         \"descriptions\" o \"patterns\").",
+    ),
+    (
+        "help-import-details",
+        "Las entradas entran en conflicto si están asociadas a la misma ruta, pero difieren. \
+         file se lee en el formato de su extensión, y se comprueba antes de cambiar la \
+         configuración: no se importa nada si tiene patrones no válidos. Se muestra cada \
+         entrada añadida, actualizada o conservada.",
+    ),
+    (
+        "help-apply-usage-1",
+        "Añade las descripciones de un archivo de anotaciones.",
+    ),
+    (
+        "help-apply-dry-run",
+        "Muestra lo que se crearía y actualizaría, sin guardar.",
+    ),
+    (
+        "help-apply-details",
+        "Cada línea de un archivo de anotaciones es una ruta y su descripción, separadas por \
+         un tabulador, con los tabuladores, saltos de línea y barras invertidas de las \
+         descripciones escritos como \\t, \\n y \\\\. Se ignoran las líneas en blanco y las \
+         que empiezan por #. Los archivos con la extensión de un formato, como layout.yaml, \
+         contienen en cambio un mapa de rutas a descripciones. Las rutas relativas se \
+         resuelven respecto al directorio del archivo, siendo . el propio directorio. Se \
+         reemplazan las descripciones existentes de las rutas del archivo, y se muestra cada \
+         descripción creada o actualizada.",
+    ),
+    (
+        "help-sync-readmes-usage-1",
+        "Sincroniza las descripciones de los directorios con archivos dentro de ellos.",
+    ),
+    (
+        "help-sync-readmes-readme",
+        "Guarda las descripciones en el front matter de README.md, como \"def: \
+         <description>\", en lugar de en .dirdesc.",
+    ),
+    (
+        "help-sync-readmes-dry-run",
+        "Muestra lo que se sincronizaría, sin cambiar nada.",
+    ),
+    (
+        "help-sync-readmes-details",
+        "Las descripciones de root y de los directorios bajo él se guardan en un archivo \
+         .dirdesc en cada directorio, que no contiene más que la descripción, para que se \
+         puedan leer y editar junto con los archivos. La sincronización es en ambos sentidos: \
+         un archivo editado después de la última actualización de la descripción de su \
+         directorio se vuelve a leer, y en caso contrario la descripción se escribe en el \
+         archivo. No se sincronizan los directorios ocultos, ni los descritos solo por \
+         patrones.",
+    ),
+    (
+        "help-pack-usage-1",
+        "Instala un paquete de patrones desde un archivo o una URL http(s).",
+    ),
+    (
+        "help-pack-usage-2",
+        "Elimina los patrones de un paquete instalado.",
+    ),
+    (
+        "help-pack-usage-3",
+        "Enumera los paquetes instalados.",
+    ),
+    (
+        "help-pack-prefix",
+        "Instala los patrones del paquete bajo dir, en lugar de bajo el prefijo del propio \
+         paquete.",
+    ),
+    (
+        "help-pack-key",
+        "Exige que el paquete esté firmado por key, una clave pública de minisign o un archivo \
+         que la contiene, en lugar de por una clave de confianza.",
+    ),
+    (
+        "help-pack-yes",
+        "Instala sin preguntar un paquete descargado de una URL.",
+    ),
+    (
+        "help-pack-details",
+        "Vea \"Paquetes de patrones\" en \"def help\" para el formato de los paquetes. \
+         Instalar un paquete reemplaza un paquete instalado del mismo nombre. Los patrones \
+         cambiados desde que se instaló su paquete se conservan al eliminarlo. Antes de \
+         instalar un paquete descargado de una URL, def muestra quién lo hizo, si se verificó \
+         su firma y las rutas para las que añade patrones, y pregunta si continuar.",
+    ),
+    (
+        "help-profile-usage-1",
+        "Enumera los perfiles.",
+    ),
+    (
+        "help-profile-usage-2",
+        "Crea un perfil sin descripciones.",
+    ),
+    (
+        "help-profile-usage-3",
+        "Borra un perfil junto con sus descripciones.",
+    ),
+    (
+        "help-profile-details",
+        "Los perfiles guardan conjuntos separados de descripciones, como uno para el trabajo y \
+         otro para las máquinas personales, para que nunca se mezclen. Un perfil se usa \
+         pasando --profile <name> a cualquier subcomando, o definiendo $DEF_PROFILE. Sus \
+         descripciones se guardan en ~/.config/def/<name>.json, y los demás ajustes los \
+         comparten todos los perfiles. Los nombres se forman con letras, dígitos, \"-\" y \
+         \"_\".",
+    ),
+    (
+        "help-sync-usage-1",
+        "Combina en la configuración las descripciones del remoto definido en los ajustes.",
+    ),
+    (
+        "help-sync-usage-2",
+        "Combina en la configuración las descripciones del remoto, y envía el resultado al \
+         remoto.",
+    ),
+    (
+        "help-sync-details",
+        "El remoto se define con \"sync_remote\" en los ajustes: un repositorio git, que \
+         guarda las descripciones en def.json, o una URL http(s) de un archivo JSON, como uno \
+         en un servidor WebDAV, que se lee con GET y se escribe con PUT. Debe estar instalado \
+         git o curl. Las entradas cambiadas en un lado desde la última sincronización toman el \
+         cambio de ese lado. Las entradas cambiadas en ambos lados entran en conflicto: se \
+         conserva la descripción actualizada en último lugar, y el patrón local, y se muestra \
+         cada conflicto. Una sincronización se puede revertir con \"def undo\", que deja el \
+         remoto como está. Requiere compilar def con la característica \"sync\".",
+    ),
+    (
+        "help-hook-usage-1",
+        "Muestra un fragmento que describe los directorios al entrar en ellos con cd.",
+    ),
+    (
+        "help-hook-details",
+        "shell es bash, zsh o fish. Una vez evaluado por la shell, el fragmento muestra la \
+         descripción del directorio actual cada vez que cambia, con \"def --quiet\", así que \
+         no se muestra nada para los directorios sin describir. Añádalo al archivo de inicio \
+         de su shell, como ~/.bashrc: eval \"$(def hook bash)\". Para fish, añada \"def hook \
+         fish | source\" a ~/.config/fish/config.fish.",
+    ),
+    (
+        "help-completions-usage-1",
+        "Muestra un script que completa subcomandos, opciones y rutas descritas.",
+    ),
+    (
+        "help-completions-details",
+        "shell es bash, zsh o fish. Los argumentos se completan con las rutas que tienen \
+         descripciones o patrones, leídas de su configuración en lugar del sistema de \
+         archivos, y con archivos si no coincide ninguna. Añada el script al archivo de inicio \
+         de su shell, como ~/.bashrc: eval \"$(def completions bash)\". Para zsh, evalúelo \
+         después de compinit. Para fish, guárdelo en ~/.config/fish/completions/def.fish.",
+    ),
+    (
+        "help-from-inode-usage-1",
+        "Muestra la descripción de un archivo abierto, encontrado por su dispositivo e inodo.",
+    ),
+    (
+        "help-from-inode-details",
+        "dev es \"major,minor\" como en la columna DEVICE de lsof, o un número de dispositivo \
+         como lo muestra stat. El archivo se busca entre los que tienen abiertos los procesos \
+         que usted puede inspeccionar, a través de /proc. Necesita Linux, y def compilado con \
+         la característica \"procfs\".",
+    ),
+    (
+        "help-from-fd-usage-1",
+        "Muestra la descripción del archivo que un proceso tiene abierto como fd.",
+    ),
+    (
+        "help-from-fd-details",
+        "pid y fd son como en las columnas PID y FD de lsof, sin el modo de acceso (como \"3\" \
+         para \"3r\"). La ruta se lee de /proc. Necesita Linux, y def compilado con la \
+         característica \"procfs\".",
+    ),
+    (
+        "help-tour-usage-1",
+        "Hace un recorrido guiado por los subcomandos de def.",
+    ),
+    (
+        "help-tour-details",
+        "Cada paso explica un subcomando y lo ejecuta al pulsar Intro. Los comandos se \
+         ejecutan sobre una configuración de prueba en un directorio temporal, que se borra al \
+         terminar el recorrido, así que no se toca su propia configuración.",
+    ),
+    (
+        "help-browse-usage-1",
+        "Explora las descripciones y patrones en un árbol interactivo.",
+    ),
+    (
+        "help-browse-details",
+        "El árbol se presenta como con list, con la entrada seleccionada en un panel de vista \
+         previa. Use las flechas (o j y k) para moverse, izquierda y derecha (o h y l) para \
+         plegar y desplegar directorios, e para editar la entrada seleccionada, d para moverla \
+         a la papelera, / para buscar y q para salir. Los cambios se guardan a medida que se \
+         hacen, y los demás comandos de def que cambian la configuración esperan hasta que se \
+         cierra el explorador. Requiere compilar def con la característica \"tui\".",
+    ),
+    (
+        "help-bundle-usage-1",
+        "Escribe los ajustes, la configuración, los paquetes instalados y las traducciones en \
+         un archivo tar.",
+    ),
+    (
+        "help-bundle-usage-2",
+        "Reemplaza los ajustes, la configuración, los paquetes y las traducciones de esta \
+         máquina por los de un paquete de def.",
+    ),
+    (
+        "help-bundle-details",
+        "Los paquetes de def llevan su estado a máquinas sin acceso a la red, como servidores \
+         aislados. Aplicar un paquete hace primero una copia de seguridad de la configuración \
+         existente, como maintain, y deja en su sitio los paquetes de patrones y las \
+         traducciones que faltan en él. Las copias de seguridad y el índice de búsqueda no se \
+         incluyen. Requiere compilar def con la característica \"archive\".",
+    ),
+    (
+        "help-fixtures-usage-1",
+        "Crea un árbol de directorios y una configuración que lo describe a partir de una \
+         especificación, para pruebas, y muestra la ruta del directorio que los contiene.",
+    ),
+    (
+        "help-fixtures-details",
+        "Una especificación enumera los archivos del árbol en \"tree\" (las rutas que terminan \
+         en \"/\" son directorios, y los archivos pueden tener \"contents\" o \"size\"), y la \
+         configuración en \"config\", organizada como un archivo .def.json con claves \
+         relativas al árbol. Las especificaciones pueden ser JSON, TOML o YAML (con la \
+         característica yaml), según su extensión, como los formatos de la configuración. El \
+         árbol se crea en el directorio \"root\" de un nuevo directorio temporal, junto a su \
+         configuración, \"config.json\", que se puede usar con --config.",
+    ),
+    (
+        "help-help-usage-1",
+        "Muestra este mensaje de ayuda, o los detalles de command.",
+    ),
+    (
+        "help-global-plain",
+        "Muestra la salida sin colores ni alineación.",
+    ),
+    (
+        "help-global-raw",
+        "Muestra las descripciones tal como se escribieron, sin interpretar Markdown ni \
+         ajustar líneas.",
+    ),
+    (
+        "help-global-no-resolve",
+        "Resuelve las rutas sin seguir enlaces simbólicos ni exigir que existan.",
+    ),
+    (
+        "help-global-no-stat",
+        "Deja sin rellenar los marcadores de metadatos y de git de los patrones, sin leer las \
+         rutas.",
+    ),
+    (
+        "help-global-inherit",
+        "Describe una ruta sin descripción por su ancestro descrito más cercano.",
+    ),
+    (
+        "help-global-allow-commands",
+        "Ejecuta los comandos que capturan descripciones, para add --from-command y --refresh.",
+    ),
+    (
+        "help-global-config",
+        "Guarda las descripciones en file, en lugar de en la configuración elegida por \
+         --profile, $DEF_CONFIG, $DEF_PROFILE o los ajustes, en este orden.",
+    ),
+    (
+        "help-global-profile",
+        "Usa las descripciones de un perfil (vea \"def help profile\"), en lugar de las de \
+         $DEF_PROFILE o las predeterminadas.",
+    ),
+    (
+        "help-global-format",
+        "Muestra las descripciones de una ruta, ls y list como \"human\" (por defecto), \
+         \"plain\" (como --plain), \"json\" o \"tsv\".",
+    ),
+    ("unknown-command", "comando desconocido \"{}\""),
+    ("invalid-arguments", "lista de argumentos no válida"),
+    ("no-description", "no hay ninguna descripción disponible"),
+    (
+        "replaced-description",
+        "se reemplazó la descripción de {}, que era \"{}\" (usa --append para añadirle texto)",
+    ),
+    ("no-drift", "no se encontraron desviaciones"),
+    ("no-stale", "no hay descripciones desactualizadas"),
+    ("no-dead", "todas las rutas descritas existen"),
+    ("prune-hint", "Usa \"def check --prune\" para eliminarlas."),
+    ("pruned", "se eliminaron {} entradas"),
+    ("no-matches", "no hay descripciones que coincidan"),
+    ("no-saved-searches", "no hay búsquedas guardadas"),
+    ("unknown-saved-search", "no hay ninguna búsqueda guardada como \"{}\""),
+    ("saved-search", "búsqueda {} guardada"),
+    ("hidden-entry", "({} entrada)"),
+    ("hidden-entries", "({} entradas)"),
+    ("section-missing", "Inexistentes"),
+    ("section-changed", "Modificadas"),
+    ("section-modified", "Modificadas tras describirse"),
+    ("section-deleted", "Eliminadas"),
+    ("stale-modified", "{}: descrita {}, modificada {}"),
+    ("section-undescribed", "Sin descripción"),
+    ("drift-changed", "{}: era {}, ahora {}"),
+    ("metadata-file", "archivo ({} bytes)"),
+    ("metadata-directory", "directorio ({} entradas)"),
+    ("metadata-symlink", "enlace simbólico"),
+    ("moved-to-trash", "{}: movida a la papelera"),
+    (
+        "no-description-to-remove",
+        "{}: no hay ninguna descripción que eliminar",
+    ),
+    (
+        "no-pattern-to-remove",
+        "{}: no hay ningún patrón que eliminar",
+    ),
+    ("overwrites", " (sobrescribe)"),
+    ("would-rename", "se renombrarían {} entradas"),
+    ("renamed", "se renombraron {} entradas"),
+    ("restored", "{}: restaurada"),
+    ("deleted-trash", "se eliminaron {} entradas de la papelera"),
+    ("minute-ago", "hace {} minuto"),
+    ("minutes-ago", "hace {} minutos"),
+    ("hour-ago", "hace {} hora"),
+    ("hours-ago", "hace {} horas"),
+    ("day-ago", "hace {} día"),
+    ("days-ago", "hace {} días"),
+    ("no-problems", "no se encontraron problemas"),
+    (
+        "possible-secret",
+        "{} puede contener un secreto ({})",
+    ),
+    (
+        "run-convert-paths",
+        "ejecute \"def doctor --convert-paths\" para corregir las claves incompatibles",
+    ),
+    (
+        "key-collision",
+        "{} no se convirtió, ya que {} ya tiene descripción",
+    ),
+    (
+        "expanded-key-collision",
+        "{} no se expande al describir rutas, ya que {} ya tiene descripción",
+    ),
+    (
+        "unknown-color-setting",
+        "ajuste de color desconocido \"{}\"",
+    ),
+    (
+        "unknown-shell",
+        "shell desconocida \"{}\", se esperaba bash, zsh o fish",
+    ),
+    ("invalid-color", "color no válido \"{}\" para \"{}\""),
+    (
+        "archive-unsupported",
+        "def se compiló sin la característica \"archive\"",
+    ),
+    ("invalid-translations", "traducciones no válidas en {}"),
+    (
+        "failed-back-up-config",
+        "no se pudo respaldar la configuración",
+    ),
+    ("failed-create-bundle", "no se pudo crear el paquete de estado"),
+    ("failed-read-bundle", "no se pudo leer el paquete de estado"),
+    ("failed-apply-bundle", "no se pudo aplicar el paquete de estado"),
+    ("ignored-bundle-member", "se ignoró {} del paquete de estado"),
+    ("bundle-created", "se empaquetaron {} archivos en {}"),
+    ("bundle-applied", "se aplicaron {} archivos de {}"),
+    ("backed-up-config", "configuración respaldada en {}"),
+    ("section-written", "Escritos"),
+    ("section-used", "Usado"),
+    ("section-overridden", "Descartados"),
+    ("section-resolution", "Orden de resolución"),
+    ("section-placeholders", "Marcadores"),
+    ("section-description", "Descripción"),
+    ("section-provenance", "Procedencia"),
+    ("section-tags", "Etiquetas"),
+    ("section-file", "Archivo"),
+    ("section-totals", "Totales"),
+    ("section-deepest", "Ruta descrita más profunda"),
+    ("section-recent", "Añadidas últimamente"),
+    ("section-coverage", "Cobertura de {}"),
+    ("stats-descriptions", "{} descripciones"),
+    ("stats-patterns", "{} patrones: {} de directorios, {} recursivos, {} globs"),
+    ("stats-coverage", "{} de {} entradas descritas ({})"),
+    ("info-specific", "añadida a la ruta misma"),
+    ("info-inherited", "heredada de {}"),
+    ("info-repository", "raíz de un repositorio git"),
+    ("info-pattern", "patrón de {} [{}]"),
+    ("info-command", "capturada de `{}`"),
+    ("info-added", "añadida el {}"),
+    ("info-updated", "actualizada el {}"),
+    ("info-private", "privada"),
+    ("info-missing", "no existe"),
+    ("info-modified", "modificado el {}"),
+    ("info-owner", "propiedad de {}"),
+    ("info-mount", "en el sistema de archivos montado en {}"),
+    ("info-recorded", "era {} al describirse"),
+    ("priority", "prioridad {}"),
+    ("inherited-from", "heredada de {}: {}"),
+    ("failed-create-summary", "no se pudo crear el resumen"),
+    ("failed-get-home", "no se pudo encontrar el directorio personal"),
+    (
+        "failed-get-absolute-path",
+        "no se pudo obtener la ruta absoluta",
+    ),
+    (
+        "failed-get-current-dir",
+        "no se pudo obtener el directorio actual",
+    ),
+    ("failed-open-config", "no se pudo abrir la configuración"),
+    (
+        "failed-read-archive",
+        "no se pudo leer el archivo comprimido",
+    ),
+    ("failed-read-config", "no se pudo leer la configuración"),
+    ("failed-read-dir", "no se pudo leer el directorio"),
+    ("failed-read-root", "no se pudo leer la raíz"),
+    ("failed-read-settings", "no se pudieron leer los ajustes"),
+    ("failed-restore", "no se pudo restaurar la descripción"),
+    (
+        "failed-write-config",
+        "no se pudo escribir la configuración",
+    ),
+    ("failed-lock-config", "no se pudo bloquear la configuración"),
+    ("failed-read-stdin", "no se pudo leer la entrada estándar"),
+    ("invalid-pattern", "patrón no válido"),
+    ("invalid-query", "consulta no válida"),
+    ("invalid-config", "configuración no válida"),
+    ("failed-run-editor", "no se pudo ejecutar el editor"),
+    ("empty-description", "la descripción está vacía, no se guardó nada"),
+    (
+        "tui-unsupported",
+        "def se compiló sin la característica \"tui\"",
+    ),
+    (
+        "procfs-unsupported",
+        "def se compiló sin la característica \"procfs\", que necesita Linux",
+    ),
+    ("failed-read-proc", "no se pudo leer /proc"),
+    ("daemon-listening", "escuchando en {}"),
+    ("failed-generate-fixture", "no se pudo generar el entorno de prueba"),
+    ("failed-run-daemon", "no se pudo ejecutar el daemon"),
+    ("daemon-unsupported", "el daemon necesita sockets de dominio Unix"),
+    (
+        "commands-disabled",
+        "la ejecución de comandos está desactivada, usa --allow-commands o activa \"allow_commands\" en los ajustes",
+    ),
+    ("failed-run-command", "no se pudo ejecutar el comando"),
+    ("failed-refresh", "no se pudo actualizar {}: {}"),
+    (
+        "not-from-command",
+        "la descripción de {} no es la salida de un comando",
+    ),
+    ("refreshed", "se actualizaron {} descripciones"),
+    (
+        "invalid-file-id",
+        "archivo \"{}\" no válido, se esperaba <dev>:<inodo> como 8,1:1234",
+    ),
+    (
+        "invalid-fd",
+        "descriptor de archivo \"{}\" no válido, se esperaba <pid>:<fd> como 1234:3",
+    ),
+    ("inode-not-open", "ningún proceso tiene abierto el archivo {}"),
+    ("browse-needs-terminal", "browse necesita una terminal"),
+    ("failed-run-browser", "no se pudo ejecutar el explorador"),
+    ("browse-title", "Descripciones"),
+    ("browse-title-search", "Descripciones que coinciden con \"{}\""),
+    ("browse-empty", "Todavía no hay nada descrito."),
+    (
+        "browse-keys",
+        "↑↓ mover  ←→ plegar  e editar  d eliminar  / buscar  q salir",
+    ),
+    ("browse-edit", "Editar:"),
+    ("browse-search", "Buscar:"),
+    ("browse-delete", "¿Mover a la papelera? [s/N]"),
+    ("browse-saved", "{}: guardada"),
+    ("browse-tags", "Etiquetas: {}"),
+    ("browse-times", "Añadida {}, actualizada {}."),
+    ("nothing-changed", "No cambió nada."),
+    ("nothing-saved", "No se guardó nada."),
+    ("edit-again", "¿Editar de nuevo? [S/n]"),
+    ("no-answers", "n,no"),
+    ("failed-read-import", "no se pudo leer el archivo a importar"),
+    ("failed-save-search", "no se pudo guardar la búsqueda"),
+    ("failed-export", "no se pudieron exportar las descripciones"),
+    ("failed-redact", "no se pudieron leer las reglas de ocultación"),
+    (
+        "exposed-config",
+        "otros usuarios pueden leer {}, que contiene descripciones privadas o secretas, \
+         ejecute \"def doctor --fix-perms\" para restringirlo",
+    ),
+    ("fixed-perms", "ahora solo usted puede acceder a {}"),
+    ("failed-fix-perms", "no se pudieron cambiar los permisos de la configuración"),
+    ("replace-existing", "¿Reemplazar la existente? [s/N]"),
+    ("yes-answers", "s,sí,si"),
+    ("import-added", "añadida"),
+    ("import-updated", "actualizada"),
+    ("import-kept", "se mantuvo la existente"),
+    (
+        "imported",
+        "{} añadidas, {} actualizadas, {} conflictos mantenidos",
+    ),
+    ("failed-read-annotations", "no se pudieron leer las anotaciones"),
+    ("apply-created", "creada"),
+    ("apply-updated", "actualizada"),
+    ("applied", "{} creadas, {} actualizadas"),
+    (
+        "would-apply",
+        "se crearían {} y se actualizarían {} descripciones",
+    ),
+    ("synced-to-file", "escrita en {}"),
+    ("synced-from-file", "leída de {}"),
+    ("synced", "{} escritas, {} leídas"),
+    (
+        "would-sync",
+        "se escribirían {} y se leerían {} descripciones",
+    ),
+    ("failed-sync", "no se pudieron sincronizar las descripciones"),
+    ("failed-undo", "no se pudo deshacer"),
+    ("nothing-to-undo", "nada que deshacer"),
+    ("undone", "Se deshizo \"def {}\", hecho {}"),
+    ("undo-removed", "{}{} eliminada"),
+    ("undo-restored", "{}{} restaurada"),
+    ("undo-reverted", "{}{} devuelta a: {}"),
+    ("failed-read-history", "no se pudo leer el historial"),
+    ("no-history", "no hay historial de {}"),
+    ("history-removed", "eliminada"),
+    ("failed-sync-remote", "no se pudo sincronizar con el remoto"),
+    ("section-pulled", "Traídos"),
+    ("section-pushed", "Enviados"),
+    ("section-conflicts", "Conflictos"),
+    ("sync-removed", "eliminada"),
+    ("sync-kept-local", "{}{}: se mantuvo el cambio local, el remoto era: {}"),
+    ("sync-kept-remote", "{}{}: se mantuvo el cambio remoto, el local era: {}"),
+    ("synced-pull", "{} traídos, {} conflictos"),
+    ("synced-push", "{} traídos, {} enviados, {} conflictos"),
+    ("sync-unsupported", "def se compiló sin la característica \"sync\""),
+    ("failed-create-sandbox", "no se pudo crear el entorno de prueba"),
+    ("failed-read-pack", "no se pudo leer el paquete"),
+    (
+        "failed-write-pack",
+        "no se pudo escribir el paquete instalado",
+    ),
+    ("failed-download", "no se pudo descargar"),
+    ("invalid-pack", "paquete no válido"),
+    ("installed-pack", "se instaló el paquete {} ({} patrones)"),
+    ("removed-pack", "se eliminó el paquete {} ({} patrones)"),
+    (
+        "pack-not-installed",
+        "no hay ningún paquete instalado llamado \"{}\"",
+    ),
+    ("no-packs", "no hay paquetes instalados"),
+    ("no-profiles", "no hay perfiles"),
+    ("active-profile", "{} (en uso)"),
+    ("created-profile", "se creó el perfil {}"),
+    ("deleted-profile", "se eliminó el perfil {}"),
+    (
+        "unknown-profile",
+        "el perfil \"{}\" no existe, créelo con \"def profile create {}\"",
+    ),
+    ("failed-use-profile", "no se pudo usar el perfil"),
+    ("failed-list-profiles", "no se pudieron listar los perfiles"),
+    ("failed-create-profile", "no se pudo crear el perfil"),
+    ("failed-delete-profile", "no se pudo eliminar el perfil"),
+    (
+        "failed-read-signature",
+        "no se pudo leer la firma del paquete",
+    ),
+    ("unverified-pack", "no se pudo verificar el paquete"),
+    ("pack-by", "de {}"),
+    ("pack-signed", "Firmado con una clave de confianza."),
+    (
+        "pack-unsigned",
+        "Sin verificar: no hay claves de confianza.",
+    ),
+    ("pack-affects", "Se añadirán patrones para:"),
+    ("install-pack", "¿Instalar? [s/N]"),
+    ("pack-declined", "no se instaló el paquete"),
+    ("failed-read-local", "no se pudo leer el archivo local"),
+    ("failed-write-local", "no se pudo escribir el archivo local"),
+    (
+        "no-local-file",
+        "no se encontró ningún archivo local para {}, y no está bajo el directorio actual",
+    ),
+    (
+        "failed-delete-sandbox",
+        "no se pudo eliminar el entorno de prueba",
+    ),
+    (
+        "tour-intro",
+        "¡Bienvenido a def! Este recorrido ejecuta algunos comandos en un entorno de prueba en {},\npor lo que sus descripciones no se modifican.",
+    ),
+    ("tour-step", "Paso {} de {}"),
+    ("tour-prompt", "Pulse intro para ejecutarlo, o q para salir."),
+    (
+        "tour-done",
+        "¡Eso es todo! Se eliminó el entorno de prueba. Ejecute \"def help\" para saber más.",
+    ),
+];
+
+/// Built-in catalogs by language code.
+const CATALOGS: [(&str, &[(&str, &str)]); 2] = [("en", EN), ("es", ES)];
+
+/// init selects the language of messages. The language is given in settings,
+/// or taken from the environment (LC_ALL, LC_MESSAGES, or LANG). Messages are
+/// taken from a JSON file named after the language in locales_dir (such as
+/// "es.json"), if one exists, then from the built-in catalog, then English.
+/// Has no effect if called more than once.
+pub fn init(language: Option<&str>, locales_dir: &str) {
+    let mut catalog: HashMap<String, String> = EN
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+
+    let language = match language.map(|l| l.to_string()).or_else(language_from_env) {
+        Some(language) => language,
+        None => {
+            let _ = CATALOG.set(catalog);
+            return;
+        }
+    };
+    for code in candidates(&language).iter().rev() {
+        if let Some((_, messages)) = CATALOGS.iter().find(|(c, _)| c == code) {
+            catalog.extend(messages.iter().map(|(k, v)| (k.to_string(), v.to_string())));
+        }
+
+        let file = Path::new(locales_dir).join(format!("{}.json", code));
+        if let Ok(json) = fs::read_to_string(&file) {
+            match serde_json::from_str::<HashMap<String, String>>(&json) {
+                Ok(messages) => catalog.extend(messages),
+                Err(_) => {
+                    crate::errors::warn(trf("invalid-translations", &[&file.to_string_lossy()]))
+                }
+            }
+        }
+    }
+    let _ = CATALOG.set(catalog);
+}
+
+/// tr returns the message with the given key in the selected language.
+pub fn tr(key: &str) -> &'static str {
+    let message = match CATALOG.get() {
+        Some(catalog) => catalog.get(key).map(|m| m.as_str()),
+        None => builtin("en", key),
+    };
+    match message {
+        Some(m) => m,
+        None => {
+            debug_assert!(false, "missing message {}", key);
+            ""
+        }
+    }
+}
+
+/// builtin returns the message with the given key in the built-in catalog
+/// of the language with the given code, or in English if the catalog lacks
+/// it.
+pub fn builtin(code: &str, key: &str) -> Option<&'static str> {
+    let find = |messages: &[(&str, &'static str)]| {
+        messages.iter().find(|(k, _)| *k == key).map(|(_, m)| *m)
+    };
+    CATALOGS
+        .iter()
+        .find(|(c, _)| *c == code)
+        .and_then(|(_, messages)| find(messages))
+        .or_else(|| find(EN))
+}
+
+/// trf returns the message with the given key in the selected language, with
+/// placeholders replaced by args. See format for placeholder syntax.
+pub fn trf(key: &str, args: &[&dyn Display]) -> String {
+    format(tr(key), args)
+}
+
+/// is_yes returns true if answer (to a question such as "Replace existing?
+/// [y/N]") means yes in the selected language, ignoring case.
+pub fn is_yes(answer: &str) -> bool {
+    is_one_of(tr("yes-answers"), answer)
+}
+
+/// is_no returns true if answer (to a question such as "Edit again? [Y/n]")
+/// means no in the selected language, ignoring case.
+pub fn is_no(answer: &str) -> bool {
+    is_one_of(tr("no-answers"), answer)
+}
+
+/// is_one_of returns true if answer is one of the comma-separated answers,
+/// ignoring case.
+fn is_one_of(answers: &str, answer: &str) -> bool {
+    let answer = answer.trim().to_lowercase();
+    !answer.is_empty()
+        && answers
+            .split(',')
+            .any(|a| a.trim().to_lowercase() == answer)
+}
+
+/// format replaces placeholders in message with args. An empty placeholder
+/// "{}" is replaced by the next argument, and "{n}" by the nth argument
+/// (starting from 0), which lets translations reorder arguments.
+fn format(message: &str, args: &[&dyn Display]) -> String {
+    let mut formatted = String::new();
+    let mut next = 0;
+    let mut rest = message;
+    while let Some(start) = rest.find('{') {
+        formatted.push_str(&rest[..start]);
+        let end = match rest[start..].find('}') {
+            Some(end) => start + end,
+            None => {
+                rest = &rest[start..];
+                break;
+            }
+        };
+
+        let placeholder = &rest[start + 1..end];
+        let index = if placeholder.is_empty() {
+            next += 1;
+            Some(next - 1)
+        } else {
+            placeholder.parse::<usize>().ok()
+        };
+        match index.and_then(|i| args.get(i)) {
+            Some(arg) => formatted.push_str(&arg.to_string()),
+            None => formatted.push_str(&rest[start..=end]),
+        }
+        rest = &rest[end + 1..];
+    }
+    formatted.push_str(rest);
+    formatted
+}
+
+/// language_from_env returns the user's language as set in the environment,
+/// or None if it isn't set (or is "C" or "POSIX").
+fn language_from_env() -> Option<String> {
+    LANGUAGE_VARIABLES
+        .iter()
+        .filter_map(|v| env::var(v).ok())
+        .find(|v| !v.is_empty())
+        .filter(|v| v != "C" && v != "POSIX")
+}
+
+/// candidates returns language codes to look up messages of a language in,
+/// most specific first. For example, "pt_BR.UTF-8" results in "pt_br" and
+/// "pt".
+fn candidates(language: &str) -> Vec<String> {
+    let code = language
+        .split(['.', '@'])
+        .next()
+        .unwrap_or(language)
+        .replace('-', "_")
+        .to_lowercase();
+    let mut candidates = vec![code.clone()];
+    if let Some((base, _)) = code.split_once('_') {
+        candidates.push(base.to_string());
+    }
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn catalogs_test() {
+        for (code, messages) in CATALOGS.iter() {
+            for (key, message) in messages.iter() {
+                let english = EN.iter().find(|(k, _)| k == key);
+                assert!(english.is_some(), "{}: unknown key {}", code, key);
+                let placeholders = |m: &str| m.matches('{').count();
+                assert_eq!(
+                    placeholders(message),
+                    placeholders(english.unwrap().1),
+                    "{}: {}",
+                    code,
+                    key
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn is_yes_test() {
+        let answers = |code: &str| {
+            let (_, messages) = CATALOGS.iter().find(|(c, _)| *c == code).unwrap();
+            messages
+                .iter()
+                .find(|(k, _)| *k == "yes-answers")
+                .unwrap()
+                .1
+        };
+        for (code, answer, yes) in [
+            ("en", "y", true),
+            ("en", "Yes\n", true),
+            ("en", "s", false),
+            ("en", "", false),
+            ("es", "s", true),
+            ("es", "Sí", true),
+            ("es", "y", false),
+        ]
+        .iter()
+        {
+            assert_eq!(
+                is_one_of(answers(code), answer),
+                *yes,
+                "{} {}",
+                code,
+                answer
+            );
+        }
+        assert!(is_yes("y"));
+        assert!(!is_yes("n"));
+        assert!(is_no("N"));
+        assert!(!is_no("y"));
+    }
+
+    #[test]
+    fn format_test() {
+        for (message, formatted) in [
+            ("no placeholders", "no placeholders"),
+            ("{} and {}", "a and 1"),
+            ("{1} before {0}", "1 before a"),
+            ("{} {} {}", "a 1 {}"),
+            ("{unclosed", "{unclosed"),
+            ("{x}", "{x}"),
+        ]
+        .iter()
+        {
+            assert_eq!(format(message, &[&"a", &1]), *formatted);
+        }
+    }
+
+    #[test]
+    fn candidates_test() {
+        for (language, codes) in [
+            ("es", vec!["es"]),
+            ("es_ES.UTF-8", vec!["es_es", "es"]),
+            ("pt-BR", vec!["pt_br", "pt"]),
+            ("de_DE@euro", vec!["de_de", "de"]),
+        ]
+        .iter()
+        {
+            assert_eq!(candidates(language), *codes);
+        }
+    }
+}
@@ -0,0 +1,275 @@
+//! An inverted index over descriptions and patterns, for searching large
+//! configs without scanning every entry.
+//!
+//! Paths and texts of entries are split into lowercase words (runs of
+//! letters and digits), and each word is mapped to the entries containing
+//! it. A query matches an entry if each of its words is a word of the entry,
+//! or the start of one. Unlike `search`, words in the middle of others (such
+//! as "load" in "downloads") aren't found, and fuzzy matching isn't
+//! supported.
+//!
+//! Matches are ranked by how many times their words are found, whether they
+//! were found in full, and, if a path to search near is given (such as the
+//! current directory), how close the match's path is to it.
+//!
+//! An index is built for a list of entries, in the order returned by
+//! `Describer::entries_under`, and keeps a fingerprint of them. `cached`
+//! keeps an index in a file, and rebuilds it only when the entries change.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::search::Match;
+use crate::{store, Entry, Error, SEPERATOR};
+
+/// Score of a query word found as a word of an entry.
+const EXACT_SCORE: u32 = 1000;
+
+/// Score of a query word found at the start of a word of an entry.
+const PREFIX_SCORE: u32 = 600;
+
+/// Bonus of each repetition of a word in an entry.
+const REPETITION_BONUS: u32 = 50;
+
+/// Maximum number of repetitions of a word earning a bonus.
+const MAX_REPETITIONS: u32 = 4;
+
+/// Bonus of an entry mapped to the path searched near, halved as the
+/// entry's path gets farther from it.
+const PROXIMITY_BONUS: u32 = 400;
+
+/// SearchIndex maps words to the entries containing them.
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+pub struct SearchIndex {
+    /// Fingerprint of the entries the index was built for.
+    fingerprint: u64,
+    /// Words, each with the positions of the entries containing it, and the
+    /// number of times it's found in each.
+    words: BTreeMap<String, Vec<(usize, u32)>>,
+}
+
+impl SearchIndex {
+    /// Build an index of the given entries.
+    pub fn build(entries: &[Entry]) -> SearchIndex {
+        let mut words: BTreeMap<String, Vec<(usize, u32)>> = BTreeMap::new();
+        for (i, entry) in entries.iter().enumerate() {
+            let mut counts: HashMap<String, u32> = HashMap::new();
+            for word in split_words(entry.path).chain(split_words(entry.description)) {
+                *counts.entry(word).or_default() += 1;
+            }
+            for (word, count) in counts {
+                words.entry(word).or_default().push((i, count));
+            }
+        }
+        SearchIndex {
+            fingerprint: fingerprint(entries),
+            words,
+        }
+    }
+
+    /// Load an index saved to the file at path.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<SearchIndex, Error> {
+        Ok(serde_json::from_slice(&fs::read(path)?)?)
+    }
+
+    /// Save the index to the file at path.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        Ok(store::write_atomic(path, &serde_json::to_vec(self)?)?)
+    }
+
+    /// Return true if the index was built for the given entries.
+    pub fn is_for(&self, entries: &[Entry]) -> bool {
+        self.fingerprint == fingerprint(entries)
+    }
+
+    /// Return entries (which the index was built for) matching query, best
+    /// matches first. Matches with equal scores are sorted by path. If near
+    /// is given, entries closer to it rank higher.
+    pub fn search<'a>(
+        &self,
+        entries: &[Entry<'a>],
+        query: &str,
+        near: Option<&str>,
+    ) -> Vec<Match<'a>> {
+        let mut scores: Option<HashMap<usize, u32>> = None;
+        for word in split_words(query) {
+            let mut found: HashMap<usize, u32> = HashMap::new();
+            for (indexed, postings) in self
+                .words
+                .range(word.clone()..)
+                .take_while(|(w, _)| w.starts_with(&word))
+            {
+                let base = if *indexed == word {
+                    EXACT_SCORE
+                } else {
+                    PREFIX_SCORE
+                };
+                for (i, count) in postings.iter() {
+                    let score = base + REPETITION_BONUS * (count - 1).min(MAX_REPETITIONS);
+                    let best = found.entry(*i).or_default();
+                    *best = (*best).max(score);
+                }
+            }
+            scores = Some(match scores {
+                None => found,
+                Some(scores) => scores
+                    .into_iter()
+                    .filter_map(|(i, score)| found.get(&i).map(|s| (i, score + s)))
+                    .collect(),
+            });
+        }
+
+        let mut matches: Vec<Match> = scores
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|(i, score)| {
+                let entry = entries.get(i)?;
+                let bonus = near.map_or(0, |near| proximity_bonus(entry.path, near));
+                Some(Match {
+                    path: entry.path,
+                    text: entry.description,
+                    pattern: entry.pattern,
+                    score: score + bonus,
+                })
+            })
+            .collect();
+        matches.sort_by(|a, b| {
+            b.score
+                .cmp(&a.score)
+                .then_with(|| a.path.cmp(b.path))
+                .then_with(|| a.pattern.cmp(&b.pattern))
+        });
+        matches
+    }
+}
+
+/// Return the index of entries saved to the file at path. If the file is
+/// missing or was built for other entries, the index is rebuilt and saved.
+/// Failing to save it is ignored, since it can always be rebuilt.
+pub fn cached<P: AsRef<Path>>(path: P, entries: &[Entry]) -> SearchIndex {
+    match SearchIndex::load(&path) {
+        Ok(index) if index.is_for(entries) => index,
+        _ => {
+            let index = SearchIndex::build(entries);
+            let _ = index.save(&path);
+            index
+        }
+    }
+}
+
+/// Return lowercase words of text, which are runs of letters and digits.
+fn split_words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+}
+
+/// Return a fingerprint of entries, which changes if any of them changes.
+fn fingerprint(entries: &[Entry]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for entry in entries.iter() {
+        entry.path.hash(&mut hasher);
+        entry.description.hash(&mut hasher);
+        entry.pattern.map(|k| k as u8).hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// Return the bonus of an entry mapped to path when searching near another
+/// path: PROXIMITY_BONUS if they're the same, halved for every component
+/// separating them, and none if they share no components.
+fn proximity_bonus(path: &str, near: &str) -> u32 {
+    let components = |p: &'_ str| {
+        p.split(SEPERATOR)
+            .filter(|c| !c.is_empty())
+            .map(String::from)
+            .collect::<Vec<String>>()
+    };
+    let (path, near) = (components(path), components(near));
+    let common = path
+        .iter()
+        .zip(near.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    if common == 0 {
+        return 0;
+    }
+    let distance = (path.len() - common) + (near.len() - common);
+    PROXIMITY_BONUS.checked_shr(distance as u32).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PatternKind;
+
+    #[test]
+    fn search_test() {
+        let entries = [
+            Entry {
+                path: "/home/user/Downloads",
+                description: "Downloaded files.",
+                pattern: None,
+            },
+            Entry {
+                path: "/srv",
+                description: "* is served to the web.",
+                pattern: Some(PatternKind::Children),
+            },
+            Entry {
+                path: "/srv/nginx",
+                description: "Web server config, web facing.",
+                pattern: None,
+            },
+            Entry {
+                path: "/srv/www",
+                description: "Web root.",
+                pattern: None,
+            },
+        ];
+        let index = SearchIndex::build(&entries);
+        assert!(index.is_for(&entries));
+        assert!(!index.is_for(&entries[1..]));
+
+        for (query, near, paths) in [
+            ("web", None, vec!["/srv/nginx", "/srv", "/srv/www"]),
+            ("WEB serv", None, vec!["/srv/nginx", "/srv"]),
+            (
+                "web",
+                Some("/srv/www"),
+                vec!["/srv/www", "/srv", "/srv/nginx"],
+            ),
+            ("down", None, vec!["/home/user/Downloads"]),
+            ("load", None, vec![]),
+            ("", None, vec![]),
+        ]
+        .iter()
+        {
+            let found: Vec<&str> = index
+                .search(&entries, query, *near)
+                .iter()
+                .map(|m| m.path)
+                .collect();
+            assert_eq!(found, *paths, "{} {:?}", query, near);
+        }
+    }
+
+    #[test]
+    fn proximity_bonus_test() {
+        for (path, near, bonus) in [
+            ("/srv/www", "/srv/www", 400),
+            ("/srv", "/srv/www", 200),
+            ("/srv/nginx", "/srv/www", 100),
+            ("/home", "/srv/www", 0),
+        ]
+        .iter()
+        {
+            assert_eq!(proximity_bonus(path, near), *bonus, "{} {}", path, near);
+        }
+    }
+}
@@ -0,0 +1,279 @@
+//! History of changes to the config, kept so that they can be reviewed and
+//! undone (see `def history` and `def undo`).
+//!
+//! Every save of the config through `config::save_as` appends a mutation to
+//! a journal beside the config file (such as `config.json.history`): the
+//! operation that made it, such as "add" or "mv", when it was made, and the
+//! entries it changed, as events (see `def::event`). Mutations are written
+//! as JSON objects, one per line:
+//!
+//! ```json
+//! {"op":"add","time":1700000000,"changes":[{"event":"add","path":"/srv","pattern":null,"before":null,"after":"Served files."}]}
+//! ```
+//!
+//! Mutations moving entries to or out of the trash also list the trashed
+//! entries they added (as "trashed") or took (as "untrashed"), so that
+//! undoing them puts the trash back as it was.
+//!
+//! Only descriptions, patterns, and the trash are journaled. Tags, metadata,
+//! and other records kept beside them aren't, so undoing a removal brings
+//! back the description, but not its tags.
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::event::{self, Event};
+use crate::store::write_atomic;
+use crate::trash::TrashedEntry;
+use crate::{Describer, Error, PatternKind};
+
+/// Mutation is a change of the config, made by one operation.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+pub struct Mutation {
+    /// The operation that made the change, such as "add".
+    pub op: String,
+    /// Time the change was made, in seconds since the Unix epoch.
+    pub time: u64,
+    /// Changed entries, sorted as by `event::diff`.
+    pub changes: Vec<Event>,
+    /// Entries moved to the trash, such as by removing them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trashed: Vec<TrashedEntry>,
+    /// Entries taken out of the trash, such as by restoring them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub untrashed: Vec<TrashedEntry>,
+}
+
+impl Mutation {
+    /// Return the mutation changing the entries and the trash of old into
+    /// those of new, made by op at time, or None if nothing changed.
+    pub fn between(old: &Describer, new: &Describer, op: &str, time: u64) -> Option<Mutation> {
+        let changes = event::diff(old, new);
+        let trashed = missing_from(&new.trash, &old.trash);
+        let untrashed = missing_from(&old.trash, &new.trash);
+        if changes.is_empty() && trashed.is_empty() && untrashed.is_empty() {
+            return None;
+        }
+        Some(Mutation {
+            op: op.to_string(),
+            time,
+            changes,
+            trashed,
+            untrashed,
+        })
+    }
+
+    /// Revert the mutation in describer, setting each changed entry back to
+    /// what it was before, and the trash back to what it held. An error is
+    /// returned, and nothing is reverted, if an entry was changed (or taken
+    /// out of the trash) since the mutation, as reverting it would lose that
+    /// change.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use def::journal::Mutation;
+    ///
+    /// let old = def::Describer::new();
+    /// let mut new = old.clone();
+    /// new.add_description("/srv", "Served files.");
+    /// let mutation = Mutation::between(&old, &new, "add", 0).unwrap();
+    ///
+    /// mutation.revert(&mut new).unwrap();
+    /// assert_eq!(new.describe("/srv"), None);
+    /// ```
+    pub fn revert(&self, describer: &mut Describer) -> Result<(), Error> {
+        for change in self.changes.iter() {
+            if text_of(describer, &change.path, change.pattern) != change.after.as_deref() {
+                return Err(Error::Conflict(change.path.clone()));
+            }
+        }
+        if let Some(entry) = missing_from(&self.trashed, &describer.trash).first() {
+            return Err(Error::Conflict(entry.path.clone()));
+        }
+        for change in self.changes.iter().rev() {
+            let (path, pattern) = (change.path.as_str(), change.pattern);
+            match (&change.before, pattern) {
+                (None, _) => {
+                    describer.retain_entries(|e| e.path != path || e.pattern != pattern);
+                }
+                (Some(before), None) => describer.add_description(path, before),
+                (Some(before), Some(kind)) => {
+                    describer
+                        .add_pattern_with_kind(path, before, kind)
+                        .map_err(|e| Error::InvalidJournal(format!("{}: {}", path, e)))?
+                }
+            }
+        }
+        for entry in self.trashed.iter() {
+            if let Some(i) = describer.trash.iter().rposition(|e| e == entry) {
+                describer.trash.remove(i);
+            }
+        }
+        for entry in self.untrashed.iter() {
+            let i = describer
+                .trash
+                .partition_point(|e| e.trashed_at <= entry.trashed_at);
+            describer.trash.insert(i, entry.clone());
+        }
+        Ok(())
+    }
+}
+
+/// Append mutation to the journal at file, creating it if it doesn't exist.
+pub fn append<P: AsRef<Path>>(file: P, mutation: &Mutation) -> Result<(), Error> {
+    let mut line = serde_json::to_string(mutation)?;
+    line.push('\n');
+    let mut journal = OpenOptions::new().create(true).append(true).open(file)?;
+    journal.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+/// Read the mutations of the journal at file, oldest first. No mutations
+/// are returned if the file doesn't exist, and an error is returned if it
+/// can't be read or parsed.
+pub fn read<P: AsRef<Path>>(file: P) -> Result<Vec<Mutation>, Error> {
+    let text = match fs::read_to_string(file) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line)
+                .map_err(|e| Error::InvalidJournal(format!("line {}: {}", i + 1, e)))
+        })
+        .collect()
+}
+
+/// Remove the last mutation from the journal at file, and return it, or
+/// None if the journal is empty.
+pub fn pop<P: AsRef<Path>>(file: P) -> Result<Option<Mutation>, Error> {
+    let mut mutations = read(&file)?;
+    let last = mutations.pop();
+    if last.is_some() {
+        let mut text = String::new();
+        for mutation in mutations.iter() {
+            text.push_str(&serde_json::to_string(mutation)?);
+            text.push('\n');
+        }
+        write_atomic(file, text.as_bytes())?;
+    }
+    Ok(last)
+}
+
+/// Return the changes of mutations to entries mapped to path, oldest first,
+/// along with the mutations that made them.
+pub fn history_of<'a>(mutations: &'a [Mutation], path: &str) -> Vec<(&'a Mutation, &'a Event)> {
+    mutations
+        .iter()
+        .flat_map(|m| {
+            m.changes
+                .iter()
+                .filter(move |c| c.path == path)
+                .map(move |c| (m, c))
+        })
+        .collect()
+}
+
+/// Return those of entries that aren't among others.
+fn missing_from(entries: &[TrashedEntry], others: &[TrashedEntry]) -> Vec<TrashedEntry> {
+    entries
+        .iter()
+        .filter(|e| !others.contains(e))
+        .cloned()
+        .collect()
+}
+
+/// Return the text of describer's entry of the given kind mapped to path, if
+/// any.
+fn text_of<'a>(
+    describer: &'a Describer,
+    path: &str,
+    pattern: Option<PatternKind>,
+) -> Option<&'a str> {
+    describer
+        .entries_under(path)
+        .into_iter()
+        .find(|e| e.path == path && e.pattern == pattern)
+        .map(|e| e.description)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn revert_test() {
+        let mut old = Describer::new();
+        old.add_description("/srv", "Served files.");
+        old.add_pattern("/srv", "* is served.").unwrap();
+        old.add_pattern_with_kind("/srv", "** is under srv.", PatternKind::Recursive)
+            .unwrap();
+        old.add_description("/tmp", "Temporary files.");
+
+        let mut new = old.clone();
+        new.add_description("/srv", "Served.");
+        new.retain_entries(|e| e.pattern != Some(PatternKind::Children));
+        new.remove_description("/tmp");
+        new.add_glob("*.log", "A log.").unwrap();
+        let mutation = Mutation::between(&old, &new, "edit", 100).unwrap();
+        assert_eq!(mutation.changes.len(), 4);
+        assert_eq!(Mutation::between(&new, &new, "edit", 100), None);
+
+        let mut changed = new.clone();
+        changed.add_description("/srv", "Changed again.");
+        assert!(matches!(
+            mutation.revert(&mut changed),
+            Err(Error::Conflict(path)) if path == "/srv"
+        ));
+        assert_eq!(changed.description("/tmp"), None);
+
+        mutation.revert(&mut new).unwrap();
+        assert!(event::diff(&old, &new).is_empty());
+    }
+
+    #[test]
+    fn journal_test() {
+        let file = std::env::temp_dir().join(format!("def-journal-test-{}", std::process::id()));
+        let empty = Describer::new();
+        let mut one = Describer::new();
+        one.add_description("/a", "First.");
+        let mut two = one.clone();
+        two.add_description("/a", "Second.");
+        two.add_description("/b", "B.");
+
+        let missing = read(&file).unwrap();
+        let first = Mutation::between(&empty, &one, "add", 1).unwrap();
+        let second = Mutation::between(&one, &two, "import", 2).unwrap();
+        append(&file, &first).unwrap();
+        append(&file, &second).unwrap();
+        let read_back = read(&file).unwrap();
+        let popped = pop(&file).unwrap();
+        let remaining = read(&file).unwrap();
+        fs::write(&file, "not json\n").unwrap();
+        let invalid = read(&file);
+        fs::remove_file(&file).unwrap();
+
+        assert!(missing.is_empty());
+        assert_eq!(read_back, vec![first.clone(), second.clone()]);
+        assert_eq!(popped, Some(second));
+        assert_eq!(remaining, vec![first]);
+        assert!(matches!(invalid, Err(Error::InvalidJournal(e)) if e.starts_with("line 1:")));
+
+        let history = history_of(&read_back, "/a");
+        let texts: Vec<(&str, Option<&str>)> = history
+            .iter()
+            .map(|(m, c)| (m.op.as_str(), c.after.as_deref()))
+            .collect();
+        assert_eq!(
+            texts,
+            vec![("add", Some("First.")), ("import", Some("Second."))]
+        );
+    }
+}
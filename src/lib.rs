@@ -2,29 +2,160 @@
 //! structwhich is used to map string descriptions to paths and retrieve them when
 //! needed.
 
-use std::collections::HashMap;
+pub mod annotations;
+pub mod builder;
+pub mod capture;
+pub mod config;
+pub mod daemon;
+pub mod drift;
+mod error;
+pub mod event;
+pub mod filter;
+pub mod fixture;
+pub mod format;
+#[cfg(feature = "git")]
+pub mod git;
+pub mod glob;
+pub mod index;
+pub mod journal;
+pub mod local;
+pub mod matching;
+pub mod merge;
+pub mod meta;
+pub mod migrate;
+pub mod pack;
+pub mod paths;
+pub mod pattern;
+pub mod readmes;
+#[cfg(feature = "redact")]
+pub mod redact;
+pub mod resolution;
+pub mod search;
+pub mod settings;
+pub mod snapshot;
+pub mod stats;
+pub mod store;
+#[cfg(feature = "sync")]
+pub mod sync;
+pub mod syntax;
+pub mod template;
+pub mod trash;
+pub mod trie;
+pub mod vars;
+pub mod virtual_tree;
 
-use serde::{Deserialize, Serialize};
+use std::borrow::{Borrow, Cow};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::hash::Hash;
+use std::path::Path;
+use std::time::Duration;
+
+pub use builder::DescriberBuilder;
+use capture::Source;
+pub use error::Error;
+use format::Format;
+use matching::MatchOptions;
+use merge::{Change, MergeReport, Strategy};
+use meta::{FileInfo, FileKind, PathMetadata, RepoInfo, Timestamps};
+use migrate::MigrationError;
+pub use paths::DefPath;
+use pattern::{Pattern, PatternError, TextMap};
+use resolution::{ResolutionOrder, Stage};
+use serde::{Deserialize, Serialize, Serializer};
+pub use snapshot::DescriberSnapshot;
+use store::StoreError;
+use syntax::Syntax;
+use template::Substitution;
+use trash::{TrashError, TrashedEntry};
+use trie::PathTrie;
 
 /// Directory seperator. Used to split a string.
 const SEPERATOR: char = '/';
 
-/// A place holder in patterns. Replaced with a name.
-const NAME_PLACEHOLDER: char = '*';
-
 /// Describer holds descriptions of files and directories.
 ///
 /// # Types of Descriptions
 ///
 /// - Specific description: A string mapped to a path describing a file or directory.
-/// When describe is called this will be retrieved as is.
+///   When describe is called this will be retrieved as is.
 /// - Pattern description: A string mapped to a directory's path describing a child
-/// of the directory. When description of a child is wanted, the pattern is retrieved.
-/// In patterns, a wildcard is interpreted as a place holder for child's name, and are
-/// replaced by the name when retreived.
+///   of the directory. When description of a child is wanted, the pattern is retrieved.
+///   In patterns, a wildcard is interpreted as a place holder for child's name, and are
+///   replaced by the name when retreived.
+/// - Recursive pattern description: Like a pattern description, but describes all
+///   descendants of the directory, not only its children. In addition to "*", a
+///   double wildcard "**" is replaced by the descendant's path relative to the
+///   directory.
+/// - Glob description: A string mapped to a glob (such as `/home/*/Downloads` or
+///   `*.log`) describing all paths matching the glob. See `glob` module for the
+///   supported syntax. In glob descriptions, "*" is replaced by the path's name,
+///   and "**" by the whole path.
+///
+/// Patterns and globs can also use named placeholders, such as `{stem}` or
+/// `{ext}`, see `template` module for all placeholders. Patterns are validated
+/// when they're added (see `pattern::Pattern`), so adding a pattern with an
+/// unknown placeholder or an unmatched brace fails.
 ///
 /// If a string can be described using both a pattern and a specific description,
-/// the specific description will be favoured.
+/// the specific description will be favoured. Otherwise, the pattern mapped
+/// to the longest prefix of the path is used: the parent for patterns, the
+/// ancestor for recursive patterns, and the directory a glob is anchored to
+/// for globs (see `glob::prefix`). Patterns mapped to equally long prefixes
+/// are ranked by their priority (see `set_priority`), then globs are favoured
+/// over patterns, and patterns over recursive patterns. If multiple globs are
+/// still tied, the most specific one (with most non-wildcard characters) is
+/// used. `explain` returns all descriptions and patterns applying to a path,
+/// in this order.
+///
+/// This is the default resolution order, which can be changed to favour some
+/// kinds of patterns over others, or to leave some out, see
+/// `set_resolution_order` and `def::resolution`.
+///
+/// # Typed Patterns
+///
+/// Patterns can be limited to children of one type: files, directories, or
+/// symlinks (see `add_typed_pattern`), so that files and subdirectories of a
+/// directory are described differently. A typed pattern wins over the
+/// untyped pattern of the same directory, but only applies when the type of
+/// the described path is known: when it's given (see `describe_as`), or
+/// read from the file system (see `describe_with_fs`). Paths that don't
+/// exist, and paths described without the file system, are described as if
+/// there were no typed patterns.
+///
+/// # Matching
+///
+/// Paths match keys of descriptions and patterns only if they're the same,
+/// unless matching is set to ignore case or to normalize Unicode (see
+/// `set_match_options` and `def::matching`), for file systems that do so.
+/// Keys are kept as written either way, and adding a description to a path
+/// matching a described key replaces the description of that key.
+///
+/// # Tags
+///
+/// Specific descriptions can be tagged (such as "work" or "archived"), and
+/// found by their tags later. Tags are kept beside descriptions, so a
+/// description keeps its tags when it's changed.
+///
+/// # Privacy
+///
+/// Specific descriptions can be marked private, such as personal notes kept
+/// in a config shared with a team. Private descriptions describe paths like
+/// any other, but are left out of exports unless asked for (see
+/// `remove_private`).
+///
+/// # Sources
+///
+/// Specific descriptions can be the output of a command rather than text
+/// written by hand, in which case the command is kept as their source, and
+/// can be run again to refresh them (see `def::capture`).
+///
+/// # Trash
+///
+/// Descriptions and patterns can be moved to a trash instead of being deleted,
+/// and restored later. Trashed entries are never used to describe a path.
 ///
 /// # Examples
 ///
@@ -38,7 +169,9 @@ const NAME_PLACEHOLDER: char = '*';
 /// // Map a pattern to a given path. The pattern applies to the path's
 /// // children. "*" works as a placeholder and will be replaced by the
 /// // child's name.
-/// describer.add_pattern("parent/directory", "* is a child of parent/directory.");
+/// describer
+///     .add_pattern("parent/directory", "* is a child of parent/directory.")
+///     .unwrap();
 ///
 /// // The description is retrieved as is.
 /// assert_eq!(
@@ -57,21 +190,515 @@ const NAME_PLACEHOLDER: char = '*';
 /// assert_eq!(describer.describe("parent/directory"), None);
 /// ```
 ///
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct Describer {
-    descriptions: HashMap<String, String>,
-    patterns: HashMap<String, String>,
+    #[serde(skip_deserializing, default = "migrate::current_version")]
+    version: u64,
+    descriptions: PathTrie<String>,
+    patterns: PathTrie<Pattern>,
+    #[serde(default, skip_serializing_if = "PathTrie::is_empty")]
+    recursive_patterns: PathTrie<Pattern>,
+    #[serde(default, skip_serializing_if = "PathTrie::is_empty")]
+    typed_patterns: PathTrie<BTreeMap<FileKind, Pattern>>,
+    #[serde(
+        default,
+        skip_serializing_if = "HashMap::is_empty",
+        serialize_with = "serialize_sorted"
+    )]
+    globs: HashMap<String, Pattern>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    trash: Vec<TrashedEntry>,
+    #[serde(
+        default,
+        skip_serializing_if = "HashMap::is_empty",
+        serialize_with = "serialize_sorted"
+    )]
+    metadata: HashMap<DefPath, PathMetadata>,
+    #[serde(
+        default,
+        skip_serializing_if = "HashMap::is_empty",
+        serialize_with = "serialize_sorted"
+    )]
+    tags: HashMap<DefPath, BTreeSet<String>>,
+    #[serde(
+        default,
+        skip_serializing_if = "HashMap::is_empty",
+        serialize_with = "serialize_sorted"
+    )]
+    timestamps: HashMap<DefPath, Timestamps>,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    private: BTreeSet<DefPath>,
+    #[serde(
+        default,
+        skip_serializing_if = "HashMap::is_empty",
+        serialize_with = "serialize_sorted"
+    )]
+    priorities: HashMap<String, i32>,
+    #[serde(
+        default,
+        skip_serializing_if = "HashMap::is_empty",
+        serialize_with = "serialize_sorted"
+    )]
+    sources: HashMap<DefPath, Source>,
+    /// Order descriptions and patterns are tried in, which is a preference
+    /// of whoever describes paths rather than part of the config.
+    #[serde(skip)]
+    resolution: ResolutionOrder,
+    /// How paths are matched against keys, which, like the resolution
+    /// order, is a preference rather than part of the config.
+    #[serde(skip)]
+    matching: MatchOptions,
+    /// Characters patterns and paths are written with, which are chosen by
+    /// programs embedding def.
+    #[serde(skip)]
+    syntax: Syntax,
+}
+
+/// Serialize a map sorted by key, so that a describer is serialized the same
+/// way every time.
+fn serialize_sorted<K, V, S>(map: &HashMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    K: Serialize + Ord + Hash,
+    V: Serialize,
+    S: Serializer,
+{
+    map.iter().collect::<BTreeMap<_, _>>().serialize(serializer)
+}
+
+/// Replace every key of map with the result of f.
+fn rekey<K, V, F>(map: &mut HashMap<K, V>, f: F)
+where
+    K: Borrow<str> + From<String> + Eq + Hash,
+    F: Fn(&str) -> String,
+{
+    *map = map
+        .drain()
+        .map(|(k, v)| (f(k.borrow()).into(), v))
+        .collect();
+}
+
+/// Replace every key of trie with the result of f, like rekey, keeping the
+/// options its keys are matched with.
+fn rekey_trie<V, F: Fn(&str) -> String>(trie: &mut PathTrie<V>, f: F) {
+    let options = trie.match_options();
+    *trie = std::mem::take(trie)
+        .into_iter()
+        .map(|(k, v)| (f(k.as_str()).into(), v))
+        .collect();
+    trie.set_match_options(options);
+}
+
+/// Replace every key of set with the result of f, like rekey.
+fn rekey_set<F: Fn(&str) -> String>(set: &mut BTreeSet<DefPath>, f: F) {
+    *set = std::mem::take(set)
+        .into_iter()
+        .map(|k| f(k.as_str()).into())
+        .collect();
+}
+
+/// Render pattern for path. If fs is true and the pattern has metadata
+/// placeholders, they're filled in from the path's current metadata, unless
+/// it can't be read, and git placeholders from the repository of the path,
+/// if it's in one.
+fn render(pattern: &Pattern, path: &DefPath, relative: &str, fs: bool) -> String {
+    match read_placeholders(pattern, path, fs) {
+        (None, None) => pattern.render(path.as_str(), relative),
+        (info, repo) => {
+            pattern.render_with_repo(path.as_str(), relative, info.as_ref(), repo.as_ref())
+        }
+    }
+}
+
+/// Render pattern for path like `render`, and return the placeholders filled
+/// in along with the rendered pattern.
+fn render_detailed(
+    pattern: &Pattern,
+    path: &DefPath,
+    relative: &str,
+    fs: bool,
+) -> (String, Vec<Substitution>) {
+    let (info, repo) = read_placeholders(pattern, path, fs);
+    let (info, repo) = (info.as_ref(), repo.as_ref());
+    (
+        pattern.render_with_repo(path.as_str(), relative, info, repo),
+        pattern.substitutions(path.as_str(), relative, info, repo),
+    )
+}
+
+/// Return the metadata and git repository of path pattern's placeholders are
+/// filled in from, if fs is true and the pattern has any, see `render`.
+fn read_placeholders(
+    pattern: &Pattern,
+    path: &DefPath,
+    fs: bool,
+) -> (Option<FileInfo>, Option<RepoInfo>) {
+    let info = if fs && pattern.uses_metadata() {
+        FileInfo::read(path.as_str()).ok()
+    } else {
+        None
+    };
+    let repo = if fs && pattern.uses_git() {
+        read_repo(path)
+    } else {
+        None
+    };
+    (info, repo)
+}
+
+/// Return information about the git repository path is in, if any.
+#[cfg(feature = "git")]
+fn read_repo(path: &DefPath) -> Option<RepoInfo> {
+    git::read(path.as_str())
+}
+
+/// Git placeholders are left as written without "git" feature.
+#[cfg(not(feature = "git"))]
+fn read_repo(_path: &DefPath) -> Option<RepoInfo> {
+    None
+}
+
+/// Return the pattern describing path if it's the root of a git repository,
+/// see `git::REPO_PATTERN`.
+#[cfg(feature = "git")]
+fn repo_root_pattern(path: &DefPath) -> Option<Pattern> {
+    if !git::is_repo_root(path.as_str()) {
+        return None;
+    }
+    Some(Pattern::from_stored(git::REPO_PATTERN.to_string()))
+}
+
+/// Repository roots aren't described without "git" feature.
+#[cfg(not(feature = "git"))]
+fn repo_root_pattern(_path: &DefPath) -> Option<Pattern> {
+    None
+}
+
+/// Return path relative to ancestor, or None if ancestor isn't an ancestor
+/// of path, matching them with options.
+fn relative_to<'a>(path: &'a DefPath, ancestor: &str, options: MatchOptions) -> Option<&'a str> {
+    paths::iter_ancestors(path.as_str())
+        .find(|(a, _)| options.same(a, ancestor))
+        .map(|(_, relative)| relative)
+}
+
+/// Rule is a pattern applying to a path, which is ranked against other
+/// patterns applying to it, see `rank`.
+#[derive(Clone, Copy)]
+struct Rule<'a> {
+    kind: PatternKind,
+    /// Key the pattern is mapped to.
+    key: &'a str,
+    /// Length of the prefix of the path the pattern is mapped to.
+    prefix: usize,
+    priority: i32,
+    pattern: &'a Pattern,
+    /// Type of paths the pattern is limited to, see
+    /// `Describer::add_typed_pattern`.
+    entry_type: Option<FileKind>,
+    /// Options the key is matched with.
+    matching: MatchOptions,
+    /// Placeholder of names the pattern is rendered with, see `def::syntax`.
+    placeholder: char,
+}
+
+impl Rule<'_> {
+    /// Render the pattern for path, see `render`. For recursive patterns,
+    /// the path relative to the ancestor they're mapped to is used as the
+    /// name.
+    fn render(&self, path: &DefPath, fs: bool) -> Option<String> {
+        let pattern = self.pattern.with_placeholder(self.placeholder);
+        Some(render(&pattern, path, self.relative(path)?, fs))
+    }
+
+    /// Render the pattern for path like `render`, leaving metadata and git
+    /// placeholders as written, into rendered. Return false if nothing was
+    /// rendered.
+    fn render_into(&self, path: &DefPath, rendered: &mut String) -> bool {
+        match self.relative(path) {
+            Some(relative) => {
+                self.pattern.with_placeholder(self.placeholder).render_into(
+                    path.as_str(),
+                    relative,
+                    rendered,
+                );
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Render the pattern for path like `render`, along with the
+    /// placeholders filled in, see `render_detailed`.
+    fn render_detailed(&self, path: &DefPath, fs: bool) -> Option<(String, Vec<Substitution>)> {
+        Some(render_detailed(
+            &self.pattern.with_placeholder(self.placeholder),
+            path,
+            self.relative(path)?,
+            fs,
+        ))
+    }
+
+    /// Return what `**` is replaced by when rendering the pattern for path,
+    /// or None if the pattern doesn't apply to it.
+    fn relative<'p>(&self, path: &'p DefPath) -> Option<&'p str> {
+        match self.kind {
+            PatternKind::Children => Some(path.name()),
+            PatternKind::Recursive => relative_to(path, self.key, self.matching),
+            PatternKind::Glob => Some(path.as_str()),
+        }
+    }
+}
+
+/// Sort rules of the stages of step (a step of the resolution order)
+/// applying to the same path, the one used to describe it first: rules with
+/// longer prefixes come first, then rules with higher priorities, then rules
+/// of stages listed first in step (globs, patterns, and recursive patterns
+/// by default), then typed patterns, and finally more specific globs.
+fn rank(rules: &mut [Rule], step: &[Stage]) {
+    rules.sort_by(|a, b| precedence(step, a, b));
+}
+
+/// Compare rules of the stages of step applying to the same path, the one
+/// used to describe it being less, see `rank`.
+fn precedence(step: &[Stage], a: &Rule, b: &Rule) -> Ordering {
+    let order = |kind: PatternKind| step.iter().position(|s| *s == kind.into());
+    b.prefix
+        .cmp(&a.prefix)
+        .then(b.priority.cmp(&a.priority))
+        .then(order(a.kind).cmp(&order(b.kind)))
+        .then(b.entry_type.is_some().cmp(&a.entry_type.is_some()))
+        .then_with(|| glob::specificity(b.key).cmp(&glob::specificity(a.key)))
+        .then_with(|| a.key.cmp(b.key))
+}
+
+/// Return the rule of rules used to describe a path in step, a step of the
+/// resolution order, if any applies, see `rank`.
+fn best_of<'a, I: Iterator<Item = Rule<'a>>>(step: &[Stage], rules: I) -> Option<Rule<'a>> {
+    rules
+        .filter(|rule| step.contains(&rule.kind.into()))
+        .min_by(|a, b| precedence(step, a, b))
+}
+
+/// Resolved is what describes a path, see `Describer::resolve`.
+enum Resolved<'a> {
+    Description(&'a str),
+    Rule(Rule<'a>),
+    /// The pattern describing the root of a git repository.
+    Repository(Pattern),
+}
+
+impl Resolved<'_> {
+    /// Return the description of path, see `Rule::render`.
+    fn render(self, path: &DefPath, fs: bool) -> Option<String> {
+        match self {
+            Resolved::Description(d) => Some(d.to_string()),
+            Resolved::Rule(rule) => rule.render(path, fs),
+            Resolved::Repository(pattern) => Some(render(&pattern, path, path.as_str(), fs)),
+        }
+    }
+
+    /// Return the description of path, along with where it comes from.
+    fn render_detailed(self, path: &DefPath, fs: bool) -> Option<DescribeResult> {
+        let (key, kind, (description, substitutions)) = match self {
+            Resolved::Description(d) => {
+                (path.as_str(), MatchKind::Specific, (d.to_string(), vec![]))
+            }
+            Resolved::Rule(rule) => (rule.key, rule.kind.into(), rule.render_detailed(path, fs)?),
+            Resolved::Repository(pattern) => (
+                path.as_str(),
+                MatchKind::Repository,
+                render_detailed(&pattern, path, path.as_str(), fs),
+            ),
+        };
+        Some(DescribeResult {
+            description,
+            key: key.to_string(),
+            kind,
+            substitutions,
+        })
+    }
+}
+
+/// Return new if key is old, for renaming a single key.
+fn exact_rename(key: &str, old: &str, new: &str) -> Option<String> {
+    if key == old && old != new {
+        Some(new.to_string())
+    } else {
+        None
+    }
+}
+
+/// PatternKind defines which paths a pattern applies to.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum PatternKind {
+    /// The pattern applies to children of the directory it's mapped to.
+    Children,
+    /// The pattern applies to all descendants of the directory it's mapped to.
+    Recursive,
+    /// The pattern applies to all paths matching the glob it's mapped to.
+    Glob,
+}
+
+impl fmt::Display for PatternKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PatternKind::Children => write!(f, "pattern"),
+            PatternKind::Recursive => write!(f, "recursive pattern"),
+            PatternKind::Glob => write!(f, "glob"),
+        }
+    }
+}
+
+/// Rename describes a key that is rewritten by `Describer::rename_prefix`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Rename {
+    /// The key before renaming.
+    pub old: String,
+    /// The key after renaming.
+    pub new: String,
+    /// Kind of the pattern the key belongs to, or None if it belongs to a
+    /// specific description.
+    pub pattern: Option<PatternKind>,
+    /// True if an existing entry will be overwritten by the renamed one.
+    pub overwrites: bool,
+}
+
+/// Candidate is a description or a pattern applying to a path, see
+/// `Describer::explain`.
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct Candidate {
+    /// Key the description or pattern is mapped to: the path itself, one of
+    /// its ancestors, or a glob.
+    pub key: String,
+    /// Kind of the pattern, or None if the candidate is the path's specific
+    /// description.
+    pub pattern: Option<PatternKind>,
+    /// Priority of the pattern, see `Describer::set_priority`.
+    pub priority: i32,
+    /// Description of the path the candidate produces.
+    pub description: String,
+}
+
+/// DescribeResult is the description of a path, along with where it comes
+/// from, see `Describer::describe_detailed`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct DescribeResult {
+    /// Description of the path.
+    pub description: String,
+    /// Key of the description or pattern used: the path itself, the
+    /// directory a pattern is mapped to, or a glob. For inherited
+    /// descriptions, the ancestor they're inherited from.
+    pub key: String,
+    /// Kind of the description or pattern used.
+    pub kind: MatchKind,
+    /// Placeholders of the pattern filled in, along with their values, each
+    /// once in the order they appear in the pattern.
+    pub substitutions: Vec<Substitution>,
+}
+
+/// MatchKind is the kind of description or pattern a path is described by.
+#[derive(Serialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchKind {
+    /// A specific description of the path.
+    Specific,
+    /// A pattern of the path's parent.
+    ParentPattern,
+    /// A recursive pattern of one of the path's ancestors.
+    RecursivePattern,
+    /// A glob matching the path.
+    Glob,
+    /// The description of roots of git repositories, see `git::REPO_PATTERN`.
+    Repository,
+    /// The description of one of the path's ancestors.
+    Inherited,
+}
+
+impl From<PatternKind> for MatchKind {
+    fn from(kind: PatternKind) -> MatchKind {
+        match kind {
+            PatternKind::Children => MatchKind::ParentPattern,
+            PatternKind::Recursive => MatchKind::RecursivePattern,
+            PatternKind::Glob => MatchKind::Glob,
+        }
+    }
+}
+
+/// Inherited is the description of an ancestor of a path, used for paths
+/// without a description of their own, see `Describer::describe_inherited`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Inherited {
+    /// The nearest described ancestor of the path.
+    pub ancestor: String,
+    /// The path relative to the ancestor, such as "src/main.rs".
+    pub relative: String,
+    /// Description of the ancestor.
+    pub description: String,
+}
+
+/// Removed is a description or a pattern removed by `Describer::prune_with`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Removed {
+    /// Path the description or pattern was mapped to.
+    pub path: String,
+    /// The removed description or pattern.
+    pub description: String,
+    /// Kind of the pattern, or None if the entry was a specific description.
+    pub pattern: Option<PatternKind>,
+}
+
+/// Entry is a description or a pattern, along with the path it's mapped to.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Entry<'a> {
+    /// Path the description or pattern is mapped to.
+    pub path: &'a str,
+    /// The description or pattern.
+    pub description: &'a str,
+    /// Kind of the pattern, or None if the entry is a specific description.
+    pub pattern: Option<PatternKind>,
+}
+
+/// Conversion is the result of converting keys to the local convention, see
+/// `Describer::convert_keys`.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct Conversion {
+    /// Converted keys, as (old, new) pairs.
+    pub converted: Vec<(String, String)>,
+    /// Keys left as they are, as (key, converted) pairs, since their
+    /// converted form was already a key, such as `D:\data` once `C:\data`
+    /// was converted to `/data`.
+    pub collisions: Vec<(String, String)>,
 }
 
 impl Describer {
     /// Create and return a new empty describer.
     pub fn new() -> Describer {
         Describer {
-            descriptions: HashMap::new(),
-            patterns: HashMap::new(),
+            version: migrate::CURRENT_VERSION,
+            descriptions: PathTrie::new(),
+            patterns: PathTrie::new(),
+            recursive_patterns: PathTrie::new(),
+            typed_patterns: PathTrie::new(),
+            globs: HashMap::new(),
+            trash: Vec::new(),
+            metadata: HashMap::new(),
+            tags: HashMap::new(),
+            timestamps: HashMap::new(),
+            private: BTreeSet::new(),
+            priorities: HashMap::new(),
+            sources: HashMap::new(),
+            resolution: ResolutionOrder::default(),
+            matching: MatchOptions::default(),
+            syntax: Syntax::default(),
         }
     }
 
+    /// Create and return a builder of describers, see `DescriberBuilder`.
+    pub fn builder() -> DescriberBuilder {
+        DescriberBuilder::new()
+    }
+
     /// Create and return a new describer using given HashMaps.
     ///
     /// # Arguments
@@ -80,144 +707,3367 @@ impl Describer {
     /// * `p` - A map of patterns.
     pub fn new_with(d: HashMap<String, String>, p: HashMap<String, String>) -> Describer {
         Describer {
-            descriptions: d,
-            patterns: p,
+            version: migrate::CURRENT_VERSION,
+            descriptions: d.into_iter().map(|(k, v)| (k.into(), v)).collect(),
+            patterns: p.into_iter().map(|(k, v)| (k.into(), v.into())).collect(),
+            recursive_patterns: PathTrie::new(),
+            typed_patterns: PathTrie::new(),
+            globs: HashMap::new(),
+            trash: Vec::new(),
+            metadata: HashMap::new(),
+            tags: HashMap::new(),
+            timestamps: HashMap::new(),
+            private: BTreeSet::new(),
+            priorities: HashMap::new(),
+            sources: HashMap::new(),
+            resolution: ResolutionOrder::default(),
+            matching: MatchOptions::default(),
+            syntax: Syntax::default(),
         }
     }
 
-    /// Create and return a new describer using the given JSON value.
+    /// Create and return a new describer using the given JSON value. Unlike
+    /// `from_versioned_json`, older formats aren't migrated.
     ///
     /// # Arguments
     ///
     /// * `json` - A string representing a JSON value that can be deserialized
-    /// into a Describer. An error is returned if the JSON string can't be
-    /// deserialized.
+    ///   into a Describer. An error is returned if the JSON string can't be
+    ///   deserialized.
     pub fn new_from_json(json: &str) -> Result<Describer, serde_json::Error> {
         serde_json::from_str::<Describer>(json)
     }
 
+    /// Create and return a new describer using the given JSON value, which
+    /// may have been serialized by an older version of the library, in which
+    /// case it's migrated to the current version. See `migrate` module.
+    pub fn from_versioned_json(json: &str) -> Result<Describer, MigrationError> {
+        let value =
+            serde_json::from_str(json).map_err(|e| MigrationError::Invalid(e.to_string()))?;
+        Describer::from_versioned_value(value).map(|(describer, _)| describer)
+    }
+
+    /// Load a describer from the file at path, decoded using format (see
+    /// `format::from_path` to choose it by the file's extension). Describers
+    /// saved by older versions of the library are migrated, but the file
+    /// isn't changed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use def::{format, Describer};
+    ///
+    /// let path = "/home/user/descriptions.toml";
+    /// let format = format::from_path(path).unwrap_or(&format::Json);
+    /// let describer = Describer::load(path, format).unwrap();
+    /// describer.save("/home/user/descriptions.json", &format::Json).unwrap();
+    /// ```
+    pub fn load<P: AsRef<Path>>(path: P, format: &dyn Format) -> Result<Describer, Error> {
+        let value = format
+            .decode(&fs::read(path)?)
+            .map_err(|e| Error::Store(StoreError::Format(e)))?;
+        let (describer, _) = Describer::from_versioned_value(value)
+            .map_err(|e| Error::Store(StoreError::Format(e.to_string())))?;
+        Ok(describer)
+    }
+
+    /// Save the describer to the file at path, encoded using format. The file
+    /// is replaced atomically (see `store::write_atomic`).
+    pub fn save<P: AsRef<Path>>(&self, path: P, format: &dyn Format) -> Result<(), Error> {
+        let bytes = format
+            .encode(self)
+            .map_err(|e| Error::Store(StoreError::Format(e)))?;
+        Ok(store::write_atomic(path, &bytes)?)
+    }
+
+    /// Load the describer used by the def command (see
+    /// `config::default_path`), or an empty describer if it doesn't exist
+    /// yet.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// let mut describer = def::Describer::load_default().unwrap();
+    /// describer.add_description("/home/user/notes", "Personal notes.");
+    /// describer.save_default().unwrap();
+    /// ```
+    pub fn load_default() -> Result<Describer, Error> {
+        let store = config::open_store(&config::default_settings()?)?;
+        if store.exists() {
+            Ok(store.load()?)
+        } else {
+            Ok(Describer::new())
+        }
+    }
+
+    /// Save the describer as the one used by the def command, holding the
+    /// config's lock, deleting expired trash, and journaling changes as def
+    /// does (see `config::save_as`). Changes made by others since the
+    /// describer was loaded are overwritten, use `config::update` to avoid
+    /// that.
+    pub fn save_default(&mut self) -> Result<(), Error> {
+        let settings = config::default_settings()?;
+        let _lock = config::lock(&settings)?;
+        config::save_as(&settings, self, config::SAVE_OP)
+    }
+
+    /// Like `load_default`, but load the describer of the profile named name
+    /// (see `config::use_profile`), as used by "def --profile <name>".
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// let mut describer = def::Describer::load_profile("work").unwrap();
+    /// describer.add_description("/mnt/work/reports", "Quarterly reports.");
+    /// describer.save_profile("work").unwrap();
+    /// ```
+    pub fn load_profile(name: &str) -> Result<Describer, Error> {
+        let settings = config::use_profile(&config::load_settings()?, name)?;
+        let store = config::open_store(&settings)?;
+        if store.exists() {
+            Ok(store.load()?)
+        } else {
+            Ok(Describer::new())
+        }
+    }
+
+    /// Like `save_default`, but save the describer as that of the profile
+    /// named name.
+    pub fn save_profile(&mut self, name: &str) -> Result<(), Error> {
+        let settings = config::use_profile(&config::load_settings()?, name)?;
+        let _lock = config::lock(&settings)?;
+        config::save_as(&settings, self, config::SAVE_OP)
+    }
+
+    /// Like `from_versioned_json`, but takes an already parsed value, and also
+    /// returns the version the describer was migrated from. The describer
+    /// should be saved again if it was migrated from an older version.
+    pub fn from_versioned_value(
+        mut value: serde_json::Value,
+    ) -> Result<(Describer, u64), MigrationError> {
+        let from = migrate::migrate(&mut value)?;
+        let describer =
+            serde_json::from_value(value).map_err(|e| MigrationError::Invalid(e.to_string()))?;
+        Ok((describer, from))
+    }
+
+    /// Return a description of the given path, or `Error::NoDescription` if
+    /// no description exists. See `describe`.
+    pub fn try_describe<P: Into<DefPath>>(&self, path: P) -> Result<String, Error> {
+        let path = self.key(path);
+        self.describe(&path)
+            .ok_or_else(|| Error::NoDescription(path.into()))
+    }
+
     /// Return a description of the given path or None if no description
     /// exists. The descriptions map is checked for a description first,
-    /// if none is found, then the patterns map is checked.
-    pub fn describe(&self, path: &str) -> Option<String> {
-        match self.descriptions.get(path) {
-            Some(d) => Some(d.clone()),
-            None => self.describe_using_pattern(path),
-        }
+    /// if none is found, then the globs map, then the patterns map, and
+    /// finally the recursive patterns map. Metadata placeholders of patterns
+    /// (such as `{size}`) are left as written, see `describe_with_fs`.
+    pub fn describe<P: Into<DefPath>>(&self, path: P) -> Option<String> {
+        self.describe_rendering(&self.key(path), false)
     }
 
-    /// Check patterns map for a description. If one exists, return it with
-    /// all place holders replaced, otherwise return None.
-    fn describe_using_pattern(&self, path: &str) -> Option<String> {
-        let parent: Vec<&str> = path.rsplitn(2, SEPERATOR).collect();
-        if parent.len() != 2 {
-            None
+    /// Return a description of the given path like `describe`, filling in
+    /// metadata placeholders of patterns (such as `{size}`) from the path's
+    /// current metadata on the file system. If the metadata can't be read,
+    /// such as when the path doesn't exist, they're left as written. With
+    /// "git" feature, git placeholders are filled in too, and roots of git
+    /// repositories nothing else describes are described by
+    /// `git::REPO_PATTERN`.
+    pub fn describe_with_fs<P: Into<DefPath>>(&self, path: P) -> Option<String> {
+        self.describe_rendering(&self.key(path), true)
+    }
+
+    /// Return a description of the given path like `describe`, as a path of
+    /// the given type, so that typed patterns (see `add_typed_pattern`) of
+    /// that type apply to it. The file system isn't read, so the path
+    /// doesn't have to exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use def::meta::FileKind;
+    ///
+    /// let mut d = def::Describer::new();
+    /// d.add_pattern("/downloads", "{name} was downloaded.").unwrap();
+    /// d.add_typed_pattern("/downloads", "{name} was extracted.", FileKind::Directory)
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     d.describe_as("/downloads/def", FileKind::Directory),
+    ///     Some("def was extracted.".to_string())
+    /// );
+    /// assert_eq!(
+    ///     d.describe_as("/downloads/def.tar", FileKind::File),
+    ///     Some("def.tar was downloaded.".to_string())
+    /// );
+    /// assert_eq!(d.describe("/downloads/def"), Some("def was downloaded.".to_string()));
+    /// ```
+    pub fn describe_as<P: Into<DefPath>>(&self, path: P, kind: FileKind) -> Option<String> {
+        let path = self.key(path);
+        self.resolve(&path, false, |step| self.best_rule(&path, Some(kind), step))?
+            .render(&path, false)
+    }
+
+    /// Write the description of the given path, as `describe` returns it,
+    /// into description, replacing what it held. Return false, leaving it
+    /// empty, if no description exists. Nothing is allocated unless
+    /// description has to grow, so a buffer reused across calls makes
+    /// describing many paths (such as the entries of a listing) cheaper.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use def::DefPath;
+    ///
+    /// let mut d = def::Describer::new();
+    /// d.add_pattern("/srv", "* is served.").unwrap();
+    /// let mut description = String::new();
+    /// for (path, described) in [("/srv/www", true), ("/tmp", false)].iter() {
+    ///     assert_eq!(d.describe_into(&DefPath::new(path), &mut description), *described);
+    /// }
+    /// assert!(description.is_empty());
+    /// d.describe_into(&DefPath::new("/srv/ftp"), &mut description);
+    /// assert_eq!(description, "ftp is served.");
+    /// ```
+    pub fn describe_into(&self, path: &DefPath, description: &mut String) -> bool {
+        description.clear();
+        let converted;
+        let path = if self.syntax.separator == SEPERATOR {
+            path
         } else {
-            match self.patterns.get(parent[1]) {
-                Some(p) => Some(p.replace(NAME_PLACEHOLDER, parent[0])),
-                None => None,
+            converted = self.key(path);
+            &converted
+        };
+        match self.resolve(path, false, |step| self.best_rule(path, None, step)) {
+            Some(Resolved::Description(d)) => description.push_str(d),
+            Some(Resolved::Rule(rule)) => return rule.render_into(path, description),
+            Some(Resolved::Repository(pattern)) => {
+                pattern.render_into(path.as_str(), path.as_str(), description)
             }
+            None => return false,
         }
+        true
     }
 
-    /// Add a description to the descriptions map.
-    pub fn add_description(&mut self, path: &str, desc: &str) {
-        self.descriptions.insert(path.to_string(), desc.to_string());
+    /// Return the description of the nearest ancestor of the given path that
+    /// is described, by a description or a pattern, or None if none is. The
+    /// path itself isn't an ancestor, so this is meant for paths `describe`
+    /// returns None for. Metadata placeholders are left as written, see
+    /// `describe_inherited_with_fs`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut d = def::Describer::new();
+    /// d.add_description("/projects", "Client work.");
+    /// let inherited = d.describe_inherited("/projects/site/index.html").unwrap();
+    /// assert_eq!(inherited.ancestor, "/projects");
+    /// assert_eq!(inherited.relative, "site/index.html");
+    /// assert_eq!(inherited.description, "Client work.");
+    /// ```
+    pub fn describe_inherited<P: Into<DefPath>>(&self, path: P) -> Option<Inherited> {
+        self.inherit_rendering(&self.key(path), false)
     }
 
-    /// Add a pattern to the patterns map.
-    pub fn add_pattern(&mut self, path: &str, desc: &str) {
-        self.patterns.insert(path.to_string(), desc.to_string());
+    /// Return the description of the nearest described ancestor of the given
+    /// path like `describe_inherited`, filling in metadata placeholders as
+    /// `describe_with_fs` does.
+    pub fn describe_inherited_with_fs<P: Into<DefPath>>(&self, path: P) -> Option<Inherited> {
+        self.inherit_rendering(&self.key(path), true)
     }
 
-    /// Return a string JSON representation of this Describer. This is
-    /// subsequently written to a file to be re-loaded on next run.
+    /// Return the description of the nearest described ancestor of the given
+    /// path, filling in metadata placeholders if fs is true, see
+    /// `describe_inherited`.
+    fn inherit_rendering(&self, path: &DefPath, fs: bool) -> Option<Inherited> {
+        path.ancestors()
+            .into_iter()
+            .find_map(|(ancestor, relative)| {
+                let description = self.describe_rendering(&DefPath::new(ancestor), fs)?;
+                Some(Inherited {
+                    ancestor: ancestor.to_string(),
+                    relative: relative.to_string(),
+                    description,
+                })
+            })
+    }
+
+    /// Return the description of the given path like `describe`, along with
+    /// the key of the description or pattern used, its kind, and the
+    /// placeholders it filled in.
     ///
-    /// # Arguments
+    /// # Examples
     ///
-    /// * `pretty` - If true, return a "pretty" JSON string.
-    pub fn to_json(&self, pretty: bool) -> Result<String, serde_json::Error> {
-        if pretty {
-            serde_json::to_string_pretty(self)
-        } else {
-            serde_json::to_string(self)
+    /// ```
+    /// use def::template::Placeholder;
+    /// use def::MatchKind;
+    ///
+    /// let mut d = def::Describer::new();
+    /// d.add_pattern("/src", "The {stem} module.").unwrap();
+    /// let result = d.describe_detailed("/src/main.rs").unwrap();
+    /// assert_eq!(result.description, "The main module.");
+    /// assert_eq!(result.key, "/src");
+    /// assert_eq!(result.kind, MatchKind::ParentPattern);
+    /// assert_eq!(result.substitutions[0].placeholder, Placeholder::Stem);
+    /// assert_eq!(result.substitutions[0].value, "main");
+    /// ```
+    pub fn describe_detailed<P: Into<DefPath>>(&self, path: P) -> Option<DescribeResult> {
+        self.detailed_rendering(&self.key(path), false)
+    }
+
+    /// Return the description of the given path like `describe_detailed`,
+    /// filling in metadata placeholders as `describe_with_fs` does.
+    pub fn describe_detailed_with_fs<P: Into<DefPath>>(&self, path: P) -> Option<DescribeResult> {
+        self.detailed_rendering(&self.key(path), true)
+    }
+
+    /// Return the description of the nearest described ancestor of the given
+    /// path like `describe_inherited`, along with where it comes from, as
+    /// `describe_detailed` does. The result's kind is `MatchKind::Inherited`,
+    /// and its key is the ancestor. Metadata placeholders are left as
+    /// written, see `describe_inherited_detailed_with_fs`.
+    pub fn describe_inherited_detailed<P: Into<DefPath>>(&self, path: P) -> Option<DescribeResult> {
+        self.inherit_detailed_rendering(&self.key(path), false)
+    }
+
+    /// Return the description of the nearest described ancestor of the given
+    /// path like `describe_inherited_detailed`, filling in metadata
+    /// placeholders as `describe_with_fs` does.
+    pub fn describe_inherited_detailed_with_fs<P: Into<DefPath>>(
+        &self,
+        path: P,
+    ) -> Option<DescribeResult> {
+        self.inherit_detailed_rendering(&self.key(path), true)
+    }
+
+    /// Return the description of the given path along with where it comes
+    /// from, filling in metadata placeholders if fs is true, see
+    /// `describe_detailed`.
+    fn detailed_rendering(&self, path: &DefPath, fs: bool) -> Option<DescribeResult> {
+        let entry_type = self.entry_type(path, fs);
+        self.resolve(path, fs, |step| self.best_rule(path, entry_type, step))?
+            .render_detailed(path, fs)
+    }
+
+    /// Return the description of the nearest described ancestor of the given
+    /// path along with where it comes from, filling in metadata placeholders
+    /// if fs is true, see `describe_inherited_detailed`.
+    fn inherit_detailed_rendering(&self, path: &DefPath, fs: bool) -> Option<DescribeResult> {
+        path.ancestors().into_iter().find_map(|(ancestor, _)| {
+            let result = self.detailed_rendering(&DefPath::new(ancestor), fs)?;
+            Some(DescribeResult {
+                key: ancestor.to_string(),
+                kind: MatchKind::Inherited,
+                ..result
+            })
+        })
+    }
+
+    /// Return a description of the given path, filling in metadata
+    /// placeholders if fs is true, see `describe`.
+    fn describe_rendering(&self, path: &DefPath, fs: bool) -> Option<String> {
+        let entry_type = self.entry_type(path, fs);
+        self.resolve(path, fs, |step| self.best_rule(path, entry_type, step))?
+            .render(path, fs)
+    }
+
+    /// Return the type of the given path, read from the file system if fs is
+    /// true, or None if it's unknown. The file system isn't read if there
+    /// are no typed patterns.
+    fn entry_type(&self, path: &DefPath, fs: bool) -> Option<FileKind> {
+        if !fs || self.typed_patterns.is_empty() {
+            return None;
         }
+        FileKind::of(path.as_str()).ok()
     }
-}
 
-impl Default for Describer {
-    fn default() -> Describer {
-        Describer::new()
+    /// Return what describes the given path: the first step of the
+    /// resolution order that does. best_rule returns the rule used to
+    /// describe the path in a step of pattern stages, if any. Roots of git
+    /// repositories are only described if fs is true.
+    fn resolve<'a, F>(&'a self, path: &DefPath, fs: bool, mut best_rule: F) -> Option<Resolved<'a>>
+    where
+        F: FnMut(&[Stage]) -> Option<Rule<'a>>,
+    {
+        self.resolution.steps().find_map(|step| match step {
+            [Stage::Description] => self
+                .descriptions
+                .get(path)
+                .map(|d| Resolved::Description(d)),
+            [Stage::Repository] if fs => repo_root_pattern(path).map(Resolved::Repository),
+            [Stage::Repository] => None,
+            step => best_rule(step).map(Resolved::Rule),
+        })
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Return the descriptions and patterns applying to the given path, the
+    /// one `describe` uses first, followed by those that also apply but lose
+    /// to it, in the order they'd be used if the ones before them were
+    /// removed. See `Describer` for how they're ordered. Metadata
+    /// placeholders are left as written, see `explain_with_fs`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut d = def::Describer::new();
+    /// d.add_pattern("/srv", "* is served.").unwrap();
+    /// d.add_glob("*.log", "A log.").unwrap();
+    /// let candidates = d.explain("/srv/access.log");
+    /// assert_eq!(candidates[0].description, "access.log is served.");
+    /// assert_eq!(candidates[1].key, "*.log");
+    /// ```
+    pub fn explain<P: Into<DefPath>>(&self, path: P) -> Vec<Candidate> {
+        self.explain_rendering(&self.key(path), false)
+    }
 
-    #[test]
-    fn new_describe_test() {
-        let mut descriptions: HashMap<String, String> = HashMap::new();
-        let mut patterns: HashMap<String, String> = HashMap::new();
-        for (path, desc, is_pattern) in [
-            ("/path/to/dir", "This is /path/to/dir.", false),
-            ("/another/dir", "This is /another/dir.", false),
-            ("/yet/another/path", "This is /yet/another/path.", false),
-            ("/path/to/dir", "* is in /path/to/dir.", true),
-            ("/yet/another/path", "* is in /yet/another/path.", true),
-            ("/obvious", "* is *", true),
-            ("/yet/another", "* is in /yet/another/path.", true),
-        ]
-        .iter()
-        {
-            if *is_pattern {
-                patterns.insert(path.to_string(), desc.to_string());
-            } else {
-                descriptions.insert(path.to_string(), desc.to_string());
+    /// Return the descriptions and patterns applying to the given path like
+    /// `explain`, filling in metadata placeholders as `describe_with_fs`
+    /// does.
+    pub fn explain_with_fs<P: Into<DefPath>>(&self, path: P) -> Vec<Candidate> {
+        self.explain_rendering(&self.key(path), true)
+    }
+
+    /// Return the descriptions and patterns applying to the given path,
+    /// filling in metadata placeholders if fs is true, see `explain`.
+    fn explain_rendering(&self, path: &DefPath, fs: bool) -> Vec<Candidate> {
+        let rules = self.rules(path, self.entry_type(path, fs));
+        let mut candidates = Vec::new();
+        for step in self.resolution.steps() {
+            match step {
+                [Stage::Description] => {
+                    candidates.extend(self.descriptions.get(path).map(|d| Candidate {
+                        key: path.to_string(),
+                        pattern: None,
+                        priority: 0,
+                        description: d.clone(),
+                    }))
+                }
+                [Stage::Repository] => {}
+                step => {
+                    let mut ranked: Vec<Rule> = rules
+                        .iter()
+                        .copied()
+                        .filter(|rule| step.contains(&rule.kind.into()))
+                        .collect();
+                    rank(&mut ranked, step);
+                    candidates.extend(ranked.into_iter().filter_map(|rule| {
+                        Some(Candidate {
+                            key: rule.key.to_string(),
+                            pattern: Some(rule.kind),
+                            priority: rule.priority,
+                            description: rule.render(path, fs)?,
+                        })
+                    }));
+                }
             }
         }
+        candidates
+    }
 
-        describe_tester(&Describer::new_with(descriptions, patterns));
+    /// Return the rule used to describe the given path, of type entry_type
+    /// if it's known, in step, a step of the resolution order, without
+    /// collecting the rules applying to it.
+    fn best_rule(
+        &self,
+        path: &DefPath,
+        entry_type: Option<FileKind>,
+        step: &[Stage],
+    ) -> Option<Rule<'_>> {
+        let entry_rules = path.parent().into_iter().flat_map(|dir| {
+            let pattern = self
+                .patterns
+                .get_key_value(dir)
+                .map(|(key, pattern)| (PatternKind::Children, key, pattern));
+            let recursive_patterns = self
+                .recursive_patterns
+                .ancestry(dir)
+                .map(|(key, pattern)| (PatternKind::Recursive, key, pattern));
+            pattern.into_iter().chain(recursive_patterns)
+        });
+        let uses_globs = step.contains(&Stage::Glob);
+        let glob_rules = self
+            .globs
+            .iter()
+            .filter(|(g, _)| uses_globs && self.glob_matches(g, path))
+            .map(|(g, p)| self.rule(PatternKind::Glob, g, glob::prefix(g).len(), p));
+        let typed_rule = path
+            .parent()
+            .and_then(|dir| self.typed_rule(dir, entry_type));
+        let rules = entry_rules
+            .map(|(kind, key, pattern)| self.rule(kind, key.as_str(), key.as_str().len(), pattern))
+            .chain(typed_rule)
+            .chain(glob_rules);
+        best_of(step, rules)
     }
 
-    #[test]
-    fn new_from_json_describe_test() {
-        match Describer::new_from_json(
-            "
-	    {
-                \"descriptions\": {
-                        \"/path/to/dir\": \"This is /path/to/dir.\",
-                        \"/another/dir\": \"This is /another/dir.\",
-                        \"/yet/another/path\": \"This is /yet/another/path.\"
-		},
-                \"patterns\": {
-                        \"/path/to/dir\": \"* is in /path/to/dir.\",
-                        \"/yet/another/path\": \"* is in /yet/another/path.\",
-                        \"/obvious\": \"* is *\",
-                        \"/yet/another\": \"* is in /yet/another/path.\"
-                }
-            }",
-        ) {
-            Ok(d) => describe_tester(&d),
-            Err(e) => panic!(e),
+    /// Return rules of the patterns and globs applying to the given path, of
+    /// type entry_type if it's known, unranked.
+    fn rules(&self, path: &DefPath, entry_type: Option<FileKind>) -> Vec<Rule<'_>> {
+        let mut rules = match path.parent() {
+            Some(parent) => self.entry_rules(parent),
+            None => Vec::new(),
         };
+        rules.extend(
+            path.parent()
+                .and_then(|dir| self.typed_rule(dir, entry_type)),
+        );
+        rules.extend(self.glob_rules(path));
+        rules
     }
 
-    #[test]
-    fn add_test() {
-        let mut d = Describer::new();
-        d.add_description("path/to/directory", "This is an empty directory.");
-        d.add_pattern("parent/directory", "* is a child of parent/directory.");
-        assert_eq!(
-            d.to_json(false).unwrap(),
-            format!(
-                "{}{}{}{}",
-                "{\"descriptions\":",
-                "{\"path/to/directory\":\"This is an empty directory.\"},",
-                "\"patterns\":",
-                "{\"parent/directory\":\"* is a child of parent/directory.\"}}"
+    /// Return the rule of the typed pattern of dir applying to its entries
+    /// of type entry_type, if it's known and dir has one.
+    fn typed_rule(&self, dir: &str, entry_type: Option<FileKind>) -> Option<Rule<'_>> {
+        let (key, patterns) = self.typed_patterns.get_key_value(dir)?;
+        let pattern = patterns.get(&entry_type?)?;
+        Some(Rule {
+            entry_type,
+            ..self.rule(
+                PatternKind::Children,
+                key.as_str(),
+                key.as_str().len(),
+                pattern,
             )
-        );
+        })
+    }
+
+    /// Return rules of the patterns applying to entries of dir: its pattern,
+    /// and recursive patterns of it and its ancestors.
+    fn entry_rules(&self, dir: &str) -> Vec<Rule<'_>> {
+        let pattern = self
+            .patterns
+            .lineage(dir)
+            .into_iter()
+            .take(1)
+            .filter(|(key, _)| self.matching.same(key.as_str(), dir))
+            .map(|(key, pattern)| (PatternKind::Children, key, pattern));
+        let recursive_patterns = self
+            .recursive_patterns
+            .lineage(dir)
+            .into_iter()
+            .map(|(key, pattern)| (PatternKind::Recursive, key, pattern));
+        pattern
+            .chain(recursive_patterns)
+            .map(|(kind, key, pattern)| self.rule(kind, key.as_str(), key.as_str().len(), pattern))
+            .collect()
+    }
+
+    /// Return rules of the globs matching path.
+    fn glob_rules(&self, path: &DefPath) -> Vec<Rule<'_>> {
+        self.globs
+            .iter()
+            .filter(|(g, _)| self.glob_matches(g, path))
+            .map(|(g, p)| self.rule(PatternKind::Glob, g, glob::prefix(g).len(), p))
+            .collect()
+    }
+
+    /// Return the rule of a pattern of the given kind mapped to key.
+    fn rule<'a>(
+        &'a self,
+        kind: PatternKind,
+        key: &'a str,
+        prefix: usize,
+        pattern: &'a Pattern,
+    ) -> Rule<'a> {
+        Rule {
+            kind,
+            key,
+            prefix,
+            priority: self.priorities.get(key).copied().unwrap_or_default(),
+            pattern,
+            entry_type: None,
+            matching: self.matching,
+            placeholder: self.syntax.placeholder,
+        }
+    }
+
+    /// Return true if glob matches path, see `glob::matches`.
+    fn glob_matches(&self, glob: &str, path: &DefPath) -> bool {
+        if self.matching.is_exact() {
+            return glob::matches(glob, path.as_str());
+        }
+        glob::matches(
+            &self.matching.fold(glob),
+            &self.matching.fold(path.as_str()),
+        )
+    }
+
+    /// Return the key the description of path is kept under, which differs
+    /// from path if they only match (see `set_match_options`), or path
+    /// itself if it has no description.
+    fn stored_key(&self, path: DefPath) -> DefPath {
+        if self.matching.is_exact() {
+            return path;
+        }
+        match self.descriptions.get_key_value(&path) {
+            Some((key, _)) => key.clone(),
+            None => path,
+        }
+    }
+
+    /// Return descriptions of the given paths, in their order, with None for
+    /// paths that can't be described.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut d = def::Describer::new();
+    /// d.add_description("/etc", "Configuration.");
+    /// assert_eq!(
+    ///     d.describe_many(vec!["/etc", "/tmp"]),
+    ///     vec![Some("Configuration.".to_string()), None]
+    /// );
+    /// ```
+    pub fn describe_many<I>(&self, paths: I) -> Vec<Option<String>>
+    where
+        I: IntoIterator,
+        I::Item: Into<DefPath>,
+    {
+        paths.into_iter().map(|p| self.describe(p)).collect()
+    }
+
+    /// Return descriptions of the entries of a directory, given the directory's
+    /// path and the names of its entries. Descriptions are returned in the
+    /// order of names, with None for entries that can't be described. The
+    /// patterns applying to the directory's entries are looked up once,
+    /// rather than once for each entry. Metadata placeholders are left as
+    /// written, see `describe_dir_entries_with_fs`.
+    pub fn describe_dir_entries<P: Into<DefPath>, S: AsRef<str>>(
+        &self,
+        dir: P,
+        names: &[S],
+    ) -> Vec<Option<String>> {
+        self.describe_dir_entries_rendering(&self.key(dir), names, false)
+    }
+
+    /// Return descriptions of the entries of a directory like
+    /// `describe_dir_entries`, filling in metadata placeholders as
+    /// `describe_with_fs` does.
+    pub fn describe_dir_entries_with_fs<P: Into<DefPath>, S: AsRef<str>>(
+        &self,
+        dir: P,
+        names: &[S],
+    ) -> Vec<Option<String>> {
+        self.describe_dir_entries_rendering(&self.key(dir), names, true)
+    }
+
+    /// Return descriptions of the entries of a directory, filling in metadata
+    /// placeholders if fs is true, see `describe_dir_entries`.
+    fn describe_dir_entries_rendering<S: AsRef<str>>(
+        &self,
+        dir: &DefPath,
+        names: &[S],
+        fs: bool,
+    ) -> Vec<Option<String>> {
+        let entry_rules = self.entry_rules(dir.as_str());
+        names
+            .iter()
+            .map(|name| {
+                let path = dir.join(name.as_ref());
+                if path.parent() != Some(dir.as_str()) {
+                    return self.describe_rendering(&path, fs);
+                }
+                let typed_rule = self.typed_rule(dir.as_str(), self.entry_type(&path, fs));
+                let best_rule = |step: &[Stage]| {
+                    let rules = entry_rules
+                        .iter()
+                        .copied()
+                        .chain(typed_rule)
+                        .chain(self.glob_rules(&path));
+                    best_of(step, rules)
+                };
+                self.resolve(&path, fs, best_rule)?.render(&path, fs)
+            })
+            .collect()
+    }
+
+    /// Add a description to the descriptions map.
+    pub fn add_description<P: Into<DefPath>>(&mut self, path: P, desc: &str) {
+        let path = self.stored_key(self.key(path));
+        self.descriptions.insert(path, desc.to_string());
+    }
+
+    /// Add text to the end of the description of the given path, separated
+    /// by a space, or add it as the path's description if it has none.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut d = def::Describer::new();
+    /// d.append_description("/srv", "Served files.");
+    /// d.append_description("/srv", "Backed up nightly.");
+    /// assert_eq!(
+    ///     d.describe("/srv"),
+    ///     Some("Served files. Backed up nightly.".to_string())
+    /// );
+    /// ```
+    pub fn append_description<P: Into<DefPath>>(&mut self, path: P, text: &str) {
+        let path = self.stored_key(self.key(path));
+        let desc = match self.descriptions.get(&path) {
+            Some(d) => format!("{} {}", d, text),
+            None => text.to_string(),
+        };
+        self.descriptions.insert(path, desc);
+    }
+
+    /// Add text to the start of the description of the given path, separated
+    /// by a space, or add it as the path's description if it has none.
+    pub fn prepend_description<P: Into<DefPath>>(&mut self, path: P, text: &str) {
+        let path = self.stored_key(self.key(path));
+        let desc = match self.descriptions.get(&path) {
+            Some(d) => format!("{} {}", text, d),
+            None => text.to_string(),
+        };
+        self.descriptions.insert(path, desc);
+    }
+
+    /// Add a pattern to the patterns map. An error is returned if the pattern
+    /// is invalid (see `Pattern::parse`).
+    pub fn add_pattern<P: Into<DefPath>>(
+        &mut self,
+        path: P,
+        desc: &str,
+    ) -> Result<(), PatternError> {
+        self.add_pattern_with_kind(path.into().as_str(), desc, PatternKind::Children)
+    }
+
+    /// Add a description for all paths matching the given glob to the globs
+    /// map. An error is returned if the glob is empty, or if the description
+    /// is an invalid pattern.
+    pub fn add_glob(&mut self, glob: &str, desc: &str) -> Result<(), PatternError> {
+        self.add_pattern_with_kind(glob, desc, PatternKind::Glob)
+    }
+
+    /// Add a pattern of the given kind to the corresponding patterns map, path
+    /// being a glob for globs. An error is returned if the pattern is
+    /// invalid, or if the glob is empty.
+    pub fn add_pattern_with_kind(
+        &mut self,
+        path: &str,
+        desc: &str,
+        kind: PatternKind,
+    ) -> Result<(), PatternError> {
+        if kind == PatternKind::Glob && path.is_empty() {
+            return Err(PatternError::EmptyGlob);
+        }
+        let pattern = Pattern::parse_with(desc, self.syntax.placeholder)?;
+        let path = self.syntax.to_key_separators(path);
+        match kind {
+            PatternKind::Children => self.patterns.insert(path.as_ref().into(), pattern),
+            PatternKind::Recursive => self
+                .recursive_patterns
+                .insert(path.as_ref().into(), pattern),
+            PatternKind::Glob => self.globs.insert(paths::glob_key(&path), pattern),
+        };
+        Ok(())
+    }
+
+    /// Add a pattern describing children of the given path of one type only,
+    /// which wins over the path's untyped pattern, see `Describer`. Each
+    /// type can have a pattern of its own. An error is returned if the
+    /// pattern is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use def::meta::FileKind;
+    ///
+    /// let mut d = def::Describer::new();
+    /// d.add_typed_pattern("/downloads", "* is a downloaded file.", FileKind::File)
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     d.describe_as("/downloads/def.tar", FileKind::File),
+    ///     Some("def.tar is a downloaded file.".to_string())
+    /// );
+    /// assert_eq!(d.describe_as("/downloads/def", FileKind::Directory), None);
+    /// assert_eq!(d.describe("/downloads/def.tar"), None);
+    /// ```
+    pub fn add_typed_pattern<P: Into<DefPath>>(
+        &mut self,
+        path: P,
+        desc: &str,
+        kind: FileKind,
+    ) -> Result<(), PatternError> {
+        let pattern = Pattern::parse_with(desc, self.syntax.placeholder)?;
+        self.insert_typed_pattern(self.key(path), kind, pattern);
+        Ok(())
+    }
+
+    /// Map the typed pattern of path applying to paths of type kind to
+    /// pattern, keeping patterns of other types.
+    fn insert_typed_pattern(&mut self, path: DefPath, kind: FileKind, pattern: Pattern) {
+        match self.typed_patterns.get_mut(&path) {
+            Some(patterns) => {
+                patterns.insert(kind, pattern);
+            }
+            None => {
+                self.typed_patterns
+                    .insert(path, BTreeMap::from([(kind, pattern)]));
+            }
+        }
+    }
+
+    /// Return typed patterns of the given path, see `add_typed_pattern`,
+    /// along with the types of paths they apply to.
+    pub fn typed_patterns_of<P: Into<DefPath>>(&self, path: P) -> Vec<(FileKind, &str)> {
+        self.typed_patterns
+            .get(&self.key(path))
+            .into_iter()
+            .flatten()
+            .map(|(kind, pattern)| (*kind, pattern.as_str()))
+            .collect()
+    }
+
+    /// Remove the typed pattern of the given path applying to paths of the
+    /// given type. Return false if there's none.
+    pub fn remove_typed_pattern(&mut self, path: &str, kind: FileKind) -> bool {
+        let path = self.key(path);
+        let path = path.as_str();
+        let patterns = match self.typed_patterns.get_mut(path) {
+            Some(patterns) => patterns,
+            None => return false,
+        };
+        let removed = patterns.remove(&kind).is_some();
+        if patterns.is_empty() {
+            self.typed_patterns.remove(path);
+        }
+        if !self.has_pattern(path) {
+            self.priorities.remove(path);
+        }
+        removed
+    }
+
+    /// Set the priority of patterns (of all kinds) mapped to the given key,
+    /// a path or a glob. Priorities decide between patterns applying to a
+    /// path that are mapped to equally long prefixes of it, higher ones
+    /// winning, see `explain`. Patterns have priority 0 unless set. Return
+    /// false, setting nothing, if no pattern is mapped to the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut d = def::Describer::new();
+    /// d.add_pattern("/srv", "* is served.").unwrap();
+    /// d.add_glob("/srv/*.log", "A log.").unwrap();
+    /// assert_eq!(d.describe("/srv/access.log"), Some("A log.".to_string()));
+    /// assert!(d.set_priority("/srv", 10));
+    /// assert_eq!(d.priority("/srv"), 10);
+    /// assert_eq!(
+    ///     d.describe("/srv/access.log"),
+    ///     Some("access.log is served.".to_string())
+    /// );
+    /// assert!(!d.set_priority("/tmp", 10));
+    /// ```
+    pub fn set_priority(&mut self, key: &str, priority: i32) -> bool {
+        let key = match self.pattern_key(key) {
+            Some(key) => key,
+            None => return false,
+        };
+        if priority == 0 {
+            self.priorities.remove(&key);
+        } else {
+            self.priorities.insert(key, priority);
+        }
+        true
+    }
+
+    /// Return the priority of patterns mapped to the given key, see
+    /// `set_priority`.
+    pub fn priority(&self, key: &str) -> i32 {
+        self.pattern_key(key)
+            .and_then(|key| self.priorities.get(&key).copied())
+            .unwrap_or_default()
+    }
+
+    /// Set the order descriptions and patterns are tried in when describing
+    /// paths, see `def::resolution`. The order isn't saved with the config,
+    /// and is kept when other describers are merged into this one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use def::resolution::{ResolutionOrder, Stage};
+    ///
+    /// let mut d = def::Describer::new();
+    /// d.add_pattern("/src", "Source of {stem}.").unwrap();
+    /// d.add_glob("*.rs", "Rust code.").unwrap();
+    /// assert_eq!(d.describe("/src/main.rs"), Some("Source of main.".to_string()));
+    ///
+    /// let order = ResolutionOrder::new(vec![
+    ///     vec![Stage::Description],
+    ///     vec![Stage::Glob],
+    ///     vec![Stage::Pattern, Stage::Recursive],
+    /// ])
+    /// .unwrap();
+    /// d.set_resolution_order(order);
+    /// assert_eq!(d.describe("/src/main.rs"), Some("Rust code.".to_string()));
+    /// ```
+    pub fn set_resolution_order(&mut self, order: ResolutionOrder) {
+        self.resolution = order;
+    }
+
+    /// Return the order descriptions and patterns are tried in, see
+    /// `set_resolution_order`.
+    pub fn resolution_order(&self) -> &ResolutionOrder {
+        &self.resolution
+    }
+
+    /// Match paths against keys of descriptions and patterns with options,
+    /// such as ignoring case (see `def::matching`). Keys that match each
+    /// other with options are merged, one of them winning, so they should
+    /// be set before descriptions are added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use def::matching::MatchOptions;
+    ///
+    /// let mut d = def::Describer::new();
+    /// d.add_description("/Users/me/Downloads", "Downloaded files.");
+    /// d.add_pattern("/Users/me/Downloads", "Downloaded {name}.").unwrap();
+    /// assert_eq!(d.describe("/users/me/downloads"), None);
+    ///
+    /// d.set_match_options(MatchOptions {
+    ///     case_insensitive: true,
+    ///     ..MatchOptions::default()
+    /// });
+    /// assert_eq!(
+    ///     d.describe("/users/me/downloads"),
+    ///     Some("Downloaded files.".to_string())
+    /// );
+    /// assert_eq!(
+    ///     d.describe("/users/me/downloads/def.tar"),
+    ///     Some("Downloaded def.tar.".to_string())
+    /// );
+    /// ```
+    pub fn set_match_options(&mut self, options: MatchOptions) {
+        self.matching = options;
+        self.descriptions.set_match_options(options);
+        self.patterns.set_match_options(options);
+        self.recursive_patterns.set_match_options(options);
+        self.typed_patterns.set_match_options(options);
+    }
+
+    /// Return the options paths are matched with, see `set_match_options`.
+    pub fn match_options(&self) -> MatchOptions {
+        self.matching
+    }
+
+    /// Write patterns and paths with the characters of syntax, such as `%`
+    /// rather than `*` as the placeholder of names (see `def::syntax`).
+    /// Patterns already added are parsed again with the new placeholder.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use def::syntax::Syntax;
+    ///
+    /// let mut d = def::Describer::new();
+    /// d.set_syntax(Syntax {
+    ///     placeholder: '%',
+    ///     separator: '\\',
+    /// });
+    /// d.add_pattern(r"C:\src", "% matches *.rs.").unwrap();
+    /// assert_eq!(
+    ///     d.describe(r"C:\src\lib"),
+    ///     Some("lib matches *.rs.".to_string())
+    /// );
+    /// assert_eq!(d.describe("C:/src/lib"), Some("lib matches *.rs.".to_string()));
+    /// ```
+    pub fn set_syntax(&mut self, syntax: Syntax) {
+        self.syntax = syntax;
+        let placeholder = syntax.placeholder;
+        let reparse = |p: &mut Pattern| *p = p.with_placeholder(placeholder).into_owned();
+        self.patterns.values_mut().for_each(reparse);
+        self.recursive_patterns.values_mut().for_each(reparse);
+        self.typed_patterns
+            .values_mut()
+            .flat_map(|patterns| patterns.values_mut())
+            .for_each(reparse);
+        self.globs.values_mut().for_each(reparse);
+    }
+
+    /// Return the characters patterns and paths are written with, see
+    /// `set_syntax`.
+    pub fn syntax(&self) -> Syntax {
+        self.syntax
+    }
+
+    /// Return path as a key, with the separator of the describer's syntax
+    /// replaced by `/`.
+    fn key<P: Into<DefPath>>(&self, path: P) -> DefPath {
+        let path = path.into();
+        match self.syntax.to_key_separators(path.as_str()) {
+            Cow::Borrowed(_) => path,
+            Cow::Owned(converted) => DefPath::new(&converted),
+        }
+    }
+
+    /// Return key as patterns mapped to it are keyed, or None if no pattern
+    /// is mapped to it.
+    fn pattern_key(&self, key: &str) -> Option<String> {
+        let key = self.syntax.to_key_separators(key);
+        let path = DefPath::new(&key);
+        let glob = paths::glob_key(&key);
+        if self.has_pattern(path.as_str()) {
+            Some(path.into())
+        } else if self.has_pattern(&glob) {
+            Some(glob)
+        } else {
+            None
+        }
+    }
+
+    /// Return true if a pattern (of any kind) is mapped to key.
+    fn has_pattern(&self, key: &str) -> bool {
+        self.patterns.contains_key(key)
+            || self.recursive_patterns.contains_key(key)
+            || self.typed_patterns.contains_key(key)
+            || self.globs.contains_key(key)
+    }
+
+    /// Return the specific description of the given path, if any. Unlike
+    /// `describe`, patterns and globs aren't used.
+    pub fn description<P: Into<DefPath>>(&self, path: P) -> Option<&str> {
+        self.descriptions.get(&self.key(path)).map(|d| d.as_str())
+    }
+
+    /// Remove the description of the given path, along with its recorded
+    /// metadata, tags, timestamps, privacy, and source. Return false if the
+    /// path has no description.
+    pub fn remove_description<P: Into<DefPath>>(&mut self, path: P) -> bool {
+        let path = self.stored_key(self.key(path));
+        self.metadata.remove(&path);
+        self.tags.remove(&path);
+        self.timestamps.remove(&path);
+        self.private.remove(&path);
+        self.sources.remove(&path);
+        self.descriptions.remove(&path).is_some()
+    }
+
+    /// Record metadata of a described path, so it can later be compared with
+    /// the path's current metadata (see `drift::report`).
+    pub fn record_metadata<P: Into<DefPath>>(&mut self, path: P, metadata: PathMetadata) {
+        let path = self.stored_key(self.key(path));
+        self.metadata.insert(path, metadata);
+    }
+
+    /// Return metadata recorded for the given path, if any.
+    pub fn metadata<P: Into<DefPath>>(&self, path: P) -> Option<&PathMetadata> {
+        self.metadata.get(&self.stored_key(self.key(path)))
+    }
+
+    /// Record that the description of the given path was added or changed at
+    /// time (in seconds since the Unix epoch), which becomes its updated
+    /// time, and its created time unless it has one. Return false, recording
+    /// nothing, if the path has no description.
+    pub fn stamp<P: Into<DefPath>>(&mut self, path: P, time: u64) -> bool {
+        let path = self.stored_key(self.key(path));
+        if !self.descriptions.contains_key(&path) {
+            return false;
+        }
+        self.timestamps
+            .entry(path)
+            .and_modify(|t| t.updated = time)
+            .or_insert(Timestamps {
+                created: time,
+                updated: time,
+            });
+        true
+    }
+
+    /// Return timestamps of the description of the given path, if they were
+    /// recorded.
+    pub fn timestamps<P: Into<DefPath>>(&self, path: P) -> Option<&Timestamps> {
+        self.timestamps.get(&self.stored_key(self.key(path)))
+    }
+
+    /// Add tags to the description of the given path. Return false, adding
+    /// nothing, if the path has no description.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut d = def::Describer::new();
+    /// d.add_description("/home/user/work", "Work projects.");
+    /// assert!(d.add_tags("/home/user/work", vec!["work", "projects"]));
+    /// assert_eq!(d.tags_of("/home/user/work"), vec!["projects", "work"]);
+    /// assert!(!d.add_tags("/tmp", vec!["work"]));
+    /// ```
+    pub fn add_tags<P, I, S>(&mut self, path: P, tags: I) -> bool
+    where
+        P: Into<DefPath>,
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let path = self.stored_key(self.key(path));
+        if !self.descriptions.contains_key(&path) {
+            return false;
+        }
+        let mut tags = tags.into_iter().map(Into::into).peekable();
+        if tags.peek().is_some() {
+            self.tags.entry(path).or_default().extend(tags);
+        }
+        true
+    }
+
+    /// Return tags of the given path, sorted.
+    pub fn tags_of<P: Into<DefPath>>(&self, path: P) -> Vec<&str> {
+        self.tags
+            .get(&self.stored_key(self.key(path)))
+            .map(|tags| tags.iter().map(|t| t.as_str()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Mark the description of the given path as private, or as shareable if
+    /// private is false. Return false, marking nothing, if the path has no
+    /// description.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut d = def::Describer::new();
+    /// d.add_description("/home/user/notes", "Personal notes.");
+    /// assert!(d.set_private("/home/user/notes", true));
+    /// assert!(d.is_private("/home/user/notes"));
+    /// assert!(!d.set_private("/tmp", true));
+    /// ```
+    pub fn set_private<P: Into<DefPath>>(&mut self, path: P, private: bool) -> bool {
+        let path = self.stored_key(self.key(path));
+        if !self.descriptions.contains_key(&path) {
+            return false;
+        }
+        if private {
+            self.private.insert(path);
+        } else {
+            self.private.remove(&path);
+        }
+        true
+    }
+
+    /// Return true if the description of the given path is private.
+    pub fn is_private<P: Into<DefPath>>(&self, path: P) -> bool {
+        self.private.contains(&self.stored_key(self.key(path)))
+    }
+
+    /// Set the source of the description of the given path, such as the
+    /// command it's the output of (see `def::capture`). Return false, setting
+    /// nothing, if the path has no description.
+    ///
+    /// ```
+    /// use def::capture::Source;
+    ///
+    /// let mut d = def::Describer::new();
+    /// d.add_description("/repo", "Fix typo.");
+    /// let command = Source::Command {
+    ///     command: "git log -1 --format=%s".to_string(),
+    /// };
+    /// assert!(d.set_source("/repo", command.clone()));
+    /// assert_eq!(d.source("/repo"), &command);
+    /// assert_eq!(d.source("/tmp"), &Source::Literal);
+    /// ```
+    pub fn set_source<P: Into<DefPath>>(&mut self, path: P, source: Source) -> bool {
+        let path = self.stored_key(self.key(path));
+        if !self.descriptions.contains_key(&path) {
+            return false;
+        }
+        match source {
+            Source::Literal => self.sources.remove(&path),
+            source => self.sources.insert(path, source),
+        };
+        true
+    }
+
+    /// Return the source of the description of the given path, which is
+    /// literal unless set otherwise.
+    pub fn source<P: Into<DefPath>>(&self, path: P) -> &Source {
+        self.sources
+            .get(&self.stored_key(self.key(path)))
+            .unwrap_or(&Source::Literal)
+    }
+
+    /// Return paths whose descriptions are the output of commands, along with
+    /// the commands, sorted by path.
+    pub fn commands(&self) -> Vec<(&str, &str)> {
+        let mut commands: Vec<(&str, &str)> = self
+            .sources
+            .iter()
+            .filter_map(|(path, source)| Some((path.as_str(), source.command()?)))
+            .collect();
+        commands.sort_unstable();
+        commands
+    }
+
+    /// Return true if any description is private.
+    pub fn has_private(&self) -> bool {
+        !self.private.is_empty()
+    }
+
+    /// Remove private descriptions, along with their metadata, tags, and
+    /// timestamps, so that the describer can be shared. Return the number of
+    /// removed descriptions.
+    pub fn remove_private(&mut self) -> usize {
+        let private = self.private.clone();
+        self.prune_with(|e| e.pattern.is_none() && private.contains(e.path))
+            .len()
+    }
+
+    /// Return descriptions tagged with tag, sorted by path.
+    pub fn find_by_tag(&self, tag: &str) -> Vec<Entry<'_>> {
+        let mut entries: Vec<Entry> = self
+            .tags
+            .iter()
+            .filter(|(_, tags)| tags.contains(tag))
+            .filter_map(|(path, _)| {
+                Some(Entry {
+                    path: path.as_str(),
+                    description: self.descriptions.get(path)?.as_str(),
+                    pattern: None,
+                })
+            })
+            .collect();
+        entries.sort_by(|a, b| a.path.cmp(b.path));
+        entries
+    }
+
+    /// Delete metadata recorded for paths that no longer have a description,
+    /// tags, timestamps, privacy, and sources of paths that have neither a
+    /// description nor a trashed one, and priorities of keys that have
+    /// neither a pattern nor a trashed one, which can be left behind when the
+    /// config is edited manually. Return the number of deleted records.
+    pub fn collect_garbage(&mut self) -> usize {
+        let count = self.records();
+        let descriptions = &self.descriptions;
+        let trash = &self.trash;
+        let kept = |path: &DefPath| {
+            descriptions.contains_key(path)
+                || trash
+                    .iter()
+                    .any(|e| e.pattern.is_none() && e.path == path.as_str())
+        };
+        self.metadata
+            .retain(|path, _| descriptions.contains_key(path));
+        self.tags.retain(|path, _| kept(path));
+        self.timestamps.retain(|path, _| kept(path));
+        self.private.retain(|path| kept(path));
+        self.sources.retain(|path, _| kept(path));
+
+        let patterns = [&self.patterns, &self.recursive_patterns];
+        let typed_patterns = &self.typed_patterns;
+        let globs = &self.globs;
+        self.priorities.retain(|key, _| {
+            patterns.iter().any(|p| p.contains_key(key))
+                || typed_patterns.contains_key(key)
+                || globs.contains_key(key)
+                || trash.iter().any(|e| e.pattern.is_some() && e.path == *key)
+        });
+        count - self.records()
+    }
+
+    /// Return the number of records kept beside descriptions and patterns.
+    fn records(&self) -> usize {
+        self.metadata.len()
+            + self.tags.len()
+            + self.timestamps.len()
+            + self.private.len()
+            + self.priorities.len()
+            + self.sources.len()
+    }
+
+    /// Remove descriptions and patterns (of all kinds) for which f returns
+    /// false, along with metadata, tags, and timestamps of removed
+    /// descriptions. Return the number of removed entries.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut d = def::Describer::new();
+    /// d.add_description("/etc", "Configuration.");
+    /// d.add_description("/tmp", "Temporary files.");
+    /// assert_eq!(d.retain_entries(|e| e.path.starts_with("/etc")), 1);
+    /// assert_eq!(d.describe("/tmp"), None);
+    /// ```
+    pub fn retain_entries<F: Fn(&Entry) -> bool>(&mut self, f: F) -> usize {
+        self.prune_with(|e| !f(e)).len()
+    }
+
+    /// Remove descriptions and patterns (of all kinds) for which predicate
+    /// returns true, along with metadata, tags, timestamps, privacy, and
+    /// sources of removed descriptions, and return what was removed, sorted as by
+    /// `entries_under`. Removed entries aren't moved to the trash.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut d = def::Describer::new();
+    /// d.add_description("/etc", "Configuration.");
+    /// d.add_description("/tmp", "Temporary files.");
+    /// let removed = d.prune_with(|e| e.description.contains("Temporary"));
+    /// assert_eq!(removed.len(), 1);
+    /// assert_eq!(removed[0].path, "/tmp");
+    /// ```
+    pub fn prune_with<F: FnMut(&Entry) -> bool>(&mut self, mut predicate: F) -> Vec<Removed> {
+        let pruned: Vec<(Option<PatternKind>, String)> = self
+            .entries_under("")
+            .into_iter()
+            .filter(|e| predicate(e))
+            .map(|e| (e.pattern, e.path.to_string()))
+            .collect();
+        pruned
+            .into_iter()
+            .map(|(kind, path)| {
+                if kind.is_none() {
+                    self.metadata.remove(path.as_str());
+                    self.tags.remove(path.as_str());
+                    self.timestamps.remove(path.as_str());
+                    self.private.remove(path.as_str());
+                    self.sources.remove(path.as_str());
+                }
+                let description = self.map_mut(kind).take(&path).unwrap_or_default();
+                if kind.is_some() && !self.has_pattern(&path) {
+                    self.priorities.remove(&path);
+                }
+                Removed {
+                    path,
+                    description,
+                    pattern: kind,
+                }
+            })
+            .collect()
+    }
+
+    /// Remove patterns (of all kinds, typed ones included) mapped to the
+    /// given path, along with their priority. Return false if no pattern is
+    /// mapped to the path.
+    pub fn remove_pattern(&mut self, path: &str) -> bool {
+        let path = self.syntax.to_key_separators(path);
+        let path = path.as_ref();
+        self.priorities.remove(path);
+        let children = self.patterns.remove(path).is_some();
+        let recursive = self.recursive_patterns.remove(path).is_some();
+        let typed = self.typed_patterns.remove(path).is_some();
+        let glob = self.globs.remove(path).is_some();
+        children || recursive || typed || glob
+    }
+
+    /// Return the map holding entries of the given kind (None for specific
+    /// descriptions).
+    fn map(&self, kind: Option<PatternKind>) -> &dyn TextMap {
+        match kind {
+            None => &self.descriptions,
+            Some(PatternKind::Children) => &self.patterns,
+            Some(PatternKind::Recursive) => &self.recursive_patterns,
+            Some(PatternKind::Glob) => &self.globs,
+        }
+    }
+
+    /// Return the map holding entries of the given kind (None for specific
+    /// descriptions) for modification.
+    fn map_mut(&mut self, kind: Option<PatternKind>) -> &mut dyn TextMap {
+        match kind {
+            None => &mut self.descriptions,
+            Some(PatternKind::Children) => &mut self.patterns,
+            Some(PatternKind::Recursive) => &mut self.recursive_patterns,
+            Some(PatternKind::Glob) => &mut self.globs,
+        }
+    }
+
+    /// Return all maps along with the kind of entries they hold.
+    fn maps(&self) -> [(Option<PatternKind>, &dyn TextMap); 4] {
+        [
+            (None, &self.descriptions),
+            (Some(PatternKind::Children), &self.patterns),
+            (Some(PatternKind::Recursive), &self.recursive_patterns),
+            (Some(PatternKind::Glob), &self.globs),
+        ]
+    }
+
+    /// Return all maps for modification.
+    fn maps_mut(&mut self) -> [&mut dyn TextMap; 4] {
+        [
+            &mut self.descriptions,
+            &mut self.patterns,
+            &mut self.recursive_patterns,
+            &mut self.globs,
+        ]
+    }
+
+    /// Return an iterator over all (path, description) pairs of specific
+    /// descriptions, in arbitrary order.
+    pub fn iter_descriptions(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.descendants("")
+    }
+
+    /// Return the closest ancestor of path with a specific description, along
+    /// with its description, or None if no ancestor is described. Patterns
+    /// aren't used, and the path itself isn't an ancestor.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut d = def::Describer::new();
+    /// d.add_description("/home/user", "Home.");
+    /// assert_eq!(
+    ///     d.nearest_ancestor_description("/home/user/notes/todo.md"),
+    ///     Some(("/home/user", "Home."))
+    /// );
+    /// assert_eq!(d.nearest_ancestor_description("/home/user"), None);
+    /// ```
+    pub fn nearest_ancestor_description<P: Into<DefPath>>(&self, path: P) -> Option<(&str, &str)> {
+        let path = self.key(path);
+        self.descriptions
+            .lineage(&path)
+            .into_iter()
+            .find(|(ancestor, _)| **ancestor != path)
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// Return the path of the description with the most components, or None
+    /// if there are no descriptions. Of paths equally deep, the first in
+    /// order is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut d = def::Describer::new();
+    /// d.add_description("/home/user/notes", "Notes.");
+    /// d.add_description("/srv/www", "Served files.");
+    /// assert_eq!(d.deepest_description(), Some("/home/user/notes"));
+    /// ```
+    pub fn deepest_description(&self) -> Option<&str> {
+        let depth = |path: &str| path.split(SEPERATOR).filter(|c| !c.is_empty()).count();
+        self.iter_descriptions()
+            .map(|(path, _)| path)
+            .min_by(|a, b| depth(b).cmp(&depth(a)).then(a.cmp(b)))
+    }
+
+    /// Return up to n paths of descriptions added last, latest first, along
+    /// with the times they were added (see `stamp`). Descriptions added
+    /// without being stamped aren't returned.
+    pub fn recently_added(&self, n: usize) -> Vec<(&str, u64)> {
+        let mut added: Vec<(&str, u64)> = self
+            .timestamps
+            .iter()
+            .filter(|(path, _)| self.descriptions.contains_key(*path))
+            .map(|(path, t)| (path.as_str(), t.created))
+            .collect();
+        added.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        added.truncate(n);
+        added
+    }
+
+    /// Return an iterator over (path, description) pairs of specific
+    /// descriptions of prefix and of paths under it (or of all paths if
+    /// prefix is empty). Directories come before their entries, and entries
+    /// of a directory are sorted by name.
+    pub fn descendants(&self, prefix: &str) -> impl Iterator<Item = (&str, &str)> {
+        self.descriptions
+            .descendants(self.key(prefix).as_str())
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// Return an iterator over all (path, pattern) pairs of patterns of the
+    /// given kind, in arbitrary order.
+    pub fn iter_patterns(&self, kind: PatternKind) -> impl Iterator<Item = (&str, &str)> {
+        self.map(Some(kind)).texts()
+    }
+
+    /// Return all descriptions and patterns mapped to prefix or to paths under
+    /// prefix (or all of them if prefix is empty), sorted by path. If multiple
+    /// entries are mapped to the same path, the description comes first, then
+    /// the pattern, the recursive pattern, and the glob.
+    pub fn entries_under(&self, prefix: &str) -> Vec<Entry<'_>> {
+        let prefix = self.key(prefix);
+        let mut entries: Vec<Entry> = self
+            .maps()
+            .iter()
+            .flat_map(|(kind, map)| {
+                map.texts_under(prefix.as_str())
+                    .map(move |(path, description)| Entry {
+                        path,
+                        description,
+                        pattern: *kind,
+                    })
+            })
+            .collect();
+        entries.sort_by(|a, b| a.path.cmp(b.path).then(a.pattern.cmp(&b.pattern)));
+        entries
+    }
+
+    /// Return a tree of the descriptions and patterns mapped to prefix or to
+    /// paths under prefix (or all of them if prefix is empty).
+    pub fn tree(&self, prefix: &str) -> virtual_tree::VirtualTree<'_> {
+        virtual_tree::VirtualTree::new(&self.entries_under(prefix))
+    }
+
+    /// Return descriptions and patterns whose path or text contains every word
+    /// of query, ignoring case, best matches first. See `search` module for
+    /// how matches are ranked.
+    pub fn search(&self, query: &str) -> Vec<search::Match<'_>> {
+        self.search_with(query, &search::SearchOptions::default())
+    }
+
+    /// Like `search`, but words of query also match paths and text containing
+    /// their characters in order, such as "dwnld" matching "Downloads", words
+    /// with the same stem, and words with a typo (see
+    /// `search::SearchOptions::fuzzy`).
+    pub fn search_fuzzy(&self, query: &str) -> Vec<search::Match<'_>> {
+        self.search_with(query, &search::SearchOptions::fuzzy())
+    }
+
+    /// Like `search`, but words of query match as set by options, such as
+    /// words with the same stem or with typos. See `search::SearchOptions`.
+    pub fn search_with(
+        &self,
+        query: &str,
+        options: &search::SearchOptions,
+    ) -> Vec<search::Match<'_>> {
+        search::search(&self.entries_under(""), query, options)
+    }
+
+    /// Like `search`, but uses an index of the describer's entries kept in
+    /// the file at cache (see `index::cached`), which is rebuilt when they
+    /// change. Matches closer to near (such as the current directory) rank
+    /// higher. See `index` module for how it differs from `search`.
+    pub fn search_indexed<P: AsRef<Path>>(
+        &self,
+        query: &str,
+        near: Option<&str>,
+        cache: P,
+    ) -> Vec<search::Match<'_>> {
+        let entries = self.entries_under("");
+        index::cached(cache, &entries).search(&entries, query, near)
+    }
+
+    /// Return the renames `rename` would perform, without changing anything.
+    /// See `plan_prefix_rename` for their order.
+    pub fn plan_rename(&self, old: &str, new: &str) -> Vec<Rename> {
+        let (old, new) = (
+            self.syntax.to_key_separators(old),
+            self.syntax.to_key_separators(new),
+        );
+        let (old, new) = (old.as_ref(), new.as_ref());
+        self.plan_renames(|key| exact_rename(key, old, new))
+    }
+
+    /// Return the renames `rename_prefix` would perform, without changing
+    /// anything. Renames of descriptions come first, then patterns, recursive
+    /// patterns, and globs, each sorted by the old key.
+    pub fn plan_prefix_rename(&self, old: &str, new: &str) -> Vec<Rename> {
+        let (old, new) = (
+            self.syntax.to_key_separators(old),
+            self.syntax.to_key_separators(new),
+        );
+        let (old, new) = (old.as_ref(), new.as_ref());
+        self.plan_renames(|key| paths::rebase(key, old, new))
+    }
+
+    /// Return renames of every key for which f returns a new key.
+    fn plan_renames<F: Fn(&str) -> Option<String>>(&self, f: F) -> Vec<Rename> {
+        let mut renames = Vec::new();
+        for (pattern, map) in self.maps().iter() {
+            for key in map.sorted_keys() {
+                if let Some(renamed) = f(key) {
+                    let overwrites = map.has(&renamed) && f(&renamed).is_none();
+                    renames.push(Rename {
+                        old: key.to_string(),
+                        new: renamed,
+                        pattern: *pattern,
+                        overwrites,
+                    });
+                }
+            }
+        }
+        renames
+    }
+
+    /// Rewrite the key old (of the description, patterns, metadata, and
+    /// trashed entries mapped to it) to new, leaving paths under old as they
+    /// are. Existing entries of new are overwritten by renamed ones. Return
+    /// the number of renamed descriptions and patterns.
+    pub fn rename(&mut self, old: &str, new: &str) -> usize {
+        let (old, new) = (
+            self.syntax.to_key_separators(old),
+            self.syntax.to_key_separators(new),
+        );
+        let (old, new) = (old.as_ref(), new.as_ref());
+        let renames = self.plan_rename(old, new);
+        self.apply_renames(&renames, |key| exact_rename(key, old, new))
+    }
+
+    /// Rewrite every key (of descriptions, patterns, and trashed entries) that
+    /// is old or is under old, replacing old with new. For example, renaming
+    /// "/old/root" to "/new" rewrites "/old/root/dir" to "/new/dir". Existing
+    /// entries are overwritten by renamed ones. Return the number of renamed
+    /// descriptions and patterns.
+    pub fn rename_prefix(&mut self, old: &str, new: &str) -> usize {
+        let (old, new) = (
+            self.syntax.to_key_separators(old),
+            self.syntax.to_key_separators(new),
+        );
+        let (old, new) = (old.as_ref(), new.as_ref());
+        let renames = self.plan_prefix_rename(old, new);
+        self.apply_renames(&renames, |key| paths::rebase(key, old, new))
+    }
+
+    /// Perform renames, and rename keys of metadata, tags, timestamps,
+    /// privacy, priorities, and trashed entries for which f returns a new
+    /// key. Return the number of renames.
+    fn apply_renames<F: Fn(&str) -> Option<String>>(&mut self, renames: &[Rename], f: F) -> usize {
+        // Remove all old keys before inserting new ones, so a renamed key is
+        // never overwritten by another key being renamed.
+        let mut moved = Vec::with_capacity(renames.len());
+        for r in renames.iter() {
+            moved.push(self.map_mut(r.pattern).take(&r.old).unwrap());
+        }
+        for (r, value) in renames.iter().zip(moved) {
+            self.map_mut(r.pattern).put(r.new.clone(), value);
+        }
+        let rename = |key: &str| f(key).unwrap_or_else(|| key.to_string());
+        rekey_trie(&mut self.typed_patterns, rename);
+        rekey(&mut self.metadata, rename);
+        rekey(&mut self.tags, rename);
+        rekey(&mut self.timestamps, rename);
+        rekey_set(&mut self.private, rename);
+        rekey(&mut self.priorities, rename);
+        rekey(&mut self.sources, rename);
+
+        for entry in self.trash.iter_mut() {
+            if let Some(renamed) = f(&entry.path) {
+                entry.path = renamed;
+            }
+        }
+        renames.len()
+    }
+
+    /// Move the description of the given path to the trash. Its recorded
+    /// metadata is deleted, but its tags and timestamps are kept, and apply
+    /// again if it's restored. Return false if the path has no description.
+    pub fn trash_description(&mut self, path: &str) -> bool {
+        let path = self.syntax.to_key_separators(path);
+        let path = path.as_ref();
+        self.metadata.remove(path);
+        self.move_to_trash(path, None)
+    }
+
+    /// Move patterns (of all kinds) mapped to the given path to the trash.
+    /// Their priority is kept, and applies again if they're restored. Return
+    /// false if no pattern is mapped to the path.
+    pub fn trash_pattern(&mut self, path: &str) -> bool {
+        let path = self.syntax.to_key_separators(path);
+        let path = path.as_ref();
+        let children = self.move_to_trash(path, Some(PatternKind::Children));
+        let recursive = self.move_to_trash(path, Some(PatternKind::Recursive));
+        let glob = self.move_to_trash(path, Some(PatternKind::Glob));
+        children || recursive || glob
+    }
+
+    /// Remove an entry of the given kind, and add it to the trash. Return false
+    /// if no such entry exists.
+    fn move_to_trash(&mut self, path: &str, pattern: Option<PatternKind>) -> bool {
+        match self.map_mut(pattern).take(path) {
+            Some(description) => {
+                self.trash.push(TrashedEntry {
+                    path: path.to_string(),
+                    description,
+                    pattern,
+                    trashed_at: trash::now(),
+                });
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Return all trashed entries, oldest first.
+    pub fn trashed(&self) -> &[TrashedEntry] {
+        &self.trash
+    }
+
+    /// Restore the most recently trashed entry of the given path, and return
+    /// it. An error is returned if nothing is trashed for the path, or if
+    /// restoring would overwrite an existing description or pattern.
+    pub fn restore(&mut self, path: &str) -> Result<TrashedEntry, TrashError> {
+        let path = self.syntax.to_key_separators(path);
+        let path = path.as_ref();
+        let i = match self.trash.iter().rposition(|e| e.path == path) {
+            Some(i) => i,
+            None => return Err(TrashError::NotTrashed(path.to_string())),
+        };
+
+        let kind = self.trash[i].pattern;
+        if self.map(kind).has(path) {
+            return Err(TrashError::Occupied(path.to_string()));
+        }
+
+        let entry = self.trash.remove(i);
+        self.map_mut(kind)
+            .put(entry.path.clone(), entry.description.clone());
+        Ok(entry)
+    }
+
+    /// Delete all trashed entries for good. Return the number of deleted
+    /// entries.
+    pub fn empty_trash(&mut self) -> usize {
+        let count = self.trash.len();
+        self.trash.clear();
+        count
+    }
+
+    /// Delete trashed entries that were trashed more than max_age ago. Return
+    /// the number of deleted entries.
+    pub fn expire_trash(&mut self, max_age: Duration) -> usize {
+        let deadline = trash::now().saturating_sub(max_age.as_secs());
+        let count = self.trash.len();
+        self.trash.retain(|e| e.trashed_at >= deadline);
+        count - self.trash.len()
+    }
+
+    /// Expand variables (such as `$HOME` or `${PROJECTS}`) in the keys of
+    /// descriptions and patterns maps. See `vars::expand` for details.
+    ///
+    /// A key expanding to a key of the same map (such as `${HOME}/x` when
+    /// `/home/user/x` or `$HOME/x` is described too) is kept as written in
+    /// every map, so that no entry replaces another. Such keys are returned
+    /// along with their expansions.
+    ///
+    /// # Arguments
+    ///
+    /// * `lookup` - Returns the value of a variable given its name, or None if
+    ///   the variable isn't defined.
+    pub fn expand_keys<F: Fn(&str) -> Option<String>>(
+        &mut self,
+        lookup: F,
+    ) -> Vec<(String, String)> {
+        let typed: Vec<&str> = self
+            .typed_patterns
+            .iter()
+            .map(|(k, _)| k.as_str())
+            .collect();
+        let mut keys: Vec<Vec<&str>> = self.maps().iter().map(|(_, m)| m.sorted_keys()).collect();
+        keys.push(typed);
+
+        let mut collisions = BTreeMap::new();
+        for keys in keys.iter_mut() {
+            keys.sort_unstable();
+            let mut taken: HashSet<String> = keys.iter().map(|k| k.to_string()).collect();
+            for key in keys.iter() {
+                let expanded = vars::expand(key, &lookup);
+                if expanded != *key && !taken.insert(expanded.clone()) {
+                    collisions.insert(key.to_string(), expanded);
+                }
+            }
+        }
+        self.map_keys(|key| {
+            if collisions.contains_key(key) {
+                key.to_string()
+            } else {
+                vars::expand(key, &lookup)
+            }
+        });
+        collisions.into_iter().collect()
+    }
+
+    /// Return the key path is written as in this describer: path itself if
+    /// it's the key of a description, a pattern, or a trashed entry, or
+    /// otherwise the first such key (in sorted order) that variables expand
+    /// to path, if any. Commands changing the config use it so that changes
+    /// to entries such as `$HOME/x` keep them written that way.
+    ///
+    /// # Arguments
+    ///
+    /// * `lookup` - Returns the value of a variable given its name, or None if
+    ///   the variable isn't defined.
+    pub fn written_key<F: Fn(&str) -> Option<String>>(&self, path: &str, lookup: F) -> String {
+        let path = self.syntax.to_key_separators(path);
+        let path = path.as_ref();
+        let mut keys: BTreeSet<&str> = self
+            .maps()
+            .iter()
+            .flat_map(|(_, m)| m.sorted_keys())
+            .chain(self.typed_patterns.iter().map(|(k, _)| k.as_str()))
+            .chain(self.trash.iter().map(|e| e.path.as_str()))
+            .collect();
+        if keys.contains(path) {
+            return path.to_string();
+        }
+        keys.retain(|key| vars::expand(key, &lookup) == path);
+        keys.first().unwrap_or(&path).to_string()
+    }
+
+    /// Replace every key (of descriptions, patterns, metadata, tags,
+    /// timestamps, privacy, priorities, and sources) with the result of f.
+    pub(crate) fn map_keys<F: Fn(&str) -> String>(&mut self, f: F) {
+        for map in self.maps_mut() {
+            map.map_keys(&f);
+        }
+        rekey_trie(&mut self.typed_patterns, &f);
+        rekey(&mut self.metadata, &f);
+        rekey(&mut self.tags, &f);
+        rekey(&mut self.timestamps, &f);
+        rekey_set(&mut self.private, &f);
+        rekey(&mut self.priorities, &f);
+        rekey(&mut self.sources, &f);
+    }
+
+    /// Add all descriptions, patterns (typed ones included), metadata, tags,
+    /// timestamps, privacy, priorities, and sources of other to this
+    /// describer, replacing entries mapped to the same keys. Trashed entries
+    /// of other are ignored.
+    pub fn merge(&mut self, other: Describer) {
+        self.descriptions.extend(other.descriptions);
+        self.patterns.extend(other.patterns);
+        self.recursive_patterns.extend(other.recursive_patterns);
+        for (path, patterns) in other.typed_patterns {
+            for (kind, pattern) in patterns {
+                self.insert_typed_pattern(path.clone(), kind, pattern);
+            }
+        }
+        self.globs.extend(other.globs);
+        self.metadata.extend(other.metadata);
+        self.tags.extend(other.tags);
+        self.timestamps.extend(other.timestamps);
+        self.private.extend(other.private);
+        self.priorities.extend(other.priorities);
+        self.sources.extend(other.sources);
+    }
+
+    /// Add descriptions, patterns, and metadata of other to this describer,
+    /// and return a report of what changed. Entries conflicting with existing
+    /// ones (mapped to the same key, but with different text) are merged
+    /// according to strategy, see `merge` module. Metadata of other is used
+    /// for added and updated descriptions, and for paths without metadata.
+    /// Timestamps, privacy, and sources of other are used for added and
+    /// updated descriptions too, and priorities for added and updated
+    /// patterns. Tags of other are added to those of described paths, and
+    /// typed patterns to those of types without one. Like `merge`, trashed
+    /// entries of other are ignored, and patterns aren't validated (see
+    /// `validate_patterns`).
+    pub fn merge_with(&mut self, other: Describer, mut strategy: Strategy) -> MergeReport {
+        let mut report = MergeReport::default();
+        for (kind, map) in other.maps().iter() {
+            let mut texts: Vec<(&str, &str)> = map.texts().collect();
+            texts.sort_unstable();
+            for (key, text) in texts {
+                let existing = self.map(*kind).text(key);
+                if existing == Some(text) {
+                    continue;
+                }
+                let change = Change {
+                    path: key.to_string(),
+                    pattern: *kind,
+                    existing: existing.map(String::from),
+                    incoming: text.to_string(),
+                };
+                if change.existing.is_some() && !strategy.takes_incoming(&change) {
+                    report.conflicts.push(change);
+                    continue;
+                }
+                self.map_mut(*kind).put(key.to_string(), text.to_string());
+                if change.existing.is_some() {
+                    report.updated.push(change);
+                } else {
+                    report.added.push(change);
+                }
+            }
+        }
+
+        let taken = |path: &DefPath| {
+            report
+                .added
+                .iter()
+                .chain(report.updated.iter())
+                .any(|c| c.pattern.is_none() && c.path == path.as_str())
+        };
+        for (path, metadata) in other.metadata {
+            if taken(&path) || !self.metadata.contains_key(&path) {
+                self.metadata.insert(path, metadata);
+            }
+        }
+        for (path, timestamps) in other.timestamps {
+            if taken(&path) {
+                self.timestamps.insert(path, timestamps);
+            }
+        }
+        for path in other.private {
+            if taken(&path) {
+                self.private.insert(path);
+            }
+        }
+        for (path, source) in other.sources {
+            if taken(&path) {
+                self.sources.insert(path, source);
+            }
+        }
+        for (key, priority) in other.priorities {
+            let taken = report
+                .added
+                .iter()
+                .chain(report.updated.iter())
+                .any(|c| c.pattern.is_some() && c.path == key);
+            if taken {
+                self.priorities.insert(key, priority);
+            }
+        }
+        for (path, tags) in other.tags {
+            self.add_tags(path, tags);
+        }
+        for (path, patterns) in other.typed_patterns {
+            for (kind, pattern) in patterns {
+                let exists = self
+                    .typed_patterns
+                    .get(&path)
+                    .is_some_and(|p| p.contains_key(&kind));
+                if !exists {
+                    self.insert_typed_pattern(path.clone(), kind, pattern);
+                }
+            }
+        }
+        report
+    }
+
+    /// Return warnings about keys that are likely incompatible with this
+    /// platform, such as keys containing backslashes or drive letters, or
+    /// keys that only differ in case. Descriptions and each kind of patterns
+    /// are checked separately.
+    pub fn validate_keys(&self) -> Vec<paths::KeyWarning> {
+        let mut warnings = Vec::new();
+        for (_, map) in self.maps().iter() {
+            let keys = map.sorted_keys();
+            for key in keys.iter() {
+                warnings.extend(paths::check(key));
+            }
+            warnings.extend(paths::check_case_collisions(keys));
+        }
+        warnings
+    }
+
+    /// Return errors of patterns (of all kinds) that are invalid, along with
+    /// their kind and the key they're mapped to, sorted by kind and key.
+    /// Patterns are validated when added, but not when loaded, so patterns
+    /// of a config that was edited by hand may be invalid.
+    pub fn validate_patterns(&self) -> Vec<(PatternKind, String, PatternError)> {
+        let mut errors = Vec::new();
+        for kind in [
+            PatternKind::Children,
+            PatternKind::Recursive,
+            PatternKind::Glob,
+        ]
+        .iter()
+        {
+            let mut texts: Vec<(&str, &str)> = self.map(Some(*kind)).texts().collect();
+            texts.sort_unstable();
+            for (key, text) in texts {
+                if let Err(e) = Pattern::parse(text) {
+                    errors.push((*kind, key.to_string(), e));
+                }
+            }
+        }
+        errors
+    }
+
+    /// Convert all keys to the local convention (see `paths::to_local`).
+    /// Keys whose converted form already exists, such as keys differing
+    /// only in their drive letters, are left untouched, and returned as
+    /// collisions along with the converted keys.
+    pub fn convert_keys(&mut self) -> Conversion {
+        let mut conversion = Conversion::default();
+        for map in self.maps_mut() {
+            let keys: Vec<String> = map.sorted_keys().into_iter().map(String::from).collect();
+            for key in keys {
+                let local = paths::to_local(&key);
+                if local == key {
+                    continue;
+                }
+                if map.has(&local) {
+                    conversion.collisions.push((key, local));
+                } else {
+                    map.rename(&key, local.clone());
+                    conversion.converted.push((key, local));
+                }
+            }
+        }
+        for (old, new) in conversion.converted.iter() {
+            if let Some(m) = self.metadata.remove(old.as_str()) {
+                self.metadata.insert(new.into(), m);
+            }
+            if let Some(t) = self.tags.remove(old.as_str()) {
+                self.tags.insert(new.into(), t);
+            }
+            if let Some(t) = self.timestamps.remove(old.as_str()) {
+                self.timestamps.insert(new.into(), t);
+            }
+            if self.private.remove(old.as_str()) {
+                self.private.insert(new.into());
+            }
+            if let Some(p) = self.priorities.remove(old) {
+                self.priorities.insert(new.clone(), p);
+            }
+            if let Some(s) = self.sources.remove(old.as_str()) {
+                self.sources.insert(new.into(), s);
+            }
+        }
+        conversion
+    }
+
+    /// Return an immutable snapshot of this describer, which later changes
+    /// to it don't affect (see `DescriberSnapshot`).
+    pub fn snapshot(&self) -> DescriberSnapshot {
+        DescriberSnapshot::new(self.clone())
+    }
+
+    /// Return a string JSON representation of this Describer. This is
+    /// subsequently written to a file to be re-loaded on next run.
+    ///
+    /// # Arguments
+    ///
+    /// * `pretty` - If true, return a "pretty" JSON string.
+    pub fn to_json(&self, pretty: bool) -> Result<String, serde_json::Error> {
+        if pretty {
+            serde_json::to_string_pretty(self)
+        } else {
+            serde_json::to_string(self)
+        }
+    }
+}
+
+impl Default for Describer {
+    fn default() -> Describer {
+        Describer::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::meta::FileKind;
+    use crate::template::Placeholder;
+
+    #[test]
+    fn new_describe_test() {
+        let mut descriptions: HashMap<String, String> = HashMap::new();
+        let mut patterns: HashMap<String, String> = HashMap::new();
+        for (path, desc, is_pattern) in [
+            ("/path/to/dir", "This is /path/to/dir.", false),
+            ("/another/dir", "This is /another/dir.", false),
+            ("/yet/another/path", "This is /yet/another/path.", false),
+            ("/path/to/dir", "* is in /path/to/dir.", true),
+            ("/yet/another/path", "* is in /yet/another/path.", true),
+            ("/obvious", "* is *", true),
+            ("/yet/another", "* is in /yet/another/path.", true),
+        ]
+        .iter()
+        {
+            if *is_pattern {
+                patterns.insert(path.to_string(), desc.to_string());
+            } else {
+                descriptions.insert(path.to_string(), desc.to_string());
+            }
+        }
+
+        describe_tester(&Describer::new_with(descriptions, patterns));
+    }
+
+    #[test]
+    fn new_from_json_describe_test() {
+        match Describer::new_from_json(
+            "
+	    {
+                \"descriptions\": {
+                        \"/path/to/dir\": \"This is /path/to/dir.\",
+                        \"/another/dir\": \"This is /another/dir.\",
+                        \"/yet/another/path\": \"This is /yet/another/path.\"
+		},
+                \"patterns\": {
+                        \"/path/to/dir\": \"* is in /path/to/dir.\",
+                        \"/yet/another/path\": \"* is in /yet/another/path.\",
+                        \"/obvious\": \"* is *\",
+                        \"/yet/another\": \"* is in /yet/another/path.\"
+                }
+            }",
+        ) {
+            Ok(d) => describe_tester(&d),
+            Err(e) => panic!("{}", e),
+        };
+    }
+
+    #[test]
+    fn add_test() {
+        let mut d = Describer::new();
+        d.add_description("path/to/directory", "This is an empty directory.");
+        d.add_pattern("parent/directory", "* is a child of parent/directory.")
+            .unwrap();
+        assert_eq!(
+            d.to_json(false).unwrap(),
+            format!(
+                "{}{}{}{}{}",
+                "{\"version\":2,",
+                "\"descriptions\":",
+                "{\"path/to/directory\":\"This is an empty directory.\"},",
+                "\"patterns\":",
+                "{\"parent/directory\":\"* is a child of parent/directory.\"}}"
+            )
+        );
+    }
+
+    #[test]
+    fn from_versioned_json_test() {
+        let unversioned = concat!(
+            "{\"descriptions\":{\"/dir\":\"A dir.\"},\"patterns\":{},",
+            "\"trash\":[{\"path\":\"/p\",\"description\":\"* in p.\",",
+            "\"pattern\":true,\"trashed_at\":1}]}"
+        );
+        let mut d = Describer::from_versioned_json(unversioned).unwrap();
+        assert_eq!(d.version, migrate::CURRENT_VERSION);
+        assert_eq!(d.describe("/dir"), Some("A dir.".to_string()));
+        assert_eq!(d.trashed()[0].pattern, Some(PatternKind::Children));
+        assert!(d.restore("/p").is_ok());
+        assert_eq!(d.describe("/p/x"), Some("x in p.".to_string()));
+
+        let current = d.to_json(false).unwrap();
+        let (_, from) =
+            Describer::from_versioned_value(serde_json::from_str(&current).unwrap()).unwrap();
+        assert_eq!(from, migrate::CURRENT_VERSION);
+
+        assert_eq!(
+            Describer::from_versioned_json("{\"version\":100}").err(),
+            Some(MigrationError::Unsupported(100))
+        );
+    }
+
+    #[test]
+    fn expand_keys_test() {
+        let mut d = Describer::new();
+        d.add_description("$HOME/dir", "This is a dir in home.");
+        d.add_description("/absolute/dir", "This is an absolute dir.");
+        d.add_pattern("${PROJECTS}", "* is a project.").unwrap();
+        d.expand_keys(|name| match name {
+            "HOME" => Some("/home/user".to_string()),
+            "PROJECTS" => Some("/home/user/projects".to_string()),
+            _ => None,
+        });
+
+        assert_eq!(
+            d.describe("/home/user/dir"),
+            Some("This is a dir in home.".to_string())
+        );
+        assert_eq!(
+            d.describe("/absolute/dir"),
+            Some("This is an absolute dir.".to_string())
+        );
+        assert_eq!(
+            d.describe("/home/user/projects/def"),
+            Some("def is a project.".to_string())
+        );
+        assert_eq!(d.describe("$HOME/dir"), None);
+
+        let mut d = Describer::new();
+        d.add_description("$HOME/x", "Through a variable.");
+        d.add_description("/home/user/x", "Written out.");
+        d.add_description("${HOME}/y", "First y.");
+        d.add_description("$HOME/y", "Second y.");
+        d.add_pattern("$HOME/x", "* is in x.").unwrap();
+        let collisions = d.expand_keys(|name| match name {
+            "HOME" => Some("/home/user".to_string()),
+            _ => None,
+        });
+
+        assert_eq!(
+            collisions,
+            vec![
+                ("$HOME/x".to_string(), "/home/user/x".to_string()),
+                ("${HOME}/y".to_string(), "/home/user/y".to_string()),
+            ]
+        );
+        assert_eq!(d.describe("/home/user/x"), Some("Written out.".to_string()));
+        assert_eq!(d.description("$HOME/x"), Some("Through a variable."));
+        assert_eq!(d.describe("/home/user/y"), Some("Second y.".to_string()));
+        assert_eq!(d.description("${HOME}/y"), Some("First y."));
+    }
+
+    #[test]
+    fn written_key_test() {
+        let lookup = |name: &str| match name {
+            "HOME" => Some("/home/user".to_string()),
+            _ => None,
+        };
+        let mut d = Describer::new();
+        d.add_description("$HOME/a", "A.");
+        d.add_description("/home/user/b", "Written out.");
+        d.add_description("$HOME/b", "Through a variable.");
+        d.add_pattern("${HOME}/c", "* is in c.").unwrap();
+
+        assert_eq!(d.written_key("/home/user/a", lookup), "$HOME/a");
+        assert_eq!(d.written_key("/home/user/b", lookup), "/home/user/b");
+        assert_eq!(d.written_key("/home/user/c", lookup), "${HOME}/c");
+        assert_eq!(d.written_key("/home/user/d", lookup), "/home/user/d");
+
+        let key = d.written_key("/home/user/a", lookup);
+        d.add_description(key.as_str(), "Changed.");
+        assert_eq!(d.description("$HOME/a"), Some("Changed."));
+        assert_eq!(d.description("/home/user/a"), None);
+
+        let key = d.written_key("/home/user/a", lookup);
+        assert_eq!(d.rename(&key, "$HOME/renamed"), 1);
+        assert_eq!(d.description("$HOME/renamed"), Some("Changed."));
+
+        let key = d.written_key("/home/user/renamed", lookup);
+        assert!(d.trash_description(&key));
+        assert_eq!(d.written_key("/home/user/renamed", lookup), "$HOME/renamed");
+        assert!(d
+            .restore(&d.written_key("/home/user/renamed", lookup))
+            .is_ok());
+        assert_eq!(d.description("$HOME/renamed"), Some("Changed."));
+    }
+
+    #[test]
+    fn validate_and_convert_keys_test() {
+        let mut d = Describer::new();
+        d.add_description("C:\\Users\\user", "Windows home.");
+        d.add_description("/home/user/Dir", "Upper dir.");
+        d.add_description("/home/user/dir", "Lower dir.");
+        d.add_pattern("/Users\\user", "* is in home.").unwrap();
+        d.add_pattern("/Users/user", "* is already local.").unwrap();
+
+        assert_eq!(
+            d.validate_keys(),
+            vec![
+                paths::KeyWarning::DriveLetter("C:\\Users\\user".to_string()),
+                paths::KeyWarning::Backslashes("C:\\Users\\user".to_string()),
+                paths::KeyWarning::CaseCollision(
+                    "/home/user/Dir".to_string(),
+                    "/home/user/dir".to_string()
+                ),
+                paths::KeyWarning::Backslashes("/Users\\user".to_string()),
+            ]
+        );
+        assert_eq!(
+            d.convert_keys(),
+            Conversion {
+                converted: vec![("C:\\Users\\user".to_string(), "/Users/user".to_string())],
+                collisions: vec![("/Users\\user".to_string(), "/Users/user".to_string())],
+            }
+        );
+        assert_eq!(d.describe("/Users/user"), Some("Windows home.".to_string()));
+        assert_eq!(
+            d.describe("/Users/user/x"),
+            Some("x is already local.".to_string())
+        );
+
+        let mut d = Describer::new();
+        d.add_description("C:\\data", "Data on C.");
+        d.add_description("D:\\data", "Data on D.");
+        let conversion = d.convert_keys();
+        if cfg!(windows) {
+            assert!(conversion.collisions.is_empty());
+        } else {
+            assert_eq!(
+                conversion.collisions,
+                vec![("D:\\data".to_string(), "/data".to_string())]
+            );
+            assert_eq!(d.describe("/data"), Some("Data on C.".to_string()));
+            assert_eq!(d.description("D:\\data"), Some("Data on D."));
+        }
+    }
+
+    #[test]
+    fn validate_patterns_test() {
+        let d = Describer::from_versioned_json(
+            "{\"descriptions\":{\"/a\":\"{owner} is fine here.\"},
+              \"patterns\":{\"/b\":\"* is {owner}.\",\"/c\":\"* is valid.\"},
+              \"globs\":{\"*.rs\":\"{stem\"}}",
+        )
+        .unwrap();
+        assert_eq!(
+            d.validate_patterns(),
+            vec![
+                (
+                    PatternKind::Children,
+                    "/b".to_string(),
+                    PatternError::UnknownPlaceholder("owner".to_string())
+                ),
+                (
+                    PatternKind::Glob,
+                    "*.rs".to_string(),
+                    PatternError::UnclosedBrace(0)
+                ),
+            ]
+        );
+        assert_eq!(d.description("/a"), Some("{owner} is fine here."));
+    }
+
+    #[test]
+    fn def_path_keys_test() {
+        let mut d = Describer::new();
+        d.add_description("/path//to/dir/", "A directory.");
+        d.add_pattern("/", "* is at the root.").unwrap();
+        d.add_pattern(DefPath::new("/path/to/"), "* is in to.")
+            .unwrap();
+
+        for (path, desc) in [
+            ("/path/to/dir", Some("A directory.")),
+            ("/path/to//dir//", Some("A directory.")),
+            ("/path/to/file", Some("file is in to.")),
+            ("/etc", Some("etc is at the root.")),
+            ("/", None),
+        ]
+        .iter()
+        {
+            assert_eq!(d.describe(*path), desc.map(|d| d.to_string()), "{}", path);
+        }
+        assert!(d.remove_description("/path/to/dir/"));
+    }
+
+    #[test]
+    fn drive_letter_keys_test() {
+        let mut d = Describer::new();
+        d.add_pattern("C:/", "* is on C.").unwrap();
+        d.add_pattern("C:/Users", "* is a user.").unwrap();
+        d.add_pattern_with_kind("D:/data", "** is data.", PatternKind::Recursive)
+            .unwrap();
+        d.add_glob("C:/**/*.log", "A log file.").unwrap();
+
+        for (path, desc) in [
+            ("C:/Users/user", Some("user is a user.")),
+            ("C:/Windows", Some("Windows is on C.")),
+            ("D:/data/a/b", Some("a/b is data.")),
+            ("C:/Users/user/x.log", Some("A log file.")),
+            ("C:/", None),
+            ("D:/other", None),
+        ]
+        .iter()
+        {
+            assert_eq!(d.describe(*path), desc.map(|d| d.to_string()), "{}", path);
+        }
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn windows_paths_test() {
+        let mut d = Describer::new();
+        d.add_description("c:\\Users\\user\\", "Home.");
+        d.add_pattern("C:\\Users", "* is a user.").unwrap();
+        d.add_glob("C:\\**\\*.log", "A log file.").unwrap();
+
+        for (path, desc) in [
+            ("C:/Users/user", Some("Home.")),
+            ("C:\\Users\\user", Some("Home.")),
+            ("\\\\?\\C:\\Users\\other", Some("other is a user.")),
+            ("c:\\Users\\user\\x.log", Some("A log file.")),
+        ]
+        .iter()
+        {
+            assert_eq!(d.describe(*path), desc.map(|d| d.to_string()), "{}", path);
+        }
+    }
+
+    #[test]
+    fn recursive_pattern_test() {
+        let mut d = Describer::new();
+        d.add_pattern_with_kind(
+            "/projects",
+            "* is in ** of projects.",
+            PatternKind::Recursive,
+        )
+        .unwrap();
+        d.add_pattern_with_kind("/projects/def/src", "** is source.", PatternKind::Recursive)
+            .unwrap();
+        d.add_pattern("/projects/def", "* is a part of def.")
+            .unwrap();
+        d.add_description("/projects/def/README.md", "Read me.");
+        d.add_pattern_with_kind("/", "** is somewhere.", PatternKind::Recursive)
+            .unwrap();
+
+        for (path, desc) in [
+            ("/projects/def", Some("def is in def of projects.")),
+            ("/projects/def/src", Some("src is a part of def.")),
+            ("/projects/def/README.md", Some("Read me.")),
+            ("/projects/def/src/main.rs", Some("main.rs is source.")),
+            ("/projects/def/src/a/b.rs", Some("a/b.rs is source.")),
+            ("/projects/other/x", Some("x is in other/x of projects.")),
+            ("/projects", Some("projects is somewhere.")),
+            ("/", None),
+        ]
+        .iter()
+        {
+            assert_eq!(d.describe(path), desc.map(|d| d.to_string()));
+        }
+
+        assert!(d.remove_pattern("/projects"));
+        assert_eq!(
+            d.describe("/projects/other/x"),
+            Some("projects/other/x is somewhere.".to_string())
+        );
+    }
+
+    #[test]
+    fn describe_into_test() {
+        let mut d = Describer::new();
+        d.add_description("/dir/a", "This is a.");
+        d.add_pattern("/dir", "* is in dir ({size}).").unwrap();
+        d.add_pattern_with_kind("/", "** is somewhere.", PatternKind::Recursive)
+            .unwrap();
+        d.add_glob("*.log", "{stem} log.").unwrap();
+        d.add_glob("/dir/*.log", "A log of dir.").unwrap();
+        d.set_priority("*.log", 1);
+
+        let mut description = "Stale.".to_string();
+        for path in [
+            "/dir/a",
+            "/dir/b",
+            "/dir/b/c",
+            "/dir/x.log",
+            "/var/sys.log",
+            "/",
+        ]
+        .iter()
+        {
+            let described = d.describe_into(&DefPath::new(path), &mut description);
+            let expected = d.describe(*path);
+            assert_eq!(described, expected.is_some(), "{}", path);
+            assert_eq!(description, expected.unwrap_or_default(), "{}", path);
+        }
+    }
+
+    #[test]
+    fn resolution_order_test() {
+        let mut d = Describer::new();
+        d.add_description("/src/lib.rs", "The library.");
+        d.add_pattern("/src", "{stem} module.").unwrap();
+        d.add_pattern_with_kind("/", "** is somewhere.", PatternKind::Recursive)
+            .unwrap();
+        d.add_glob("*.rs", "Rust code.").unwrap();
+
+        for (steps, main, lib, readme) in [
+            (None, "main module.", "The library.", Some("README module.")),
+            (
+                Some(vec![
+                    vec![Stage::Description],
+                    vec![Stage::Glob],
+                    vec![Stage::Pattern, Stage::Recursive],
+                ]),
+                "Rust code.",
+                "The library.",
+                Some("README module."),
+            ),
+            (
+                Some(vec![vec![Stage::Pattern], vec![Stage::Description]]),
+                "main module.",
+                "lib module.",
+                Some("README module."),
+            ),
+            (
+                Some(vec![vec![Stage::Recursive, Stage::Glob]]),
+                "src/main.rs is somewhere.",
+                "src/lib.rs is somewhere.",
+                Some("src/README is somewhere."),
+            ),
+            (
+                Some(vec![vec![Stage::Glob]]),
+                "Rust code.",
+                "Rust code.",
+                None,
+            ),
+        ]
+        .iter()
+        {
+            let order = match steps {
+                Some(steps) => ResolutionOrder::new(steps.clone()).unwrap(),
+                None => ResolutionOrder::default(),
+            };
+            d.set_resolution_order(order.clone());
+            assert_eq!(
+                d.describe_dir_entries("/src", &["main.rs", "lib.rs", "README"]),
+                vec![
+                    Some(main.to_string()),
+                    Some(lib.to_string()),
+                    readme.map(str::to_string),
+                ],
+                "{}",
+                order
+            );
+            let mut description = String::new();
+            d.describe_into(&DefPath::new("/src/lib.rs"), &mut description);
+            assert_eq!(description, *lib, "{}", order);
+            let explained = d.explain("/src/lib.rs");
+            assert_eq!(explained[0].description, *lib, "{}", order);
+            let stages: Vec<Stage> = explained
+                .iter()
+                .map(|c| c.pattern.map_or(Stage::Description, Stage::from))
+                .collect();
+            assert!(stages.iter().all(|s| order.uses(*s)), "{}", order);
+        }
+    }
+
+    #[test]
+    fn match_options_test() {
+        let mut d = Describer::new();
+        d.add_description("/Users/me/Café", "Coffee.");
+        d.add_pattern("/Users/me/Downloads", "Downloaded {name}.")
+            .unwrap();
+        d.add_pattern_with_kind("/Users/me/Src", "** is source.", PatternKind::Recursive)
+            .unwrap();
+        d.add_glob("*.TXT", "Text.").unwrap();
+        d.add_tags("/Users/me/Café", vec!["drinks"]);
+        d.set_match_options(MatchOptions {
+            case_insensitive: true,
+            normalize_unicode: true,
+        });
+
+        for (path, description) in [
+            ("/Users/me/Café", Some("Coffee.")),
+            ("/users/me/cafe\u{301}", Some("Coffee.")),
+            ("/USERS/ME/DOWNLOADS/a.tar", Some("Downloaded a.tar.")),
+            ("/users/me/src/lib/Mod.rs", Some("lib/Mod.rs is source.")),
+            ("/tmp/notes.txt", Some("Text.")),
+            ("/Users/you/Café", None),
+        ]
+        .iter()
+        {
+            assert_eq!(d.describe(path).as_deref(), *description, "{}", path);
+        }
+
+        d.add_description("/users/me/café", "Espresso.");
+        assert_eq!(d.iter_descriptions().count(), 1);
+        assert_eq!(d.description("/Users/me/Café"), Some("Espresso."));
+        assert_eq!(d.tags_of("/USERS/me/café"), vec!["drinks"]);
+        assert_eq!(d.entries_under("/users/ME").len(), 3);
+        assert!(d.remove_description("/users/me/CAFÉ"));
+        assert!(d.tags_of("/Users/me/Café").is_empty());
+
+        d.set_match_options(MatchOptions::default());
+        assert_eq!(d.describe("/users/me/downloads/a.tar"), None);
+        assert_eq!(d.entries_under("/users/me").len(), 0);
+    }
+
+    #[test]
+    fn describe_detailed_test() {
+        let mut d = Describer::new();
+        d.add_description("/srv", "Served files.");
+        d.add_pattern("/srv", "* ({size}) is served as *.").unwrap();
+        d.add_pattern_with_kind("/home", "** of {parent}.", PatternKind::Recursive)
+            .unwrap();
+        d.add_glob("*.log", "A log.").unwrap();
+
+        for (path, key, kind, substitutions) in [
+            ("/srv", "/srv", MatchKind::Specific, vec![]),
+            (
+                "/srv/www",
+                "/srv",
+                MatchKind::ParentPattern,
+                vec![(Placeholder::Name, "www")],
+            ),
+            (
+                "/home/user/a.txt",
+                "/home",
+                MatchKind::RecursivePattern,
+                vec![
+                    (Placeholder::Relative, "user/a.txt"),
+                    (Placeholder::Parent, "user"),
+                ],
+            ),
+            ("/var/sys.log", "*.log", MatchKind::Glob, vec![]),
+        ]
+        .iter()
+        {
+            let result = d.describe_detailed(*path).unwrap();
+            assert_eq!(Some(&result.description), d.describe(*path).as_ref());
+            assert_eq!(
+                (result.key.as_str(), result.kind),
+                (*key, *kind),
+                "{}",
+                path
+            );
+            let found: Vec<_> = result
+                .substitutions
+                .iter()
+                .map(|s| (s.placeholder, s.value.as_str()))
+                .collect();
+            assert_eq!(found, *substitutions, "{}", path);
+        }
+
+        assert_eq!(d.describe_detailed("/srv/www/index.html"), None);
+        let inherited = d
+            .describe_inherited_detailed("/srv/www/index.html")
+            .unwrap();
+        assert_eq!(inherited.description, "www ({size}) is served as www.");
+        assert_eq!(
+            (inherited.key.as_str(), inherited.kind),
+            ("/srv/www", MatchKind::Inherited)
+        );
+    }
+
+    #[test]
+    fn describe_dir_entries_test() {
+        let mut d = Describer::new();
+        d.add_description("/dir/a", "This is a.");
+        d.add_pattern("/dir", "* is in dir.").unwrap();
+        d.add_description("/b", "This is b.");
+
+        assert_eq!(
+            d.describe_dir_entries("/dir/", &["a", "b"]),
+            vec![
+                Some("This is a.".to_string()),
+                Some("b is in dir.".to_string())
+            ]
+        );
+        assert_eq!(
+            d.describe_dir_entries("/", &["b", "c"]),
+            vec![Some("This is b.".to_string()), None]
+        );
+    }
+
+    #[test]
+    fn describe_with_fs_test() {
+        let dir = std::env::temp_dir().join(format!("def-fs-test-{}", std::process::id()));
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("notes.md"), vec![b'a'; 1536]).unwrap();
+        let dir = dir.to_string_lossy().to_string();
+
+        let mut d = Describer::new();
+        d.add_pattern(dir.as_str(), "* is a {type} of {size}.")
+            .unwrap();
+        for (name, virtual_, with_fs) in [
+            (
+                "notes.md",
+                "notes.md is a {type} of {size}.",
+                "notes.md is a file of 1.5 KiB.",
+            ),
+            (
+                "sub",
+                "sub is a {type} of {size}.",
+                "sub is a directory of 0 entries.",
+            ),
+            (
+                "gone",
+                "gone is a {type} of {size}.",
+                "gone is a {type} of {size}.",
+            ),
+        ]
+        .iter()
+        {
+            let path = format!("{}/{}", dir, name);
+            assert_eq!(d.describe(path.as_str()).unwrap(), *virtual_);
+            assert_eq!(d.describe_with_fs(path.as_str()).unwrap(), *with_fs);
+            assert_eq!(
+                d.describe_dir_entries_with_fs(dir.as_str(), &[name]),
+                vec![Some(with_fs.to_string())]
+            );
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn typed_pattern_test() {
+        let dir = std::env::temp_dir().join(format!("def-typed-test-{}", std::process::id()));
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("file.txt"), "").unwrap();
+        std::os::unix::fs::symlink("file.txt", dir.join("link")).unwrap();
+        let dir = dir.to_string_lossy().to_string();
+
+        let mut d = Describer::new();
+        d.add_pattern(dir.as_str(), "* is here.").unwrap();
+        d.add_typed_pattern(dir.as_str(), "* is a file.", FileKind::File)
+            .unwrap();
+        d.add_typed_pattern(dir.as_str(), "* is a dir.", FileKind::Directory)
+            .unwrap();
+        d.add_typed_pattern(dir.as_str(), "* is a link.", FileKind::Symlink)
+            .unwrap();
+        d.add_typed_pattern(dir.as_str(), "{name} is a link.", FileKind::Symlink)
+            .unwrap();
+        assert_eq!(d.typed_patterns_of(dir.as_str()).len(), 3);
+
+        let names = ["file.txt", "sub", "link", "missing"];
+        let expected = [
+            Some("file.txt is a file.".to_string()),
+            Some("sub is a dir.".to_string()),
+            Some("link is a link.".to_string()),
+            Some("missing is here.".to_string()),
+        ];
+        for (name, description) in names.iter().zip(expected.iter()) {
+            let path = format!("{}/{}", dir, name);
+            assert_eq!(d.describe_with_fs(path.as_str()), *description);
+            assert_eq!(
+                d.describe(path.as_str()),
+                Some(format!("{} is here.", name))
+            );
+        }
+        assert_eq!(
+            d.describe_dir_entries_with_fs(dir.as_str(), &names),
+            expected.to_vec()
+        );
+        let candidates = d.explain_with_fs(format!("{}/sub", dir));
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(candidates[1].description, "sub is here.");
+        assert_eq!(
+            d.describe_as(format!("{}/missing", dir), FileKind::File),
+            Some("missing is a file.".to_string())
+        );
+
+        assert!(d.remove_typed_pattern(&dir, FileKind::File));
+        assert!(!d.remove_typed_pattern(&dir, FileKind::File));
+        let path = format!("{}/file.txt", dir);
+        assert_eq!(
+            d.describe_with_fs(path.as_str()),
+            Some("file.txt is here.".to_string())
+        );
+
+        d.rename_prefix(&dir, "/moved");
+        assert_eq!(d.typed_patterns_of("/moved").len(), 2);
+        assert!(d.remove_pattern("/moved"));
+        assert!(d.typed_patterns_of("/moved").is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn glob_test() {
+        let mut d = Describer::new();
+        d.add_glob("/home/*/Downloads", "Downloads of a user.")
+            .unwrap();
+        d.add_glob("/home/user/Downloads", "Not a glob, but matches anyway.")
+            .unwrap();
+        d.add_glob("*.log", "** is a log file.").unwrap();
+        d.add_glob("/var/**", "* is in /var.").unwrap();
+        d.add_description("/home/admin/Downloads", "Downloads of admin.");
+        d.add_pattern("/var/log", "* is a log.").unwrap();
+        d.add_glob("*.rs", "{stem} module of {parent}.").unwrap();
+
+        for (path, desc) in [
+            (
+                "/home/user/Downloads",
+                Some("Not a glob, but matches anyway."),
+            ),
+            ("/home/other/Downloads", Some("Downloads of a user.")),
+            ("/home/admin/Downloads", Some("Downloads of admin.")),
+            ("/tmp/x.log", Some("/tmp/x.log is a log file.")),
+            ("/var/log/syslog", Some("syslog is a log.")),
+            ("/var/log/x.log", Some("x.log is a log.")),
+            ("/var/tmp/x.log", Some("x.log is in /var.")),
+            ("/src/tree/main.rs", Some("main module of tree.")),
+            ("/home/user/Documents", None),
+        ]
+        .iter()
+        {
+            assert_eq!(d.describe(path), desc.map(|d| d.to_string()));
+        }
+
+        assert!(d.remove_pattern("/var/log"));
+        assert_eq!(
+            d.describe("/var/log/syslog"),
+            Some("syslog is in /var.".to_string())
+        );
+
+        assert_eq!(d.add_glob("", "Anything."), Err(PatternError::EmptyGlob));
+        assert_eq!(d.describe("/home/user/Documents"), None);
+    }
+
+    #[test]
+    fn append_test() {
+        let mut d = Describer::new();
+        d.add_description("/srv", "Served files.");
+        d.append_description("/srv", "Backed up nightly.");
+        d.prepend_description("/srv", "Public.");
+        d.prepend_description("/tmp", "Temporary files.");
+        for (path, desc) in [
+            ("/srv", "Public. Served files. Backed up nightly."),
+            ("/tmp", "Temporary files."),
+        ]
+        .iter()
+        {
+            assert_eq!(d.describe(path), Some(desc.to_string()));
+        }
+    }
+
+    #[test]
+    fn remove_test() {
+        let mut d = Describer::new();
+        d.add_description("/path/to/dir", "This is /path/to/dir.");
+        d.add_pattern("/path/to/dir", "* is in /path/to/dir.")
+            .unwrap();
+
+        assert!(d.remove_description("/path/to/dir"));
+        assert!(!d.remove_description("/path/to/dir"));
+        assert_eq!(d.describe("/path/to/dir"), None);
+        assert_eq!(
+            d.describe("/path/to/dir/1"),
+            Some("1 is in /path/to/dir.".to_string())
+        );
+
+        assert!(d.remove_pattern("/path/to/dir"));
+        assert!(!d.remove_pattern("/path/to/dir"));
+        assert_eq!(d.describe("/path/to/dir/1"), None);
+        assert!(d.trashed().is_empty());
+    }
+
+    #[test]
+    fn entries_under_test() {
+        let mut d = Describer::new();
+        d.add_description("/a", "This is a.");
+        d.add_description("/a/b", "This is b.");
+        d.add_description("/ab", "This is ab.");
+        d.add_pattern("/a", "* is in a.").unwrap();
+
+        assert_eq!(d.iter_descriptions().count(), 3);
+        assert_eq!(
+            d.iter_patterns(PatternKind::Children).collect::<Vec<_>>(),
+            vec![("/a", "* is in a.")]
+        );
+        assert_eq!(
+            d.entries_under("/a"),
+            vec![
+                Entry {
+                    path: "/a",
+                    description: "This is a.",
+                    pattern: None,
+                },
+                Entry {
+                    path: "/a",
+                    description: "* is in a.",
+                    pattern: Some(PatternKind::Children),
+                },
+                Entry {
+                    path: "/a/b",
+                    description: "This is b.",
+                    pattern: None,
+                },
+            ]
+        );
+        assert_eq!(d.entries_under("/").len(), 4);
+        assert_eq!(d.entries_under("").len(), 4);
+        assert!(d.entries_under("/c").is_empty());
+    }
+
+    #[test]
+    fn ancestor_queries_test() {
+        let mut d = Describer::new();
+        d.add_description("/", "The root.");
+        d.add_description("/a", "This is a.");
+        d.add_description("/a/b/c", "This is c.");
+        d.add_description("/a-b", "This is a-b.");
+
+        for (path, ancestor) in [
+            ("/a/b/c/d", Some(("/a/b/c", "This is c."))),
+            ("/a/b/c", Some(("/a", "This is a."))),
+            ("/a/", Some(("/", "The root."))),
+            ("/", None),
+            ("a", None),
+        ]
+        .iter()
+        {
+            assert_eq!(d.nearest_ancestor_description(*path), *ancestor, "{}", path);
+        }
+
+        for (prefix, paths) in [
+            ("/a", vec!["/a", "/a/b/c"]),
+            ("/a/b/", vec!["/a/b/c"]),
+            ("", vec!["/", "/a", "/a/b/c", "/a-b"]),
+            ("/c", vec![]),
+        ]
+        .iter()
+        {
+            let found: Vec<&str> = d.descendants(prefix).map(|(p, _)| p).collect();
+            assert_eq!(found, *paths, "{}", prefix);
+        }
+
+        d.add_pattern_with_kind("/a", "** is under a.", PatternKind::Recursive)
+            .unwrap();
+        d.add_pattern("/a/b", "* is in b.").unwrap();
+        assert_eq!(
+            d.describe_dir_entries("/a/b", &["c", "d", "e/f"]),
+            vec![
+                Some("This is c.".to_string()),
+                Some("d is in b.".to_string()),
+                Some("b/e/f is under a.".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn describe_inherited_test() {
+        let mut d = Describer::new();
+        d.add_description("/a", "This is a.");
+        d.add_pattern("/a/b", "{name} is in b.").unwrap();
+
+        for (path, inherited) in [
+            ("/a/b/c/d", Some(("/a/b/c", "d", "c is in b."))),
+            ("/a/b/c", Some(("/a", "b/c", "This is a."))),
+            ("/a/x/y", Some(("/a", "x/y", "This is a."))),
+            ("/a", None),
+            ("/z", None),
+        ]
+        .iter()
+        {
+            let expected = inherited.map(|(ancestor, relative, description)| Inherited {
+                ancestor: ancestor.to_string(),
+                relative: relative.to_string(),
+                description: description.to_string(),
+            });
+            assert_eq!(d.describe_inherited(*path), expected, "{}", path);
+        }
+    }
+
+    #[test]
+    fn rename_prefix_test() {
+        let mut d = Describer::new();
+        d.add_description("/old/root", "The root.");
+        d.add_description("/old/root/dir", "A dir in root.");
+        d.add_description("/old/rootless", "Not in root.");
+        d.add_description("/new/dir", "Will be overwritten.");
+        d.add_pattern("/old/root/dir", "* is in dir.").unwrap();
+        d.add_description("/old/root/trashed", "Trashed.");
+        d.trash_description("/old/root/trashed");
+
+        assert_eq!(
+            d.plan_prefix_rename("/old/root", "/new"),
+            vec![
+                Rename {
+                    old: "/old/root".to_string(),
+                    new: "/new".to_string(),
+                    pattern: None,
+                    overwrites: false,
+                },
+                Rename {
+                    old: "/old/root/dir".to_string(),
+                    new: "/new/dir".to_string(),
+                    pattern: None,
+                    overwrites: true,
+                },
+                Rename {
+                    old: "/old/root/dir".to_string(),
+                    new: "/new/dir".to_string(),
+                    pattern: Some(PatternKind::Children),
+                    overwrites: false,
+                },
+            ]
+        );
+        assert_eq!(d.describe("/old/root"), Some("The root.".to_string()));
+
+        assert_eq!(d.rename_prefix("/old/root", "/new"), 3);
+        for (path, desc) in [
+            ("/new", Some("The root.")),
+            ("/new/dir", Some("A dir in root.")),
+            ("/new/dir/x", Some("x is in dir.")),
+            ("/old/rootless", Some("Not in root.")),
+            ("/old/root", None),
+            ("/old/root/dir", None),
+            ("/old/root/dir/x", None),
+        ]
+        .iter()
+        {
+            assert_eq!(d.describe(path), desc.map(|d| d.to_string()));
+        }
+        assert_eq!(d.trashed()[0].path, "/new/trashed");
+    }
+
+    #[test]
+    fn rename_test() {
+        let mut d = Describer::new();
+        d.add_description("/old", "Old.");
+        d.add_description("/old/dir", "A dir in old.");
+        d.add_pattern("/old", "* is in old.").unwrap();
+        d.add_description("/new", "Will be overwritten.");
+
+        let renames = d.plan_rename("/old", "/new");
+        assert_eq!(renames.len(), 2);
+        assert!(renames[0].overwrites);
+        assert!(!renames[1].overwrites);
+
+        assert_eq!(d.rename("/old", "/new"), 2);
+        for (path, desc) in [
+            ("/new", Some("Old.")),
+            ("/new/x", Some("x is in old.")),
+            ("/old/dir", Some("A dir in old.")),
+            ("/old", None),
+            ("/new/dir", Some("dir is in old.")),
+        ]
+        .iter()
+        {
+            assert_eq!(d.describe(path), desc.map(|d| d.to_string()), "{}", path);
+        }
+        assert_eq!(d.rename("/new", "/new"), 0);
+    }
+
+    #[test]
+    fn rename_prefix_into_itself_test() {
+        let mut d = Describer::new();
+        d.add_description("/a", "This is a.");
+        d.add_description("/a/b", "This is b.");
+        assert_eq!(d.rename_prefix("/a", "/a/b"), 2);
+        assert_eq!(d.describe("/a/b"), Some("This is a.".to_string()));
+        assert_eq!(d.describe("/a/b/b"), Some("This is b.".to_string()));
+        assert_eq!(d.describe("/a"), None);
+    }
+
+    #[test]
+    fn trash_test() {
+        let mut d = Describer::new();
+        d.add_description("/path/to/dir", "This is /path/to/dir.");
+        d.add_pattern("/path/to/dir", "* is in /path/to/dir.")
+            .unwrap();
+
+        assert!(d.trash_description("/path/to/dir"));
+        assert!(!d.trash_description("/path/to/dir"));
+        assert_eq!(d.describe("/path/to/dir"), None);
+        assert_eq!(
+            d.describe("/path/to/dir/1"),
+            Some("1 is in /path/to/dir.".to_string())
+        );
+
+        assert!(d.trash_pattern("/path/to/dir"));
+        assert_eq!(d.describe("/path/to/dir/1"), None);
+        assert_eq!(d.trashed().len(), 2);
+
+        // The pattern was trashed last, so it's restored first.
+        assert_eq!(
+            d.restore("/path/to/dir").unwrap().pattern,
+            Some(PatternKind::Children)
+        );
+        assert_eq!(d.restore("/path/to/dir").unwrap().pattern, None);
+        assert_eq!(
+            d.restore("/path/to/dir"),
+            Err(TrashError::NotTrashed("/path/to/dir".to_string()))
+        );
+        assert_eq!(
+            d.describe("/path/to/dir"),
+            Some("This is /path/to/dir.".to_string())
+        );
+
+        d.trash_description("/path/to/dir");
+        d.add_description("/path/to/dir", "A new description.");
+        assert_eq!(
+            d.restore("/path/to/dir"),
+            Err(TrashError::Occupied("/path/to/dir".to_string()))
+        );
+        assert_eq!(d.empty_trash(), 1);
+        assert!(d.trashed().is_empty());
+    }
+
+    #[test]
+    fn collect_garbage_test() {
+        let metadata = PathMetadata {
+            kind: FileKind::File,
+            size: 1,
+        };
+        let mut d = Describer::new();
+        d.add_description("/described", "Described.");
+        d.record_metadata("/described", metadata);
+        d.record_metadata("/orphan", metadata);
+
+        assert_eq!(d.collect_garbage(), 1);
+        assert_eq!(d.collect_garbage(), 0);
+        assert_eq!(d.metadata("/described"), Some(&metadata));
+        assert_eq!(d.metadata("/orphan"), None);
+    }
+
+    #[test]
+    fn stamp_test() {
+        let mut d = Describer::new();
+        d.add_description("/a", "A.");
+        assert!(d.stamp("/a", 100));
+        assert!(d.stamp("/a", 200));
+        assert!(!d.stamp("/b", 100));
+        assert_eq!(
+            d.timestamps("/a"),
+            Some(&Timestamps {
+                created: 100,
+                updated: 200,
+            })
+        );
+        assert_eq!(d.timestamps("/b"), None);
+
+        d.add_description("/b/c", "C.");
+        d.add_description("/d", "D.");
+        d.stamp("/b/c", 300);
+        d.stamp("/d", 150);
+        assert_eq!(
+            d.recently_added(5),
+            vec![("/b/c", 300), ("/d", 150), ("/a", 100)]
+        );
+        assert_eq!(d.recently_added(1), vec![("/b/c", 300)]);
+        assert_eq!(d.deepest_description(), Some("/b/c"));
+        d.remove_description("/b/c");
+        d.remove_description("/d");
+
+        d.rename_prefix("/a", "/c");
+        assert_eq!(d.timestamps("/c").map(|t| t.updated), Some(200));
+        d.remove_description("/c");
+        assert_eq!(d.timestamps("/c"), None);
+    }
+
+    #[test]
+    fn tags_test() {
+        let mut d = Describer::new();
+        d.add_description("/work", "Work.");
+        d.add_description("/work/old", "Old work.");
+        d.add_description("/notes", "Notes.");
+        assert!(d.add_tags("/work", vec!["work"]));
+        assert!(d.add_tags("/work/old", vec!["work", "archived"]));
+        assert!(!d.add_tags("/undescribed", vec!["work"]));
+        assert!(d.add_tags("/notes", Vec::<String>::new()));
+        assert!(!d.to_json(false).unwrap().contains("/notes\":[]"));
+
+        let paths = |d: &Describer, tag| {
+            d.find_by_tag(tag)
+                .iter()
+                .map(|e| e.path.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(paths(&d, "work"), vec!["/work", "/work/old"]);
+        assert_eq!(paths(&d, "archived"), vec!["/work/old"]);
+        assert!(paths(&d, "notes").is_empty());
+
+        // Tags follow renamed paths, and survive the trash.
+        d.rename_prefix("/work", "/job");
+        assert_eq!(d.tags_of("/job/old"), vec!["archived", "work"]);
+        d.trash_description("/job");
+        assert_eq!(paths(&d, "work"), vec!["/job/old"]);
+        assert_eq!(d.collect_garbage(), 0);
+        d.restore("/job").unwrap();
+        assert_eq!(paths(&d, "work"), vec!["/job", "/job/old"]);
+
+        d.remove_description("/job/old");
+        assert!(d.tags_of("/job/old").is_empty());
+        assert_eq!(paths(&d, "archived"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn private_test() {
+        let mut d = Describer::new();
+        d.add_description("/work", "Work.");
+        d.add_description("/work/notes", "Personal notes.");
+        d.add_pattern("/work", "* is a project.").unwrap();
+        assert!(d.set_private("/work/notes", true));
+        assert!(!d.set_private("/undescribed", true));
+
+        // Privacy follows renamed paths, and is kept when merged.
+        d.rename_prefix("/work", "/job");
+        assert!(d.is_private("/job/notes"));
+        assert!(!d.is_private("/job"));
+        let mut merged = Describer::new();
+        merged.merge_with(d.clone(), Strategy::Overwrite);
+        assert!(merged.is_private("/job/notes"));
+        assert!(d
+            .to_json(false)
+            .unwrap()
+            .contains(r#""private":["/job/notes"]"#));
+
+        let mut shared = d.clone();
+        assert_eq!(shared.remove_private(), 1);
+        assert_eq!(
+            shared.describe("/job/notes"),
+            Some("notes is a project.".to_string())
+        );
+        assert_eq!(shared.describe("/job"), Some("Work.".to_string()));
+        assert!(!shared.is_private("/job/notes"));
+
+        assert!(d.set_private("/job/notes", false));
+        assert_eq!(d.remove_private(), 0);
+        assert!(!d.to_json(false).unwrap().contains("private"));
+    }
+
+    #[test]
+    fn explain_test() {
+        let mut d = Describer::new();
+        d.add_description("/srv/www", "Served files.");
+        d.add_pattern("/srv", "* is served.").unwrap();
+        d.add_pattern_with_kind("/srv", "** is under /srv.", PatternKind::Recursive)
+            .unwrap();
+        d.add_pattern_with_kind("/", "** is somewhere.", PatternKind::Recursive)
+            .unwrap();
+        d.add_glob("/srv/*.log", "A log.").unwrap();
+        d.add_glob("*.log", "A log anywhere.").unwrap();
+        d.add_pattern("/srv/www", "* is a page.").unwrap();
+
+        let candidate = |key: &str, pattern, priority, description: &str| Candidate {
+            key: key.to_string(),
+            pattern,
+            priority,
+            description: description.to_string(),
+        };
+        for (path, priorities, candidates) in [
+            (
+                "/srv/www",
+                vec![],
+                vec![
+                    candidate("/srv/www", None, 0, "Served files."),
+                    candidate("/srv", Some(PatternKind::Children), 0, "www is served."),
+                    candidate(
+                        "/srv",
+                        Some(PatternKind::Recursive),
+                        0,
+                        "www is under /srv.",
+                    ),
+                    candidate(
+                        "/",
+                        Some(PatternKind::Recursive),
+                        0,
+                        "srv/www is somewhere.",
+                    ),
+                ],
+            ),
+            (
+                "/srv/www/index.log",
+                vec![],
+                vec![
+                    candidate(
+                        "/srv/www",
+                        Some(PatternKind::Children),
+                        0,
+                        "index.log is a page.",
+                    ),
+                    candidate(
+                        "/srv",
+                        Some(PatternKind::Recursive),
+                        0,
+                        "www/index.log is under /srv.",
+                    ),
+                    candidate(
+                        "/",
+                        Some(PatternKind::Recursive),
+                        0,
+                        "srv/www/index.log is somewhere.",
+                    ),
+                    candidate("*.log", Some(PatternKind::Glob), 0, "A log anywhere."),
+                ],
+            ),
+            (
+                "/srv/a.log",
+                vec![("/srv", 10), ("*.log", 20)],
+                vec![
+                    candidate("/srv", Some(PatternKind::Children), 10, "a.log is served."),
+                    candidate(
+                        "/srv",
+                        Some(PatternKind::Recursive),
+                        10,
+                        "a.log is under /srv.",
+                    ),
+                    candidate("/srv/*.log", Some(PatternKind::Glob), 0, "A log."),
+                    candidate(
+                        "/",
+                        Some(PatternKind::Recursive),
+                        0,
+                        "srv/a.log is somewhere.",
+                    ),
+                    candidate("*.log", Some(PatternKind::Glob), 20, "A log anywhere."),
+                ],
+            ),
+            (
+                "/tmp",
+                vec![],
+                vec![candidate(
+                    "/",
+                    Some(PatternKind::Recursive),
+                    0,
+                    "tmp is somewhere.",
+                )],
+            ),
+        ]
+        .iter()
+        {
+            let mut d = d.clone();
+            for (key, priority) in priorities.iter() {
+                assert!(d.set_priority(key, *priority));
+            }
+            assert_eq!(d.explain(*path), *candidates, "{}", path);
+            assert_eq!(
+                d.describe(*path).as_ref(),
+                candidates.first().map(|c| &c.description),
+                "{}",
+                path
+            );
+            assert_eq!(
+                d.describe_dir_entries(
+                    DefPath::new(path).parent().unwrap(),
+                    &[DefPath::new(path).name()]
+                ),
+                vec![candidates.first().map(|c| c.description.clone())],
+                "{}",
+                path
+            );
+        }
+
+        assert!(d.set_priority("/srv", 10));
+        assert!(d
+            .to_json(false)
+            .unwrap()
+            .contains(r#""priorities":{"/srv":10}"#));
+        d.rename_prefix("/srv", "/var/www");
+        assert_eq!(d.priority("/var/www"), 10);
+        d.trash_pattern("/var/www");
+        assert_eq!(d.collect_garbage(), 0);
+        assert!(d.empty_trash() > 0);
+        assert_eq!(d.collect_garbage(), 1);
+        assert!(!d.set_priority("/var/www", 10));
+        assert!(!d.to_json(false).unwrap().contains("priorities"));
+    }
+
+    #[test]
+    fn expire_trash_test() {
+        let mut d = Describer::new();
+        d.add_description("/old", "Trashed long ago.");
+        d.add_description("/new", "Trashed just now.");
+        d.trash_description("/old");
+        d.trash_description("/new");
+        d.trash[0].trashed_at -= 10 * 24 * 60 * 60;
+
+        assert_eq!(d.expire_trash(Duration::from_secs(24 * 60 * 60)), 1);
+        assert_eq!(d.trashed().len(), 1);
+        assert_eq!(d.trashed()[0].path, "/new");
+    }
+
+    #[test]
+    fn merge_with_test() {
+        let metadata = PathMetadata {
+            kind: FileKind::File,
+            size: 1,
+        };
+        let mut other = Describer::new();
+        other.add_description("/same", "Same.");
+        other.add_description("/new", "New.");
+        other.add_description("/changed", "Theirs.");
+        other.add_pattern("/changed", "* is theirs.").unwrap();
+        other.record_metadata("/new", metadata);
+
+        let ours = |d: &mut Describer| {
+            d.add_description("/same", "Same.");
+            d.add_description("/changed", "Ours.");
+            d.add_pattern("/changed", "* is ours.").unwrap();
+        };
+        let mut asked = Vec::new();
+        let mut ask = |c: &Change| {
+            asked.push(c.path.clone());
+            c.pattern.is_none()
+        };
+        for (strategy, description, pattern, updated, conflicts) in [
+            (Strategy::Overwrite, "Theirs.", "x is theirs.", 2, 0),
+            (Strategy::KeepExisting, "Ours.", "x is ours.", 0, 2),
+            (Strategy::Ask(&mut ask), "Theirs.", "x is ours.", 1, 1),
+        ] {
+            let mut d = Describer::new();
+            ours(&mut d);
+            let report = d.merge_with(other.clone(), strategy);
+
+            assert_eq!(
+                report.added,
+                vec![Change {
+                    path: "/new".to_string(),
+                    pattern: None,
+                    existing: None,
+                    incoming: "New.".to_string(),
+                }]
+            );
+            assert_eq!(report.updated.len(), updated);
+            assert_eq!(report.conflicts.len(), conflicts);
+            assert_eq!(d.describe("/changed"), Some(description.to_string()));
+            assert_eq!(d.describe("/changed/x"), Some(pattern.to_string()));
+            assert_eq!(d.metadata("/new"), Some(&metadata));
+        }
+        assert_eq!(asked, vec!["/changed", "/changed"]);
+
+        let mut d = other.clone();
+        assert!(!d.merge_with(other, Strategy::KeepExisting).changed());
     }
 
     fn describe_tester(describer: &Describer) {
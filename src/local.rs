@@ -0,0 +1,154 @@
+//! Local description files.
+//!
+//! Besides the global config, descriptions can be kept in a local file named
+//! `.def.json` (or `.def`) in any directory, such as the root of a repository,
+//! so that they can be committed along with it. Keys in local files are
+//! relative to the file's directory (`.` being the directory itself), so the
+//! file stays valid wherever the directory is checked out. Globs without a
+//! separator (such as `*.log`) are kept as is.
+//!
+//! When a path is described, local files found in the path and its ancestors
+//! are merged over the global config, the nearest file winning.
+
+use std::path::{Path, PathBuf};
+
+use crate::format::Json;
+use crate::store::{FileStore, Store};
+use crate::{glob, paths, DefPath, Describer, Error, SEPERATOR};
+
+/// Names of local files, in order of preference.
+pub const FILE_NAMES: [&str; 2] = [".def.json", ".def"];
+
+/// Key of the directory containing a local file.
+const CURRENT_DIR: &str = ".";
+
+/// Return the local file in dir, or None if dir doesn't contain one.
+pub fn find<P: AsRef<Path>>(dir: P) -> Option<PathBuf> {
+    FILE_NAMES
+        .iter()
+        .map(|name| dir.as_ref().join(name))
+        .find(|file| file.is_file())
+}
+
+/// Return local files in path and its ancestors, farthest first.
+pub fn discover(path: &str) -> Vec<PathBuf> {
+    let mut dirs = vec![path];
+    dirs.extend(paths::ancestors(path).into_iter().map(|(a, _)| a));
+    dirs.into_iter().rev().filter_map(find).collect()
+}
+
+/// Load a describer from a local file, with keys resolved against the
+/// file's directory.
+pub fn load<P: AsRef<Path>>(file: P) -> Result<Describer, Error> {
+    let base = base_dir(file.as_ref())?;
+    let mut describer = FileStore::new(file.as_ref(), &Json).load()?;
+    describer.map_keys(|key| absolute_key(key, &base));
+    Ok(describer)
+}
+
+/// Save a describer to a local file, with keys under the file's directory
+/// made relative to it.
+pub fn save<P: AsRef<Path>>(file: P, describer: &Describer) -> Result<(), Error> {
+    let base = base_dir(file.as_ref())?;
+    let mut relative = describer.clone();
+    relative.map_keys(|key| relative_key(key, &base));
+    Ok(FileStore::new(file.as_ref(), &Json).save(&relative)?)
+}
+
+/// Merge local files found in path and its ancestors over describer, the
+/// nearest file winning (see `Describer::merge`).
+pub fn merge_into(describer: &mut Describer, path: &str) -> Result<(), Error> {
+    for file in discover(path) {
+        describer.merge(load(file)?);
+    }
+    Ok(())
+}
+
+/// Return the directory of a local file as a string.
+fn base_dir(file: &Path) -> Result<String, Error> {
+    let dir = file.parent().unwrap_or_else(|| Path::new(""));
+    dir.to_str()
+        .map(|d| DefPath::new(d).into())
+        .ok_or_else(|| Error::InvalidPath(dir.to_string_lossy().to_string()))
+}
+
+/// Return a key of a local file resolved against base.
+pub(crate) fn absolute_key(key: &str, base: &str) -> String {
+    if key == CURRENT_DIR {
+        base.to_string()
+    } else if paths::root_len(key).is_some() || (glob::is_glob(key) && !key.contains(SEPERATOR)) {
+        key.to_string()
+    } else {
+        format!("{}{}{}", base.trim_end_matches(SEPERATOR), SEPERATOR, key)
+    }
+}
+
+/// Return key relative to base if it's under base, or key itself otherwise.
+fn relative_key(key: &str, base: &str) -> String {
+    match paths::rebase(key, base, "") {
+        Some(rest) if rest.is_empty() => CURRENT_DIR.to_string(),
+        Some(rest) => rest.trim_start_matches(SEPERATOR).to_string(),
+        None => key.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn key_test() {
+        for (key, base, relative) in [
+            ("/repo", "/repo", "."),
+            ("/repo/src/main.rs", "/repo", "src/main.rs"),
+            ("/repo/src/*.rs", "/repo", "src/*.rs"),
+            ("*.rs", "/repo", "*.rs"),
+            ("/elsewhere", "/repo", "/elsewhere"),
+            ("/a", "/", "a"),
+        ]
+        .iter()
+        {
+            assert_eq!(relative_key(key, base), *relative);
+            assert_eq!(absolute_key(relative, base), *key);
+        }
+    }
+
+    #[test]
+    fn merge_into_test() {
+        let root = std::env::temp_dir().join(format!("def-local-test-{}", std::process::id()));
+        let repo = root.join("repo");
+        fs::create_dir_all(repo.join("src")).unwrap();
+        let root_str = root.to_str().unwrap();
+        let repo_str = repo.to_str().unwrap();
+
+        let mut outer = Describer::new();
+        outer.add_description(repo_str, "Outer.");
+        outer.add_pattern(repo_str, "* is in the repo.").unwrap();
+        save(root.join(".def"), &outer).unwrap();
+        let mut inner = Describer::new();
+        inner.add_description(repo_str, "Inner.");
+        inner.add_glob("*.rs", "Rust.").unwrap();
+        save(repo.join(".def.json"), &inner).unwrap();
+        let saved = fs::read_to_string(repo.join(".def.json")).unwrap();
+
+        let mut d = Describer::new();
+        d.add_description(root_str, "Global.");
+        d.add_description(repo_str, "Global repo.");
+        let src = format!("{}/src", repo_str);
+        merge_into(&mut d, &src).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert!(saved.contains("\".\": \"Inner.\""));
+        for (path, desc) in [
+            (root_str.to_string(), "Global."),
+            (repo_str.to_string(), "Inner."),
+            (src, "src is in the repo."),
+            (format!("{}/src/main.rs", repo_str), "Rust."),
+        ]
+        .iter()
+        {
+            assert_eq!(d.describe(path), Some(desc.to_string()), "{}", path);
+        }
+    }
+}
@@ -1,153 +1,2805 @@
+#[cfg(feature = "archive")]
+mod archive;
+mod backup;
+#[cfg(feature = "tui")]
+mod browse;
+#[cfg(feature = "archive")]
+mod bundle;
 mod command;
+mod editor;
 mod errors;
+mod help;
+mod hook;
+mod i18n;
+mod markdown;
+mod output;
+#[cfg(all(feature = "procfs", target_os = "linux"))]
+mod procfs;
+mod theme;
+mod tour;
+mod tree;
 
 use std::env;
 use std::fs;
-use std::path::Path;
-use std::process;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+use std::sync::OnceLock;
+use std::time::{Duration, UNIX_EPOCH};
 
-use colored::*;
-use command::InvokedTo;
-use def::Describer;
-use errors::Handle;
+use command::{AddMode, ImportStrategy, InvokedTo, Subset};
+use def::config::resolve::{self, Choice};
+#[cfg(unix)]
+use def::daemon::Daemon;
+use def::daemon::{self, Item, Lookup, Request, Response};
+use def::filter::Filter;
+use def::merge::{Change, Strategy};
+use def::meta::{self, FileInfo, FileKind, PathMetadata};
+use def::pack::{self, Pack};
+use def::readmes::{self, Direction, Target};
+use def::redact::{self, Redactor};
+use def::search::{Match, SearchOptions};
+use def::settings::Settings;
+use def::store::{Lock, Store};
+#[cfg(feature = "sync")]
+use def::sync::Side;
+use def::virtual_tree::VirtualTree;
+use def::{annotations, capture, config, fixture, format, local, paths, vars};
+use def::{Describer, Entry, MatchKind, PatternKind};
+use errors::{warn, Handle};
+use i18n::{tr, trf};
+use output::{OutputFormat, Record, Source};
+use theme::{error_label, paint, Role, Theme};
 
-const JSON_PRETTY: bool = true; // Use pretty JSON
+/// Settings used instead of the user's settings, set by use_sandbox.
+static SANDBOX: OnceLock<Settings> = OnceLock::new();
+
+/// Config file used instead of the one of settings, chosen by --config,
+/// --profile, or the environment (see def::config::resolve).
+static CHOICE: OnceLock<Choice> = OnceLock::new();
+
+/// Whether paths are resolved lexically, set by --no-resolve.
+static NO_RESOLVE: OnceLock<bool> = OnceLock::new();
+
+/// Whether metadata placeholders are left unfilled, set by --no-stat.
+static NO_STAT: OnceLock<bool> = OnceLock::new();
+
+/// Whether descriptions are printed as written, set by --raw.
+static RAW: OnceLock<bool> = OnceLock::new();
+
+/// Whether paths without a description inherit that of their nearest
+/// described ancestor, set by --inherit or "inherit" in settings.
+static INHERIT: OnceLock<bool> = OnceLock::new();
+
+/// Whether commands capturing descriptions can be run, set by
+/// --allow-commands or "allow_commands" in settings.
+static ALLOW_COMMANDS: OnceLock<bool> = OnceLock::new();
+
+/// Name of the operation def was invoked to do, which changes it makes to the
+/// config are journaled under, see command::operation.
+static OPERATION: OnceLock<String> = OnceLock::new();
+
+/// Exit code of "def maintain" when it finds something needing attention.
+const ATTENTION_EXIT_CODE: i32 = 2;
+
+/// Number of descriptions added last listed by "def stats".
+const RECENT_SHOWN: usize = 5;
+
+/// Interval between checks of the config for changes by "def events".
+const EVENTS_INTERVAL: Duration = Duration::from_millis(500);
 
 fn main() {
-    match command::parse(&env::args().collect::<Vec<String>>()) {
+    let (args, options) = command::parse_options(&env::args().collect::<Vec<String>>());
+    let settings = match config::load_settings() {
+        Err(def::Error::NoHome) => Settings::default(),
+        result => result.extract_or_exit(tr("failed-read-settings")),
+    };
+    if let Ok(dir) = config::config_dir() {
+        i18n::init(
+            settings.language.as_deref(),
+            &dir.join("locales").to_string_lossy(),
+        );
+    }
+    if !io::stdout().is_terminal() {
+        colored::control::set_override(false);
+    }
+    theme::init(Theme::new(
+        &settings.colors,
+        options.plain || options.format == OutputFormat::Plain || settings.plain_output,
+    ));
+    output::init(options.format);
+    markdown::init(settings.markdown && !options.raw);
+    let _ = NO_RESOLVE.set(options.no_resolve);
+    let _ = NO_STAT.set(options.no_stat);
+    let _ = RAW.set(options.raw);
+    let _ = INHERIT.set(options.inherit || settings.inherit);
+    let _ = ALLOW_COMMANDS.set(options.allow_commands || settings.allow_commands);
+    let _ = OPERATION.set(command::operation(&args));
+
+    let invoked = command::parse(&args);
+    let choice = resolve::choose_from_env(options.config.as_deref(), options.profile.as_deref());
+    if let Some(choice) = choice {
+        use_choice(choice, &invoked);
+    }
+    run(invoked);
+}
+
+/// use_choice makes def use the chosen config file. Exits if a chosen profile
+/// doesn't exist, unless invoked manages profiles, so that a mistyped name
+/// doesn't start an empty profile.
+fn use_choice(choice: Choice, invoked: &InvokedTo) {
+    if let Choice::Profile(name) = &choice {
+        let file = config::profile_file(name).extract_or_exit(tr("failed-use-profile"));
+        let manages_profiles = matches!(
+            invoked,
+            InvokedTo::ListProfiles | InvokedTo::CreateProfile(_) | InvokedTo::DeleteProfile(_)
+        );
+        if !file.exists() && !manages_profiles {
+            eprintln!(
+                "{}: {}",
+                error_label(),
+                trf("unknown-profile", &[name, name])
+            );
+            process::exit(1);
+        }
+    }
+    let _ = CHOICE.set(choice);
+}
+
+/// run does what def was invoked to do.
+fn run(invoked: InvokedTo) {
+    match invoked {
         InvokedTo::ShortHelp => help(),
         InvokedTo::Help => usage(),
+        InvokedTo::HelpFor(command) => usage_of(&command),
         InvokedTo::DescribePath(p) => print_description(&p),
-        InvokedTo::AddDescription(p, d) => add_description(&p, &d, false),
-        InvokedTo::AddPattern(p, d) => add_description(&p, &d, true),
-        InvokedTo::Unknown => eprintln!("{}: {}", "Err".red(), "invalid argument list"),
+        InvokedTo::DescribeStdin => print_descriptions_from_stdin(),
+        InvokedTo::DescribeQuietly(p) => print_description_quietly(&p),
+        InvokedTo::Refresh(p) => refresh(p.as_deref()),
+        InvokedTo::AddDescription {
+            path,
+            description,
+            tags,
+            private,
+            mode,
+            from_command: false,
+        } => add_description(&path, &description, &tags, private, mode),
+        InvokedTo::AddDescription {
+            path,
+            description,
+            tags,
+            private,
+            mode,
+            from_command: true,
+        } => add_description_from_command(&path, &description, &tags, private, mode),
+        InvokedTo::AddLocalDescription(p, d) => add_local_description(&p, &d),
+        InvokedTo::AddPattern(p, d, priority) => {
+            add_pattern(&p, &d, PatternKind::Children, priority)
+        }
+        InvokedTo::AddGlob(g, d, priority) => add_pattern(&g, &d, PatternKind::Glob, priority),
+        InvokedTo::AddRecursivePattern(p, d, priority) => {
+            add_pattern(&p, &d, PatternKind::Recursive, priority)
+        }
+        InvokedTo::AddTypedPattern(p, d, kind, priority) => {
+            add_typed_pattern(&p, &d, kind, priority)
+        }
+        InvokedTo::Edit(p) => edit_description(&p),
+        InvokedTo::EditAll => edit_config(),
+        InvokedTo::Doctor => doctor(),
+        InvokedTo::ConvertPaths => convert_paths(),
+        InvokedTo::FixPermissions => fix_permissions(),
+        InvokedTo::RemoveDescription(p) => remove_description(&p, false),
+        InvokedTo::RemovePattern(p) => remove_description(&p, true),
+        InvokedTo::ListTrash => list_trash(),
+        InvokedTo::RestoreFromTrash(p) => restore_from_trash(&p),
+        InvokedTo::EmptyTrash => empty_trash(),
+        InvokedTo::Move {
+            old,
+            new,
+            recursive,
+            dry_run,
+        } => move_path(&old, &new, recursive, dry_run),
+        InvokedTo::List {
+            prefix,
+            depth,
+            filter,
+        } => list(prefix, depth, filter),
+        InvokedTo::Ls(dir) => ls(dir),
+        InvokedTo::Drift(root) => drift(&root),
+        InvokedTo::Stats(dir) => stats(dir),
+        InvokedTo::Undo => undo(),
+        InvokedTo::History(path) => history(&path),
+        InvokedTo::Stale(days) => stale(days),
+        InvokedTo::Check(prune) => check(prune),
+        InvokedTo::FollowEvents => follow_events(),
+        InvokedTo::Maintain(root) => maintain(root),
+        InvokedTo::AnnotateArchive { archive, root } => annotate_archive(&archive, root),
+        InvokedTo::Tour => tour::run(),
+        InvokedTo::Browse => browse(),
+        InvokedTo::Daemon => run_daemon(),
+        InvokedTo::Bundle(output) => create_bundle(&output),
+        InvokedTo::ApplyBundle(file) => apply_bundle(&file),
+        InvokedTo::GenerateFixture(spec) => generate_fixture(&spec),
+        InvokedTo::Search {
+            query,
+            options,
+            filter,
+        } => search(&query, &options, filter),
+        InvokedTo::SaveSearch { name, query } => save_search(&name, &query),
+        InvokedTo::ListSavedSearches => list_saved_searches(),
+        InvokedTo::RunSavedSearch(name) => run_saved_search(&name),
+        InvokedTo::ListTagged(tag) => list_tagged(&tag),
+        InvokedTo::InstallPack {
+            source,
+            prefix,
+            key,
+            yes,
+        } => install_pack(&source, prefix, key, yes),
+        InvokedTo::RemovePack(name) => remove_pack(&name),
+        InvokedTo::ListPacks => list_packs(),
+        InvokedTo::ListProfiles => list_profiles(),
+        InvokedTo::CreateProfile(name) => create_profile(&name),
+        InvokedTo::DeleteProfile(name) => delete_profile(&name),
+        InvokedTo::SyncPull => sync_remote(false),
+        InvokedTo::SyncPush => sync_remote(true),
+        InvokedTo::Hook(shell) => print_hook(&shell),
+        InvokedTo::Completions(shell) => print_completions(&shell),
+        InvokedTo::DescribeInode(id) => describe_inode(&id),
+        InvokedTo::DescribeFd(id) => describe_fd(&id),
+        InvokedTo::Complete(prefix) => print_candidates(&prefix),
+        InvokedTo::Export {
+            file,
+            filter,
+            only,
+            include_private,
+        } => export(file, filter, only, include_private),
+        InvokedTo::SetPrivate(path, private) => set_private(&path, private),
+        InvokedTo::Explain(path) => explain(&path),
+        InvokedTo::Info(path) => info(&path),
+        InvokedTo::Import {
+            file,
+            strategy,
+            only,
+        } => import(&file, strategy, only),
+        InvokedTo::Apply { file, dry_run } => apply(&file, dry_run),
+        InvokedTo::SyncReadmes {
+            root,
+            target,
+            dry_run,
+        } => sync_readmes(&root, target, dry_run),
+        InvokedTo::Unknown => eprintln!("{}: {}", error_label(), tr("invalid-arguments")),
     }
 }
 
 /// help prints a short help message to stderr and exits with code 1.
 fn help() {
     eprintln!(
-        "{}{}{}",
-        "Usage\n",
-        "  def [ <path> | add <path> <description> | pattern <path> <description> ]\n",
-        "Try \"def help\" for more details.",
+        "{}\n{}\n{}",
+        tr("usage-heading"),
+        help::synopsis(),
+        tr("try-help")
     );
     process::exit(1);
 }
 
 /// usage prints a help message to stderr and exits with exit code 1.
 fn usage() {
-    eprintln!(
-        "{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}",
-        "def keeps track of file and directory descriptions for you.\n",
-        "\n",
-        "Usage\n",
-        "\n",
-        "  def <path>                        Print description of file/dir at path.\n",
-        "  def add <path> <description>      Add a description for file/dir at path.\n",
-        "  def pattern <path> <description>  Add a pattern to describe children of dir. A\n",
-        "                                    wildcard in the pattern will be replaced with\n",
-        "                                    the child's name.\n",
-        "\n",
-        "Descriptions\n",
-        "\n",
-        "  To describe a file or directory use add sub-command which simply maps a description\n",
-        "  to an absolute path.\n",
-        "\n",
-        "  The pattern sub-command is used to describe all children of a directory using a\n",
-        "  common trait. When pattern is used, a description is mapped to a dir, but is used\n",
-        "  only to describe its children. If a wildcard \"*\" exists in the pattern, it will\n",
-        "  be replaced by the child's name.\n",
-        "\n",
-        "  For example:\n",
-        "\n",
-        "  $ def pattern dir \"* is a child of dir\"\n",
-        "  $ def dir/temp\n",
-        "  /path/to/dir/temp: temp is a child of dir\n",
-        "\n",
-        "Descriptions and patterns are kept in ~/.config/def/config.json which maps each\n",
-        "description to an absolute path and can be added to or adjusted manually.",
-    );
+    eprintln!("{}", help::full());
+    process::exit(1);
+}
+
+/// usage_of prints the help message of a sub-command to stderr and exits with
+/// exit code 1. Prints a short help message instead if there's no such
+/// sub-command.
+fn usage_of(command: &str) {
+    match help::for_command(command) {
+        Some(help) => eprintln!("{}", help),
+        None => {
+            eprintln!("{}: {}", error_label(), trf("unknown-command", &[&command]));
+            help();
+        }
+    }
     process::exit(1);
 }
 
 /// add_description creates a describer, either from config_file if it exists,
-/// or empty otherwise. Maps the given description to path, and (re)writes the
-/// describer to config_file.
-fn add_description(path: &str, description: &str, pattern: bool) {
-    let mut describer = if Path::new(&config_file()).exists() {
+/// or empty otherwise. Maps the given description to path, combined with its
+/// existing description according to mode, and (re)writes the describer to
+/// config_file. A warning is printed if a different description is replaced
+/// without --force. Metadata of described paths is recorded, to be compared
+/// against later by drift, and tags and times of described paths are
+/// recorded. If private is true, the description is marked private, and
+/// otherwise keeps the privacy it had.
+fn add_description(path: &str, description: &str, tags: &[String], private: bool, mode: AddMode) {
+    let _lock = lock_config();
+    let mut describer = if open_store().exists() {
         get_describer()
     } else {
-        fs::create_dir_all(&config_dir()).extract_or_exit("failed to create config");
         Describer::new()
     };
 
-    if pattern {
-        describer.add_pattern(&absolute_path(path), description);
+    let path = absolute_path(path);
+    let key = written_key(&describer, &path);
+    add_to(
+        &mut describer,
+        &key,
+        &path,
+        description,
+        tags,
+        private,
+        mode,
+    );
+    describer.set_source(&key, capture::Source::Literal);
+    write_describer(&mut describer)
+}
+
+/// add_description_from_command runs command and adds its output as the
+/// description of path, like add_description, keeping command as the
+/// description's source so that it can be refreshed. Exits if commands
+/// aren't allowed or the command fails.
+fn add_description_from_command(
+    path: &str,
+    command: &str,
+    tags: &[String],
+    private: bool,
+    mode: AddMode,
+) {
+    require_commands_allowed();
+    let path = absolute_path(path);
+    let output = capture::run(command, capture::working_dir(&path), command_timeout())
+        .extract_or_exit(tr("failed-run-command"));
+
+    let _lock = lock_config();
+    let mut describer = if open_store().exists() {
+        get_describer()
     } else {
-        describer.add_description(&absolute_path(path), description);
+        Describer::new()
+    };
+    let key = written_key(&describer, &path);
+    add_to(&mut describer, &key, &path, &output, tags, private, mode);
+    describer.set_source(
+        &key,
+        capture::Source::Command {
+            command: command.to_string(),
+        },
+    );
+    write_describer(&mut describer)
+}
+
+/// add_to adds description to key, the key path is written as (see
+/// written_key), in describer according to mode, along with tags, privacy,
+/// and the path's current metadata.
+fn add_to(
+    describer: &mut Describer,
+    key: &str,
+    path: &str,
+    description: &str,
+    tags: &[String],
+    private: bool,
+    mode: AddMode,
+) {
+    match mode {
+        AddMode::Append => describer.append_description(key, description),
+        AddMode::Prepend => describer.prepend_description(key, description),
+        AddMode::Add | AddMode::Force => {
+            match describer.description(key) {
+                Some(old) if mode == AddMode::Add && old != description => {
+                    warn(trf("replaced-description", &[&path, &old]))
+                }
+                _ => {}
+            }
+            describer.add_description(key, description);
+        }
+    }
+    describer.stamp(key, def::trash::now());
+    describer.add_tags(key, tags);
+    if private {
+        describer.set_private(key, true);
+    }
+    if let Ok(metadata) = PathMetadata::read(path) {
+        describer.record_metadata(key, metadata);
     }
+}
 
-    fs::write(
-        &config_file(),
-        describer
-            .to_json(JSON_PRETTY)
-            .extract_or_exit("failed to create config"),
-    )
-    .extract_or_exit("failed to write config to file")
+/// refresh runs the commands capturing the description of path, or of every
+/// description captured from a command if path is None, and replaces the
+/// descriptions with their output. The refreshed description of path is
+/// printed. Exits if commands aren't allowed, if path's description isn't
+/// captured from a command, or if a command fails.
+fn refresh(path: Option<&str>) {
+    require_commands_allowed();
+    let lock = lock_config();
+    let mut describer = get_describer();
+    let paths: Vec<String> = match path {
+        Some(path) => {
+            let path = absolute_path(path);
+            if describer.source(&path).command().is_none() {
+                eprintln!("{}: {}", error_label(), trf("not-from-command", &[&path]));
+                process::exit(1);
+            }
+            vec![path]
+        }
+        None => describer
+            .commands()
+            .into_iter()
+            .map(|(path, _)| path.to_string())
+            .collect(),
+    };
+
+    let mut refreshed = 0;
+    let mut failed = false;
+    for p in paths.iter() {
+        match capture::refresh(&mut describer, p, command_timeout()) {
+            Ok(true) => {
+                describer.stamp(p, def::trash::now());
+                refreshed += 1;
+            }
+            Ok(false) => {}
+            Err(e) => {
+                warn(trf("failed-refresh", &[p, &e]));
+                failed = true;
+            }
+        }
+    }
+    if refreshed > 0 {
+        write_describer(&mut describer);
+    }
+    drop(lock);
+
+    match path {
+        Some(path) if !failed => print_description(path),
+        Some(_) => {}
+        None => println!("{}", trf("refreshed", &[&refreshed])),
+    }
+    if failed {
+        process::exit(1);
+    }
+}
+
+/// require_commands_allowed exits unless commands capturing descriptions
+/// can be run, so that a shared config can't run commands by surprise.
+fn require_commands_allowed() {
+    if !ALLOW_COMMANDS.get().copied().unwrap_or(false) {
+        eprintln!("{}: {}", error_label(), tr("commands-disabled"));
+        process::exit(1);
+    }
+}
+
+/// command_timeout returns the time commands capturing descriptions are given
+/// to finish.
+fn command_timeout() -> Duration {
+    Duration::from_secs(settings().command_timeout)
+}
+
+/// add_pattern maps a pattern of the given kind to path, or to a glob for
+/// globs, like add_description. If priority is given, it becomes the
+/// priority of patterns of path, which otherwise keep the one they had.
+fn add_pattern(path: &str, pattern: &str, kind: PatternKind, priority: Option<i32>) {
+    let _lock = lock_config();
+    let mut describer = if open_store().exists() {
+        get_describer()
+    } else {
+        Describer::new()
+    };
+
+    let path = match kind {
+        PatternKind::Glob => glob_path(path),
+        _ => written_key(&describer, &absolute_path(path)),
+    };
+    describer
+        .add_pattern_with_kind(&path, pattern, kind)
+        .extract_or_exit(tr("invalid-pattern"));
+    if let Some(priority) = priority {
+        describer.set_priority(&path, priority);
+    }
+
+    write_describer(&mut describer)
+}
+
+/// add_typed_pattern adds a pattern describing children of path of the
+/// given type only, see `Describer::add_typed_pattern`, setting the priority
+/// of path's patterns if given.
+fn add_typed_pattern(path: &str, pattern: &str, kind: FileKind, priority: Option<i32>) {
+    let _lock = lock_config();
+    let mut describer = if open_store().exists() {
+        get_describer()
+    } else {
+        Describer::new()
+    };
+
+    let path = written_key(&describer, &absolute_path(path));
+    describer
+        .add_typed_pattern(path.as_str(), pattern, kind)
+        .extract_or_exit(tr("invalid-pattern"));
+    if let Some(priority) = priority {
+        describer.set_priority(&path, priority);
+    }
+
+    write_describer(&mut describer)
+}
+
+/// add_local_description maps the given description to path in the nearest
+/// local file in path's ancestors (see def::local). If there's none, a local
+/// file is created in the current directory, as long as path is under it.
+fn add_local_description(path: &str, description: &str) {
+    let path = absolute_path(path);
+    let file = match local::discover(&path).pop() {
+        Some(file) => file,
+        None => {
+            let current = absolute_path(".");
+            if !paths::is_under(&path, &current) {
+                eprintln!("{}: {}", error_label(), trf("no-local-file", &[&path]));
+                process::exit(1);
+            }
+            Path::new(&current).join(local::FILE_NAMES[0])
+        }
+    };
+
+    let mut describer = if file.exists() {
+        local::load(&file).extract_or_exit(tr("failed-read-local"))
+    } else {
+        Describer::new()
+    };
+    describer.add_description(&path, description);
+    local::save(&file, &describer).extract_or_exit(tr("failed-write-local"));
+}
+
+/// set_private marks the description of path as private, or as shareable if
+/// private is false. Exits if path has no description.
+fn set_private(path: &str, private: bool) {
+    let _lock = lock_config();
+    let mut describer = get_describer();
+    if !describer.set_private(absolute_path(path), private) {
+        eprintln!("{}: {}", error_label(), tr("no-description"));
+        process::exit(1);
+    }
+    write_describer(&mut describer);
+}
+
+/// edit_description opens the description of path (or an empty buffer if
+/// it has none) in the user's editor, and saves the edited description as
+/// add would. Nothing is saved if the description is left empty or
+/// unchanged.
+fn edit_description(path: &str) {
+    let describer = if open_store().exists() {
+        get_describer()
+    } else {
+        Describer::new()
+    };
+    let current = describer
+        .description(absolute_path(path))
+        .unwrap_or_default();
+
+    let edited = editor::edit(current, ".md").extract_or_exit(tr("failed-run-editor"));
+    let edited = editor::trim_edited(&edited);
+    if edited.trim().is_empty() {
+        eprintln!("{}: {}", error_label(), tr("empty-description"));
+        process::exit(1);
+    } else if edited == current {
+        println!("{}", tr("nothing-changed"));
+    } else {
+        add_description(path, edited, &[], false, AddMode::Force);
+    }
+}
+
+/// edit_config opens the whole config as JSON in the user's editor, and
+/// saves it once the editor exits. If the edited config is invalid, the user
+/// is asked to edit it again, and nothing is saved if they decline.
+fn edit_config() {
+    let describer = if open_store().exists() {
+        read_describer()
+    } else {
+        Describer::new()
+    };
+    let original = describer
+        .to_json(true)
+        .extract_or_exit(tr("failed-read-config"));
+
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    let mut text = original.clone();
+    loop {
+        text = editor::edit(&text, ".json").extract_or_exit(tr("failed-run-editor"));
+        if editor::trim_edited(&text) == editor::trim_edited(&original) {
+            println!("{}", tr("nothing-changed"));
+            return;
+        }
+
+        match parse_config(&text) {
+            Ok(mut edited) => {
+                let _lock = lock_config();
+                write_describer(&mut edited);
+                warn_incompatible_keys(&edited);
+                return;
+            }
+            Err(problems) => {
+                for p in problems.iter() {
+                    eprintln!("{}: {}", error_label(), p);
+                }
+                if !editor::ask_to_retry(&mut input) {
+                    eprintln!("{}", tr("nothing-saved"));
+                    process::exit(1);
+                }
+            }
+        }
+    }
+}
+
+/// parse_config creates a describer from a config edited by edit_config.
+/// Returns descriptions of its problems if it isn't valid JSON or has
+/// invalid patterns.
+fn parse_config(text: &str) -> Result<Describer, Vec<String>> {
+    let describer = Describer::from_versioned_json(text)
+        .map_err(|e| vec![format!("{}: {}", tr("invalid-config"), e)])?;
+    let problems = pattern_problems(&describer);
+    if problems.is_empty() {
+        Ok(describer)
+    } else {
+        Err(problems)
+    }
+}
+
+/// pattern_problems returns descriptions of invalid patterns of describer.
+fn pattern_problems(describer: &Describer) -> Vec<String> {
+    describer
+        .validate_patterns()
+        .iter()
+        .map(|(kind, key, e)| {
+            format!(
+                "{}: {}{}: {}",
+                tr("invalid-pattern"),
+                key,
+                kind_label(Some(*kind)),
+                e
+            )
+        })
+        .collect()
+}
+
+/// export writes all descriptions and patterns to file, in the format of its
+/// extension (JSON if it has none), or prints them as JSON if no file is
+/// given. Trashed entries aren't exported, and neither are private ones
+/// unless include_private is true. If filter or only is given, only entries
+/// matching them are exported. Text matching redaction rules of settings is
+/// masked, see def::redact.
+fn export(
+    file: Option<String>,
+    filter: Option<String>,
+    only: Option<Subset>,
+    include_private: bool,
+) {
+    let filter = Filter::And(vec![parse_filter(filter.as_deref()), subset_filter(only)]);
+    let mut describer = if open_store().exists() {
+        read_describer()
+    } else {
+        Describer::new()
+    };
+    describer.empty_trash();
+    if !include_private {
+        describer.remove_private();
+    }
+    describer.retain_entries(|e| filter.matches(e));
+    let redactor = match settings().redact {
+        Some(rules) => Redactor::new(&rules).extract_or_exit(tr("failed-redact")),
+        None => Redactor::secrets(),
+    };
+    redact::redact(&mut describer, &redactor);
+    match file {
+        Some(file) => describer
+            .save(&file, format::from_path(&file).unwrap_or(&format::Json))
+            .extract_or_exit(tr("failed-export")),
+        None => println!(
+            "{}",
+            describer.to_json(true).extract_or_exit(tr("failed-export"))
+        ),
+    }
+}
+
+/// import merges descriptions and patterns of file (read in the format of
+/// its extension, JSON if it has none) into the config, resolving conflicts
+/// using strategy, and prints what changed. The file is validated before the
+/// config is touched: nothing is imported if it has invalid patterns. If only
+/// is given, other entries of file are ignored.
+fn import(file: &str, strategy: ImportStrategy, only: Option<Subset>) {
+    let mut imported = Describer::load(file, format::from_path(file).unwrap_or(&format::Json))
+        .extract_or_exit(tr("failed-read-import"));
+    let filter = subset_filter(only);
+    imported.retain_entries(|e| filter.matches(e));
+    let problems = pattern_problems(&imported);
+    if !problems.is_empty() {
+        for p in problems.iter() {
+            eprintln!("{}: {}", error_label(), p);
+        }
+        eprintln!("{}", tr("nothing-saved"));
+        process::exit(1);
+    }
+    warn_incompatible_keys(&imported);
+
+    let _lock = lock_config();
+    let mut describer = if open_store().exists() {
+        get_describer()
+    } else {
+        Describer::new()
+    };
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    let mut ask = |change: &Change| ask_to_replace(&mut input, change);
+    let report = describer.merge_with(
+        imported,
+        match strategy {
+            ImportStrategy::Overwrite => Strategy::Overwrite,
+            ImportStrategy::KeepExisting => Strategy::KeepExisting,
+            ImportStrategy::Interactive => Strategy::Ask(&mut ask),
+        },
+    );
+
+    for (changes, key) in [
+        (&report.added, "import-added"),
+        (&report.updated, "import-updated"),
+        (&report.conflicts, "import-kept"),
+    ]
+    .iter()
+    {
+        for change in changes.iter() {
+            println!(
+                "{}{}: {}",
+                paint(Role::Path, &change.path),
+                kind_label(change.pattern),
+                tr(key)
+            );
+        }
+    }
+    if report.changed() {
+        write_describer(&mut describer);
+    }
+    println!(
+        "{}",
+        trf(
+            "imported",
+            &[
+                &report.added.len(),
+                &report.updated.len(),
+                &report.conflicts.len()
+            ]
+        )
+    );
+}
+
+/// apply adds the descriptions of an annotation file, replacing existing
+/// descriptions of its paths, and prints what changed. Nothing is saved if
+/// dry_run is set.
+fn apply(file: &str, dry_run: bool) {
+    let annotations = annotations::read(file).extract_or_exit(tr("failed-read-annotations"));
+    let applied = annotations::to_describer(&annotations);
+    warn_incompatible_keys(&applied);
+
+    let _lock = lock_config();
+    let mut describer = if open_store().exists() {
+        get_describer()
+    } else {
+        Describer::new()
+    };
+    let report = describer.merge_with(applied, Strategy::Overwrite);
+    for (changes, key) in [
+        (&report.added, "apply-created"),
+        (&report.updated, "apply-updated"),
+    ]
+    .iter()
+    {
+        for change in changes.iter() {
+            println!("{}: {}", paint(Role::Path, &change.path), tr(key));
+        }
+    }
+
+    let (created, updated) = (report.added.len(), report.updated.len());
+    if dry_run {
+        println!("{}", trf("would-apply", &[&created, &updated]));
+        return;
+    }
+    if report.changed() {
+        write_describer(&mut describer);
+    }
+    println!("{}", trf("applied", &[&created, &updated]));
+}
+
+/// sync_readmes syncs descriptions of root and directories under it with
+/// files in them, and prints what's synced. Nothing is changed if dry_run is
+/// set.
+fn sync_readmes(root: &str, target: Target, dry_run: bool) {
+    let _lock = lock_config();
+    let mut describer = if open_store().exists() {
+        get_describer()
+    } else {
+        Describer::new()
+    };
+    let changes = readmes::plan(&describer, &absolute_path(root), target)
+        .extract_or_exit(tr("failed-read-root"));
+    for change in changes.iter() {
+        let key = match change.direction {
+            Direction::ToFile => "synced-to-file",
+            Direction::FromFile => "synced-from-file",
+        };
+        println!(
+            "{}: {}",
+            paint(Role::Path, &change.path),
+            trf(key, &[&target.file_name()])
+        );
+    }
+
+    let written = changes
+        .iter()
+        .filter(|c| c.direction == Direction::ToFile)
+        .count();
+    let read = changes.len() - written;
+    if dry_run {
+        println!("{}", trf("would-sync", &[&written, &read]));
+        return;
+    }
+    readmes::apply(&mut describer, &changes, target, def::trash::now())
+        .extract_or_exit(tr("failed-sync"));
+    if read > 0 {
+        write_describer(&mut describer);
+    }
+    println!("{}", trf("synced", &[&written, &read]));
+}
+
+/// ask_to_replace shows both sides of a conflicting import, and returns true
+/// if the user agreed to replace the existing entry. Returns false (the
+/// default) if input is closed.
+fn ask_to_replace<R: BufRead>(input: &mut R, change: &Change) -> bool {
+    println!(
+        "{}{}",
+        paint(Role::Path, &change.path),
+        kind_label(change.pattern)
+    );
+    println!("  - {}", change.existing.as_deref().unwrap_or_default());
+    println!("  + {}", change.incoming);
+    ask(input, tr("replace-existing"))
+}
+
+/// ask prints a yes or no question, and returns true if the user answered
+/// yes in the selected language (see i18n::is_yes). Returns false (the
+/// default) if input is closed.
+fn ask<R: BufRead>(input: &mut R, question: &str) -> bool {
+    print!("{} ", question);
+    let _ = io::stdout().flush();
+    let mut line = String::new();
+    match input.read_line(&mut line) {
+        Ok(0) | Err(_) => false,
+        Ok(_) => i18n::is_yes(&line),
+    }
 }
 
-/// print_description creates a describer using config_file, and prints
-/// a description of the specified path. If no description exists, an error
-/// message is printed.
+/// print_description creates a describer using config_file, and prints a
+/// description of the specified path. If no description exists, an error
+/// message is printed. The daemon is asked instead if it's running.
 fn print_description(path: &str) {
-    let describer = get_describer();
     let path = absolute_path(path);
+    let lookup = ask_daemon_to_describe(&path).unwrap_or_else(|| {
+        let describer = get_path_describer(&path);
+        daemon::lookup(&describer, &path, !no_stat(), inherit())
+    });
+    if output::is_structured() {
+        output::print_record(&Record {
+            path: &path,
+            description: lookup.description.as_deref(),
+            source: Source::of_lookup(&lookup),
+            inherited_from: lookup.inherited_from.as_deref(),
+        });
+        return;
+    }
     println!(
         "{}",
-        match describer.describe(&path) {
-            Some(description) => format!("{}: {}", path.green(), description),
-            None => format!("{}: {}", "Err".red(), "no available description"),
+        match lookup_text(lookup) {
+            Some(description) => format_description(&path, &description),
+            None => format!("{}: {}", error_label(), tr("no-description")),
         }
     )
 }
 
-/// get_describer loads JSON from config_file, creates a describer and
-/// returns it. Exits on error.
-fn get_describer() -> def::Describer {
-    Describer::new_from_json(
-        &fs::read_to_string(&config_file()).extract_or_exit("failed to read config"),
-    )
-    .extract_or_exit("invalid JSON config")
+/// explain prints the descriptions and patterns applying to path: the one
+/// used to describe it, followed by those that also apply but lose to it.
+fn explain(path: &str) {
+    let path = absolute_path(path);
+    let describer = get_path_describer(&path);
+    let candidates = if no_stat() {
+        describer.explain(&path)
+    } else {
+        describer.explain_with_fs(&path)
+    };
+    if output::is_structured() {
+        output::print_candidates(&candidates);
+        return;
+    }
+
+    let order = describer.resolution_order();
+    if !order.is_default() {
+        print_section(tr("section-resolution"), &[order.to_string()]);
+    }
+
+    let lines: Vec<String> = candidates
+        .iter()
+        .map(|c| {
+            let priority = match c.priority {
+                0 => String::new(),
+                p => format!(" ({})", trf("priority", &[&p])),
+            };
+            format!(
+                "{}{}{}: {}",
+                paint(Role::Path, &c.key),
+                kind_label(c.pattern),
+                priority,
+                paint(Role::Description, &output::summary(&c.description, None))
+            )
+        })
+        .collect();
+    match lines.split_first() {
+        Some((used, overridden)) => {
+            print_section(tr("section-used"), std::slice::from_ref(used));
+            let result = if no_stat() {
+                describer.describe_detailed(&path)
+            } else {
+                describer.describe_detailed_with_fs(&path)
+            };
+            let substitutions: Vec<String> = result
+                .map(|r| r.substitutions)
+                .unwrap_or_default()
+                .iter()
+                .map(|s| format!("{} = {}", s.placeholder, s.value))
+                .collect();
+            print_section(tr("section-placeholders"), &substitutions);
+            print_section(tr("section-overridden"), overridden);
+        }
+        None => println!("{}: {}", error_label(), tr("no-description")),
+    }
 }
 
-/// config_file returns path to configuration file.
-fn config_file() -> String {
-    format!(
-        "{}/.config/def/config.json",
-        env::var("HOME").extract_or_exit("failed to get $HOME"),
-    )
+/// info prints everything known about path in one panel: its description and
+/// where it comes from, its tags, and its current metadata.
+fn info(path: &str) {
+    let path = existing_or_absolute_path(path);
+    let describer = get_lookup_describer_for(&path);
+    let result = if no_stat() {
+        describer.describe_detailed(&path)
+    } else {
+        describer.describe_detailed_with_fs(&path)
+    };
+    let result = result.or_else(|| match (inherit(), no_stat()) {
+        (false, _) => None,
+        (true, true) => describer.describe_inherited_detailed(&path),
+        (true, false) => describer.describe_inherited_detailed_with_fs(&path),
+    });
+    if output::is_structured() {
+        output::print_record(&Record {
+            path: &path,
+            description: result.as_ref().map(|r| r.description.as_str()),
+            source: result.as_ref().map(|r| match r.kind {
+                MatchKind::Specific => Source::Specific,
+                MatchKind::Inherited => Source::Inherited,
+                _ => Source::Pattern,
+            }),
+            inherited_from: result
+                .as_ref()
+                .filter(|r| r.kind == MatchKind::Inherited)
+                .map(|r| r.key.as_str()),
+        });
+        return;
+    }
+
+    println!("{}", paint(Role::Path, &path));
+    match &result {
+        Some(result) => {
+            let rendered = markdown::render(&result.description);
+            let lines: Vec<String> = rendered
+                .lines()
+                .map(|l| paint(Role::Description, l).to_string())
+                .collect();
+            print_section(tr("section-description"), &lines);
+            print_section(tr("section-provenance"), &provenance(&describer, result));
+        }
+        None => println!("{}: {}", error_label(), tr("no-description")),
+    }
+    let tags = describer.tags_of(path.as_str());
+    if !tags.is_empty() {
+        print_section(tr("section-tags"), &[tags.join(", ")]);
+    }
+    print_section(tr("section-file"), &file_details(&describer, &path));
 }
 
-/// config_dir returns path to directory containing configuration file.
-fn config_dir() -> String {
-    format!(
-        "{}/.config/def",
-        env::var("HOME").extract_or_exit("failed to get $HOME"),
-    )
+/// provenance returns lines telling where the description of a path comes
+/// from: the entry used, and for descriptions, how and when they were
+/// written.
+fn provenance(describer: &Describer, result: &def::DescribeResult) -> Vec<String> {
+    let key = paint(Role::Path, &result.key).to_string();
+    let mut lines = vec![match result.kind {
+        MatchKind::Specific => tr("info-specific").to_string(),
+        MatchKind::Inherited => trf("info-inherited", &[&key]),
+        MatchKind::Repository => tr("info-repository").to_string(),
+        MatchKind::ParentPattern => trf("info-pattern", &[&key, &PatternKind::Children]),
+        MatchKind::RecursivePattern => trf("info-pattern", &[&key, &PatternKind::Recursive]),
+        MatchKind::Glob => trf("info-pattern", &[&key, &PatternKind::Glob]),
+    }];
+    if !matches!(result.kind, MatchKind::Specific | MatchKind::Inherited) {
+        return lines;
+    }
+
+    if let Some(command) = describer.source(result.key.as_str()).command() {
+        lines.push(trf("info-command", &[&command]));
+    }
+    if let Some(t) = describer.timestamps(result.key.as_str()) {
+        let now = def::trash::now();
+        let time = |t: u64| {
+            format!(
+                "{} ({})",
+                meta::human_time(UNIX_EPOCH + Duration::from_secs(t)),
+                elapsed(now.saturating_sub(t))
+            )
+        };
+        lines.push(trf("info-added", &[&time(t.created)]));
+        if t.updated != t.created {
+            lines.push(trf("info-updated", &[&time(t.updated)]));
+        }
+    }
+    if describer.is_private(result.key.as_str()) {
+        lines.push(tr("info-private").to_string());
+    }
+    lines
 }
 
-/// absolute_path takes a path and returns its absolute representation.
-/// Exits on failure (if path doesn't exist).
-fn absolute_path(path: &str) -> String {
-    match fs::canonicalize(path)
-        .extract_or_exit("failed to get absolute path")
-        .to_str()
+/// file_details returns lines describing the current metadata of path, and
+/// the metadata recorded when it was described if it contradicts them.
+/// Nothing but the recorded metadata is read if --no-stat is set.
+fn file_details(describer: &Describer, path: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+    let recorded = describer.metadata(path);
+    if no_stat() {
+        lines.extend(recorded.map(|m| trf("info-recorded", &[&describe_metadata(m)])));
+        return lines;
+    }
+    let info = match FileInfo::read(path) {
+        Ok(info) => info,
+        Err(_) => {
+            lines.push(tr("info-missing").to_string());
+            return lines;
+        }
+    };
+
+    lines.push(format!("{}, {}", info.kind, info.display_size()));
+    if let Some(modified) = info.modified {
+        lines.push(trf("info-modified", &[&meta::human_time(modified)]));
+    }
+    #[cfg(unix)]
     {
-        Some(p) => p.to_string(),
+        if let Ok(owner) = meta::owner(path) {
+            lines.push(trf("info-owner", &[&owner]));
+        }
+        if let Ok(mount) = meta::mount_point(path) {
+            lines.push(trf("info-mount", &[&mount.display()]));
+        }
+    }
+    let contradicted = recorded.filter(|m| {
+        PathMetadata::read(path)
+            .map(|current| m.contradicts(&current))
+            .unwrap_or(false)
+    });
+    if let Some(m) = contradicted {
+        lines.push(trf("info-recorded", &[&describe_metadata(m)]));
+    }
+    lines
+}
+
+/// format_description returns path followed by its description, rendered
+/// for the terminal. Descriptions spanning multiple lines, or too long to
+/// follow the path on its line, start on the next line instead, wrapped to
+/// the terminal's width and indented.
+fn format_description(path: &str, description: &str) -> String {
+    let rendered = markdown::render(description);
+    match output::terminal_width().filter(|_| !raw()) {
+        Some(width)
+            if rendered.contains('\n')
+                || path.chars().count() + 2 + output::visible_width(&rendered) > width =>
+        {
+            format!(
+                "{}:\n{}",
+                paint(Role::Path, path),
+                wrap_description(&rendered, width, output::INDENT)
+            )
+        }
+        _ => format!(
+            "{}: {}",
+            paint(Role::Path, path),
+            paint(Role::Description, &rendered)
+        ),
+    }
+}
+
+/// wrap_description returns a rendered description wrapped to width, with
+/// lines indented by indent spaces and colored as descriptions.
+fn wrap_description(rendered: &str, width: usize, indent: usize) -> String {
+    output::wrap(rendered, width.saturating_sub(indent))
+        .iter()
+        .map(|line| match line.as_str() {
+            "" => String::new(),
+            _ => format!("{}{}", " ".repeat(indent), paint(Role::Description, line)),
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// print_description_quietly prints the description of path, and exits
+/// with code 1 if it has none. Nothing else is printed, not even errors or
+/// warnings, so that shell hooks (see "def hook") stay silent for paths
+/// without descriptions. The daemon is asked instead if it's running.
+fn print_description_quietly(path: &str) {
+    let description = if no_resolve() {
+        paths::lexical(path)
+    } else {
+        paths::absolute(path)
+    }
+    .ok()
+    .and_then(|path| {
+        let lookup = match ask_daemon_to_describe(&path) {
+            Some(lookup) => lookup,
+            None => {
+                let mut describer = config::load_for_lookup(&settings()).ok()?;
+                local::merge_into(&mut describer, &path).ok()?;
+                daemon::lookup(&describer, &path, !no_stat(), inherit())
+            }
+        };
+        lookup_text(lookup)
+    });
+    match description {
+        Some(description) => {
+            let rendered = markdown::render(&description);
+            match output::terminal_width().filter(|_| !raw()) {
+                Some(width) => println!("{}", wrap_description(&rendered, width, 0)),
+                None => println!("{}", paint(Role::Description, &rendered)),
+            }
+        }
+        None => process::exit(1),
+    }
+}
+
+/// print_hook prints the snippet of "def hook" for shell.
+fn print_hook(shell: &str) {
+    match hook::Shell::from_name(shell) {
+        Some(shell) => print!("{}", shell.snippet()),
+        None => {
+            eprintln!("{}: {}", error_label(), trf("unknown-shell", &[&shell]));
+            process::exit(1);
+        }
+    }
+}
+
+/// print_completions prints the completion script of "def completions" for
+/// shell.
+fn print_completions(shell: &str) {
+    let words: Vec<&str> = help::COMMANDS
+        .iter()
+        .map(|c| c.name)
+        .filter(|name| !name.is_empty())
+        .chain(help::GLOBAL_FLAGS.iter().map(|f| f.name))
+        .collect();
+    match hook::Shell::from_name(shell) {
+        Some(shell) => print!("{}", shell.completions(&words)),
         None => {
-            eprintln!("{}: {}", "Err".red(), "path contains invalid chars");
+            eprintln!("{}: {}", error_label(), trf("unknown-shell", &[&shell]));
             process::exit(1);
         }
     }
 }
+
+/// print_candidates prints described paths starting with prefix, one per
+/// line, for completion scripts (see "def completions"). Paths described by
+/// descriptions and by patterns are listed, but not globs. Errors print
+/// nothing, so that completing falls back to files.
+fn print_candidates(prefix: &str) {
+    let describer = match config::load_for_lookup(&settings()) {
+        Ok(describer) => describer,
+        Err(_) => return,
+    };
+    let cwd = env::current_dir()
+        .map(|dir| dir.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let home = config::home_dir()
+        .ok()
+        .map(|dir| dir.to_string_lossy().to_string());
+    let paths = describer
+        .iter_descriptions()
+        .chain(describer.iter_patterns(PatternKind::Children))
+        .chain(describer.iter_patterns(PatternKind::Recursive))
+        .map(|(path, _)| path);
+    for candidate in hook::candidates(prefix, paths, &cwd, home.as_deref()) {
+        println!("{}", candidate);
+    }
+}
+
+/// print_descriptions_from_stdin reads newline-separated paths from stdin
+/// and prints a line for each: its description with newlines replaced by
+/// spaces, or an empty line if it has none. The daemon is asked if it's
+/// running. Otherwise, local files are looked up from the current directory
+/// only, so that the config is read once.
+fn print_descriptions_from_stdin() {
+    let current = existing_or_absolute_path(".");
+    // Loaded once the daemon doesn't answer.
+    let mut describer = None;
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    let mut out = io::BufWriter::new(stdout.lock());
+    for line in stdin.lock().lines() {
+        let line = line.extract_or_exit(tr("failed-read-stdin"));
+        let path = if line.is_empty() {
+            None
+        } else if no_resolve() {
+            paths::lexical(&line).ok()
+        } else {
+            paths::absolute_or_joined(&line).ok()
+        };
+        let description = path.and_then(|p| {
+            let lookup = ask_daemon_to_describe(&p).unwrap_or_else(|| {
+                let describer = describer.get_or_insert_with(|| get_lookup_describer_for(&current));
+                daemon::lookup(describer, &p, !no_stat(), inherit())
+            });
+            lookup_text(lookup)
+        });
+        let description = description.unwrap_or_default().replace('\n', " ");
+        if writeln!(out, "{}", description).is_err() {
+            // Stdout was closed, such as by "| head".
+            return;
+        }
+    }
+}
+
+/// parse_filter parses the query of --where, which matches every entry if
+/// it isn't given. Exits if the query is invalid.
+fn parse_filter(query: Option<&str>) -> Filter {
+    Filter::parse(query.unwrap_or_default()).unwrap_or_else(|e| {
+        eprintln!("{}: {}: {}", error_label(), tr("invalid-query"), e);
+        process::exit(1);
+    })
+}
+
+/// subset_filter returns a filter matching entries of subset, or all entries
+/// if it isn't given (see --only).
+fn subset_filter(subset: Option<Subset>) -> Filter {
+    match subset {
+        None => Filter::All,
+        Some(Subset::Descriptions) => Filter::Kind(None),
+        Some(Subset::Patterns) => Filter::Not(Box::new(Filter::Kind(None))),
+    }
+}
+
+/// source_of returns the source of the description of path, which is
+/// expected to be described by describer.
+fn source_of(describer: &Describer, path: &str) -> Source {
+    match describer.description(path) {
+        Some(_) => Source::Specific,
+        None => Source::Pattern,
+    }
+}
+
+/// list prints all descriptions and patterns as a tree. If prefix is given,
+/// only descriptions and patterns of prefix and paths under it are printed,
+/// and if filter is given, only ones matching it. If depth is given, only
+/// that many levels of the tree are printed. The daemon is asked if it's
+/// running.
+fn list(prefix: Option<String>, depth: Option<usize>, filter: Option<String>) {
+    let filter = parse_filter(filter.as_deref());
+    let prefix = match prefix {
+        Some(p) => existing_or_absolute_path(&p),
+        None => String::new(),
+    };
+    let dir = if prefix.is_empty() {
+        existing_or_absolute_path(".")
+    } else {
+        prefix.clone()
+    };
+    let request = Request::List {
+        prefix: prefix.clone(),
+        dir: Some(dir.clone()),
+    };
+    if let Some(items) = ask_daemon_for_entries(&request) {
+        let mut entries: Vec<Entry> = items.iter().map(Item::entry).collect();
+        entries.retain(|e| filter.matches(e));
+        print_entries(&entries, depth);
+        return;
+    }
+
+    let describer = get_lookup_describer_for(&dir);
+    let mut entries = describer.entries_under(&prefix);
+    entries.retain(|e| filter.matches(e));
+    print_entries(&entries, depth);
+}
+
+/// list_tagged prints descriptions tagged with tag, like list.
+fn list_tagged(tag: &str) {
+    let describer = get_lookup_describer_for(&existing_or_absolute_path("."));
+    print_entries(&describer.find_by_tag(tag), None);
+}
+
+/// print_entries prints entries as records in structured formats, as lines
+/// in plain output, and as a tree (up to depth, if given) otherwise.
+fn print_entries(entries: &[Entry], depth: Option<usize>) {
+    if output::is_structured() {
+        let records: Vec<Record> = entries
+            .iter()
+            .map(|entry| Record {
+                path: entry.path,
+                description: Some(entry.description),
+                source: Some(Source::of(entry.pattern)),
+                inherited_from: None,
+            })
+            .collect();
+        output::print_records(&records);
+    } else if theme::is_plain() {
+        for entry in entries.iter() {
+            println!(
+                "{}{}: {}",
+                entry.path,
+                kind_label(entry.pattern),
+                output::summary(entry.description, None)
+            );
+        }
+    } else {
+        tree::print(&VirtualTree::new(entries), depth);
+    }
+}
+
+/// search prints descriptions and patterns matching query, best matches
+/// first, asking the daemon if it's running. Words of query match as set by
+/// options. The search index is used only for strict searches, since it
+/// doesn't support other matches. If filter is given, only matches of it are
+/// printed, and query may be empty to print all of them, sorted by path.
+fn search(query: &str, options: &SearchOptions, filter: Option<String>) {
+    let filter = parse_filter(filter.as_deref());
+    let current = existing_or_absolute_path(".");
+    let request = if query.is_empty() {
+        Some(Request::List {
+            prefix: String::new(),
+            dir: Some(current.clone()),
+        })
+    } else if options.is_strict() || *options == SearchOptions::fuzzy() {
+        Some(Request::Search {
+            text: query.to_string(),
+            fuzzy: !options.is_strict(),
+            dir: Some(current.clone()),
+        })
+    } else {
+        None
+    };
+    let items = request.and_then(|request| ask_daemon_for_entries(&request));
+
+    let describer;
+    let mut matches = match &items {
+        Some(items) => items.iter().map(Item::search_match).collect(),
+        None if query.is_empty() => {
+            describer = get_lookup_describer_for(&current);
+            describer
+                .entries_under("")
+                .into_iter()
+                .map(|e| Match {
+                    path: e.path,
+                    text: e.description,
+                    pattern: e.pattern,
+                    score: 0,
+                })
+                .collect()
+        }
+        None if options.is_strict() && settings().search_index => {
+            describer = get_lookup_describer_for(&current);
+            let cache = config::search_index_file().extract_or_exit(tr("failed-get-home"));
+            describer.search_indexed(query, Some(&current), cache)
+        }
+        None => {
+            describer = get_lookup_describer_for(&current);
+            describer.search_with(query, options)
+        }
+    };
+    matches.retain(|m| {
+        filter.matches(&Entry {
+            path: m.path,
+            description: m.text,
+            pattern: m.pattern,
+        })
+    });
+    if matches.is_empty() {
+        println!("{}: {}", error_label(), tr("no-matches"));
+    }
+    for m in matches.iter() {
+        println!(
+            "{}{}: {}",
+            paint(Role::Path, m.path),
+            kind_label(m.pattern),
+            paint(Role::Description, m.text)
+        );
+    }
+}
+
+/// save_search saves query as the search named name in settings.
+fn save_search(name: &str, query: &str) {
+    config::save_search(name, query).extract_or_exit(tr("failed-save-search"));
+    println!(
+        "{}",
+        trf(
+            "saved-search",
+            &[&format!("{}{}", command::SAVED_SEARCH_PREFIX, name)]
+        )
+    );
+}
+
+/// list_saved_searches prints saved searches and their queries, sorted by
+/// name.
+fn list_saved_searches() {
+    let settings = settings();
+    if settings.searches.is_empty() {
+        println!("{}: {}", error_label(), tr("no-saved-searches"));
+    }
+    for (name, query) in settings.searches.iter() {
+        println!(
+            "{}: {}",
+            paint(
+                Role::Path,
+                &format!("{}{}", command::SAVED_SEARCH_PREFIX, name)
+            ),
+            query
+        );
+    }
+}
+
+/// run_saved_search prints descriptions and patterns matching the query saved
+/// as name, like search.
+fn run_saved_search(name: &str) {
+    match settings().saved_search(name) {
+        Some(query) => search(query, &SearchOptions::default(), None),
+        None => {
+            eprintln!(
+                "{}: {}",
+                error_label(),
+                trf("unknown-saved-search", &[&name])
+            );
+            process::exit(1);
+        }
+    }
+}
+
+/// ls prints non-hidden entries of dir (or of the current directory) sorted by
+/// name, each followed by its description if it has one. Names of directories
+/// end with a separator, and descriptions are aligned in a second column.
+fn ls(dir: Option<String>) {
+    let dir = absolute_path(&dir.unwrap_or_else(|| ".".to_string()));
+    let describer = get_lookup_describer_for(&dir);
+
+    let mut entries = Vec::new();
+    for entry in fs::read_dir(&dir).extract_or_exit(tr("failed-read-dir")) {
+        let entry = entry.extract_or_exit(tr("failed-read-dir"));
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with('.') {
+            continue;
+        }
+        let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+        entries.push((name, is_dir));
+    }
+    entries.sort();
+
+    let names: Vec<&str> = entries.iter().map(|(name, _)| name.as_str()).collect();
+    let descriptions = if no_stat() {
+        describer.describe_dir_entries(&dir, &names)
+    } else {
+        describer.describe_dir_entries_with_fs(&dir, &names)
+    };
+    if output::is_structured() {
+        let dir = def::DefPath::new(&dir);
+        let paths: Vec<def::DefPath> = names.iter().map(|name| dir.join(name)).collect();
+        let records: Vec<Record> = paths
+            .iter()
+            .zip(descriptions.iter())
+            .map(|(path, description)| Record {
+                path: path.as_str(),
+                description: description.as_deref(),
+                source: description
+                    .as_ref()
+                    .map(|_| source_of(&describer, path.as_str())),
+                inherited_from: None,
+            })
+            .collect();
+        output::print_records(&records);
+        return;
+    }
+    let labels: Vec<String> = entries
+        .iter()
+        .map(|(name, is_dir)| {
+            if *is_dir {
+                format!("{}{}", name, std::path::MAIN_SEPARATOR)
+            } else {
+                name.to_string()
+            }
+        })
+        .collect();
+    let width = labels.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+    let summary_width = output::terminal_width().map(|w| w.saturating_sub(width + 2));
+
+    for (label, description) in labels.iter().zip(descriptions.iter()) {
+        match description {
+            Some(d) if theme::is_plain() => println!("{}: {}", label, output::summary(d, None)),
+            Some(d) => println!(
+                "{}{}  {}",
+                paint(Role::Path, label),
+                " ".repeat(width - label.chars().count()),
+                paint(Role::Description, &output::summary(d, summary_width))
+            ),
+            None => println!("{}", label),
+        }
+    }
+}
+
+/// drift prints a report of described paths under root that no longer exist
+/// or whose metadata changed drastically, and of dirs in root that lack
+/// descriptions.
+fn drift(root: &str) {
+    let describer = get_lookup_describer();
+    let report = def::drift::report(&describer, &absolute_path(root))
+        .extract_or_exit(tr("failed-read-root"));
+    if report.is_empty() {
+        println!("{}", tr("no-drift"));
+        return;
+    }
+
+    print_section(tr("section-missing"), &report.missing);
+    print_section(
+        tr("section-changed"),
+        &report
+            .changed
+            .iter()
+            .map(|c| {
+                trf(
+                    "drift-changed",
+                    &[
+                        &c.path,
+                        &describe_metadata(&c.recorded),
+                        &describe_metadata(&c.current),
+                    ],
+                )
+            })
+            .collect::<Vec<String>>(),
+    );
+    print_section(tr("section-undescribed"), &report.undescribed);
+}
+
+/// stats prints counts of descriptions and patterns, the deepest described
+/// path, and the descriptions added last, followed by how many entries of dir
+/// can be described if dir is given.
+fn stats(dir: Option<String>) {
+    let dir = dir.map(|dir| absolute_path(&dir));
+    let describer = match &dir {
+        Some(dir) => get_lookup_describer_for(dir),
+        None => get_lookup_describer(),
+    };
+    let summary = def::stats::summarize(&describer, RECENT_SHOWN);
+    print_section(
+        tr("section-totals"),
+        &[
+            trf("stats-descriptions", &[&summary.descriptions]),
+            trf(
+                "stats-patterns",
+                &[
+                    &summary.all_patterns(),
+                    &summary.patterns,
+                    &summary.recursive_patterns,
+                    &summary.globs,
+                ],
+            ),
+        ],
+    );
+    if let Some(deepest) = summary.deepest {
+        print_section(
+            tr("section-deepest"),
+            &[paint(Role::Path, deepest).to_string()],
+        );
+    }
+    let now = def::trash::now();
+    print_section(
+        tr("section-recent"),
+        &summary
+            .recent
+            .iter()
+            .map(|(path, added)| {
+                format!(
+                    "{} ({})",
+                    paint(Role::Path, path),
+                    elapsed(now.saturating_sub(*added))
+                )
+            })
+            .collect::<Vec<String>>(),
+    );
+
+    if let Some(dir) = dir {
+        let coverage =
+            def::stats::coverage(&describer, &dir).extract_or_exit(tr("failed-read-dir"));
+        let percent = format!("{:.0}%", coverage.fraction() * 100.0);
+        print_section(
+            &trf("section-coverage", &[&dir]),
+            &[trf(
+                "stats-coverage",
+                &[&coverage.described(), &coverage.entries, &percent],
+            )],
+        );
+        print_section(tr("section-undescribed"), &coverage.undescribed);
+    }
+}
+
+/// undo reverts the last journaled change of the config, and prints what was
+/// reverted.
+fn undo() {
+    let mutation = match config::undo(&settings()).extract_or_exit(tr("failed-undo")) {
+        Some(mutation) => mutation,
+        None => {
+            println!("{}", tr("nothing-to-undo"));
+            return;
+        }
+    };
+    let when = elapsed(def::trash::now().saturating_sub(mutation.time));
+    print_section(
+        &trf("undone", &[&mutation.op, &when]),
+        &mutation
+            .changes
+            .iter()
+            .map(|change| {
+                let path = paint(Role::Path, &change.path);
+                let kind = kind_label(change.pattern);
+                match (&change.before, &change.after) {
+                    (None, _) => trf("undo-removed", &[&path, &kind]),
+                    (Some(_), None) => trf("undo-restored", &[&path, &kind]),
+                    (Some(before), Some(_)) => trf("undo-reverted", &[&path, &kind, before]),
+                }
+            })
+            .collect::<Vec<String>>(),
+    );
+}
+
+/// sync_remote syncs the config with the remote set in settings (see
+/// def::sync), pushing the merged config if push is true, and prints the
+/// changes pulled and pushed, and the conflicts.
+#[cfg(feature = "sync")]
+fn sync_remote(push: bool) {
+    let report = config::sync(&settings(), push).extract_or_exit(tr("failed-sync-remote"));
+    let changes = |events: &[def::event::Event]| -> Vec<String> {
+        events
+            .iter()
+            .map(|e| {
+                let text = e.after.as_deref().unwrap_or(tr("sync-removed"));
+                format!(
+                    "{}{}: {}",
+                    paint(Role::Path, &e.path),
+                    kind_label(e.pattern),
+                    text
+                )
+            })
+            .collect()
+    };
+    print_section(tr("section-pulled"), &changes(&report.pulled));
+    if push {
+        print_section(tr("section-pushed"), &changes(&report.pushed));
+    }
+    print_section(
+        tr("section-conflicts"),
+        &report
+            .conflicts
+            .iter()
+            .map(|c| {
+                let (key, discarded) = match c.kept {
+                    Side::Local => ("sync-kept-local", &c.remote),
+                    Side::Remote => ("sync-kept-remote", &c.local),
+                };
+                let discarded = discarded.as_deref().unwrap_or(tr("sync-removed"));
+                let path = paint(Role::Path, &c.path);
+                trf(key, &[&path, &kind_label(c.pattern), &discarded])
+            })
+            .collect::<Vec<String>>(),
+    );
+
+    let (pulled, pushed, conflicts) = (
+        report.pulled.len(),
+        report.pushed.len(),
+        report.conflicts.len(),
+    );
+    println!(
+        "{}",
+        if push {
+            trf("synced-push", &[&pulled, &pushed, &conflicts])
+        } else {
+            trf("synced-pull", &[&pulled, &conflicts])
+        }
+    );
+}
+
+/// sync_remote prints an error message, as def was built without support
+/// for remotes.
+#[cfg(not(feature = "sync"))]
+fn sync_remote(_: bool) {
+    eprintln!("{}: {}", error_label(), tr("sync-unsupported"));
+    process::exit(1);
+}
+
+/// history prints the journaled changes of the entries of path, oldest
+/// first, each under the time it was made and the operation that made it.
+fn history(path: &str) {
+    let path = existing_or_absolute_path(path);
+    let file = config::journal_file(&settings()).extract_or_exit(tr("failed-read-history"));
+    let mutations = def::journal::read(file).extract_or_exit(tr("failed-read-history"));
+    let changes = def::journal::history_of(&mutations, &path);
+    if changes.is_empty() {
+        println!("{}", trf("no-history", &[&path]));
+        return;
+    }
+
+    let now = def::trash::now();
+    for (mutation, change) in changes {
+        let heading = format!(
+            "{} ({}), def {}",
+            meta::human_time(UNIX_EPOCH + Duration::from_secs(mutation.time)),
+            elapsed(now.saturating_sub(mutation.time)),
+            mutation.op
+        );
+        let text = match &change.after {
+            Some(after) => paint(Role::Description, after).to_string(),
+            None => tr("history-removed").to_string(),
+        };
+        print_section(
+            &heading,
+            &[format!("{}{}", text, kind_label(change.pattern))],
+        );
+    }
+}
+
+/// maintain backs up the config, deletes expired trash and stale metadata,
+/// and checks root (or the root set in settings, or $HOME) for drift. A JSON
+/// summary is printed to stdout, and the process exits with
+/// ATTENTION_EXIT_CODE if described paths are missing or changed.
+fn maintain(root: Option<String>) {
+    let settings = settings();
+    let rotation = backup::rotate(
+        &config_file(),
+        &config_dir("backups"),
+        settings.backups_kept,
+    )
+    .extract_or_exit(tr("failed-back-up-config"));
+
+    let _lock = lock_config();
+    let mut describer = get_describer();
+    let expired = describer.expire_trash(config::trash_retention(&settings));
+    let collected = describer.collect_garbage();
+    if expired + collected > 0 {
+        write_describer(&mut describer);
+    }
+
+    let root = root.or(settings.maintain_root).unwrap_or_else(|| {
+        config::home_dir()
+            .extract_or_exit(tr("failed-get-home"))
+            .to_string_lossy()
+            .to_string()
+    });
+    let report = def::drift::report(&get_lookup_describer(), &absolute_path(&root))
+        .extract_or_exit(tr("failed-read-root"));
+    let needs_attention = !report.missing.is_empty() || !report.changed.is_empty();
+
+    let summary = serde_json::json!({
+        "backup": rotation.created,
+        "deleted_backups": rotation.deleted,
+        "expired_trash": expired,
+        "deleted_metadata": collected,
+        "drift": {
+            "missing": report.missing,
+            "changed": report.changed.iter().map(|c| &c.path).collect::<Vec<&String>>(),
+            "undescribed": report.undescribed,
+        },
+        "needs_attention": needs_attention,
+    });
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&summary).extract_or_exit(tr("failed-create-summary"))
+    );
+
+    if needs_attention {
+        process::exit(ATTENTION_EXIT_CODE);
+    }
+}
+
+/// stale prints descriptions whose paths were modified more than days after
+/// the descriptions were last updated, and descriptions of missing paths.
+fn stale(days: u64) {
+    let describer = get_describer();
+    let stale = def::drift::stale(&describer, Duration::from_secs(days * 24 * 60 * 60));
+    if stale.is_empty() {
+        println!("{}", tr("no-stale"));
+        return;
+    }
+
+    let now = def::trash::now();
+    let (missing, modified): (Vec<_>, Vec<_>) = stale.iter().partition(|s| s.modified.is_none());
+    print_section(
+        tr("section-missing"),
+        &missing.iter().map(|s| s.path.clone()).collect::<Vec<_>>(),
+    );
+    print_section(
+        tr("section-modified"),
+        &modified
+            .iter()
+            .map(|s| {
+                let ago = |time: Option<u64>| elapsed(now.saturating_sub(time.unwrap_or(0)));
+                trf(
+                    "stale-modified",
+                    &[&s.path, &ago(s.updated), &ago(s.modified)],
+                )
+            })
+            .collect::<Vec<_>>(),
+    );
+}
+
+/// check prints descriptions and patterns mapped to paths that no longer
+/// exist, and exits with ATTENTION_EXIT_CODE if there are any. With prune,
+/// they're deleted instead, and printed as a summary of what was deleted.
+fn check(prune: bool) {
+    let line = |path: &str, pattern: Option<PatternKind>, description: &str| {
+        format!("{}{}: {}", path, kind_label(pattern), description)
+    };
+
+    if prune {
+        let _lock = lock_config();
+        let mut describer = get_describer();
+        let removed = describer.prune_with(def::drift::is_dead);
+        if removed.is_empty() {
+            println!("{}", tr("no-dead"));
+            return;
+        }
+        write_describer(&mut describer);
+        print_section(
+            tr("section-deleted"),
+            &removed
+                .iter()
+                .map(|r| line(&r.path, r.pattern, &r.description))
+                .collect::<Vec<_>>(),
+        );
+        println!("{}", trf("pruned", &[&removed.len()]));
+        return;
+    }
+
+    let describer = get_describer();
+    let dead: Vec<String> = describer
+        .entries_under("")
+        .iter()
+        .filter(|e| def::drift::is_dead(e))
+        .map(|e| line(e.path, e.pattern, e.description))
+        .collect();
+    if dead.is_empty() {
+        println!("{}", tr("no-dead"));
+        return;
+    }
+    print_section(tr("section-missing"), &dead);
+    println!("{}", tr("prune-hint"));
+    process::exit(ATTENTION_EXIT_CODE);
+}
+
+/// follow_events checks config_file for changes every EVENTS_INTERVAL, and
+/// prints changed descriptions and patterns as NDJSON events (see
+/// def::event), until interrupted or stdout is closed. If the changed config
+/// can't be read, a warning is printed, and its changes are reported along
+/// with the next ones.
+fn follow_events() {
+    let version = |file: &str| {
+        fs::metadata(file)
+            .ok()
+            .map(|m| (m.modified().ok(), m.len()))
+    };
+    let print = |events: &[def::event::Event]| -> io::Result<()> {
+        let mut stdout = io::stdout().lock();
+        for event in events.iter() {
+            serde_json::to_writer(&mut stdout, event)?;
+            writeln!(stdout)?;
+        }
+        stdout.flush()
+    };
+
+    let file = config_file();
+    let mut describer = read_describer();
+    let mut seen = version(&file);
+    loop {
+        std::thread::sleep(EVENTS_INTERVAL);
+        let current = version(&file);
+        if current == seen {
+            continue;
+        }
+        seen = current;
+
+        match config::load(&settings()) {
+            Ok(changed) => {
+                let events = def::event::diff(&describer, &changed);
+                if print(&events).is_err() {
+                    return;
+                }
+                describer = changed;
+            }
+            Err(e) => warn(format!("{}: {}", tr("failed-read-config"), e)),
+        }
+    }
+}
+
+/// install_pack installs the pattern pack read from source, a file or an
+/// http(s) URL, replacing an installed pack of the same name. Patterns are
+/// mapped under prefix if it's given, or under the pack's own prefix, with
+/// variables (such as $HOME) expanded. A copy of the pack is kept in the
+/// packs dir, so that it can be removed later.
+fn install_pack(source: &str, prefix: Option<String>, key: Option<String>, yes: bool) {
+    let remote = source.starts_with("http://") || source.starts_with("https://");
+    let bytes = if remote {
+        download(source)
+    } else {
+        fs::read(source).extract_or_exit(tr("failed-read-pack"))
+    };
+    let settings = settings();
+    let keys = match key {
+        Some(key) => vec![fs::read_to_string(&key).unwrap_or(key)],
+        None if remote => settings.trusted_keys.clone(),
+        None => Vec::new(),
+    };
+    let verified = !keys.is_empty();
+    if verified {
+        verify_pack(source, remote, &bytes, &keys);
+    }
+
+    let value = format::from_path(source)
+        .unwrap_or(&format::Json)
+        .decode(&bytes)
+        .extract_or_exit(tr("invalid-pack"));
+    let mut pack = Pack::from_value(value).extract_or_exit(tr("invalid-pack"));
+    pack.prefix = match prefix {
+        Some(prefix) => existing_or_absolute_path(&prefix),
+        None => vars::expand(&pack.prefix, |name| settings.variable(name)),
+    };
+    if remote && !yes && !confirm_pack(&mut io::stdin().lock(), &pack, verified) {
+        println!("{}", tr("pack-declined"));
+        return;
+    }
+
+    let file = pack_file(&pack.name);
+    let _lock = lock_config();
+    let mut describer = if open_store().exists() {
+        get_describer()
+    } else {
+        Describer::new()
+    };
+    if let Some(installed) = read_pack(&file) {
+        pack::uninstall(&mut describer, &installed);
+    }
+    let count = pack::install(&mut describer, &pack).extract_or_exit(tr("invalid-pack"));
+    write_describer(&mut describer);
+
+    let json = serde_json::to_string_pretty(&pack).extract_or_exit(tr("failed-write-pack"));
+    fs::create_dir_all(packs_dir()).extract_or_exit(tr("failed-write-pack"));
+    fs::write(&file, json).extract_or_exit(tr("failed-write-pack"));
+    println!("{}", trf("installed-pack", &[&pack.name, &count]));
+}
+
+/// verify_pack exits with an error unless bytes of the pack at source are
+/// signed by one of keys. The signature is read from source with a
+/// ".minisig" extension added, as minisign writes it.
+fn verify_pack(source: &str, remote: bool, bytes: &[u8], keys: &[String]) {
+    let signature_source = format!("{}.minisig", source);
+    let signature = if remote {
+        download(&signature_source)
+    } else {
+        fs::read(&signature_source).extract_or_exit(tr("failed-read-signature"))
+    };
+    pack::verify(bytes, &String::from_utf8_lossy(&signature), keys)
+        .extract_or_exit(tr("unverified-pack"));
+}
+
+/// confirm_pack shows who made pack, whether its signature was verified, and
+/// the paths its patterns are mapped to, and returns true if the user agreed
+/// to install it.
+fn confirm_pack<R: BufRead>(input: &mut R, pack: &Pack, verified: bool) -> bool {
+    match &pack.author {
+        Some(author) => println!(
+            "{} {}",
+            paint(Role::Heading, &pack.name),
+            trf("pack-by", &[author])
+        ),
+        None => println!("{}", paint(Role::Heading, &pack.name)),
+    }
+    if let Some(description) = &pack.description {
+        println!("{}", description);
+    }
+    println!(
+        "{}",
+        tr(if verified {
+            "pack-signed"
+        } else {
+            "pack-unsigned"
+        })
+    );
+    println!("{}", tr("pack-affects"));
+    for (kind, key, _) in pack.entries() {
+        println!("  {}{}", paint(Role::Path, &key), kind_label(Some(kind)));
+    }
+    ask(input, tr("install-pack"))
+}
+
+/// remove_pack removes patterns of the installed pack name, except those
+/// changed since it was installed, and deletes its copy.
+fn remove_pack(name: &str) {
+    let file = pack_file(name);
+    let installed = match read_pack(&file) {
+        Some(pack) => pack,
+        None => {
+            eprintln!("{}: {}", error_label(), trf("pack-not-installed", &[&name]));
+            process::exit(1);
+        }
+    };
+
+    let _lock = lock_config();
+    let mut describer = get_describer();
+    let count = pack::uninstall(&mut describer, &installed);
+    write_describer(&mut describer);
+    fs::remove_file(&file).extract_or_exit(tr("failed-write-pack"));
+    println!("{}", trf("removed-pack", &[&name, &count]));
+}
+
+/// list_packs prints installed packs, with their prefixes and descriptions.
+fn list_packs() {
+    let mut packs: Vec<Pack> = fs::read_dir(packs_dir())
+        .map(|entries| {
+            entries
+                .filter_map(|e| read_pack(&e.ok()?.path().to_string_lossy()))
+                .collect()
+        })
+        .unwrap_or_default();
+    if packs.is_empty() {
+        println!("{}: {}", error_label(), tr("no-packs"));
+    }
+    packs.sort_by(|a, b| a.name.cmp(&b.name));
+    for pack in packs.iter() {
+        println!(
+            "{} ({}): {}",
+            paint(Role::Path, &pack.name),
+            pack.prefix,
+            pack.description.as_deref().unwrap_or_default()
+        );
+    }
+}
+
+/// list_profiles prints names of profiles, marking the one in use.
+fn list_profiles() {
+    let profiles = config::profiles().extract_or_exit(tr("failed-list-profiles"));
+    if profiles.is_empty() {
+        println!("{}: {}", error_label(), tr("no-profiles"));
+    }
+    for name in profiles.iter() {
+        if CHOICE.get() == Some(&Choice::Profile(name.clone())) {
+            println!("{}", trf("active-profile", &[&paint(Role::Path, name)]));
+        } else {
+            println!("{}", paint(Role::Path, name));
+        }
+    }
+}
+
+/// create_profile creates a profile with no descriptions.
+fn create_profile(name: &str) {
+    config::create_profile(name).extract_or_exit(tr("failed-create-profile"));
+    println!("{}", trf("created-profile", &[&name]));
+}
+
+/// delete_profile deletes a profile along with its descriptions.
+fn delete_profile(name: &str) {
+    config::delete_profile(name).extract_or_exit(tr("failed-delete-profile"));
+    println!("{}", trf("deleted-profile", &[&name]));
+}
+
+/// packs_dir returns the directory installed packs are kept in.
+fn packs_dir() -> PathBuf {
+    config::packs_dir().extract_or_exit(tr("failed-get-home"))
+}
+
+/// pack_file returns the path of the installed copy of the pack name.
+fn pack_file(name: &str) -> String {
+    packs_dir()
+        .join(format!("{}.json", name))
+        .to_string_lossy()
+        .to_string()
+}
+
+/// read_pack returns the installed pack at file, or None if it can't be read.
+fn read_pack(file: &str) -> Option<Pack> {
+    Pack::from_value(serde_json::from_slice(&fs::read(file).ok()?).ok()?).ok()
+}
+
+/// download returns the body of an http(s) URL, fetched using curl.
+fn download(url: &str) -> Vec<u8> {
+    let output = Command::new("curl")
+        .args(["--fail", "--silent", "--show-error", "--location", url])
+        .output()
+        .extract_or_exit(tr("failed-download"));
+    if !output.status.success() {
+        let message = String::from_utf8_lossy(&output.stderr);
+        eprintln!(
+            "{}: {}: {}",
+            error_label(),
+            tr("failed-download"),
+            message.trim()
+        );
+        process::exit(1);
+    }
+    output.stdout
+}
+
+/// print_section prints a titled list of lines, or nothing if lines is empty.
+fn print_section(title: &str, lines: &[String]) {
+    if lines.is_empty() {
+        return;
+    }
+    println!("{}", paint(Role::Heading, title));
+    for line in lines.iter() {
+        println!("  {}", line);
+    }
+}
+
+/// describe_metadata returns a human readable summary of path metadata, such
+/// as "file (12 bytes)".
+fn describe_metadata(metadata: &PathMetadata) -> String {
+    match metadata.kind {
+        FileKind::File => trf("metadata-file", &[&metadata.size]),
+        FileKind::Directory => trf("metadata-directory", &[&metadata.size]),
+        FileKind::Symlink => tr("metadata-symlink").to_string(),
+    }
+}
+
+/// remove_description moves the description (or pattern) of path to the
+/// trash. Exits with an error message if path has no description (or pattern).
+fn remove_description(path: &str, pattern: bool) {
+    let _lock = lock_config();
+    let mut describer = read_describer();
+    let path = existing_or_absolute_path(path);
+    let path = written_key(&describer, &path);
+    let removed = if pattern {
+        describer.trash_pattern(&path)
+    } else {
+        describer.trash_description(&path)
+    };
+
+    if removed {
+        write_describer(&mut describer);
+        println!("{}", trf("moved-to-trash", &[&paint(Role::Path, &path)]));
+    } else {
+        let key = if pattern {
+            "no-pattern-to-remove"
+        } else {
+            "no-description-to-remove"
+        };
+        eprintln!("{}: {}", error_label(), trf(key, &[&path]));
+        process::exit(1);
+    }
+}
+
+/// move_path rewrites keys of descriptions and patterns of old (and, if
+/// recursive is true, of paths under old) to use new instead, and prints each
+/// rewritten key. If dry_run is true, the config isn't changed.
+fn move_path(old: &str, new: &str, recursive: bool, dry_run: bool) {
+    let _lock = lock_config();
+    let mut describer = read_describer();
+    let old = written_key(&describer, &existing_or_absolute_path(old));
+    let new = existing_or_absolute_path(new);
+
+    let renames = if recursive {
+        describer.plan_prefix_rename(&old, &new)
+    } else {
+        describer.plan_rename(&old, &new)
+    };
+    for r in renames.iter() {
+        println!(
+            "{}{} -> {}{}",
+            r.old,
+            kind_label(r.pattern),
+            paint(Role::Path, &r.new),
+            if r.overwrites { tr("overwrites") } else { "" },
+        );
+    }
+
+    if dry_run {
+        println!("{}", trf("would-rename", &[&renames.len()]));
+    } else {
+        let count = if recursive {
+            describer.rename_prefix(&old, &new)
+        } else {
+            describer.rename(&old, &new)
+        };
+        if count > 0 {
+            write_describer(&mut describer);
+        }
+        println!("{}", trf("renamed", &[&count]));
+    }
+}
+
+/// list_trash prints all trashed descriptions, oldest first.
+fn list_trash() {
+    let describer = read_describer();
+    let now = def::trash::now();
+    for entry in describer.trashed().iter() {
+        println!(
+            "{}{} ({}): {}",
+            paint(Role::Path, &entry.path),
+            kind_label(entry.pattern),
+            elapsed(now.saturating_sub(entry.trashed_at)),
+            paint(Role::Description, &entry.description),
+        );
+    }
+}
+
+/// restore_from_trash restores the last trashed description of path.
+fn restore_from_trash(path: &str) {
+    let _lock = lock_config();
+    let mut describer = read_describer();
+    let path = written_key(&describer, &existing_or_absolute_path(path));
+    describer
+        .restore(&path)
+        .extract_or_exit(tr("failed-restore"));
+    write_describer(&mut describer);
+    println!("{}", trf("restored", &[&paint(Role::Path, &path)]));
+}
+
+/// empty_trash deletes all trashed descriptions for good.
+fn empty_trash() {
+    let _lock = lock_config();
+    let mut describer = read_describer();
+    let count = describer.empty_trash();
+    write_describer(&mut describer);
+    println!("{}", trf("deleted-trash", &[&count]));
+}
+
+/// kind_label returns a label marking entries of the given pattern kind, or an
+/// empty string for specific descriptions.
+fn kind_label(pattern: Option<PatternKind>) -> String {
+    match pattern {
+        Some(kind) => format!(" [{}]", kind),
+        None => String::new(),
+    }
+}
+
+/// elapsed returns a human readable representation of the given number of
+/// seconds, such as "3 days ago".
+fn elapsed(seconds: u64) -> String {
+    let (count, one, many) = match seconds {
+        s if s < 60 * 60 => (s / 60, "minute-ago", "minutes-ago"),
+        s if s < 24 * 60 * 60 => (s / (60 * 60), "hour-ago", "hours-ago"),
+        s => (s / (24 * 60 * 60), "day-ago", "days-ago"),
+    };
+    trf(if count == 1 { one } else { many }, &[&count])
+}
+
+/// doctor prints warnings about keys in config_file that are incompatible with
+/// this platform or left unexpanded since they'd expand to other keys, about
+/// entries that look like they hold secrets, and about config_file being
+/// readable by other users while holding such entries or private ones, if
+/// any.
+fn doctor() {
+    let describer = read_describer();
+    let warnings = describer.validate_keys();
+    let settings = settings();
+    let collisions = describer
+        .clone()
+        .expand_keys(|name| settings.variable(name));
+    let secrets = redact::find_secrets(&describer);
+    let exposed = is_exposed(&describer);
+    if warnings.is_empty() && collisions.is_empty() && secrets.is_empty() && !exposed {
+        println!("{}", tr("no-problems"));
+    }
+    for w in warnings.iter() {
+        warn(w);
+    }
+    for (key, expanded) in collisions.iter() {
+        warn(trf("expanded-key-collision", &[key, expanded]));
+    }
+    for (entry, name) in secrets.iter() {
+        warn(trf("possible-secret", &[&entry.path, name]));
+    }
+    if exposed {
+        warn(trf("exposed-config", &[&config_file()]));
+    }
+}
+
+/// fix_permissions makes config_file accessible only by its owner.
+fn fix_permissions() {
+    config::restrict_permissions(&settings()).extract_or_exit(tr("failed-fix-perms"));
+    println!(
+        "{}",
+        trf("fixed-perms", &[&paint(Role::Path, &config_file())])
+    );
+}
+
+/// is_exposed returns true if config_file can be read by other users while
+/// describer holds private or secret-like entries (see config::is_sensitive).
+fn is_exposed(describer: &Describer) -> bool {
+    config::is_exposed(&settings()).unwrap_or(false) && config::is_sensitive(describer)
+}
+
+/// convert_paths rewrites keys in config_file to use this platform's
+/// conventions, and prints each converted key. Prints a warning for each key
+/// left as is, since its converted form is already a key.
+fn convert_paths() {
+    let _lock = lock_config();
+    let mut describer = read_describer();
+    let conversion = describer.convert_keys();
+    for (old, new) in conversion.converted.iter() {
+        println!("{} -> {}", old, paint(Role::Path, new));
+    }
+    for (key, local) in conversion.collisions.iter() {
+        warn(trf("key-collision", &[key, local]));
+    }
+    if !conversion.converted.is_empty() {
+        write_describer(&mut describer);
+    }
+    for w in describer.validate_keys().iter() {
+        warn(w);
+    }
+}
+
+/// annotate_archive prints members of the given archive, with descriptions of
+/// members that would be described if the archive was extracted in root (or
+/// the current directory).
+#[cfg(feature = "archive")]
+fn annotate_archive(archive: &str, root: Option<String>) {
+    let members = archive::members(archive).extract_or_exit(tr("failed-read-archive"));
+    let root = match root {
+        Some(r) => existing_or_absolute_path(&r),
+        None => existing_or_absolute_path("."),
+    };
+    let describer = get_lookup_describer_for(&root);
+
+    // Archives can have many members, so one buffer is reused for all of
+    // their descriptions.
+    let mut description = String::new();
+    for member in members.iter() {
+        let path = def::DefPath::new(&Path::new(&root).join(member).to_string_lossy());
+        if describer.describe_into(&path, &mut description) {
+            println!(
+                "{}: {}",
+                paint(Role::Path, member),
+                paint(Role::Description, &description)
+            );
+        } else {
+            println!("{}", member);
+        }
+    }
+}
+
+/// annotate_archive prints an error message, as def was built without
+/// archive support.
+#[cfg(not(feature = "archive"))]
+fn annotate_archive(_: &str, _: Option<String>) {
+    eprintln!("{}: {}", error_label(), tr("archive-unsupported"));
+    process::exit(1);
+}
+
+/// browse opens the interactive browser of the config, see browse::run.
+#[cfg(feature = "tui")]
+fn browse() {
+    browse::run();
+}
+
+/// browse prints an error message, as def was built without the
+/// interactive browser.
+#[cfg(not(feature = "tui"))]
+fn browse() {
+    eprintln!("{}: {}", error_label(), tr("tui-unsupported"));
+    process::exit(1);
+}
+
+/// describe_inode prints the description of a file, identified by id as
+/// "<dev>:<inode>", that some process has open. Exits if no process does.
+#[cfg(all(feature = "procfs", target_os = "linux"))]
+fn describe_inode(id: &str) {
+    let (dev, ino) = procfs::parse_file_id(id).unwrap_or_else(|| {
+        eprintln!("{}: {}", error_label(), trf("invalid-file-id", &[&id]));
+        process::exit(1);
+    });
+    match procfs::find_inode(dev, ino).extract_or_exit(tr("failed-read-proc")) {
+        Some(path) => print_description(&path.to_string_lossy()),
+        None => {
+            eprintln!("{}: {}", error_label(), trf("inode-not-open", &[&id]));
+            process::exit(1);
+        }
+    }
+}
+
+/// describe_fd prints the description of the file open by a process, with
+/// id as "<pid>:<fd>".
+#[cfg(all(feature = "procfs", target_os = "linux"))]
+fn describe_fd(id: &str) {
+    let (pid, fd) = procfs::parse_fd(id).unwrap_or_else(|| {
+        eprintln!("{}: {}", error_label(), trf("invalid-fd", &[&id]));
+        process::exit(1);
+    });
+    let path = procfs::path_of_fd(pid, fd).extract_or_exit(tr("failed-read-proc"));
+    print_description(&path.to_string_lossy());
+}
+
+/// describe_inode prints an error message, as def was built without
+/// support for /proc.
+#[cfg(not(all(feature = "procfs", target_os = "linux")))]
+fn describe_inode(_: &str) {
+    eprintln!("{}: {}", error_label(), tr("procfs-unsupported"));
+    process::exit(1);
+}
+
+/// describe_fd prints an error message, as def was built without support
+/// for /proc.
+#[cfg(not(all(feature = "procfs", target_os = "linux")))]
+fn describe_fd(_: &str) {
+    eprintln!("{}: {}", error_label(), tr("procfs-unsupported"));
+    process::exit(1);
+}
+
+/// generate_fixture creates the fixture of the spec at file in a new temporary
+/// directory (see def::fixture), and prints the directory's path.
+fn generate_fixture(file: &str) {
+    let fixture = fixture::read_spec(file)
+        .and_then(|spec| fixture::generate(&spec, fixture::temp_dir()))
+        .extract_or_exit(tr("failed-generate-fixture"));
+    println!("{}", fixture.dir.display());
+}
+
+/// create_bundle writes def's state (settings, config, installed packs, and
+/// translations) to a bundle at output, which can be carried to machines
+/// without network access and applied there.
+#[cfg(feature = "archive")]
+fn create_bundle(output: &str) {
+    let config = PathBuf::from(config_file());
+    let _lock = lock_config();
+    let (manifest, members) = bundle::collect(
+        &config::settings_file().extract_or_exit(tr("failed-get-home")),
+        Some(config.as_path()).filter(|_| open_store().exists()),
+        &packs_dir(),
+        Path::new(&config_dir("locales")),
+        def::trash::now(),
+    )
+    .extract_or_exit(tr("failed-create-bundle"));
+    bundle::write(output, &manifest, &members).extract_or_exit(tr("failed-create-bundle"));
+    println!("{}", trf("bundle-created", &[&members.len(), &output]));
+}
+
+/// apply_bundle writes the state in the bundle at file to this machine,
+/// replacing the settings, config, and files of installed packs and
+/// translations it holds. The config is backed up first, as by maintain.
+/// Settings are written first, as they decide where the config is written.
+/// Nothing is written if the bundled config is stored in another format
+/// than the config it would replace.
+#[cfg(feature = "archive")]
+fn apply_bundle(file: &str) {
+    let (manifest, members) = bundle::read(file).extract_or_exit(tr("failed-read-bundle"));
+    let mut parts = Vec::new();
+    for member in members.iter() {
+        match bundle::part(&member.name, &manifest) {
+            Some(part) => parts.push((part, member)),
+            None => warn(trf("ignored-bundle-member", &[&member.name])),
+        }
+    }
+    parts.sort_by_key(|(part, _)| *part != bundle::Part::Settings);
+
+    let target = match parts.first() {
+        Some((bundle::Part::Settings, member)) if SANDBOX.get().is_none() => chosen(
+            serde_json::from_slice(&member.contents).extract_or_exit(tr("failed-read-bundle")),
+        ),
+        _ => settings(),
+    };
+    let backend = config::store_backend(&target).extract_or_exit(tr("failed-open-config"));
+    bundle::check_config(&manifest, backend).extract_or_exit(tr("failed-apply-bundle"));
+
+    let _lock = lock_config();
+    if open_store().exists() {
+        let rotation = backup::rotate(
+            &config_file(),
+            &config_dir("backups"),
+            settings().backups_kept,
+        )
+        .extract_or_exit(tr("failed-back-up-config"));
+        if let Some(backup) = rotation.created {
+            println!("{}", trf("backed-up-config", &[&backup]));
+        }
+    }
+
+    let mut written = Vec::new();
+    for (part, member) in parts {
+        let destination = match part {
+            bundle::Part::Settings => {
+                config::settings_file().extract_or_exit(tr("failed-get-home"))
+            }
+            bundle::Part::Config => PathBuf::from(config_file()),
+            bundle::Part::Pack(name) => packs_dir().join(name),
+            bundle::Part::Locale(name) => Path::new(&config_dir("locales")).join(name),
+        };
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent).extract_or_exit(tr("failed-apply-bundle"));
+        }
+        def::store::write_atomic(&destination, &member.contents)
+            .extract_or_exit(tr("failed-apply-bundle"));
+        written.push(destination.to_string_lossy().to_string());
+    }
+    print_section(tr("section-written"), &written);
+    println!("{}", trf("bundle-applied", &[&written.len(), &file]));
+}
+
+/// create_bundle prints an error message, as def was built without archive
+/// support.
+#[cfg(not(feature = "archive"))]
+fn create_bundle(_: &str) {
+    eprintln!("{}: {}", error_label(), tr("archive-unsupported"));
+    process::exit(1);
+}
+
+/// apply_bundle prints an error message, as def was built without archive
+/// support.
+#[cfg(not(feature = "archive"))]
+fn apply_bundle(_: &str) {
+    eprintln!("{}: {}", error_label(), tr("archive-unsupported"));
+    process::exit(1);
+}
+
+/// get_lookup_describer returns a describer loaded from config_file, with
+/// variables in its keys expanded, ready to describe paths. Prints warnings
+/// about keys incompatible with this platform.
+fn get_lookup_describer() -> Describer {
+    let describer = config::load_for_lookup(&settings()).extract_or_exit(tr("failed-read-config"));
+    warn_incompatible_keys(&describer);
+    describer
+}
+
+/// get_lookup_describer_for returns a lookup describer (see
+/// get_lookup_describer) with local files found in path and its ancestors
+/// merged over it, so that their entries are used to describe path.
+fn get_lookup_describer_for(path: &str) -> Describer {
+    let mut describer = get_lookup_describer();
+    local::merge_into(&mut describer, path).extract_or_exit(tr("failed-read-local"));
+    describer
+}
+
+/// get_path_describer returns a lookup describer for path like
+/// get_lookup_describer_for, but loads only the entries of config_file
+/// needed to describe path, when its store can (see `Store::load_for`).
+fn get_path_describer(path: &str) -> Describer {
+    let mut describer =
+        config::load_for_paths(&settings(), &[path]).extract_or_exit(tr("failed-read-config"));
+    warn_incompatible_keys(&describer);
+    local::merge_into(&mut describer, path).extract_or_exit(tr("failed-read-local"));
+    describer
+}
+
+/// get_describer loads a describer from config_file, prints warnings about
+/// keys incompatible with this platform, and returns it. Exits on error.
+fn get_describer() -> Describer {
+    let describer = read_describer();
+    warn_incompatible_keys(&describer);
+    describer
+}
+
+/// warn_incompatible_keys prints warnings about keys of describer that are
+/// incompatible with this platform.
+fn warn_incompatible_keys(describer: &Describer) {
+    let warnings = describer.validate_keys();
+    for w in warnings.iter() {
+        warn(w);
+    }
+    if !warnings.is_empty() {
+        warn(tr("run-convert-paths"));
+    }
+}
+
+/// written_key returns the key path is written as in describer, which may
+/// use variables (see `Describer::written_key`), so that commands changing
+/// path's entries keep their keys as written.
+fn written_key(describer: &Describer, path: &str) -> String {
+    let settings = settings();
+    describer.written_key(path, |name| settings.variable(name))
+}
+
+/// read_describer loads a describer from config_file and returns it. Exits on
+/// error.
+fn read_describer() -> Describer {
+    config::load(&settings()).extract_or_exit(tr("failed-read-config"))
+}
+
+/// write_describer deletes expired entries from the describer's trash, and
+/// (re)writes it to config_file, journaling the changes (see def::journal).
+/// Prints a warning if config_file can be read by other users while holding
+/// sensitive entries. Exits on error.
+fn write_describer(describer: &mut Describer) {
+    let operation = OPERATION.get().map_or("", String::as_str);
+    config::save_as(&settings(), describer, operation).extract_or_exit(tr("failed-write-config"));
+    if is_exposed(describer) {
+        warn(trf("exposed-config", &[&config_file()]));
+    }
+}
+
+/// lock_config acquires the lock of config_file, which commands changing the
+/// config hold from reading it until writing it, so that concurrent
+/// invocations don't lose each other's changes. Exits on error.
+fn lock_config() -> Lock {
+    config::lock(&settings()).extract_or_exit(tr("failed-lock-config"))
+}
+
+/// open_store returns the store of config_file. Exits on error.
+fn open_store() -> Box<dyn Store> {
+    config::open_store(&settings()).extract_or_exit(tr("failed-open-config"))
+}
+
+/// settings loads settings from the settings file, using the config file
+/// chosen by flags or the environment if any, or returns the sandbox's
+/// settings if use_sandbox was called. Exits on error.
+fn settings() -> Settings {
+    if let Some(settings) = SANDBOX.get() {
+        return settings.clone();
+    }
+    chosen(config::load_settings().extract_or_exit(tr("failed-read-settings")))
+}
+
+/// chosen returns settings using the config file chosen by flags or the
+/// environment if any. Exits on error.
+fn chosen(settings: Settings) -> Settings {
+    match CHOICE.get() {
+        Some(choice) => resolve::apply(&settings, choice).extract_or_exit(tr("failed-open-config")),
+        None => settings,
+    }
+}
+
+/// use_sandbox makes def use the given settings instead of the user's for the
+/// rest of the process, so that a config can be used without touching the
+/// user's. Has no effect if called more than once.
+fn use_sandbox(settings: Settings) {
+    let _ = SANDBOX.set(settings);
+}
+
+/// config_file returns path to configuration file.
+fn config_file() -> String {
+    config::config_file(&settings())
+        .extract_or_exit(tr("failed-open-config"))
+        .to_string_lossy()
+        .to_string()
+}
+
+/// config_dir returns path to the directory containing def's files, joined
+/// with the given name.
+fn config_dir(name: &str) -> String {
+    config::config_dir()
+        .extract_or_exit(tr("failed-get-home"))
+        .join(name)
+        .to_string_lossy()
+        .to_string()
+}
+
+/// existing_or_absolute_path returns the absolute representation of path if
+/// it exists. Otherwise, path is resolved lexically. This is used to refer to
+/// descriptions of paths that may have been deleted.
+fn existing_or_absolute_path(path: &str) -> String {
+    if no_resolve() {
+        return absolute_path(path);
+    }
+    paths::absolute_or_joined(path).extract_or_exit(tr("failed-get-absolute-path"))
+}
+
+/// glob_path returns the absolute representation of a glob. Globs containing a
+/// separator are resolved lexically against the current directory, while
+/// globs without one (such as "*.log") match names of paths, and so are
+/// returned unchanged.
+fn glob_path(glob: &str) -> String {
+    if glob.contains(std::path::MAIN_SEPARATOR) {
+        let current = env::current_dir().extract_or_exit(tr("failed-get-current-dir"));
+        paths::resolve_lexically(Path::new(glob), &current)
+            .to_string_lossy()
+            .to_string()
+    } else {
+        glob.to_string()
+    }
+}
+
+/// absolute_path takes a path and returns its absolute representation, which
+/// is canonical unless --no-resolve is given (see def::paths::lexical).
+/// Exits on failure (if path doesn't exist and is resolved canonically).
+fn absolute_path(path: &str) -> String {
+    if no_resolve() {
+        paths::lexical(path)
+    } else {
+        paths::absolute(path)
+    }
+    .extract_or_exit(tr("failed-get-absolute-path"))
+}
+
+/// no_resolve returns true if paths are resolved lexically.
+fn no_resolve() -> bool {
+    NO_RESOLVE.get().copied().unwrap_or(false)
+}
+
+/// raw returns true if descriptions are printed as written, without being
+/// rendered or wrapped.
+fn raw() -> bool {
+    RAW.get().copied().unwrap_or(false)
+}
+
+/// inherit returns true if paths without a description inherit that of
+/// their nearest described ancestor.
+fn inherit() -> bool {
+    INHERIT.get().copied().unwrap_or(false)
+}
+
+/// no_stat returns true if metadata placeholders are left unfilled.
+fn no_stat() -> bool {
+    NO_STAT.get().copied().unwrap_or(false)
+}
+
+/// lookup_text returns the description of a lookup, marked as inherited
+/// from its ancestor if it is (see --inherit).
+fn lookup_text(lookup: Lookup) -> Option<String> {
+    let description = lookup.description?;
+    Some(match lookup.inherited_from {
+        Some(ancestor) => trf("inherited-from", &[&ancestor, &description]),
+        None => description,
+    })
+}
+
+/// ask_daemon_to_describe asks the daemon for the description of path, see
+/// ask_daemon.
+fn ask_daemon_to_describe(path: &str) -> Option<Lookup> {
+    let request = Request::Describe {
+        path: path.to_string(),
+        fs: !no_stat(),
+        inherit: inherit(),
+    };
+    match ask_daemon(&request)? {
+        Response::Description(lookup) => Some(lookup),
+        _ => None,
+    }
+}
+
+/// ask_daemon_for_entries asks the daemon to list or search entries, see
+/// ask_daemon.
+fn ask_daemon_for_entries(request: &Request) -> Option<Vec<Item>> {
+    match ask_daemon(request)? {
+        Response::Entries { entries } => Some(entries),
+        _ => None,
+    }
+}
+
+/// ask_daemon sends request to the daemon serving the config, and returns
+/// its response. None is returned if no daemon is running, or it fails to
+/// answer, in which case the config should be read instead.
+#[cfg(unix)]
+fn ask_daemon(request: &Request) -> Option<Response> {
+    let socket = daemon::socket_file(config::config_file(&settings()).ok()?);
+    let mut client = daemon::Client::connect(socket).ok()?;
+    match client.ask(request).ok()? {
+        Response::Error { .. } => None,
+        response => Some(response),
+    }
+}
+
+/// ask_daemon returns None, as the daemon needs Unix domain sockets.
+#[cfg(not(unix))]
+fn ask_daemon(_: &Request) -> Option<Response> {
+    None
+}
+
+/// run_daemon loads the config, and answers queries about it on its socket
+/// (see def::daemon) until interrupted.
+#[cfg(unix)]
+fn run_daemon() {
+    let settings = settings();
+    let file = config::config_file(&settings).extract_or_exit(tr("failed-open-config"));
+    let socket = daemon::socket_file(&file);
+    let daemon = Daemon::new(settings).extract_or_exit(tr("failed-read-config"));
+    let listener = daemon::listen(&socket).extract_or_exit(tr("failed-run-daemon"));
+    eprintln!("{}", trf("daemon-listening", &[&socket.display()]));
+    daemon::serve(daemon, listener).extract_or_exit(tr("failed-run-daemon"));
+}
+
+/// run_daemon prints an error message, as the daemon needs Unix domain
+/// sockets.
+#[cfg(not(unix))]
+fn run_daemon() {
+    eprintln!("{}: {}", error_label(), tr("daemon-unsupported"));
+    process::exit(1);
+}
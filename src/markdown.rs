@@ -0,0 +1,243 @@
+use std::sync::OnceLock;
+
+#[cfg(feature = "markdown")]
+use colored::*;
+#[cfg(feature = "markdown")]
+use pulldown_cmark::{Event, Parser, Tag, TagEnd};
+
+#[cfg(feature = "markdown")]
+use crate::theme::{self, paint, Role};
+
+/// Whether descriptions are rendered as Markdown, set once by init.
+static ENABLED: OnceLock<bool> = OnceLock::new();
+
+/// Marker of items of unordered lists.
+#[cfg(feature = "markdown")]
+const BULLET: &str = "•";
+
+/// Marker of items of unordered lists in plain output.
+#[cfg(feature = "markdown")]
+const PLAIN_BULLET: &str = "-";
+
+/// Number of spaces nested lists and code blocks are indented by.
+#[cfg(feature = "markdown")]
+const INDENT: usize = 2;
+
+/// init sets whether render renders descriptions as Markdown. Has no effect
+/// if called more than once.
+pub fn init(enabled: bool) {
+    let _ = ENABLED.set(enabled);
+}
+
+/// render returns description rendered as Markdown for the terminal, or as is
+/// if rendering is disabled (or def is built without "markdown" feature).
+pub fn render(description: &str) -> String {
+    if ENABLED.get().copied().unwrap_or(false) {
+        to_terminal(description)
+    } else {
+        description.to_string()
+    }
+}
+
+#[cfg(not(feature = "markdown"))]
+fn to_terminal(markdown: &str) -> String {
+    markdown.to_string()
+}
+
+/// to_terminal renders markdown for the terminal, see render_markdown.
+#[cfg(feature = "markdown")]
+fn to_terminal(markdown: &str) -> String {
+    render_markdown(markdown, !theme::is_plain())
+}
+
+/// render_markdown renders markdown as text: links are followed by their
+/// URLs, and list items are bulleted or numbered. If styled is true, bold,
+/// italics, and strikethrough are shown as such, code is colored, and links
+/// are underlined.
+#[cfg(feature = "markdown")]
+fn render_markdown(markdown: &str, styled: bool) -> String {
+    let mut renderer = Renderer {
+        styled,
+        ..Renderer::default()
+    };
+    for event in Parser::new(markdown) {
+        renderer.render(event);
+    }
+    renderer.output.trim_end().to_string()
+}
+
+/// Renderer keeps the state of rendering a Markdown document.
+#[cfg(feature = "markdown")]
+#[derive(Default)]
+struct Renderer {
+    output: String,
+    styled: bool,
+    bold: usize,
+    italic: usize,
+    strikethrough: usize,
+    code_block: bool,
+    /// URLs and text of links being rendered, innermost last.
+    links: Vec<(String, String)>,
+    /// Next number of each list being rendered, None for unordered lists.
+    lists: Vec<Option<u64>>,
+}
+
+#[cfg(feature = "markdown")]
+impl Renderer {
+    /// render appends the rendering of event to output.
+    fn render(&mut self, event: Event) {
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) if self.code_block => {
+                let indent = " ".repeat(INDENT);
+                for line in text.lines() {
+                    let line = format!("{}{}\n", indent, line);
+                    self.push_styled(&line, Some(Role::Code));
+                }
+            }
+            Event::Text(text) => self.push_text(&text),
+            Event::Code(code) => {
+                for (_, link_text) in self.links.iter_mut() {
+                    link_text.push_str(&code);
+                }
+                self.push_styled(&code, Some(Role::Code));
+            }
+            Event::Html(html) | Event::InlineHtml(html) => self.output.push_str(&html),
+            Event::SoftBreak => self.push_text(" "),
+            Event::HardBreak => self.output.push('\n'),
+            Event::Rule => self.output.push_str("---\n\n"),
+            _ => {}
+        }
+    }
+
+    /// start handles the start of a Markdown element.
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Heading { .. } | Tag::Strong => self.bold += 1,
+            Tag::Emphasis | Tag::BlockQuote(_) => self.italic += 1,
+            Tag::Strikethrough => self.strikethrough += 1,
+            Tag::CodeBlock(_) => self.code_block = true,
+            Tag::Link { dest_url, .. } | Tag::Image { dest_url, .. } => {
+                self.links.push((dest_url.to_string(), String::new()))
+            }
+            Tag::List(start) => {
+                if !self.lists.is_empty() && !self.output.ends_with('\n') {
+                    self.output.push('\n');
+                }
+                self.lists.push(start);
+            }
+            Tag::Item => {
+                let depth = self.lists.len().saturating_sub(1);
+                let marker = match self.lists.last_mut() {
+                    Some(Some(n)) => {
+                        *n += 1;
+                        format!("{}.", *n - 1)
+                    }
+                    _ if self.styled => BULLET.to_string(),
+                    _ => PLAIN_BULLET.to_string(),
+                };
+                self.output
+                    .push_str(&format!("{}{} ", " ".repeat(depth * INDENT), marker));
+            }
+            _ => {}
+        }
+    }
+
+    /// end handles the end of a Markdown element.
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Paragraph if self.lists.is_empty() => self.output.push_str("\n\n"),
+            TagEnd::Heading(_) => {
+                self.bold -= 1;
+                self.output.push_str("\n\n");
+            }
+            TagEnd::Strong => self.bold -= 1,
+            TagEnd::Emphasis => self.italic -= 1,
+            TagEnd::BlockQuote(_) => self.italic -= 1,
+            TagEnd::Strikethrough => self.strikethrough -= 1,
+            TagEnd::CodeBlock => {
+                self.code_block = false;
+                self.output.push('\n');
+            }
+            TagEnd::Link | TagEnd::Image => {
+                if let Some((url, text)) = self.links.pop() {
+                    if url != text {
+                        self.output.push_str(&format!(" ({})", url));
+                    }
+                }
+            }
+            TagEnd::List(_) => {
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.output.push('\n');
+                }
+            }
+            TagEnd::Item if !self.output.ends_with('\n') => self.output.push('\n'),
+            _ => {}
+        }
+    }
+
+    /// push_text appends text to output, styled by the elements it's in.
+    fn push_text(&mut self, text: &str) {
+        for (_, link_text) in self.links.iter_mut() {
+            link_text.push_str(text);
+        }
+        self.push_styled(text, None);
+    }
+
+    /// push_styled appends text to output, styled by the elements it's in, and
+    /// colored using role if given.
+    fn push_styled(&mut self, text: &str, role: Option<Role>) {
+        if !self.styled {
+            self.output.push_str(text);
+            return;
+        }
+
+        let mut styled = match role {
+            Some(role) => paint(role, text),
+            None => text.normal(),
+        };
+        if self.bold > 0 {
+            styled = styled.bold();
+        }
+        if self.italic > 0 {
+            styled = styled.italic();
+        }
+        if self.strikethrough > 0 {
+            styled = styled.strikethrough();
+        }
+        if !self.links.is_empty() {
+            styled = styled.underline();
+        }
+        self.output.push_str(&styled.to_string());
+    }
+}
+
+#[cfg(all(test, feature = "markdown"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_markdown_test() {
+        for (markdown, rendered) in [
+            ("A directory.", "A directory."),
+            ("**Bold** and _italic_ `code`.", "Bold and italic code."),
+            (
+                "See [the docs](https://example.com).",
+                "See the docs (https://example.com).",
+            ),
+            ("<https://example.com>", "https://example.com"),
+            (
+                "Steps:\n\n1. Build\n2. Deploy\n   - Staging\n   - Production",
+                "Steps:\n\n1. Build\n2. Deploy\n  - Staging\n  - Production",
+            ),
+            ("# Runbook\nRestart it.", "Runbook\n\nRestart it."),
+            ("```\nmake\n```", "  make"),
+        ]
+        .iter()
+        {
+            assert_eq!(render_markdown(markdown, false), *rendered, "{}", markdown);
+        }
+    }
+}
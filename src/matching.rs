@@ -0,0 +1,113 @@
+//! Options of how paths are matched against keys of descriptions and
+//! patterns.
+//!
+//! By default, a path matches a key only if they're the same, byte for byte.
+//! On file systems that ignore case, as those of macOS and Windows do by
+//! default, `~/Downloads` and `~/downloads` are the same directory, and names
+//! with accents can be written either with composed characters ("é") or with
+//! combining ones ("e" followed by an accent), depending on the program that
+//! created them. With `MatchOptions`, such differences are ignored: keys are
+//! kept as written, but compared after being folded (see
+//! `MatchOptions::fold`). Options are set in settings, such as:
+//!
+//! ```json
+//! "matching": {"case_insensitive": true, "normalize_unicode": true}
+//! ```
+
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+use unicode_normalization::{is_nfc_quick, IsNormalized, UnicodeNormalization};
+
+/// MatchOptions are options of how paths are matched against keys, see the
+/// module's documentation.
+///
+/// # Examples
+///
+/// ```
+/// use def::matching::MatchOptions;
+///
+/// let options = MatchOptions {
+///     case_insensitive: true,
+///     normalize_unicode: true,
+/// };
+/// assert!(options.same("/Users/me/Café", "/users/me/cafe\u{301}"));
+/// assert!(!MatchOptions::default().same("/Users", "/users"));
+/// ```
+#[derive(Deserialize, Serialize, Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[serde(default, deny_unknown_fields)]
+pub struct MatchOptions {
+    /// Ignore case, so that "/Users" matches "/users".
+    pub case_insensitive: bool,
+    /// Normalize keys and paths to Unicode's NFC form, so that composed and
+    /// combining characters match.
+    pub normalize_unicode: bool,
+}
+
+impl MatchOptions {
+    /// Return true if paths only match keys that are exactly the same.
+    pub fn is_exact(&self) -> bool {
+        !self.case_insensitive && !self.normalize_unicode
+    }
+
+    /// Return s as it's compared: lowercased if case is ignored, then
+    /// normalized to NFC if Unicode is normalized. s is borrowed if it's
+    /// already folded.
+    pub fn fold<'a>(&self, s: &'a str) -> Cow<'a, str> {
+        let mut folded = Cow::Borrowed(s);
+        if self.case_insensitive && s.chars().any(char::is_uppercase) {
+            folded = Cow::Owned(s.to_lowercase());
+        }
+        if self.normalize_unicode && is_nfc_quick(folded.chars()) != IsNormalized::Yes {
+            folded = Cow::Owned(folded.nfc().collect());
+        }
+        folded
+    }
+
+    /// Return true if a and b match, that is, if they're the same once
+    /// folded.
+    pub fn same(&self, a: &str, b: &str) -> bool {
+        a == b || (!self.is_exact() && self.fold(a) == self.fold(b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fold_test() {
+        let options = |case_insensitive, normalize_unicode| MatchOptions {
+            case_insensitive,
+            normalize_unicode,
+        };
+        for (options, s, folded) in [
+            (
+                options(false, false),
+                "/Users/Cafe\u{301}",
+                "/Users/Cafe\u{301}",
+            ),
+            (
+                options(true, false),
+                "/Users/Cafe\u{301}",
+                "/users/cafe\u{301}",
+            ),
+            (options(false, true), "/Users/Cafe\u{301}", "/Users/Café"),
+            (options(true, true), "/Users/CAFÉ", "/users/café"),
+            (options(true, true), "/users/café", "/users/café"),
+            (options(true, false), "C:/Straße", "c:/straße"),
+        ]
+        .iter()
+        {
+            assert_eq!(options.fold(s), *folded, "{:?} {}", options, s);
+        }
+        assert!(matches!(
+            options(true, true).fold("/already/folded"),
+            Cow::Borrowed(_)
+        ));
+
+        let settings: MatchOptions = serde_json::from_str(r#"{"case_insensitive": true}"#).unwrap();
+        assert_eq!(settings, options(true, false));
+        assert!(serde_json::from_str::<MatchOptions>(r#"{"ignore_case": true}"#).is_err());
+    }
+}
@@ -0,0 +1,66 @@
+//! Merging descriptions of another describer, such as ones imported from a
+//! file or shared by a team.
+//!
+//! Entries of the other describer are added if their key is new. If a key
+//! is already mapped to a different text, the entries conflict, and the
+//! `Strategy` passed to `Describer::merge_with` decides which one is kept.
+//! Entries with the same text don't conflict, and are left as they are.
+
+use crate::PatternKind;
+
+/// Strategy decides how conflicting entries are merged.
+pub enum Strategy<'a> {
+    /// Replace existing entries with incoming ones.
+    Overwrite,
+    /// Keep existing entries, ignoring incoming ones.
+    KeepExisting,
+    /// Call the function for each conflict, and replace the existing entry
+    /// if it returns true.
+    Ask(&'a mut dyn FnMut(&Change) -> bool),
+}
+
+/// Change is an incoming entry, along with the existing entry mapped to the
+/// same key, if any.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Change {
+    /// Path the entry is mapped to.
+    pub path: String,
+    /// Kind of the pattern, or None if the entry is a specific description.
+    pub pattern: Option<PatternKind>,
+    /// Text of the existing entry, or None if the key is new.
+    pub existing: Option<String>,
+    /// Text of the incoming entry.
+    pub incoming: String,
+}
+
+/// MergeReport lists what merging changed. Each list holds descriptions
+/// first, then patterns, recursive patterns, and globs, each sorted by key.
+#[derive(Debug, Default, PartialEq)]
+pub struct MergeReport {
+    /// Incoming entries whose keys were new.
+    pub added: Vec<Change>,
+    /// Incoming entries which replaced conflicting existing ones.
+    pub updated: Vec<Change>,
+    /// Incoming entries which conflicted with existing ones, and were
+    /// ignored.
+    pub conflicts: Vec<Change>,
+}
+
+impl MergeReport {
+    /// Return true if merging changed any entry.
+    pub fn changed(&self) -> bool {
+        !self.added.is_empty() || !self.updated.is_empty()
+    }
+}
+
+impl Strategy<'_> {
+    /// Return true if the incoming entry of a conflict replaces the existing
+    /// one.
+    pub(crate) fn takes_incoming(&mut self, change: &Change) -> bool {
+        match self {
+            Strategy::Overwrite => true,
+            Strategy::KeepExisting => false,
+            Strategy::Ask(f) => f(change),
+        }
+    }
+}
@@ -0,0 +1,375 @@
+//! Metadata of described paths.
+//!
+//! Metadata of a path can be recorded when it's described, and compared later
+//! against the path's current metadata to detect descriptions that may have
+//! become outdated. Times descriptions were added and updated (see
+//! `Timestamps`) are compared with the times their paths were modified for
+//! the same reason. Current metadata is also used to fill in metadata
+//! placeholders of patterns (see `FileInfo`), formatted by `human_size` and
+//! `human_time`. On Unix, owners of paths and mount points of the file
+//! systems they're on can be read too (see `owner` and `mount_point`).
+
+use std::fmt;
+use std::fs;
+use std::io;
+#[cfg(unix)]
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+/// Files whose recorded and current sizes are both smaller than this are
+/// never considered to have changed drastically.
+const MIN_SIGNIFICANT_SIZE: u64 = 4096;
+
+/// Size must grow or shrink by at least this factor to be considered a
+/// drastic change.
+const SIZE_CHANGE_FACTOR: u64 = 10;
+
+/// Units of human-readable sizes, each 1024 times the one before it.
+const SIZE_UNITS: &[&str] = &["B", "KiB", "MiB", "GiB", "TiB", "PiB"];
+
+/// File listing users, and their ids.
+#[cfg(unix)]
+const PASSWD_FILE: &str = "/etc/passwd";
+
+/// FileKind is the type of a file system entry.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum FileKind {
+    File,
+    Directory,
+    Symlink,
+}
+
+impl FileKind {
+    /// Read the type of the given path. Symbolic links aren't followed, so
+    /// links to directories are symlinks rather than directories.
+    pub fn of<P: AsRef<Path>>(path: P) -> io::Result<FileKind> {
+        let file_type = fs::symlink_metadata(path)?.file_type();
+        Ok(if file_type.is_symlink() {
+            FileKind::Symlink
+        } else if file_type.is_dir() {
+            FileKind::Directory
+        } else {
+            FileKind::File
+        })
+    }
+}
+
+impl fmt::Display for FileKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FileKind::File => write!(f, "file"),
+            FileKind::Directory => write!(f, "directory"),
+            FileKind::Symlink => write!(f, "symlink"),
+        }
+    }
+}
+
+/// PathMetadata is a summary of a path's metadata.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub struct PathMetadata {
+    /// Type of the path.
+    pub kind: FileKind,
+    /// Size of a file in bytes, or number of entries of a directory.
+    pub size: u64,
+}
+
+impl PathMetadata {
+    /// Read metadata of the given path. Symbolic links aren't followed.
+    pub fn read<P: AsRef<Path>>(path: P) -> io::Result<PathMetadata> {
+        let path = path.as_ref();
+        let metadata = fs::symlink_metadata(path)?;
+        let file_type = metadata.file_type();
+        if file_type.is_symlink() {
+            Ok(PathMetadata {
+                kind: FileKind::Symlink,
+                size: 0,
+            })
+        } else if file_type.is_dir() {
+            Ok(PathMetadata {
+                kind: FileKind::Directory,
+                size: fs::read_dir(path)?.count() as u64,
+            })
+        } else {
+            Ok(PathMetadata {
+                kind: FileKind::File,
+                size: metadata.len(),
+            })
+        }
+    }
+
+    /// Return true if other differs drastically from this metadata, which
+    /// happens if the type is different, or if the size of a file grew or
+    /// shrank by a factor of SIZE_CHANGE_FACTOR or more.
+    pub fn contradicts(&self, other: &PathMetadata) -> bool {
+        if self.kind != other.kind {
+            return true;
+        }
+        if self.kind != FileKind::File {
+            return false;
+        }
+
+        let (small, large) = if self.size < other.size {
+            (self.size, other.size)
+        } else {
+            (other.size, self.size)
+        };
+        large >= MIN_SIGNIFICANT_SIZE && large >= small.saturating_mul(SIZE_CHANGE_FACTOR)
+    }
+}
+
+/// Timestamps are the times a description was added and last updated, in
+/// seconds since the Unix epoch.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Timestamps {
+    /// Time the description was added.
+    pub created: u64,
+    /// Time the description was last changed, or added if it never was.
+    pub updated: u64,
+}
+
+/// FileInfo is the metadata filling in placeholders of patterns, such as
+/// `{size}` (see `def::template`).
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct FileInfo {
+    /// Type of the path.
+    pub kind: FileKind,
+    /// Size of a file in bytes, or number of entries of a directory.
+    pub size: u64,
+    /// Time the path was last modified, if the platform records it.
+    pub modified: Option<SystemTime>,
+}
+
+/// RepoInfo is information about a git repository filling in git
+/// placeholders of patterns, such as `{git_branch}` (see `def::template`).
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct RepoInfo {
+    /// Name of the checked out branch, or "detached HEAD".
+    pub branch: Option<String>,
+    /// URL of the "origin" remote, or of the first remote if there's none.
+    pub remote: Option<String>,
+    /// Abbreviated hash and summary of the last commit of the branch.
+    pub last_commit: Option<String>,
+}
+
+impl FileInfo {
+    /// Read metadata of the given path. Symbolic links are followed, unless
+    /// they're broken, in which case the link itself is read.
+    pub fn read<P: AsRef<Path>>(path: P) -> io::Result<FileInfo> {
+        let path = path.as_ref();
+        let metadata = fs::metadata(path).or_else(|_| fs::symlink_metadata(path))?;
+        let file_type = metadata.file_type();
+        let (kind, size) = if file_type.is_symlink() {
+            (FileKind::Symlink, 0)
+        } else if file_type.is_dir() {
+            (FileKind::Directory, fs::read_dir(path)?.count() as u64)
+        } else {
+            (FileKind::File, metadata.len())
+        };
+        Ok(FileInfo {
+            kind,
+            size,
+            modified: metadata.modified().ok(),
+        })
+    }
+
+    /// Return the size for display: a human-readable size for files (see
+    /// `human_size`), or the number of entries for directories.
+    pub fn display_size(&self) -> String {
+        match self.kind {
+            FileKind::Directory if self.size == 1 => "1 entry".to_string(),
+            FileKind::Directory => format!("{} entries", self.size),
+            _ => human_size(self.size),
+        }
+    }
+}
+
+/// Return a size in bytes as a human-readable string, using binary units with
+/// one decimal.
+///
+/// # Examples
+///
+/// ```
+/// use def::meta::human_size;
+///
+/// assert_eq!(human_size(512), "512 B");
+/// assert_eq!(human_size(1536), "1.5 KiB");
+/// assert_eq!(human_size(3 * 1024 * 1024), "3.0 MiB");
+/// ```
+pub fn human_size(bytes: u64) -> String {
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < SIZE_UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, SIZE_UNITS[0])
+    } else {
+        format!("{:.1} {}", size, SIZE_UNITS[unit])
+    }
+}
+
+/// Return a time as a human-readable UTC date and time, such as
+/// "2024-03-01 14:05".
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use def::meta::human_time;
+///
+/// let time = UNIX_EPOCH + Duration::from_secs(1_709_301_900);
+/// assert_eq!(human_time(time), "2024-03-01 14:05");
+/// ```
+pub fn human_time(time: SystemTime) -> String {
+    let seconds = match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => since.as_secs() as i64,
+        Err(e) => -(e.duration().as_secs() as i64),
+    };
+    let (days, seconds) = (seconds.div_euclid(86400), seconds.rem_euclid(86400));
+    let (year, month, day) = civil_from_days(days);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds % 3600 / 60
+    )
+}
+
+/// Return the name of the user owning path, or their id if it has no name.
+/// Symbolic links aren't followed.
+#[cfg(unix)]
+pub fn owner<P: AsRef<Path>>(path: P) -> io::Result<String> {
+    let uid = fs::symlink_metadata(path)?.uid();
+    let passwd = fs::read_to_string(PASSWD_FILE).unwrap_or_default();
+    Ok(user_name(&passwd, uid).unwrap_or_else(|| uid.to_string()))
+}
+
+/// Return the mount point of the file system path is on: its farthest
+/// ancestor on the same device. Symbolic links are followed.
+#[cfg(unix)]
+pub fn mount_point<P: AsRef<Path>>(path: P) -> io::Result<PathBuf> {
+    let path = fs::canonicalize(path)?;
+    let device = fs::metadata(&path)?.dev();
+    let mut mount = path.as_path();
+    while let Some(parent) = mount.parent() {
+        if fs::metadata(parent)?.dev() != device {
+            break;
+        }
+        mount = parent;
+    }
+    Ok(mount.to_path_buf())
+}
+
+/// Return the name of the user with id uid in passwd, the contents of
+/// PASSWD_FILE.
+#[cfg(unix)]
+fn user_name(passwd: &str, uid: u32) -> Option<String> {
+    passwd.lines().find_map(|line| {
+        let mut fields = line.split(':');
+        let name = fields.next()?;
+        match fields.nth(1)?.parse::<u32>() {
+            Ok(id) if id == uid && !name.is_empty() => Some(name.to_string()),
+            _ => None,
+        }
+    })
+}
+
+/// Return the (year, month, day) of the given number of days since
+/// 1970-01-01 in the proleptic Gregorian calendar.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    // Days are shifted so eras (400-year cycles) start on March 1st, which
+    // puts leap days at the end of a year.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contradicts_test() {
+        let file = |size| PathMetadata {
+            kind: FileKind::File,
+            size,
+        };
+        let dir = |size| PathMetadata {
+            kind: FileKind::Directory,
+            size,
+        };
+
+        for (a, b, contradicts) in [
+            (file(10), file(10), false),
+            (file(10), file(4000), false),
+            (file(1000), file(5000), false),
+            (file(1000), file(10000), true),
+            (file(100000), file(0), true),
+            (dir(1), dir(100), false),
+            (file(10), dir(10), true),
+        ]
+        .iter()
+        {
+            assert_eq!(a.contradicts(b), *contradicts);
+            assert_eq!(b.contradicts(a), *contradicts);
+        }
+    }
+
+    #[test]
+    fn human_time_test() {
+        for (seconds, time) in [
+            (0, "1970-01-01 00:00"),
+            (951_782_400, "2000-02-29 00:00"),
+            (1_709_251_199, "2024-02-29 23:59"),
+            (-86_400, "1969-12-31 00:00"),
+        ]
+        .iter()
+        {
+            let t = if *seconds < 0 {
+                UNIX_EPOCH - std::time::Duration::from_secs(-seconds as u64)
+            } else {
+                UNIX_EPOCH + std::time::Duration::from_secs(*seconds as u64)
+            };
+            assert_eq!(human_time(t), *time, "{}", seconds);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn owner_test() {
+        let passwd = "root:x:0:0:root:/root:/bin/sh\n# comment\n:x:7::\ndef:x:1000:1000::/home/def:/bin/sh\n";
+        for (uid, name) in [(0, Some("root")), (1000, Some("def")), (7, None), (2, None)].iter() {
+            assert_eq!(user_name(passwd, *uid).as_deref(), *name, "{}", uid);
+        }
+
+        assert_eq!(mount_point("/").unwrap(), Path::new("/"));
+        let mount = mount_point(std::env::temp_dir()).unwrap();
+        assert!(std::env::temp_dir()
+            .canonicalize()
+            .unwrap()
+            .starts_with(mount));
+        assert!(!owner("/").unwrap().is_empty());
+        assert!(owner("/def-no-such-path").is_err());
+    }
+}
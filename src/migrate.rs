@@ -0,0 +1,195 @@
+//! Versioning and migration of serialized describers.
+//!
+//! Serialized describers carry a "version" field. Describers serialized before
+//! versioning was introduced lack the field, and are treated as version 0.
+//! When an older describer is loaded, it's migrated one version at a time
+//! until it reaches `CURRENT_VERSION`.
+//!
+//! # Versions
+//!
+//! - 0: Unversioned. Trashed entries may mark patterns using a boolean
+//!   "pattern" field.
+//! - 1: Adds "version". Trashed patterns are marked by their kind, such as
+//!   `"pattern": "children"`.
+//! - 2: Adds "tags", mapping described paths to their tags. Nothing else
+//!   changes, but older versions of the library would drop tags when saving
+//!   a describer, so they refuse to load it instead.
+//!
+//! A describer serialized by every version is kept in `testdata/` as
+//! `v<version>.json`, and must migrate to the current one, which is also kept
+//! in every format. Golden files are rewritten by running tests with
+//! `DEF_UPDATE_GOLDEN=1`, so that changes to serialization show up in review.
+//! Files of older versions must never be rewritten.
+
+use std::error::Error;
+use std::fmt;
+
+use serde_json::{Map, Value};
+
+/// Version of describers serialized by this version of the library.
+pub const CURRENT_VERSION: u64 = 2;
+
+/// Name of the field holding the version.
+const VERSION_FIELD: &str = "version";
+
+/// Migrations from each version to the next, indexed by the older version.
+const MIGRATIONS: [fn(&mut Map<String, Value>); CURRENT_VERSION as usize] = [v0_to_v1, v1_to_v2];
+
+/// MigrationError is returned when a serialized describer can't be migrated.
+#[derive(Debug, PartialEq, Clone)]
+pub enum MigrationError {
+    /// The serialized describer isn't valid JSON, or can't be deserialized
+    /// after migration.
+    Invalid(String),
+    /// The serialized describer isn't an object.
+    NotAnObject,
+    /// The version field isn't a non-negative integer.
+    InvalidVersion(Value),
+    /// The describer was serialized by a newer version of the library.
+    Unsupported(u64),
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MigrationError::Invalid(e) => write!(f, "{}", e),
+            MigrationError::NotAnObject => write!(f, "describer isn't an object"),
+            MigrationError::InvalidVersion(v) => write!(f, "invalid version {}", v),
+            MigrationError::Unsupported(v) => write!(
+                f,
+                "version {} is newer than supported version {}",
+                v, CURRENT_VERSION
+            ),
+        }
+    }
+}
+
+impl Error for MigrationError {}
+
+/// Return the version of a serialized describer, 0 if it's unversioned.
+pub fn version(value: &Value) -> Result<u64, MigrationError> {
+    match value
+        .as_object()
+        .ok_or(MigrationError::NotAnObject)?
+        .get(VERSION_FIELD)
+    {
+        None => Ok(0),
+        Some(v) => v
+            .as_u64()
+            .ok_or_else(|| MigrationError::InvalidVersion(v.clone())),
+    }
+}
+
+/// Migrate a serialized describer in place to CURRENT_VERSION. Return the
+/// version it was migrated from.
+pub fn migrate(value: &mut Value) -> Result<u64, MigrationError> {
+    let from = version(value)?;
+    if from > CURRENT_VERSION {
+        return Err(MigrationError::Unsupported(from));
+    }
+
+    let object = value.as_object_mut().ok_or(MigrationError::NotAnObject)?;
+    for migration in MIGRATIONS[from as usize..].iter() {
+        migration(object);
+    }
+    object.insert(VERSION_FIELD.to_string(), Value::from(CURRENT_VERSION));
+    Ok(from)
+}
+
+/// Return CURRENT_VERSION, used as the default version of describers.
+pub(crate) fn current_version() -> u64 {
+    CURRENT_VERSION
+}
+
+/// Migrate from version 0 to 1, replacing boolean "pattern" fields of trashed
+/// entries with "children" (the only kind of pattern in version 0).
+fn v0_to_v1(describer: &mut Map<String, Value>) {
+    let trash = match describer.get_mut("trash").and_then(|t| t.as_array_mut()) {
+        Some(trash) => trash,
+        None => return,
+    };
+    for entry in trash.iter_mut().filter_map(|e| e.as_object_mut()) {
+        match entry.get("pattern") {
+            Some(Value::Bool(true)) => {
+                entry.insert("pattern".to_string(), Value::from("children"));
+            }
+            Some(Value::Bool(false)) => {
+                entry.remove("pattern");
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Migrate from version 1 to 2. Describers of version 1 have no tags, so
+/// only the version changes.
+fn v1_to_v2(_: &mut Map<String, Value>) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn migrate_test() {
+        for (before, after, from) in [
+            (
+                json!({"descriptions": {}, "patterns": {}}),
+                json!({"descriptions": {}, "patterns": {}, "version": 2}),
+                0,
+            ),
+            (
+                json!({
+                    "descriptions": {},
+                    "patterns": {},
+                    "trash": [
+                        {"path": "/a", "description": "A", "pattern": true, "trashed_at": 1},
+                        {"path": "/b", "description": "B", "pattern": false, "trashed_at": 2},
+                    ],
+                }),
+                json!({
+                    "descriptions": {},
+                    "patterns": {},
+                    "trash": [
+                        {"path": "/a", "description": "A", "pattern": "children", "trashed_at": 1},
+                        {"path": "/b", "description": "B", "trashed_at": 2},
+                    ],
+                    "version": 2,
+                }),
+                0,
+            ),
+            (
+                json!({"version": 1, "descriptions": {}, "patterns": {}}),
+                json!({"version": 2, "descriptions": {}, "patterns": {}}),
+                1,
+            ),
+            (
+                json!({"version": 2, "descriptions": {}, "tags": {"/a": ["work"]}}),
+                json!({"version": 2, "descriptions": {}, "tags": {"/a": ["work"]}}),
+                2,
+            ),
+        ]
+        .iter()
+        {
+            let mut value = before.clone();
+            assert_eq!(migrate(&mut value), Ok(*from));
+            assert_eq!(value, *after);
+        }
+    }
+
+    #[test]
+    fn migrate_error_test() {
+        for (value, err) in [
+            (json!([]), MigrationError::NotAnObject),
+            (
+                json!({"version": "1"}),
+                MigrationError::InvalidVersion(json!("1")),
+            ),
+            (json!({"version": 3}), MigrationError::Unsupported(3)),
+        ]
+        .iter()
+        {
+            assert_eq!(migrate(&mut value.clone()), Err(err.clone()));
+        }
+    }
+}
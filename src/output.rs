@@ -0,0 +1,362 @@
+use std::sync::OnceLock;
+
+use def::daemon::Lookup;
+use def::{Candidate, PatternKind};
+use serde::Serialize;
+use terminal_size::{terminal_size, Width};
+
+use crate::theme;
+
+/// Output format, set once by init.
+static FORMAT: OnceLock<OutputFormat> = OnceLock::new();
+
+/// Number of spaces descriptions printed below their paths are indented by.
+pub const INDENT: usize = 2;
+
+/// Marker of summaries of descriptions which were shortened.
+const ELLIPSIS: &str = "…";
+
+/// OutputFormat is how descriptions are printed.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum OutputFormat {
+    /// Colored and aligned output, for people.
+    #[default]
+    Human,
+    /// "path: description" lines without colors, see the --plain flag.
+    Plain,
+    /// A JSON object, or an array of objects when listing many paths.
+    Json,
+    /// Tab-separated path, description, and source lines.
+    Tsv,
+}
+
+impl OutputFormat {
+    /// Return the format with the given name, or None if there's none.
+    pub fn from_name(name: &str) -> Option<OutputFormat> {
+        match name {
+            "human" => Some(OutputFormat::Human),
+            "plain" => Some(OutputFormat::Plain),
+            "json" => Some(OutputFormat::Json),
+            "tsv" => Some(OutputFormat::Tsv),
+            _ => None,
+        }
+    }
+}
+
+/// Source is where a description comes from.
+#[derive(Serialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum Source {
+    /// A description added for the path itself.
+    Specific,
+    /// A pattern or a glob.
+    Pattern,
+    /// The description of an ancestor, see --inherit.
+    Inherited,
+}
+
+impl Source {
+    /// Return the source of an entry of the given pattern kind.
+    pub fn of(pattern: Option<PatternKind>) -> Source {
+        match pattern {
+            Some(_) => Source::Pattern,
+            None => Source::Specific,
+        }
+    }
+
+    /// Return the source of a lookup's description, or None if it has none.
+    pub fn of_lookup(lookup: &Lookup) -> Option<Source> {
+        lookup.description.as_ref()?;
+        Some(match (&lookup.inherited_from, lookup.specific) {
+            (Some(_), _) => Source::Inherited,
+            (None, true) => Source::Specific,
+            (None, false) => Source::Pattern,
+        })
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Source::Specific => "specific",
+            Source::Pattern => "pattern",
+            Source::Inherited => "inherited",
+        }
+    }
+}
+
+/// Record is a path and its description, printed in structured formats.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Record<'a> {
+    pub path: &'a str,
+    /// Description of path, or None if it has none.
+    pub description: Option<&'a str>,
+    /// Source of the description, or None if path has none.
+    pub source: Option<Source>,
+    /// Ancestor the description is inherited from, if it is (see --inherit).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inherited_from: Option<&'a str>,
+}
+
+/// init sets the output format.
+pub fn init(format: OutputFormat) {
+    let _ = FORMAT.set(format);
+}
+
+/// format returns the output format, Human if init wasn't called.
+pub fn format() -> OutputFormat {
+    FORMAT.get().copied().unwrap_or_default()
+}
+
+/// is_structured returns true if output is meant for other programs (JSON or
+/// TSV), in which case commands print records using print_record or
+/// print_records instead of their usual output.
+pub fn is_structured() -> bool {
+    matches!(format(), OutputFormat::Json | OutputFormat::Tsv)
+}
+
+/// print_record prints a single record, as a JSON object or a TSV line.
+pub fn print_record(record: &Record) {
+    match format() {
+        OutputFormat::Json => println!("{}", to_json(record)),
+        _ => println!("{}", to_tsv(record)),
+    }
+}
+
+/// print_records prints records, as a JSON array or TSV lines.
+pub fn print_records(records: &[Record]) {
+    match format() {
+        OutputFormat::Json => println!("{}", to_json(&records)),
+        _ => {
+            for record in records.iter() {
+                println!("{}", to_tsv(record));
+            }
+        }
+    }
+}
+
+/// print_candidates prints descriptions and patterns applying to a path (see
+/// Describer::explain), as a JSON array or TSV lines of key, pattern kind,
+/// priority, and description.
+pub fn print_candidates(candidates: &[Candidate]) {
+    match format() {
+        OutputFormat::Json => println!("{}", to_json(&candidates)),
+        _ => {
+            for c in candidates.iter() {
+                let kind = c.pattern.map(|k| to_json(&k).replace('"', ""));
+                println!(
+                    "{}",
+                    tsv_line(&[
+                        &c.key,
+                        kind.as_deref().unwrap_or_default(),
+                        &c.priority.to_string(),
+                        &c.description,
+                    ])
+                );
+            }
+        }
+    }
+}
+
+/// terminal_width returns the width of the terminal stdout is printed to, or
+/// None if it isn't a terminal or output is plain, in which case output isn't
+/// wrapped or shortened to fit.
+pub fn terminal_width() -> Option<usize> {
+    if theme::is_plain() {
+        return None;
+    }
+    terminal_size().map(|(Width(width), _)| width as usize)
+}
+
+/// wrap splits text into lines at most width characters wide, breaking lines
+/// between words. Wrapped lines are indented like the lines they continue,
+/// and aligned with the text of list items. Words longer than width are kept
+/// whole, and ANSI escape sequences (such as colors) don't count towards
+/// width.
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    for line in text.lines() {
+        let words = line.trim_start_matches(' ');
+        let marker = words.split(' ').next().filter(|w| is_list_marker(w));
+        let indent =
+            " ".repeat(line.len() - words.len() + marker.map_or(0, |m| m.chars().count() + 1));
+        let mut wrapped = line[..line.len() - words.len()].to_string();
+        let mut wrapped_width = wrapped.len();
+        for (i, word) in words.split(' ').enumerate() {
+            let word_width = visible_width(word);
+            if i > 0 && wrapped_width + 1 + word_width > width {
+                lines.push(wrapped);
+                wrapped = indent.clone();
+                wrapped_width = indent.len();
+            } else if i > 0 {
+                wrapped.push(' ');
+                wrapped_width += 1;
+            }
+            wrapped.push_str(word);
+            wrapped_width += word_width;
+        }
+        lines.push(wrapped);
+    }
+    lines
+}
+
+/// is_list_marker returns true if word marks an item of a list, such as "-"
+/// or "1.".
+fn is_list_marker(word: &str) -> bool {
+    match word.strip_suffix('.') {
+        Some(number) => !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()),
+        None => matches!(word, "-" | "*" | "•"),
+    }
+}
+
+/// summary returns the first line of description, so that listings print a
+/// line per path. The line is followed by "…" if description has more lines,
+/// and shortened to width characters if width is given.
+pub fn summary(description: &str, width: Option<usize>) -> String {
+    let mut lines = description.lines().map(str::trim).filter(|l| !l.is_empty());
+    let first = lines.next().unwrap_or_default();
+    let more = lines.next().is_some();
+    let first_width = first.chars().count();
+    match width {
+        Some(width) if first_width + usize::from(more) > width => {
+            let shortened: String = first.chars().take(width.saturating_sub(1)).collect();
+            format!("{}{}", shortened.trim_end(), ELLIPSIS)
+        }
+        _ if more => format!("{}{}", first, ELLIPSIS),
+        _ => first.to_string(),
+    }
+}
+
+/// visible_width returns the number of characters of text shown by the
+/// terminal, excluding ANSI escape sequences.
+pub fn visible_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Sequences end with a letter, such as "m" of "\x1b[1;32m".
+            chars.find(|c| c.is_ascii_alphabetic());
+        } else {
+            width += 1;
+        }
+    }
+    width
+}
+
+/// to_json returns value as a single line of JSON.
+fn to_json<T: Serialize>(value: &T) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+/// to_tsv returns a record as a line of tab-separated path, description, and
+/// source. Missing fields are empty.
+fn to_tsv(record: &Record) -> String {
+    tsv_line(&[
+        record.path,
+        record.description.unwrap_or_default(),
+        record.source.map(Source::name).unwrap_or_default(),
+    ])
+}
+
+/// tsv_line returns fields separated by tabs, with tabs and newlines in
+/// fields replaced by spaces.
+fn tsv_line(fields: &[&str]) -> String {
+    fields
+        .iter()
+        .map(|field| field.replace(['\t', '\n', '\r'], " "))
+        .collect::<Vec<String>>()
+        .join("\t")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_test() {
+        for (record, json, tsv) in [
+            (
+                Record {
+                    path: "/etc",
+                    description: Some("Configuration,\n\tsystem-wide."),
+                    source: Some(Source::Specific),
+                    inherited_from: None,
+                },
+                r#"{"path":"/etc","description":"Configuration,\n\tsystem-wide.","source":"specific"}"#,
+                "/etc\tConfiguration,  system-wide.\tspecific",
+            ),
+            (
+                Record {
+                    path: "/tmp",
+                    description: None,
+                    source: None,
+                    inherited_from: None,
+                },
+                r#"{"path":"/tmp","description":null,"source":null}"#,
+                "/tmp\t\t",
+            ),
+            (
+                Record {
+                    path: "/srv/www",
+                    description: Some("Served."),
+                    source: Some(Source::Inherited),
+                    inherited_from: Some("/srv"),
+                },
+                r#"{"path":"/srv/www","description":"Served.","source":"inherited","inherited_from":"/srv"}"#,
+                "/srv/www\tServed.\tinherited",
+            ),
+        ]
+        .iter()
+        {
+            assert_eq!(to_json(record), *json);
+            assert_eq!(to_tsv(record), *tsv);
+        }
+    }
+
+    #[test]
+    fn wrap_test() {
+        for (text, width, lines) in [
+            ("A short line.", 20, vec!["A short line."]),
+            (
+                "Files served by nginx, and its logs.",
+                16,
+                vec!["Files served by", "nginx, and its", "logs."],
+            ),
+            (
+                "Runbook:\n\n- Restart it.\n10. Check it.",
+                10,
+                vec!["Runbook:", "", "- Restart", "  it.", "10. Check", "    it."],
+            ),
+            (
+                "  indented code block",
+                12,
+                vec!["  indented", "  code block"],
+            ),
+            ("a verylongword", 5, vec!["a", "verylongword"]),
+            (
+                "\x1b[1mBold\x1b[0m text",
+                9,
+                vec!["\x1b[1mBold\x1b[0m text"],
+            ),
+        ]
+        .iter()
+        {
+            assert_eq!(wrap(text, *width), *lines, "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn summary_test() {
+        for (description, width, summary) in [
+            ("A directory.", None, "A directory."),
+            ("A directory.", Some(20), "A directory."),
+            ("A directory.", Some(8), "A direc…"),
+            ("Runbook:\n\n1. Restart it.", None, "Runbook:…"),
+            ("Runbook:\n\n1. Restart it.", Some(8), "Runbook…"),
+            ("\n  Indented.\n", None, "Indented."),
+            ("", None, ""),
+        ]
+        .iter()
+        {
+            assert_eq!(super::summary(description, *width), *summary);
+        }
+    }
+}
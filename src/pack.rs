@@ -0,0 +1,312 @@
+//! Pattern packs.
+//!
+//! A pack is a shareable set of patterns, such as patterns describing the
+//! layout of a Linux server or of a data science project, so that patterns
+//! maintained by others can be installed at once, and removed later. Packs
+//! are written in any format of `def::format`, such as JSON:
+//!
+//! ```json
+//! {
+//!   "name": "datascience-project",
+//!   "author": "Jane Doe",
+//!   "description": "Layout of data science projects.",
+//!   "prefix": "$HOME/projects",
+//!   "patterns": [
+//!     {"path": "data", "pattern": "{name} is a dataset."},
+//!     {"path": "notebooks", "pattern": "** is a notebook.", "kind": "recursive"},
+//!     {"path": "*.ipynb", "pattern": "A notebook.", "kind": "glob"}
+//!   ]
+//! }
+//! ```
+//!
+//! Paths of patterns are relative to the pack's prefix, like keys of local
+//! files (see `def::local`): "." is the prefix itself, and globs without a
+//! separator (such as `*.ipynb`) match paths anywhere. The prefix is a
+//! convention of the pack, "/" by default, which can be replaced when the
+//! pack is installed.
+//!
+//! Packs can be signed using minisign, so that packs fetched from URLs can
+//! be checked against the public keys of authors the user trusts, see
+//! `verify`.
+
+use std::error::Error;
+use std::fmt;
+
+#[cfg(feature = "signing")]
+use minisign_verify::{PublicKey, Signature};
+use serde::{Deserialize, Serialize};
+
+use crate::pattern::{Pattern, PatternError};
+use crate::{local, paths, DefPath, Describer, PatternKind};
+
+/// Pack is a named set of patterns, see the module's documentation.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+pub struct Pack {
+    /// Name of the pack, made of ASCII letters, digits, "-", and "_".
+    pub name: String,
+    /// Author of the pack.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// What the pack describes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Directory paths of patterns are relative to.
+    #[serde(default = "default_prefix")]
+    pub prefix: String,
+    /// Patterns of the pack.
+    pub patterns: Vec<PackPattern>,
+}
+
+/// PackPattern is a pattern of a pack.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+pub struct PackPattern {
+    /// Path the pattern is mapped to relative to the pack's prefix, or a
+    /// glob for globs.
+    pub path: String,
+    /// The pattern.
+    pub pattern: String,
+    /// Kind of the pattern, "children" by default.
+    #[serde(default = "default_kind")]
+    pub kind: PatternKind,
+}
+
+/// Return the default prefix of packs.
+fn default_prefix() -> String {
+    "/".to_string()
+}
+
+/// Return the default kind of patterns of packs.
+fn default_kind() -> PatternKind {
+    PatternKind::Children
+}
+
+/// PackError is returned when a pack is invalid.
+#[derive(Debug, PartialEq, Clone)]
+pub enum PackError {
+    /// The pack can't be deserialized.
+    Invalid(String),
+    /// The name of the pack is empty, or has characters other than ASCII
+    /// letters, digits, "-", and "_".
+    InvalidName(String),
+    /// A pattern mapped to the given path can't be parsed.
+    InvalidPattern(String, PatternError),
+    /// The given public key can't be decoded.
+    InvalidKey(String),
+    /// The signature of a pack can't be decoded, or wasn't made by a trusted
+    /// key.
+    InvalidSignature(String),
+}
+
+impl fmt::Display for PackError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PackError::Invalid(e) => write!(f, "{}", e),
+            PackError::InvalidName(name) => write!(f, "invalid pack name \"{}\"", name),
+            PackError::InvalidPattern(path, e) => write!(f, "{}: {}", path, e),
+            PackError::InvalidKey(key) => write!(f, "invalid public key \"{}\"", key),
+            PackError::InvalidSignature(e) => write!(f, "invalid signature: {}", e),
+        }
+    }
+}
+
+impl Error for PackError {}
+
+impl Pack {
+    /// Deserialize a pack from a decoded value (see `Format::decode`), and
+    /// check that it's valid.
+    pub fn from_value(value: serde_json::Value) -> Result<Pack, PackError> {
+        let pack: Pack =
+            serde_json::from_value(value).map_err(|e| PackError::Invalid(e.to_string()))?;
+        pack.validate()?;
+        Ok(pack)
+    }
+
+    /// Return an error if the name of the pack or one of its patterns is
+    /// invalid.
+    pub fn validate(&self) -> Result<(), PackError> {
+        let valid_name = !self.name.is_empty()
+            && self
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid_name {
+            return Err(PackError::InvalidName(self.name.clone()));
+        }
+        for p in self.patterns.iter() {
+            Pattern::parse(&p.pattern).map_err(|e| PackError::InvalidPattern(p.path.clone(), e))?;
+        }
+        Ok(())
+    }
+
+    /// Return the kind, key, and text of each pattern, with keys resolved
+    /// against the pack's prefix.
+    pub fn entries(&self) -> Vec<(PatternKind, String, &str)> {
+        self.patterns
+            .iter()
+            .map(|p| {
+                let key = local::absolute_key(&p.path, &self.prefix);
+                let key = match p.kind {
+                    PatternKind::Glob => paths::glob_key(&key),
+                    _ => DefPath::new(&key).into(),
+                };
+                (p.kind, key, p.pattern.as_str())
+            })
+            .collect()
+    }
+}
+
+/// Check that signature, the contents of a minisign signature file (such as
+/// "pack.json.minisig"), is a signature of bytes made by one of keys. Keys
+/// are minisign public keys, either in base64 or as the contents of a
+/// "minisign.pub" file.
+#[cfg(feature = "signing")]
+pub fn verify(bytes: &[u8], signature: &str, keys: &[String]) -> Result<(), PackError> {
+    let signature =
+        Signature::decode(signature).map_err(|e| PackError::InvalidSignature(e.to_string()))?;
+    for key in keys.iter() {
+        let encoded = key.lines().last().unwrap_or_default().trim();
+        let public_key =
+            PublicKey::from_base64(encoded).map_err(|_| PackError::InvalidKey(key.clone()))?;
+        if public_key.verify(bytes, &signature, true).is_ok() {
+            return Ok(());
+        }
+    }
+    Err(PackError::InvalidSignature(
+        "not signed by a trusted key".to_string(),
+    ))
+}
+
+/// Add patterns of pack to describer, replacing patterns mapped to the same
+/// keys. Return the number of added patterns. Nothing is added if the pack
+/// is invalid.
+pub fn install(describer: &mut Describer, pack: &Pack) -> Result<usize, PackError> {
+    pack.validate()?;
+    let entries = pack.entries();
+    for (kind, key, text) in entries.iter() {
+        describer
+            .add_pattern_with_kind(key, text, *kind)
+            .map_err(|e| PackError::InvalidPattern(key.clone(), e))?;
+    }
+    Ok(entries.len())
+}
+
+/// Remove patterns of pack from describer, unless they were changed since
+/// the pack was installed. Return the number of removed patterns.
+pub fn uninstall(describer: &mut Describer, pack: &Pack) -> usize {
+    let mut removed = 0;
+    for (kind, key, text) in pack.entries() {
+        let map = describer.map_mut(Some(kind));
+        if map.text(&key) == Some(text) {
+            map.take(&key);
+            removed += 1;
+        }
+    }
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn pack_test() {
+        let pack = Pack::from_value(json!({
+            "name": "server",
+            "prefix": "/srv/",
+            "patterns": [
+                {"path": ".", "pattern": "{name} is served."},
+                {"path": "logs", "pattern": "** is a log.", "kind": "recursive"},
+                {"path": "*.conf", "pattern": "Configures {stem}.", "kind": "glob"},
+            ],
+        }))
+        .unwrap();
+
+        let mut d = Describer::new();
+        assert_eq!(install(&mut d, &pack), Ok(3));
+        for (path, description) in [
+            ("/srv/www", Some("www is served.")),
+            ("/srv/logs/a/b", Some("a/b is a log.")),
+            ("/etc/nginx.conf", Some("Configures nginx.")),
+            ("/var/www", None),
+        ]
+        .iter()
+        {
+            assert_eq!(d.describe(path).as_deref(), *description, "{}", path);
+        }
+
+        // Changed patterns are kept.
+        d.add_pattern("/srv", "Changed.").unwrap();
+        assert_eq!(uninstall(&mut d, &pack), 2);
+        assert_eq!(d.describe("/srv/www"), Some("Changed.".to_string()));
+        assert_eq!(d.describe("/etc/nginx.conf"), None);
+    }
+
+    #[test]
+    fn invalid_pack_test() {
+        for (value, err) in [
+            (
+                json!({"name": "a pack", "patterns": []}),
+                PackError::InvalidName("a pack".to_string()),
+            ),
+            (
+                json!({"name": "pack", "patterns": [{"path": "a", "pattern": "{owner}"}]}),
+                PackError::InvalidPattern(
+                    "a".to_string(),
+                    PatternError::UnknownPlaceholder("owner".to_string()),
+                ),
+            ),
+        ]
+        .iter()
+        {
+            assert_eq!(Pack::from_value(value.clone()), Err(err.clone()));
+        }
+        assert!(matches!(
+            Pack::from_value(json!({"name": "pack"})),
+            Err(PackError::Invalid(_))
+        ));
+    }
+
+    #[cfg(feature = "signing")]
+    #[test]
+    fn verify_test() {
+        let bytes = br#"{"name": "server", "patterns": []}"#;
+        let signature = "untrusted comment: signature from minisign secret key
+RUQBAgMEBQYHCBQCX3CsCRwDIybkaQCIGvHnBEWgvfIaXG4IoP4t5HQumtnTS3qSui39/aPfRdP+f6k8spMVucgw3B7hag+aqAs=
+trusted comment: timestamp:1700000000\tfile:pack.json
++axCY1+1wzuqkE0RgwQOs99Xam4eCUZrcRm01vuBzPVul01y22cvhZlUw8YE83tBa2qx5qyMy+ahAj5ijvFBCA==";
+        let key = "RWQBAgMEBQYHCAOhB7/zzhC+HXDdGOdLwJln5NYwm6UNXx3chmQSVTG4";
+        let other = "RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3";
+        let untrusted = Err(PackError::InvalidSignature(
+            "not signed by a trusted key".to_string(),
+        ));
+
+        for (bytes, keys, result) in [
+            (&bytes[..], vec![key.to_string()], Ok(())),
+            (
+                &bytes[..],
+                vec![
+                    other.to_string(),
+                    format!("untrusted comment: key\n{}\n", key),
+                ],
+                Ok(()),
+            ),
+            (&bytes[..], vec![other.to_string()], untrusted.clone()),
+            (&bytes[..], vec![], untrusted.clone()),
+            (&b"{}"[..], vec![key.to_string()], untrusted.clone()),
+            (
+                &bytes[..],
+                vec!["key".to_string()],
+                Err(PackError::InvalidKey("key".to_string())),
+            ),
+        ]
+        .iter()
+        {
+            assert_eq!(verify(bytes, signature, keys), *result, "{:?}", keys);
+        }
+        assert!(matches!(
+            verify(bytes, "signature", &[key.to_string()]),
+            Err(PackError::InvalidSignature(_))
+        ));
+    }
+}
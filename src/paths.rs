@@ -0,0 +1,675 @@
+//! Paths used as keys, mapping of keys between platform conventions, and
+//! resolution of paths to keys.
+//!
+//! Paths descriptions are mapped to are kept as `DefPath`s, which are
+//! normalized so that different spellings of a path (such as "/dir/" and
+//! "/dir") map to the same key. Keys always use `SEPERATOR`: on Windows,
+//! backslashes are replaced by it, and drive letters are kept in upper case,
+//! so `c:\Users` is kept as `C:/Users` (whose root is `C:/`).
+//!
+//! A config authored on another platform may contain keys that never match
+//! a local path, such as keys using backslashes or drive letters on Unix, or
+//! keys without a drive letter on Windows. This module detects such keys and
+//! converts them to the local convention where possible.
+//!
+//! Paths given on the command line are resolved to keys either canonically
+//! (see `absolute`), which requires them to exist and follows symlinks, or
+//! lexically (see `lexical`), which doesn't.
+
+use std::borrow::Borrow;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, SEPERATOR};
+
+/// Abbreviation of the home directory in contracted paths.
+const HOME_ABBREVIATION: &str = "~";
+
+/// DefPath is a path used as a key, such as a described path or the
+/// directory a pattern is mapped to. Repeated separators are collapsed, and
+/// trailing separators (other than the root) are removed. On Windows, paths
+/// are converted to keys as described in the module's documentation. On
+/// other platforms, backslashes are kept as is, see `to_local` to convert
+/// them.
+///
+/// # Examples
+///
+/// ```
+/// use def::DefPath;
+///
+/// let path = DefPath::from("/home//user/notes/");
+/// assert_eq!(path.as_str(), "/home/user/notes");
+/// assert_eq!(path.split_parent(), Some(("/home/user", "notes")));
+/// assert_eq!(path.join("todo.md").as_str(), "/home/user/notes/todo.md");
+/// assert_eq!(path.contract("/home/user"), "~/notes");
+/// ```
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+#[serde(from = "String", into = "String")]
+pub struct DefPath(String);
+
+impl DefPath {
+    /// Create a path, normalizing it.
+    pub fn new(path: &str) -> DefPath {
+        DefPath(normalize(path, cfg!(windows)))
+    }
+
+    /// Return the path as a string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Split the path into its parent and its name, or return None if it has
+    /// no parent. The parent of a path under the root is the root.
+    pub fn split_parent(&self) -> Option<(&str, &str)> {
+        iter_ancestors(&self.0).next()
+    }
+
+    /// Return the parent of the path, see `split_parent`.
+    pub fn parent(&self) -> Option<&str> {
+        self.split_parent().map(|(parent, _)| parent)
+    }
+
+    /// Return the last component of the path.
+    pub fn name(&self) -> &str {
+        self.split_parent().map_or(&self.0, |(_, name)| name)
+    }
+
+    /// Return the path of name under this path.
+    pub fn join(&self, name: &str) -> DefPath {
+        DefPath::new(&format!("{}{}{}", self.0, SEPERATOR, name))
+    }
+
+    /// Return all ancestors of the path, see `ancestors`.
+    pub fn ancestors(&self) -> Vec<(&str, &str)> {
+        ancestors(&self.0)
+    }
+
+    /// Return true if the path is prefix or is under prefix, see `is_under`.
+    pub fn is_under(&self, prefix: &str) -> bool {
+        is_under(&self.0, prefix)
+    }
+
+    /// Return the path with prefix replaced by new_prefix, see `rebase`.
+    pub fn rebase(&self, prefix: &str, new_prefix: &str) -> Option<DefPath> {
+        rebase(&self.0, prefix, new_prefix).map(DefPath::from)
+    }
+
+    /// Return the path for display, with home replaced by "~" if the path is
+    /// home or is under it.
+    pub fn contract(&self, home: &str) -> String {
+        let home = DefPath::new(home);
+        if home.0.is_empty() || root_len(&home.0) == Some(home.0.len()) {
+            return self.0.clone();
+        }
+        match rebase(&self.0, &home.0, HOME_ABBREVIATION) {
+            Some(contracted) => contracted,
+            None => self.0.clone(),
+        }
+    }
+}
+
+impl From<String> for DefPath {
+    fn from(path: String) -> DefPath {
+        DefPath::new(&path)
+    }
+}
+
+impl<T: AsRef<str> + ?Sized> From<&T> for DefPath {
+    fn from(path: &T) -> DefPath {
+        DefPath::new(path.as_ref())
+    }
+}
+
+impl From<DefPath> for String {
+    fn from(path: DefPath) -> String {
+        path.0
+    }
+}
+
+impl AsRef<str> for DefPath {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for DefPath {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for DefPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Separator used by Windows paths.
+const FOREIGN_SEPERATOR: char = '\\';
+
+/// Prefix of verbatim Windows paths (such as `\\?\C:\Users`), which
+/// fs::canonicalize returns on Windows, after replacing backslashes.
+const VERBATIM_PREFIX: &str = "//?/";
+
+/// Return path normalized as a key. If windows is true, path is converted
+/// from a Windows path as described in the module's documentation.
+fn normalize(path: &str, windows: bool) -> String {
+    let converted;
+    let mut path = path;
+    if windows {
+        converted = to_key_seperators(path, true);
+        path = converted
+            .strip_prefix(VERBATIM_PREFIX)
+            .unwrap_or(&converted);
+    }
+
+    let mut normalized = String::with_capacity(path.len());
+    for c in path.chars() {
+        if !(c == SEPERATOR && normalized.ends_with(SEPERATOR)) {
+            normalized.push(c);
+        }
+    }
+    if windows && drive_letter(&normalized).is_some() {
+        normalized[..1].make_ascii_uppercase();
+    }
+    if root_len(&normalized).is_none_or(|len| normalized.len() > len)
+        && normalized.ends_with(SEPERATOR)
+    {
+        normalized.pop();
+    }
+    normalized
+}
+
+/// Return path with backslashes replaced by `SEPERATOR` if windows is true,
+/// or path as is otherwise. Used for globs, which aren't otherwise
+/// normalized.
+fn to_key_seperators(path: &str, windows: bool) -> String {
+    if windows {
+        path.replace(FOREIGN_SEPERATOR, &SEPERATOR.to_string())
+    } else {
+        path.to_string()
+    }
+}
+
+/// Return a glob with its separators converted as keys' are on this
+/// platform (see `DefPath`).
+pub fn glob_key(glob: &str) -> String {
+    to_key_seperators(glob, cfg!(windows))
+}
+
+/// Return the length of the root of a key, such as `/` or `C:/`, or None if
+/// the key isn't absolute.
+pub fn root_len(key: &str) -> Option<usize> {
+    let start = drive_letter(key).unwrap_or(0);
+    if key[start..].starts_with(SEPERATOR) {
+        Some(start + SEPERATOR.len_utf8())
+    } else {
+        None
+    }
+}
+
+/// KeyWarning describes a key that is likely incompatible with this platform.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum KeyWarning {
+    /// The key contains backslash separators.
+    Backslashes(String),
+    /// The key starts with a drive letter, such as `C:`.
+    DriveLetter(String),
+    /// The key is absolute, but has no drive letter (on Windows).
+    NoDriveLetter(String),
+    /// The keys only differ in case, and map to the same path on
+    /// case-insensitive platforms.
+    CaseCollision(String, String),
+}
+
+impl fmt::Display for KeyWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            KeyWarning::Backslashes(k) => write!(f, "{:?} contains backslashes", k),
+            KeyWarning::DriveLetter(k) => write!(f, "{:?} starts with a drive letter", k),
+            KeyWarning::NoDriveLetter(k) => write!(f, "{:?} has no drive letter", k),
+            KeyWarning::CaseCollision(a, b) => {
+                write!(f, "{:?} and {:?} only differ in case", a, b)
+            }
+        }
+    }
+}
+
+/// Return warnings about the given key, without considering other keys.
+pub fn check(key: &str) -> Vec<KeyWarning> {
+    check_for(key, cfg!(windows))
+}
+
+/// Return warnings about the given key on Windows if windows is true, or on
+/// other platforms otherwise.
+fn check_for(key: &str, windows: bool) -> Vec<KeyWarning> {
+    let mut warnings = Vec::new();
+    if windows {
+        if key.starts_with(SEPERATOR) {
+            warnings.push(KeyWarning::NoDriveLetter(key.to_string()));
+        }
+        return warnings;
+    }
+    if drive_letter(key).is_some() {
+        warnings.push(KeyWarning::DriveLetter(key.to_string()));
+    }
+    if key.contains(FOREIGN_SEPERATOR) {
+        warnings.push(KeyWarning::Backslashes(key.to_string()));
+    }
+    warnings
+}
+
+/// Return a warning for every pair of the given keys that only differ in
+/// case. Keys are expected to be unique.
+pub fn check_case_collisions<I>(keys: I) -> Vec<KeyWarning>
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    let mut keys: Vec<I::Item> = keys.into_iter().collect();
+    keys.sort_by(|a, b| {
+        let (a, b) = (a.as_ref(), b.as_ref());
+        a.to_lowercase().cmp(&b.to_lowercase()).then(a.cmp(b))
+    });
+    keys.windows(2)
+        .map(|pair| (pair[0].as_ref(), pair[1].as_ref()))
+        .filter(|(a, b)| a.to_lowercase() == b.to_lowercase())
+        .map(|(a, b)| KeyWarning::CaseCollision(a.to_string(), b.to_string()))
+        .collect()
+}
+
+/// Convert a key to the local convention. Backslashes are replaced by
+/// `SEPERATOR`, and drive letters are dropped on platforms other than
+/// Windows, so keys differing only in their drive letters convert to the
+/// same key (see `Describer::convert_keys`). Keys without a drive letter
+/// can't be converted on Windows, and are returned as is.
+///
+/// # Examples
+///
+/// ```
+/// # if cfg!(not(windows)) {
+/// assert_eq!(def::paths::to_local("C:\\Users\\user\\dir"), "/Users/user/dir");
+/// assert_eq!(def::paths::to_local("/home/user/dir"), "/home/user/dir");
+/// # }
+/// ```
+pub fn to_local(key: &str) -> String {
+    to_local_for(key, cfg!(windows))
+}
+
+/// Convert a key to the convention of Windows if windows is true, or of
+/// other platforms otherwise.
+fn to_local_for(key: &str, windows: bool) -> String {
+    if windows {
+        return normalize(key, true);
+    }
+    let key = match drive_letter(key) {
+        Some(len) => &key[len..],
+        None => key,
+    };
+    key.replace(FOREIGN_SEPERATOR, &SEPERATOR.to_string())
+}
+
+/// If key is prefix or is under prefix (path-wise), return key with prefix
+/// replaced by new_prefix. Otherwise, return None. Trailing separators of
+/// both prefixes are ignored.
+///
+/// # Examples
+///
+/// ```
+/// use def::paths::rebase;
+///
+/// assert_eq!(rebase("/old/root/dir", "/old/root", "/new"), Some("/new/dir".to_string()));
+/// assert_eq!(rebase("/old/root", "/old/root/", "/new"), Some("/new".to_string()));
+/// assert_eq!(rebase("/old/rootless", "/old/root", "/new"), None);
+/// ```
+pub fn rebase(key: &str, prefix: &str, new_prefix: &str) -> Option<String> {
+    let prefix = trim_trailing_seperators(prefix);
+    let new_prefix = trim_trailing_seperators(new_prefix);
+    let rest = key.strip_prefix(prefix)?;
+    let rest = match rest.strip_prefix(SEPERATOR) {
+        Some(rest) => rest,
+        // Prefix is the root, or key itself.
+        None if prefix.ends_with(SEPERATOR) || rest.is_empty() => rest,
+        None => return None,
+    };
+    if rest.is_empty() {
+        return Some(new_prefix.to_string());
+    }
+    Some(format!(
+        "{}{}{}",
+        new_prefix.trim_end_matches(SEPERATOR),
+        SEPERATOR,
+        rest
+    ))
+}
+
+/// Return all ancestors of path, closest first, each along with path relative
+/// to it. The root of an absolute path is returned as `SEPERATOR` (or as a
+/// drive letter followed by it, see `root_len`).
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(
+///     def::paths::ancestors("/a/b/c"),
+///     vec![("/a/b", "c"), ("/a", "b/c"), ("/", "a/b/c")]
+/// );
+/// ```
+pub fn ancestors(path: &str) -> Vec<(&str, &str)> {
+    iter_ancestors(path).collect()
+}
+
+/// Return ancestors of path like `ancestors`, without collecting them.
+pub fn iter_ancestors(path: &str) -> impl Iterator<Item = (&str, &str)> {
+    let sep_len = SEPERATOR.len_utf8();
+    let root = root_len(path);
+    path.rmatch_indices(SEPERATOR)
+        .filter(move |(i, _)| *i + sep_len < path.len())
+        .map(move |(i, _)| {
+            let ancestor = if root == Some(i + sep_len) {
+                &path[..i + sep_len]
+            } else {
+                &path[..i]
+            };
+            (ancestor, &path[i + sep_len..])
+        })
+}
+
+/// Return true if key is prefix or is under prefix (path-wise). Every key is
+/// under an empty prefix.
+pub fn is_under(key: &str, prefix: &str) -> bool {
+    prefix.is_empty() || rebase(key, prefix, prefix).is_some()
+}
+
+/// Return path without trailing separators, unless path is a root (see
+/// `root_len`).
+fn trim_trailing_seperators(path: &str) -> &str {
+    let trimmed = path.trim_end_matches(SEPERATOR);
+    match root_len(path) {
+        Some(len) if trimmed.len() < len => &path[..len],
+        _ => trimmed,
+    }
+}
+
+/// Return the length of the drive letter prefix (such as `C:`) if the key
+/// starts with one.
+fn drive_letter(key: &str) -> Option<usize> {
+    let mut chars = key.chars();
+    match (chars.next(), chars.next()) {
+        (Some(letter), Some(':')) if letter.is_ascii_alphabetic() => Some(2),
+        _ => None,
+    }
+}
+
+/// Return the absolute, canonical representation of an existing path, which
+/// is used as its key. An error is returned if path doesn't exist, or isn't
+/// valid unicode.
+pub fn absolute<P: AsRef<Path>>(path: P) -> Result<String, Error> {
+    to_key(&fs::canonicalize(path.as_ref())?)
+}
+
+/// Return the absolute representation of path if it exists. Otherwise, path is
+/// resolved lexically (see `lexical`). This is used to refer to descriptions
+/// of paths that may have been deleted.
+pub fn absolute_or_joined<P: AsRef<Path>>(path: P) -> Result<String, Error> {
+    let path = path.as_ref();
+    if path.exists() {
+        absolute(path)
+    } else {
+        lexical(path)
+    }
+}
+
+/// Return the absolute representation of path without touching the file
+/// system: a relative path is joined with the current directory, and "." and
+/// ".." components are collapsed (see `resolve_lexically`). Unlike
+/// `absolute`, path doesn't need to exist, and symlinks aren't followed. An
+/// error is returned if path isn't valid unicode.
+pub fn lexical<P: AsRef<Path>>(path: P) -> Result<String, Error> {
+    to_key(&resolve_lexically(path.as_ref(), &env::current_dir()?))
+}
+
+/// Return the key of an absolute path (see `DefPath`). An error is returned
+/// if path isn't valid unicode.
+fn to_key(path: &Path) -> Result<String, Error> {
+    path.to_str()
+        .map(|p| DefPath::new(p).into())
+        .ok_or_else(|| Error::InvalidPath(path.to_string_lossy().to_string()))
+}
+
+/// Return path joined with base (if it's relative), with "." components
+/// removed, and ".." components removing the component before them. ".." at
+/// the root stays at the root.
+///
+/// # Examples
+///
+/// ```
+/// use def::paths::resolve_lexically;
+/// use std::path::Path;
+///
+/// assert_eq!(
+///     resolve_lexically(Path::new("../b/./c"), Path::new("/home/a")),
+///     Path::new("/home/b/c")
+/// );
+/// ```
+pub fn resolve_lexically(path: &Path, base: &Path) -> PathBuf {
+    let mut resolved = PathBuf::new();
+    for component in base.join(path).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if resolved.parent().is_some() {
+                    resolved.pop();
+                }
+            }
+            c => resolved.push(c),
+        }
+    }
+    resolved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_test() {
+        for (key, warnings) in [
+            ("/home/user", vec![]),
+            (
+                "C:\\Users",
+                vec![
+                    KeyWarning::DriveLetter("C:\\Users".to_string()),
+                    KeyWarning::Backslashes("C:\\Users".to_string()),
+                ],
+            ),
+            (
+                "d:/data",
+                vec![KeyWarning::DriveLetter("d:/data".to_string())],
+            ),
+            (
+                "\\\\server\\share",
+                vec![KeyWarning::Backslashes("\\\\server\\share".to_string())],
+            ),
+            ("/dir/with:colon", vec![]),
+        ]
+        .iter()
+        {
+            assert_eq!(check_for(key, false), *warnings);
+        }
+    }
+
+    #[test]
+    fn check_windows_test() {
+        for (key, warnings) in [
+            ("C:/Users", vec![]),
+            ("*.log", vec![]),
+            (
+                "/home/user",
+                vec![KeyWarning::NoDriveLetter("/home/user".to_string())],
+            ),
+        ]
+        .iter()
+        {
+            assert_eq!(check_for(key, true), *warnings);
+        }
+    }
+
+    #[test]
+    fn check_case_collisions_test() {
+        let keys = vec![
+            "/home/user/Downloads".to_string(),
+            "/home/user/dir".to_string(),
+            "/home/user/downloads".to_string(),
+        ];
+        assert_eq!(
+            check_case_collisions(&keys),
+            vec![KeyWarning::CaseCollision(
+                "/home/user/Downloads".to_string(),
+                "/home/user/downloads".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn rebase_test() {
+        for (key, prefix, new_prefix, rebased) in [
+            ("/old/root", "/old/root", "/new", Some("/new")),
+            ("/old/root/a/b", "/old/root", "/new", Some("/new/a/b")),
+            ("/old/root/a", "/old/root/", "/new/", Some("/new/a")),
+            ("/old/rootless", "/old/root", "/new", None),
+            ("/other/root", "/old/root", "/new", None),
+            ("/old", "/old/root", "/new", None),
+            ("/a/b", "/", "/mnt", Some("/mnt/a/b")),
+            ("C:/a/b", "C:/", "D:/", Some("D:/a/b")),
+            ("C:/a/b", "C:/a", "D:/", Some("D:/b")),
+            ("C:/a", "D:/", "/", None),
+            ("/", "/", "/mnt", Some("/mnt")),
+            ("/old/a", "/old", "/", Some("/a")),
+        ]
+        .iter()
+        {
+            assert_eq!(
+                rebase(key, prefix, new_prefix),
+                rebased.map(|r| r.to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn ancestors_test() {
+        for (path, ancestors_) in [
+            ("/a/b", vec![("/a", "b"), ("/", "a/b")]),
+            ("/a", vec![("/", "a")]),
+            ("/", vec![]),
+            ("a/b", vec![("a", "b")]),
+            ("a", vec![]),
+            ("/a/b/", vec![("/a", "b/"), ("/", "a/b/")]),
+            ("C:/a/b", vec![("C:/a", "b"), ("C:/", "a/b")]),
+            ("C:/", vec![]),
+        ]
+        .iter()
+        {
+            assert_eq!(ancestors(path), *ancestors_);
+        }
+    }
+
+    #[test]
+    fn normalize_windows_test() {
+        for (path, normalized) in [
+            ("C:\\Users\\user\\", "C:/Users/user"),
+            ("c:\\", "C:/"),
+            ("c:/Users//user", "C:/Users/user"),
+            ("\\\\?\\D:\\data", "D:/data"),
+            ("relative\\dir", "relative/dir"),
+            ("/home/user", "/home/user"),
+        ]
+        .iter()
+        {
+            assert_eq!(normalize(path, true), *normalized, "{}", path);
+        }
+        assert_eq!(normalize("a\\b\\", false), "a\\b\\");
+    }
+
+    #[test]
+    fn def_path_test() {
+        for (path, normalized, parent, name) in [
+            ("/a/b", "/a/b", Some("/a"), "b"),
+            ("/a//b/", "/a/b", Some("/a"), "b"),
+            ("/a", "/a", Some("/"), "a"),
+            ("//", "/", None, "/"),
+            ("a/b", "a/b", Some("a"), "b"),
+            ("a", "a", None, "a"),
+            ("", "", None, ""),
+        ]
+        .iter()
+        {
+            let p = DefPath::from(path);
+            assert_eq!(p.as_str(), *normalized, "{}", path);
+            assert_eq!(p.parent(), *parent, "{}", path);
+            assert_eq!(p.name(), *name, "{}", path);
+        }
+    }
+
+    #[test]
+    fn contract_test() {
+        for (path, home, contracted) in [
+            ("/home/user/notes", "/home/user", "~/notes"),
+            ("/home/user", "/home/user/", "~"),
+            ("/home/username", "/home/user", "/home/username"),
+            ("/etc", "/", "/etc"),
+            ("/etc", "", "/etc"),
+        ]
+        .iter()
+        {
+            assert_eq!(DefPath::from(path).contract(home), *contracted, "{}", path);
+        }
+    }
+
+    #[test]
+    fn resolve_lexically_test() {
+        for (path, base, resolved) in [
+            ("notes", "/home/user", "/home/user/notes"),
+            ("./notes/", "/home/user", "/home/user/notes"),
+            ("../../../etc", "/home/user", "/etc"),
+            ("a/../b/./c", "/", "/b/c"),
+            ("/abs/./path/..", "/home/user", "/abs"),
+            (".", "/home/user", "/home/user"),
+        ]
+        .iter()
+        {
+            assert_eq!(
+                resolve_lexically(Path::new(path), Path::new(base)),
+                Path::new(resolved),
+                "{}",
+                path
+            );
+        }
+    }
+
+    #[test]
+    fn to_local_test() {
+        for (key, local) in [
+            ("/home/user", "/home/user"),
+            ("C:\\Users\\user", "/Users/user"),
+            ("c:/Users/user", "/Users/user"),
+            ("relative\\dir", "relative/dir"),
+        ]
+        .iter()
+        {
+            assert_eq!(to_local_for(key, false), *local);
+        }
+        for (key, local) in [
+            ("c:\\Users\\user", "C:/Users/user"),
+            ("/home/user", "/home/user"),
+        ]
+        .iter()
+        {
+            assert_eq!(to_local_for(key, true), *local);
+        }
+    }
+}
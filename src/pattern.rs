@@ -0,0 +1,382 @@
+//! Descriptions of patterns and globs.
+//!
+//! A `Pattern` is a template (see `def::template`) parsed when it's added to
+//! a describer, so that invalid placeholders are reported right away rather
+//! than printed as is, and so that it isn't parsed again every time it's
+//! rendered. Patterns are stored as their source strings, and parsed with
+//! `*` as the placeholder of names unless another one is given (see
+//! `def::syntax`).
+
+use std::borrow::{Borrow, Cow};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::hash::Hash;
+
+use serde::{Deserialize, Serialize};
+
+use crate::meta::{FileInfo, RepoInfo};
+use crate::paths;
+use crate::syntax::DEFAULT_PLACEHOLDER;
+use crate::template::{self, Placeholder, Substitution, Token};
+
+/// Pattern is a parsed description of a pattern or a glob.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone)]
+#[serde(from = "String", into = "String")]
+pub struct Pattern {
+    source: String,
+    tokens: Vec<Token>,
+    /// Placeholder of names the pattern was parsed with.
+    placeholder: char,
+}
+
+impl Pattern {
+    /// Parse a pattern, returning an error if it contains an unknown
+    /// placeholder or an unmatched brace (literal braces are written as `{{`
+    /// and `}}`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use def::pattern::{Pattern, PatternError};
+    ///
+    /// let pattern = Pattern::parse("{stem} in {parent}").unwrap();
+    /// assert_eq!(pattern.render("/dir/notes.md", "notes.md"), "notes in dir");
+    /// assert_eq!(
+    ///     Pattern::parse("owned by {owner}"),
+    ///     Err(PatternError::UnknownPlaceholder("owner".to_string()))
+    /// );
+    /// ```
+    pub fn parse(source: &str) -> Result<Pattern, PatternError> {
+        Pattern::parse_with(source, DEFAULT_PLACEHOLDER)
+    }
+
+    /// Parse a pattern like `parse`, with placeholder replacing `*` (see
+    /// `template::parse_with`).
+    pub fn parse_with(source: &str, placeholder: char) -> Result<Pattern, PatternError> {
+        validate(source)?;
+        Ok(Pattern::from_stored_with(source.to_string(), placeholder))
+    }
+
+    /// Create a pattern without validating it, keeping invalid placeholders as
+    /// is. Used for stored patterns, which may have been added before
+    /// patterns were validated.
+    pub fn from_stored(source: String) -> Pattern {
+        Pattern::from_stored_with(source, DEFAULT_PLACEHOLDER)
+    }
+
+    /// Create a pattern like `from_stored`, with placeholder replacing `*`.
+    pub fn from_stored_with(source: String, placeholder: char) -> Pattern {
+        let tokens = template::parse_with(&source, placeholder);
+        Pattern {
+            source,
+            tokens,
+            placeholder,
+        }
+    }
+
+    /// Return the pattern as parsed with placeholder replacing `*`, borrowed
+    /// if it already was.
+    pub fn with_placeholder(&self, placeholder: char) -> Cow<'_, Pattern> {
+        if self.placeholder == placeholder {
+            Cow::Borrowed(self)
+        } else {
+            Cow::Owned(Pattern::from_stored_with(self.source.clone(), placeholder))
+        }
+    }
+
+    /// Return the source of the pattern.
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Return the parsed pattern.
+    pub fn tokens(&self) -> &[Token] {
+        &self.tokens
+    }
+
+    /// Render the pattern for the given path, see `template::render`.
+    pub fn render(&self, path: &str, relative: &str) -> String {
+        template::render_tokens(&self.tokens, path, relative, None)
+    }
+
+    /// Render the pattern for the given path like `render`, appending it to
+    /// rendered, see `template::render_tokens_into`.
+    pub fn render_into(&self, path: &str, relative: &str, rendered: &mut String) {
+        template::render_tokens_into(&self.tokens, path, relative, None, None, rendered)
+    }
+
+    /// Render the pattern for the given path, filling in metadata
+    /// placeholders from info, see `template::render_with`.
+    pub fn render_with(&self, path: &str, relative: &str, info: &FileInfo) -> String {
+        template::render_tokens(&self.tokens, path, relative, Some(info))
+    }
+
+    /// Render the pattern for the given path, filling in metadata
+    /// placeholders from info and git placeholders from repo, those given,
+    /// see `template::render_tokens_with_repo`.
+    pub fn render_with_repo(
+        &self,
+        path: &str,
+        relative: &str,
+        info: Option<&FileInfo>,
+        repo: Option<&RepoInfo>,
+    ) -> String {
+        template::render_tokens_with_repo(&self.tokens, path, relative, info, repo)
+    }
+
+    /// Return the placeholders rendering the pattern like `render_with_repo`
+    /// fills in, along with their values, see `template::substitutions`.
+    pub fn substitutions(
+        &self,
+        path: &str,
+        relative: &str,
+        info: Option<&FileInfo>,
+        repo: Option<&RepoInfo>,
+    ) -> Vec<Substitution> {
+        template::substitutions(&self.tokens, path, relative, info, repo)
+    }
+
+    /// Return true if the pattern contains a metadata placeholder, such as
+    /// `{size}`, which is filled in from the file system.
+    pub fn uses_metadata(&self) -> bool {
+        self.tokens
+            .iter()
+            .any(|t| matches!(t, Token::Placeholder(p) if p.is_metadata()))
+    }
+
+    /// Return true if the pattern contains a git placeholder, such as
+    /// `{git_branch}`, which is filled in from a git repository.
+    pub fn uses_git(&self) -> bool {
+        self.tokens
+            .iter()
+            .any(|t| matches!(t, Token::Placeholder(p) if p.is_git()))
+    }
+}
+
+impl From<String> for Pattern {
+    fn from(source: String) -> Pattern {
+        Pattern::from_stored(source)
+    }
+}
+
+impl From<Pattern> for String {
+    fn from(pattern: Pattern) -> String {
+        pattern.source
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+/// PatternError is returned when a pattern can't be parsed.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum PatternError {
+    /// A `{` at the given byte offset isn't closed.
+    UnclosedBrace(usize),
+    /// A `}` at the given byte offset isn't opened.
+    UnmatchedBrace(usize),
+    /// A placeholder with the given name doesn't exist.
+    UnknownPlaceholder(String),
+    /// A glob is empty, so no path could match it.
+    EmptyGlob,
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PatternError::UnclosedBrace(i) => {
+                write!(f, "unclosed \"{{\" at {} (write \"{{{{\" for a brace)", i)
+            }
+            PatternError::UnmatchedBrace(i) => {
+                write!(f, "unmatched \"}}\" at {} (write \"}}}}\" for a brace)", i)
+            }
+            PatternError::UnknownPlaceholder(name) => {
+                write!(f, "unknown placeholder {{{}}}", name)
+            }
+            PatternError::EmptyGlob => write!(f, "empty glob"),
+        }
+    }
+}
+
+impl Error for PatternError {}
+
+/// Return an error if source contains an unknown placeholder or an
+/// unmatched brace.
+fn validate(source: &str) -> Result<(), PatternError> {
+    let mut i = 0;
+    while let Some(c) = source[i..].chars().next() {
+        let rest = &source[i..];
+        if rest.starts_with("{{") || rest.starts_with("}}") {
+            i += 2;
+            continue;
+        }
+        match c {
+            '{' => {
+                let end = rest.find('}').ok_or(PatternError::UnclosedBrace(i))?;
+                let name = &rest[1..end];
+                if name.contains('{') {
+                    return Err(PatternError::UnclosedBrace(i));
+                }
+                if Placeholder::from_name(name).is_none() {
+                    return Err(PatternError::UnknownPlaceholder(name.to_string()));
+                }
+                i += end + 1;
+            }
+            '}' => return Err(PatternError::UnmatchedBrace(i)),
+            _ => i += c.len_utf8(),
+        }
+    }
+    Ok(())
+}
+
+/// Text is the value of an entry of a describer: a description or a
+/// pattern.
+pub(crate) trait Text {
+    fn text(&self) -> &str;
+    fn from_text(text: String) -> Self;
+}
+
+impl Text for String {
+    fn text(&self) -> &str {
+        self
+    }
+
+    fn from_text(text: String) -> String {
+        text
+    }
+}
+
+impl Text for Pattern {
+    fn text(&self) -> &str {
+        self.as_str()
+    }
+
+    fn from_text(text: String) -> Pattern {
+        Pattern::from_stored(text)
+    }
+}
+
+/// TextMap is a map of paths to descriptions or patterns, so that maps of
+/// either can be handled alike.
+pub(crate) trait TextMap {
+    /// Return true if key is in the map.
+    fn has(&self, key: &str) -> bool;
+
+    /// Return keys of the map, sorted.
+    fn sorted_keys(&self) -> Vec<&str>;
+
+    /// Return the text key is mapped to, if any.
+    fn text(&self, key: &str) -> Option<&str>;
+
+    /// Return an iterator over (key, text) pairs, in arbitrary order.
+    fn texts(&self) -> Box<dyn Iterator<Item = (&str, &str)> + '_>;
+
+    /// Return an iterator over (key, text) pairs of keys that are prefix or
+    /// are under it (see `paths::is_under`), in arbitrary order.
+    fn texts_under<'a: 'b, 'b>(
+        &'a self,
+        prefix: &'b str,
+    ) -> Box<dyn Iterator<Item = (&'a str, &'a str)> + 'b> {
+        Box::new(
+            self.texts()
+                .filter(move |(key, _)| paths::is_under(key, prefix)),
+        )
+    }
+
+    /// Remove key, and return its text.
+    fn take(&mut self, key: &str) -> Option<String>;
+
+    /// Map key to text, which is trusted to be valid (see
+    /// `Pattern::from_stored`).
+    fn put(&mut self, key: String, text: String);
+
+    /// Move the value of old to new, replacing whatever new held.
+    fn rename(&mut self, old: &str, new: String);
+
+    /// Replace every key with the result of f.
+    fn map_keys(&mut self, f: &dyn Fn(&str) -> String);
+}
+
+impl<K, V> TextMap for HashMap<K, V>
+where
+    K: Borrow<str> + From<String> + Hash + Eq,
+    V: Text,
+{
+    fn has(&self, key: &str) -> bool {
+        self.contains_key(key)
+    }
+
+    fn sorted_keys(&self) -> Vec<&str> {
+        let mut keys: Vec<&str> = self.keys().map(|k| k.borrow()).collect();
+        keys.sort_unstable();
+        keys
+    }
+
+    fn text(&self, key: &str) -> Option<&str> {
+        self.get(key).map(|v| v.text())
+    }
+
+    fn texts(&self) -> Box<dyn Iterator<Item = (&str, &str)> + '_> {
+        Box::new(self.iter().map(|(k, v)| (k.borrow(), v.text())))
+    }
+
+    fn take(&mut self, key: &str) -> Option<String> {
+        self.remove(key).map(|v| v.text().to_string())
+    }
+
+    fn put(&mut self, key: String, text: String) {
+        self.insert(K::from(key), V::from_text(text));
+    }
+
+    fn rename(&mut self, old: &str, new: String) {
+        if let Some(value) = self.remove(old) {
+            self.insert(K::from(new), value);
+        }
+    }
+
+    fn map_keys(&mut self, f: &dyn Fn(&str) -> String) {
+        *self = self
+            .drain()
+            .map(|(k, v)| (K::from(f(k.borrow())), v))
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_test() {
+        for (source, result) in [
+            ("* is {name}", Ok(())),
+            ("{{braces}} and }}", Ok(())),
+            ("{{{stem}}}", Ok(())),
+            (
+                "{owner}",
+                Err(PatternError::UnknownPlaceholder("owner".to_string())),
+            ),
+            ("a {name", Err(PatternError::UnclosedBrace(2))),
+            ("{a {name}", Err(PatternError::UnclosedBrace(0))),
+            ("a } b", Err(PatternError::UnmatchedBrace(2))),
+        ]
+        .iter()
+        {
+            assert_eq!(Pattern::parse(source).map(|_| ()), *result, "{}", source);
+        }
+    }
+
+    #[test]
+    fn serialize_test() {
+        let pattern = Pattern::parse("* in {parent}").unwrap();
+        let json = serde_json::to_string(&pattern).unwrap();
+        assert_eq!(json, "\"* in {parent}\"");
+
+        let stored: Pattern = serde_json::from_str("\"{owner} in {parent}\"").unwrap();
+        assert_eq!(stored.render("/dir/file", "file"), "{owner} in dir");
+    }
+}
@@ -0,0 +1,120 @@
+use std::fs;
+use std::io;
+use std::os::unix::fs::MetadataExt;
+use std::path::PathBuf;
+
+/// parse_file_id parses "<dev>:<inode>" into a device and an inode number.
+/// The device is either "major,minor" as printed by lsof, or a device number
+/// as printed by stat, in decimal or in hexadecimal prefixed with "0x".
+pub fn parse_file_id(id: &str) -> Option<(u64, u64)> {
+    let (dev, ino) = id.rsplit_once(':')?;
+    let dev = match dev.split_once(',') {
+        Some((major, minor)) => makedev(major.parse().ok()?, minor.parse().ok()?),
+        None => match dev.strip_prefix("0x") {
+            Some(hex) => u64::from_str_radix(hex, 16).ok()?,
+            None => dev.parse().ok()?,
+        },
+    };
+    Some((dev, ino.parse().ok()?))
+}
+
+/// parse_fd parses "<pid>:<fd>" into a process ID and a file descriptor.
+pub fn parse_fd(id: &str) -> Option<(u32, u32)> {
+    let (pid, fd) = id.split_once(':')?;
+    Some((pid.parse().ok()?, fd.parse().ok()?))
+}
+
+/// Return the device number of a device with the given major and minor
+/// numbers, encoded like glibc's makedev.
+fn makedev(major: u64, minor: u64) -> u64 {
+    ((major & 0xffff_f000) << 32)
+        | ((major & 0x0000_0fff) << 8)
+        | ((minor & 0xffff_ff00) << 12)
+        | (minor & 0x0000_00ff)
+}
+
+/// path_of_fd returns the path of the file open as fd by process pid. An
+/// error is returned if the process can't be inspected, or fd isn't a file
+/// with a path (such as a socket or a pipe).
+pub fn path_of_fd(pid: u32, fd: u32) -> io::Result<PathBuf> {
+    let path = fs::read_link(format!("/proc/{}/fd/{}", pid, fd))?;
+    if !path.is_absolute() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} isn't a file", path.display()),
+        ));
+    }
+    Ok(path)
+}
+
+/// find_inode returns the path of a file with the given device and inode
+/// numbers that some process has open, or as its working directory. Only
+/// processes readable by the user are checked, and None is returned if none
+/// of them has the file open.
+pub fn find_inode(dev: u64, ino: u64) -> io::Result<Option<PathBuf>> {
+    for process in fs::read_dir("/proc")? {
+        let process = process?.path();
+        let is_pid = process
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().parse::<u32>().is_ok());
+        if !is_pid {
+            continue;
+        }
+
+        let fds = fs::read_dir(process.join("fd")).into_iter().flatten();
+        let links = fds
+            .flatten()
+            .map(|fd| fd.path())
+            .chain(std::iter::once(process.join("cwd")));
+        for link in links {
+            // Metadata of the link is that of the open file, even if it was
+            // renamed since it was opened.
+            let matches = fs::metadata(&link).is_ok_and(|m| m.dev() == dev && m.ino() == ino);
+            if matches {
+                if let Ok(path) = fs::read_link(&link) {
+                    return Ok(Some(path));
+                }
+            }
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_test() {
+        for (id, file_id) in [
+            ("8,1:1234", Some((2049, 1234))),
+            ("259,3:12", Some((66307, 12))),
+            ("2049:1234", Some((2049, 1234))),
+            ("0x801:1234", Some((2049, 1234))),
+            ("1234", None),
+            ("8,x:1234", None),
+            ("sda1:1234", None),
+        ]
+        .iter()
+        {
+            assert_eq!(parse_file_id(id), *file_id, "{}", id);
+        }
+
+        for (id, fd) in [("1234:3", Some((1234, 3))), ("1234", None), ("-1:3", None)].iter() {
+            assert_eq!(parse_fd(id), *fd, "{}", id);
+        }
+    }
+
+    #[test]
+    fn find_test() {
+        let file = std::env::current_exe().unwrap();
+        let open = fs::File::open(&file).unwrap();
+        let metadata = open.metadata().unwrap();
+
+        assert_eq!(
+            find_inode(metadata.dev(), metadata.ino()).unwrap(),
+            Some(file)
+        );
+        assert_eq!(find_inode(metadata.dev(), 0).unwrap(), None);
+    }
+}
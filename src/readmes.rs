@@ -0,0 +1,341 @@
+//! Descriptions of directories kept in files beside them, for teams reading
+//! documentation in their editors rather than through def.
+//!
+//! The description of a directory is kept in a `.dirdesc` file in the
+//! directory, holding nothing but the description, or in a front matter
+//! block at the top of its README.md:
+//!
+//! ```text
+//! ---
+//! def: "Source code."
+//! ---
+//! ```
+//!
+//! Syncing is two-way (see `plan`): a file modified after the description of
+//! its directory was last updated is read back into def, and the description
+//! is written to the file otherwise. Only descriptions added to directories
+//! themselves are synced, patterns aren't.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::drift::modified_time;
+use crate::{DefPath, Describer};
+
+/// Name of files holding descriptions of their directories.
+pub const DIRDESC_FILE: &str = ".dirdesc";
+
+/// Name of READMEs holding descriptions in front matter.
+pub const README_FILE: &str = "README.md";
+
+/// Delimits front matter blocks.
+const FRONT_MATTER: &str = "---";
+
+/// Key of descriptions in front matter.
+const FRONT_MATTER_KEY: &str = "def:";
+
+/// Target is where descriptions of directories are kept.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Target {
+    /// A `.dirdesc` file.
+    Dirdesc,
+    /// Front matter of a README.md, which is created if it doesn't exist.
+    Readme,
+}
+
+impl Target {
+    /// Return the name of the target's files.
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Target::Dirdesc => DIRDESC_FILE,
+            Target::Readme => README_FILE,
+        }
+    }
+
+    /// Return the description kept in dir, or None if there's none.
+    pub fn read<P: AsRef<Path>>(self, dir: P) -> io::Result<Option<String>> {
+        let text = match fs::read_to_string(dir.as_ref().join(self.file_name())) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let description = match self {
+            Target::Dirdesc => Some(text.trim().to_string()),
+            Target::Readme => front_matter(&text),
+        };
+        Ok(description.filter(|d| !d.is_empty()))
+    }
+
+    /// Keep description in dir, replacing the one kept there, if any.
+    pub fn write<P: AsRef<Path>>(self, dir: P, description: &str) -> io::Result<()> {
+        let file = dir.as_ref().join(self.file_name());
+        let text = match self {
+            Target::Dirdesc => format!("{}\n", description),
+            Target::Readme => match fs::read_to_string(&file) {
+                Ok(text) => with_front_matter(&text, description),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => with_front_matter("", description),
+                Err(e) => return Err(e),
+            },
+        };
+        fs::write(file, text)
+    }
+}
+
+/// Direction is the way a description is synced.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Direction {
+    /// The description is written to the directory's file.
+    ToFile,
+    /// The description is read back from the directory's file.
+    FromFile,
+}
+
+/// SyncChange is a description to sync.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SyncChange {
+    /// The described directory.
+    pub path: String,
+    /// The way the description is synced.
+    pub direction: Direction,
+    /// The description, as it will be on both sides.
+    pub description: String,
+}
+
+/// Return changes syncing descriptions of root and directories under it
+/// with their files, sorted by path. Hidden directories aren't synced. A
+/// description differing from its file is written to the file, unless the
+/// file was modified after the description was last updated (or the update
+/// wasn't recorded), in which case it's read back. An error is returned if
+/// a directory or a file can't be read.
+pub fn plan(describer: &Describer, root: &str, target: Target) -> io::Result<Vec<SyncChange>> {
+    let mut changes = Vec::new();
+    let mut dirs = vec![root.to_string()];
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            if !name.starts_with('.') && entry.file_type()?.is_dir() {
+                dirs.push(entry.path().to_string_lossy().to_string());
+            }
+        }
+
+        let path = DefPath::new(&dir);
+        let kept = target.read(&dir)?;
+        let (direction, description) = match (describer.description(&path), kept) {
+            (None, None) => continue,
+            (Some(d), Some(k)) if d == k => continue,
+            (Some(d), None) => (Direction::ToFile, d.to_string()),
+            (None, Some(k)) => (Direction::FromFile, k),
+            (Some(d), Some(k)) => {
+                let file = Path::new(&dir).join(target.file_name());
+                let modified = modified_time(&file.to_string_lossy())?;
+                match describer.timestamps(&path) {
+                    Some(t) if t.updated >= modified => (Direction::ToFile, d.to_string()),
+                    _ => (Direction::FromFile, k),
+                }
+            }
+        };
+        changes.push(SyncChange {
+            path: path.into(),
+            direction,
+            description,
+        });
+    }
+    changes.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(changes)
+}
+
+/// Make the changes of a plan, writing files and adding descriptions read
+/// back to describer, stamped with now (see `Describer::stamp`). An error is
+/// returned if a file can't be written, and changes after it aren't made.
+pub fn apply(
+    describer: &mut Describer,
+    changes: &[SyncChange],
+    target: Target,
+    now: u64,
+) -> io::Result<()> {
+    for change in changes.iter() {
+        match change.direction {
+            Direction::ToFile => target.write(&change.path, &change.description)?,
+            Direction::FromFile => {
+                describer.add_description(change.path.as_str(), &change.description);
+                describer.stamp(change.path.as_str(), now);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Return the description in the front matter of text, if any. Quoted
+/// descriptions are read as JSON strings (which YAML's double-quoted strings
+/// are compatible with), and others as written.
+///
+/// # Examples
+///
+/// ```
+/// use def::readmes::front_matter;
+///
+/// let text = "---\ntitle: Docs\ndef: \"Docs of \\\"def\\\".\"\n---\n# Docs\n";
+/// assert_eq!(front_matter(text), Some("Docs of \"def\".".to_string()));
+/// assert_eq!(front_matter("# Docs\ndef: Not front matter.\n"), None);
+/// ```
+pub fn front_matter(text: &str) -> Option<String> {
+    let mut lines = text.lines();
+    if lines.next()?.trim_end() != FRONT_MATTER {
+        return None;
+    }
+    let value = lines
+        .take_while(|line| line.trim_end() != FRONT_MATTER)
+        .find_map(|line| line.strip_prefix(FRONT_MATTER_KEY))?
+        .trim();
+    if value.starts_with('"') {
+        serde_json::from_str(value).ok()
+    } else {
+        Some(value.to_string())
+    }
+}
+
+/// Return text with description in its front matter, replacing the one
+/// there, if any. Front matter is added to the top of text if it has none.
+///
+/// # Examples
+///
+/// ```
+/// use def::readmes::with_front_matter;
+///
+/// assert_eq!(
+///     with_front_matter("# Docs\n", "Docs."),
+///     "---\ndef: \"Docs.\"\n---\n\n# Docs\n"
+/// );
+/// ```
+pub fn with_front_matter(text: &str, description: &str) -> String {
+    let line = format!(
+        "{} {}",
+        FRONT_MATTER_KEY,
+        serde_json::Value::from(description)
+    );
+    let lines: Vec<&str> = text.lines().collect();
+    let end = match lines.first() {
+        Some(first) if first.trim_end() == FRONT_MATTER => lines[1..]
+            .iter()
+            .position(|l| l.trim_end() == FRONT_MATTER)
+            .map(|i| i + 1),
+        _ => None,
+    };
+    let end = match end {
+        Some(end) => end,
+        None if text.is_empty() => return format!("{0}\n{1}\n{0}\n", FRONT_MATTER, line),
+        None => return format!("{0}\n{1}\n{0}\n\n{2}", FRONT_MATTER, line, text),
+    };
+
+    let mut matter: Vec<String> = lines[1..end]
+        .iter()
+        .filter(|l| !l.starts_with(FRONT_MATTER_KEY))
+        .map(|l| l.to_string())
+        .collect();
+    matter.push(line);
+    let mut synced = format!(
+        "{}\n{}\n{}\n",
+        FRONT_MATTER,
+        matter.join("\n"),
+        FRONT_MATTER
+    );
+    let rest = lines[end + 1..].join("\n");
+    if !rest.is_empty() {
+        synced.push_str(&rest);
+        synced.push('\n');
+    }
+    synced
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn front_matter_test() {
+        for (text, description, synced) in [
+            ("", None, "---\ndef: \"New.\"\n---\n"),
+            ("# Docs\n", None, "---\ndef: \"New.\"\n---\n\n# Docs\n"),
+            (
+                "---\ntitle: Docs\ndef: Old.\n---\n# Docs\n",
+                Some("Old."),
+                "---\ntitle: Docs\ndef: \"New.\"\n---\n# Docs\n",
+            ),
+            (
+                "---\ndef: \"Two\\nlines.\"\n---\n",
+                Some("Two\nlines."),
+                "---\ndef: \"New.\"\n---\n",
+            ),
+            (
+                "---\ntitle: Docs\n---\n\n# Docs\n\ndef: Not matter.\n",
+                None,
+                "---\ntitle: Docs\ndef: \"New.\"\n---\n\n# Docs\n\ndef: Not matter.\n",
+            ),
+        ]
+        .iter()
+        {
+            assert_eq!(front_matter(text).as_deref(), *description, "{}", text);
+            let written = with_front_matter(text, "New.");
+            assert_eq!(written, *synced, "{}", text);
+            assert_eq!(front_matter(&written).as_deref(), Some("New."));
+        }
+    }
+
+    #[test]
+    fn sync_test() {
+        let dir = std::env::temp_dir().join(format!("def-readmes-test-{}", std::process::id()));
+        for sub in ["docs", "src", "src/bin", ".git", "empty"].iter() {
+            fs::create_dir_all(dir.join(sub)).unwrap();
+        }
+        let root = dir.to_string_lossy().to_string();
+        let path = |sub: &str| format!("{}/{}", root, sub);
+        fs::write(dir.join("docs").join(DIRDESC_FILE), "Edited docs.\n").unwrap();
+        fs::write(dir.join("src/bin").join(DIRDESC_FILE), "Binaries.\n").unwrap();
+        fs::write(dir.join(".git").join(DIRDESC_FILE), "Hidden.\n").unwrap();
+        let modified = modified_time(&path("docs/.dirdesc")).unwrap();
+
+        let mut d = Describer::new();
+        d.add_description(path("docs"), "Docs.");
+        d.stamp(path("docs"), modified - 1);
+        d.add_description(path("src"), "Source code.");
+        d.add_description(path("src/bin"), "Binaries.");
+        let planned = plan(&d, &root, Target::Dirdesc).unwrap();
+        apply(&mut d, &planned, Target::Dirdesc, modified + 1).unwrap();
+        let written = Target::Dirdesc.read(dir.join("src")).unwrap();
+
+        d.add_description(path("docs"), "Docs again.");
+        d.stamp(path("docs"), modified + 10);
+        let replanned = plan(&d, &root, Target::Dirdesc).unwrap();
+        let readme = plan(&d, &root, Target::Readme).unwrap().len();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            planned,
+            vec![
+                SyncChange {
+                    path: path("docs"),
+                    direction: Direction::FromFile,
+                    description: "Edited docs.".to_string(),
+                },
+                SyncChange {
+                    path: path("src"),
+                    direction: Direction::ToFile,
+                    description: "Source code.".to_string(),
+                },
+            ]
+        );
+        assert_eq!(d.timestamps(path("docs")).unwrap().updated, modified + 10);
+        assert_eq!(written.as_deref(), Some("Source code."));
+        assert_eq!(
+            replanned,
+            vec![SyncChange {
+                path: path("docs"),
+                direction: Direction::ToFile,
+                description: "Docs again.".to_string(),
+            }]
+        );
+        assert_eq!(readme, 3);
+    }
+}
@@ -0,0 +1,282 @@
+//! The order descriptions and patterns are tried in when describing a path.
+//!
+//! A path is described in steps. Each step is a stage, such as "description"
+//! for descriptions added to the path itself, or a group of pattern stages
+//! whose patterns are ranked together (see `Describer` for how). The first
+//! step that describes the path wins, and stages missing from the order are
+//! never used. The default order is:
+//!
+//! ```json
+//! ["description", ["glob", "pattern", "recursive"], "repository"]
+//! ```
+//!
+//! To prefer globs, such as `*.rs`, over patterns of the directories paths
+//! are in, and to never describe roots of git repositories:
+//!
+//! ```json
+//! ["description", "glob", ["pattern", "recursive"]]
+//! ```
+
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::PatternKind;
+
+/// Stage is a source of descriptions.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum Stage {
+    /// Descriptions added to paths themselves.
+    Description,
+    /// Globs, see `PatternKind::Glob`.
+    Glob,
+    /// Patterns of directories, see `PatternKind::Children`.
+    Pattern,
+    /// Recursive patterns, see `PatternKind::Recursive`.
+    Recursive,
+    /// Roots of git repositories (with "git" feature), see `def::git`.
+    Repository,
+}
+
+impl Stage {
+    /// Return the kind of patterns of the stage, or None if it isn't a
+    /// pattern stage.
+    pub fn pattern_kind(self) -> Option<PatternKind> {
+        match self {
+            Stage::Glob => Some(PatternKind::Glob),
+            Stage::Pattern => Some(PatternKind::Children),
+            Stage::Recursive => Some(PatternKind::Recursive),
+            Stage::Description | Stage::Repository => None,
+        }
+    }
+}
+
+impl From<PatternKind> for Stage {
+    fn from(kind: PatternKind) -> Stage {
+        match kind {
+            PatternKind::Glob => Stage::Glob,
+            PatternKind::Children => Stage::Pattern,
+            PatternKind::Recursive => Stage::Recursive,
+        }
+    }
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Stage::Description => write!(f, "description"),
+            Stage::Glob => write!(f, "glob"),
+            Stage::Pattern => write!(f, "pattern"),
+            Stage::Recursive => write!(f, "recursive"),
+            Stage::Repository => write!(f, "repository"),
+        }
+    }
+}
+
+/// Step is a stage, or a group of pattern stages, as written in settings.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+#[serde(untagged)]
+enum Step {
+    Stage(Stage),
+    Group(Vec<Stage>),
+}
+
+/// ResolutionOrder is a validated order of stages, see the module's
+/// documentation.
+///
+/// # Examples
+///
+/// ```
+/// use def::resolution::{ResolutionOrder, Stage};
+///
+/// let order = ResolutionOrder::new(vec![
+///     vec![Stage::Description],
+///     vec![Stage::Glob],
+///     vec![Stage::Pattern, Stage::Recursive],
+/// ])
+/// .unwrap();
+/// assert!(!order.uses(Stage::Repository));
+/// assert_eq!(order.to_string(), "description > glob > pattern + recursive");
+/// ```
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone)]
+#[serde(try_from = "Vec<Step>", into = "Vec<Step>")]
+pub struct ResolutionOrder {
+    steps: Vec<Vec<Stage>>,
+}
+
+impl Default for ResolutionOrder {
+    fn default() -> ResolutionOrder {
+        ResolutionOrder {
+            steps: vec![
+                vec![Stage::Description],
+                vec![Stage::Glob, Stage::Pattern, Stage::Recursive],
+                vec![Stage::Repository],
+            ],
+        }
+    }
+}
+
+impl ResolutionOrder {
+    /// Create an order of the given steps. An error is returned if the
+    /// order or one of its steps is empty, if a stage appears more than once,
+    /// or if "description" or "repository" are grouped with other stages, as
+    /// only patterns can be ranked against each other.
+    pub fn new(steps: Vec<Vec<Stage>>) -> Result<ResolutionOrder, ResolutionError> {
+        if steps.is_empty() {
+            return Err(ResolutionError::Empty);
+        }
+        let mut seen = Vec::new();
+        for step in steps.iter() {
+            if step.is_empty() {
+                return Err(ResolutionError::EmptyGroup);
+            }
+            for stage in step.iter() {
+                if seen.contains(stage) {
+                    return Err(ResolutionError::Repeated(*stage));
+                }
+                if step.len() > 1 && stage.pattern_kind().is_none() {
+                    return Err(ResolutionError::Grouped(*stage));
+                }
+                seen.push(*stage);
+            }
+        }
+        Ok(ResolutionOrder { steps })
+    }
+
+    /// Return the steps of the order, first to last.
+    pub fn steps(&self) -> impl Iterator<Item = &[Stage]> {
+        self.steps.iter().map(Vec::as_slice)
+    }
+
+    /// Return true if stage is used.
+    pub fn uses(&self, stage: Stage) -> bool {
+        self.steps.iter().any(|step| step.contains(&stage))
+    }
+
+    /// Return true if the order is the default one.
+    pub fn is_default(&self) -> bool {
+        *self == ResolutionOrder::default()
+    }
+}
+
+impl TryFrom<Vec<Step>> for ResolutionOrder {
+    type Error = ResolutionError;
+
+    fn try_from(steps: Vec<Step>) -> Result<ResolutionOrder, ResolutionError> {
+        let steps = steps
+            .into_iter()
+            .map(|step| match step {
+                Step::Stage(stage) => vec![stage],
+                Step::Group(group) => group,
+            })
+            .collect();
+        ResolutionOrder::new(steps)
+    }
+}
+
+impl From<ResolutionOrder> for Vec<Step> {
+    fn from(order: ResolutionOrder) -> Vec<Step> {
+        order
+            .steps
+            .into_iter()
+            .map(|mut step| match step.len() {
+                1 => Step::Stage(step.remove(0)),
+                _ => Step::Group(step),
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for ResolutionOrder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, step) in self.steps.iter().enumerate() {
+            if i > 0 {
+                write!(f, " > ")?;
+            }
+            for (j, stage) in step.iter().enumerate() {
+                if j > 0 {
+                    write!(f, " + ")?;
+                }
+                write!(f, "{}", stage)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// ResolutionError is returned when an order of stages is invalid.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ResolutionError {
+    /// The order has no stages.
+    Empty,
+    /// A group of stages is empty.
+    EmptyGroup,
+    /// The stage appears more than once.
+    Repeated(Stage),
+    /// The stage, which isn't a pattern stage, is grouped with others.
+    Grouped(Stage),
+}
+
+impl fmt::Display for ResolutionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ResolutionError::Empty => write!(f, "resolution order has no stages"),
+            ResolutionError::EmptyGroup => write!(f, "resolution order has an empty group"),
+            ResolutionError::Repeated(stage) => {
+                write!(f, "stage \"{}\" appears more than once", stage)
+            }
+            ResolutionError::Grouped(stage) => {
+                write!(f, "stage \"{}\" can't be grouped with other stages", stage)
+            }
+        }
+    }
+}
+
+impl Error for ResolutionError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_test() {
+        for (json, order) in [
+            (
+                r#"["description", ["glob", "pattern", "recursive"], "repository"]"#,
+                Ok("description > glob + pattern + recursive > repository"),
+            ),
+            (r#"["glob", "description"]"#, Ok("glob > description")),
+            (r#"[["pattern"]]"#, Ok("pattern")),
+            (r#"[]"#, Err(ResolutionError::Empty)),
+            (r#"["glob", []]"#, Err(ResolutionError::EmptyGroup)),
+            (
+                r#"["glob", ["pattern", "glob"]]"#,
+                Err(ResolutionError::Repeated(Stage::Glob)),
+            ),
+            (
+                r#"[["description", "glob"]]"#,
+                Err(ResolutionError::Grouped(Stage::Description)),
+            ),
+        ]
+        .iter()
+        {
+            let steps: Vec<Step> = serde_json::from_str(json).unwrap();
+            let parsed = ResolutionOrder::try_from(steps).map(|o| o.to_string());
+            assert_eq!(parsed, order.clone().map(str::to_string), "{}", json);
+        }
+
+        let default = ResolutionOrder::default();
+        let json = serde_json::to_string(&default).unwrap();
+        assert_eq!(
+            json,
+            r#"["description",["glob","pattern","recursive"],"repository"]"#
+        );
+        assert!(serde_json::from_str::<ResolutionOrder>(&json)
+            .unwrap()
+            .is_default());
+        assert!(serde_json::from_str::<ResolutionOrder>(r#"["unknown"]"#).is_err());
+    }
+}
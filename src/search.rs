@@ -0,0 +1,334 @@
+//! Searching descriptions and patterns.
+//!
+//! A query is split into words, and an entry matches if each word is found in
+//! its path or its description, ignoring case. Words are found as substrings,
+//! and, depending on `SearchOptions`, as subsequences (such as "dwnld" in
+//! "Downloads"), as words with the same stem (such as "backups" for
+//! "backup"), or as words with a few typos (such as "bakcup" for "backup").
+//! Matches are ranked by how well their words matched: substrings first,
+//! then stems, typos, and subsequences.
+//!
+//! Stems are found by removing common English suffixes, such as "s", "es",
+//! "ed", and "ing", so "configured" and "configures" share a stem, but
+//! irregular forms don't. Typos are counted as the number of characters
+//! inserted, deleted, or replaced, and are allowed only in words long enough
+//! (see `SearchOptions::max_typos`).
+
+use crate::{Entry, PatternKind};
+
+/// Score of a word found as a substring.
+const SUBSTRING_SCORE: u32 = 1000;
+
+/// Bonus of a substring found at the start of a word.
+const WORD_START_BONUS: u32 = 200;
+
+/// Score of a word found as a subsequence, before subtracting gaps.
+const FUZZY_SCORE: u32 = 500;
+
+/// Score of a word with the same stem as a word of the text.
+const STEM_SCORE: u32 = 800;
+
+/// Score of a word found with typos, before subtracting TYPO_PENALTY for
+/// each typo.
+const TYPO_SCORE: u32 = 700;
+
+/// Penalty of each typo.
+const TYPO_PENALTY: u32 = 100;
+
+/// Number of characters of a word needed for each allowed typo.
+const CHARS_PER_TYPO: usize = 4;
+
+/// Shortest stem left after removing a suffix.
+const MIN_STEM: usize = 3;
+
+/// Maximum penalty of a match's position or gaps.
+const MAX_PENALTY: u32 = 100;
+
+/// Match is a description or pattern matching a query.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Match<'a> {
+    /// Path the description or pattern is mapped to.
+    pub path: &'a str,
+    /// The description or pattern.
+    pub text: &'a str,
+    /// Kind of the pattern, or None if the match is a specific description.
+    pub pattern: Option<PatternKind>,
+    /// How well the match matched the query, higher is better.
+    pub score: u32,
+}
+
+/// SearchOptions control how strictly words of a query are matched. By
+/// default, words match only as substrings.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct SearchOptions {
+    /// Match words as subsequences, such as "dwnld" in "Downloads".
+    pub subsequences: bool,
+    /// Match words with the same stem, such as "backups" and "backup".
+    pub stem: bool,
+    /// Maximum number of typos in a word. A word is allowed one typo for
+    /// every CHARS_PER_TYPO (4) characters, up to max_typos, so short words
+    /// must always match exactly.
+    pub max_typos: usize,
+}
+
+impl SearchOptions {
+    /// Return the options of fuzzy searches, which match subsequences,
+    /// stems, and words with a typo.
+    pub fn fuzzy() -> SearchOptions {
+        SearchOptions {
+            subsequences: true,
+            stem: true,
+            max_typos: 1,
+        }
+    }
+
+    /// Return true if words match only as substrings.
+    pub fn is_strict(&self) -> bool {
+        *self == SearchOptions::default()
+    }
+}
+
+/// Return entries matching query, best matches first. Matches with equal
+/// scores are sorted by path. See `SearchOptions` for how words of the query
+/// match.
+pub fn search<'a>(entries: &[Entry<'a>], query: &str, options: &SearchOptions) -> Vec<Match<'a>> {
+    let words: Vec<String> = query.split_whitespace().map(|w| w.to_lowercase()).collect();
+    if words.is_empty() {
+        return Vec::new();
+    }
+
+    let mut matches: Vec<Match> = entries
+        .iter()
+        .filter_map(|entry| {
+            let path = entry.path.to_lowercase();
+            let text = entry.description.to_lowercase();
+            let mut total = 0;
+            for word in words.iter() {
+                let best = score(word, &path, options).max(score(word, &text, options));
+                total += best?;
+            }
+            Some(Match {
+                path: entry.path,
+                text: entry.description,
+                pattern: entry.pattern,
+                score: total,
+            })
+        })
+        .collect();
+    matches.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| a.path.cmp(b.path))
+            .then_with(|| a.pattern.cmp(&b.pattern))
+    });
+    matches
+}
+
+/// Return the score of word in text (both lowercase), or None if it isn't
+/// found. Substrings score higher than other matches, and earlier substrings
+/// (especially at the start of a word) score higher than later ones.
+fn score(word: &str, text: &str, options: &SearchOptions) -> Option<u32> {
+    if let Some(i) = text.find(word) {
+        let word_start = text[..i]
+            .chars()
+            .next_back()
+            .map(|c| !c.is_alphanumeric())
+            .unwrap_or(true);
+        let bonus = if word_start { WORD_START_BONUS } else { 0 };
+        return Some(SUBSTRING_SCORE + bonus - penalty(text[..i].chars().count()));
+    }
+
+    let mut best = None;
+    let words = || {
+        text.split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+    };
+    if options.stem && words().any(|w| stem(w) == stem(word)) {
+        best = Some(STEM_SCORE);
+    }
+    let allowed = options.max_typos.min(word.chars().count() / CHARS_PER_TYPO);
+    if best.is_none() && allowed > 0 {
+        let typos = words()
+            .map(|w| {
+                let typos = distance(word, w);
+                if options.stem {
+                    typos.min(distance(&stem(word), &stem(w)))
+                } else {
+                    typos
+                }
+            })
+            .min()
+            .filter(|typos| *typos <= allowed);
+        best = typos.map(|typos| TYPO_SCORE - TYPO_PENALTY * typos as u32);
+    }
+    if best.is_none() && options.subsequences {
+        best = subsequence_gaps(word, text).map(|gaps| FUZZY_SCORE - penalty(gaps));
+    }
+    best
+}
+
+/// Return the stem of a lowercase word, which is the word without common
+/// suffixes (and a final "e"), such as "configur" for "configured",
+/// "configures", and "configure".
+fn stem(word: &str) -> String {
+    let len = word.chars().count();
+    let mut stem = word.to_string();
+    if len > MIN_STEM + 2 && word.ends_with("ies") {
+        stem.truncate(stem.len() - 3);
+        stem.push('y');
+    } else if let Some(suffix) = ["ing", "ed", "ly"].iter().find(|s| word.ends_with(*s)) {
+        if len >= MIN_STEM + suffix.len() {
+            stem.truncate(stem.len() - suffix.len());
+        }
+    } else if word.ends_with("es")
+        && ["ses", "xes", "zes", "ches", "shes"]
+            .iter()
+            .any(|s| word.ends_with(s))
+    {
+        stem.truncate(stem.len() - 2);
+    } else if len > MIN_STEM && word.ends_with('s') && !word.ends_with("ss") {
+        stem.truncate(stem.len() - 1);
+    }
+    if stem.chars().count() > MIN_STEM && stem.ends_with('e') {
+        stem.pop();
+    }
+    stem
+}
+
+/// Return the edit distance of a and b: the number of characters inserted,
+/// deleted, or replaced to turn a into b.
+fn distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let replaced = previous[j] + (ca != *cb) as usize;
+            current.push(replaced.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Return the number of characters skipped between the first and last
+/// characters of word when it's found in text as a subsequence, or None if
+/// it isn't a subsequence of text.
+fn subsequence_gaps(word: &str, text: &str) -> Option<usize> {
+    let mut chars = text.chars();
+    let mut gaps = 0;
+    for (i, w) in word.chars().enumerate() {
+        let skipped = chars.by_ref().position(|c| c == w)?;
+        if i > 0 {
+            gaps += skipped;
+        }
+    }
+    Some(gaps)
+}
+
+/// Return the penalty of n characters, capped by MAX_PENALTY.
+fn penalty(n: usize) -> u32 {
+    n.min(MAX_PENALTY as usize) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn score_test() {
+        let strict = SearchOptions::default();
+        let stem = SearchOptions {
+            stem: true,
+            ..SearchOptions::default()
+        };
+        let fuzzy = SearchOptions::fuzzy();
+        for (word, text, options, expected) in [
+            ("down", "downloads", strict, Some(1200)),
+            ("load", "downloads", strict, Some(996)),
+            ("load", "my loads", strict, Some(1197)),
+            ("dwnld", "downloads", strict, None),
+            ("dwnld", "downloads", fuzzy, Some(497)),
+            ("xyz", "downloads", fuzzy, None),
+            ("backups", "daily backup", strict, None),
+            ("backups", "daily backup", stem, Some(800)),
+            ("configured", "configure it", stem, Some(800)),
+            ("bakcup", "daily backup", stem, None),
+            ("bakcup", "daily backup", fuzzy, None),
+            ("bakup", "daily backups", fuzzy, Some(600)),
+            ("bckp", "daily backup", fuzzy, Some(498)),
+            ("nginx", "nignx config", fuzzy, None),
+            ("nginx", "ngnx config", fuzzy, Some(600)),
+        ]
+        .iter()
+        {
+            assert_eq!(score(word, text, options), *expected, "{} {}", word, text);
+        }
+    }
+
+    #[test]
+    fn stem_test() {
+        for (word, expected) in [
+            ("backups", "backup"),
+            ("backup", "backup"),
+            ("directories", "directory"),
+            ("directory", "directory"),
+            ("boxes", "box"),
+            ("files", "fil"),
+            ("file", "fil"),
+            ("configuring", "configur"),
+            ("access", "access"),
+            ("is", "is"),
+            ("bed", "bed"),
+        ]
+        .iter()
+        {
+            assert_eq!(stem(word), *expected, "{}", word);
+        }
+    }
+
+    #[test]
+    fn search_test() {
+        let entries = [
+            Entry {
+                path: "/home/user/Downloads",
+                description: "Downloaded files.",
+                pattern: None,
+            },
+            Entry {
+                path: "/srv/nginx",
+                description: "Web server config.",
+                pattern: None,
+            },
+            Entry {
+                path: "/srv",
+                description: "* is served to the web.",
+                pattern: Some(PatternKind::Children),
+            },
+        ];
+
+        for (query, fuzzy, paths) in [
+            ("web", false, vec!["/srv/nginx", "/srv"]),
+            ("WEB server", false, vec!["/srv/nginx"]),
+            ("nginx config", false, vec!["/srv/nginx"]),
+            ("dwnld", false, vec![]),
+            ("dwnld", true, vec!["/home/user/Downloads"]),
+            ("servers", false, vec![]),
+            ("servers", true, vec!["/srv/nginx"]),
+            ("", true, vec![]),
+        ]
+        .iter()
+        {
+            let options = if *fuzzy {
+                SearchOptions::fuzzy()
+            } else {
+                SearchOptions::default()
+            };
+            let found: Vec<&str> = search(&entries, query, &options)
+                .iter()
+                .map(|m| m.path)
+                .collect();
+            assert_eq!(found, *paths, "{}", query);
+        }
+    }
+}
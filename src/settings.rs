@@ -0,0 +1,226 @@
+//! User preferences.
+
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::matching::MatchOptions;
+use crate::resolution::ResolutionOrder;
+use crate::{capture, store, Error};
+
+/// Settings holds user preferences that affect how def behaves, as opposed
+/// to the config which holds the descriptions themselves. Settings are read
+/// from a JSON file, and every field is optional.
+///
+/// For example:
+///
+/// ```json
+/// {
+///     "variables": {
+///         "PROJECTS": "/home/user/projects"
+///     },
+///     "trash_retention_days": 30,
+///     "backups_kept": 5,
+///     "maintain_root": "/home/user",
+///     "plain_output": false,
+///     "markdown": true,
+///     "inherit": true,
+///     "resolution_order": ["description", "glob", ["pattern", "recursive"]],
+///     "matching": {"case_insensitive": true, "normalize_unicode": true},
+///     "allow_commands": true,
+///     "command_timeout": 10,
+///     "language": "es",
+///     "search_index": true,
+///     "searches": {
+///         "infra": "nginx config"
+///     },
+///     "trusted_keys": [
+///         "RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3"
+///     ],
+///     "redact": ["\\w+\\.corp\\.example\\.com"],
+///     "config_file": "/home/user/.config/def/config.toml",
+///     "store": "toml",
+///     "sync_remote": "git@github.com:user/descriptions.git",
+///     "colors": {
+///         "path": "bright blue",
+///         "error": "#ff8700"
+///     }
+/// }
+/// ```
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct Settings {
+    /// Variables that can be used in config keys, such as `${PROJECTS}`.
+    pub variables: HashMap<String, String>,
+    /// Number of days trashed entries are kept before being deleted.
+    pub trash_retention_days: u64,
+    /// Number of config backups kept by "def maintain".
+    pub backups_kept: usize,
+    /// Directory checked for drift by "def maintain", $HOME if not set.
+    pub maintain_root: Option<String>,
+    /// Colors of printed text by role, such as "error" or "path".
+    pub colors: HashMap<String, String>,
+    /// Print plain output, as if --plain is always used.
+    pub plain_output: bool,
+    /// Render descriptions as Markdown (if def is built with "markdown"
+    /// feature), unless --raw is used.
+    pub markdown: bool,
+    /// Describe paths without a description by that of their nearest
+    /// described ancestor, as if --inherit is always used.
+    pub inherit: bool,
+    /// Order descriptions and patterns are tried in when describing paths,
+    /// see `def::resolution`.
+    pub resolution_order: ResolutionOrder,
+    /// How paths are matched against keys, such as ignoring case, see
+    /// `def::matching`.
+    pub matching: MatchOptions,
+    /// Run commands capturing descriptions, as if --allow-commands is always
+    /// used, see `def::capture`.
+    pub allow_commands: bool,
+    /// Number of seconds commands capturing descriptions are given to
+    /// finish.
+    pub command_timeout: u64,
+    /// Path of the file descriptions are kept in, ~/.config/def/config.json
+    /// if not set.
+    pub config_file: Option<String>,
+    /// Storage backend of config_file: the name of a format (see
+    /// `def::format`), "sqlite", or "crdt". Chosen based on config_file's
+    /// extension if not set.
+    pub store: Option<String>,
+    /// Remote the config is synced with by "def sync": a git repository, or
+    /// an http(s) URL of a JSON file, see `def::sync`.
+    pub sync_remote: Option<String>,
+    /// Language of printed messages, such as "es". Taken from the environment
+    /// if not set.
+    pub language: Option<String>,
+    /// Search using an index cached in def's directory (see `def::index`),
+    /// which is faster for large configs.
+    pub search_index: bool,
+    /// Saved searches: queries by name, such as "infra", see `save_search`.
+    pub searches: BTreeMap<String, String>,
+    /// Minisign public keys trusted to sign pattern packs. Packs installed
+    /// from URLs must be signed by one of them, unless the list is empty,
+    /// see `pack::verify`.
+    pub trusted_keys: Vec<String>,
+    /// Regular expressions of text masked in exports, see `def::redact`.
+    /// Common kinds of secrets are masked if not set, and nothing is if the
+    /// list is empty.
+    pub redact: Option<Vec<String>>,
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings {
+            variables: HashMap::new(),
+            trash_retention_days: 30,
+            backups_kept: 5,
+            maintain_root: None,
+            colors: HashMap::new(),
+            plain_output: false,
+            markdown: true,
+            inherit: false,
+            resolution_order: ResolutionOrder::default(),
+            matching: MatchOptions::default(),
+            allow_commands: false,
+            command_timeout: capture::DEFAULT_TIMEOUT.as_secs(),
+            config_file: None,
+            store: None,
+            sync_remote: None,
+            language: None,
+            search_index: false,
+            searches: BTreeMap::new(),
+            trusted_keys: Vec::new(),
+            redact: None,
+        }
+    }
+}
+
+impl Settings {
+    /// Read settings from the given file. If the file doesn't exist, default
+    /// settings are returned.
+    pub fn load<P: AsRef<Path>>(file: P) -> Result<Settings, Error> {
+        if file.as_ref().exists() {
+            Ok(serde_json::from_str(&fs::read_to_string(file)?)?)
+        } else {
+            Ok(Settings::default())
+        }
+    }
+
+    /// Return the query saved as name, if any.
+    pub fn saved_search(&self, name: &str) -> Option<&str> {
+        self.searches.get(name).map(String::as_str)
+    }
+
+    /// Return the value of a variable used in config keys. Variables defined
+    /// in settings take precedence over environment variables.
+    pub fn variable(&self, name: &str) -> Option<String> {
+        match self.variables.get(name) {
+            Some(value) => Some(value.clone()),
+            None => env::var(name).ok(),
+        }
+    }
+}
+
+/// Save query as the search named name in the settings file, which is
+/// created if it doesn't exist. Other settings in the file are kept as they
+/// are, including ones this version of the library doesn't know.
+pub fn save_search<P: AsRef<Path>>(file: P, name: &str, query: &str) -> Result<(), Error> {
+    let file = file.as_ref();
+    let mut settings: serde_json::Map<String, serde_json::Value> = if file.exists() {
+        serde_json::from_str(&fs::read_to_string(file)?)?
+    } else {
+        serde_json::Map::new()
+    };
+    let searches = settings
+        .entry("searches")
+        .or_insert_with(|| serde_json::Value::Object(serde_json::Map::new()));
+    if !searches.is_object() {
+        *searches = serde_json::Value::Object(serde_json::Map::new());
+    }
+    if let Some(searches) = searches.as_object_mut() {
+        searches.insert(name.to_string(), query.into());
+    }
+
+    if let Some(dir) = file.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    Ok(store::write_atomic(
+        file,
+        serde_json::to_string_pretty(&settings)?.as_bytes(),
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn save_search_test() {
+        let dir = env::temp_dir().join(format!("def-settings-test-{}", std::process::id()));
+        let file = dir.join("settings.json");
+        save_search(&file, "infra", "nginx config").unwrap();
+        fs::write(
+            &file,
+            fs::read_to_string(&file).unwrap().replacen(
+                '{',
+                "{\"language\": \"es\", \"unknown\": 1,",
+                1,
+            ),
+        )
+        .unwrap();
+        save_search(&file, "backups", "backup").unwrap();
+        save_search(&file, "infra", "nginx").unwrap();
+
+        let settings = Settings::load(&file).unwrap();
+        let text = fs::read_to_string(&file).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(settings.saved_search("infra"), Some("nginx"));
+        assert_eq!(settings.saved_search("backups"), Some("backup"));
+        assert_eq!(settings.saved_search("other"), None);
+        assert_eq!(settings.language, Some("es".to_string()));
+        assert!(text.contains("\"unknown\": 1"));
+    }
+}
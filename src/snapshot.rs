@@ -0,0 +1,171 @@
+//! Immutable snapshots of describers, for tools embedding def.
+//!
+//! A snapshot is a describer frozen at a point in time. It has the read API
+//! of `Describer` (describing paths, listing and searching entries, and so
+//! on), and cloning it is cheap, as clones share the describer. Long-running
+//! tools, such as file managers and daemons, can hand snapshots to the
+//! threads answering lookups, reload the config in the background, and swap
+//! the new snapshot in (see `SnapshotCell`) without blocking lookups in
+//! between. Lookups that started before the swap finish with the snapshot
+//! they started with.
+
+use std::ops::Deref;
+use std::sync::{Arc, RwLock};
+
+use crate::Describer;
+
+/// DescriberSnapshot is an immutable, cheaply cloneable view of a describer.
+/// It dereferences to the describer, so every method taking `&Describer`
+/// can be used.
+///
+/// # Examples
+///
+/// ```
+/// use def::DescriberSnapshot;
+///
+/// let mut d = def::Describer::new();
+/// d.add_description("/srv", "Served files.");
+/// let snapshot = d.snapshot();
+/// d.add_description("/srv", "Changed.");
+///
+/// let clone = snapshot.clone();
+/// assert!(DescriberSnapshot::ptr_eq(&snapshot, &clone));
+/// assert_eq!(clone.describe("/srv"), Some("Served files.".to_string()));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DescriberSnapshot {
+    describer: Arc<Describer>,
+}
+
+impl DescriberSnapshot {
+    /// Create a snapshot of describer.
+    pub fn new(describer: Describer) -> DescriberSnapshot {
+        DescriberSnapshot {
+            describer: Arc::new(describer),
+        }
+    }
+
+    /// Return true if a and b are clones of the same snapshot.
+    pub fn ptr_eq(a: &DescriberSnapshot, b: &DescriberSnapshot) -> bool {
+        Arc::ptr_eq(&a.describer, &b.describer)
+    }
+
+    /// Return a describer that can be changed, with the contents of the
+    /// snapshot. The describer is only copied if other clones of the snapshot
+    /// exist.
+    pub fn into_describer(self) -> Describer {
+        Arc::try_unwrap(self.describer).unwrap_or_else(|shared| (*shared).clone())
+    }
+}
+
+impl Deref for DescriberSnapshot {
+    type Target = Describer;
+
+    fn deref(&self) -> &Describer {
+        &self.describer
+    }
+}
+
+impl AsRef<Describer> for DescriberSnapshot {
+    fn as_ref(&self) -> &Describer {
+        &self.describer
+    }
+}
+
+impl From<Describer> for DescriberSnapshot {
+    fn from(describer: Describer) -> DescriberSnapshot {
+        DescriberSnapshot::new(describer)
+    }
+}
+
+/// SnapshotCell holds the current snapshot of a describer, which threads can
+/// get and replace concurrently. Getting a snapshot only clones a pointer,
+/// so the cell is locked only briefly.
+#[derive(Debug, Default)]
+pub struct SnapshotCell {
+    current: RwLock<DescriberSnapshot>,
+}
+
+impl SnapshotCell {
+    /// Create a cell holding snapshot.
+    pub fn new(snapshot: DescriberSnapshot) -> SnapshotCell {
+        SnapshotCell {
+            current: RwLock::new(snapshot),
+        }
+    }
+
+    /// Return the current snapshot.
+    pub fn get(&self) -> DescriberSnapshot {
+        match self.current.read() {
+            Ok(current) => current.clone(),
+            // Replacing a snapshot can't leave it half written, so a
+            // poisoned lock still holds a whole one.
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+
+    /// Make snapshot the current one, and return the one it replaced.
+    pub fn replace(&self, snapshot: DescriberSnapshot) -> DescriberSnapshot {
+        let mut current = match self.current.write() {
+            Ok(current) => current,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        std::mem::replace(&mut *current, snapshot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn snapshot_test() {
+        let mut d = Describer::new();
+        d.add_description("/srv", "Served files.");
+        d.add_pattern("/srv", "{name} is served.").unwrap();
+        let snapshot = DescriberSnapshot::from(d.clone());
+        d.remove_description("/srv");
+
+        for (path, description) in [
+            ("/srv", Some("Served files.")),
+            ("/srv/www", Some("www is served.")),
+            ("/tmp", None),
+        ]
+        .iter()
+        {
+            assert_eq!(snapshot.describe(path).as_deref(), *description, "{}", path);
+        }
+        assert_eq!(snapshot.entries_under("").len(), 2);
+
+        let clone = snapshot.clone();
+        assert!(DescriberSnapshot::ptr_eq(&snapshot, &clone));
+        assert!(!DescriberSnapshot::ptr_eq(&snapshot, &d.snapshot()));
+        let mut changed = clone.into_describer();
+        changed.add_description("/tmp", "Temporary files.");
+        assert_eq!(snapshot.describe("/tmp"), None);
+    }
+
+    #[test]
+    fn cell_test() {
+        let cell = Arc::new(SnapshotCell::default());
+        let before = cell.get();
+        assert_eq!(before.describe("/srv"), None);
+
+        let mut d = Describer::new();
+        d.add_description("/srv", "Served files.");
+        let swapper = Arc::clone(&cell);
+        thread::spawn(move || swapper.replace(d.snapshot()))
+            .join()
+            .unwrap();
+
+        assert_eq!(
+            cell.get().describe("/srv"),
+            Some("Served files.".to_string())
+        );
+        assert_eq!(before.describe("/srv"), None);
+        let replaced = cell.replace(DescriberSnapshot::default());
+        assert_eq!(replaced.describe("/srv"), Some("Served files.".to_string()));
+        assert!(cell.get().entries_under("").is_empty());
+    }
+}
@@ -0,0 +1,153 @@
+//! Statistics of a describer, and how much of a directory it covers (see
+//! `def stats`).
+//!
+//! A summary counts descriptions and patterns, and lists the descriptions
+//! added last, to see what a config holds at a glance. Coverage of a
+//! directory is the fraction of its entries that can be described, by a
+//! description or by a pattern, which teams documenting a project can keep an
+//! eye on as it grows.
+
+use std::fs;
+use std::io;
+
+use crate::{DefPath, Describer, PatternKind};
+
+/// Summary is statistics of a describer.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Summary<'a> {
+    /// Number of descriptions.
+    pub descriptions: usize,
+    /// Number of patterns, see `PatternKind::Children`.
+    pub patterns: usize,
+    /// Number of recursive patterns, see `PatternKind::Recursive`.
+    pub recursive_patterns: usize,
+    /// Number of globs, see `PatternKind::Glob`.
+    pub globs: usize,
+    /// The described path with the most components, if any, see
+    /// `Describer::deepest_description`.
+    pub deepest: Option<&'a str>,
+    /// Descriptions added last, latest first, along with the times they
+    /// were added, see `Describer::recently_added`.
+    pub recent: Vec<(&'a str, u64)>,
+}
+
+impl Summary<'_> {
+    /// Return the number of patterns of all kinds.
+    pub fn all_patterns(&self) -> usize {
+        self.patterns + self.recursive_patterns + self.globs
+    }
+}
+
+/// Return a summary of describer, listing up to recent descriptions added
+/// last.
+///
+/// # Examples
+///
+/// ```
+/// let mut d = def::Describer::new();
+/// d.add_description("/srv", "Served files.");
+/// d.add_description("/srv/www/index.html", "Home page.");
+/// d.add_glob("*.log", "Logs.").unwrap();
+///
+/// let summary = def::stats::summarize(&d, 5);
+/// assert_eq!(summary.descriptions, 2);
+/// assert_eq!(summary.all_patterns(), 1);
+/// assert_eq!(summary.deepest, Some("/srv/www/index.html"));
+/// ```
+pub fn summarize(describer: &Describer, recent: usize) -> Summary<'_> {
+    Summary {
+        descriptions: describer.iter_descriptions().count(),
+        patterns: describer.iter_patterns(PatternKind::Children).count(),
+        recursive_patterns: describer.iter_patterns(PatternKind::Recursive).count(),
+        globs: describer.iter_patterns(PatternKind::Glob).count(),
+        deepest: describer.deepest_description(),
+        recent: describer.recently_added(recent),
+    }
+}
+
+/// Coverage is how much of a directory's entries can be described.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct Coverage {
+    /// Number of entries of the directory.
+    pub entries: usize,
+    /// Paths of entries that can't be described, sorted.
+    pub undescribed: Vec<String>,
+}
+
+impl Coverage {
+    /// Return the number of entries that can be described.
+    pub fn described(&self) -> usize {
+        self.entries - self.undescribed.len()
+    }
+
+    /// Return the fraction of entries that can be described, between 0 and
+    /// 1. A directory without entries is fully covered.
+    pub fn fraction(&self) -> f64 {
+        if self.entries == 0 {
+            1.0
+        } else {
+            self.described() as f64 / self.entries as f64
+        }
+    }
+}
+
+/// Return the coverage of the non-hidden entries directly in dir, as `def ls`
+/// lists them. An error is returned if dir can't be read.
+pub fn coverage(describer: &Describer, dir: &str) -> io::Result<Coverage> {
+    let mut names = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let name = entry?.file_name().to_string_lossy().to_string();
+        if !name.starts_with('.') {
+            names.push(name);
+        }
+    }
+    names.sort();
+
+    let dir = DefPath::new(dir);
+    let descriptions = describer.describe_dir_entries(dir.as_str(), &names);
+    Ok(Coverage {
+        entries: names.len(),
+        undescribed: names
+            .iter()
+            .zip(descriptions.iter())
+            .filter(|(_, description)| description.is_none())
+            .map(|(name, _)| dir.join(name).into())
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coverage_test() {
+        let dir = std::env::temp_dir().join(format!("def-stats-test-{}", std::process::id()));
+        for sub in ["src", "docs", "target", ".git"].iter() {
+            fs::create_dir_all(dir.join(sub)).unwrap();
+        }
+        fs::write(dir.join("README.md"), "# Readme\n").unwrap();
+        fs::write(dir.join("src").join("main.rs"), "").unwrap();
+        let root = dir.to_string_lossy().to_string();
+        let path = |sub: &str| format!("{}/{}", root, sub);
+
+        let mut d = Describer::new();
+        d.add_description(path("src"), "Source code.");
+        d.add_glob("*.md", "Markdown.").unwrap();
+        let covered = coverage(&d, &root);
+        d.add_pattern(&root, "Part of the project.").unwrap();
+        let fully_covered = coverage(&d, &root);
+        let empty = coverage(&d, &path("docs"));
+        let missing = coverage(&d, &path("missing"));
+        fs::remove_dir_all(&dir).unwrap();
+
+        let covered = covered.unwrap();
+        assert_eq!(covered.entries, 4);
+        assert_eq!(covered.undescribed, vec![path("docs"), path("target")]);
+        assert_eq!(covered.described(), 2);
+        assert!((covered.fraction() - 0.5).abs() < f64::EPSILON);
+        assert_eq!(fully_covered.unwrap().fraction(), 1.0);
+        assert_eq!(empty.unwrap(), Coverage::default());
+        assert!(missing.is_err());
+    }
+}
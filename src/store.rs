@@ -0,0 +1,466 @@
+//! Persistence of describers.
+//!
+//! A `Store` loads a describer from, and saves it to, some storage. Describers
+//! can be stored as a file of any `Format` (see `def::format`), a CRDT file
+//! which merges copies changed on different machines (see `CrdtStore`), or
+//! (with "sqlite" feature) an SQLite database, which is updated in place
+//! rather than rewritten on every save.
+//!
+//! Files are saved atomically: they're written to a temporary file which then
+//! replaces the original, so a crash mid-save never leaves a partially
+//! written file. To keep concurrent processes from losing each other's
+//! changes, hold the store's lock (see `Store::lock`) while loading,
+//! changing, and saving a describer.
+//!
+//! Describing a path needs few of a describer's entries, but files have to
+//! be parsed whole to get them. SQLite databases instead load only the
+//! entries needed to describe the paths given to `Store::load_for`, once
+//! they're larger than `PARTIAL_LOAD_MIN_BYTES`; for configs with tens of
+//! thousands of entries, SQLite is the storage of choice.
+
+mod crdt;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::format::{self, Format};
+use crate::{migrate, Describer};
+
+pub use crdt::CrdtStore;
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteStore;
+
+/// Store loads and saves a describer.
+pub trait Store {
+    /// Return true if the storage exists, and so can be loaded.
+    fn exists(&self) -> bool;
+
+    /// Load a describer from the storage.
+    fn load(&self) -> Result<Describer, StoreError>;
+
+    /// Save the given describer to the storage, replacing whatever it held.
+    fn save(&self, describer: &Describer) -> Result<(), StoreError>;
+
+    /// Load a describer holding the entries needed to describe paths: their
+    /// descriptions and those of their ancestors, patterns of their
+    /// ancestors, and all globs, along with records kept beside them. Stores
+    /// that can't load entries selectively load all of them, as does
+    /// `load`. The returned describer shouldn't be saved, as that would
+    /// delete the entries it lacks.
+    fn load_for(&self, paths: &[&str]) -> Result<Describer, StoreError> {
+        let _ = paths;
+        self.load()
+    }
+
+    /// Acquire an exclusive lock of the storage, blocking until other
+    /// processes release it. The lock is advisory: it only excludes others
+    /// acquiring it, and is released when the returned value is dropped.
+    fn lock(&self) -> Result<Lock, StoreError>;
+}
+
+/// Lock is an exclusive lock of a store, released when dropped.
+#[derive(Debug)]
+pub struct Lock {
+    _file: File,
+}
+
+impl Lock {
+    /// Acquire an exclusive lock of the file at path, using a lock file next
+    /// to it (path with ".lock" appended). Blocks until the lock is acquired.
+    pub fn acquire<P: AsRef<Path>>(path: P) -> Result<Lock, StoreError> {
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(lock_file(path.as_ref()))?;
+        file.lock()?;
+        Ok(Lock { _file: file })
+    }
+}
+
+/// Write contents to the file at path atomically: contents are written to a
+/// temporary file in the same directory, which then replaces the file. The
+/// permissions of the file are kept if it exists.
+pub fn write_atomic<P: AsRef<Path>>(path: P, contents: &[u8]) -> io::Result<()> {
+    let path = path.as_ref();
+    let temp = sibling(path, &format!(".tmp-{}", std::process::id()));
+    let result = File::create(&temp).and_then(|mut file| {
+        file.write_all(contents)?;
+        if let Ok(metadata) = fs::metadata(path) {
+            file.set_permissions(metadata.permissions())?;
+        }
+        file.sync_all()
+    });
+    match result.and_then(|_| fs::rename(&temp, path)) {
+        Ok(()) => Ok(()),
+        Err(e) => {
+            let _ = fs::remove_file(&temp);
+            Err(e)
+        }
+    }
+}
+
+/// Return path of the lock file of the file at path.
+fn lock_file(path: &Path) -> PathBuf {
+    sibling(path, ".lock")
+}
+
+/// Return path with suffix appended to its file name.
+pub(crate) fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// Minimum size of SQLite databases, in bytes, that `Store::load_for` loads
+/// selectively. Smaller databases (below roughly 100 entries) load whole as
+/// fast as selectively, while from 1,000 entries on selective loads are ten
+/// times faster or more, see `benches/store.rs`.
+pub const PARTIAL_LOAD_MIN_BYTES: u64 = 64 * 1024;
+
+/// Name of the SQLite backend.
+const SQLITE: &str = "sqlite";
+
+/// Extensions of SQLite databases.
+const SQLITE_EXTENSIONS: [&str; 3] = ["db", "sqlite", "sqlite3"];
+
+/// Name, and extension, of the CRDT backend.
+const CRDT: &str = "crdt";
+
+/// Backend is a kind of storage.
+#[derive(Clone, Copy)]
+pub enum Backend {
+    /// A file of the given format.
+    File(&'static dyn Format),
+    /// An SQLite database.
+    Sqlite,
+    /// A CRDT file, see `CrdtStore`.
+    Crdt,
+}
+
+impl Backend {
+    /// Return the backend with the given name (the name of a format,
+    /// "sqlite", or "crdt"), or None if there's no such backend.
+    pub fn from_name(name: &str) -> Option<Backend> {
+        if name.eq_ignore_ascii_case(SQLITE) {
+            Some(Backend::Sqlite)
+        } else if name.eq_ignore_ascii_case(CRDT) {
+            Some(Backend::Crdt)
+        } else {
+            format::from_name(name).map(Backend::File)
+        }
+    }
+
+    /// Return the backend matching extension of the given path: ".db",
+    /// ".sqlite", or ".sqlite3" for SQLite, ".crdt" for CRDT files, the
+    /// format with the extension (see `format::from_path`), and JSON
+    /// otherwise.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Backend {
+        let ext = path.as_ref().extension().and_then(|e| e.to_str());
+        if ext.is_some_and(|e| SQLITE_EXTENSIONS.contains(&e)) {
+            return Backend::Sqlite;
+        }
+        if ext == Some(CRDT) {
+            return Backend::Crdt;
+        }
+        Backend::File(format::from_path(path).unwrap_or(&format::Json))
+    }
+
+    /// Return the name of the backend.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Backend::File(format) => format.name(),
+            Backend::Sqlite => SQLITE,
+            Backend::Crdt => CRDT,
+        }
+    }
+}
+
+impl PartialEq for Backend {
+    fn eq(&self, other: &Backend) -> bool {
+        self.name() == other.name()
+    }
+}
+
+impl Eq for Backend {}
+
+impl fmt::Debug for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Backend::File(format) => write!(f, "File({})", format.name()),
+            Backend::Sqlite => write!(f, "Sqlite"),
+            Backend::Crdt => write!(f, "Crdt"),
+        }
+    }
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// StoreError is returned when a describer can't be loaded or saved.
+#[derive(Debug)]
+pub enum StoreError {
+    /// The storage can't be read or written.
+    Io(io::Error),
+    /// The stored data is invalid, or the describer can't be serialized.
+    Format(String),
+    /// The backend isn't supported by this build.
+    Unsupported(Backend),
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StoreError::Io(e) => write!(f, "{}", e),
+            StoreError::Format(e) => write!(f, "invalid format: {}", e),
+            StoreError::Unsupported(b) => write!(f, "{} storage isn't supported by this build", b),
+        }
+    }
+}
+
+impl Error for StoreError {}
+
+impl From<io::Error> for StoreError {
+    fn from(e: io::Error) -> StoreError {
+        StoreError::Io(e)
+    }
+}
+
+/// Open a store of the given backend at path. If backend is None, it's chosen
+/// based on path's extension (see `Backend::from_path`).
+pub fn open<P: AsRef<Path>>(
+    path: P,
+    backend: Option<Backend>,
+) -> Result<Box<dyn Store>, StoreError> {
+    let path = path.as_ref().to_path_buf();
+    match backend.unwrap_or_else(|| Backend::from_path(&path)) {
+        Backend::File(format) => Ok(Box::new(FileStore::new(path, format))),
+        #[cfg(feature = "sqlite")]
+        Backend::Sqlite => Ok(Box::new(SqliteStore::new(path))),
+        #[cfg(not(feature = "sqlite"))]
+        Backend::Sqlite => Err(StoreError::Unsupported(Backend::Sqlite)),
+        Backend::Crdt => Ok(Box::new(CrdtStore::new(path))),
+    }
+}
+
+/// Create a describer from a value loaded from store, migrating it to the
+/// current version if needed. Migrated describers are saved back to store.
+fn migrate_in_place(store: &dyn Store, value: serde_json::Value) -> Result<Describer, StoreError> {
+    let (describer, from) =
+        Describer::from_versioned_value(value).map_err(|e| StoreError::Format(e.to_string()))?;
+    if from < migrate::CURRENT_VERSION {
+        store.save(&describer)?;
+    }
+    Ok(describer)
+}
+
+/// FileStore stores a describer as a file of some format.
+pub struct FileStore {
+    path: PathBuf,
+    format: &'static dyn Format,
+}
+
+impl FileStore {
+    /// Create a store of the file at path, encoded using format.
+    pub fn new<P: Into<PathBuf>>(path: P, format: &'static dyn Format) -> FileStore {
+        FileStore {
+            path: path.into(),
+            format,
+        }
+    }
+}
+
+impl Store for FileStore {
+    fn exists(&self) -> bool {
+        self.path.exists()
+    }
+
+    fn load(&self) -> Result<Describer, StoreError> {
+        let value = self
+            .format
+            .decode(&fs::read(&self.path)?)
+            .map_err(StoreError::Format)?;
+        migrate_in_place(self, value)
+    }
+
+    fn save(&self, describer: &Describer) -> Result<(), StoreError> {
+        let bytes = self.format.encode(describer).map_err(StoreError::Format)?;
+        Ok(write_atomic(&self.path, &bytes)?)
+    }
+
+    fn lock(&self) -> Result<Lock, StoreError> {
+        Lock::acquire(&self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capture::Source;
+    use crate::meta::FileKind;
+    use crate::PatternKind;
+
+    #[test]
+    fn backend_from_path_test() {
+        for (path, backend) in [
+            ("/config.json", Backend::File(&format::Json)),
+            #[cfg(feature = "toml")]
+            ("/config.toml", Backend::File(&format::Toml)),
+            ("/config.db", Backend::Sqlite),
+            ("/config.sqlite3", Backend::Sqlite),
+            ("/config.crdt", Backend::Crdt),
+            ("/config", Backend::File(&format::Json)),
+        ]
+        .iter()
+        {
+            assert_eq!(Backend::from_path(path), *backend);
+        }
+    }
+
+    #[test]
+    fn write_atomic_test() {
+        let dir = std::env::temp_dir().join(format!("def-atomic-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("config.json");
+
+        write_atomic(&file, b"old").unwrap();
+        write_atomic(&file, b"new").unwrap();
+        let contents = fs::read_to_string(&file).unwrap();
+        let entries = fs::read_dir(&dir).unwrap().count();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(contents, "new");
+        assert_eq!(entries, 1);
+    }
+
+    #[test]
+    fn lock_test() {
+        let file = std::env::temp_dir().join(format!("def-lock-test-{}.json", std::process::id()));
+        let store = FileStore::new(&file, &format::Json);
+
+        let lock = store.lock().unwrap();
+        let other = File::open(lock_file(&file)).unwrap();
+        assert!(other.try_lock().is_err());
+        drop(lock);
+        assert!(other.try_lock().is_ok());
+        fs::remove_file(lock_file(&file)).unwrap();
+    }
+
+    #[test]
+    fn migrate_in_place_test() {
+        let file =
+            std::env::temp_dir().join(format!("def-migrate-test-{}.json", std::process::id()));
+        fs::write(&file, "{\"descriptions\":{\"/a\":\"A.\"},\"patterns\":{}}").unwrap();
+
+        let store = FileStore::new(&file, &format::Json);
+        let loaded = store.load().map(|d| d.describe("/a"));
+        let saved = fs::read_to_string(&file).unwrap();
+        fs::remove_file(&file).unwrap();
+
+        assert_eq!(loaded.unwrap(), Some("A.".to_string()));
+        assert!(saved.contains("\"version\": 2"));
+    }
+
+    #[test]
+    fn round_trip_test() {
+        let dir = std::env::temp_dir().join(format!("def-store-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let mut d = Describer::new();
+        d.add_description("/path/to/dir", "A directory.");
+        d.add_tags("/path/to/dir", vec!["work", "archived"]);
+        d.stamp("/path/to/dir", 1700000000);
+        d.set_private("/path/to/dir", true);
+        d.set_source(
+            "/path/to/dir",
+            Source::Command {
+                command: "pwd".to_string(),
+            },
+        );
+        d.add_pattern("/path/to", "* is in /path/to.").unwrap();
+        d.add_pattern_with_kind("/path", "** is under /path.", PatternKind::Recursive)
+            .unwrap();
+        d.add_glob("*.log", "A log file.").unwrap();
+        d.set_priority("*.log", -1);
+        d.add_typed_pattern("/path/to", "* is a dir in /path/to.", FileKind::Directory)
+            .unwrap();
+        d.add_description("/trashed", "Trashed.");
+        d.trash_description("/trashed");
+
+        let mut backends: Vec<Backend> = format::formats().into_iter().map(Backend::File).collect();
+        backends.push(Backend::Crdt);
+        if cfg!(feature = "sqlite") {
+            backends.push(Backend::Sqlite);
+        }
+        for backend in backends.iter() {
+            let store = open(dir.join(format!("config.{}", backend)), None).unwrap();
+            assert!(!store.exists());
+            store.save(&d).unwrap();
+            store.save(&d).unwrap();
+            assert!(store.exists());
+
+            let loaded = store.load().unwrap();
+            for path in ["/path/to/dir", "/path/to/x", "/path/a/b", "/var/x.log"].iter() {
+                assert_eq!(loaded.describe(path), d.describe(path), "{}", backend);
+            }
+            assert_eq!(loaded.trashed(), d.trashed());
+            assert_eq!(loaded.tags_of("/path/to/dir"), vec!["archived", "work"]);
+            assert_eq!(
+                loaded.timestamps("/path/to/dir"),
+                d.timestamps("/path/to/dir")
+            );
+            assert!(loaded.is_private("/path/to/dir"));
+            assert_eq!(loaded.source("/path/to/dir"), d.source("/path/to/dir"));
+            assert_eq!(loaded.priority("*.log"), -1);
+            assert_eq!(
+                loaded.describe_as("/path/to/x", FileKind::Directory),
+                d.describe_as("/path/to/x", FileKind::Directory)
+            );
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn load_for_test() {
+        let file =
+            std::env::temp_dir().join(format!("def-load-for-test-{}.db", std::process::id()));
+        let mut d = Describer::new();
+        for i in 0..1000 {
+            d.add_description(format!("/other/{}", i).as_str(), "Another path.");
+        }
+        d.add_description("/path", "A path.");
+        d.add_pattern("/path/to", "* is in /path/to.").unwrap();
+        d.add_pattern("$ROOT", "* is in the root.").unwrap();
+        d.add_glob("*.log", "A log file.").unwrap();
+        d.set_priority("*.log", 1);
+
+        let store = SqliteStore::new(&file);
+        store.save(&d).unwrap();
+        let size = fs::metadata(&file).unwrap().len();
+        let loaded = store.load_for(&["/path/to/x.log"]);
+        fs::remove_file(&file).unwrap();
+
+        let loaded = loaded.unwrap();
+        assert!(size >= PARTIAL_LOAD_MIN_BYTES);
+        assert_eq!(
+            loaded.describe("/path/to/x.log"),
+            d.describe("/path/to/x.log")
+        );
+        assert_eq!(loaded.describe("/path"), Some("A path.".to_string()));
+        assert_eq!(loaded.priority("*.log"), 1);
+        assert!(loaded
+            .iter_patterns(PatternKind::Children)
+            .any(|(key, _)| key == "$ROOT"));
+        assert_eq!(loaded.describe("/other/1"), None);
+    }
+}
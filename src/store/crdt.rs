@@ -0,0 +1,380 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::{write_atomic, Lock, Store, StoreError};
+use crate::Describer;
+
+/// Version of the layout of CRDT files written by this version of def.
+const VERSION: u64 = 1;
+
+/// Sections of a serialized describer holding sets, each member of which is
+/// a register of its own, so that members added on different machines are
+/// all kept.
+const SET_SECTIONS: [&str; 1] = ["private"];
+
+/// Text that names of conflicting copies made by file sync tools contain,
+/// such as "config.sync-conflict-20240101-120000-ABCDEFG.crdt" (Syncthing)
+/// or "config (conflicted copy 2024-01-01).crdt" (Dropbox, Nextcloud).
+const CONFLICT_MARKER: &str = "conflict";
+
+/// Register is a last-writer-wins register holding one value of a
+/// describer: an entry of a map (such as the description of a path), a
+/// member of a set, or a whole section (such as the trash).
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+struct Register {
+    /// Section of the serialized describer the value is in, such as
+    /// "descriptions".
+    section: String,
+    /// Key of the value in its section, or None if the register holds the
+    /// whole section.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    key: Option<String>,
+    /// The value, or None if it was deleted.
+    value: Option<Value>,
+    /// Hybrid timestamp of the write: the time in milliseconds since the
+    /// Unix epoch, or one more than the latest timestamp the writer had seen
+    /// if that's later, so that writes made after seeing others win over
+    /// them even if clocks are skewed.
+    time: u64,
+}
+
+/// State is the contents of a CRDT file.
+#[derive(Deserialize, Serialize, Debug)]
+struct State {
+    /// Version of the layout of the file.
+    crdt: u64,
+    registers: Vec<Register>,
+}
+
+/// Identity of a register: its section and key.
+type Id = (String, Option<String>);
+
+/// Registers by identity.
+type Registers = BTreeMap<Id, Register>;
+
+/// CrdtStore stores a describer as a JSON file of last-writer-wins registers,
+/// one for each description, pattern, and other value, so that copies of the
+/// file changed on different machines can be merged without conflicts.
+///
+/// File sync tools (such as Syncthing or Dropbox) keep both versions of a
+/// file changed on two machines at once, one of them as a conflicting copy
+/// next to the file. Loading merges the file with its conflicting copies,
+/// keeping the latest write of every register, and saving folds them into
+/// the file and deletes them. Deletions are kept as registers without a
+/// value, so that a deleted entry isn't brought back by an older copy.
+pub struct CrdtStore {
+    path: PathBuf,
+}
+
+impl CrdtStore {
+    /// Create a store of the CRDT file at path.
+    pub fn new<P: Into<PathBuf>>(path: P) -> CrdtStore {
+        CrdtStore { path: path.into() }
+    }
+
+    /// Return paths of conflicting copies of the store's file: files next to
+    /// it whose names start with its stem, end with its extension, and
+    /// contain CONFLICT_MARKER.
+    fn conflicts(&self) -> io::Result<Vec<PathBuf>> {
+        let name = |path: &Path| {
+            path.file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string()
+        };
+        let stem = self.path.file_stem().unwrap_or_default().to_string_lossy();
+        let extension = match self.path.extension() {
+            Some(extension) => format!(".{}", extension.to_string_lossy()),
+            None => String::new(),
+        };
+        let dir = match self.path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut conflicts = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            let file_name = name(&path);
+            let is_conflict = file_name != name(&self.path)
+                && file_name.starts_with(stem.as_ref())
+                && file_name.ends_with(&extension)
+                && file_name.to_lowercase().contains(CONFLICT_MARKER);
+            if is_conflict && path.is_file() {
+                conflicts.push(path);
+            }
+        }
+        conflicts.sort();
+        Ok(conflicts)
+    }
+
+    /// Return registers of the store's file merged with those of its
+    /// conflicting copies, along with the paths of the copies.
+    fn registers(&self) -> Result<(Registers, Vec<PathBuf>), StoreError> {
+        let mut registers = Registers::new();
+        let conflicts = self.conflicts()?;
+        let files = std::iter::once(&self.path).chain(conflicts.iter());
+        for file in files.filter(|f| f.exists()) {
+            let state: State = serde_json::from_slice(&fs::read(file)?)
+                .map_err(|e| StoreError::Format(format!("{}: {}", file.display(), e)))?;
+            if state.crdt > VERSION {
+                return Err(StoreError::Format(format!(
+                    "{}: unsupported CRDT version {}",
+                    file.display(),
+                    state.crdt
+                )));
+            }
+            for register in state.registers {
+                merge(&mut registers, register);
+            }
+        }
+        Ok((registers, conflicts))
+    }
+}
+
+impl Store for CrdtStore {
+    fn exists(&self) -> bool {
+        self.path.exists()
+    }
+
+    fn load(&self) -> Result<Describer, StoreError> {
+        let (registers, _) = self.registers()?;
+        to_describer(&registers)
+    }
+
+    fn save(&self, describer: &Describer) -> Result<(), StoreError> {
+        let (mut registers, conflicts) = self.registers()?;
+        let values = to_values(describer)?;
+        let latest = registers.values().map(|r| r.time + 1).max().unwrap_or(0);
+        let time = now().max(latest);
+
+        let mut writes = Vec::new();
+        for (id, value) in values.iter() {
+            let current = registers.get(id).and_then(|r| r.value.as_ref());
+            if current != Some(value) {
+                writes.push((id.clone(), Some(value.clone())));
+            }
+        }
+        for (id, register) in registers.iter() {
+            if register.value.is_some() && !values.contains_key(id) {
+                writes.push((id.clone(), None));
+            }
+        }
+        for ((section, key), value) in writes {
+            registers.insert(
+                (section.clone(), key.clone()),
+                Register {
+                    section,
+                    key,
+                    value,
+                    time,
+                },
+            );
+        }
+
+        let state = State {
+            crdt: VERSION,
+            registers: registers.into_values().collect(),
+        };
+        let bytes =
+            serde_json::to_vec_pretty(&state).map_err(|e| StoreError::Format(e.to_string()))?;
+        write_atomic(&self.path, &bytes)?;
+        for conflict in conflicts.iter() {
+            fs::remove_file(conflict)?;
+        }
+        Ok(())
+    }
+
+    fn lock(&self) -> Result<Lock, StoreError> {
+        Lock::acquire(&self.path)
+    }
+}
+
+/// Add register to registers, unless a register with the same identity was
+/// written later. Writes at the same time are ordered by their values, so
+/// that every machine keeps the same one.
+fn merge(registers: &mut Registers, register: Register) {
+    let id = (register.section.clone(), register.key.clone());
+    let order = |r: &Register| (r.time, r.value.as_ref().map(Value::to_string));
+    match registers.get(&id) {
+        Some(existing) if order(existing) >= order(&register) => {}
+        _ => {
+            registers.insert(id, register);
+        }
+    }
+}
+
+/// Return values of the registers describer is split into, by identity.
+fn to_values(describer: &Describer) -> Result<BTreeMap<Id, Value>, StoreError> {
+    let value = serde_json::to_value(describer).map_err(|e| StoreError::Format(e.to_string()))?;
+    let mut values = BTreeMap::new();
+    for (section, value) in into_object(value) {
+        match value {
+            // The version is that of the layout of the file.
+            _ if section == "version" => {}
+            Value::Object(map) => {
+                for (key, value) in map {
+                    values.insert((section.clone(), Some(key)), value);
+                }
+            }
+            Value::Array(members) if SET_SECTIONS.contains(&section.as_str()) => {
+                for member in members {
+                    let key = member.as_str().map(str::to_string).unwrap_or_default();
+                    values.insert((section.clone(), Some(key)), Value::Bool(true));
+                }
+            }
+            value => {
+                values.insert((section, None), value);
+            }
+        }
+    }
+    Ok(values)
+}
+
+/// Return the describer made of the values of registers.
+fn to_describer(registers: &Registers) -> Result<Describer, StoreError> {
+    let mut object = into_object(
+        serde_json::to_value(Describer::new()).map_err(|e| StoreError::Format(e.to_string()))?,
+    );
+    for register in registers.values() {
+        let value = match &register.value {
+            Some(value) => value.clone(),
+            None => continue,
+        };
+        let section = register.section.clone();
+        match &register.key {
+            None => {
+                object.insert(section, value);
+            }
+            Some(key) if SET_SECTIONS.contains(&section.as_str()) => {
+                let members = object
+                    .entry(section)
+                    .or_insert_with(|| Value::Array(Vec::new()));
+                if let Value::Array(members) = members {
+                    members.push(Value::String(key.clone()));
+                }
+            }
+            Some(key) => {
+                let map = object
+                    .entry(section)
+                    .or_insert_with(|| Value::Object(Map::new()));
+                if let Value::Object(map) = map {
+                    map.insert(key.clone(), value);
+                }
+            }
+        }
+    }
+    serde_json::from_value(Value::Object(object)).map_err(|e| StoreError::Format(e.to_string()))
+}
+
+/// Return the fields of value if it's an object, or no fields otherwise.
+fn into_object(value: Value) -> Map<String, Value> {
+    match value {
+        Value::Object(map) => map,
+        _ => Map::new(),
+    }
+}
+
+/// Return the current time in milliseconds since the Unix epoch.
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conflict_test() {
+        let dir =
+            std::env::temp_dir().join(format!("def-crdt-conflict-test-{}", std::process::id()));
+        fs::create_dir_all(dir.join("b")).unwrap();
+        let a = CrdtStore::new(dir.join("config.crdt"));
+        let b = CrdtStore::new(dir.join("b").join("config.crdt"));
+
+        let mut base = Describer::new();
+        base.add_description("/kept", "Kept.");
+        base.add_description("/removed", "Removed.");
+        base.add_description("/changed", "Changed.");
+        a.save(&base).unwrap();
+        fs::copy(&a.path, &b.path).unwrap();
+
+        // Machine A adds an entry, and machine B changes and removes others.
+        let mut on_a = a.load().unwrap();
+        on_a.add_description("/a", "Added on A.");
+        a.save(&on_a).unwrap();
+        let mut on_b = b.load().unwrap();
+        on_b.add_description("/changed", "Changed on B.");
+        on_b.remove_description("/removed");
+        on_b.set_private("/kept", true);
+        b.save(&on_b).unwrap();
+
+        // The sync tool keeps B's version as a conflicting copy.
+        let conflict = dir.join("config.sync-conflict-20240101-120000-B.crdt");
+        fs::copy(&b.path, &conflict).unwrap();
+        fs::write(dir.join("config.crdt.lock"), "").unwrap();
+        let conflicts = a.conflicts().unwrap();
+        let merged = a.load().unwrap();
+        a.save(&merged).unwrap();
+        let conflict_exists = conflict.exists();
+        let reloaded = a.load().unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(conflicts, vec![conflict]);
+        assert!(!conflict_exists);
+        for d in [&merged, &reloaded].iter() {
+            for (path, description) in [
+                ("/kept", Some("Kept.")),
+                ("/a", Some("Added on A.")),
+                ("/changed", Some("Changed on B.")),
+                ("/removed", None),
+            ]
+            .iter()
+            {
+                assert_eq!(d.describe(path).as_deref(), *description, "{}", path);
+            }
+            assert!(d.is_private("/kept"));
+        }
+    }
+
+    #[test]
+    fn merge_test() {
+        let register = |value: Option<&str>, time| Register {
+            section: "descriptions".to_string(),
+            key: Some("/a".to_string()),
+            value: value.map(|v| Value::String(v.to_string())),
+            time,
+        };
+        for (first, second, winner) in [
+            (register(Some("old"), 1), register(Some("new"), 2), "new"),
+            (register(Some("new"), 2), register(Some("old"), 1), "new"),
+            (register(Some("x"), 1), register(Some("y"), 1), "y"),
+            (register(Some("y"), 1), register(Some("x"), 1), "y"),
+        ]
+        .iter()
+        {
+            let mut registers = Registers::new();
+            merge(&mut registers, first.clone());
+            merge(&mut registers, second.clone());
+            let value = registers.values().next().unwrap().value.clone();
+            assert_eq!(value, Some(Value::String(winner.to_string())));
+        }
+
+        let mut registers = Registers::new();
+        merge(&mut registers, register(Some("a"), 1));
+        merge(&mut registers, register(None, 2));
+        assert_eq!(registers.values().next().unwrap().value, None);
+    }
+}
@@ -0,0 +1,382 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::PathBuf;
+
+use rusqlite::{params, Connection, Transaction};
+
+use super::{Lock, Store, StoreError, PARTIAL_LOAD_MIN_BYTES};
+use crate::trash::TrashedEntry;
+use crate::{paths, DefPath, Describer, PatternKind};
+
+/// Kind of rows holding specific descriptions.
+const DESCRIPTION_KIND: &str = "description";
+
+/// Kind of rows holding recorded metadata as JSON.
+const METADATA_KIND: &str = "metadata";
+
+/// Kind of rows holding tags of a path as a JSON array.
+const TAGS_KIND: &str = "tags";
+
+/// Kind of rows holding timestamps of a description as JSON.
+const TIMESTAMPS_KIND: &str = "timestamps";
+
+/// Kind of rows marking a description as private, with empty values.
+const PRIVATE_KIND: &str = "private";
+
+/// Kind of rows holding the priority of patterns mapped to a key.
+const PRIORITY_KIND: &str = "priority";
+
+/// Kind of rows holding the source of a description as JSON.
+const SOURCE_KIND: &str = "source";
+
+/// Kind of rows holding typed patterns of a directory, by the type of paths
+/// they apply to, as JSON.
+const TYPED_KIND: &str = "typed";
+
+/// Selects rows needed to describe paths whose keys, and those of their
+/// ancestors, are given as a JSON array: rows of those keys, globs and
+/// priorities, which aren't keyed by paths, and keys with variables, which
+/// match paths only once expanded.
+const PARTIAL_SELECT: &str = "
+    SELECT kind, key, value FROM entries WHERE key IN (SELECT value FROM json_each(?1))
+    UNION ALL
+    SELECT kind, key, value FROM entries WHERE kind IN (?2, ?3)
+    UNION ALL
+    SELECT kind, key, value FROM entries WHERE instr(key, '$') > 0
+";
+
+/// Tables of the database.
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS entries (
+        kind TEXT NOT NULL,
+        key TEXT NOT NULL,
+        value TEXT NOT NULL,
+        PRIMARY KEY (kind, key)
+    );
+    CREATE INDEX IF NOT EXISTS entries_by_key ON entries (key);
+    CREATE INDEX IF NOT EXISTS entries_with_variables ON entries (key)
+        WHERE instr(key, '$') > 0;
+    CREATE TABLE IF NOT EXISTS trash (
+        path TEXT NOT NULL,
+        description TEXT NOT NULL,
+        pattern TEXT,
+        trashed_at INTEGER NOT NULL
+    );
+";
+
+/// SqliteStore stores a describer in an SQLite database. Saving only writes
+/// entries that changed since the database was last saved, so large
+/// describers aren't rewritten on every change.
+pub struct SqliteStore {
+    path: PathBuf,
+}
+
+impl SqliteStore {
+    /// Create a store of the database at path.
+    pub fn new<P: Into<PathBuf>>(path: P) -> SqliteStore {
+        SqliteStore { path: path.into() }
+    }
+
+    /// Open a connection to the database, creating its tables if needed.
+    fn connect(&self) -> Result<Connection, StoreError> {
+        let connection = Connection::open(&self.path).map_err(format_error)?;
+        connection.execute_batch(SCHEMA).map_err(format_error)?;
+        Ok(connection)
+    }
+}
+
+impl Store for SqliteStore {
+    fn exists(&self) -> bool {
+        self.path.exists()
+    }
+
+    fn load(&self) -> Result<Describer, StoreError> {
+        let connection = self.connect()?;
+        let mut describer = Describer::new();
+        for (kind, rows) in read_entries(&connection)? {
+            for (key, value) in rows {
+                put_row(&mut describer, &kind, key, value)?;
+            }
+        }
+
+        let mut statement = connection
+            .prepare("SELECT path, description, pattern, trashed_at FROM trash ORDER BY rowid")
+            .map_err(format_error)?;
+        let rows = statement
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                    row.get::<_, i64>(3)?,
+                ))
+            })
+            .map_err(format_error)?;
+        for row in rows {
+            let (path, description, pattern, trashed_at) = row.map_err(format_error)?;
+            describer.trash.push(TrashedEntry {
+                path,
+                description,
+                pattern: pattern.as_deref().map(parse_kind).transpose()?,
+                trashed_at: trashed_at as u64,
+            });
+        }
+
+        Ok(describer)
+    }
+
+    fn save(&self, describer: &Describer) -> Result<(), StoreError> {
+        let mut connection = self.connect()?;
+        let transaction = connection.transaction().map_err(format_error)?;
+        let stored = read_entries(&transaction)?;
+
+        let mut current: HashMap<String, HashMap<String, String>> = HashMap::new();
+        for (kind, map) in describer.maps().iter() {
+            current.insert(
+                kind_name(*kind),
+                map.texts()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect(),
+            );
+        }
+        let mut metadata = HashMap::new();
+        for (path, m) in describer.metadata.iter() {
+            let json = serde_json::to_string(m).map_err(|e| StoreError::Format(e.to_string()))?;
+            metadata.insert(path.to_string(), json);
+        }
+        current.insert(METADATA_KIND.to_string(), metadata);
+        let mut tags = HashMap::new();
+        for (path, t) in describer.tags.iter() {
+            let json = serde_json::to_string(t).map_err(|e| StoreError::Format(e.to_string()))?;
+            tags.insert(path.to_string(), json);
+        }
+        current.insert(TAGS_KIND.to_string(), tags);
+        let mut timestamps = HashMap::new();
+        for (path, t) in describer.timestamps.iter() {
+            let json = serde_json::to_string(t).map_err(|e| StoreError::Format(e.to_string()))?;
+            timestamps.insert(path.to_string(), json);
+        }
+        current.insert(TIMESTAMPS_KIND.to_string(), timestamps);
+        current.insert(
+            PRIVATE_KIND.to_string(),
+            describer
+                .private
+                .iter()
+                .map(|path| (path.to_string(), String::new()))
+                .collect(),
+        );
+        current.insert(
+            PRIORITY_KIND.to_string(),
+            describer
+                .priorities
+                .iter()
+                .map(|(key, priority)| (key.clone(), priority.to_string()))
+                .collect(),
+        );
+        let mut sources = HashMap::new();
+        for (path, s) in describer.sources.iter() {
+            let json = serde_json::to_string(s).map_err(|e| StoreError::Format(e.to_string()))?;
+            sources.insert(path.to_string(), json);
+        }
+        current.insert(SOURCE_KIND.to_string(), sources);
+        let mut typed = HashMap::new();
+        for (path, patterns) in describer.typed_patterns.iter() {
+            let json =
+                serde_json::to_string(patterns).map_err(|e| StoreError::Format(e.to_string()))?;
+            typed.insert(path.to_string(), json);
+        }
+        current.insert(TYPED_KIND.to_string(), typed);
+
+        write_changes(&transaction, &stored, &current)?;
+
+        transaction
+            .execute("DELETE FROM trash", [])
+            .map_err(format_error)?;
+        for entry in describer.trash.iter() {
+            transaction
+                .execute(
+                    "INSERT INTO trash (path, description, pattern, trashed_at)
+                     VALUES (?1, ?2, ?3, ?4)",
+                    params![
+                        entry.path,
+                        entry.description,
+                        entry.pattern.map(|k| kind_name(Some(k))),
+                        entry.trashed_at as i64
+                    ],
+                )
+                .map_err(format_error)?;
+        }
+
+        transaction.commit().map_err(format_error)
+    }
+
+    fn lock(&self) -> Result<Lock, StoreError> {
+        Lock::acquire(&self.path)
+    }
+
+    fn load_for(&self, paths: &[&str]) -> Result<Describer, StoreError> {
+        if fs::metadata(&self.path)?.len() < PARTIAL_LOAD_MIN_BYTES {
+            return self.load();
+        }
+
+        let mut keys = BTreeSet::new();
+        for path in paths.iter() {
+            let path = DefPath::new(path);
+            for (ancestor, _) in paths::iter_ancestors(path.as_str()) {
+                keys.insert(ancestor.to_string());
+            }
+            keys.insert(path.into());
+        }
+        let keys = serde_json::to_string(&keys).map_err(|e| StoreError::Format(e.to_string()))?;
+
+        let connection = self.connect()?;
+        let mut statement = connection.prepare(PARTIAL_SELECT).map_err(format_error)?;
+        let rows = statement
+            .query_map(
+                params![keys, kind_name(Some(PatternKind::Glob)), PRIORITY_KIND],
+                |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, String>(2)?,
+                    ))
+                },
+            )
+            .map_err(format_error)?;
+        let mut describer = Describer::new();
+        for row in rows {
+            let (kind, key, value) = row.map_err(format_error)?;
+            put_row(&mut describer, &kind, key, value)?;
+        }
+        Ok(describer)
+    }
+}
+
+/// Add a row of entries table, of the given kind, to describer.
+fn put_row(
+    describer: &mut Describer,
+    kind: &str,
+    key: String,
+    value: String,
+) -> Result<(), StoreError> {
+    match kind {
+        DESCRIPTION_KIND => describer.map_mut(None).put(key, value),
+        METADATA_KIND => {
+            let metadata =
+                serde_json::from_str(&value).map_err(|e| StoreError::Format(e.to_string()))?;
+            describer.metadata.insert(key.into(), metadata);
+        }
+        TAGS_KIND => {
+            let tags =
+                serde_json::from_str(&value).map_err(|e| StoreError::Format(e.to_string()))?;
+            describer.tags.insert(key.into(), tags);
+        }
+        TIMESTAMPS_KIND => {
+            let timestamps =
+                serde_json::from_str(&value).map_err(|e| StoreError::Format(e.to_string()))?;
+            describer.timestamps.insert(key.into(), timestamps);
+        }
+        PRIVATE_KIND => {
+            describer.private.insert(key.into());
+        }
+        PRIORITY_KIND => {
+            let priority = value
+                .parse()
+                .map_err(|_| StoreError::Format(format!("invalid priority {}", value)))?;
+            describer.priorities.insert(key, priority);
+        }
+        SOURCE_KIND => {
+            let source =
+                serde_json::from_str(&value).map_err(|e| StoreError::Format(e.to_string()))?;
+            describer.sources.insert(key.into(), source);
+        }
+        TYPED_KIND => {
+            let patterns =
+                serde_json::from_str(&value).map_err(|e| StoreError::Format(e.to_string()))?;
+            describer.typed_patterns.insert(key.into(), patterns);
+        }
+        _ => describer.map_mut(Some(parse_kind(kind)?)).put(key, value),
+    }
+    Ok(())
+}
+
+/// Read all rows of entries table, grouped by kind.
+fn read_entries(
+    connection: &Connection,
+) -> Result<HashMap<String, HashMap<String, String>>, StoreError> {
+    let mut statement = connection
+        .prepare("SELECT kind, key, value FROM entries")
+        .map_err(format_error)?;
+    let rows = statement
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })
+        .map_err(format_error)?;
+
+    let mut entries: HashMap<String, HashMap<String, String>> = HashMap::new();
+    for row in rows {
+        let (kind, key, value) = row.map_err(format_error)?;
+        entries.entry(kind).or_default().insert(key, value);
+    }
+    Ok(entries)
+}
+
+/// Write the difference between stored and current entries: delete entries
+/// that no longer exist, and insert new or changed ones.
+fn write_changes(
+    transaction: &Transaction,
+    stored: &HashMap<String, HashMap<String, String>>,
+    current: &HashMap<String, HashMap<String, String>>,
+) -> Result<(), StoreError> {
+    let empty = HashMap::new();
+    for (kind, rows) in stored.iter() {
+        let current_rows = current.get(kind).unwrap_or(&empty);
+        for key in rows.keys().filter(|k| !current_rows.contains_key(*k)) {
+            transaction
+                .execute(
+                    "DELETE FROM entries WHERE kind = ?1 AND key = ?2",
+                    params![kind, key],
+                )
+                .map_err(format_error)?;
+        }
+    }
+
+    for (kind, rows) in current.iter() {
+        let stored_rows = stored.get(kind).unwrap_or(&empty);
+        for (key, value) in rows.iter().filter(|(k, v)| stored_rows.get(*k) != Some(v)) {
+            transaction
+                .execute(
+                    "INSERT OR REPLACE INTO entries (kind, key, value) VALUES (?1, ?2, ?3)",
+                    params![kind, key, value],
+                )
+                .map_err(format_error)?;
+        }
+    }
+    Ok(())
+}
+
+/// Return the name of rows holding entries of the given kind.
+fn kind_name(kind: Option<PatternKind>) -> String {
+    match kind {
+        None => DESCRIPTION_KIND.to_string(),
+        Some(k) => serde_json::to_value(k)
+            .ok()
+            .and_then(|v| v.as_str().map(|s| s.to_string()))
+            .unwrap_or_default(),
+    }
+}
+
+/// Parse the name of a pattern kind, as returned by kind_name.
+fn parse_kind(name: &str) -> Result<PatternKind, StoreError> {
+    serde_json::from_value(serde_json::Value::String(name.to_string()))
+        .map_err(|e| StoreError::Format(e.to_string()))
+}
+
+/// Convert an SQLite error into a StoreError.
+fn format_error(e: rusqlite::Error) -> StoreError {
+    StoreError::Format(e.to_string())
+}
@@ -0,0 +1,509 @@
+//! Syncing the config with a remote copy, shared by several machines.
+//!
+//! A `Remote` is a git repository, holding the config in `def.json` at its
+//! root, or an http(s) URL of a JSON file, such as one on a WebDAV server,
+//! which is read with GET and written with PUT. Remotes are reached by
+//! running git and curl, which have to be installed.
+//!
+//! The local and remote copies are merged three-way (see `merge`), against
+//! the base: the remote copy as it was when last synced, kept beside the
+//! config file (see `config::sync_base_file`). Entries changed by one copy
+//! since the base take its change. Entries changed differently by both
+//! conflict: of two descriptions, the one updated last is kept (see
+//! `Describer::timestamps`), so that an edit wins over a removal, and of two
+//! patterns, the local one is. Conflicts are reported, so that a discarded
+//! change can be made again by hand.
+
+use std::borrow::Borrow;
+use std::collections::{BTreeSet, HashMap};
+use std::env;
+use std::fs;
+use std::hash::Hash;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command, Output, Stdio};
+
+use crate::event::{self, Event};
+use crate::format::Json;
+use crate::{Describer, Error, PatternKind};
+
+/// Name of the file holding the config in git remotes.
+pub const REMOTE_FILE: &str = "def.json";
+
+/// Message of commits pushing the config to git remotes.
+const COMMIT_MESSAGE: &str = "Sync descriptions";
+
+/// Identity of commits pushed by users who haven't configured one for git.
+const FALLBACK_IDENTITY: [&str; 4] = ["-c", "user.name=def", "-c", "user.email=def@localhost"];
+
+/// Remote is where the shared copy of the config is kept.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Remote {
+    /// A git repository, by any URL git accepts, such as a path or an ssh
+    /// URL.
+    Git(String),
+    /// A JSON file served over http(s).
+    Http(String),
+}
+
+impl Remote {
+    /// Return the remote at url: http(s) URLs are files, unless they end
+    /// with ".git", and other URLs are git repositories.
+    pub fn parse(url: &str) -> Remote {
+        let http = url.starts_with("http://") || url.starts_with("https://");
+        if http && !url.ends_with(".git") {
+            Remote::Http(url.to_string())
+        } else {
+            Remote::Git(url.to_string())
+        }
+    }
+
+    /// Return the remote copy of the config, or None if there's none yet,
+    /// such as in a new repository.
+    pub fn fetch(&self) -> Result<Option<Describer>, Error> {
+        match self {
+            Remote::Git(url) => with_clone(url, |dir| {
+                let file = dir.join(REMOTE_FILE);
+                if file.exists() {
+                    Describer::load(file, &Json).map(Some)
+                } else {
+                    Ok(None)
+                }
+            }),
+            Remote::Http(url) => fetch_http(url),
+        }
+    }
+
+    /// Replace the remote copy of the config with describer.
+    pub fn push(&self, describer: &Describer) -> Result<(), Error> {
+        match self {
+            Remote::Git(url) => with_clone(url, |dir| push_git(dir, describer)),
+            Remote::Http(url) => push_http(url, describer),
+        }
+    }
+}
+
+/// Side is one of the copies being synced.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Side {
+    /// The config file.
+    Local,
+    /// The copy kept by the remote.
+    Remote,
+}
+
+/// Conflict is an entry changed differently by both copies since they were
+/// last synced.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Conflict {
+    /// Path the entry is mapped to.
+    pub path: String,
+    /// Kind of the pattern, or None if the entry is a specific description.
+    pub pattern: Option<PatternKind>,
+    /// Text of the local entry, or None if it was removed.
+    pub local: Option<String>,
+    /// Text of the remote entry, or None if it was removed.
+    pub remote: Option<String>,
+    /// Copy whose entry was kept.
+    pub kept: Side,
+}
+
+/// SyncReport lists what syncing changed. Changes are sorted like those of
+/// `event::diff`.
+#[derive(Debug, Default, PartialEq)]
+pub struct SyncReport {
+    /// Changes of local entries taken from the remote copy.
+    pub pulled: Vec<Event>,
+    /// Changes of remote entries taken from the local copy, which pushing
+    /// the merged copy makes.
+    pub pushed: Vec<Event>,
+    /// Entries changed by both copies.
+    pub conflicts: Vec<Conflict>,
+}
+
+/// Merge changes local and remote made since base, their last synced copy,
+/// and return the merged describer along with what changed. The merged
+/// describer keeps local's trash, resolution order, and match options.
+/// Typed patterns of a directory are merged as a whole, keeping local ones
+/// if both copies changed them.
+pub fn merge(base: &Describer, local: &Describer, remote: &Describer) -> (Describer, SyncReport) {
+    let (base_texts, local_texts, remote_texts) = (
+        event::texts(base),
+        event::texts(local),
+        event::texts(remote),
+    );
+    let keys: BTreeSet<_> = base_texts
+        .keys()
+        .chain(local_texts.keys())
+        .chain(remote_texts.keys())
+        .copied()
+        .collect();
+
+    let mut merged = local.clone();
+    let mut conflicts = Vec::new();
+    for (path, pattern) in keys {
+        let before = base_texts.get(&(path, pattern)).copied();
+        let ours = local_texts.get(&(path, pattern)).copied();
+        let theirs = remote_texts.get(&(path, pattern)).copied();
+        if ours == theirs || theirs == before {
+            continue;
+        }
+        if ours != before {
+            let kept = updated_last(local, remote, path, pattern);
+            conflicts.push(Conflict {
+                path: path.to_string(),
+                pattern,
+                local: ours.map(str::to_string),
+                remote: theirs.map(str::to_string),
+                kept,
+            });
+            if kept == Side::Local {
+                continue;
+            }
+        }
+        take(&mut merged, remote, path, pattern);
+    }
+
+    let dirs: BTreeSet<_> = base
+        .typed_patterns
+        .iter()
+        .chain(local.typed_patterns.iter())
+        .chain(remote.typed_patterns.iter())
+        .map(|(dir, _)| dir)
+        .collect();
+    for dir in dirs {
+        let before = base.typed_patterns.get(dir);
+        let ours = local.typed_patterns.get(dir);
+        let theirs = remote.typed_patterns.get(dir);
+        if ours != before || theirs == before {
+            continue;
+        }
+        match theirs {
+            Some(patterns) => merged.typed_patterns.insert(dir.clone(), patterns.clone()),
+            None => merged.typed_patterns.remove(dir),
+        };
+    }
+
+    let report = SyncReport {
+        pulled: event::diff(local, &merged),
+        pushed: event::diff(remote, &merged),
+        conflicts,
+    };
+    (merged, report)
+}
+
+/// Return the copy whose conflicting entry is kept: the one whose
+/// description was updated last, or the local one if the entry is a pattern
+/// or both were updated at the same time.
+fn updated_last(
+    local: &Describer,
+    remote: &Describer,
+    path: &str,
+    pattern: Option<PatternKind>,
+) -> Side {
+    let updated = |d: &Describer| d.timestamps.get(path).map(|t| t.updated);
+    if pattern.is_none() && updated(remote) > updated(local) {
+        Side::Remote
+    } else {
+        Side::Local
+    }
+}
+
+/// Replace merged's entry of the given kind mapped to path with remote's,
+/// along with the records kept beside it.
+fn take(merged: &mut Describer, remote: &Describer, path: &str, pattern: Option<PatternKind>) {
+    match remote.map(pattern).text(path) {
+        Some(text) => merged
+            .map_mut(pattern)
+            .put(path.to_string(), text.to_string()),
+        None => {
+            merged.map_mut(pattern).take(path);
+        }
+    }
+    if pattern.is_some() {
+        copy(&mut merged.priorities, &remote.priorities, path);
+        return;
+    }
+    copy(&mut merged.metadata, &remote.metadata, path);
+    copy(&mut merged.tags, &remote.tags, path);
+    copy(&mut merged.timestamps, &remote.timestamps, path);
+    copy(&mut merged.sources, &remote.sources, path);
+    match remote.private.get(path) {
+        Some(key) => merged.private.insert(key.clone()),
+        None => merged.private.remove(path),
+    };
+}
+
+/// Replace the value key is mapped to in to with its value in from, or
+/// remove it if from has none.
+fn copy<K, V>(to: &mut HashMap<K, V>, from: &HashMap<K, V>, key: &str)
+where
+    K: Borrow<str> + Hash + Eq + Clone,
+    V: Clone,
+{
+    match from.get_key_value(key) {
+        Some((key, value)) => to.insert(key.clone(), value.clone()),
+        None => to.remove(key),
+    };
+}
+
+/// Clone the git repository at url into a temporary directory, and return
+/// what f returns when called with it. The directory is deleted afterwards.
+fn with_clone<T, F>(url: &str, f: F) -> Result<T, Error>
+where
+    F: FnOnce(&Path) -> Result<T, Error>,
+{
+    let dir = scratch_path("clone");
+    let _ = fs::remove_dir_all(&dir);
+    let result = run(Command::new("git")
+        .args(["clone", "--quiet", "--depth", "1", url])
+        .arg(&dir))
+    .and_then(|_| f(&dir));
+    let _ = fs::remove_dir_all(&dir);
+    result
+}
+
+/// Commit describer to the clone at dir, and push it, unless the remote
+/// copy is the same.
+fn push_git(dir: &Path, describer: &Describer) -> Result<(), Error> {
+    describer.save(dir.join(REMOTE_FILE), &Json)?;
+    run(git(dir).args(["add", REMOTE_FILE]))?;
+    if run(git(dir).args(["status", "--porcelain"]))?
+        .stdout
+        .is_empty()
+    {
+        return Ok(());
+    }
+    let mut commit = git(dir);
+    if run(git(dir).args(["config", "user.email"])).is_err() {
+        commit.args(FALLBACK_IDENTITY);
+    }
+    run(commit.args(["commit", "--quiet", "--message", COMMIT_MESSAGE]))?;
+    run(git(dir).args(["push", "--quiet", "origin", "HEAD"]))?;
+    Ok(())
+}
+
+/// Return a git command run in dir.
+fn git(dir: &Path) -> Command {
+    let mut command = Command::new("git");
+    command.arg("-C").arg(dir);
+    command
+}
+
+/// Download the file at url, or return None if the server doesn't have it
+/// (HTTP 404).
+fn fetch_http(url: &str) -> Result<Option<Describer>, Error> {
+    let file = scratch_path("fetched.json");
+    let result = run(Command::new("curl")
+        .args(["--silent", "--show-error", "--location"])
+        .args(["--write-out", "%{http_code}", "--output"])
+        .arg(&file)
+        .arg(url))
+    .and_then(
+        |output| match String::from_utf8_lossy(&output.stdout).trim() {
+            "404" => Ok(None),
+            status if status.starts_with('2') => Describer::load(&file, &Json).map(Some),
+            status => Err(Error::Sync(format!("{} returned HTTP {}", url, status))),
+        },
+    );
+    let _ = fs::remove_file(&file);
+    result
+}
+
+/// Upload describer to url.
+fn push_http(url: &str, describer: &Describer) -> Result<(), Error> {
+    let file = scratch_path("pushed.json");
+    describer.save(&file, &Json)?;
+    let result = run(Command::new("curl")
+        .args(["--fail", "--silent", "--show-error", "--location"])
+        .arg("--upload-file")
+        .arg(&file)
+        .arg(url));
+    let _ = fs::remove_file(&file);
+    result.map(|_| ())
+}
+
+/// Run command, and return its output, or its error message if it fails.
+fn run(command: &mut Command) -> Result<Output, Error> {
+    let output = command.stdin(Stdio::null()).output().map_err(|e| {
+        let program = command.get_program().to_string_lossy();
+        Error::Sync(format!("failed to run {}: {}", program, e))
+    })?;
+    if output.status.success() {
+        Ok(output)
+    } else {
+        let message = String::from_utf8_lossy(&output.stderr);
+        Err(Error::Sync(message.trim().to_string()))
+    }
+}
+
+/// Return a path in the temporary directory, unique to this process.
+fn scratch_path(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("def-sync-{}-{}", process::id(), name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::EventKind;
+
+    #[test]
+    fn parse_test() {
+        for (url, http) in [
+            ("https://dav.example.com/def.json", true),
+            ("http://localhost:8080/def.json", true),
+            ("https://github.com/user/descriptions.git", false),
+            ("git@github.com:user/descriptions.git", false),
+            ("/srv/git/descriptions", false),
+        ] {
+            let expected = if http {
+                Remote::Http(url.to_string())
+            } else {
+                Remote::Git(url.to_string())
+            };
+            assert_eq!(Remote::parse(url), expected);
+        }
+    }
+
+    #[test]
+    fn merge_test() {
+        let mut base = Describer::new();
+        base.add_description("/same", "Unchanged.");
+        base.add_description("/local", "Before.");
+        base.add_description("/remote", "Before.");
+        base.add_description("/removed", "Removed by remote.");
+        base.add_description("/both", "Before.");
+        base.add_pattern("/pattern", "Before.").unwrap();
+
+        let mut local = base.clone();
+        local.add_description("/local", "Changed locally.");
+        local.add_description("/new", "Added locally.");
+        local.add_description("/both", "Changed locally.");
+        local.add_pattern("/pattern", "Changed locally.").unwrap();
+
+        let mut remote = base.clone();
+        remote.add_description("/remote", "Changed remotely.");
+        remote.add_tags("/remote", ["synced"]);
+        remote.remove_description("/removed");
+        remote.add_description("/both", "Changed remotely.");
+        remote.add_pattern("/pattern", "Changed remotely.").unwrap();
+        local.stamp("/both", 100);
+        remote.stamp("/both", 160);
+
+        let (merged, report) = merge(&base, &local, &remote);
+        assert_eq!(merged.describe("/same"), Some("Unchanged.".to_string()));
+        assert_eq!(
+            merged.describe("/local"),
+            Some("Changed locally.".to_string())
+        );
+        assert_eq!(merged.describe("/new"), Some("Added locally.".to_string()));
+        assert_eq!(
+            merged.describe("/remote"),
+            Some("Changed remotely.".to_string())
+        );
+        assert_eq!(merged.tags_of("/remote"), ["synced"]);
+        assert_eq!(merged.describe("/removed"), None);
+        assert_eq!(
+            merged.describe("/both"),
+            Some("Changed remotely.".to_string())
+        );
+        assert_eq!(
+            merged.describe("/pattern/child"),
+            Some("Changed locally.".to_string())
+        );
+
+        let pulled: Vec<_> = report
+            .pulled
+            .iter()
+            .map(|e| (e.path.as_str(), e.kind))
+            .collect();
+        assert_eq!(
+            pulled,
+            [
+                ("/both", EventKind::Update),
+                ("/remote", EventKind::Update),
+                ("/removed", EventKind::Remove),
+            ]
+        );
+        let pushed: Vec<_> = report
+            .pushed
+            .iter()
+            .map(|e| (e.path.as_str(), e.kind))
+            .collect();
+        assert_eq!(
+            pushed,
+            [
+                ("/local", EventKind::Update),
+                ("/new", EventKind::Add),
+                ("/pattern", EventKind::Update),
+            ]
+        );
+        assert_eq!(
+            report.conflicts,
+            [
+                Conflict {
+                    path: "/both".to_string(),
+                    pattern: None,
+                    local: Some("Changed locally.".to_string()),
+                    remote: Some("Changed remotely.".to_string()),
+                    kept: Side::Remote,
+                },
+                Conflict {
+                    path: "/pattern".to_string(),
+                    pattern: Some(PatternKind::Children),
+                    local: Some("Changed locally.".to_string()),
+                    remote: Some("Changed remotely.".to_string()),
+                    kept: Side::Local,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn merge_without_base_test() {
+        let mut local = Describer::new();
+        local.add_description("/local", "Local.");
+        local.add_description("/both", "Same.");
+        let mut remote = Describer::new();
+        remote.add_description("/remote", "Remote.");
+        remote.add_description("/both", "Same.");
+
+        let (merged, report) = merge(&Describer::new(), &local, &remote);
+        for path in ["/local", "/remote", "/both"] {
+            assert!(merged.describe(path).is_some());
+        }
+        assert!(report.conflicts.is_empty());
+        assert_eq!(report.pulled.len(), 1);
+        assert_eq!(report.pushed.len(), 1);
+    }
+
+    #[test]
+    fn git_test() {
+        if Command::new("git").arg("--version").output().is_err() {
+            return;
+        }
+        let repo = env::temp_dir().join(format!("def-sync-test-{}.git", process::id()));
+        let _ = fs::remove_dir_all(&repo);
+        run(Command::new("git")
+            .args(["init", "--quiet", "--bare"])
+            .arg(&repo))
+        .unwrap();
+        let remote = Remote::Git(repo.to_string_lossy().to_string());
+
+        let fetched = remote.fetch();
+        let mut d = Describer::new();
+        d.add_description("/srv", "Served files.");
+        let pushed = remote.push(&d);
+        let refetched = remote.fetch();
+        let repushed = remote.push(&d);
+        fs::remove_dir_all(&repo).unwrap();
+
+        assert!(fetched.unwrap().is_none());
+        pushed.unwrap();
+        let refetched = refetched.unwrap().unwrap();
+        assert_eq!(
+            refetched.describe("/srv"),
+            Some("Served files.".to_string())
+        );
+        repushed.unwrap();
+    }
+}
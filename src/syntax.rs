@@ -0,0 +1,76 @@
+//! Characters of the syntax of patterns and paths, which programs embedding
+//! def can change.
+//!
+//! By default, `*` in a pattern is replaced by the name of the described path
+//! and `**` by its relative path (see `def::template`), and components of
+//! paths are separated by `/`. A `Syntax` set on a describer (see
+//! `Describer::set_syntax` and `DescriberBuilder`) changes both, such as to
+//! write `%` and `%%` as placeholders, leaving `*` as is in descriptions
+//! mentioning globs, or to give a describer paths separated by `\` on any
+//! platform.
+//!
+//! Keys are still kept with `/`: paths given to a describer are converted to
+//! keys as Windows paths are (see `DefPath`), so the returned keys, and
+//! paths filled in for `{path}` and the relative placeholder, use `/`.
+//! Patterns are stored as written, so a config whose patterns use another
+//! placeholder should be loaded by describers using the same syntax.
+
+use std::borrow::Cow;
+
+use crate::SEPERATOR;
+
+/// Character replaced by the name of the described path in patterns, and,
+/// doubled, by its relative path, unless another one is set.
+pub const DEFAULT_PLACEHOLDER: char = '*';
+
+/// Syntax holds the characters patterns and paths are written with, see the
+/// module's documentation.
+///
+/// # Examples
+///
+/// ```
+/// use def::syntax::Syntax;
+///
+/// let syntax = Syntax {
+///     separator: '\\',
+///     ..Syntax::default()
+/// };
+/// assert_eq!(syntax.to_key_separators(r"C:\Users\me"), "C:/Users/me");
+/// assert!(Syntax::default().is_default());
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Syntax {
+    /// Placeholder replaced by the name of the path, written twice for its
+    /// relative path. It shouldn't be a brace, or the separator.
+    pub placeholder: char,
+    /// Separator of components of paths given to a describer, replaced by
+    /// `/` in keys.
+    pub separator: char,
+}
+
+impl Default for Syntax {
+    fn default() -> Syntax {
+        Syntax {
+            placeholder: DEFAULT_PLACEHOLDER,
+            separator: SEPERATOR,
+        }
+    }
+}
+
+impl Syntax {
+    /// Return true if the syntax is def's own, so that paths and patterns
+    /// are used as written.
+    pub fn is_default(&self) -> bool {
+        *self == Syntax::default()
+    }
+
+    /// Return path with the separator replaced by `/`, borrowed if the
+    /// separator is `/` or path doesn't contain it.
+    pub fn to_key_separators<'a>(&self, path: &'a str) -> Cow<'a, str> {
+        if self.separator == SEPERATOR || !path.contains(self.separator) {
+            Cow::Borrowed(path)
+        } else {
+            Cow::Owned(path.replace(self.separator, &SEPERATOR.to_string()))
+        }
+    }
+}
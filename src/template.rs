@@ -0,0 +1,506 @@
+//! Placeholders in patterns and globs.
+//!
+//! Descriptions of patterns and globs are templates, rendered for each path
+//! they describe. A template can contain the following placeholders:
+//!
+//! - `{name}`: Name of the path, such as "notes.tar.gz".
+//! - `{stem}`: Name without its extension, such as "notes.tar".
+//! - `{ext}`: Extension of the name (without a dot), such as "gz", or nothing
+//!   if the name has no extension.
+//! - `{parent}`: Name of the directory containing the path.
+//! - `{path}`: The whole path.
+//! - `*`: Same as `{name}`, kept for compatibility.
+//! - `**`: The path relative to the directory a recursive pattern is mapped
+//!   to, or the whole path for globs.
+//!
+//! Describers can use another character than `*` for the last two, see
+//! `def::syntax`.
+//!
+//! The following metadata placeholders are filled in from the file system,
+//! only when rendering with a `FileInfo` (see `render_with` and
+//! `Describer::describe_with_fs`). Otherwise, they're left as written.
+//!
+//! - `{size}`: Size of a file, such as "1.5 KiB", or number of entries of a
+//!   directory, such as "3 entries".
+//! - `{mtime}`: Time the path was last modified, such as "2024-03-01 14:05"
+//!   (in UTC).
+//! - `{type}`: "file", "directory", or "symlink".
+//!
+//! Git placeholders are filled in from the git repository containing the
+//! path, only when rendering with a `RepoInfo` (see `def::git`, which needs
+//! def to be built with "git" feature). Values a repository doesn't have,
+//! such as the remote of a repository without remotes, are rendered as
+//! "none".
+//!
+//! - `{git_branch}`: The checked out branch, such as "main".
+//! - `{git_remote}`: URL of the "origin" remote, or of the first remote.
+//! - `{git_last_commit}`: Abbreviated hash and summary of the last commit,
+//!   such as "1a2b3c4 Fix typo".
+//!
+//! Literal braces are written as `{{` and `}}`. Unknown placeholders, such as
+//! `{owner}`, are left untouched when rendering, though patterns containing
+//! them can't be added to a describer (see `def::pattern`).
+
+use std::borrow::Cow;
+use std::fmt::{self, Write};
+
+use crate::meta::{self, FileInfo, RepoInfo};
+use crate::syntax::DEFAULT_PLACEHOLDER;
+use crate::SEPERATOR;
+
+/// Rendering of `{mtime}` on platforms that don't record modification
+/// times.
+const UNKNOWN_TIME: &str = "unknown";
+
+/// Rendering of git placeholders whose values a repository doesn't have.
+const MISSING_GIT_VALUE: &str = "none";
+
+/// Placeholder is a part of a template replaced by a value of the described
+/// path.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Placeholder {
+    Name,
+    Stem,
+    Ext,
+    Parent,
+    Path,
+    Relative,
+    Size,
+    Mtime,
+    Type,
+    GitBranch,
+    GitRemote,
+    GitLastCommit,
+}
+
+impl Placeholder {
+    /// Return the placeholder written as `{name}` in templates, or None if
+    /// there's no such placeholder.
+    pub fn from_name(name: &str) -> Option<Placeholder> {
+        match name {
+            "name" => Some(Placeholder::Name),
+            "stem" => Some(Placeholder::Stem),
+            "ext" => Some(Placeholder::Ext),
+            "parent" => Some(Placeholder::Parent),
+            "path" => Some(Placeholder::Path),
+            "size" => Some(Placeholder::Size),
+            "mtime" => Some(Placeholder::Mtime),
+            "type" => Some(Placeholder::Type),
+            "git_branch" => Some(Placeholder::GitBranch),
+            "git_remote" => Some(Placeholder::GitRemote),
+            "git_last_commit" => Some(Placeholder::GitLastCommit),
+            _ => None,
+        }
+    }
+
+    /// Return true if the placeholder is filled in from the file system.
+    pub fn is_metadata(self) -> bool {
+        matches!(
+            self,
+            Placeholder::Size | Placeholder::Mtime | Placeholder::Type
+        )
+    }
+
+    /// Return true if the placeholder is filled in from a git repository.
+    pub fn is_git(self) -> bool {
+        matches!(
+            self,
+            Placeholder::GitBranch | Placeholder::GitRemote | Placeholder::GitLastCommit
+        )
+    }
+}
+
+impl fmt::Display for Placeholder {
+    /// Write the placeholder as it's written in templates, such as
+    /// `{stem}`. `{name}` is written as such rather than as `*`, and the
+    /// relative path as `**`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Placeholder::Relative => {
+                return write!(f, "{0}{0}", DEFAULT_PLACEHOLDER);
+            }
+            Placeholder::Name => "name",
+            Placeholder::Stem => "stem",
+            Placeholder::Ext => "ext",
+            Placeholder::Parent => "parent",
+            Placeholder::Path => "path",
+            Placeholder::Size => "size",
+            Placeholder::Mtime => "mtime",
+            Placeholder::Type => "type",
+            Placeholder::GitBranch => "git_branch",
+            Placeholder::GitRemote => "git_remote",
+            Placeholder::GitLastCommit => "git_last_commit",
+        };
+        write!(f, "{{{}}}", name)
+    }
+}
+
+/// Substitution is a placeholder filled in when rendering a template, along
+/// with the value it was replaced by.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Substitution {
+    pub placeholder: Placeholder,
+    pub value: String,
+}
+
+/// Token is a part of a parsed template.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Token {
+    Literal(String),
+    Placeholder(Placeholder),
+}
+
+/// Parse a template into literals and placeholders. Adjacent literals are
+/// merged.
+pub fn parse(template: &str) -> Vec<Token> {
+    parse_with(template, DEFAULT_PLACEHOLDER)
+}
+
+/// Parse a template like `parse`, with placeholder replacing `*` as the
+/// placeholder of the name, and doubled, of the relative path.
+///
+/// # Examples
+///
+/// ```
+/// use def::template::{self, Placeholder, Token};
+///
+/// assert_eq!(
+///     template::parse_with("%% matches *.rs", '%'),
+///     vec![
+///         Token::Placeholder(Placeholder::Relative),
+///         Token::Literal(" matches *.rs".to_string()),
+///     ]
+/// );
+/// ```
+pub fn parse_with(template: &str, placeholder: char) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut literal = String::new();
+    let mut rest = template;
+    while let Some(c) = rest.chars().next() {
+        let (placeholder, len) = match c {
+            '{' if rest.starts_with("{{") => {
+                literal.push('{');
+                rest = &rest[2..];
+                continue;
+            }
+            '}' if rest.starts_with("}}") => {
+                literal.push('}');
+                rest = &rest[2..];
+                continue;
+            }
+            '{' => match rest.find('}') {
+                Some(end) => (Placeholder::from_name(&rest[1..end]), end + 1),
+                None => (None, 1),
+            },
+            c if c == placeholder && rest[c.len_utf8()..].starts_with(placeholder) => {
+                (Some(Placeholder::Relative), 2 * c.len_utf8())
+            }
+            c if c == placeholder => (Some(Placeholder::Name), c.len_utf8()),
+            _ => (None, c.len_utf8()),
+        };
+
+        match placeholder {
+            Some(p) => {
+                if !literal.is_empty() {
+                    tokens.push(Token::Literal(std::mem::take(&mut literal)));
+                }
+                tokens.push(Token::Placeholder(p));
+            }
+            None => literal.push_str(&rest[..len]),
+        }
+        rest = &rest[len..];
+    }
+
+    if !literal.is_empty() {
+        tokens.push(Token::Literal(literal));
+    }
+    tokens
+}
+
+/// Render a template for the given path, replacing all placeholders.
+///
+/// # Arguments
+///
+/// * `template` - A template containing placeholders.
+/// * `path` - The described path.
+/// * `relative` - The path relative to the directory the pattern is mapped
+///   to, replaces `**`.
+///
+/// # Examples
+///
+/// ```
+/// use def::template::render;
+///
+/// let path = "/home/user/notes.tar.gz";
+/// assert_eq!(render("{stem} ({ext})", path, path), "notes.tar (gz)");
+/// assert_eq!(render("* in {parent}", path, path), "notes.tar.gz in user");
+/// assert_eq!(render("{{name}}", path, path), "{name}");
+/// ```
+pub fn render(template: &str, path: &str, relative: &str) -> String {
+    render_tokens(&parse(template), path, relative, None)
+}
+
+/// Render a template like `render`, filling in metadata placeholders from
+/// info.
+///
+/// # Examples
+///
+/// ```
+/// use def::meta::{FileInfo, FileKind};
+/// use def::template::render_with;
+///
+/// let info = FileInfo {
+///     kind: FileKind::File,
+///     size: 2048,
+///     modified: None,
+/// };
+/// let path = "/dir/notes.md";
+/// assert_eq!(
+///     render_with("* ({type}, {size})", path, path, &info),
+///     "notes.md (file, 2.0 KiB)"
+/// );
+/// assert_eq!(render_with("{mtime}", path, path, &info), "unknown");
+/// ```
+pub fn render_with(template: &str, path: &str, relative: &str, info: &FileInfo) -> String {
+    render_tokens(&parse(template), path, relative, Some(info))
+}
+
+/// Render an already parsed template, see `render`. Metadata placeholders
+/// are filled in from info if it's given, and left as written otherwise.
+pub fn render_tokens(
+    tokens: &[Token],
+    path: &str,
+    relative: &str,
+    info: Option<&FileInfo>,
+) -> String {
+    render_tokens_with_repo(tokens, path, relative, info, None)
+}
+
+/// Render an already parsed template like `render_tokens`, also filling in
+/// git placeholders from repo if it's given, and leaving them as written
+/// otherwise.
+///
+/// # Examples
+///
+/// ```
+/// use def::meta::RepoInfo;
+/// use def::template::{parse, render_tokens_with_repo};
+///
+/// let repo = RepoInfo {
+///     branch: Some("main".to_string()),
+///     remote: None,
+///     last_commit: Some("1a2b3c4 Fix typo".to_string()),
+/// };
+/// let tokens = parse("* on {git_branch} ({git_last_commit}), remote {git_remote}");
+/// assert_eq!(
+///     render_tokens_with_repo(&tokens, "/src/def", "def", None, Some(&repo)),
+///     "def on main (1a2b3c4 Fix typo), remote none"
+/// );
+/// ```
+pub fn render_tokens_with_repo(
+    tokens: &[Token],
+    path: &str,
+    relative: &str,
+    info: Option<&FileInfo>,
+    repo: Option<&RepoInfo>,
+) -> String {
+    let mut rendered = String::new();
+    render_tokens_into(tokens, path, relative, info, repo, &mut rendered);
+    rendered
+}
+
+/// Render an already parsed template like `render_tokens_with_repo`,
+/// appending it to rendered rather than returning a new string. Nothing is
+/// allocated unless rendered has to grow, or metadata placeholders are
+/// filled in.
+pub fn render_tokens_into(
+    tokens: &[Token],
+    path: &str,
+    relative: &str,
+    info: Option<&FileInfo>,
+    repo: Option<&RepoInfo>,
+    rendered: &mut String,
+) {
+    for token in tokens {
+        match token {
+            Token::Literal(l) => rendered.push_str(l),
+            Token::Placeholder(p) => match value(*p, path, relative, info, repo) {
+                Some(value) => rendered.push_str(&value),
+                None => {
+                    // Writing to a string can't fail.
+                    let _ = write!(rendered, "{}", p);
+                }
+            },
+        }
+    }
+}
+
+/// Return the placeholders of an already parsed template that rendering it
+/// like `render_tokens_with_repo` fills in, each once, in the order they
+/// first appear, along with their values.
+///
+/// # Examples
+///
+/// ```
+/// use def::template::{parse, substitutions, Placeholder};
+///
+/// let tokens = parse("* is {size}, {ext} file of *");
+/// let found: Vec<_> = substitutions(&tokens, "/src/main.rs", "main.rs", None, None)
+///     .into_iter()
+///     .map(|s| (s.placeholder, s.value))
+///     .collect();
+/// assert_eq!(
+///     found,
+///     vec![
+///         (Placeholder::Name, "main.rs".to_string()),
+///         (Placeholder::Ext, "rs".to_string()),
+///     ]
+/// );
+/// ```
+pub fn substitutions(
+    tokens: &[Token],
+    path: &str,
+    relative: &str,
+    info: Option<&FileInfo>,
+    repo: Option<&RepoInfo>,
+) -> Vec<Substitution> {
+    let mut found: Vec<Substitution> = Vec::new();
+    for token in tokens {
+        let placeholder = match token {
+            Token::Placeholder(p) if found.iter().all(|s| s.placeholder != *p) => *p,
+            _ => continue,
+        };
+        if let Some(value) = value(placeholder, path, relative, info, repo) {
+            found.push(Substitution {
+                placeholder,
+                value: value.into_owned(),
+            });
+        }
+    }
+    found
+}
+
+/// Return the value placeholder is replaced by, or None if it's left as
+/// written: metadata placeholders without info, and git placeholders without
+/// repo.
+fn value<'a>(
+    placeholder: Placeholder,
+    path: &'a str,
+    relative: &'a str,
+    info: Option<&FileInfo>,
+    repo: Option<&'a RepoInfo>,
+) -> Option<Cow<'a, str>> {
+    let name = name(path);
+    let value = match (placeholder, info, repo) {
+        (Placeholder::Name, _, _) => name,
+        (Placeholder::Stem, _, _) => split_ext(name).0,
+        (Placeholder::Ext, _, _) => split_ext(name).1,
+        (Placeholder::Parent, _, _) => parent(path),
+        (Placeholder::Path, _, _) => path,
+        (Placeholder::Relative, _, _) => relative,
+        (Placeholder::Size, Some(info), _) => return Some(info.display_size().into()),
+        (Placeholder::Mtime, Some(info), _) => {
+            return Some(
+                info.modified
+                    .map_or_else(|| UNKNOWN_TIME.to_string(), meta::human_time)
+                    .into(),
+            )
+        }
+        (Placeholder::Type, Some(info), _) => return Some(info.kind.to_string().into()),
+        (Placeholder::GitBranch, _, Some(repo)) => or_missing(&repo.branch),
+        (Placeholder::GitRemote, _, Some(repo)) => or_missing(&repo.remote),
+        (Placeholder::GitLastCommit, _, Some(repo)) => or_missing(&repo.last_commit),
+        _ => return None,
+    };
+    Some(Cow::Borrowed(value))
+}
+
+/// Return the value of a git placeholder, or `MISSING_GIT_VALUE` if the
+/// repository doesn't have it.
+fn or_missing(value: &Option<String>) -> &str {
+    value.as_deref().unwrap_or(MISSING_GIT_VALUE)
+}
+
+/// Return the last component of path.
+fn name(path: &str) -> &str {
+    path.rsplit(SEPERATOR).next().unwrap_or(path)
+}
+
+/// Return the name of the directory containing path, or an empty string if
+/// path has no parent.
+fn parent(path: &str) -> &str {
+    match path.rsplit_once(SEPERATOR) {
+        Some((parent, _)) => name(parent),
+        None => "",
+    }
+}
+
+/// Split a name into its stem and extension. Names starting with a dot and
+/// containing no other dots (such as ".bashrc") have no extension.
+fn split_ext(name: &str) -> (&str, &str) {
+    match name.rfind('.') {
+        Some(0) | None => (name, ""),
+        Some(i) => (&name[..i], &name[i + 1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_test() {
+        for (template, tokens) in [
+            ("", vec![]),
+            (
+                "plain {{text}}",
+                vec![Token::Literal("plain {text}".to_string())],
+            ),
+            (
+                "* and **",
+                vec![
+                    Token::Placeholder(Placeholder::Name),
+                    Token::Literal(" and ".to_string()),
+                    Token::Placeholder(Placeholder::Relative),
+                ],
+            ),
+            (
+                "{stem}.{ext}",
+                vec![
+                    Token::Placeholder(Placeholder::Stem),
+                    Token::Literal(".".to_string()),
+                    Token::Placeholder(Placeholder::Ext),
+                ],
+            ),
+            (
+                "{owner} {unclosed",
+                vec![Token::Literal("{owner} {unclosed".to_string())],
+            ),
+        ]
+        .iter()
+        {
+            assert_eq!(parse(template), *tokens, "{}", template);
+        }
+    }
+
+    #[test]
+    fn render_test() {
+        for (template, path, relative, rendered) in [
+            ("* is *", "/dir/file", "file", "file is file"),
+            ("{name} is *", "/dir/file", "file", "file is file"),
+            ("** in {parent}", "/a/b/c.rs", "b/c.rs", "b/c.rs in b"),
+            ("{stem}: {ext}", "/dir/notes.tar.gz", "", "notes.tar: gz"),
+            ("{stem}: {ext}", "/dir/.bashrc", "", ".bashrc: "),
+            ("{path}", "/dir/file", "file", "/dir/file"),
+            ("{{name}} is {name}", "/dir/file", "file", "{name} is file"),
+            ("}} {", "/dir/file", "file", "} {"),
+            ("{unknown}", "/dir/file", "file", "{unknown}"),
+            ("{size}, {type}", "/dir/file", "file", "{size}, {type}"),
+            ("on {git_branch}", "/dir/file", "file", "on {git_branch}"),
+        ]
+        .iter()
+        {
+            assert_eq!(render(template, path, relative), *rendered, "{}", template);
+        }
+    }
+}
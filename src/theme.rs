@@ -0,0 +1,274 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use colored::*;
+
+use crate::errors::warn;
+use crate::i18n::{tr, trf};
+
+/// Theme used by paint, set once by init.
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+/// Levels of red, green, and blue used by the 6x6x6 color cube of 256-color
+/// terminals.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// The 16 basic colors, in the order of their 256-color codes.
+const BASIC_COLORS: [Color; 16] = [
+    Color::Black,
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::White,
+    Color::BrightBlack,
+    Color::BrightRed,
+    Color::BrightGreen,
+    Color::BrightYellow,
+    Color::BrightBlue,
+    Color::BrightMagenta,
+    Color::BrightCyan,
+    Color::BrightWhite,
+];
+
+/// Role is the kind of text being printed, each role has its own color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Role {
+    /// "Err" label of error messages.
+    Error,
+    /// "Warn" label of warnings.
+    Warning,
+    /// Described paths.
+    Path,
+    /// Descriptions.
+    Description,
+    /// Patterns shown in trees.
+    Pattern,
+    /// Globs shown in trees.
+    Glob,
+    /// Titles of sections in reports.
+    Heading,
+    /// Code in descriptions rendered as Markdown.
+    Code,
+}
+
+/// Names of roles as used in settings, and their default colors.
+const ROLES: [(&str, Role, Option<Color>); 8] = [
+    ("error", Role::Error, Some(Color::Red)),
+    ("warning", Role::Warning, Some(Color::Yellow)),
+    ("path", Role::Path, Some(Color::Green)),
+    ("description", Role::Description, None),
+    ("pattern", Role::Pattern, Some(Color::Yellow)),
+    ("glob", Role::Glob, Some(Color::Cyan)),
+    ("heading", Role::Heading, Some(Color::Yellow)),
+    ("code", Role::Code, Some(Color::Cyan)),
+];
+
+/// Theme maps each role to a color, or to None if text of the role isn't
+/// colored. A plain theme doesn't color any text, and uses labels that are
+/// easier to follow using screen readers.
+#[derive(Debug, PartialEq)]
+pub struct Theme {
+    colors: HashMap<Role, Option<Color>>,
+    plain: bool,
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme {
+            colors: ROLES
+                .iter()
+                .map(|(_, role, color)| (*role, *color))
+                .collect(),
+            plain: false,
+        }
+    }
+}
+
+impl Theme {
+    /// new creates a theme from a map of role names to colors (see
+    /// parse_color), as given in settings. Roles missing from the map use
+    /// their default colors. Unknown roles and invalid colors are ignored
+    /// with a warning. If plain is true, colors are ignored altogether.
+    pub fn new(colors: &HashMap<String, String>, plain: bool) -> Theme {
+        let mut theme = Theme {
+            plain,
+            ..Theme::default()
+        };
+        for (name, value) in colors.iter() {
+            let role = match ROLES.iter().find(|(n, _, _)| n == name) {
+                Some((_, role, _)) => *role,
+                None => {
+                    warn(trf("unknown-color-setting", &[name]));
+                    continue;
+                }
+            };
+            match parse_color(value) {
+                Some(color) => {
+                    theme.colors.insert(role, color);
+                }
+                None => warn(trf("invalid-color", &[value, name])),
+            }
+        }
+        theme
+    }
+}
+
+/// init sets the theme used by paint. Has no effect if called more than once.
+pub fn init(theme: Theme) {
+    let _ = THEME.set(theme);
+}
+
+/// paint colors text using the color of the given role in the current theme,
+/// or the default theme if init wasn't called yet.
+pub fn paint(role: Role, text: &str) -> ColoredString {
+    let color = match THEME.get() {
+        Some(theme) if theme.plain => None,
+        Some(theme) => theme.colors.get(&role).copied().flatten(),
+        None => ROLES
+            .iter()
+            .find(|(_, r, _)| *r == role)
+            .and_then(|(_, _, color)| *color),
+    };
+    match color {
+        Some(color) => text.color(color),
+        None => text.normal(),
+    }
+}
+
+/// is_plain returns true if the current theme is plain, in which case output
+/// shouldn't be colored or aligned.
+pub fn is_plain() -> bool {
+    THEME.get().map(|t| t.plain).unwrap_or(false)
+}
+
+/// error_label returns the label prefixing error messages.
+pub fn error_label() -> ColoredString {
+    if is_plain() {
+        tr("error-label-plain").normal()
+    } else {
+        paint(Role::Error, tr("error-label"))
+    }
+}
+
+/// warning_label returns the label prefixing warnings.
+pub fn warning_label() -> ColoredString {
+    if is_plain() {
+        tr("warning-label-plain").normal()
+    } else {
+        paint(Role::Warning, tr("warning-label"))
+    }
+}
+
+/// parse_color parses a color given as a name (such as "red" or "bright
+/// blue"), a 256-color code (such as "208"), a hex RGB value (such as
+/// "#ff8700"), or "none" for no color. Returns None if the color is invalid.
+fn parse_color(value: &str) -> Option<Option<Color>> {
+    let value = value.trim();
+    if value.eq_ignore_ascii_case("none") {
+        return Some(None);
+    }
+    if let Some(hex) = value.strip_prefix('#') {
+        return parse_hex(hex).map(Some);
+    }
+    if let Ok(code) = value.parse::<u8>() {
+        return Some(Some(from_256(code)));
+    }
+    Color::from_str(value).ok().map(Some)
+}
+
+/// parse_hex parses a "rrggbb" hex value into a true color.
+fn parse_hex(hex: &str) -> Option<Color> {
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some(Color::TrueColor {
+        r: channel(0)?,
+        g: channel(2)?,
+        b: channel(4)?,
+    })
+}
+
+/// from_256 converts a 256-color code into a color. The first 16 codes are
+/// the basic colors, and the rest (the color cube and the grayscale ramp) are
+/// converted to their true color equivalents.
+fn from_256(code: u8) -> Color {
+    match code {
+        0..=15 => BASIC_COLORS[code as usize],
+        16..=231 => {
+            let i = code - 16;
+            Color::TrueColor {
+                r: CUBE_LEVELS[(i / 36) as usize],
+                g: CUBE_LEVELS[(i / 6 % 6) as usize],
+                b: CUBE_LEVELS[(i % 6) as usize],
+            }
+        }
+        _ => {
+            let level = 8 + (code - 232) * 10;
+            Color::TrueColor {
+                r: level,
+                g: level,
+                b: level,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_color_test() {
+        for (value, color) in [
+            ("red", Some(Some(Color::Red))),
+            ("Bright Blue", Some(Some(Color::BrightBlue))),
+            ("none", Some(None)),
+            ("9", Some(Some(Color::BrightRed))),
+            (
+                "208",
+                Some(Some(Color::TrueColor {
+                    r: 255,
+                    g: 135,
+                    b: 0,
+                })),
+            ),
+            ("232", Some(Some(Color::TrueColor { r: 8, g: 8, b: 8 }))),
+            (
+                "#ff8700",
+                Some(Some(Color::TrueColor {
+                    r: 255,
+                    g: 135,
+                    b: 0,
+                })),
+            ),
+            ("#ff87", None),
+            ("#gg8700", None),
+            ("256", None),
+            ("reddish", None),
+        ]
+        .iter()
+        {
+            assert_eq!(parse_color(value), *color, "{}", value);
+        }
+    }
+
+    #[test]
+    fn new_test() {
+        let mut colors = HashMap::new();
+        colors.insert("error".to_string(), "magenta".to_string());
+        colors.insert("path".to_string(), "none".to_string());
+        colors.insert("unknown".to_string(), "red".to_string());
+        colors.insert("glob".to_string(), "invalid".to_string());
+
+        let theme = Theme::new(&colors, false);
+        assert_eq!(theme.colors[&Role::Error], Some(Color::Magenta));
+        assert_eq!(theme.colors[&Role::Path], None);
+        assert_eq!(theme.colors[&Role::Glob], Some(Color::Cyan));
+        assert_eq!(theme.colors[&Role::Warning], Some(Color::Yellow));
+    }
+}
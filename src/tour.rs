@@ -0,0 +1,177 @@
+use std::env;
+use std::fs;
+use std::io::{self, BufRead};
+use std::path::Path;
+use std::process;
+
+use def::settings::Settings;
+
+use crate::command;
+use crate::errors::Handle;
+use crate::i18n::{tr, trf};
+use crate::theme::{paint, Role};
+
+/// Files and directories created in the sandbox, for the tour to describe.
+/// Names ending with a separator are directories.
+const SANDBOX_FILES: [&str; 5] = [
+    "projects/",
+    "projects/website/",
+    "projects/website/README.md",
+    "projects/cli/",
+    "notes.md",
+];
+
+/// Step is a step of the tour: an explanation followed by a command line,
+/// which is run in the sandbox.
+struct Step {
+    explanation: &'static str,
+    args: &'static [&'static str],
+}
+
+/// Steps of the tour, in order.
+const STEPS: &[Step] = &[
+    Step {
+        explanation: "def maps descriptions to paths. Use add to describe a file or directory.",
+        args: &["add", "projects", "Things I'm working on."],
+    },
+    Step {
+        explanation: "Give def a path to print its description.",
+        args: &["projects"],
+    },
+    Step {
+        explanation: "A pattern describes all children of a directory at once. \"*\" is \
+                      replaced by the child's name.",
+        args: &["pattern", "projects", "* is one of my projects."],
+    },
+    Step {
+        explanation: "Children without descriptions of their own are described by the \
+                      pattern of their directory.",
+        args: &["projects/website"],
+    },
+    Step {
+        explanation: "A glob describes all paths matching it, wherever they are. Globs are \
+                      favoured over patterns.",
+        args: &["glob", "*.md", "* is a markdown document."],
+    },
+    Step {
+        explanation: "Files matching the glob are now described.",
+        args: &["projects/website/README.md"],
+    },
+    Step {
+        explanation: "list prints all descriptions, patterns, and globs as a tree.",
+        args: &["list"],
+    },
+    Step {
+        explanation: "ls lists a directory with descriptions of its entries.",
+        args: &["ls", "projects"],
+    },
+];
+
+/// run walks the user through def's sub-commands. Each step is explained, and
+/// its command is run once the user presses enter, against a sandbox config in
+/// a temporary directory so that the user's config isn't touched. The sandbox
+/// is deleted when the tour ends.
+pub fn run() {
+    let sandbox = env::temp_dir().join(format!("def-tour-{}", process::id()));
+    create_sandbox(&sandbox).extract_or_exit(tr("failed-create-sandbox"));
+    env::set_current_dir(&sandbox).extract_or_exit(tr("failed-create-sandbox"));
+    crate::use_sandbox(Settings {
+        config_file: Some(sandbox.join("config.json").to_string_lossy().to_string()),
+        ..Settings::default()
+    });
+
+    println!("{}\n", trf("tour-intro", &[&sandbox.to_string_lossy()]));
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    for (i, step) in STEPS.iter().enumerate() {
+        println!(
+            "{}",
+            paint(Role::Heading, &trf("tour-step", &[&(i + 1), &STEPS.len()]))
+        );
+        println!("{}", step.explanation);
+        println!("  $ def {}", quote(step.args).join(" "));
+        if !proceed(&mut input) {
+            break;
+        }
+        crate::run(command::parse(&command_line(step.args)));
+        println!();
+    }
+
+    let _ = env::set_current_dir(env::temp_dir());
+    fs::remove_dir_all(&sandbox).extract_or_exit(tr("failed-delete-sandbox"));
+    println!("{}", tr("tour-done"));
+}
+
+/// create_sandbox creates dir containing SANDBOX_FILES.
+fn create_sandbox(dir: &Path) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    for file in SANDBOX_FILES.iter() {
+        match file.strip_suffix('/') {
+            Some(d) => fs::create_dir_all(dir.join(d))?,
+            None => fs::write(dir.join(file), "")?,
+        }
+    }
+    Ok(())
+}
+
+/// proceed prints a prompt and waits for a line of input. Returns false if
+/// the user asked to quit. Returns true without waiting if input is closed,
+/// so that the tour can be run non-interactively.
+fn proceed<R: BufRead>(input: &mut R) -> bool {
+    print!("{} ", tr("tour-prompt"));
+    let _ = io::Write::flush(&mut io::stdout());
+    let mut line = String::new();
+    match input.read_line(&mut line) {
+        Ok(0) | Err(_) => {
+            println!();
+            true
+        }
+        Ok(_) => !line.trim().eq_ignore_ascii_case("q"),
+    }
+}
+
+/// command_line returns the arguments def is invoked with by a step.
+fn command_line(args: &[&str]) -> Vec<String> {
+    let mut line = vec!["def".to_string()];
+    line.extend(args.iter().map(|a| a.to_string()));
+    line
+}
+
+/// quote wraps arguments containing spaces or wildcards in double quotes, as
+/// they'd be typed in a shell.
+fn quote(args: &[&str]) -> Vec<String> {
+    args.iter()
+        .map(|a| {
+            if a.contains([' ', '*', '?', '\'']) {
+                format!("\"{}\"", a)
+            } else {
+                a.to_string()
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::InvokedTo;
+
+    #[test]
+    fn steps_test() {
+        for step in STEPS.iter() {
+            assert_ne!(
+                command::parse(&command_line(step.args)),
+                InvokedTo::Unknown,
+                "{:?}",
+                step.args
+            );
+        }
+    }
+
+    #[test]
+    fn proceed_test() {
+        for (input, proceeds) in [("", true), ("\n", true), ("q\n", false), ("Q\n", false)].iter() {
+            assert_eq!(proceed(&mut input.as_bytes()), *proceeds, "{:?}", input);
+        }
+    }
+}
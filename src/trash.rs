@@ -0,0 +1,55 @@
+//! Trashed (soft-deleted) descriptions and patterns.
+//!
+//! Removing a description moves it to the describer's trash instead of
+//! deleting it immediately, so it can be restored later. Trashed entries are
+//! deleted for good when the trash is emptied or when they expire.
+
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::PatternKind;
+
+/// TrashedEntry is a description or a pattern that was moved to the trash.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct TrashedEntry {
+    /// Path the description or pattern was mapped to.
+    pub path: String,
+    /// The trashed description or pattern.
+    pub description: String,
+    /// Kind of the pattern, or None if the entry is a specific description.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<PatternKind>,
+    /// Time the entry was trashed at, in seconds since the Unix epoch.
+    pub trashed_at: u64,
+}
+
+/// TrashError is returned when a trashed entry can't be restored.
+#[derive(Debug, PartialEq)]
+pub enum TrashError {
+    /// No entry is trashed for the path.
+    NotTrashed(String),
+    /// The path already has a description (or pattern), which would be
+    /// overwritten by restoring.
+    Occupied(String),
+}
+
+impl fmt::Display for TrashError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TrashError::NotTrashed(p) => write!(f, "nothing is trashed for {}", p),
+            TrashError::Occupied(p) => write!(f, "{} already has a description", p),
+        }
+    }
+}
+
+impl std::error::Error for TrashError {}
+
+/// Return current time in seconds since the Unix epoch.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
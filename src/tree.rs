@@ -0,0 +1,116 @@
+use def::virtual_tree::{Node, VirtualTree};
+use def::PatternKind;
+
+use crate::i18n::trf;
+use crate::output;
+use crate::theme::{paint, Role};
+
+/// Name shown for patterns, which are displayed as a child of the directory
+/// they're mapped to.
+const PATTERN_NAME: &str = "*";
+
+/// Name shown for recursive patterns, which are displayed as a child of the
+/// directory they're mapped to.
+const RECURSIVE_PATTERN_NAME: &str = "**";
+
+/// Number of spaces each level of the tree is indented by.
+const INDENT: usize = 2;
+
+/// print prints the tree to stdout. Paths with descriptions are green, globs
+/// are cyan, and patterns are shown as a yellow "*" (or "**" for recursive
+/// patterns) child of their directories. Chains of directories without
+/// descriptions, each with a single child, are collapsed into one line. If
+/// max_depth is given, only that many levels are printed, and nodes whose
+/// children are hidden are followed by the number of hidden entries.
+/// Descriptions are summarized to a line, which fits the terminal's width.
+pub fn print(tree: &VirtualTree, max_depth: Option<usize>) {
+    print_nodes(tree.roots(), 0, max_depth, output::terminal_width());
+}
+
+/// print_nodes prints nodes and their children, indented by depth levels,
+/// with descriptions summarized to fit width.
+fn print_nodes<'a, 'b: 'a>(
+    nodes: impl Iterator<Item = &'a Node<'b>>,
+    depth: usize,
+    max_depth: Option<usize>,
+    width: Option<usize>,
+) {
+    // Summary of text following a label of label_width characters.
+    let summary = |label_width: usize, text: &str| {
+        let width = width.map(|w| w.saturating_sub(label_width + 2));
+        output::summary(text, width)
+    };
+    let indent = " ".repeat(depth * INDENT);
+    for node in nodes {
+        let (name, node) = node.collapse();
+        let hidden = node.child_count() > 0 && max_depth.is_some_and(|m| depth + 1 >= m);
+        let label = if hidden {
+            let count: usize = node.children().map(|c| c.stats().entries).sum();
+            format!("{} {}", name, count_label(count))
+        } else {
+            name
+        };
+
+        let description = node.description();
+        let glob = node.pattern(PatternKind::Glob);
+        if description.is_none() && glob.is_none() {
+            println!("{}{}", indent, label);
+        }
+        if let Some(d) = description {
+            println!(
+                "{}{}: {}",
+                indent,
+                paint(Role::Path, &label),
+                paint(
+                    Role::Description,
+                    &summary(indent.len() + label.chars().count(), d)
+                )
+            );
+        }
+        if let Some(g) = glob {
+            println!(
+                "{}{}: {}",
+                indent,
+                paint(Role::Glob, &label),
+                paint(
+                    Role::Description,
+                    &summary(indent.len() + label.chars().count(), g)
+                )
+            );
+        }
+        for (name, kind) in [
+            (PATTERN_NAME, PatternKind::Children),
+            (RECURSIVE_PATTERN_NAME, PatternKind::Recursive),
+        ]
+        .iter()
+        {
+            if let Some(p) = node.pattern(*kind) {
+                println!(
+                    "{}{}{}: {}",
+                    indent,
+                    " ".repeat(INDENT),
+                    paint(Role::Pattern, name),
+                    paint(
+                        Role::Description,
+                        &summary(indent.len() + INDENT + name.len(), p)
+                    )
+                );
+            }
+        }
+        if !hidden {
+            print_nodes(node.children(), depth + 1, max_depth, width);
+        }
+    }
+}
+
+/// count_label returns the label of a node whose count entries are hidden.
+fn count_label(count: usize) -> String {
+    trf(
+        if count == 1 {
+            "hidden-entry"
+        } else {
+            "hidden-entries"
+        },
+        &[&count],
+    )
+}
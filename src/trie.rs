@@ -0,0 +1,408 @@
+//! A map of paths arranged by their components.
+//!
+//! Descriptions and patterns are kept in `PathTrie`s rather than flat maps,
+//! so that queries following the hierarchy of paths, such as finding the
+//! nearest ancestor of a path with an entry, or every entry under a prefix,
+//! visit only the paths involved instead of every key. The root of an
+//! absolute path (such as `/` or `C:/`) is its first component; relative
+//! paths, such as keys of configs written by hand, start with their first
+//! name. Nodes are keyed by components folded according to the trie's
+//! `MatchOptions`, so that lookups can ignore case, for instance, while keys
+//! are kept as written.
+
+use std::borrow::{Borrow, Cow};
+use std::collections::{BTreeMap, HashMap};
+use std::iter::FromIterator;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::matching::MatchOptions;
+use crate::pattern::{Text, TextMap};
+use crate::{paths, DefPath, SEPERATOR};
+
+/// PathTrie maps paths to values, with a node for each component of a path.
+#[derive(Debug, Clone)]
+pub struct PathTrie<V> {
+    entry: Option<(DefPath, V)>,
+    children: BTreeMap<String, PathTrie<V>>,
+    /// How components are folded, which only matters at the root.
+    options: MatchOptions,
+}
+
+impl<V> Default for PathTrie<V> {
+    fn default() -> PathTrie<V> {
+        PathTrie {
+            entry: None,
+            children: BTreeMap::new(),
+            options: MatchOptions::default(),
+        }
+    }
+}
+
+/// Return components of key, starting with its root if it's absolute.
+fn components(key: &str) -> impl Iterator<Item = &str> {
+    let (root, rest) = match paths::root_len(key) {
+        Some(len) => (Some(&key[..len]), &key[len..]),
+        None => (None, key),
+    };
+    root.into_iter()
+        .chain(rest.split(SEPERATOR).filter(|c| !c.is_empty()))
+}
+
+impl<V> PathTrie<V> {
+    /// Create an empty trie.
+    pub fn new() -> PathTrie<V> {
+        PathTrie::default()
+    }
+
+    /// Return the options keys are matched with.
+    pub fn match_options(&self) -> MatchOptions {
+        self.options
+    }
+
+    /// Match keys with options from now on, see `def::matching`. Keys that
+    /// match each other with options are merged, the last in the order of
+    /// `iter` winning.
+    pub fn set_match_options(&mut self, options: MatchOptions) {
+        if options == self.options {
+            return;
+        }
+        let entries = self.drain();
+        self.options = options;
+        self.extend(entries);
+    }
+
+    /// Return components of key, folded as nodes are keyed.
+    fn folded<'k>(&self, key: &'k str) -> impl Iterator<Item = Cow<'k, str>> {
+        let options = self.options;
+        components(key).map(move |c| options.fold(c))
+    }
+
+    /// Return true if no path is mapped to a value.
+    pub fn is_empty(&self) -> bool {
+        self.entry.is_none() && self.children.is_empty()
+    }
+
+    /// Return the number of paths mapped to a value.
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    /// Return the node of key, if key or a path under it has a value.
+    fn node<K: Borrow<str> + ?Sized>(&self, key: &K) -> Option<&PathTrie<V>> {
+        self.folded(key.borrow())
+            .try_fold(self, |node, c| node.children.get(c.as_ref()))
+    }
+
+    /// Return the value of key, if any.
+    pub fn get<K: Borrow<str> + ?Sized>(&self, key: &K) -> Option<&V> {
+        self.node(key)?.entry.as_ref().map(|(_, v)| v)
+    }
+
+    /// Return the value of key for modification, if any.
+    pub fn get_mut<K: Borrow<str> + ?Sized>(&mut self, key: &K) -> Option<&mut V> {
+        let components: Vec<Cow<str>> = self.folded(key.borrow()).collect();
+        components
+            .iter()
+            .try_fold(self, |node, c| node.children.get_mut(c.as_ref()))?
+            .entry
+            .as_mut()
+            .map(|(_, v)| v)
+    }
+
+    /// Return key as stored, along with its value, if it has one.
+    pub fn get_key_value<K: Borrow<str> + ?Sized>(&self, key: &K) -> Option<(&DefPath, &V)> {
+        self.node(key)?.entry.as_ref().map(|(k, v)| (k, v))
+    }
+
+    /// Return true if key has a value.
+    pub fn contains_key<K: Borrow<str> + ?Sized>(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Map key to value, and return the value it replaced, if any.
+    pub fn insert(&mut self, key: DefPath, value: V) -> Option<V> {
+        let components: Vec<String> = self.folded(key.as_str()).map(Cow::into_owned).collect();
+        let mut node = self;
+        for c in components {
+            node = node.children.entry(c).or_default();
+        }
+        node.entry.replace((key, value)).map(|(_, v)| v)
+    }
+
+    /// Remove key, and return its value, if any. Nodes left without values
+    /// under them are removed as well.
+    pub fn remove<K: Borrow<str> + ?Sized>(&mut self, key: &K) -> Option<V> {
+        let components: Vec<Cow<str>> = self.folded(key.borrow()).collect();
+        self.remove_components(&components)
+    }
+
+    fn remove_components(&mut self, components: &[Cow<str>]) -> Option<V> {
+        let (first, rest) = match components.split_first() {
+            Some(split) => split,
+            None => return self.entry.take().map(|(_, v)| v),
+        };
+        let child = self.children.get_mut(first.as_ref())?;
+        let value = child.remove_components(rest);
+        if child.is_empty() {
+            self.children.remove(first.as_ref());
+        }
+        value
+    }
+
+    /// Return entries of key and of its ancestors, nearest first.
+    pub fn lineage<K: Borrow<str> + ?Sized>(&self, key: &K) -> Vec<(&DefPath, &V)> {
+        let mut entries: Vec<_> = self.ancestry(key.borrow()).collect();
+        entries.reverse();
+        entries
+    }
+
+    /// Return entries of key and of its ancestors like `lineage`, farthest
+    /// first, without collecting them.
+    pub fn ancestry<'a, 'k>(
+        &'a self,
+        key: &'k str,
+    ) -> impl Iterator<Item = (&'a DefPath, &'a V)> + 'k
+    where
+        'a: 'k,
+    {
+        let mut components = self.folded(key);
+        let mut node = Some(self);
+        std::iter::from_fn(move || loop {
+            let current = node?;
+            node = components
+                .next()
+                .and_then(|c| current.children.get(c.as_ref()));
+            if let Some((k, v)) = &current.entry {
+                return Some((k, v));
+            }
+        })
+    }
+
+    /// Return entries of prefix and of paths under it (or all entries if
+    /// prefix is empty), parents before their children, and children sorted
+    /// by name.
+    pub fn descendants(&self, prefix: &str) -> impl Iterator<Item = (&DefPath, &V)> {
+        let mut stack: Vec<&PathTrie<V>> = self.node(prefix).into_iter().collect();
+        std::iter::from_fn(move || loop {
+            let node = stack.pop()?;
+            stack.extend(node.children.values().rev());
+            if let Some((k, v)) = &node.entry {
+                return Some((k, v));
+            }
+        })
+    }
+
+    /// Return all entries, see `descendants`.
+    pub fn iter(&self) -> impl Iterator<Item = (&DefPath, &V)> {
+        self.descendants("")
+    }
+
+    /// Return all values for modification, in the order of `iter`.
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut V> {
+        let mut stack = vec![self];
+        std::iter::from_fn(move || loop {
+            let PathTrie {
+                entry, children, ..
+            } = stack.pop()?;
+            stack.extend(children.values_mut().rev());
+            if let Some((_, v)) = entry {
+                return Some(v);
+            }
+        })
+    }
+
+    /// Remove and return all entries, keeping the trie's options.
+    fn drain(&mut self) -> Vec<(DefPath, V)> {
+        let mut entries = Vec::new();
+        let options = self.options;
+        let mut stack = vec![std::mem::take(self)];
+        self.options = options;
+        while let Some(node) = stack.pop() {
+            entries.extend(node.entry);
+            stack.extend(node.children.into_values());
+        }
+        entries
+    }
+}
+
+impl<V> FromIterator<(DefPath, V)> for PathTrie<V> {
+    fn from_iter<I: IntoIterator<Item = (DefPath, V)>>(iter: I) -> PathTrie<V> {
+        let mut trie = PathTrie::new();
+        trie.extend(iter);
+        trie
+    }
+}
+
+impl<V> Extend<(DefPath, V)> for PathTrie<V> {
+    fn extend<I: IntoIterator<Item = (DefPath, V)>>(&mut self, iter: I) {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+impl<V> IntoIterator for PathTrie<V> {
+    type Item = (DefPath, V);
+    type IntoIter = std::vec::IntoIter<(DefPath, V)>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        self.drain().into_iter()
+    }
+}
+
+/// Tries are serialized as maps sorted by key, so that a describer is
+/// serialized the same way every time.
+impl<V: Serialize> Serialize for PathTrie<V> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.iter()
+            .collect::<BTreeMap<_, _>>()
+            .serialize(serializer)
+    }
+}
+
+impl<'de, V: Deserialize<'de>> Deserialize<'de> for PathTrie<V> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<PathTrie<V>, D::Error> {
+        Ok(HashMap::<DefPath, V>::deserialize(deserializer)?
+            .into_iter()
+            .collect())
+    }
+}
+
+impl<V: Text> TextMap for PathTrie<V> {
+    fn has(&self, key: &str) -> bool {
+        self.contains_key(key)
+    }
+
+    fn sorted_keys(&self) -> Vec<&str> {
+        let mut keys: Vec<&str> = self.iter().map(|(k, _)| k.as_str()).collect();
+        keys.sort_unstable();
+        keys
+    }
+
+    fn text(&self, key: &str) -> Option<&str> {
+        self.get(key).map(|v| v.text())
+    }
+
+    fn texts(&self) -> Box<dyn Iterator<Item = (&str, &str)> + '_> {
+        Box::new(self.iter().map(|(k, v)| (k.as_str(), v.text())))
+    }
+
+    fn texts_under<'a: 'b, 'b>(
+        &'a self,
+        prefix: &'b str,
+    ) -> Box<dyn Iterator<Item = (&'a str, &'a str)> + 'b> {
+        Box::new(
+            self.descendants(prefix)
+                .map(|(k, v)| (k.as_str(), v.text())),
+        )
+    }
+
+    fn take(&mut self, key: &str) -> Option<String> {
+        self.remove(key).map(|v| v.text().to_string())
+    }
+
+    fn put(&mut self, key: String, text: String) {
+        self.insert(DefPath::from(key), V::from_text(text));
+    }
+
+    fn rename(&mut self, old: &str, new: String) {
+        if let Some(value) = self.remove(old) {
+            self.insert(DefPath::from(new), value);
+        }
+    }
+
+    fn map_keys(&mut self, f: &dyn Fn(&str) -> String) {
+        let entries = self.drain();
+        self.extend(
+            entries
+                .into_iter()
+                .map(|(k, v)| (DefPath::from(f(k.as_str())), v)),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trie_test() {
+        let mut trie: PathTrie<&str> = ["/a", "/a/b/c", "/a-b", "C:/x", "rel/y", "/"]
+            .iter()
+            .map(|k| (DefPath::from(*k), *k))
+            .collect();
+        assert_eq!(trie.len(), 6);
+        assert_eq!(trie.get("/a/b/c/"), Some(&"/a/b/c"));
+        assert_eq!(trie.get("/a/b"), None);
+        let (key, _) = trie.get_key_value("/a/b/c/").unwrap();
+        assert_eq!(key.as_str(), "/a/b/c");
+        assert!(!trie.contains_key("rel"));
+
+        for (key, lineage) in [
+            ("/a/b/c/d", vec!["/a/b/c", "/a", "/"]),
+            ("/a/b", vec!["/a", "/"]),
+            ("/other", vec!["/"]),
+            ("C:/x/y", vec!["C:/x"]),
+            ("rel/y", vec!["rel/y"]),
+            ("other", vec![]),
+        ]
+        .iter()
+        {
+            let found: Vec<&str> = trie.lineage(key).iter().map(|(_, v)| **v).collect();
+            assert_eq!(found, *lineage, "{}", key);
+        }
+
+        for (prefix, descendants) in [
+            ("/a", vec!["/a", "/a/b/c"]),
+            ("/a/b", vec!["/a/b/c"]),
+            ("/", vec!["/", "/a", "/a/b/c", "/a-b"]),
+            ("/c", vec![]),
+        ]
+        .iter()
+        {
+            let found: Vec<&str> = trie.descendants(prefix).map(|(_, v)| *v).collect();
+            assert_eq!(found, *descendants, "{}", prefix);
+        }
+
+        assert_eq!(trie.remove("/a/b/c"), Some("/a/b/c"));
+        assert_eq!(trie.remove("/a/b/c"), None);
+        assert!(trie.node("/a/b").is_none());
+        assert_eq!(trie.len(), 5);
+        assert_eq!(
+            serde_json::to_string(&trie).unwrap(),
+            "{\"/\":\"/\",\"/a\":\"/a\",\"/a-b\":\"/a-b\",\"C:/x\":\"C:/x\",\"rel/y\":\"rel/y\"}"
+        );
+    }
+
+    #[test]
+    fn match_options_test() {
+        let mut trie: PathTrie<&str> = ["/Users/Me", "/Users/Me/Cafe\u{301}", "/tmp"]
+            .iter()
+            .map(|k| (DefPath::from(*k), *k))
+            .collect();
+        assert_eq!(trie.get("/users/me"), None);
+        trie.set_match_options(MatchOptions {
+            case_insensitive: true,
+            normalize_unicode: true,
+        });
+        assert_eq!(trie.len(), 3);
+
+        let (key, _) = trie.get_key_value("/USERS/me/café").unwrap();
+        assert_eq!(key.as_str(), "/Users/Me/Cafe\u{301}");
+        let found: Vec<&str> = trie
+            .lineage("/users/ME/Café/x")
+            .iter()
+            .map(|(_, v)| **v)
+            .collect();
+        assert_eq!(found, vec!["/Users/Me/Cafe\u{301}", "/Users/Me"]);
+        let found: Vec<&str> = trie.descendants("/users").map(|(_, v)| *v).collect();
+        assert_eq!(found, vec!["/Users/Me", "/Users/Me/Cafe\u{301}"]);
+
+        trie.insert(DefPath::from("/users/me"), "replaced");
+        assert_eq!(trie.len(), 3);
+        assert_eq!(trie.remove("/USERS/ME"), Some("replaced"));
+        assert_eq!(trie.remove("/Tmp"), Some("/tmp"));
+        assert_eq!(trie.len(), 1);
+    }
+}
@@ -0,0 +1,148 @@
+//! Expansion of variables in stored keys.
+//!
+//! Keys (paths) kept in the config can contain variables such as `$HOME` or
+//! `${PROJECTS}`, which are expanded when the config is loaded. This keeps a
+//! config portable across machines with different usernames and layouts.
+
+/// Character starting a variable.
+const VARIABLE_START: char = '$';
+
+/// Expand all variables in the given key and return the result.
+///
+/// Both `$NAME` and `${NAME}` forms are supported. A name consists of ASCII
+/// letters, digits, and underscores. Variables that can't be resolved using
+/// `lookup` are left untouched, so the key simply won't match any path.
+///
+/// # Arguments
+///
+/// * `key` - A key possibly containing variables.
+/// * `lookup` - Returns the value of a variable given its name, or None if the
+///   variable isn't defined.
+///
+/// # Examples
+///
+/// ```
+/// let lookup = |name: &str| match name {
+///     "PROJECTS" => Some("/home/user/projects".to_string()),
+///     _ => None,
+/// };
+///
+/// assert_eq!(def::vars::expand("${PROJECTS}/def", lookup), "/home/user/projects/def");
+/// assert_eq!(def::vars::expand("$UNKNOWN/def", lookup), "$UNKNOWN/def");
+/// ```
+pub fn expand<F: Fn(&str) -> Option<String>>(key: &str, lookup: F) -> String {
+    let mut expanded = String::with_capacity(key.len());
+    let mut rest = key;
+    while let Some(i) = rest.find(VARIABLE_START) {
+        expanded.push_str(&rest[..i]);
+        rest = &rest[i..];
+
+        let (name, len) = match variable_at(rest) {
+            Some(v) => v,
+            None => {
+                expanded.push(VARIABLE_START);
+                rest = &rest[VARIABLE_START.len_utf8()..];
+                continue;
+            }
+        };
+
+        match lookup(name) {
+            Some(value) => expanded.push_str(&value),
+            None => expanded.push_str(&rest[..len]),
+        }
+        rest = &rest[len..];
+    }
+
+    expanded.push_str(rest);
+    expanded
+}
+
+/// Return true if the given key contains at least one variable.
+pub fn contains_variable(key: &str) -> bool {
+    key.match_indices(VARIABLE_START)
+        .any(|(i, _)| variable_at(&key[i..]).is_some())
+}
+
+/// Parse a variable at the start of s (which starts with `$`). Return the
+/// variable's name and the length of the whole variable (including `$` and
+/// braces), or None if s doesn't start with a valid variable.
+fn variable_at(s: &str) -> Option<(&str, usize)> {
+    let after = &s[VARIABLE_START.len_utf8()..];
+    if let Some(braced) = after.strip_prefix('{') {
+        let end = braced.find('}')?;
+        let name = &braced[..end];
+        if is_name(name) {
+            Some((name, end + 3))
+        } else {
+            None
+        }
+    } else {
+        let end = after
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(after.len());
+        let name = &after[..end];
+        if is_name(name) {
+            Some((name, end + 1))
+        } else {
+            None
+        }
+    }
+}
+
+/// Return true if name is a valid (non-empty) variable name.
+fn is_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "HOME" => Some("/home/user".to_string()),
+            "PROJECTS" => Some("/home/user/projects".to_string()),
+            "EMPTY" => Some("".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn expand_test() {
+        for (key, expanded) in [
+            ("/no/variables", "/no/variables"),
+            ("$HOME", "/home/user"),
+            ("$HOME/dir", "/home/user/dir"),
+            ("${HOME}/dir", "/home/user/dir"),
+            ("${PROJECTS}/def", "/home/user/projects/def"),
+            ("$PROJECTS/def/$HOME", "/home/user/projects/def//home/user"),
+            ("/prefix${EMPTY}/dir", "/prefix/dir"),
+            ("$UNKNOWN/dir", "$UNKNOWN/dir"),
+            ("${UNKNOWN}/dir", "${UNKNOWN}/dir"),
+            ("/costs/$/dir", "/costs/$/dir"),
+            ("/costs/$", "/costs/$"),
+            ("${unterminated/dir", "${unterminated/dir"),
+            ("${}/dir", "${}/dir"),
+            ("$HOME.bak", "/home/user.bak"),
+        ]
+        .iter()
+        {
+            assert_eq!(expand(key, lookup), *expanded);
+        }
+    }
+
+    #[test]
+    fn contains_variable_test() {
+        for (key, contains) in [
+            ("/no/variables", false),
+            ("/costs/$", false),
+            ("${}/dir", false),
+            ("$HOME/dir", true),
+            ("/dir/${PROJECTS}", true),
+        ]
+        .iter()
+        {
+            assert_eq!(contains_variable(key), *contains);
+        }
+    }
+}
@@ -0,0 +1,342 @@
+//! Described paths arranged as a tree.
+//!
+//! A `VirtualTree` arranges entries of a describer (see `Describer::tree`) by
+//! the components of their paths, so that features showing hierarchy (such as
+//! `def list`) don't each re-derive it from flat keys. Paths may have
+//! different roots, such as `/` and `C:/`, or none at all, such as globs
+//! without a separator; each is a root of the tree. Counts of a node's
+//! entries are computed when first needed, and kept.
+
+use std::cell::OnceCell;
+use std::collections::BTreeMap;
+
+use crate::{paths, Entry, PatternKind, SEPERATOR};
+
+/// VirtualTree is a tree of described paths.
+#[derive(Debug, Default)]
+pub struct VirtualTree<'a> {
+    roots: BTreeMap<String, Node<'a>>,
+}
+
+/// Node is a component of a described path, along with the entries mapped to
+/// its path.
+#[derive(Debug)]
+pub struct Node<'a> {
+    name: String,
+    path: String,
+    children: BTreeMap<String, Node<'a>>,
+    description: Option<&'a str>,
+    pattern: Option<&'a str>,
+    recursive_pattern: Option<&'a str>,
+    glob: Option<&'a str>,
+    stats: OnceCell<Stats>,
+}
+
+/// Stats are counts of entries and paths under a node, including the node.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct Stats {
+    /// Number of descriptions, patterns, and globs.
+    pub entries: usize,
+    /// Number of paths in the tree.
+    pub paths: usize,
+    /// Number of paths in the tree that are described, by a description or
+    /// by a pattern of an ancestor.
+    pub described: usize,
+}
+
+impl Stats {
+    /// Return the fraction of paths that are described, between 0 and 1.
+    pub fn coverage(&self) -> f64 {
+        if self.paths == 0 {
+            0.0
+        } else {
+            self.described as f64 / self.paths as f64
+        }
+    }
+}
+
+impl<'a> VirtualTree<'a> {
+    /// Create a tree containing the given entries, creating a node for each
+    /// component of their paths.
+    pub fn new(entries: &[Entry<'a>]) -> VirtualTree<'a> {
+        let mut tree = VirtualTree::default();
+        for entry in entries.iter() {
+            tree.insert(entry);
+        }
+        tree
+    }
+
+    /// Add an entry to the tree.
+    pub fn insert(&mut self, entry: &Entry<'a>) {
+        let (root, rest) = match paths::root_len(entry.path) {
+            Some(len) => entry.path.split_at(len),
+            None => match entry.path.split_once(SEPERATOR) {
+                Some((root, rest)) => (root, rest),
+                None => (entry.path, ""),
+            },
+        };
+        let mut node = self
+            .roots
+            .entry(root.to_string())
+            .or_insert_with(|| Node::new(root, root.to_string()));
+        node.stats.take();
+        for component in rest.split(SEPERATOR).filter(|c| !c.is_empty()) {
+            let path = if node.path.ends_with(SEPERATOR) {
+                format!("{}{}", node.path, component)
+            } else {
+                format!("{}{}{}", node.path, SEPERATOR, component)
+            };
+            node = node
+                .children
+                .entry(component.to_string())
+                .or_insert_with(|| Node::new(component, path));
+            node.stats.take();
+        }
+
+        let text = Some(entry.description);
+        match entry.pattern {
+            Some(PatternKind::Children) => node.pattern = text,
+            Some(PatternKind::Recursive) => node.recursive_pattern = text,
+            Some(PatternKind::Glob) => node.glob = text,
+            None => node.description = text,
+        }
+    }
+
+    /// Return the roots of the tree, sorted by name.
+    pub fn roots(&self) -> impl Iterator<Item = &Node<'a>> {
+        self.roots.values()
+    }
+
+    /// Return the node of the given path, or None if it isn't in the tree.
+    pub fn find(&self, path: &str) -> Option<&Node<'a>> {
+        self.roots().find_map(|root| root.find(path))
+    }
+
+    /// Return stats of the whole tree.
+    pub fn stats(&self) -> Stats {
+        self.roots().fold(Stats::default(), |total, root| {
+            let stats = root.stats();
+            Stats {
+                entries: total.entries + stats.entries,
+                paths: total.paths + stats.paths,
+                described: total.described + stats.described,
+            }
+        })
+    }
+}
+
+impl<'a> Node<'a> {
+    fn new(name: &str, path: String) -> Node<'a> {
+        Node {
+            name: name.to_string(),
+            path,
+            children: BTreeMap::new(),
+            description: None,
+            pattern: None,
+            recursive_pattern: None,
+            glob: None,
+            stats: OnceCell::new(),
+        }
+    }
+
+    /// Return the last component of the node's path (or its root).
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Return the node's path.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Return children of the node, sorted by name.
+    pub fn children(&self) -> impl Iterator<Item = &Node<'a>> {
+        self.children.values()
+    }
+
+    /// Return the child with the given name, if any.
+    pub fn child(&self, name: &str) -> Option<&Node<'a>> {
+        self.children.get(name)
+    }
+
+    /// Return the number of children of the node.
+    pub fn child_count(&self) -> usize {
+        self.children.len()
+    }
+
+    /// Return the description mapped to the node's path, if any.
+    pub fn description(&self) -> Option<&'a str> {
+        self.description
+    }
+
+    /// Return the pattern of the given kind mapped to the node's path, if
+    /// any.
+    pub fn pattern(&self, kind: PatternKind) -> Option<&'a str> {
+        match kind {
+            PatternKind::Children => self.pattern,
+            PatternKind::Recursive => self.recursive_pattern,
+            PatternKind::Glob => self.glob,
+        }
+    }
+
+    /// Return true if a description, pattern, or glob is mapped to the
+    /// node's path.
+    pub fn has_entries(&self) -> bool {
+        self.description.is_some()
+            || self.pattern.is_some()
+            || self.recursive_pattern.is_some()
+            || self.glob.is_some()
+    }
+
+    /// Return the node of the given path if it's this node or is under it.
+    pub fn find(&self, path: &str) -> Option<&Node<'a>> {
+        let rest = paths::rebase(path, &self.path, "")?;
+        rest.split(SEPERATOR)
+            .filter(|c| !c.is_empty())
+            .try_fold(self, |node, component| node.child(component))
+    }
+
+    /// Follow the chain of nodes starting at this one while they have no
+    /// entries and a single child. Return the names of the chain joined by
+    /// separators, and its last node.
+    pub fn collapse(&self) -> (String, &Node<'a>) {
+        let mut name = self.name.clone();
+        let mut node = self;
+        while !node.has_entries() && node.children.len() == 1 {
+            let child = node.children.values().next().unwrap();
+            if !name.ends_with(SEPERATOR) {
+                name.push(SEPERATOR);
+            }
+            name.push_str(&child.name);
+            node = child;
+        }
+        (name, node)
+    }
+
+    /// Return stats of the node and the nodes under it, ignoring patterns of
+    /// the node's ancestors.
+    pub fn stats(&self) -> Stats {
+        *self.stats.get_or_init(|| self.compute_stats(false, false))
+    }
+
+    /// Return stats of the node, where by_parent is true if its parent's
+    /// pattern describes it, and by_ancestor is true if an ancestor's
+    /// recursive pattern does.
+    fn compute_stats(&self, by_parent: bool, by_ancestor: bool) -> Stats {
+        let own_entries = [
+            self.description,
+            self.pattern,
+            self.recursive_pattern,
+            self.glob,
+        ]
+        .iter()
+        .filter(|e| e.is_some())
+        .count();
+        let described = by_parent || by_ancestor || self.description.is_some();
+        let mut stats = Stats {
+            entries: own_entries,
+            paths: 1,
+            described: described as usize,
+        };
+        let by_parent = self.pattern.is_some();
+        let by_ancestor = by_ancestor || self.recursive_pattern.is_some();
+        for child in self.children.values() {
+            let child_stats = if by_parent || by_ancestor {
+                child.compute_stats(by_parent, by_ancestor)
+            } else {
+                child.stats()
+            };
+            stats.entries += child_stats.entries;
+            stats.paths += child_stats.paths;
+            stats.described += child_stats.described;
+        }
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tree_test() {
+        let entries = [
+            Entry {
+                path: "/home/user/projects/def",
+                description: "def.",
+                pattern: None,
+            },
+            Entry {
+                path: "/home/user/projects/def/src",
+                description: "Source.",
+                pattern: None,
+            },
+            Entry {
+                path: "/home/user/notes",
+                description: "* is a note.",
+                pattern: Some(PatternKind::Children),
+            },
+            Entry {
+                path: "/home/user/notes/todo.md",
+                description: "To do.",
+                pattern: None,
+            },
+            Entry {
+                path: "C:/Users",
+                description: "** is a user's.",
+                pattern: Some(PatternKind::Recursive),
+            },
+            Entry {
+                path: "C:/Users/user/Desktop",
+                description: "Desktop.",
+                pattern: None,
+            },
+            Entry {
+                path: "*.log",
+                description: "A log file.",
+                pattern: Some(PatternKind::Glob),
+            },
+        ];
+        let tree = VirtualTree::new(&entries);
+
+        let names: Vec<&str> = tree.roots().map(|r| r.name()).collect();
+        assert_eq!(names, vec!["*.log", "/", "C:/"]);
+
+        let (name, node) = tree.find("/").unwrap().collapse();
+        assert_eq!(name, "/home/user");
+        assert_eq!(node.path(), "/home/user");
+        assert_eq!(node.child_count(), 2);
+        let (name, node) = node.child("projects").unwrap().collapse();
+        assert_eq!(name, "projects/def");
+        assert_eq!(node.description(), Some("def."));
+
+        let desktop = tree.find("C:/Users/user/Desktop").unwrap();
+        assert_eq!(desktop.path(), "C:/Users/user/Desktop");
+        assert!(tree.find("/home/other").is_none());
+
+        for (path, entries, paths, described) in [
+            ("/home/user/notes", 2, 2, 1),
+            ("/home/user", 4, 6, 3),
+            ("C:/", 2, 4, 2),
+        ]
+        .iter()
+        {
+            let stats = tree.find(path).unwrap().stats();
+            assert_eq!(
+                (stats.entries, stats.paths, stats.described),
+                (*entries, *paths, *described),
+                "{}",
+                path
+            );
+        }
+        assert_eq!(tree.stats().entries, entries.len());
+
+        let mut tree = tree;
+        tree.insert(&Entry {
+            path: "/home/user/notes/ideas.md",
+            description: "Ideas.",
+            pattern: None,
+        });
+        assert_eq!(tree.find("/home/user").unwrap().stats().paths, 7);
+    }
+}